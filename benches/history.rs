@@ -0,0 +1,60 @@
+//! Cost of adding a price to a full 10k-long strategy history: the old
+//! `Vec` trimmed with `drain` against `RingBuffer`. Run with
+//! `cargo bench --bench history`.
+
+use hyperliquid_trading_bot::strategies::base::RingBuffer;
+use rust_decimal::Decimal;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const HISTORY: usize = 10_000;
+const PUSHES: usize = 100_000;
+
+fn price(i: usize) -> Decimal {
+    Decimal::from(40_000 + (i % 500) as i64)
+}
+
+fn vec_drain() -> Duration {
+    let mut history: Vec<Decimal> = (0..HISTORY).map(price).collect();
+    
+    let start = Instant::now();
+    for i in 0..PUSHES {
+        history.push(black_box(price(i)));
+        if history.len() > HISTORY {
+            history.drain(0..history.len() - HISTORY);
+        }
+    }
+    black_box(&history);
+    start.elapsed()
+}
+
+fn ring_buffer() -> Duration {
+    let mut history = RingBuffer::new(HISTORY);
+    history.extend((0..HISTORY).map(price));
+    
+    let start = Instant::now();
+    for i in 0..PUSHES {
+        history.push(black_box(price(i)));
+    }
+    black_box(&history);
+    start.elapsed()
+}
+
+fn report(name: &str, elapsed: Duration) {
+    println!(
+        "{:<12} {:>10.1} ns/push ({} pushes at {} history)",
+        name,
+        elapsed.as_nanos() as f64 / PUSHES as f64,
+        PUSHES,
+        HISTORY
+    );
+}
+
+fn main() {
+    // Once untimed, to warm caches and the allocator
+    vec_drain();
+    ring_buffer();
+    
+    report("Vec::drain", vec_drain());
+    report("RingBuffer", ring_buffer());
+}
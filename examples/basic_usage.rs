@@ -5,7 +5,7 @@
 
 use hyperliquid_trading_bot::{
     config::Config,
-    strategies::{DCAStrategy, GridStrategy, MomentumStrategy},
+    strategies::{DCAStrategy, GridStrategy, MomentumStrategy, Strategy},
     trading_bot::TradingBot,
     utils::setup_logging,
 };
@@ -109,7 +109,7 @@ async fn example_momentum_strategy() -> Result<(), Box<dyn std::error::Error>> {
     parameters.insert("fast_period".to_string(), serde_json::Value::Number(10.into()));
     parameters.insert("slow_period".to_string(), serde_json::Value::Number(20.into()));
     parameters.insert("rsi_period".to_string(), serde_json::Value::Number(14.into()));
-    parameters.insert("min_confidence".to_string(), serde_json::Value::Number(0.7.into()));
+    parameters.insert("min_confidence".to_string(), serde_json::json!(0.7));
     
     momentum.update_parameters(parameters).await?;
     
@@ -137,8 +137,9 @@ async fn example_run_bot() -> Result<(), Box<dyn std::error::Error>> {
     info!("  - Risk management");
     info!("  - Real-time market data");
     info!("  - Comprehensive logging");
+    info!("Strategies loaded: {}", bot.cached_snapshot().await.strategies.len());
     
-    // Note: In a real scenario, you would call bot.start().await? here
+    // Note: In a real scenario, you would call bot.run(RunMode::Continuous).await? here
     // For this example, we'll just show the bot was created successfully
     
     info!("✅ All examples completed successfully!");
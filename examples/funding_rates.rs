@@ -0,0 +1,27 @@
+//! Funding rate example
+//! 
+//! Prints BTC's current hourly funding rate and its last day of funding from
+//! the public info API. No keys needed. Run with: `cargo run --example funding_rates`
+
+use chrono::{Duration, Utc};
+use hyperliquid_trading_bot::{api::HyperliquidClient, error::Result};
+use rust_decimal::Decimal;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let client = HyperliquidClient::new("https://api.hyperliquid.xyz".to_string(), String::new(), String::new(), false);
+    
+    let current = client.get_funding_rate("BTC").await?;
+    println!(
+        "BTC funding: {}% per hour (premium {}%)",
+        current.rate * Decimal::from(100),
+        current.premium * Decimal::from(100)
+    );
+    
+    let since = (Utc::now() - Duration::days(1)).timestamp_millis() as u64;
+    for rate in client.get_funding_history("BTC", since, None).await? {
+        println!("{}  {}", rate.time.format("%Y-%m-%d %H:%M"), rate.rate);
+    }
+    
+    Ok(())
+}
@@ -6,10 +6,10 @@
 use hyperliquid_trading_bot::{
     error::Result,
     models::{MarketData, StrategySignal, SignalAction},
-    strategies::base::Strategy,
+    strategies::base::{RingBuffer, Strategy},
 };
 use async_trait::async_trait;
-use rust_decimal::Decimal;
+use rust_decimal::{prelude::ToPrimitive, Decimal};
 use std::collections::HashMap;
 use tracing::info;
 
@@ -27,7 +27,7 @@ pub struct MeanReversionStrategy {
     lookback_period: usize,
     deviation_threshold: Decimal,
     position_size: Decimal,
-    price_history: Vec<Decimal>,
+    price_history: RingBuffer<Decimal>,
 }
 
 impl MeanReversionStrategy {
@@ -40,7 +40,7 @@ impl MeanReversionStrategy {
             lookback_period: 20,
             deviation_threshold: Decimal::new(2, 0), // 2%
             position_size: Decimal::from(100),
-            price_history: Vec::new(),
+            price_history: RingBuffer::new(20),
         }
     }
     
@@ -49,9 +49,8 @@ impl MeanReversionStrategy {
             return None;
         }
         
-        let recent_prices = &self.price_history[self.price_history.len() - self.lookback_period..];
-        let sum: Decimal = recent_prices.iter().sum();
-        Some(sum / Decimal::from(recent_prices.len()))
+        let sum: Decimal = self.price_history.iter_recent(self.lookback_period).sum();
+        Some(sum / Decimal::from(self.lookback_period))
     }
     
     fn calculate_deviation(&self, price: Decimal, ma: Decimal) -> Decimal {
@@ -92,22 +91,16 @@ impl Strategy for MeanReversionStrategy {
         self.enabled
     }
     
-    async fn analyze(&self, market_data: &MarketData) -> Result<Option<StrategySignal>> {
+    async fn analyze(&mut self, market_data: &MarketData) -> Result<Option<StrategySignal>> {
         if !self.enabled {
             return Ok(None);
         }
         
-        // Update price history
-        let mut strategy = self.clone();
-        strategy.price_history.push(market_data.price);
-        
-        // Keep only recent prices
-        if strategy.price_history.len() > self.lookback_period * 2 {
-            strategy.price_history.drain(0..strategy.price_history.len() - self.lookback_period);
-        }
+        // Update price history; the buffer keeps only the lookback period
+        self.price_history.push(market_data.price);
         
         // Calculate moving average
-        let ma = match strategy.calculate_moving_average() {
+        let ma = match self.calculate_moving_average() {
             Some(ma) => ma,
             None => return Ok(None), // Not enough data
         };
@@ -115,15 +108,15 @@ impl Strategy for MeanReversionStrategy {
         let price = market_data.price;
         
         // Check for buy signal (price below MA with significant deviation)
-        if strategy.should_buy(price, ma) {
-            let confidence = strategy.calculate_confidence(price, ma);
+        if self.should_buy(price, ma) {
+            let confidence = self.calculate_confidence(price, ma);
             
             info!(
                 "Mean Reversion BUY signal: {} at {} (MA: {}, deviation: {:.2}%)",
                 self.symbol,
                 price,
                 ma,
-                strategy.calculate_deviation(price, ma)
+                self.calculate_deviation(price, ma)
             );
             
             return Ok(Some(StrategySignal {
@@ -136,23 +129,24 @@ impl Strategy for MeanReversionStrategy {
                 metadata: HashMap::from([
                     ("moving_average".to_string(), serde_json::Value::String(ma.to_string())),
                     ("deviation_percent".to_string(), serde_json::Value::String(
-                        strategy.calculate_deviation(price, ma).to_string()
+                        self.calculate_deviation(price, ma).to_string()
                     )),
                     ("lookback_period".to_string(), serde_json::Value::Number(self.lookback_period.into())),
                 ]),
+                reduce_only: false,
             }));
         }
         
         // Check for sell signal (price above MA with significant deviation)
-        if strategy.should_sell(price, ma) {
-            let confidence = strategy.calculate_confidence(price, ma);
+        if self.should_sell(price, ma) {
+            let confidence = self.calculate_confidence(price, ma);
             
             info!(
                 "Mean Reversion SELL signal: {} at {} (MA: {}, deviation: {:.2}%)",
                 self.symbol,
                 price,
                 ma,
-                strategy.calculate_deviation(price, ma)
+                self.calculate_deviation(price, ma)
             );
             
             return Ok(Some(StrategySignal {
@@ -165,10 +159,11 @@ impl Strategy for MeanReversionStrategy {
                 metadata: HashMap::from([
                     ("moving_average".to_string(), serde_json::Value::String(ma.to_string())),
                     ("deviation_percent".to_string(), serde_json::Value::String(
-                        strategy.calculate_deviation(price, ma).to_string()
+                        self.calculate_deviation(price, ma).to_string()
                     )),
                     ("lookback_period".to_string(), serde_json::Value::String(self.lookback_period.to_string())),
                 ]),
+                reduce_only: false,
             }));
         }
         
@@ -176,11 +171,12 @@ impl Strategy for MeanReversionStrategy {
     }
     
     async fn update_parameters(&mut self, parameters: HashMap<String, serde_json::Value>) -> Result<()> {
-        for (key, value) in parameters {
+        for (key, value) in &parameters {
             match key.as_str() {
                 "lookback_period" => {
                     if let Some(period) = value.as_u64() {
                         self.lookback_period = period as usize;
+                        self.price_history.set_capacity(self.lookback_period);
                     }
                 }
                 "deviation_threshold" => {
@@ -213,7 +209,7 @@ impl Strategy for MeanReversionStrategy {
                 "lookback_period" => {
                     if let Some(period) = value.as_u64() {
                         if period == 0 || period > 100 {
-                            return Err(crate::error::Error::Strategy(
+                            return Err(hyperliquid_trading_bot::error::Error::Strategy(
                                 "Lookback period must be between 1 and 100".to_string()
                             ));
                         }
@@ -222,7 +218,7 @@ impl Strategy for MeanReversionStrategy {
                 "deviation_threshold" => {
                     if let Some(threshold) = value.as_str().and_then(|s| s.parse::<Decimal>().ok()) {
                         if threshold <= Decimal::ZERO || threshold > Decimal::from(50) {
-                            return Err(crate::error::Error::Strategy(
+                            return Err(hyperliquid_trading_bot::error::Error::Strategy(
                                 "Deviation threshold must be between 0 and 50".to_string()
                             ));
                         }
@@ -231,7 +227,7 @@ impl Strategy for MeanReversionStrategy {
                 "position_size" => {
                     if let Some(size) = value.as_str().and_then(|s| s.parse::<Decimal>().ok()) {
                         if size <= Decimal::ZERO {
-                            return Err(crate::error::Error::Strategy(
+                            return Err(hyperliquid_trading_bot::error::Error::Strategy(
                                 "Position size must be positive".to_string()
                             ));
                         }
@@ -262,7 +258,7 @@ impl Clone for MeanReversionStrategy {
 }
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
     // Setup logging
     hyperliquid_trading_bot::utils::setup_logging(false)?;
     
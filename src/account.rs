@@ -0,0 +1,171 @@
+use crate::{
+    api::HyperliquidClient,
+    error::Result,
+    models::{Order, PositionSide, Trade},
+    utils::{format_currency, format_percentage},
+};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+
+/// Fills shown by `account`, most recent first
+pub const RECENT_FILLS: usize = 10;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PositionSummary {
+    pub symbol: String,
+    pub side: PositionSide,
+    pub size: Decimal,
+    pub entry_price: Decimal,
+    pub mark_price: Option<Decimal>,
+    pub unrealized_pnl: Decimal,
+    pub liquidation_price: Option<Decimal>,
+    /// Distance from the mark price to liquidation, as a percentage of the
+    /// mark price
+    pub liquidation_distance: Option<Decimal>,
+}
+
+/// Read-only view of an account as the exchange reports it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountSnapshot {
+    pub address: String,
+    pub fetched_at: DateTime<Utc>,
+    pub account_value: Decimal,
+    pub withdrawable: Decimal,
+    pub margin_used: Decimal,
+    pub positions: Vec<PositionSummary>,
+    pub open_orders: Vec<Order>,
+    pub recent_fills: Vec<Trade>,
+}
+
+pub fn liquidation_distance(mark_price: Decimal, liquidation_price: Decimal) -> Option<Decimal> {
+    if mark_price <= Decimal::ZERO {
+        return None;
+    }
+    Some(((mark_price - liquidation_price).abs() / mark_price * Decimal::from(100)).round_dp(2))
+}
+
+/// Fetches balances, positions, open orders and recent fills for the client's
+/// account address. Only info requests, so no private key is needed.
+pub async fn fetch(client: &HyperliquidClient) -> Result<AccountSnapshot> {
+    let address = client.account_address()?;
+    
+    let (state, mids, open_orders, fills) = tokio::try_join!(
+        client.get_user_state(&address),
+        client.get_all_mids(),
+        client.get_user_open_orders(&address),
+        client.get_user_fills(&address),
+    )?;
+    
+    let positions = state
+        .asset_positions
+        .iter()
+        .map(|asset| &asset.position)
+        .filter(|position| !position.szi.is_zero())
+        .map(|position| {
+            let mark_price = mids.get(&position.coin).copied();
+            PositionSummary {
+                symbol: position.coin.clone(),
+                side: if position.szi > Decimal::ZERO { PositionSide::Long } else { PositionSide::Short },
+                size: position.szi.abs(),
+                entry_price: position.entry_px,
+                mark_price,
+                unrealized_pnl: position.unrealized_pnl,
+                liquidation_price: position.liquidation_px,
+                liquidation_distance: mark_price.zip(position.liquidation_px).and_then(|(mark, liq)| liquidation_distance(mark, liq)),
+            }
+        })
+        .collect();
+    
+    let (account_value, margin_used) = match &state.margin_summary {
+        Some(summary) => (summary.account_value, summary.total_margin_used),
+        None => (state.withdrawable, Decimal::ZERO),
+    };
+    
+    Ok(AccountSnapshot {
+        address,
+        fetched_at: Utc::now(),
+        account_value,
+        withdrawable: state.withdrawable,
+        margin_used,
+        positions,
+        open_orders: open_orders.iter().map(|order| order.to_order()).collect(),
+        recent_fills: fills.iter().take(RECENT_FILLS).map(|fill| fill.to_trade()).collect(),
+    })
+}
+
+fn optional(value: Option<Decimal>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_else(|| "n/a".to_string())
+}
+
+impl AccountSnapshot {
+    pub fn write_table<W: Write>(&self, mut writer: W) -> Result<()> {
+        writeln!(writer, "Account:        {}", self.address)?;
+        writeln!(writer, "As of:          {}", self.fetched_at.format("%Y-%m-%d %H:%M:%S UTC"))?;
+        writeln!(writer, "Account value:  {}", format_currency(self.account_value))?;
+        writeln!(writer, "Withdrawable:   {}", format_currency(self.withdrawable))?;
+        writeln!(writer, "Margin used:    {}", format_currency(self.margin_used))?;
+        
+        writeln!(writer)?;
+        writeln!(writer, "Positions:")?;
+        if self.positions.is_empty() {
+            writeln!(writer, "  none")?;
+        } else {
+            writeln!(
+                writer,
+                "  {:<10} {:<6} {:>12} {:>12} {:>12} {:>12} {:>12} {:>10}",
+                "SYMBOL", "SIDE", "SIZE", "ENTRY", "MARK", "UPNL", "LIQ", "LIQ DIST"
+            )?;
+        }
+        for position in &self.positions {
+            writeln!(
+                writer,
+                "  {:<10} {:<6} {:>12} {:>12} {:>12} {:>12} {:>12} {:>10}",
+                position.symbol,
+                format!("{:?}", position.side),
+                position.size,
+                position.entry_price,
+                optional(position.mark_price),
+                format_currency(position.unrealized_pnl),
+                optional(position.liquidation_price),
+                position.liquidation_distance.map(format_percentage).unwrap_or_else(|| "n/a".to_string())
+            )?;
+        }
+        
+        writeln!(writer)?;
+        writeln!(writer, "Open orders:")?;
+        if self.open_orders.is_empty() {
+            writeln!(writer, "  none")?;
+        }
+        for order in &self.open_orders {
+            let price = order.price.map(|p| p.to_string()).unwrap_or_else(|| "market".to_string());
+            writeln!(
+                writer,
+                "  {:<10} {:?} {} @ {} ({:?}, {})",
+                order.symbol, order.side, order.quantity, price, order.order_type, order.id
+            )?;
+        }
+        
+        writeln!(writer)?;
+        writeln!(writer, "Recent fills:")?;
+        if self.recent_fills.is_empty() {
+            writeln!(writer, "  none")?;
+        }
+        for fill in &self.recent_fills {
+            writeln!(
+                writer,
+                "  {} {:<10} {:?} {} @ {} (fee {})",
+                fill.timestamp.format("%Y-%m-%d %H:%M:%S"),
+                fill.symbol,
+                fill.side,
+                fill.quantity,
+                fill.price,
+                fill.fee
+            )?;
+        }
+        
+        writer.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,97 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::Mutex;
+use tokio::time::{Duration, Instant};
+
+/// How a strategy's analyses have gone, for the status command
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AnalysisStats {
+    /// Analyses run, including those that timed out
+    pub runs: u64,
+    pub last_ms: u64,
+    pub max_ms: u64,
+    pub total_ms: u64,
+    pub timeouts: u64,
+    pub consecutive_timeouts: u32,
+    /// Set after too many timeouts in a row; lasts until the bot restarts
+    pub disabled: bool,
+}
+
+impl AnalysisStats {
+    pub fn mean_ms(&self) -> u64 {
+        self.total_ms.checked_div(self.runs).unwrap_or(0)
+    }
+}
+
+/// What one timed analysis came to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnalysisOutcome {
+    Completed,
+    TimedOut,
+    /// Timed out once too often in a row; the strategy is now disabled
+    Disabled,
+}
+
+/// Runs one analysis within `budget`, returning its output if it finished
+/// in time and how long it took. An analysis that blocks the thread can't
+/// be cut short, so one that overruns counts as timed out even if it
+/// finished.
+pub async fn timed<T>(budget: Duration, analysis: impl Future<Output = T>) -> (Option<T>, Duration) {
+    let started = Instant::now();
+    let output = tokio::time::timeout(budget, analysis).await.ok();
+    let elapsed = started.elapsed();
+    (output.filter(|_| elapsed <= budget), elapsed)
+}
+
+/// Keeps [`AnalysisStats`] per strategy and disables strategies that keep
+/// timing out
+pub struct AnalysisTracker {
+    /// 0 never disables
+    max_consecutive_timeouts: u32,
+    stats: Mutex<BTreeMap<String, AnalysisStats>>,
+}
+
+impl AnalysisTracker {
+    pub fn new(max_consecutive_timeouts: u32) -> Self {
+        Self {
+            max_consecutive_timeouts,
+            stats: Mutex::new(BTreeMap::new()),
+        }
+    }
+    
+    /// Records one analysis of strategy `name`
+    pub fn record(&self, name: &str, elapsed: Duration, timed_out: bool) -> AnalysisOutcome {
+        let mut stats = self.stats.lock().unwrap();
+        let stats = stats.entry(name.to_string()).or_default();
+        let ms = elapsed.as_millis() as u64;
+        stats.runs += 1;
+        stats.last_ms = ms;
+        stats.max_ms = stats.max_ms.max(ms);
+        stats.total_ms += ms;
+        
+        if !timed_out {
+            stats.consecutive_timeouts = 0;
+            return AnalysisOutcome::Completed;
+        }
+        
+        stats.timeouts += 1;
+        stats.consecutive_timeouts += 1;
+        if self.max_consecutive_timeouts > 0
+            && stats.consecutive_timeouts >= self.max_consecutive_timeouts
+            && !stats.disabled
+        {
+            stats.disabled = true;
+            return AnalysisOutcome::Disabled;
+        }
+        AnalysisOutcome::TimedOut
+    }
+    
+    pub fn is_disabled(&self, name: &str) -> bool {
+        self.stats.lock().unwrap().get(name).is_some_and(|stats| stats.disabled)
+    }
+    
+    pub fn stats(&self, name: &str) -> AnalysisStats {
+        self.stats.lock().unwrap().get(name).cloned().unwrap_or_default()
+    }
+}
@@ -0,0 +1,143 @@
+use crate::error::{Error, Result};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use super::types::{AssetContext, Meta};
+
+/// How long a market snapshot serves lookups before it's fetched again
+pub const DEFAULT_MARKET_DATA_TTL: Duration = Duration::from_secs(1);
+
+/// How long a snapshot keeps serving a coin whose mid is still streaming in.
+/// Its volume, funding and day range are no more than this old.
+pub const DEFAULT_LIVE_SNAPSHOT_TTL: Duration = Duration::from_secs(60);
+
+/// Asset contexts from one `metaAndAssetCtxs` response, by coin
+struct Snapshot {
+    contexts: HashMap<String, AssetContext>,
+    fetched_at: Instant,
+}
+
+/// A symbol's high and low over the last day
+#[derive(Debug, Clone, Copy)]
+struct DayRange {
+    high: Decimal,
+    low: Decimal,
+    fetched_at: Instant,
+}
+
+/// The last market snapshot, shared by every symbol lookup until it's `ttl`
+/// old. Lookups that find it stale wait on one fetch between them, so a
+/// cycle's strategies cost one request however many there are. Mids pushed
+/// by the websocket after the snapshot was fetched replace its own, and
+/// while a coin's mid has been pushed within the TTL, its lookups keep
+/// being served from the snapshot, for up to `live_ttl`, with no request.
+pub struct MarketDataCache {
+    ttl: Duration,
+    live_ttl: Duration,
+    snapshot: tokio::sync::Mutex<Option<Snapshot>>,
+    /// Latest websocket mid per coin and when it came in
+    mids: Mutex<HashMap<String, (Decimal, Instant)>>,
+    /// Each symbol's day range, fetched once per `ttl` like the snapshot
+    ranges: Mutex<HashMap<String, Arc<tokio::sync::Mutex<Option<DayRange>>>>>,
+}
+
+impl MarketDataCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            live_ttl: DEFAULT_LIVE_SNAPSHOT_TTL,
+            snapshot: tokio::sync::Mutex::new(None),
+            mids: Mutex::new(HashMap::new()),
+            ranges: Mutex::new(HashMap::new()),
+        }
+    }
+    
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+    
+    /// `symbol`'s context from the cached snapshot, with `fetch` run first
+    /// if there's none young enough
+    pub async fn context<F>(&self, symbol: &str, fetch: F) -> Result<AssetContext>
+    where
+        F: Future<Output = Result<(Meta, Vec<AssetContext>)>>,
+    {
+        let live = self.is_live(symbol);
+        let mut snapshot = self.snapshot.lock().await;
+        if snapshot.as_ref().is_none_or(|s| self.is_stale(s.fetched_at, live)) {
+            let (meta, contexts) = fetch.await?;
+            *snapshot = Some(Snapshot {
+                contexts: meta.universe.into_iter().map(|asset| asset.name).zip(contexts).collect(),
+                fetched_at: Instant::now(),
+            });
+        }
+        let snapshot = snapshot.as_ref().expect("snapshot was just fetched");
+        
+        let mut context = snapshot
+            .contexts
+            .get(symbol)
+            .cloned()
+            .ok_or_else(|| Error::Api(format!("Symbol {} not found", symbol)))?;
+        if let Some((mid, _)) = self.mids.lock().unwrap().get(symbol).filter(|(_, at)| *at > snapshot.fetched_at) {
+            context.mid_px = Some(*mid);
+        }
+        Ok(context)
+    }
+    
+    /// `symbol`'s high and low over the last day, with `fetch` run first if
+    /// they're too old, like the snapshot. A failed fetch isn't cached.
+    pub async fn day_range<F>(&self, symbol: &str, fetch: F) -> Result<(Decimal, Decimal)>
+    where
+        F: Future<Output = Result<(Decimal, Decimal)>>,
+    {
+        let live = self.is_live(symbol);
+        let range = self.ranges.lock().unwrap().entry(symbol.to_string()).or_default().clone();
+        let mut range = range.lock().await;
+        if let Some(cached) = range.as_ref().filter(|r| !self.is_stale(r.fetched_at, live)) {
+            return Ok((cached.high, cached.low));
+        }
+        
+        let (high, low) = fetch.await?;
+        *range = Some(DayRange {
+            high,
+            low,
+            fetched_at: Instant::now(),
+        });
+        Ok((high, low))
+    }
+    
+    /// Takes in a mid pushed by the websocket, served in place of the
+    /// snapshot's until a newer snapshot is fetched
+    pub fn update_mid(&self, symbol: &str, mid: Decimal) {
+        self.mids.lock().unwrap().insert(symbol.to_string(), (mid, Instant::now()));
+    }
+    
+    /// Takes in every coin's mid at once, as the `allMids` channel pushes them
+    pub fn update_mids(&self, mids: &HashMap<String, Decimal>) {
+        let now = Instant::now();
+        let mut held = self.mids.lock().unwrap();
+        for (symbol, mid) in mids {
+            held.insert(symbol.clone(), (*mid, now));
+        }
+    }
+    
+    /// Whether `symbol`'s mid was pushed within the TTL
+    fn is_live(&self, symbol: &str) -> bool {
+        self.mids.lock().unwrap().get(symbol).is_some_and(|(_, at)| at.elapsed() < self.ttl)
+    }
+    
+    /// Whether something fetched at `fetched_at` needs fetching again
+    fn is_stale(&self, fetched_at: Instant, live: bool) -> bool {
+        let age = fetched_at.elapsed();
+        age >= self.ttl && !(live && age < self.live_ttl)
+    }
+}
+
+impl Default for MarketDataCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_MARKET_DATA_TTL)
+    }
+}
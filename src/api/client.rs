@@ -1,56 +1,565 @@
 use crate::{
-    error::{Error, Result},
-    models::{AccountInfo, MarketData, Order, OrderSide, OrderType, Position, PositionSide, Trade},
-    utils::log_error_with_context,
+    clock::Clock,
+    config::{HttpClientConfig, Network, RateLimitConfig},
+    error::{Error, Result, ResultExt},
+    models::{
+        AccountInfo, EquityPoint, FeeSchedule, FundingPayment, FundingRate, MarketData, Order, OrderSide, OrderState, OrderStatusInfo,
+        OrderType, Position, PositionSide, Trade, TwapStatus,
+    },
+    utils,
 };
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
+use rand::Rng;
 use reqwest::Client;
 use rust_decimal::Decimal;
 use serde_json::json;
-use sha2::{Digest, Sha256};
-use std::collections::HashMap;
-use tracing::{debug, error, info, warn};
+use futures_util::{stream, Stream, StreamExt, TryStreamExt};
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
 
+use super::cache::MarketDataCache;
+use super::nonce::NonceManager;
+use super::rate_limit::RateLimiter;
+use super::signing;
 use super::types::*;
+use super::wallet;
+use super::websocket::PostChannel;
+
+/// How long a fetched asset listing is trusted before it's fetched again,
+/// so assets listed while the bot runs become tradable
+const DEFAULT_ASSET_TTL: Duration = Duration::from_secs(3600);
+
+/// How long a connection or a whole request may take unless the builder
+/// says otherwise
+const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Most market data requests a batch has in flight at once
+const MARKET_DATA_CONCURRENCY: usize = 8;
+
+/// Most fills a `userFills` or `userFillsByTime` response holds
+const FILLS_PAGE_LIMIT: usize = 2000;
+
+/// How far back each `userFillsByTime` request reaches when walking back
+/// through a user's fills, doubled after a stretch without any
+const HISTORY_WINDOW_MS: u64 = 30 * 86_400_000;
+
+/// 2023-01-01, before the exchange's first fills, where a walk back stops
+const HISTORY_START_MS: u64 = 1_672_531_200_000;
+
+/// How long a fetched portfolio history serves before it's fetched again;
+/// the exchange works it out from the account's whole history
+const PORTFOLIO_TTL: Duration = Duration::from_secs(300);
+
+/// Soonest a scheduled cancel may be set for
+const MIN_CANCEL_DELAY: Duration = Duration::from_secs(5);
+
+/// Shortest and longest TWAP the exchange runs
+const TWAP_MINUTES_RANGE: std::ops::RangeInclusive<u32> = 5..=1440;
+
+/// A TWAP order this client placed
+struct PlacedTwap {
+    coin: String,
+    size: Decimal,
+    /// When its last slice is due, or when it was cancelled
+    ends_at: DateTime<Utc>,
+}
+
+/// How far a walk back through a user's fills has got
+enum HistoryPage {
+    /// The most recent fills, from `userFills`
+    Latest,
+    /// The `span` millis of fills up to `end`
+    Before { end: u64, span: u64 },
+    Done,
+}
+
+/// Where an asset sits in the universe and how finely it trades
+#[derive(Debug, Clone, Copy)]
+struct ListedAsset {
+    id: u32,
+    sz_decimals: u32,
+}
+
+/// Listed assets by upper-cased coin name, from one `meta` response
+struct AssetIds {
+    ids: HashMap<String, ListedAsset>,
+    fetched_at: Instant,
+}
+
+impl AssetIds {
+    fn new(meta: &Meta) -> Self {
+        Self {
+            ids: meta
+                .universe
+                .iter()
+                .enumerate()
+                .map(|(index, asset)| {
+                    let listed = ListedAsset {
+                        id: index as u32,
+                        sz_decimals: asset.sz_decimals,
+                    };
+                    (asset.name.to_uppercase(), listed)
+                })
+                .collect(),
+            fetched_at: Instant::now(),
+        }
+    }
+}
 
 pub struct HyperliquidClient {
     client: Client,
     base_url: String,
+    ws_url: String,
     api_key: String,
     private_key: String,
+    account_address: Option<String>,
+    /// Vault or subaccount the signer trades for
+    vault_address: Option<String>,
     testnet: bool,
+    nonces: NonceManager,
+    info_limiter: RateLimiter,
+    exchange_limiter: RateLimiter,
+    /// Retries after a failed request; none unless configured
+    retry_attempts: u32,
+    retry_delay: Duration,
+    /// Listed assets, to turn symbols into asset indices
+    assets: tokio::sync::Mutex<Option<AssetIds>>,
+    asset_ttl: Duration,
+    /// Coin of each order id seen, to address cancels
+    order_coins: Mutex<HashMap<u64, String>>,
+    /// TWAP orders placed, by id
+    twaps: Mutex<HashMap<u64, PlacedTwap>>,
+    /// Serves market data lookups from one snapshot at a time
+    market_data: Arc<MarketDataCache>,
+    /// The last portfolio history fetched and when
+    portfolio: tokio::sync::Mutex<Option<(Instant, Vec<EquityPoint>)>>,
+    /// Where orders and cancels are posted instead of over HTTP while it's
+    /// open
+    posts: Mutex<Option<PostChannel>>,
 }
 
-impl HyperliquidClient {
-    pub fn new(base_url: String, api_key: String, private_key: String, testnet: bool) -> Self {
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(30))
+/// Sets up a [`HyperliquidClient`]: where it connects, whose keys it signs
+/// with and how its HTTP requests go out
+pub struct HyperliquidClientBuilder {
+    base_url: String,
+    ws_url: String,
+    api_key: String,
+    private_key: String,
+    account_address: Option<String>,
+    testnet: bool,
+    connect_timeout: Duration,
+    request_timeout: Duration,
+    proxy: Option<String>,
+    user_agent: Option<String>,
+    ca_certificate: Option<PathBuf>,
+}
+
+impl Default for HyperliquidClientBuilder {
+    fn default() -> Self {
+        Self {
+            base_url: String::new(),
+            ws_url: String::new(),
+            api_key: String::new(),
+            private_key: String::new(),
+            account_address: None,
+            testnet: false,
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
+            proxy: None,
+            user_agent: None,
+            ca_certificate: None,
+        }
+    }
+}
+
+impl HyperliquidClientBuilder {
+    /// The REST endpoint, e.g. `https://api.hyperliquid-testnet.xyz`
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+    
+    /// The websocket endpoint; the network's when left out
+    pub fn ws_url(mut self, ws_url: impl Into<String>) -> Self {
+        self.ws_url = ws_url.into();
+        self
+    }
+    
+    pub fn credentials(mut self, api_key: impl Into<String>, private_key: impl Into<String>) -> Self {
+        self.api_key = api_key.into();
+        self.private_key = private_key.into();
+        self
+    }
+    
+    /// Queries info requests for this address instead of the private
+    /// key's. Enough on its own for a client that only reads.
+    pub fn account_address(mut self, account_address: Option<String>) -> Self {
+        self.account_address = account_address.filter(|address| !address.is_empty());
+        self
+    }
+    
+    /// Signs for testnet rather than mainnet
+    pub fn testnet(mut self, testnet: bool) -> Self {
+        self.testnet = testnet;
+        self
+    }
+    
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = timeout;
+        self
+    }
+    
+    /// Longest a request may take, connecting included
+    pub fn request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+    
+    /// Sends every request through the proxy at `url`
+    pub fn proxy(mut self, url: impl Into<String>) -> Self {
+        self.proxy = Some(url.into());
+        self
+    }
+    
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+    
+    /// Trusts the root certificate in the PEM file at `path` besides the
+    /// system's
+    pub fn ca_certificate(mut self, path: impl Into<PathBuf>) -> Self {
+        self.ca_certificate = Some(path.into());
+        self
+    }
+    
+    /// Takes the timeouts, proxy, user agent and certificate from `config`
+    pub fn http(mut self, config: &HttpClientConfig) -> Self {
+        self.connect_timeout = Duration::from_millis(config.connect_timeout_ms);
+        self.request_timeout = Duration::from_millis(config.request_timeout_ms);
+        self.proxy = config.proxy.clone().filter(|proxy| !proxy.is_empty());
+        self.user_agent = config.user_agent.clone().filter(|agent| !agent.is_empty());
+        self.ca_certificate = config.ca_certificate.as_ref().filter(|path| !path.is_empty()).map(PathBuf::from);
+        self
+    }
+    
+    /// The client, once it has a base URL and a private key, or at least
+    /// an account address to read as. Fails with [`Error::Config`] without
+    /// them, or if the proxy or certificate won't load.
+    pub fn build(self) -> Result<HyperliquidClient> {
+        if self.base_url.trim().is_empty() {
+            return Err(Error::Config("The client needs a base_url".to_string()));
+        }
+        if self.private_key.is_empty() && self.account_address.is_none() {
+            return Err(Error::Config(
+                "The client needs a private key, or an account address to read as".to_string(),
+            ));
+        }
+        self.assemble()
+    }
+    
+    /// The client as configured, an empty base URL meaning the network's
+    fn assemble(self) -> Result<HyperliquidClient> {
+        let mut http = Client::builder()
+            .connect_timeout(self.connect_timeout)
+            .timeout(self.request_timeout);
+        if let Some(url) = &self.proxy {
+            let proxy = reqwest::Proxy::all(url).map_err(|e| Error::Config(format!("Invalid proxy {}: {}", url, e)))?;
+            http = http.proxy(proxy);
+        }
+        if let Some(user_agent) = &self.user_agent {
+            http = http.user_agent(user_agent);
+        }
+        if let Some(path) = &self.ca_certificate {
+            let certificate = std::fs::read(path)
+                .map_err(|e| e.to_string())
+                .and_then(|pem| reqwest::Certificate::from_pem(&pem).map_err(|e| e.to_string()))
+                .map_err(|e| Error::Config(format!("Can't load CA certificate {}: {}", path.display(), e)))?;
+            http = http.add_root_certificate(certificate);
+        }
+        let client = http
             .build()
-            .expect("Failed to create HTTP client");
+            .map_err(|e| Error::Config(format!("Can't set up the HTTP client: {}", e)))?;
+        let (info_limiter, exchange_limiter) = RateLimiter::pair(&RateLimitConfig::default());
+        let network = Network::from_testnet(self.testnet);
         
-        Self {
+        Ok(HyperliquidClient {
             client,
-            base_url,
-            api_key,
-            private_key,
-            testnet,
+            base_url: match self.base_url.trim_end_matches('/') {
+                "" => network.api_url().to_string(),
+                base_url => base_url.to_string(),
+            },
+            ws_url: match self.ws_url.as_str() {
+                "" => network.ws_url().to_string(),
+                ws_url => ws_url.to_string(),
+            },
+            api_key: self.api_key,
+            private_key: self.private_key,
+            account_address: self.account_address,
+            vault_address: None,
+            testnet: self.testnet,
+            nonces: NonceManager::new(),
+            info_limiter,
+            exchange_limiter,
+            retry_attempts: 0,
+            retry_delay: Duration::ZERO,
+            assets: tokio::sync::Mutex::new(None),
+            asset_ttl: DEFAULT_ASSET_TTL,
+            order_coins: Mutex::new(HashMap::new()),
+            twaps: Mutex::new(HashMap::new()),
+            market_data: Arc::new(MarketDataCache::default()),
+            portfolio: tokio::sync::Mutex::new(None),
+            posts: Mutex::new(None),
+        })
+    }
+}
+
+impl HyperliquidClient {
+    /// A client for the exchange at `base_url`, or at the mainnet or
+    /// testnet endpoint if it's empty, with the default HTTP settings
+    pub fn new(base_url: String, api_key: String, private_key: String, testnet: bool) -> Self {
+        Self::builder()
+            .base_url(base_url)
+            .credentials(api_key, private_key)
+            .testnet(testnet)
+            .assemble()
+            .expect("Failed to create HTTP client")
+    }
+    
+    pub fn builder() -> HyperliquidClientBuilder {
+        HyperliquidClientBuilder::default()
+    }
+    
+    /// The REST endpoint requests go to
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+    
+    /// The websocket endpoint for this client's network
+    pub fn ws_url(&self) -> &str {
+        &self.ws_url
+    }
+    
+    /// Takes nonces from `clock` rather than the wall clock, so a test
+    /// signs the same payloads every run
+    pub fn with_nonce_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.nonces = NonceManager::with_clock(clock);
+        self
+    }
+    
+    /// Retries failed requests up to `attempts` times, waiting `delay`
+    /// before the first retry and twice as long before each one after
+    pub fn with_retries(mut self, attempts: u32, delay: Duration) -> Self {
+        self.retry_attempts = attempts;
+        self.retry_delay = delay;
+        self
+    }
+    
+    /// Paces requests by `config` rather than the default budgets
+    pub fn with_rate_limits(mut self, config: &RateLimitConfig) -> Self {
+        (self.info_limiter, self.exchange_limiter) = RateLimiter::pair(config);
+        self
+    }
+    
+    /// Fetches the asset listing again once it's `ttl` old
+    pub fn with_asset_ttl(mut self, ttl: Duration) -> Self {
+        self.asset_ttl = ttl;
+        self
+    }
+    
+    /// Serves market data from a snapshot until it's `ttl` old
+    pub fn with_market_data_ttl(mut self, ttl: Duration) -> Self {
+        self.market_data = Arc::new(MarketDataCache::new(ttl));
+        self
+    }
+    
+    /// The snapshot market data is served from, e.g. for the websocket to
+    /// push fresher mids into
+    pub fn market_data_cache(&self) -> Arc<MarketDataCache> {
+        self.market_data.clone()
+    }
+    
+    /// Query info requests for this address instead of the private key's
+    pub fn with_account_address(mut self, account_address: Option<String>) -> Self {
+        self.account_address = account_address.filter(|address| !address.is_empty());
+        self
+    }
+    
+    /// Trades for `vault_address`, a vault the signer leads or one of its
+    /// subaccounts. Actions name it and account data is read from it.
+    pub fn with_vault_address(mut self, vault_address: Option<String>) -> Self {
+        self.vault_address = vault_address.filter(|address| !address.is_empty()).map(|a| a.to_lowercase());
+        self
+    }
+    
+    /// Posts orders and cancels through `posts` while it has a connection
+    /// open, and over HTTP otherwise; `None` always uses HTTP
+    pub fn post_orders_over(&self, posts: Option<PostChannel>) {
+        *self.posts.lock().unwrap() = posts;
+    }
+    
+    /// Address used for info requests: the vault traded for, else the
+    /// configured account address, else the one derived from the private key
+    pub fn account_address(&self) -> Result<String> {
+        if let Some(vault) = &self.vault_address {
+            return Ok(vault.clone());
+        }
+        if let Some(address) = &self.account_address {
+            return Ok(address.to_lowercase());
         }
+        if self.private_key.is_empty() {
+            return Err(Error::Config(
+                "Set hyperliquid.account_address or a private key to query account data".to_string(),
+            ));
+        }
+        wallet::address_from_private_key(&self.private_key)
     }
     
-    fn create_signature(&self, data: &str) -> Result<String> {
-        use hmac::{Hmac, Mac};
-        use sha2::Sha256;
+    async fn make_request<T>(&self, request: InfoRequest) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        // Errors name the request, e.g. `info:clearinghouseState`
+        let name = format!("info:{}", request.name());
+        let body = serde_json::to_string(&request)?;
         
-        let mut mac = Hmac::<Sha256>::new_from_slice(self.private_key.as_bytes())
-            .map_err(|e| Error::Api(format!("Invalid private key: {}", e)))?;
+        self.send_request("info", Some(body)).await.context_api(&name)
+    }
+    
+    /// Sends an exchange action, naming it (e.g. `exchange:order`) on errors
+    async fn make_action_request<T>(&self, action: Action) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let name = format!("exchange:{}", action.name());
+        self.send_action(action).await.context_api(&name)
+    }
+    
+    /// The request body for `action`: the action with `nonce` and the
+    /// signature over both, for mainnet or testnet as configured. Transfers
+    /// take `nonce` as their time and are signed by the wallet itself.
+    pub fn signed_action(&self, action: &Action, nonce: u64) -> Result<serde_json::Value> {
+        let (kind, transfer) = match action {
+            Action::UsdSend(transfer) => ("UsdSend", transfer),
+            Action::Withdraw3(transfer) => ("Withdraw", transfer),
+            _ => return self.signed_l1_action(action, nonce),
+        };
+        let transfer = UsdTransfer {
+            time: nonce,
+            ..transfer.clone()
+        };
+        let signature = signing::sign_usd_transfer(&self.private_key, kind, &transfer)?;
+        let action = match action {
+            Action::UsdSend(_) => Action::UsdSend(transfer),
+            _ => Action::Withdraw3(transfer),
+        };
+        Ok(json!({
+            "action": action,
+            "nonce": nonce,
+            "signature": signature
+        }))
+    }
+    
+    fn signed_l1_action(&self, action: &Action, nonce: u64) -> Result<serde_json::Value> {
+        let vault = self.vault_address.as_deref();
+        let signature = signing::sign_l1_action(&self.private_key, action, vault, nonce, !self.testnet)?;
+        Ok(json!({
+            "action": action,
+            "nonce": nonce,
+            "signature": signature,
+            "vaultAddress": vault
+        }))
+    }
+    
+    /// Sends an exchange action with the next nonce. The nonce is assigned
+    /// and the payload signed before any other action gets a nonce, so
+    /// concurrent orders still carry strictly increasing nonces while their
+    /// round trips overlap.
+    ///
+    /// An action turned down for its nonce never took effect, so it's signed
+    /// again with a fresh one and resent, up to the configured retries.
+    async fn send_action<T>(&self, action: Action) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let mut attempt = 0;
+        loop {
+            let body = self.nonces.sign(|nonce| self.signed_action(&action, nonce))?;
+            
+            let reply: ExchangeResponse = match self.open_posts(&action) {
+                Some(posts) => {
+                    self.exchange_limiter.acquire().await?;
+                    serde_json::from_value(posts.post_action(body).await?)?
+                }
+                None => self.send_request("exchange", Some(body.to_string())).await?,
+            };
+            if reply.status == "ok" {
+                return Ok(serde_json::from_value(reply.response)?);
+            }
+            // Rejected actions carry the reason as a bare string
+            let message = match reply.response {
+                serde_json::Value::String(message) => message,
+                other => other.to_string(),
+            };
+            match Error::from_exchange_message(&message).unwrap_or(Error::Api(message)) {
+                Error::InvalidNonce(message) if attempt < self.retry_attempts => {
+                    attempt += 1;
+                    warn!("{} rejected its nonce (attempt {}): {}, signing it again", action.name(), attempt, message);
+                }
+                error => return Err(error),
+            }
+        }
+    }
+    
+    /// Where `action` is posted instead of over HTTP: orders and cancels go
+    /// over the websocket when it's configured and open
+    fn open_posts(&self, action: &Action) -> Option<PostChannel> {
+        if !matches!(action, Action::Order { .. } | Action::Cancel { .. } | Action::CancelByCloid { .. }) {
+            return None;
+        }
+        self.posts.lock().unwrap().clone().filter(PostChannel::is_open)
+    }
+    
+    /// Sends a request, retrying failures that may pass on another try up to
+    /// the configured number of times. Info requests are retried on any
+    /// retryable error; exchange actions only when the exchange can't have
+    /// acted on them, since a timed-out order may still have been placed.
+    async fn send_request<T>(&self, endpoint: &str, body: Option<String>) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let limiter = if endpoint == "exchange" { &self.exchange_limiter } else { &self.info_limiter };
+        let mut attempt = 0;
         
-        mac.update(data.as_bytes());
-        let result = mac.finalize();
-        Ok(hex::encode(result.into_bytes()))
+        loop {
+            limiter.acquire().await?;
+            match self.send_once(endpoint, body.clone()).await {
+                Err(error) if attempt < self.retry_attempts && retry_safe(endpoint, &error) => {
+                    attempt += 1;
+                    let delay = error.retry_after().unwrap_or_else(|| self.backoff(attempt));
+                    warn!("Request to {} failed (attempt {}): {}, retrying in {:?}", endpoint, attempt, error, delay);
+                    tokio::time::sleep(delay).await;
+                }
+                result => return result,
+            }
+        }
     }
     
-    async fn make_request<T>(&self, endpoint: &str, data: Option<serde_json::Value>) -> Result<T>
+    /// The wait before retry `attempt`: the retry delay doubled for each
+    /// attempt before it, give or take half, so clients that failed together
+    /// don't all retry together
+    fn backoff(&self, attempt: u32) -> Duration {
+        let delay = self.retry_delay * (1 << (attempt - 1).min(5));
+        delay.mul_f64(rand::thread_rng().gen_range(0.5..1.5))
+    }
+    
+    async fn send_once<T>(&self, endpoint: &str, body: Option<String>) -> Result<T>
     where
         T: serde::de::DeserializeOwned,
     {
@@ -58,45 +567,997 @@ impl HyperliquidClient {
         
         let mut request_builder = self.client.post(&url);
         
-        if let Some(data) = data {
-            let data_str = serde_json::to_string(&data)?;
-            let signature = self.create_signature(&data_str)?;
-            
+        if let Some(body) = body {
             request_builder = request_builder
                 .header("Content-Type", "application/json")
                 .header("Authorization", format!("Bearer {}", self.api_key))
-                .header("X-Signature", signature)
-                .body(data_str);
+                .body(body);
         }
         
         let response = request_builder.send().await?;
         
         if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_default();
-            return Err(Error::Api(format!("HTTP {}: {}", response.status(), error_text)));
+            return Err(match Error::from_response(response).await {
+                // Some failures come back as a bare message with the status
+                Error::Http { status, body } => Error::from_exchange_message(&body).unwrap_or(Error::Http { status, body }),
+                error => error,
+            });
+        }
+        
+        Ok(response.json().await?)
+    }
+    
+    /// Index of `symbol`, in any case, in the listed universe. The listing
+    /// is fetched on first use and again once it's older than the asset
+    /// TTL; if that refetch fails the old listing is used meanwhile.
+    pub async fn resolve_asset_id(&self, symbol: &str) -> Result<u32> {
+        Ok(self.listed_asset(symbol).await?.id)
+    }
+    
+    async fn listed_asset(&self, symbol: &str) -> Result<ListedAsset> {
+        let mut assets = self.assets.lock().await;
+        if assets.as_ref().is_none_or(|assets| assets.fetched_at.elapsed() >= self.asset_ttl) {
+            match self.get_meta().await {
+                Ok(meta) => *assets = Some(AssetIds::new(&meta)),
+                Err(e) if assets.is_some() => warn!("Keeping the old asset listing: {}", e),
+                Err(e) => return Err(e),
+            }
         }
         
-        let response_data: HyperliquidResponse<T> = response.json().await?;
+        assets
+            .as_ref()
+            .and_then(|assets| assets.ids.get(&symbol.to_uppercase()).copied())
+            .ok_or_else(|| Error::Trading(format!("{} is not listed on the exchange", symbol)))
+    }
+    
+    /// Rounds `size` down to the decimals `symbol` trades in
+    pub async fn round_size(&self, symbol: &str, size: Decimal) -> Result<Decimal> {
+        utils::round_order_size(size, self.listed_asset(symbol).await?.sz_decimals)
+    }
+    
+    /// Rounds `price` to the nearest valid `symbol` price: 5 significant
+    /// figures, and no more than 6 decimals less the size decimals
+    pub async fn round_price(&self, symbol: &str, price: Decimal) -> Result<Decimal> {
+        utils::round_order_price(price, self.listed_asset(symbol).await?.sz_decimals)
+    }
+    
+    /// `order` with its size, price and trigger price rounded as the
+    /// exchange takes them. A size that rounds to nothing fails with
+    /// [`Error::InvalidInput`].
+    pub async fn normalize_order(&self, order: &Order) -> Result<Order> {
+        let sz_decimals = self.listed_asset(&order.symbol).await?.sz_decimals;
+        let quantity = utils::round_order_size(order.quantity, sz_decimals)?;
+        if quantity.is_zero() {
+            return Err(Error::InvalidInput(format!(
+                "{} {} is below the minimum size of {}",
+                order.quantity,
+                order.symbol,
+                Decimal::new(1, sz_decimals)
+            )));
+        }
+        let round_price = |price| utils::round_order_price(price, sz_decimals);
+        Ok(Order {
+            quantity,
+            price: order.price.map(round_price).transpose()?,
+            trigger_price: order.trigger_price.map(round_price).transpose()?,
+            ..order.clone()
+        })
+    }
+    
+    /// Fetches the asset listing now rather than when it expires
+    pub async fn refresh_assets(&self) -> Result<()> {
+        let meta = self.get_meta().await?;
+        *self.assets.lock().await = Some(AssetIds::new(&meta));
+        Ok(())
+    }
+    
+    fn remember_order(&self, oid: u64, coin: &str) {
+        self.order_coins.lock().unwrap().insert(oid, coin.to_string());
+    }
+    
+    /// Coin of order `oid`, looking it up among the open orders if this
+    /// client hasn't seen it; `None` if it isn't open
+    async fn order_coin(&self, oid: u64) -> Result<Option<String>> {
+        let known = self.order_coins.lock().unwrap().get(&oid).cloned();
+        if known.is_some() {
+            return Ok(known);
+        }
+        let orders = self.get_user_open_orders(&self.account_address()?).await?;
+        for order in &orders {
+            self.remember_order(order.oid, &order.coin);
+        }
+        Ok(orders.into_iter().find(|order| order.oid == oid).map(|order| order.coin))
+    }
+    
+    /// The `order` action that places `order`, as `place_order` signs and
+    /// sends it, with its size and prices rounded to what the exchange takes
+    pub async fn order_action(&self, order: &Order) -> Result<Action> {
+        if let Some(cloid) = order.cloid.as_deref().filter(|cloid| !is_cloid(cloid)) {
+            return Err(Error::InvalidInput(format!("{} is not 0x and 32 hex digits", cloid)));
+        }
+        let order = &self.normalize_order(order).await?;
+        let (t, p) = order_type_wire(order)?;
+        
+        // Convert our Order model to Hyperliquid's format
+        let order_request = OrderRequest {
+            a: self.resolve_asset_id(&order.symbol).await?,
+            b: matches!(order.side, OrderSide::Buy),
+            p,
+            s: order.quantity,
+            r: order.reduce_only,
+            t,
+            c: order.cloid.clone(),
+        };
+        
+        Ok(Action::Order {
+            orders: vec![order_request],
+            grouping: "na".to_string(),
+        })
+    }
+    
+    /// Changes the price and/or size of resting order `order_id` in place,
+    /// keeping the rest of it (side, type, time in force, reduce-only and
+    /// cloid), and returns its id. Unlike cancelling and
+    /// placing it again, an unchanged price keeps its place in the queue.
+    /// Fails with [`Error::OrderNotFound`] if the order isn't resting.
+    pub async fn modify_order(
+        &self,
+        order_id: &str,
+        new_price: Option<Decimal>,
+        new_size: Option<Decimal>,
+    ) -> Result<String> {
+        debug!("Modifying order {}: price {:?}, size {:?}", order_id, new_price, new_size);
+        
+        let oid: u64 = order_id
+            .parse()
+            .map_err(|_| Error::InvalidInput(format!("{} is not an exchange order id", order_id)))?;
+        let request = InfoRequest::FrontendOpenOrders { user: self.account_address()? };
+        let orders: Vec<OpenOrder> = self.make_request(request).await?;
+        let resting = orders.iter().find(|order| order.oid == oid).ok_or(Error::OrderNotFound)?;
+        let new_price = match new_price {
+            Some(price) => Some(self.round_price(&resting.coin, price).await?),
+            None => None,
+        };
+        let new_size = match new_size {
+            Some(size) => Some(self.round_size(&resting.coin, size).await?),
+            None => None,
+        };
+        
+        let action = Action::Modify(ModifyRequest {
+            oid,
+            order: OrderRequest {
+                a: self.resolve_asset_id(&resting.coin).await?,
+                b: resting.side == "B",
+                p: new_price.unwrap_or(resting.limit_px),
+                s: new_size.unwrap_or(resting.sz),
+                r: resting.reduce_only,
+                t: resting.order_type_wire(),
+                c: resting.cloid.clone(),
+            },
+        });
+        let _: serde_json::Value = self.make_action_request(action).await?;
+        info!("Order {} modified", oid);
+        Ok(oid.to_string())
+    }
+    
+    /// The order placed with client order id `cloid`, whether it's still
+    /// open or not; `None` if the exchange doesn't know it
+    pub async fn get_order_by_cloid(&self, cloid: &str) -> Result<Option<Order>> {
+        debug!("Looking up order {}", cloid);
+        Ok(self.order_status(OrderRef::Cloid(cloid.to_string())).await?.map(|found| found.to_order()))
+    }
+    
+    /// `orderStatus` for an exchange order id or a cloid
+    async fn order_status(&self, oid: OrderRef) -> Result<Option<OrderStatusUpdate>> {
+        let request = InfoRequest::OrderStatus {
+            user: self.account_address()?,
+            oid,
+        };
+        let reply: OrderStatusReply = self.make_request(request).await?;
+        if let Some(found) = &reply.order {
+            self.remember_order(found.order.oid, &found.order.coin);
+        }
+        Ok(reply.order)
+    }
+    
+    /// Cancels the `symbol` order placed with client order id `cloid`, which
+    /// works before its exchange id is known. Returns whether it was
+    /// cancelled.
+    pub async fn cancel_order_by_cloid(&self, symbol: &str, cloid: &str) -> Result<bool> {
+        debug!("Cancelling order {}", cloid);
+        
+        if !is_cloid(cloid) {
+            return Err(Error::InvalidInput(format!("{} is not 0x and 32 hex digits", cloid)));
+        }
+        let action = Action::CancelByCloid {
+            cancels: vec![CancelByCloidRequest {
+                asset: self.resolve_asset_id(symbol).await?,
+                cloid: cloid.to_string(),
+            }],
+        };
+        let result: ExchangeResult<CancelStatusWire> = self.make_action_request(action).await?;
+        match result.data.statuses.into_iter().next() {
+            Some(CancelStatusWire::Success(_)) => Ok(true),
+            Some(CancelStatusWire::Error { error }) => Err(rejection("Failed to cancel order", &error)),
+            None => Err(Error::Api("No cancel status in response".to_string())),
+        }
+    }
+    
+    /// Places a TWAP order that the exchange slices into market orders
+    /// spread over `minutes`, at randomized times if `randomize`, and returns
+    /// its id. Durations outside 5 minutes to a day fail with
+    /// [`Error::InvalidInput`].
+    pub async fn place_twap_order(
+        &self,
+        symbol: &str,
+        side: OrderSide,
+        size: Decimal,
+        minutes: u32,
+        randomize: bool,
+    ) -> Result<String> {
+        self.submit_twap(symbol, side, size, minutes, randomize, false).await
+    }
+    
+    async fn submit_twap(
+        &self,
+        symbol: &str,
+        side: OrderSide,
+        size: Decimal,
+        minutes: u32,
+        randomize: bool,
+        reduce_only: bool,
+    ) -> Result<String> {
+        debug!("Placing TWAP order: {:?} {} {} over {} minutes", side, size, symbol, minutes);
+        
+        if !TWAP_MINUTES_RANGE.contains(&minutes) {
+            return Err(Error::InvalidInput(format!(
+                "TWAP orders run for {} to {} minutes, not {}",
+                TWAP_MINUTES_RANGE.start(),
+                TWAP_MINUTES_RANGE.end(),
+                minutes
+            )));
+        }
+        if size <= Decimal::ZERO {
+            return Err(Error::InvalidInput(format!("TWAP size must be positive, got {}", size)));
+        }
+        let size = self.round_size(symbol, size).await?;
+        if size.is_zero() {
+            return Err(Error::InvalidInput(format!("TWAP of {} rounds to nothing", symbol)));
+        }
+        
+        let action = Action::TwapOrder {
+            twap: TwapRequest {
+                a: self.resolve_asset_id(symbol).await?,
+                b: matches!(side, OrderSide::Buy),
+                s: size,
+                r: reduce_only,
+                m: minutes,
+                t: randomize,
+            },
+        };
+        let result: TwapResult<TwapStatusWire> = self.make_action_request(action).await?;
+        match result.data.status {
+            TwapStatusWire::Running { twap_id } => {
+                info!("TWAP {} running: {} {} over {} minutes", twap_id, size, symbol, minutes);
+                self.twaps.lock().unwrap().insert(
+                    twap_id,
+                    PlacedTwap {
+                        coin: symbol.to_string(),
+                        size,
+                        ends_at: Utc::now() + chrono::Duration::minutes(minutes as i64),
+                    },
+                );
+                Ok(twap_id.to_string())
+            }
+            TwapStatusWire::Error(message) => Err(rejection("Failed to place TWAP order", &message)),
+        }
+    }
+    
+    /// Stops TWAP order `twap_id` before its remaining slices go out. Fails
+    /// with [`Error::OrderNotFound`] for a TWAP this client didn't place.
+    pub async fn cancel_twap(&self, twap_id: &str) -> Result<()> {
+        debug!("Cancelling TWAP {}", twap_id);
+        
+        let id = parse_twap_id(twap_id)?;
+        let coin = self
+            .twaps
+            .lock()
+            .unwrap()
+            .get(&id)
+            .map(|twap| twap.coin.clone())
+            .ok_or(Error::OrderNotFound)?;
+        
+        let action = Action::TwapCancel {
+            a: self.resolve_asset_id(&coin).await?,
+            t: id,
+        };
+        let result: TwapResult<CancelStatusWire> = self.make_action_request(action).await?;
+        match result.data.status {
+            CancelStatusWire::Success(_) => {
+                info!("TWAP {} cancelled", id);
+                if let Some(twap) = self.twaps.lock().unwrap().get_mut(&id) {
+                    twap.ends_at = twap.ends_at.min(Utc::now());
+                }
+                Ok(())
+            }
+            CancelStatusWire::Error { error } => Err(rejection(&format!("Failed to cancel TWAP {}", id), &error)),
+        }
+    }
+    
+    /// How much of TWAP order `twap_id` has filled, from its slice fills.
+    /// Fails with [`Error::OrderNotFound`] for a TWAP this client didn't
+    /// place.
+    pub async fn get_twap_status(&self, twap_id: &str) -> Result<TwapStatus> {
+        let id = parse_twap_id(twap_id)?;
+        let (coin, size, ends_at) = self
+            .twaps
+            .lock()
+            .unwrap()
+            .get(&id)
+            .map(|twap| (twap.coin.clone(), twap.size, twap.ends_at))
+            .ok_or(Error::OrderNotFound)?;
+        
+        let request = InfoRequest::UserTwapSliceFills {
+            user: self.account_address()?,
+        };
+        let slices: Vec<TwapSliceFill> = self.make_request(request).await?;
         
-        if !response_data.success {
-            return Err(Error::Api(
-                response_data.error.unwrap_or_else(|| "Unknown API error".to_string())
+        let mut status = TwapStatus {
+            twap_id: id.to_string(),
+            symbol: coin,
+            size,
+            filled_size: Decimal::ZERO,
+            filled_notional: Decimal::ZERO,
+            fee: Decimal::ZERO,
+            done: false,
+        };
+        for slice in slices.iter().filter(|slice| slice.twap_id == id) {
+            status.filled_size += slice.fill.sz;
+            status.filled_notional += slice.fill.sz * slice.fill.px;
+            status.fee += slice.fill.fee;
+        }
+        status.done = status.filled_size >= size || Utc::now() >= ends_at;
+        Ok(status)
+    }
+    
+    /// Sets the leverage of `symbol` positions, and whether their margin is
+    /// cross or isolated. Leverage above what the asset allows, or cross
+    /// margin on an isolated-only asset, fails with [`Error::Trading`].
+    pub async fn set_leverage(&self, symbol: &str, leverage: u32, cross: bool) -> Result<()> {
+        debug!("Setting {} leverage to {}x, cross: {}", symbol, leverage, cross);
+        
+        if leverage == 0 {
+            return Err(Error::InvalidInput("leverage must be at least 1".to_string()));
+        }
+        let meta = self.get_meta().await?;
+        let (index, asset) = meta
+            .universe
+            .iter()
+            .enumerate()
+            .find(|(_, asset)| asset.name.eq_ignore_ascii_case(symbol))
+            .ok_or_else(|| Error::Trading(format!("{} is not listed on the exchange", symbol)))?;
+        if leverage > asset.max_leverage {
+            return Err(Error::Trading(format!(
+                "{} allows at most {}x leverage, not {}x",
+                asset.name, asset.max_leverage, leverage
+            )));
+        }
+        if cross && asset.only_isolated {
+            return Err(Error::Trading(format!("{} only trades on isolated margin", asset.name)));
+        }
+        
+        let action = Action::UpdateLeverage {
+            asset: index as u32,
+            is_cross: cross,
+            leverage,
+        };
+        let _: serde_json::Value = self.make_action_request(action).await?;
+        info!("{} leverage set to {}x {}", asset.name, leverage, if cross { "cross" } else { "isolated" });
+        Ok(())
+    }
+    
+    /// Has the exchange cancel every open order at `at`, replacing any
+    /// earlier schedule, or clears the schedule if `at` is `None`. The time
+    /// must be at least 5 seconds away.
+    pub async fn schedule_cancel(&self, at: Option<DateTime<Utc>>) -> Result<()> {
+        debug!("Scheduling cancel of all orders at {:?}", at);
+        
+        if let Some(at) = at {
+            let earliest = Utc::now() + chrono::Duration::from_std(MIN_CANCEL_DELAY).expect("delay fits");
+            if at < earliest {
+                return Err(Error::InvalidInput(format!(
+                    "A scheduled cancel must be at least {}s away, got {}",
+                    MIN_CANCEL_DELAY.as_secs(),
+                    at
+                )));
+            }
+        }
+        
+        let action = Action::ScheduleCancel {
+            time: at.map(|at| at.timestamp_millis() as u64),
+        };
+        let _: serde_json::Value = self.make_action_request(action).await?;
+        Ok(())
+    }
+    
+    /// Sends `amount` USDC from the signer's account to `destination`'s on
+    /// the exchange
+    pub async fn usd_transfer(&self, destination: &str, amount: Decimal) -> Result<()> {
+        debug!("Sending {} USDC to {}", amount, destination);
+        
+        let transfer = self.usd_transfer_to(destination, amount).await?;
+        let _: serde_json::Value = self.make_action_request(Action::UsdSend(transfer)).await?;
+        info!("Sent {} USDC to {}", amount, destination);
+        Ok(())
+    }
+    
+    /// Withdraws `amount` USDC from the signer's account to `destination` on
+    /// Arbitrum. The exchange takes its withdrawal fee out of `amount`.
+    pub async fn withdraw(&self, destination: &str, amount: Decimal) -> Result<()> {
+        debug!("Withdrawing {} USDC to {}", amount, destination);
+        
+        let transfer = self.usd_transfer_to(destination, amount).await?;
+        let _: serde_json::Value = self.make_action_request(Action::Withdraw3(transfer)).await?;
+        info!("Withdrew {} USDC to {}", amount, destination);
+        Ok(())
+    }
+    
+    /// A transfer of `amount` to `destination`, once it's checked: transfers
+    /// move the signer's own funds, so they can't be made for a vault or by
+    /// an API wallet, and can't be more than the account can withdraw
+    async fn usd_transfer_to(&self, destination: &str, amount: Decimal) -> Result<UsdTransfer> {
+        if !wallet::is_address(destination) {
+            return Err(Error::InvalidInput(format!("{} is not an address", destination)));
+        }
+        if amount <= Decimal::ZERO {
+            return Err(Error::InvalidInput(format!("Transfer amount must be positive, got {}", amount)));
+        }
+        if self.vault_address.is_some() {
+            return Err(Error::InvalidInput("Transfers can't be made for a vault or subaccount".to_string()));
+        }
+        let signer = wallet::address_from_private_key(&self.private_key)?;
+        if self.account_address()? != signer {
+            return Err(Error::InvalidInput(
+                "Transfers must be signed by the account's own key, not an API wallet".to_string(),
             ));
         }
+        if destination.eq_ignore_ascii_case(&signer) {
+            return Err(Error::InvalidInput(format!("{} is the account itself", destination)));
+        }
+        
+        let withdrawable = self.get_user_state(&signer).await?.withdrawable;
+        if amount > withdrawable {
+            return Err(Error::Trading(format!(
+                "Can't move {} USDC, only {} is withdrawable",
+                amount, withdrawable
+            )));
+        }
+        Ok(UsdTransfer {
+            signature_chain_id: signing::SIGNATURE_CHAIN_ID.to_string(),
+            hyperliquid_chain: Network::from_testnet(self.testnet).chain_name().to_string(),
+            destination: destination.to_lowercase(),
+            amount: amount.normalize().to_string(),
+            time: 0,
+        })
+    }
+    
+    /// Perpetuals metadata: listed assets with size decimals and max leverage
+    pub async fn get_meta(&self) -> Result<Meta> {
+        debug!("Fetching exchange meta");
+        
+        self.make_request(InfoRequest::Meta).await
+    }
+    
+    /// Listed assets with their live contexts (mark, mid, day volume,
+    /// funding), the contexts in universe order
+    pub async fn get_meta_and_asset_ctxs(&self) -> Result<(Meta, Vec<AssetContext>)> {
+        debug!("Fetching exchange meta and asset contexts");
         
-        response_data.data.ok_or_else(|| Error::Api("No data in response".to_string()))
+        self.make_request(InfoRequest::MetaAndAssetCtxs).await
+    }
+    
+    /// Account state for any wallet address, not just the configured one
+    pub async fn get_user_state(&self, user: &str) -> Result<UserState> {
+        debug!("Fetching account state for {}", user);
+        
+        self.make_request(InfoRequest::ClearinghouseState { user: user.to_string() }).await
+    }
+    
+    /// Mid prices for every listed asset
+    pub async fn get_all_mids(&self) -> Result<HashMap<String, Decimal>> {
+        debug!("Fetching all mids");
+        
+        self.make_request(InfoRequest::AllMids).await
+    }
+    
+    /// The book for `symbol`, each side cut to its best `depth` levels if
+    /// given. The exchange sends at most 20 a side.
+    pub async fn get_l2_book(&self, symbol: &str, depth: Option<usize>) -> Result<L2Book> {
+        debug!("Fetching order book for {}", symbol);
+        
+        let mut book: L2Book = self.make_request(InfoRequest::L2Book { coin: symbol.to_string() }).await?;
+        if let Some(depth) = depth {
+            book.bids.truncate(depth);
+            book.asks.truncate(depth);
+        }
+        Ok(book)
+    }
+    
+    /// `symbol`'s live context from `metaAndAssetCtxs`
+    async fn asset_context(&self, symbol: &str) -> Result<AssetContext> {
+        self.market_data.context(symbol, self.get_meta_and_asset_ctxs()).await
+    }
+    
+    /// `symbol`'s high and low over the last day, from its hourly candles
+    async fn day_range(&self, symbol: &str) -> Result<(Decimal, Decimal)> {
+        let fetch = async {
+            let now = Utc::now().timestamp_millis() as u64;
+            let candles = self.get_candles(symbol, "1h", now - 24 * 3_600_000, now).await?;
+            Ok((
+                candles.iter().map(|candle| candle.h).fold(Decimal::MIN, Decimal::max),
+                candles.iter().map(|candle| candle.l).fold(Decimal::MAX, Decimal::min),
+            ))
+        };
+        self.market_data.day_range(symbol, fetch).await
+    }
+    
+    /// `symbol`'s current hourly funding rate
+    pub async fn get_funding_rate(&self, symbol: &str) -> Result<FundingRate> {
+        let context = self.asset_context(symbol).await?;
+        Ok(FundingRate {
+            coin: symbol.to_string(),
+            rate: context.funding,
+            premium: context.premium.unwrap_or(Decimal::ZERO),
+            time: Utc::now(),
+        })
+    }
+    
+    /// `symbol`'s funding rates from `start` to `end` (epoch millis), oldest
+    /// first
+    pub async fn get_funding_history(&self, symbol: &str, start: u64, end: Option<u64>) -> Result<Vec<FundingRate>> {
+        debug!("Fetching funding history for {} from {} to {:?}", symbol, start, end);
+        
+        let request = InfoRequest::FundingHistory {
+            coin: symbol.to_string(),
+            start_time: start,
+            end_time: end,
+        };
+        let mut history: Vec<FundingHistoryEntry> = self.make_request(request).await?;
+        history.sort_by_key(|entry| entry.time);
+        Ok(history.iter().map(FundingHistoryEntry::to_funding_rate).collect())
+    }
+    
+    /// Funding settled on any wallet address's positions from `start` to
+    /// `end` (epoch millis), oldest first
+    pub async fn get_user_funding(&self, user: &str, start: u64, end: Option<u64>) -> Result<Vec<FundingPayment>> {
+        debug!("Fetching funding payments for {} from {} to {:?}", user, start, end);
+        
+        let request = InfoRequest::UserFunding {
+            user: user.to_string(),
+            start_time: start,
+            end_time: end,
+        };
+        let mut payments: Vec<UserFunding> = self.make_request(request).await?;
+        payments.sort_by_key(|payment| payment.time);
+        Ok(payments.iter().map(UserFunding::to_payment).collect())
+    }
+    
+    /// Funding settled on this account's positions from `start` to `end`,
+    /// oldest first
+    pub async fn get_funding_payments(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<FundingPayment>> {
+        let user = self.account_address()?;
+        self.get_user_funding(&user, start.timestamp_millis() as u64, Some(end.timestamp_millis() as u64))
+            .await
+    }
+    
+    /// The fee rates this account pays, after its volume tier and discounts
+    pub async fn get_user_fees(&self) -> Result<FeeSchedule> {
+        let user = self.account_address()?;
+        debug!("Fetching fee schedule for {}", user);
+        
+        let fees: UserFees = self.make_request(InfoRequest::UserFees { user }).await?;
+        Ok(fees.to_schedule())
+    }
+    
+    /// The account's value and PnL over its whole life, oldest first, from
+    /// the all-time period of the `portfolio` request. The exchange is only
+    /// asked again once the last answer is 5 minutes old.
+    pub async fn get_portfolio_history(&self) -> Result<Vec<EquityPoint>> {
+        let mut cached = self.portfolio.lock().await;
+        if let Some((_, history)) = cached.as_ref().filter(|(fetched_at, _)| fetched_at.elapsed() < PORTFOLIO_TTL) {
+            return Ok(history.clone());
+        }
+        
+        let user = self.account_address()?;
+        debug!("Fetching portfolio history for {}", user);
+        let periods: Vec<(String, PortfolioPeriod)> = self.make_request(InfoRequest::Portfolio { user }).await?;
+        let history = periods
+            .into_iter()
+            .find(|(period, _)| period == "allTime")
+            .map(|(_, period)| period.to_equity_points())
+            .ok_or_else(|| Error::Api("Portfolio response has no allTime period".to_string()))?;
+        *cached = Some((Instant::now(), history.clone()));
+        Ok(history)
+    }
+    
+    /// Resting orders for any wallet address
+    pub async fn get_user_open_orders(&self, user: &str) -> Result<Vec<OpenOrder>> {
+        debug!("Fetching open orders for {}", user);
+        
+        self.make_request(InfoRequest::OpenOrders { user: user.to_string() }).await
+    }
+    
+    /// Fills for any wallet address, most recent first
+    pub async fn get_user_fills(&self, user: &str) -> Result<Vec<UserFill>> {
+        debug!("Fetching fills for {}", user);
+        
+        let mut fills: Vec<UserFill> = self.make_request(InfoRequest::UserFills { user: user.to_string() }).await?;
+        fills.sort_by_key(|fill| Reverse(fill.time));
+        Ok(fills)
+    }
+    
+    /// Fills for any wallet address from `start` to `end` (epoch millis,
+    /// inclusive), most recent first. Responses hold at most 2000 fills, so
+    /// longer ranges are walked a page at a time, each starting at the last
+    /// fill seen. The exchange only keeps a user's 10000 most recent fills
+    /// this way.
+    pub async fn get_user_fills_by_time(&self, user: &str, start: u64, end: Option<u64>) -> Result<Vec<UserFill>> {
+        debug!("Fetching fills for {} from {} to {:?}", user, start, end);
+        
+        let mut fills: Vec<UserFill> = Vec::new();
+        let mut seen = HashSet::new();
+        let mut start = start;
+        loop {
+            let request = InfoRequest::UserFillsByTime {
+                user: user.to_string(),
+                start_time: start,
+                end_time: end,
+            };
+            let page: Vec<UserFill> = self.make_request(request).await?;
+            let full = page.len() >= FILLS_PAGE_LIMIT;
+            let last = page.iter().map(|fill| fill.time).max();
+            
+            // Pages overlap at the millisecond they meet
+            let before = fills.len();
+            fills.extend(page.into_iter().filter(|fill| seen.insert(fill.key())));
+            match last {
+                Some(last) if full && fills.len() > before => start = last,
+                _ => break,
+            }
+        }
+        
+        fills.sort_by_key(|fill| Reverse(fill.time));
+        Ok(fills)
+    }
+    
+    /// This account's fills for `symbol`, or every symbol, most recent
+    /// first, stopping after `limit` if there is one. The `userFills` page
+    /// comes first; if it's full, the older fills are walked back to a
+    /// window of `userFillsByTime` at a time, each fetched only once the
+    /// last is used up. Windows meet at a millisecond, so fills seen twice
+    /// are dropped by their hash.
+    pub fn stream_trade_history<'a>(
+        &'a self,
+        symbol: Option<&'a str>,
+        limit: Option<usize>,
+    ) -> impl Stream<Item = Result<Trade>> + 'a {
+        let walk = (HistoryPage::Latest, VecDeque::new(), HashSet::new());
+        let trades = stream::try_unfold(walk, move |(mut page, mut buffered, mut seen)| async move {
+            loop {
+                if let Some(fill) = buffered.pop_front() {
+                    let trade = UserFill::to_trade(&fill);
+                    return Ok(Some((trade, (page, buffered, seen))));
+                }
+                
+                let fills = match page {
+                    HistoryPage::Done => return Ok(None),
+                    HistoryPage::Latest => {
+                        let fills = self.get_user_fills(&self.account_address()?).await?;
+                        page = match fills.last() {
+                            Some(oldest) if fills.len() >= FILLS_PAGE_LIMIT => HistoryPage::Before {
+                                end: oldest.time,
+                                span: HISTORY_WINDOW_MS,
+                            },
+                            _ => HistoryPage::Done,
+                        };
+                        fills
+                    }
+                    HistoryPage::Before { end, span } => {
+                        let start = end.saturating_sub(span).max(HISTORY_START_MS);
+                        let fills = self.get_user_fills_by_time(&self.account_address()?, start, Some(end)).await?;
+                        let found = fills.iter().any(|fill| !seen.contains(&fill.key()));
+                        page = if start <= HISTORY_START_MS {
+                            HistoryPage::Done
+                        } else {
+                            HistoryPage::Before {
+                                end: start,
+                                span: if found { span } else { span * 2 },
+                            }
+                        };
+                        fills
+                    }
+                };
+                buffered.extend(
+                    fills
+                        .into_iter()
+                        .filter(|fill| seen.insert(fill.key()))
+                        .filter(|fill| symbol.is_none_or(|symbol| fill.coin == symbol)),
+                );
+            }
+        });
+        trades.take(limit.unwrap_or(usize::MAX))
+    }
+    
+    /// Candles for `symbol` between `start` and `end` (epoch millis), oldest
+    /// first. `interval` is one of 1m, 5m, 15m, 1h, 4h or 1d.
+    pub async fn get_candles(&self, symbol: &str, interval: &str, start: u64, end: u64) -> Result<Vec<Candle>> {
+        debug!("Fetching {} candles for {} from {} to {}", interval, symbol, start, end);
+        interval_duration(interval)?;
+        
+        let request = InfoRequest::CandleSnapshot {
+            req: CandleRequest {
+                coin: symbol.to_string(),
+                interval: interval.to_string(),
+                start_time: start,
+                end_time: end,
+            },
+        };
+        let mut candles: Vec<Candle> = self.make_request(request).await?;
+        candles.sort_by_key(|c| c.t);
+        Ok(candles)
+    }
+}
+
+/// Whether a request to `endpoint` that failed with `error` can be sent
+/// again. An exchange action can only be repeated if it never reached the
+/// exchange or the exchange turned it away unread; anything else might
+/// place an order twice.
+fn retry_safe(endpoint: &str, error: &Error) -> bool {
+    if endpoint != "exchange" {
+        return error.is_retryable();
+    }
+    match error.root() {
+        Error::Network(e) => e.is_connect(),
+        Error::RateLimit { .. } => true,
+        _ => false,
+    }
+}
+
+/// Whether `cloid` is a client order id the exchange takes: 0x and 16
+/// bytes of hex
+fn is_cloid(cloid: &str) -> bool {
+    cloid
+        .strip_prefix("0x")
+        .is_some_and(|hex| hex.len() == 32 && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+fn parse_twap_id(twap_id: &str) -> Result<u64> {
+    twap_id
+        .parse()
+        .map_err(|_| Error::InvalidInput(format!("{} is not a TWAP id", twap_id)))
+}
+
+/// Size filled across `fills` and their size-weighted average price
+fn fill_average(fills: &[Trade]) -> (Decimal, Option<Decimal>) {
+    let size: Decimal = fills.iter().map(|fill| fill.quantity).sum();
+    let notional: Decimal = fills.iter().map(|fill| fill.quantity * fill.price).sum();
+    (size, (!size.is_zero()).then(|| notional / size))
+}
+
+/// The typed error for an exchange `message`, or else a trading error with
+/// `what` failed
+fn rejection(what: &str, message: &str) -> Error {
+    Error::from_exchange_message(message).unwrap_or_else(|| Error::Trading(format!("{}: {}", what, message)))
+}
+
+/// `cancelled`, unless some cancels failed
+fn cancel_failures(cancelled: u32, failures: Vec<String>) -> Result<u32> {
+    if failures.is_empty() {
+        return Ok(cancelled);
     }
+    Err(Error::Trading(format!(
+        "{} of {} cancels failed: {}",
+        failures.len(),
+        cancelled as usize + failures.len(),
+        failures.join("; ")
+    )))
+}
+
+/// The exchange's order type for `order` and the limit price to send.
+/// Market orders are limits that take what they can at once. Stops rest as
+/// trigger orders; a market stop without a price is limited to its trigger
+/// price.
+fn order_type_wire(order: &Order) -> Result<(OrderTypeWire, Decimal)> {
+    let tif = |tif: &str| OrderTypeWire::Limit { tif: tif.to_string() };
+    let price = order.price.unwrap_or(Decimal::ZERO);
+    let is_market = match order.order_type {
+        OrderType::Market => return Ok((tif("Ioc"), price)),
+        OrderType::Limit => return Ok((tif("Gtc"), price)),
+        OrderType::Stop => true,
+        OrderType::StopLimit => false,
+        OrderType::Twap { .. } => {
+            return Err(Error::InvalidInput(format!("TWAP order for {} isn't an order action", order.symbol)))
+        }
+    };
+    
+    let trigger_px = order
+        .trigger_price
+        .ok_or_else(|| Error::InvalidInput(format!("{:?} order for {} has no trigger price", order.order_type, order.symbol)))?;
+    let price = match order.price {
+        Some(price) => price,
+        None if is_market => trigger_px,
+        None => return Err(Error::InvalidInput(format!("StopLimit order for {} has no limit price", order.symbol))),
+    };
+    let trigger = OrderTypeWire::Trigger {
+        is_market,
+        trigger_px,
+        tpsl: if order.is_take_profit { "tp" } else { "sl" }.to_string(),
+    };
+    Ok((trigger, price))
 }
 
 #[async_trait]
 pub trait TradingClient {
     async fn get_market_data(&self, symbol: &str) -> Result<MarketData>;
+    
+    /// Market data for each of `symbols`, fetched concurrently. A symbol
+    /// that fails is logged and left out; the batch only fails if they all
+    /// do.
+    async fn get_market_data_batch(&self, symbols: &[&str]) -> Result<HashMap<String, MarketData>> {
+        let permits = tokio::sync::Semaphore::new(MARKET_DATA_CONCURRENCY);
+        let fetches = symbols.iter().map(|&symbol| {
+            let permits = &permits;
+            async move {
+                let _permit = permits.acquire().await.expect("semaphore is never closed");
+                (symbol, self.get_market_data(symbol).await)
+            }
+        });
+        
+        let mut batch = HashMap::new();
+        let mut first_error = None;
+        for (symbol, result) in futures_util::future::join_all(fetches).await {
+            match result {
+                Ok(market_data) => {
+                    batch.insert(symbol.to_string(), market_data);
+                }
+                Err(e) => {
+                    warn!("Failed to fetch market data for {}: {}", symbol, e);
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+        match first_error {
+            Some(e) if batch.is_empty() => Err(e),
+            _ => Ok(batch),
+        }
+    }
+    
     async fn get_account_info(&self) -> Result<AccountInfo>;
     async fn get_positions(&self) -> Result<Vec<Position>>;
     async fn get_open_orders(&self) -> Result<Vec<Order>>;
     async fn place_order(&self, order: &Order) -> Result<String>;
     async fn cancel_order(&self, order_id: &str) -> Result<bool>;
+    
+    /// Cancels every open order, or only `symbol`'s, and returns how many
+    /// were cancelled. Tries them all even if some fail, then fails with how
+    /// many did.
+    async fn cancel_all_orders(&self, symbol: Option<&str>) -> Result<u32> {
+        let orders = self.get_open_orders().await?;
+        let mut cancelled = 0;
+        let mut failures = Vec::new();
+        for order in orders.iter().filter(|order| symbol.is_none_or(|s| s.eq_ignore_ascii_case(&order.symbol))) {
+            match self.cancel_order(&order.id).await {
+                Ok(true) => cancelled += 1,
+                // Filled or cancelled meanwhile
+                Ok(false) => {}
+                Err(e) => failures.push(format!("{}: {}", order.id, e)),
+            }
+        }
+        cancel_failures(cancelled, failures)
+    }
+    
+    /// What became of order `order_id`. Clients without an order status
+    /// query look for it among the open orders, then the fills; one in
+    /// neither counts as cancelled.
+    async fn get_order_status(&self, order_id: &str) -> Result<OrderStatusInfo> {
+        if let Some(order) = self.get_open_orders().await?.into_iter().find(|order| order.id == order_id) {
+            return Ok(OrderStatusInfo {
+                state: OrderState::Resting,
+                filled_size: order.filled_quantity,
+                average_price: order.average_price,
+                fee: Decimal::ZERO,
+            });
+        }
+        let fills: Vec<Trade> = self
+            .get_trade_history(None)
+            .await?
+            .into_iter()
+            .filter(|trade| trade.order_id == order_id)
+            .collect();
+        let (filled_size, average_price) = fill_average(&fills);
+        Ok(OrderStatusInfo {
+            state: if fills.is_empty() { OrderState::Cancelled } else { OrderState::Filled },
+            filled_size,
+            average_price,
+            fee: fills.iter().map(|fill| fill.fee).sum(),
+        })
+    }
+    
     async fn get_trade_history(&self, symbol: Option<&str>) -> Result<Vec<Trade>>;
+    
+    /// Fills for `symbol`, or every symbol, from `since` to `until`
+    /// inclusive. Clients that can't ask for a range filter their whole
+    /// history.
+    async fn get_trade_history_between(
+        &self,
+        symbol: Option<&str>,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<Vec<Trade>> {
+        let trades = self.get_trade_history(symbol).await?;
+        Ok(trades
+            .into_iter()
+            .filter(|trade| since.is_none_or(|since| trade.timestamp >= since))
+            .filter(|trade| until.is_none_or(|until| trade.timestamp <= until))
+            .collect())
+    }
+    
+    /// The account's fills from `since` to `until` inclusive, each with
+    /// the hash and trade id that tell it apart from the same fill
+    /// streamed. Clients that only report trades make them up from
+    /// `get_trade_history_between`.
+    async fn get_fills_between(&self, since: DateTime<Utc>, until: DateTime<Utc>) -> Result<Vec<UserFill>> {
+        let trades = self.get_trade_history_between(None, Some(since), Some(until)).await?;
+        Ok(trades.iter().map(UserFill::from_trade).collect())
+    }
+    
+    /// Candles for `symbol` between `start` and `end` (epoch millis), oldest
+    /// first. Clients without candle history return an error.
+    async fn get_candles(&self, symbol: &str, _interval: &str, _start: u64, _end: u64) -> Result<Vec<Candle>> {
+        Err(Error::Api(format!("No candle history for {} from this client", symbol)))
+    }
+    
+    /// Listed assets with their limits. Clients that aren't backed by an
+    /// exchange return an error.
+    async fn get_meta(&self) -> Result<Meta> {
+        Err(Error::Api("No exchange metadata from this client".to_string()))
+    }
+    
+    /// Funding settled on the account's positions from `start` to `end`.
+    /// Clients without funding history report none.
+    async fn get_funding_payments(&self, _start: DateTime<Utc>, _end: DateTime<Utc>) -> Result<Vec<FundingPayment>> {
+        Ok(Vec::new())
+    }
+    
+    /// The fee rates the account pays. Clients without an account on the
+    /// exchange return an error.
+    async fn get_user_fees(&self) -> Result<FeeSchedule> {
+        Err(Error::Api("No fee schedule from this client".to_string()))
+    }
+    
+    /// The account's value over its life, oldest first. Clients without an
+    /// account on the exchange return an error.
+    async fn get_portfolio_history(&self) -> Result<Vec<EquityPoint>> {
+        Err(Error::Api("No portfolio history from this client".to_string()))
+    }
+    
+    /// Has the exchange cancel every open order at `at`, or clears the
+    /// schedule. Clients without scheduled cancels return an error.
+    async fn schedule_cancel(&self, _at: Option<DateTime<Utc>>) -> Result<()> {
+        Err(Error::Api("No scheduled cancels from this client".to_string()))
+    }
+    
+    /// Sends USDC to another account on the exchange. Clients that can't
+    /// move funds return an error.
+    async fn usd_transfer(&self, _destination: &str, _amount: Decimal) -> Result<()> {
+        Err(Error::Api("No transfers from this client".to_string()))
+    }
+    
+    /// Withdraws USDC to an address on Arbitrum. Clients that can't move
+    /// funds return an error.
+    async fn withdraw(&self, _destination: &str, _amount: Decimal) -> Result<()> {
+        Err(Error::Api("No withdrawals from this client".to_string()))
+    }
 }
 
 #[async_trait]
@@ -104,67 +1565,62 @@ impl TradingClient for HyperliquidClient {
     async fn get_market_data(&self, symbol: &str) -> Result<MarketData> {
         debug!("Fetching market data for {}", symbol);
         
-        let data = json!({
-            "type": "allMids"
-        });
-        
-        let response: HashMap<String, Decimal> = self.make_request("info", Some(data)).await?;
-        
-        let price = response.get(symbol)
-            .ok_or_else(|| Error::Api(format!("Symbol {} not found", symbol)))?;
+        // The asset context has no range, so the day's high and low come
+        // from its hourly candles
+        let (context, range) = tokio::join!(self.asset_context(symbol), self.day_range(symbol));
+        let context = context?;
         
-        // For now, return basic market data. In production, you'd want to fetch
-        // more detailed data including volume, 24h change, etc.
+        let price = context.mid_px.unwrap_or(context.mark_px);
+        let (high, low) = range.unwrap_or_else(|e| {
+            warn!("No 24h range for {}: {}", symbol, e);
+            (price, price)
+        });
         Ok(MarketData {
             symbol: symbol.to_string(),
-            price: *price,
-            volume_24h: Decimal::ZERO, // Would need separate API call
-            change_24h: Decimal::ZERO, // Would need separate API call
-            high_24h: *price, // Would need separate API call
-            low_24h: *price, // Would need separate API call
+            price,
+            volume_24h: context.day_ntl_vlm,
+            change_24h: price - context.prev_day_px,
+            high_24h: high.max(price),
+            low_24h: low.min(price),
             timestamp: Utc::now(),
         })
     }
     
     async fn get_account_info(&self) -> Result<AccountInfo> {
-        debug!("Fetching account info");
+        let address = self.account_address()?;
+        match &self.vault_address {
+            Some(_) => debug!("Fetching account info for vault {}", address),
+            None => debug!("Fetching account info for {}", address),
+        }
         
-        let data = json!({
-            "type": "clearinghouseState",
-            "user": self.api_key
-        });
+        let state = self.get_user_state(&address).await?;
         
-        let response: UserState = self.make_request("info", Some(data)).await?;
-        
-        let mut positions = Vec::new();
-        for asset_pos in response.asset_positions {
-            if asset_pos.sz != Decimal::ZERO {
-                positions.push(Position {
-                    symbol: asset_pos.coin,
-                    side: if asset_pos.sz > Decimal::ZERO {
-                        PositionSide::Long
-                    } else {
-                        PositionSide::Short
-                    },
-                    size: asset_pos.sz.abs(),
-                    entry_price: asset_pos.entry_px,
-                    current_price: Decimal::ZERO, // Would need separate call
-                    unrealized_pnl: asset_pos.unrealized_pnl,
-                    realized_pnl: Decimal::ZERO, // Would need separate call
-                    margin: asset_pos.position.margin_used,
-                    timestamp: Utc::now(),
-                });
-            }
-        }
+        let positions: Vec<Position> = state
+            .asset_positions
+            .iter()
+            .map(|asset| &asset.position)
+            .filter(|position| !position.szi.is_zero())
+            .map(|position| Position {
+                symbol: position.coin.clone(),
+                side: if position.szi > Decimal::ZERO { PositionSide::Long } else { PositionSide::Short },
+                size: position.szi.abs(),
+                entry_price: position.entry_px,
+                current_price: position.position_value / position.szi.abs(),
+                unrealized_pnl: position.unrealized_pnl,
+                realized_pnl: Decimal::ZERO, // Not in the account state
+                margin: position.margin_used,
+                timestamp: Utc::now(),
+            })
+            .collect();
         
-        let balance = response.withdrawable;
+        let balance = state.margin_summary.as_ref().map_or(state.withdrawable, |summary| summary.account_value);
         let total_pnl = positions.iter()
             .map(|p| p.unrealized_pnl)
             .sum();
         
         Ok(AccountInfo {
             balance,
-            available_balance: balance,
+            available_balance: state.withdrawable,
             total_pnl,
             total_margin: positions.iter()
                 .map(|p| p.margin)
@@ -180,69 +1636,213 @@ impl TradingClient for HyperliquidClient {
     }
     
     async fn get_open_orders(&self) -> Result<Vec<Order>> {
-        // This would require a separate API call to get open orders
-        // For now, return empty vector
-        Ok(Vec::new())
+        let orders = self.get_user_open_orders(&self.account_address()?).await?;
+        for order in &orders {
+            self.remember_order(order.oid, &order.coin);
+        }
+        Ok(orders.iter().map(OpenOrder::to_order).collect())
     }
     
     async fn place_order(&self, order: &Order) -> Result<String> {
         debug!("Placing order: {:?}", order);
         
-        // Convert our Order model to Hyperliquid's format
-        let order_request = OrderRequest {
-            a: 0, // asset_id - would need to map symbol to asset_id
-            b: matches!(order.side, OrderSide::Buy),
-            p: order.price.unwrap_or(Decimal::ZERO),
-            s: order.quantity,
-            r: false, // reduce_only
-            t: match order.order_type {
-                OrderType::Market => "Market".to_string(),
-                OrderType::Limit => "Limit".to_string(),
-                _ => "Limit".to_string(),
-            },
-            cid: 0, // client_order_id - would generate unique ID
-        };
+        if let OrderType::Twap { duration_minutes } = order.order_type {
+            return self
+                .submit_twap(&order.symbol, order.side.clone(), order.quantity, duration_minutes, false, order.reduce_only)
+                .await;
+        }
         
-        let data = json!({
-            "action": {
-                "type": "order",
-                "orders": [order_request]
+        let action = self.order_action(order).await?;
+        let result: ExchangeResult<OrderStatusEntry> = self.make_action_request(action).await?;
+        match result.data.statuses.into_iter().next() {
+            Some(OrderStatusEntry::Resting { oid }) => {
+                info!("Order {} resting on the book", oid);
+                self.remember_order(oid, &order.symbol);
+                Ok(oid.to_string())
             }
-        });
-        
-        let response: OrderResponse = self.make_request("exchange", Some(data)).await?;
-        
-        if response.status == "ok" {
-            info!("Order placed successfully");
-            Ok("order_id".to_string()) // Would return actual order ID
-        } else {
-            Err(Error::Trading(format!("Failed to place order: {}", response.status)))
+            Some(OrderStatusEntry::Filled { oid, total_sz, avg_px }) => {
+                info!("Order {} filled {} @ {}", oid, total_sz, avg_px);
+                self.remember_order(oid, &order.symbol);
+                Ok(oid.to_string())
+            }
+            Some(OrderStatusEntry::Error(message)) => Err(rejection("Failed to place order", &message)),
+            None => Err(Error::Api("No order status in response".to_string())),
         }
     }
     
     async fn cancel_order(&self, order_id: &str) -> Result<bool> {
         debug!("Cancelling order: {}", order_id);
         
+        let oid: u64 = order_id
+            .parse()
+            .map_err(|_| Error::InvalidInput(format!("{} is not an exchange order id", order_id)))?;
+        let Some(coin) = self.order_coin(oid).await? else {
+            debug!("Order {} isn't open; nothing to cancel", order_id);
+            return Ok(false);
+        };
+        
         let cancel_request = CancelRequest {
-            coin: "".to_string(), // Would need to map order_id to coin
-            oid: order_id.parse().unwrap_or(0),
+            a: self.resolve_asset_id(&coin).await?,
+            o: oid,
         };
         
-        let data = json!({
-            "action": {
-                "type": "cancel",
-                "cancels": [cancel_request]
+        let action = Action::Cancel {
+            cancels: vec![cancel_request],
+        };
+        
+        let result: ExchangeResult<CancelStatusWire> = self.make_action_request(action).await?;
+        match result.data.statuses.into_iter().next() {
+            Some(CancelStatusWire::Success(_)) => {
+                self.order_coins.lock().unwrap().remove(&oid);
+                Ok(true)
             }
-        });
+            Some(CancelStatusWire::Error { error }) => Err(rejection("Failed to cancel order", &error)),
+            None => Err(Error::Api("No cancel status in response".to_string())),
+        }
+    }
+    
+    /// Cancels the orders in one `cancel` action
+    async fn cancel_all_orders(&self, symbol: Option<&str>) -> Result<u32> {
+        let orders: Vec<OpenOrder> = self
+            .get_user_open_orders(&self.account_address()?)
+            .await?
+            .into_iter()
+            .filter(|order| symbol.is_none_or(|s| s.eq_ignore_ascii_case(&order.coin)))
+            .collect();
+        if orders.is_empty() {
+            return Ok(0);
+        }
+        debug!("Cancelling {} open orders", orders.len());
         
-        let response: CancelResponse = self.make_request("exchange", Some(data)).await?;
+        let mut cancels = Vec::with_capacity(orders.len());
+        for order in &orders {
+            cancels.push(CancelRequest {
+                a: self.resolve_asset_id(&order.coin).await?,
+                o: order.oid,
+            });
+        }
         
-        Ok(response.status == "ok")
+        let result: ExchangeResult<CancelStatusWire> = self.make_action_request(Action::Cancel { cancels }).await?;
+        let mut cancelled = 0;
+        let mut failures = Vec::new();
+        for (order, status) in orders.iter().zip(result.data.statuses) {
+            match status {
+                CancelStatusWire::Success(_) => {
+                    self.order_coins.lock().unwrap().remove(&order.oid);
+                    cancelled += 1;
+                }
+                CancelStatusWire::Error { error } => failures.push(format!("{}: {}", order.oid, error)),
+            }
+        }
+        cancel_failures(cancelled, failures)
     }
     
-    async fn get_trade_history(&self, _symbol: Option<&str>) -> Result<Vec<Trade>> {
-        // This would require a separate API call to get trade history
-        // For now, return empty vector
-        Ok(Vec::new())
+    /// Asks `orderStatus` by exchange order id or cloid, then averages the
+    /// order's fills and adds up their fees if it has any. Fails with [`Error::OrderNotFound`] if
+    /// the exchange doesn't know it.
+    async fn get_order_status(&self, order_id: &str) -> Result<OrderStatusInfo> {
+        let oid = match order_id.parse::<u64>() {
+            Ok(oid) => OrderRef::Oid(oid),
+            Err(_) if is_cloid(order_id) => OrderRef::Cloid(order_id.to_string()),
+            Err(_) => {
+                return Err(Error::InvalidInput(format!("{} is neither an exchange order id nor a cloid", order_id)))
+            }
+        };
+        let found = self.order_status(oid).await?.ok_or(Error::OrderNotFound)?;
+        let filled_size = found.filled_size();
+        let fills: Vec<Trade> = if filled_size.is_zero() {
+            Vec::new()
+        } else {
+            // The walk back stops once the order's fills are all found
+            let oid = found.order.oid.to_string();
+            let trades = self.stream_trade_history(Some(&found.order.coin), None);
+            let mut trades = std::pin::pin!(trades);
+            let mut fills = Vec::new();
+            let mut found_size = Decimal::ZERO;
+            while found_size < filled_size {
+                match trades.try_next().await? {
+                    Some(trade) if trade.order_id == oid => {
+                        found_size += trade.quantity;
+                        fills.push(trade);
+                    }
+                    Some(_) => {}
+                    None => break,
+                }
+            }
+            fills
+        };
+        Ok(OrderStatusInfo {
+            state: found.state(),
+            filled_size,
+            average_price: fill_average(&fills).1,
+            fee: fills.iter().map(|fill| fill.fee).sum(),
+        })
+    }
+    
+    async fn get_trade_history(&self, symbol: Option<&str>) -> Result<Vec<Trade>> {
+        self.stream_trade_history(symbol, None).try_collect().await
+    }
+    
+    async fn get_trade_history_between(
+        &self,
+        symbol: Option<&str>,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<Vec<Trade>> {
+        let Some(since) = since else {
+            // The most recent fills are all there is without a start
+            let trades = self.get_trade_history(symbol).await?;
+            return Ok(trades
+                .into_iter()
+                .filter(|trade| until.is_none_or(|until| trade.timestamp <= until))
+                .collect());
+        };
+        
+        let millis = |time: DateTime<Utc>| time.timestamp_millis().max(0) as u64;
+        let fills = self
+            .get_user_fills_by_time(&self.account_address()?, millis(since), until.map(millis))
+            .await?;
+        Ok(fills
+            .iter()
+            .filter(|fill| symbol.is_none_or(|symbol| fill.coin == symbol))
+            .map(UserFill::to_trade)
+            .collect())
+    }
+    
+    async fn get_fills_between(&self, since: DateTime<Utc>, until: DateTime<Utc>) -> Result<Vec<UserFill>> {
+        let millis = |time: DateTime<Utc>| time.timestamp_millis().max(0) as u64;
+        self.get_user_fills_by_time(&self.account_address()?, millis(since), Some(millis(until))).await
+    }
+    
+    async fn get_candles(&self, symbol: &str, interval: &str, start: u64, end: u64) -> Result<Vec<Candle>> {
+        HyperliquidClient::get_candles(self, symbol, interval, start, end).await
+    }
+    
+    async fn get_meta(&self) -> Result<Meta> {
+        HyperliquidClient::get_meta(self).await
+    }
+    
+    async fn get_funding_payments(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<FundingPayment>> {
+        HyperliquidClient::get_funding_payments(self, start, end).await
+    }
+    
+    async fn get_user_fees(&self) -> Result<FeeSchedule> {
+        HyperliquidClient::get_user_fees(self).await
+    }
+    
+    async fn get_portfolio_history(&self) -> Result<Vec<EquityPoint>> {
+        HyperliquidClient::get_portfolio_history(self).await
+    }
+    
+    async fn schedule_cancel(&self, at: Option<DateTime<Utc>>) -> Result<()> {
+        HyperliquidClient::schedule_cancel(self, at).await
+    }
+    
+    async fn usd_transfer(&self, destination: &str, amount: Decimal) -> Result<()> {
+        HyperliquidClient::usd_transfer(self, destination, amount).await
+    }
+    
+    async fn withdraw(&self, destination: &str, amount: Decimal) -> Result<()> {
+        HyperliquidClient::withdraw(self, destination, amount).await
     }
 }
@@ -0,0 +1,230 @@
+use crate::{
+    market_stream::MarketEvent,
+    models::{MarketData, OrderSide, OrderStatus},
+};
+use chrono::{DateTime, TimeZone, Utc};
+use rust_decimal::Decimal;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use tokio::time::{Duration, Instant};
+use tracing::warn;
+
+use super::types::*;
+
+/// Counted against frames that aren't a channel message at all
+pub const MALFORMED: &str = "malformed";
+
+/// How much of a bad frame gets logged
+const LOGGED_PAYLOAD_CHARS: usize = 256;
+const ERROR_WINDOW: Duration = Duration::from_secs(60);
+
+/// One decoded websocket message
+#[derive(Debug, Clone)]
+pub enum WsEvent {
+    Market(MarketEvent),
+    /// A coin's book down to the levels the exchange sends
+    L2Book(L2Book),
+    /// Every coin's mid, by coin
+    AllMids(HashMap<String, Decimal>),
+    /// Trades on a subscribed coin, as decoded. The client passes each one
+    /// on as a `Trade` instead.
+    Trades(Vec<WsTrade>),
+    /// One trade on a subscribed coin; `side` is the aggressor's
+    Trade {
+        coin: String,
+        px: Decimal,
+        sz: Decimal,
+        side: OrderSide,
+        time: DateTime<Utc>,
+    },
+    /// A coin's candle at one interval, pushed again with each trade until
+    /// the next one opens
+    Candle(CandleUpdate),
+    /// Fills on a subscribed user's account, as decoded. The client passes
+    /// each one on as a `Fill` instead.
+    UserFills(UserFillsUpdate),
+    /// One fill on a subscribed user's account, the first time it's seen
+    Fill { user: String, fill: UserFill },
+    /// Orders on a subscribed user's account that changed status, as
+    /// decoded. The client passes each one on as an `OrderUpdate` instead.
+    OrderUpdates(Vec<OrderStatusUpdate>),
+    /// One order on a subscribed user's account that changed status
+    OrderUpdate {
+        oid: u64,
+        cloid: Option<String>,
+        status: OrderStatus,
+        filled_sz: Decimal,
+    },
+    /// A message for a subscribed user from the exchange, e.g. a warning
+    /// that a position is close to liquidation. It doesn't say whose.
+    Notification(String),
+    /// The response to a request posted over the websocket. The client hands
+    /// it to whoever posted the request rather than passing it on.
+    Posted { id: u64, response: Value },
+    /// The exchange acknowledging a subscription
+    Subscribed(Value),
+    Pong,
+    /// A channel we don't decode, with its raw message
+    Unknown { channel: String, data: Value },
+}
+
+impl WsEvent {
+    /// The channel the event came in on
+    pub fn channel(&self) -> &str {
+        match self {
+            WsEvent::Market(MarketEvent::Ticker(_)) => "ticker",
+            WsEvent::Market(MarketEvent::Candle(_)) | WsEvent::Candle(_) => "candle",
+            WsEvent::Market(MarketEvent::Book(_)) | WsEvent::L2Book(_) => "l2Book",
+            WsEvent::AllMids(_) => "allMids",
+            WsEvent::Trades(_) | WsEvent::Trade { .. } => "trades",
+            WsEvent::UserFills(_) | WsEvent::Fill { .. } => "userFills",
+            WsEvent::OrderUpdates(_) | WsEvent::OrderUpdate { .. } => "orderUpdates",
+            WsEvent::Notification(_) => "notification",
+            WsEvent::Posted { .. } => "post",
+            WsEvent::Subscribed(_) => "subscriptionResponse",
+            WsEvent::Pong => "pong",
+            WsEvent::Unknown { channel, .. } => channel,
+        }
+    }
+    
+    /// Whether this is news about a subscribed account, which has to reach
+    /// every consumer, rather than market data the next update supersedes
+    pub fn is_account_event(&self) -> bool {
+        matches!(self.channel(), "userFills" | "orderUpdates" | "notification")
+    }
+}
+
+/// What became of one frame
+#[derive(Debug, Clone)]
+pub enum FrameOutcome {
+    Event(WsEvent),
+    /// The frame couldn't be decoded and was dropped
+    Dropped,
+    /// Dropped, and its channel has failed more than the limit allows in the
+    /// last minute, so it should be resubscribed
+    Resubscribe(String),
+}
+
+/// Parse errors per channel, shared between a client's connections so the
+/// counts survive reconnects
+pub type ParseErrorCounts = Arc<Mutex<BTreeMap<String, u64>>>;
+
+/// Decodes websocket frames. A frame that doesn't decode is logged, counted
+/// against its channel and dropped, so one bad message never stops the ones
+/// after it.
+pub struct FrameParser {
+    max_errors_per_minute: u32,
+    counts: ParseErrorCounts,
+    /// When each channel's recent errors happened
+    recent: HashMap<String, VecDeque<Instant>>,
+}
+
+impl FrameParser {
+    pub fn new(max_errors_per_minute: u32) -> Self {
+        Self::with_counts(max_errors_per_minute, ParseErrorCounts::default())
+    }
+    
+    /// Counts errors into `counts`
+    pub fn with_counts(max_errors_per_minute: u32, counts: ParseErrorCounts) -> Self {
+        Self {
+            max_errors_per_minute,
+            counts,
+            recent: HashMap::new(),
+        }
+    }
+    
+    pub fn parse(&mut self, text: &str, now: Instant) -> FrameOutcome {
+        let message: WebSocketMessage = match serde_json::from_str(text) {
+            Ok(message) => message,
+            Err(e) => return self.reject(MALFORMED, &e.to_string(), text, now),
+        };
+        
+        let decoded = match message.channel.as_str() {
+            "ticker" => decode::<Ticker>(&message.data).and_then(ticker_event),
+            "allMids" => decode::<AllMidsUpdate>(&message.data).map(|update| WsEvent::AllMids(update.mids)),
+            "trades" => decode(&message.data).map(WsEvent::Trades),
+            "candle" => decode::<CandleUpdate>(&message.data).map(WsEvent::Candle),
+            "l2Book" => decode::<L2Book>(&message.data).map(WsEvent::L2Book),
+            "userFills" => decode(&message.data).map(WsEvent::UserFills),
+            "orderUpdates" => decode(&message.data).map(WsEvent::OrderUpdates),
+            "notification" => decode::<NotificationUpdate>(&message.data).map(|update| WsEvent::Notification(update.notification)),
+            "post" => decode::<PostUpdate>(&message.data).map(|update| WsEvent::Posted {
+                id: update.id,
+                response: update.response,
+            }),
+            "subscriptionResponse" => Ok(WsEvent::Subscribed(message.data)),
+            "pong" => Ok(WsEvent::Pong),
+            _ => Ok(WsEvent::Unknown {
+                channel: message.channel.clone(),
+                data: message.data,
+            }),
+        };
+        
+        match decoded {
+            Ok(event) => FrameOutcome::Event(event),
+            Err(reason) => self.reject(&message.channel, &reason, text, now),
+        }
+    }
+    
+    /// Parse errors so far, by channel
+    pub fn parse_errors(&self) -> BTreeMap<String, u64> {
+        self.counts.lock().unwrap().clone()
+    }
+    
+    fn reject(&mut self, channel: &str, reason: &str, text: &str, now: Instant) -> FrameOutcome {
+        warn!(
+            event = "ws_parse_error",
+            channel,
+            "Dropping websocket frame that failed to parse: {}; payload: {}",
+            reason,
+            truncate(text, LOGGED_PAYLOAD_CHARS)
+        );
+        *self.counts.lock().unwrap().entry(channel.to_string()).or_default() += 1;
+        
+        if channel == MALFORMED {
+            return FrameOutcome::Dropped;
+        }
+        let recent = self.recent.entry(channel.to_string()).or_default();
+        recent.push_back(now);
+        while recent.front().is_some_and(|&at| now.duration_since(at) >= ERROR_WINDOW) {
+            recent.pop_front();
+        }
+        if recent.len() > self.max_errors_per_minute as usize {
+            // Start counting afresh so one burst resubscribes once
+            recent.clear();
+            return FrameOutcome::Resubscribe(channel.to_string());
+        }
+        FrameOutcome::Dropped
+    }
+}
+
+fn decode<T: DeserializeOwned>(data: &Value) -> Result<T, String> {
+    T::deserialize(data).map_err(|e| e.to_string())
+}
+
+fn timestamp(millis: u64) -> Result<DateTime<Utc>, String> {
+    Utc.timestamp_millis_opt(millis as i64)
+        .single()
+        .ok_or_else(|| format!("timestamp {} out of range", millis))
+}
+
+fn ticker_event(ticker: Ticker) -> Result<WsEvent, String> {
+    Ok(WsEvent::Market(MarketEvent::Ticker(MarketData {
+        symbol: ticker.coin,
+        price: ticker.px,
+        volume_24h: Decimal::ZERO, // Not in ticker updates
+        change_24h: Decimal::ZERO,
+        high_24h: ticker.px,
+        low_24h: ticker.px,
+        timestamp: timestamp(ticker.time)?,
+    })))
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}… ({} bytes)", &text[..end], text.len()),
+        None => text.to_string(),
+    }
+}
@@ -1,6 +1,20 @@
+pub mod cache;
 pub mod client;
+pub mod frames;
+pub mod nonce;
+pub mod orderbook;
+pub mod rate_limit;
+pub mod recording;
+pub mod signing;
 pub mod websocket;
+pub mod ws_pool;
 pub mod types;
+pub mod wallet;
 
-pub use client::HyperliquidClient;
-pub use websocket::WebSocketClient;
+pub use cache::MarketDataCache;
+pub use client::{HyperliquidClient, HyperliquidClientBuilder};
+pub use nonce::NonceManager;
+pub use orderbook::OrderBookTracker;
+pub use recording::{FrameRecorder, ReplayWebSocketClient};
+pub use websocket::{EventReceiver, PostChannel, WebSocketClient, WebSocketHandle};
+pub use ws_pool::WsPool;
@@ -0,0 +1,42 @@
+use crate::clock::{Clock, SystemClock};
+use std::sync::{Arc, Mutex};
+
+/// Hands out nonces for signed exchange actions. Hyperliquid nonces are
+/// millisecond timestamps and must strictly increase per signer, so two
+/// actions signed in the same millisecond get consecutive values.
+pub struct NonceManager {
+    last: Mutex<u64>,
+    clock: Arc<dyn Clock>,
+}
+
+impl Default for NonceManager {
+    fn default() -> Self {
+        Self::with_clock(Arc::new(SystemClock))
+    }
+}
+
+impl NonceManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Nonces from `clock`'s time, e.g. a simulated clock so tests sign
+    /// the same payloads every run
+    pub fn with_clock(clock: Arc<dyn Clock>) -> Self {
+        Self {
+            last: Mutex::new(0),
+            clock,
+        }
+    }
+
+    /// Assigns the next nonce and runs `sign` with it before any other
+    /// caller gets one, so nonces increase in the order payloads are signed
+    /// however many requests are in flight. Only signing is serialized; the
+    /// requests themselves can overlap.
+    pub fn sign<T>(&self, sign: impl FnOnce(u64) -> T) -> T {
+        let mut last = self.last.lock().unwrap();
+        let now = self.clock.now().timestamp_millis().max(0) as u64;
+        *last = now.max(*last + 1);
+        sign(*last)
+    }
+}
@@ -0,0 +1,149 @@
+use rust_decimal::Decimal;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::{Duration, Instant};
+use tracing::warn;
+
+use super::frames::WsEvent;
+use super::websocket::EventReceiver;
+use super::types::{L2Book, L2Level};
+
+/// How long a book goes without an update before it's no longer trusted
+pub const DEFAULT_STALE_BOOK: Duration = Duration::from_secs(10);
+
+/// Size resting on each side of a book near its mid
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Depth {
+    pub bids: Decimal,
+    pub asks: Decimal,
+}
+
+/// One coin's book, as of its latest snapshot
+struct Book {
+    /// Size by price; the best bid is the last
+    bids: BTreeMap<Decimal, Decimal>,
+    /// Size by price; the best ask is the first
+    asks: BTreeMap<Decimal, Decimal>,
+    /// Exchange time of the snapshot, in milliseconds
+    time: u64,
+    received: Instant,
+}
+
+impl Book {
+    fn best_bid(&self) -> Option<Decimal> {
+        self.bids.keys().next_back().copied()
+    }
+    
+    fn best_ask(&self) -> Option<Decimal> {
+        self.asks.keys().next().copied()
+    }
+    
+    fn mid(&self) -> Option<Decimal> {
+        Some((self.best_bid()? + self.best_ask()?) / Decimal::TWO)
+    }
+}
+
+/// The latest book per coin, kept from the `l2Book` snapshots the
+/// websocket pushes. Each snapshot replaces the coin's book outright, and
+/// one older than the book it would replace is ignored. A book that
+/// hasn't been updated within `stale_after` answers nothing until the
+/// next snapshot.
+pub struct OrderBookTracker {
+    stale_after: Duration,
+    books: Mutex<HashMap<String, Book>>,
+}
+
+impl OrderBookTracker {
+    pub fn new(stale_after: Duration) -> Self {
+        Self {
+            stale_after,
+            books: Mutex::new(HashMap::new()),
+        }
+    }
+    
+    /// Takes in `event` if it's a book snapshot
+    pub fn apply(&self, event: &WsEvent) {
+        if let WsEvent::L2Book(book) = event {
+            self.update(book);
+        }
+    }
+    
+    /// Replaces `book.coin`'s book with `book`, unless it's older than
+    /// the one already held
+    pub fn update(&self, book: &L2Book) {
+        let mut books = self.books.lock().unwrap();
+        if books.get(&book.coin).is_some_and(|held| held.time > book.time) {
+            return;
+        }
+        let levels = |side: &[L2Level]| {
+            side.iter()
+                .filter(|level| level.sz > Decimal::ZERO)
+                .map(|level| (level.px, level.sz))
+                .collect()
+        };
+        books.insert(
+            book.coin.clone(),
+            Book {
+                bids: levels(&book.bids),
+                asks: levels(&book.asks),
+                time: book.time,
+                received: Instant::now(),
+            },
+        );
+    }
+    
+    /// Applies the book snapshots in `events` until the websocket client
+    /// is dropped
+    pub async fn follow(&self, mut events: EventReceiver) {
+        loop {
+            match events.recv().await {
+                Ok(event) => self.apply(&event),
+                // The next snapshot replaces whatever was missed
+                Err(RecvError::Lagged(skipped)) => warn!("📚 Order books skipped {} messages", skipped),
+                Err(RecvError::Closed) => return,
+            }
+        }
+    }
+    
+    /// Whether `symbol` has a book updated within the staleness threshold
+    pub fn is_valid(&self, symbol: &str) -> bool {
+        self.with_book(symbol, |_| ()).is_some()
+    }
+    
+    pub fn best_bid(&self, symbol: &str) -> Option<Decimal> {
+        self.with_book(symbol, Book::best_bid)?
+    }
+    
+    pub fn best_ask(&self, symbol: &str) -> Option<Decimal> {
+        self.with_book(symbol, Book::best_ask)?
+    }
+    
+    /// Halfway between the best bid and ask, if both sides have orders
+    pub fn mid(&self, symbol: &str) -> Option<Decimal> {
+        self.with_book(symbol, Book::mid)?
+    }
+    
+    /// Size resting within `px_distance` of the mid on each side
+    pub fn depth_at(&self, symbol: &str, px_distance: Decimal) -> Option<Depth> {
+        self.with_book(symbol, |book| {
+            let mid = book.mid()?;
+            Some(Depth {
+                bids: book.bids.range(mid - px_distance..).map(|(_, sz)| sz).sum(),
+                asks: book.asks.range(..=mid + px_distance).map(|(_, sz)| sz).sum(),
+            })
+        })?
+    }
+    
+    /// `f` applied to `symbol`'s book, if it has one that isn't stale
+    fn with_book<T>(&self, symbol: &str, f: impl FnOnce(&Book) -> T) -> Option<T> {
+        let books = self.books.lock().unwrap();
+        books.get(symbol).filter(|book| book.received.elapsed() < self.stale_after).map(f)
+    }
+}
+
+impl Default for OrderBookTracker {
+    fn default() -> Self {
+        Self::new(DEFAULT_STALE_BOOK)
+    }
+}
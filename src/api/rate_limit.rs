@@ -0,0 +1,67 @@
+use crate::config::RateLimitConfig;
+use crate::error::{Error, Result};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// A token bucket: up to `burst` requests at once, refilled at `per_second`.
+/// Requests over budget queue for a token in the order they asked, rather
+/// than failing.
+pub struct RateLimiter {
+    per_second: f64,
+    burst: f64,
+    max_wait: Duration,
+    /// Tokens left as of when it was last updated. Below zero, the requests
+    /// already waiting have spoken for the tokens still to come.
+    bucket: Mutex<(f64, Instant)>,
+}
+
+impl RateLimiter {
+    /// `per_second` of 0 or less lets every request straight through
+    pub fn new(per_second: f64, burst: u32, max_wait: Duration) -> Self {
+        let burst = f64::from(burst.max(1));
+        Self {
+            per_second,
+            burst,
+            max_wait,
+            bucket: Mutex::new((burst, Instant::now())),
+        }
+    }
+    
+    /// The info and exchange limits from `config`
+    pub fn pair(config: &RateLimitConfig) -> (Self, Self) {
+        let max_wait = Duration::from_millis(config.max_wait_ms);
+        (
+            Self::new(config.info_per_second, config.info_burst, max_wait),
+            Self::new(config.exchange_per_second, config.exchange_burst, max_wait),
+        )
+    }
+    
+    /// Waits until a request may go out. A request that would have to wait
+    /// longer than the maximum fails with [`Error::RateLimit`] straight away
+    /// and doesn't take a token.
+    pub async fn acquire(&self) -> Result<()> {
+        if self.per_second <= 0.0 {
+            return Ok(());
+        }
+        
+        let wait = {
+            let mut bucket = self.bucket.lock().unwrap();
+            let (tokens, updated) = *bucket;
+            let now = Instant::now();
+            let tokens = (tokens + now.duration_since(updated).as_secs_f64() * self.per_second).min(self.burst) - 1.0;
+            let wait = Duration::from_secs_f64((-tokens).max(0.0) / self.per_second);
+            if wait > self.max_wait {
+                *bucket = (tokens + 1.0, now);
+                return Err(Error::RateLimit { retry_after: Some(wait) });
+            }
+            *bucket = (tokens, now);
+            wait
+        };
+        
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,256 @@
+use crate::error::Result;
+use chrono::{DateTime, Utc};
+use futures_util::{sink, stream, Sink, Stream};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::sync::Arc;
+use std::thread;
+use tokio::io::{AsyncBufReadExt, BufReader, Lines};
+use tokio::sync::watch;
+use tokio::time::Instant;
+use tokio_tungstenite::tungstenite::{self, Message};
+use tracing::{error, info, warn};
+
+/// Frames coming in over a connection, live or replayed
+pub type FrameStream = Pin<Box<dyn Stream<Item = std::result::Result<Message, tungstenite::Error>> + Send>>;
+
+/// Where a connection's outgoing messages go
+pub type FrameSink = Pin<Box<dyn Sink<Message, Error = tungstenite::Error> + Send>>;
+
+/// One websocket frame as it arrived, a line of a recording
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedFrame {
+    pub received_at: DateTime<Utc>,
+    /// The frame exactly as received, whether or not it parsed
+    pub text: String,
+}
+
+enum RecorderMessage {
+    Frame(RecordedFrame),
+    Flush(mpsc::Sender<()>),
+}
+
+/// Appends every frame a websocket client receives to a newline-JSON file,
+/// for replaying with [`ReplayWebSocketClient`]. Like the market recorder,
+/// the file is written on a thread of its own behind a bounded queue, and
+/// frames arriving while it's full are dropped and counted rather than
+/// holding up the stream.
+#[derive(Clone)]
+pub struct FrameRecorder {
+    tx: SyncSender<RecorderMessage>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl FrameRecorder {
+    /// Appends to the file at `path`, creating it and its directory if need be
+    pub fn new(path: impl AsRef<Path>, queue_size: usize) -> Result<Self> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let file = BufWriter::new(OpenOptions::new().create(true).append(true).open(path)?);
+        
+        let (tx, rx) = mpsc::sync_channel(queue_size.max(1));
+        thread::Builder::new()
+            .name("frame-recorder".to_string())
+            .spawn(move || write_frames(file, rx))?;
+        
+        info!("🎥 Recording websocket frames to {}", path.display());
+        Ok(Self {
+            tx,
+            dropped: Arc::new(AtomicU64::new(0)),
+        })
+    }
+    
+    /// Queues `text`, stamped now
+    pub fn record(&self, text: &str) {
+        let frame = RecordedFrame {
+            received_at: Utc::now(),
+            text: text.to_string(),
+        };
+        match self.tx.try_send(RecorderMessage::Frame(frame)) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => {
+                let dropped = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+                if dropped == 1 || dropped.is_multiple_of(1000) {
+                    warn!("Frame recorder queue full, {} frames dropped so far", dropped);
+                }
+            }
+            Err(TrySendError::Disconnected(_)) => {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+    
+    pub fn dropped_count(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+    
+    /// Waits until every frame queued so far is on disk
+    pub async fn flush(&self) {
+        let (done_tx, done_rx) = mpsc::channel();
+        let tx = self.tx.clone();
+        let _ = tokio::task::spawn_blocking(move || {
+            if tx.send(RecorderMessage::Flush(done_tx)).is_ok() {
+                let _ = done_rx.recv();
+            }
+        })
+        .await;
+    }
+}
+
+/// Writes frames as they come, flushing whenever the queue runs dry
+fn write_frames(mut file: BufWriter<File>, rx: mpsc::Receiver<RecorderMessage>) {
+    while let Ok(message) = rx.recv() {
+        let mut next = Some(message);
+        while let Some(message) = next.take() {
+            match message {
+                RecorderMessage::Frame(frame) => {
+                    let written = serde_json::to_writer(&mut file, &frame)
+                        .map_err(std::io::Error::from)
+                        .and_then(|()| file.write_all(b"\n"));
+                    if let Err(e) = written {
+                        error!("Failed to record websocket frame: {}", e);
+                    }
+                }
+                RecorderMessage::Flush(done) => {
+                    if let Err(e) = file.flush() {
+                        error!("Failed to flush websocket recording: {}", e);
+                    }
+                    let _ = done.send(());
+                }
+            }
+            next = rx.try_recv().ok();
+        }
+        if let Err(e) = file.flush() {
+            error!("Failed to flush websocket recording: {}", e);
+        }
+    }
+}
+
+/// Where a replay has got to, kept across connections
+#[derive(Default)]
+struct Playback {
+    opened: bool,
+    /// The rest of the recording, until it has all been read
+    lines: Option<Lines<BufReader<tokio::fs::File>>>,
+    /// Read but not yet played, kept for the next connection if this one
+    /// ends while waiting for it to be due
+    due: Option<RecordedFrame>,
+    /// The first frame's recorded time and when it was played, which every
+    /// later frame is timed against
+    started: Option<(DateTime<Utc>, Instant)>,
+}
+
+/// A recording from [`FrameRecorder`] played back as though it were the
+/// exchange. [`WebSocketClient::replay`](super::WebSocketClient::replay)
+/// connects to it in place of the exchange, so replayed frames are decoded
+/// and passed on exactly as live ones were. Frames come at the pace they
+/// were recorded, divided by `speed`; what the client sends is discarded.
+///
+/// The recording plays once. A connection that drops picks up where the
+/// last one stopped, and once it's all been played connecting fails.
+#[derive(Clone)]
+pub struct ReplayWebSocketClient {
+    path: PathBuf,
+    speed: f64,
+    playback: Arc<tokio::sync::Mutex<Playback>>,
+}
+
+impl ReplayWebSocketClient {
+    /// Plays the recording at `path` at the pace it was recorded
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            speed: 1.0,
+            playback: Arc::default(),
+        }
+    }
+    
+    /// Plays `speed` times faster than recorded; 0 plays every frame as
+    /// soon as it's read
+    pub fn with_speed(mut self, speed: f64) -> Self {
+        self.speed = speed;
+        self
+    }
+    
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+    
+    /// Opens the recording, or carries on with it, as a connection
+    pub async fn connect(&self) -> Result<(FrameSink, FrameStream)> {
+        {
+            let mut playback = self.playback.lock().await;
+            if !playback.opened {
+                let file = tokio::fs::File::open(&self.path).await?;
+                playback.lines = Some(BufReader::new(file).lines());
+                playback.opened = true;
+            }
+            if playback.lines.is_none() && playback.due.is_none() {
+                info!("Replay of {} has finished", self.path.display());
+                return Err(tungstenite::Error::AlreadyClosed.into());
+            }
+        }
+        
+        // Sending a close ends the replay straight away, as the exchange
+        // would close back
+        let (closed, on_close) = watch::channel(false);
+        let discard = sink::unfold(closed, |closed, message: Message| async move {
+            if matches!(message, Message::Close(_)) {
+                closed.send_replace(true);
+            }
+            Ok::<_, tungstenite::Error>(closed)
+        });
+        
+        let speed = self.speed;
+        let frames = stream::unfold((self.playback.clone(), on_close), move |(playback, mut on_close)| async move {
+            let frame = tokio::select! {
+                frame = async { next_frame(&mut *playback.lock().await, speed).await } => frame?,
+                _ = on_close.wait_for(|closed| *closed) => return None,
+            };
+            Some((frame, (playback, on_close)))
+        });
+        Ok((Box::pin(discard), Box::pin(frames)))
+    }
+}
+
+/// The recording's next frame, once it's due; `None` once it has all
+/// been played. Frames carry tungstenite's error to stand in for the
+/// live socket's stream.
+#[allow(clippy::result_large_err)]
+async fn next_frame(playback: &mut Playback, speed: f64) -> Option<std::result::Result<Message, tungstenite::Error>> {
+    while playback.due.is_none() {
+        let line = match playback.lines.as_mut()?.next_line().await {
+            Ok(Some(line)) => line,
+            Ok(None) => {
+                playback.lines = None;
+                return None;
+            }
+            Err(e) => {
+                playback.lines = None;
+                return Some(Err(e.into()));
+            }
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(&line) {
+            Ok(frame) => playback.due = Some(frame),
+            Err(e) => warn!("Skipping unreadable line in websocket recording: {}", e),
+        }
+    }
+    
+    let received_at = playback.due.as_ref()?.received_at;
+    let (first_at, first_played) = *playback.started.get_or_insert((received_at, Instant::now()));
+    if speed > 0.0 {
+        let offset = (received_at - first_at).to_std().unwrap_or_default();
+        tokio::time::sleep_until(first_played + offset.div_f64(speed)).await;
+    }
+    playback.due.take().map(|frame| Ok(Message::Text(frame.text)))
+}
@@ -0,0 +1,181 @@
+use super::types::UsdTransfer;
+use super::wallet;
+use crate::error::{Error, Result};
+use secp256k1::{ecdsa::RecoverableSignature, Message, PublicKey, Secp256k1};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+
+/// Chain id of the EIP-712 domain for actions, on mainnet and testnet alike
+const CHAIN_ID: u64 = 1337;
+
+/// Chain id transfers are signed for, as the exchange's own frontend does
+/// (Arbitrum Sepolia). The exchange takes any, as long as the action names
+/// the one signed for.
+pub const SIGNATURE_CHAIN_ID: &str = "0x66eee";
+
+/// The `signature` field of an exchange request
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Signature {
+    pub r: String,
+    pub s: String,
+    pub v: u8,
+}
+
+/// The hash of `action` the phantom agent carries: keccak256 of the
+/// msgpack-encoded action, the nonce as 8 big-endian bytes, then 0x00 or
+/// 0x01 followed by the vault address
+pub fn action_hash<T: Serialize>(action: &T, vault_address: Option<&str>, nonce: u64) -> Result<[u8; 32]> {
+    let mut data = rmp_serde::to_vec_named(action)
+        .map_err(|e| Error::InvalidInput(format!("Failed to encode action: {}", e)))?;
+    data.extend_from_slice(&nonce.to_be_bytes());
+    match vault_address {
+        Some(vault) => {
+            data.push(1);
+            data.extend_from_slice(&address_bytes(vault)?);
+        }
+        None => data.push(0),
+    }
+    Ok(keccak(&data))
+}
+
+/// Signs `action` with `private_key` as the exchange endpoint expects, for
+/// mainnet or testnet. The exchange authenticates an action by who signed
+/// it: the action hash is wrapped in a "phantom agent", which is signed as
+/// EIP-712 typed data. The exchange rebuilds the hash from the JSON it
+/// receives, so actions must serialize their fields in the order it expects.
+pub fn sign_l1_action<T: Serialize>(
+    private_key: &str,
+    action: &T,
+    vault_address: Option<&str>,
+    nonce: u64,
+    mainnet: bool,
+) -> Result<Signature> {
+    let connection_id = action_hash(action, vault_address, nonce)?;
+    let source = if mainnet { "a" } else { "b" };
+    sign_digest(private_key, &agent_digest(source, &connection_id))
+}
+
+/// Signs a USDC transfer as the wallet itself, as EIP-712 typed data
+/// `HyperliquidTransaction:<kind>`, where `kind` is "UsdSend" or "Withdraw"
+pub fn sign_usd_transfer(private_key: &str, kind: &str, transfer: &UsdTransfer) -> Result<Signature> {
+    sign_digest(private_key, &transfer_digest(kind, transfer)?)
+}
+
+/// Address of the wallet that produced `signature` over `digest`, as the
+/// exchange recovers it
+pub fn recover_signer(digest: &[u8; 32], signature: &Signature) -> Result<String> {
+    let invalid = || Error::InvalidInput("Malformed signature".to_string());
+    let mut compact = [0u8; 64];
+    compact[..32].copy_from_slice(&word(&signature.r).ok_or_else(invalid)?);
+    compact[32..].copy_from_slice(&word(&signature.s).ok_or_else(invalid)?);
+    let recovery_id = secp256k1::ecdsa::RecoveryId::from_i32(signature.v as i32 - 27).map_err(|_| invalid())?;
+    let signature = RecoverableSignature::from_compact(&compact, recovery_id).map_err(|_| invalid())?;
+    
+    let public: PublicKey = Secp256k1::verification_only()
+        .recover_ecdsa(&Message::from_digest(*digest), &signature)
+        .map_err(|_| invalid())?;
+    Ok(wallet::address_from_public_key(&public))
+}
+
+/// The EIP-712 digest of `Agent { source, connectionId }` in the exchange's
+/// domain
+pub fn agent_digest(source: &str, connection_id: &[u8; 32]) -> [u8; 32] {
+    let domain_separator = domain_separator("Exchange", CHAIN_ID);
+    let agent = keccak(
+        &[
+            keccak(b"Agent(string source,bytes32 connectionId)"),
+            keccak(source.as_bytes()),
+            *connection_id,
+        ]
+        .concat(),
+    );
+    keccak(&[&[0x19, 0x01][..], &domain_separator, &agent].concat())
+}
+
+/// The EIP-712 digest of a USDC transfer of `kind` in the domain of the
+/// chain it names
+pub fn transfer_digest(kind: &str, transfer: &UsdTransfer) -> Result<[u8; 32]> {
+    let chain_id = u64::from_str_radix(transfer.signature_chain_id.trim_start_matches("0x"), 16).map_err(|_| {
+        Error::InvalidInput(format!("{} is not a chain id", transfer.signature_chain_id))
+    })?;
+    let domain_separator = domain_separator("HyperliquidSignTransaction", chain_id);
+    let type_hash = keccak(
+        format!(
+            "HyperliquidTransaction:{}(string hyperliquidChain,string destination,string amount,uint64 time)",
+            kind
+        )
+        .as_bytes(),
+    );
+    let message = keccak(
+        &[
+            type_hash,
+            keccak(transfer.hyperliquid_chain.as_bytes()),
+            keccak(transfer.destination.as_bytes()),
+            keccak(transfer.amount.as_bytes()),
+            uint256(transfer.time),
+        ]
+        .concat(),
+    );
+    Ok(keccak(&[&[0x19, 0x01][..], &domain_separator, &message].concat()))
+}
+
+fn domain_separator(name: &str, chain_id: u64) -> [u8; 32] {
+    keccak(
+        &[
+            keccak(b"EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)"),
+            keccak(name.as_bytes()),
+            keccak(b"1"),
+            uint256(chain_id),
+            [0u8; 32],
+        ]
+        .concat(),
+    )
+}
+
+fn sign_digest(private_key: &str, digest: &[u8; 32]) -> Result<Signature> {
+    let secret = wallet::secret_key(private_key)?;
+    let signature = Secp256k1::signing_only().sign_ecdsa_recoverable(&Message::from_digest(*digest), &secret);
+    let (recovery_id, compact) = signature.serialize_compact();
+    Ok(Signature {
+        r: hex_word(&compact[..32]),
+        s: hex_word(&compact[32..]),
+        v: 27 + recovery_id.to_i32() as u8,
+    })
+}
+
+fn keccak(data: &[u8]) -> [u8; 32] {
+    Keccak256::digest(data).into()
+}
+
+fn uint256(value: u64) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[24..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+/// `bytes` as 0x-prefixed hex without leading zeros, as the official SDKs
+/// write `r` and `s`
+fn hex_word(bytes: &[u8]) -> String {
+    let digits = hex::encode(bytes);
+    match digits.trim_start_matches('0') {
+        "" => "0x0".to_string(),
+        trimmed => format!("0x{}", trimmed),
+    }
+}
+
+/// A hex value of up to 32 bytes, left-padded to a full word
+fn word(value: &str) -> Option<[u8; 32]> {
+    let digits = value.trim_start_matches("0x");
+    if digits.len() > 64 {
+        return None;
+    }
+    let bytes = hex::decode(format!("{:0>64}", digits)).ok()?;
+    bytes.try_into().ok()
+}
+
+fn address_bytes(address: &str) -> Result<[u8; 20]> {
+    hex::decode(address.trim_start_matches("0x"))
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| Error::InvalidInput(format!("{} is not an address", address)))
+}
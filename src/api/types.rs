@@ -1,14 +1,15 @@
+use crate::{
+    error::{Error, Result},
+    models::{
+        EquityPoint, FeeSchedule, FundingPayment, FundingRate, MarketTrade, Order, OrderSide, OrderState, OrderStatus, OrderType,
+        Trade,
+    },
+};
+use chrono::{Duration, TimeZone, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct HyperliquidResponse<T> {
-    pub success: bool,
-    pub data: Option<T>,
-    pub error: Option<String>,
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MarketInfo {
     pub name: String,
@@ -20,28 +21,92 @@ pub struct MarketInfo {
     pub max_leverage: Decimal,
 }
 
+// Field names follow the `meta` info response
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct AssetInfo {
     pub name: String,
     pub sz_decimals: u32,
+    #[serde(default)]
     pub wei_decimals: u32,
+    pub max_leverage: u32,
+    #[serde(default)]
     pub only_isolated: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct Meta {
     pub universe: Vec<AssetInfo>,
+    #[serde(default)]
     pub amms: Vec<serde_json::Value>,
+    #[serde(default)]
     pub open_interest: HashMap<String, Decimal>,
 }
 
+impl Meta {
+    pub fn asset(&self, name: &str) -> Option<&AssetInfo> {
+        self.universe.iter().find(|asset| asset.name == name)
+    }
+}
+
+/// A snapshot of one coin's book, from the `l2Book` info request or
+/// websocket channel
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "L2BookWire")]
 pub struct L2Book {
     pub coin: String,
-    pub levels: Vec<[Decimal; 2]>,
+    /// Highest first
+    pub bids: Vec<L2Level>,
+    /// Lowest first
+    pub asks: Vec<L2Level>,
     pub time: u64,
 }
 
+// Field names follow the `l2Book` responses, which pair the sides up
+#[derive(Deserialize)]
+struct L2BookWire {
+    coin: String,
+    /// Bids then asks, best first
+    levels: [Vec<L2Level>; 2],
+    time: u64,
+}
+
+impl From<L2BookWire> for L2Book {
+    fn from(wire: L2BookWire) -> Self {
+        let [bids, asks] = wire.levels;
+        Self {
+            coin: wire.coin,
+            bids,
+            asks,
+            time: wire.time,
+        }
+    }
+}
+
+impl L2Book {
+    pub fn best_bid(&self) -> Option<Decimal> {
+        self.bids.first().map(|level| level.px)
+    }
+    
+    pub fn best_ask(&self) -> Option<Decimal> {
+        self.asks.first().map(|level| level.px)
+    }
+    
+    /// Best ask less best bid, if both sides have orders
+    pub fn spread(&self) -> Option<Decimal> {
+        Some(self.best_ask()? - self.best_bid()?)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct L2Level {
+    pub px: Decimal,
+    pub sz: Decimal,
+    /// Number of orders at this price
+    pub n: u32,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Ticker {
     pub coin: String,
@@ -50,7 +115,78 @@ pub struct Ticker {
     pub time: u64,
 }
 
+// Field names follow the `allMids` websocket channel: every coin's mid,
+// pushed as a whole each time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AllMidsUpdate {
+    pub mids: HashMap<String, Decimal>,
+}
+
+// Field names follow the `post` websocket channel: the response to a
+// request posted with `id`, as `{"type", "payload"}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostUpdate {
+    pub id: u64,
+    pub response: serde_json::Value,
+}
+
+// Field names follow the `notification` websocket channel: a message for
+// a subscribed user, e.g. a liquidation warning
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationUpdate {
+    pub notification: String,
+}
+
+// Field names follow the `trades` websocket channel, which pushes a coin's
+// trades in batches
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WsTrade {
+    pub coin: String,
+    /// The aggressor's side: "B" bought, "A" sold
+    pub side: String,
+    pub px: Decimal,
+    pub sz: Decimal,
+    pub time: u64,
+    #[serde(default)]
+    pub hash: String,
+    #[serde(default)]
+    pub tid: u64,
+}
+
+impl WsTrade {
+    pub fn to_market_trade(&self) -> MarketTrade {
+        MarketTrade {
+            symbol: self.coin.clone(),
+            side: side_from_code(&self.side),
+            price: self.px,
+            size: self.sz,
+            timestamp: datetime_from_millis(self.time),
+        }
+    }
+}
+
+// Field names follow the `userFills` websocket channel. The first message
+// after subscribing is a snapshot of recent fills; the rest are new ones.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserFillsUpdate {
+    pub user: String,
+    #[serde(default)]
+    pub is_snapshot: bool,
+    pub fills: Vec<UserFill>,
+}
+
+// Field names follow the `candle` websocket channel, which adds the coin
+// and interval
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CandleUpdate {
+    pub s: String,
+    pub i: String,
+    #[serde(flatten)]
+    pub candle: Candle,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Candle {
     pub t: u64,
     pub o: Decimal,
@@ -60,11 +196,31 @@ pub struct Candle {
     pub v: Decimal,
 }
 
+/// How long one candle of `interval` spans, for the intervals the exchange
+/// serves
+pub fn interval_duration(interval: &str) -> Result<Duration> {
+    match interval {
+        "1m" => Ok(Duration::minutes(1)),
+        "5m" => Ok(Duration::minutes(5)),
+        "15m" => Ok(Duration::minutes(15)),
+        "1h" => Ok(Duration::hours(1)),
+        "4h" => Ok(Duration::hours(4)),
+        "1d" => Ok(Duration::days(1)),
+        _ => Err(Error::InvalidInput(format!("Unsupported candle interval: {}", interval))),
+    }
+}
+
+// Field names follow the `clearinghouseState` info response
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct UserState {
     pub asset_positions: Vec<AssetPosition>,
-    pub cross_margin_summary: Option<CrossMarginSummary>,
+    #[serde(default)]
+    pub cross_margin_summary: Option<MarginSummary>,
+    #[serde(default)]
     pub margin_summary: Option<MarginSummary>,
+    #[serde(default)]
+    pub cross_maintenance_margin_used: Decimal,
     pub time: u64,
     pub withdrawable: Decimal,
 }
@@ -72,39 +228,40 @@ pub struct UserState {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AssetPosition {
     pub position: Position,
+    /// "oneWay" for perpetuals
+    #[serde(rename = "type")]
     pub type_: String,
-    pub coin: String,
-    pub pnl: Decimal,
-    pub value: Decimal,
-    pub entry_px: Decimal,
-    pub leverage: Decimal,
-    pub sz: Decimal,
-    pub unrealized_pnl: Decimal,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct Position {
     pub coin: String,
     pub entry_px: Decimal,
-    pub leverage: Decimal,
+    pub leverage: Leverage,
     pub liquidation_px: Option<Decimal>,
     pub margin_used: Decimal,
-    pub max_leverage: Decimal,
+    pub max_leverage: u32,
     pub position_value: Decimal,
     pub return_on_equity: Decimal,
+    /// Signed size: positive long, negative short
     pub szi: Decimal,
     pub unrealized_pnl: Decimal,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct CrossMarginSummary {
-    pub account_value: Decimal,
-    pub total_margin_used: Decimal,
-    pub total_ntl_pos: Decimal,
-    pub total_raw_usd: Decimal,
+#[serde(rename_all = "camelCase")]
+pub struct Leverage {
+    /// "cross" or "isolated"
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub value: u32,
+    #[serde(default)]
+    pub raw_usd: Option<Decimal>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct MarginSummary {
     pub account_value: Decimal,
     pub total_margin_used: Decimal,
@@ -112,38 +269,655 @@ pub struct MarginSummary {
     pub total_raw_usd: Decimal,
 }
 
+// Field names follow the second half of the `metaAndAssetCtxs` info response
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct OrderRequest {
-    pub a: u32, // asset_id
-    pub b: bool, // is_buy
-    pub p: Decimal, // price
-    pub s: Decimal, // size
-    pub r: bool, // reduce_only
-    pub t: String, // order_type
-    pub cid: u64, // client_order_id
+#[serde(rename_all = "camelCase")]
+pub struct AssetContext {
+    pub day_ntl_vlm: Decimal,
+    pub funding: Decimal,
+    pub mark_px: Decimal,
+    /// Missing when the book is one-sided
+    #[serde(default)]
+    pub mid_px: Option<Decimal>,
+    pub open_interest: Decimal,
+    pub oracle_px: Decimal,
+    pub prev_day_px: Decimal,
+    /// Missing without a mark premium, e.g. for a one-sided book
+    #[serde(default)]
+    pub premium: Option<Decimal>,
+}
+
+// Field names follow the `fundingHistory` info response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FundingHistoryEntry {
+    pub coin: String,
+    pub funding_rate: Decimal,
+    pub premium: Decimal,
+    pub time: u64,
+}
+
+impl FundingHistoryEntry {
+    pub fn to_funding_rate(&self) -> FundingRate {
+        FundingRate {
+            coin: self.coin.clone(),
+            rate: self.funding_rate,
+            premium: self.premium,
+            time: datetime_from_millis(self.time),
+        }
+    }
+}
+
+// Field names follow the `userFunding` info response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserFunding {
+    pub time: u64,
+    pub hash: String,
+    pub delta: FundingDelta,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct OrderResponse {
+#[serde(rename_all = "camelCase")]
+pub struct FundingDelta {
+    pub coin: String,
+    /// Received, negative when paid
+    pub usdc: Decimal,
+    pub szi: Decimal,
+    pub funding_rate: Decimal,
+}
+
+impl UserFunding {
+    pub fn to_payment(&self) -> FundingPayment {
+        FundingPayment {
+            coin: self.delta.coin.clone(),
+            amount: self.delta.usdc,
+            size: self.delta.szi,
+            rate: self.delta.funding_rate,
+            time: datetime_from_millis(self.time),
+        }
+    }
+}
+
+fn side_from_code(side: &str) -> OrderSide {
+    // "B" is a bid, "A" an ask
+    if side == "B" {
+        OrderSide::Buy
+    } else {
+        OrderSide::Sell
+    }
+}
+
+fn datetime_from_millis(millis: u64) -> chrono::DateTime<Utc> {
+    Utc.timestamp_millis_opt(millis as i64).single().unwrap_or_default()
+}
+
+// Field names follow the `openOrders` info response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenOrder {
+    pub coin: String,
+    pub side: String,
+    pub limit_px: Decimal,
+    pub sz: Decimal,
+    pub oid: u64,
+    pub timestamp: u64,
+    #[serde(default)]
+    pub orig_sz: Option<Decimal>,
+    #[serde(default)]
+    pub cloid: Option<String>,
+    // Only in `frontendOpenOrders` and `orderStatus` replies
+    #[serde(default)]
+    pub reduce_only: bool,
+    /// e.g. "Limit", "Stop Market" or "Take Profit Limit"
+    #[serde(default)]
+    pub order_type: Option<String>,
+    /// "Gtc", "Alo" or "Ioc" for limit orders
+    #[serde(default)]
+    pub tif: Option<String>,
+    #[serde(default)]
+    pub is_trigger: bool,
+    #[serde(default)]
+    pub trigger_px: Option<Decimal>,
+}
+
+impl OpenOrder {
+    /// The order's type as placed, to send it again in a modify. Without
+    /// the frontend fields it's taken for a Gtc limit.
+    pub fn order_type_wire(&self) -> OrderTypeWire {
+        let order_type = self.order_type.as_deref().unwrap_or("Limit");
+        match self.trigger_px {
+            Some(trigger_px) if self.is_trigger => OrderTypeWire::Trigger {
+                is_market: order_type.ends_with("Market"),
+                trigger_px,
+                tpsl: if order_type.starts_with("Take Profit") { "tp" } else { "sl" }.to_string(),
+            },
+            _ => OrderTypeWire::Limit {
+                tif: self.tif.clone().unwrap_or_else(|| "Gtc".to_string()),
+            },
+        }
+    }
+    
+    pub fn to_order(&self) -> Order {
+        let quantity = self.orig_sz.unwrap_or(self.sz);
+        
+        Order {
+            id: self.oid.to_string(),
+            symbol: self.coin.clone(),
+            side: side_from_code(&self.side),
+            order_type: OrderType::Limit,
+            quantity,
+            price: Some(self.limit_px),
+            status: if self.sz < quantity { OrderStatus::PartiallyFilled } else { OrderStatus::Open },
+            created_at: datetime_from_millis(self.timestamp),
+            updated_at: None,
+            filled_quantity: quantity - self.sz,
+            average_price: None,
+            reduce_only: false,
+            trigger_price: None,
+            is_take_profit: false,
+            cloid: self.cloid.clone(),
+        }
+    }
+}
+
+// Field names follow the `orderStatus` info response: `status` is "order"
+// with the order, or "unknownOid"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderStatusReply {
     pub status: String,
-    pub response: Option<serde_json::Value>,
+    #[serde(default)]
+    pub order: Option<OrderStatusUpdate>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct CancelRequest {
+#[serde(rename_all = "camelCase")]
+pub struct OrderStatusUpdate {
+    pub order: OpenOrder,
+    /// "open", "filled", "canceled", "triggered", "rejected", or one of the
+    /// reasons it was cancelled, e.g. "marginCanceled"
+    pub status: String,
+    pub status_timestamp: u64,
+}
+
+impl OrderStatusUpdate {
+    pub fn state(&self) -> OrderState {
+        match self.status.as_str() {
+            "open" | "triggered" => OrderState::Resting,
+            "filled" => OrderState::Filled,
+            "rejected" => OrderState::Rejected,
+            _ => OrderState::Cancelled,
+        }
+    }
+    
+    /// Size filled so far; all of it once the order has filled
+    pub fn filled_size(&self) -> Decimal {
+        match self.state() {
+            OrderState::Filled => self.order.orig_sz.unwrap_or(self.order.sz),
+            _ => self.order.orig_sz.map_or(Decimal::ZERO, |orig_sz| orig_sz - self.order.sz),
+        }
+    }
+    
+    pub fn to_order(&self) -> Order {
+        let order = self.order.to_order();
+        let status = match self.state() {
+            OrderState::Resting => order.status,
+            OrderState::Filled => OrderStatus::Filled,
+            OrderState::Rejected => OrderStatus::Rejected,
+            OrderState::Cancelled => OrderStatus::Cancelled,
+        };
+        Order {
+            status,
+            filled_quantity: self.filled_size(),
+            updated_at: Some(datetime_from_millis(self.status_timestamp)),
+            ..order
+        }
+    }
+}
+
+// Field names follow the `userFills` info response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserFill {
+    pub coin: String,
+    pub px: Decimal,
+    pub sz: Decimal,
+    pub side: String,
+    pub time: u64,
+    pub oid: u64,
+    #[serde(default)]
+    pub tid: u64,
+    #[serde(default)]
+    pub cloid: Option<String>,
+    #[serde(default)]
+    pub fee: Decimal,
+    #[serde(default)]
+    pub closed_pnl: Decimal,
+    /// The transaction's hash, shared by every fill it made
+    #[serde(default)]
+    pub hash: String,
+}
+
+impl UserFill {
+    /// What tells this fill apart when pages overlap
+    pub fn key(&self) -> (String, u64) {
+        (self.hash.clone(), self.tid)
+    }
+    
+    /// When the exchange made the fill
+    pub fn timestamp(&self) -> chrono::DateTime<Utc> {
+        datetime_from_millis(self.time)
+    }
+    
+    pub fn to_trade(&self) -> Trade {
+        Trade {
+            id: if self.tid == 0 { self.oid.to_string() } else { self.tid.to_string() },
+            order_id: self.oid.to_string(),
+            cloid: self.cloid.clone(),
+            symbol: self.coin.clone(),
+            side: side_from_code(&self.side),
+            quantity: self.sz,
+            price: self.px,
+            fee: self.fee,
+            realized_pnl: self.closed_pnl,
+            timestamp: self.timestamp(),
+        }
+    }
+    
+    /// `trade` as a fill, for clients that only report trades. Without a
+    /// hash, the trade's id stands in for one.
+    pub fn from_trade(trade: &Trade) -> Self {
+        Self {
+            coin: trade.symbol.clone(),
+            px: trade.price,
+            sz: trade.quantity,
+            side: match trade.side {
+                OrderSide::Buy => "B",
+                OrderSide::Sell => "A",
+            }
+            .to_string(),
+            time: trade.timestamp.timestamp_millis().max(0) as u64,
+            oid: trade.order_id.parse().unwrap_or_default(),
+            tid: trade.id.parse().unwrap_or_default(),
+            cloid: trade.cloid.clone(),
+            fee: trade.fee,
+            closed_pnl: trade.realized_pnl,
+            hash: trade.id.clone(),
+        }
+    }
+}
+
+// Field names follow one period of the `portfolio` info response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortfolioPeriod {
+    /// (epoch millis, account value) samples, oldest first
+    pub account_value_history: Vec<(u64, Decimal)>,
+    /// (epoch millis, PnL since the period began) samples
+    pub pnl_history: Vec<(u64, Decimal)>,
+    /// Volume traded over the period
+    #[serde(default)]
+    pub vlm: Decimal,
+}
+
+impl PortfolioPeriod {
+    /// Each account value sample with the PnL sampled at the same time,
+    /// oldest first
+    pub fn to_equity_points(&self) -> Vec<EquityPoint> {
+        let pnl: HashMap<u64, Decimal> = self.pnl_history.iter().copied().collect();
+        let mut points: Vec<EquityPoint> = self
+            .account_value_history
+            .iter()
+            .map(|&(time, account_value)| EquityPoint {
+                time: datetime_from_millis(time),
+                account_value,
+                pnl: pnl.get(&time).copied().unwrap_or_default(),
+            })
+            .collect();
+        points.sort_by_key(|point| point.time);
+        points
+    }
+}
+
+// Field names follow the `userFees` info response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserFees {
+    /// Taker rate after volume tiers and discounts
+    pub user_cross_rate: Decimal,
+    /// Maker rate after volume tiers and discounts; negative for a rebate
+    pub user_add_rate: Decimal,
+    #[serde(default)]
+    pub active_referral_discount: Decimal,
+}
+
+impl UserFees {
+    pub fn to_schedule(&self) -> FeeSchedule {
+        FeeSchedule {
+            maker_rate: self.user_add_rate,
+            taker_rate: self.user_cross_rate,
+        }
+    }
+}
+
+/// A request to the `info` endpoint, serialized as the body the exchange
+/// expects: `type` first, then the request's own fields
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum InfoRequest {
+    Meta,
+    MetaAndAssetCtxs,
+    AllMids,
+    ClearinghouseState { user: String },
+    OpenOrders { user: String },
+    /// Open orders with their type, time in force and reduce-only flag
+    FrontendOpenOrders { user: String },
+    OrderStatus { user: String, oid: OrderRef },
+    UserFills { user: String },
+    #[serde(rename_all = "camelCase")]
+    UserFillsByTime {
+        user: String,
+        /// Epoch millis
+        start_time: u64,
+        end_time: Option<u64>,
+    },
+    UserTwapSliceFills { user: String },
+    UserFees { user: String },
+    Portfolio { user: String },
+    #[serde(rename_all = "camelCase")]
+    UserFunding {
+        user: String,
+        start_time: u64,
+        end_time: Option<u64>,
+    },
+    #[serde(rename_all = "camelCase")]
+    FundingHistory {
+        coin: String,
+        start_time: u64,
+        end_time: Option<u64>,
+    },
+    L2Book { coin: String },
+    CandleSnapshot { req: CandleRequest },
+}
+
+impl InfoRequest {
+    /// The request's `type`, e.g. "clearinghouseState"
+    pub fn name(&self) -> &'static str {
+        match self {
+            InfoRequest::Meta => "meta",
+            InfoRequest::MetaAndAssetCtxs => "metaAndAssetCtxs",
+            InfoRequest::AllMids => "allMids",
+            InfoRequest::ClearinghouseState { .. } => "clearinghouseState",
+            InfoRequest::OpenOrders { .. } => "openOrders",
+            InfoRequest::FrontendOpenOrders { .. } => "frontendOpenOrders",
+            InfoRequest::OrderStatus { .. } => "orderStatus",
+            InfoRequest::UserFills { .. } => "userFills",
+            InfoRequest::UserFillsByTime { .. } => "userFillsByTime",
+            InfoRequest::UserTwapSliceFills { .. } => "userTwapSliceFills",
+            InfoRequest::UserFees { .. } => "userFees",
+            InfoRequest::Portfolio { .. } => "portfolio",
+            InfoRequest::UserFunding { .. } => "userFunding",
+            InfoRequest::FundingHistory { .. } => "fundingHistory",
+            InfoRequest::L2Book { .. } => "l2Book",
+            InfoRequest::CandleSnapshot { .. } => "candleSnapshot",
+        }
+    }
+}
+
+/// An order as `orderStatus` takes it: by exchange order id or by cloid
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum OrderRef {
+    Oid(u64),
+    Cloid(String),
+}
+
+// Field names follow the `req` of the `candleSnapshot` info request
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CandleRequest {
     pub coin: String,
-    pub oid: u64, // order_id
+    /// One of 1m, 5m, 15m, 1h, 4h or 1d
+    pub interval: String,
+    /// Epoch millis
+    pub start_time: u64,
+    pub end_time: u64,
+}
+
+/// An exchange action. Actions are signed over their msgpack encoding,
+/// which keeps field order, so fields are declared in the order the
+/// exchange hashes them: `type` first, then as below.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Action {
+    Order { orders: Vec<OrderRequest>, grouping: String },
+    Cancel { cancels: Vec<CancelRequest> },
+    CancelByCloid { cancels: Vec<CancelByCloidRequest> },
+    Modify(ModifyRequest),
+    TwapOrder { twap: TwapRequest },
+    TwapCancel {
+        /// Asset index in the `meta` universe
+        a: u32,
+        /// TWAP id
+        t: u64,
+    },
+    UpdateLeverage {
+        /// Asset index in the `meta` universe
+        asset: u32,
+        #[serde(rename = "isCross")]
+        is_cross: bool,
+        leverage: u32,
+    },
+    /// Sends USDC to another account on the exchange
+    UsdSend(UsdTransfer),
+    /// Withdraws USDC to an address on Arbitrum
+    Withdraw3(UsdTransfer),
+    /// Cancels every open order at `time` (epoch millis), or clears the
+    /// schedule when it's left out
+    ScheduleCancel {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        time: Option<u64>,
+    },
+}
+
+impl Action {
+    /// The action's `type`, e.g. "order"
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Order { .. } => "order",
+            Action::Cancel { .. } => "cancel",
+            Action::CancelByCloid { .. } => "cancelByCloid",
+            Action::Modify(_) => "modify",
+            Action::TwapOrder { .. } => "twapOrder",
+            Action::TwapCancel { .. } => "twapCancel",
+            Action::UpdateLeverage { .. } => "updateLeverage",
+            Action::UsdSend(_) => "usdSend",
+            Action::Withdraw3(_) => "withdraw3",
+            Action::ScheduleCancel { .. } => "scheduleCancel",
+        }
+    }
+}
+
+/// Fields of the `usdSend` and `withdraw3` actions. Unlike other actions
+/// they move the wallet's own funds, so the wallet signs their fields as
+/// EIP-712 typed data rather than a phantom agent.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsdTransfer {
+    /// Chain id of the signature's EIP-712 domain, in hex
+    pub signature_chain_id: String,
+    /// "Mainnet" or "Testnet"
+    pub hyperliquid_chain: String,
+    pub destination: String,
+    /// USDC, without trailing zeros
+    pub amount: String,
+    /// Epoch millis, the same as the request's nonce
+    pub time: u64,
+}
+
+// Field names follow the `order` exchange action
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderRequest {
+    /// Asset index in the `meta` universe
+    pub a: u32,
+    /// Is buy
+    pub b: bool,
+    #[serde(serialize_with = "wire_decimal")]
+    pub p: Decimal,
+    #[serde(serialize_with = "wire_decimal")]
+    pub s: Decimal,
+    /// Reduce only
+    pub r: bool,
+    pub t: OrderTypeWire,
+    /// Client order id
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub c: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OrderTypeWire {
+    Limit { tif: String },
+    /// Rests unseen until the mark price crosses `trigger_px`, then goes out
+    /// as a market order, or as a limit at the order's price
+    Trigger {
+        #[serde(rename = "isMarket")]
+        is_market: bool,
+        #[serde(rename = "triggerPx", serialize_with = "wire_decimal")]
+        trigger_px: Decimal,
+        /// "tp" to take profit, "sl" to stop a loss
+        tpsl: String,
+    },
 }
 
+// Field names follow the `twapOrder` exchange action
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct CancelResponse {
+pub struct TwapRequest {
+    /// Asset index in the `meta` universe
+    pub a: u32,
+    /// Is buy
+    pub b: bool,
+    #[serde(serialize_with = "wire_decimal")]
+    pub s: Decimal,
+    /// Reduce only
+    pub r: bool,
+    /// Minutes to spread the slices over
+    pub m: u32,
+    /// Randomize the slices' timing
+    pub t: bool,
+}
+
+// Field names follow the `cancel` exchange action
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CancelRequest {
+    /// Asset index in the `meta` universe
+    pub a: u32,
+    /// Order id
+    pub o: u64,
+}
+
+// Field names follow the `cancelByCloid` exchange action
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CancelByCloidRequest {
+    /// Asset index in the `meta` universe
+    pub asset: u32,
+    pub cloid: String,
+}
+
+// Field names follow the `modify` exchange action
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModifyRequest {
+    /// Id of the resting order to change
+    pub oid: u64,
+    /// The order as it should rest from now on
+    pub order: OrderRequest,
+}
+
+/// Prices and sizes go out as strings without trailing zeros, which is how
+/// the exchange re-encodes them when it checks the signature
+fn wire_decimal<S: serde::Serializer>(value: &Decimal, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+    serializer.serialize_str(&value.normalize().to_string())
+}
+
+/// Reply to an exchange action: `response` holds the result when `status`
+/// is "ok" and the error message otherwise
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExchangeResponse {
     pub status: String,
-    pub response: Option<serde_json::Value>,
+    pub response: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExchangeResult<S> {
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub data: Statuses<S>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Statuses<S> {
+    pub statuses: Vec<S>,
+}
+
+/// What became of one order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OrderStatusEntry {
+    Resting { oid: u64 },
+    Filled {
+        oid: u64,
+        #[serde(rename = "totalSz")]
+        total_sz: Decimal,
+        #[serde(rename = "avgPx")]
+        avg_px: Decimal,
+    },
+    Error(String),
+}
+
+/// What became of one cancel: "success" or an error
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum CancelStatusWire {
+    Success(String),
+    Error { error: String },
+}
+
+/// Reply to a TWAP action, which reports a single status
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TwapResult<S> {
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub data: TwapData<S>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TwapData<S> {
+    pub status: S,
+}
+
+/// What became of a TWAP order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TwapStatusWire {
+    Running {
+        #[serde(rename = "twapId")]
+        twap_id: u64,
+    },
+    Error(String),
+}
+
+// Field names follow the `userTwapSliceFills` info response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TwapSliceFill {
+    pub fill: UserFill,
+    pub twap_id: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WebSocketMessage {
     pub channel: String,
+    /// Absent on `pong`
+    #[serde(default)]
     pub data: serde_json::Value,
 }
 
@@ -0,0 +1,33 @@
+use crate::error::{Error, Result};
+use secp256k1::{PublicKey, Secp256k1, SecretKey};
+use sha3::{Digest, Keccak256};
+
+/// The secp256k1 key in `private_key` (hex, with or without 0x)
+pub fn secret_key(private_key: &str) -> Result<SecretKey> {
+    let bytes = hex::decode(private_key.trim().trim_start_matches("0x"))
+        .map_err(|_| Error::InvalidInput("Private key is not valid hex".to_string()))?;
+    SecretKey::from_slice(&bytes)
+        .map_err(|_| Error::InvalidInput("Private key is not a valid secp256k1 key".to_string()))
+}
+
+/// Lowercase 0x-prefixed address of the wallet that owns `private_key`
+/// (hex, with or without 0x)
+pub fn address_from_private_key(private_key: &str) -> Result<String> {
+    let secret = secret_key(private_key)?;
+    let public = PublicKey::from_secret_key(&Secp256k1::signing_only(), &secret);
+    Ok(address_from_public_key(&public))
+}
+
+/// Whether `value` is an address: 0x and 20 bytes of hex
+pub fn is_address(value: &str) -> bool {
+    value
+        .strip_prefix("0x")
+        .is_some_and(|hex| hex.len() == 40 && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// Lowercase 0x-prefixed address of `public`: the last 20 bytes of the
+/// keccak hash of the uncompressed key, without its 0x04 prefix byte
+pub fn address_from_public_key(public: &PublicKey) -> String {
+    let hash = Keccak256::digest(&public.serialize_uncompressed()[1..]);
+    format!("0x{}", hex::encode(&hash[12..]))
+}
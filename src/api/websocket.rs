@@ -1,46 +1,743 @@
-use crate::error::{Error, Result};
+use crate::{
+    error::{Error, Result},
+    market_stream::MarketEvent,
+    price_cache::PriceCache,
+};
+use chrono::{DateTime, TimeZone, Utc};
 use futures_util::{SinkExt, StreamExt};
-use serde_json::json;
-use std::sync::Arc;
-use tokio::sync::mpsc;
-use tokio_tungstenite::{connect_async, tungstenite::Message};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{mpsc, oneshot, Notify};
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use tokio_tungstenite::{connect_async, tungstenite::{self, Message}};
 use tracing::{debug, error, info, warn};
 
-use super::types::*;
+use super::cache::MarketDataCache;
+use super::frames::{FrameOutcome, FrameParser, ParseErrorCounts, WsEvent};
+use super::recording::{FrameRecorder, FrameSink, FrameStream, ReplayWebSocketClient};
+use super::types::{OrderStatusUpdate, UserFill, UserFillsUpdate, WsTrade};
+
+/// Parse errors a channel may have in a minute before it's resubscribed
+const DEFAULT_MAX_PARSE_ERRORS_PER_MINUTE: u32 = 10;
+
+/// Decoded messages held for `next_message`, and for each `events`
+/// receiver, unless configured otherwise
+const DEFAULT_EVENT_BUFFER: usize = 1024;
+
+/// Outgoing messages queued for the write task before sending refuses more,
+/// unless configured otherwise
+const DEFAULT_WRITE_BUFFER: usize = 256;
+
+/// Where outgoing messages refused for a full write buffer are counted
+const OUTGOING: &str = "outgoing";
+
+/// Fills remembered to spot a reconnect's snapshot replaying them
+const SEEN_FILLS: usize = 10_000;
+
+/// How often a connection is pinged, and how long it may go without
+/// receiving anything before it's dropped, unless configured otherwise
+const DEFAULT_PING_INTERVAL: Duration = Duration::from_secs(20);
+const DEFAULT_STALE_AFTER: Duration = Duration::from_secs(60);
+
+/// How long a posted action waits for its response, unless configured
+/// otherwise
+const DEFAULT_POST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long `disconnect` waits for the connection's tasks to wind down
+/// before aborting them
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
 
 pub struct WebSocketClient {
     ws_url: String,
-    sender: Option<mpsc::UnboundedSender<Message>>,
-    receiver: Option<mpsc::UnboundedReceiver<Message>>,
+    prices: Option<PriceCache>,
+    /// REST market data snapshot the streamed mids supersede
+    market_data: Option<Arc<MarketDataCache>>,
+    max_parse_errors_per_minute: u32,
+    parse_errors: ParseErrorCounts,
+    ping_interval: Duration,
+    stale_after: Duration,
+    event_buffer: usize,
+    write_buffer: usize,
+    /// Every `events` receiver still held, fed over every connection
+    subscribers: Arc<Subscribers>,
+    dropped: DroppedCounts,
+    stats: Arc<StreamStats>,
+    posts: PostChannel,
+    seen_fills: Arc<Mutex<SeenFills>>,
+    /// Whose channels `subscribe_user_channels` streams
+    account: Option<String>,
+    /// Played back instead of connecting to `ws_url`
+    replay: Option<ReplayWebSocketClient>,
+    recorder: Option<FrameRecorder>,
+}
+
+/// How the client's connections have fared, kept up to date by their read
+/// tasks
+#[derive(Default)]
+struct StreamStats {
+    /// The open connection's number, or 0 while there's none
+    open: AtomicU64,
+    /// Connections opened so far, numbering them from 1
+    connections: AtomicU64,
+    /// When the open connection was made, in milliseconds since the epoch
+    connected_ms: AtomicI64,
+    messages: AtomicU64,
+    /// Milliseconds since the epoch, or 0 before the first message
+    last_message_ms: AtomicI64,
+}
+
+/// Marks its connection closed once the read task ends, however it ends,
+/// unless a newer one has opened since, and fails the actions posted on it
+/// still waiting for a response
+struct OpenConnection {
+    stats: Arc<StreamStats>,
+    posts: PostChannel,
+    number: u64,
+}
+
+impl Drop for OpenConnection {
+    fn drop(&mut self) {
+        let _ = self.stats.open.compare_exchange(self.number, 0, Ordering::Relaxed, Ordering::Relaxed);
+        self.posts.close(self.number);
+    }
+}
+
+/// Sends signed exchange actions over a [`WebSocketClient`]'s open
+/// connection instead of HTTP, saving a round trip per order. Each post
+/// gets an id its response carries back, so posts in flight at once each
+/// get their own. Cheap to clone; clones post over the same connections.
+#[derive(Clone)]
+pub struct PostChannel {
+    posts: Arc<Posts>,
+    timeout: Duration,
+}
+
+#[derive(Default)]
+struct Posts {
+    /// The open connection's number and outgoing messages
+    connection: Mutex<Option<(u64, mpsc::Sender<Message>)>>,
+    /// Posts awaiting a response, by id, with the connection each went out on
+    pending: Mutex<HashMap<u64, (u64, oneshot::Sender<Value>)>>,
+    next_id: AtomicU64,
+}
+
+impl PostChannel {
+    fn new() -> Self {
+        Self {
+            posts: Arc::default(),
+            timeout: DEFAULT_POST_TIMEOUT,
+        }
+    }
+    
+    /// Whether there's a connection to post on
+    pub fn is_open(&self) -> bool {
+        self.posts.connection.lock().unwrap().as_ref().is_some_and(|(_, sender)| !sender.is_closed())
+    }
+    
+    /// Posts `action`, a signed exchange request body, and waits for the
+    /// exchange's reply to it: the same `{"status", "response"}` an HTTP
+    /// request gets. Fails without sending anything when no connection is
+    /// open; once sent, a reply that doesn't come within the timeout, or a
+    /// connection that closes first, fails it without saying whether the
+    /// action took effect.
+    pub async fn post_action(&self, action: Value) -> Result<Value> {
+        let id = self.posts.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let (reply, response) = oneshot::channel();
+        {
+            let connection = self.posts.connection.lock().unwrap();
+            let Some((number, sender)) = connection.as_ref() else {
+                return Err(Error::from(tungstenite::Error::ConnectionClosed));
+            };
+            let request = json!({
+                "method": "post",
+                "id": id,
+                "request": { "type": "action", "payload": action }
+            });
+            sender.try_send(Message::Text(request.to_string())).map_err(|e| match e {
+                mpsc::error::TrySendError::Full(message) => Error::from(tungstenite::Error::WriteBufferFull(message)),
+                mpsc::error::TrySendError::Closed(_) => Error::from(tungstenite::Error::ConnectionClosed),
+            })?;
+            self.posts.pending.lock().unwrap().insert(id, (*number, reply));
+        }
+        
+        let response = match tokio::time::timeout(self.timeout, response).await {
+            Ok(Ok(response)) => response,
+            Ok(Err(_)) => return Err(Error::from(tungstenite::Error::AlreadyClosed)),
+            Err(_) => {
+                self.posts.pending.lock().unwrap().remove(&id);
+                return Err(Error::Timeout(format!("No response to post {} within {}s", id, self.timeout.as_secs())));
+            }
+        };
+        match response["type"].as_str() {
+            Some("action") => Ok(response["payload"].clone()),
+            Some("error") => Err(Error::Api(match &response["payload"] {
+                Value::String(message) => message.clone(),
+                other => other.to_string(),
+            })),
+            _ => Err(Error::Api(format!("Unexpected post response: {}", response))),
+        }
+    }
+    
+    /// Hands `response` to the post with `id`, if it's still waiting
+    fn respond(&self, id: u64, response: Value) {
+        match self.posts.pending.lock().unwrap().remove(&id) {
+            Some((_, reply)) => {
+                let _ = reply.send(response);
+            }
+            None => debug!("Response to post {} arrived after it was given up on", id),
+        }
+    }
+    
+    /// Posts go out on connection `number` from now on
+    fn open(&self, number: u64, sender: mpsc::Sender<Message>) {
+        *self.posts.connection.lock().unwrap() = Some((number, sender));
+    }
+    
+    /// Connection `number` has ended: nothing more is posted on it, and
+    /// posts awaiting its responses fail
+    fn close(&self, number: u64) {
+        let mut connection = self.posts.connection.lock().unwrap();
+        if connection.as_ref().is_some_and(|(open, _)| *open == number) {
+            *connection = None;
+        }
+        self.posts.pending.lock().unwrap().retain(|_, (posted_on, _)| *posted_on != number);
+    }
+}
+
+/// Fills already passed on, shared between a client's connections
+#[derive(Default)]
+struct SeenFills {
+    keys: HashSet<(String, u64)>,
+    /// Oldest first, to forget the oldest once there are too many
+    order: VecDeque<(String, u64)>,
+}
+
+impl SeenFills {
+    /// `update`'s fills not passed on before, each as an event of its own
+    fn unseen(&mut self, update: UserFillsUpdate) -> Vec<WsEvent> {
+        let user = update.user;
+        update
+            .fills
+            .into_iter()
+            .filter(|fill| self.insert(fill.key()))
+            .map(|fill| WsEvent::Fill { user: user.clone(), fill })
+            .collect()
+    }
+    
+    fn insert(&mut self, key: (String, u64)) -> bool {
+        if !self.keys.insert(key.clone()) {
+            return false;
+        }
+        self.order.push_back(key);
+        if self.order.len() > SEEN_FILLS {
+            if let Some(oldest) = self.order.pop_front() {
+                self.keys.remove(&oldest);
+            }
+        }
+        true
+    }
+}
+
+/// Messages dropped for consumers that fell behind, by channel, shared
+/// between a client's buffers so the counts survive reconnects
+type DroppedCounts = Arc<Mutex<BTreeMap<String, u64>>>;
+
+/// One consumer's buffer of decoded messages, never holding more than its
+/// capacity however far behind the consumer falls.
+///
+/// Once it's full, room is made by dropping the oldest market data held
+/// (tickers, books, mids, trades, candles and the like): the next update
+/// supersedes it, so the consumer is told with `RecvError::Lagged` how many
+/// it missed and carries on. Fills and order updates are never dropped to
+/// make room. With nothing else left to drop, new market data is dropped
+/// instead, and a new fill or order update waits for the consumer to make
+/// room, holding up the read task (and with it every other consumer) rather
+/// than losing it; one that stops reading altogether eventually gets the
+/// connection dropped as stale. The handle's own buffer, which nothing may be
+/// reading, drops the oldest message of any kind instead. Every drop is
+/// counted against its channel.
+struct EventQueue {
+    capacity: usize,
+    /// Whether fills and order updates wait for room rather than drop
+    must_deliver: bool,
+    state: Mutex<QueueState>,
+    readable: Notify,
+    writable: Notify,
+    dropped: DroppedCounts,
+}
+
+#[derive(Default)]
+struct QueueState {
+    events: VecDeque<WsEvent>,
+    /// Dropped since the consumer was last told
+    missed: u64,
+    closed: bool,
+}
+
+impl EventQueue {
+    fn new(capacity: usize, must_deliver: bool, dropped: DroppedCounts) -> Self {
+        Self {
+            capacity,
+            must_deliver,
+            state: Mutex::new(QueueState::default()),
+            readable: Notify::new(),
+            writable: Notify::new(),
+            dropped,
+        }
+    }
+    
+    async fn push(&self, event: WsEvent) {
+        loop {
+            {
+                let mut state = self.state.lock().unwrap();
+                if state.closed {
+                    return;
+                }
+                if state.events.len() >= self.capacity {
+                    let room = match state.events.iter().position(|held| !held.is_account_event()) {
+                        Some(oldest) => state.events.remove(oldest),
+                        None if !event.is_account_event() => {
+                            self.drop_one(&mut state, &event);
+                            return;
+                        }
+                        None if !self.must_deliver => state.events.pop_front(),
+                        None => None,
+                    };
+                    if let Some(dropped) = room {
+                        self.drop_one(&mut state, &dropped);
+                    }
+                }
+                if state.events.len() < self.capacity {
+                    state.events.push_back(event);
+                    self.readable.notify_one();
+                    return;
+                }
+            }
+            self.writable.notified().await;
+        }
+    }
+    
+    fn drop_one(&self, state: &mut QueueState, event: &WsEvent) {
+        state.missed += 1;
+        *self.dropped.lock().unwrap().entry(event.channel().to_string()).or_default() += 1;
+    }
+    
+    /// The oldest message held, waiting for one if there's none. A consumer
+    /// that missed some is told so first; `Closed` once the stream has ended
+    /// and everything held has been read.
+    async fn recv(&self) -> std::result::Result<WsEvent, RecvError> {
+        loop {
+            {
+                let mut state = self.state.lock().unwrap();
+                if state.missed > 0 {
+                    return Err(RecvError::Lagged(std::mem::take(&mut state.missed)));
+                }
+                if let Some(event) = state.events.pop_front() {
+                    self.writable.notify_one();
+                    return Ok(event);
+                }
+                if state.closed {
+                    return Err(RecvError::Closed);
+                }
+            }
+            self.readable.notified().await;
+        }
+    }
+    
+    fn len(&self) -> usize {
+        self.state.lock().unwrap().events.len()
+    }
+    
+    /// Ends the stream: what's held can still be read, nothing more is added
+    fn close(&self) {
+        self.state.lock().unwrap().closed = true;
+        self.readable.notify_one();
+        self.writable.notify_one();
+    }
+}
+
+/// Closes its queue once dropped, so the reader learns the stream has ended
+/// however the task pushing into it stops
+struct QueueWriter(Arc<EventQueue>);
+
+impl Drop for QueueWriter {
+    fn drop(&mut self) {
+        self.0.close();
+    }
+}
+
+/// The queues of every `events` receiver still held. Closes them once the
+/// client and all its connections are gone.
+#[derive(Default)]
+struct Subscribers {
+    queues: Mutex<Vec<Weak<EventQueue>>>,
+}
+
+impl Subscribers {
+    fn subscribe(&self, capacity: usize, dropped: DroppedCounts) -> EventReceiver {
+        let queue = Arc::new(EventQueue::new(capacity, true, dropped));
+        self.queues.lock().unwrap().push(Arc::downgrade(&queue));
+        EventReceiver { queue }
+    }
+    
+    async fn push(&self, event: &WsEvent) {
+        let queues: Vec<Arc<EventQueue>> = {
+            let mut queues = self.queues.lock().unwrap();
+            queues.retain(|queue| queue.strong_count() > 0);
+            queues.iter().filter_map(Weak::upgrade).collect()
+        };
+        for queue in queues {
+            queue.push(event.clone()).await;
+        }
+    }
+}
+
+impl Drop for Subscribers {
+    fn drop(&mut self) {
+        for queue in self.queues.lock().unwrap().iter().filter_map(Weak::upgrade) {
+            queue.close();
+        }
+    }
+}
+
+/// Every message a [`WebSocketClient`] decodes, from when it was made, as
+/// handed out by [`WebSocketClient::events`]
+pub struct EventReceiver {
+    queue: Arc<EventQueue>,
+}
+
+impl EventReceiver {
+    /// The next message, waiting for one if none is buffered. Like a
+    /// broadcast receiver's: `Lagged` with how many were dropped after
+    /// falling behind, `Closed` once the client is gone.
+    pub async fn recv(&mut self) -> std::result::Result<WsEvent, RecvError> {
+        self.queue.recv().await
+    }
+    
+    /// Messages buffered and not yet read, never more than the capacity
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Sends commands to a connected [`WebSocketClient`] and reads what it
+/// receives. Cheap to clone, so subscribing and disconnecting never wait on
+/// a lock; clones share one stream of messages.
+#[derive(Clone)]
+pub struct WebSocketHandle {
+    sender: mpsc::Sender<Message>,
+    /// Messages decoded by the read task, oldest first
+    events: Arc<EventQueue>,
+    dropped: DroppedCounts,
+    health: Arc<ConnectionHealth>,
+    account: Option<String>,
+    /// The read and write tasks, taken by the first `disconnect`
+    tasks: Arc<Mutex<Vec<JoinHandle<()>>>>,
+}
+
+/// Kept up to date by the read task
+struct ConnectionHealth {
+    /// Milliseconds since the epoch, or 0 before the first message
+    last_message_ms: AtomicI64,
+    open: AtomicBool,
+    /// What each subscribe request asked for, to resubscribe a channel
+    subscriptions: Mutex<Vec<Value>>,
+}
+
+impl ConnectionHealth {
+    fn last_message_at(&self) -> Option<DateTime<Utc>> {
+        match self.last_message_ms.load(Ordering::Relaxed) {
+            0 => None,
+            ms => Utc.timestamp_millis_opt(ms).single(),
+        }
+    }
 }
 
 impl WebSocketClient {
     pub fn new(ws_url: String) -> Self {
         Self {
             ws_url,
-            sender: None,
-            receiver: None,
+            prices: None,
+            market_data: None,
+            max_parse_errors_per_minute: DEFAULT_MAX_PARSE_ERRORS_PER_MINUTE,
+            parse_errors: ParseErrorCounts::default(),
+            ping_interval: DEFAULT_PING_INTERVAL,
+            stale_after: DEFAULT_STALE_AFTER,
+            event_buffer: DEFAULT_EVENT_BUFFER,
+            write_buffer: DEFAULT_WRITE_BUFFER,
+            subscribers: Arc::default(),
+            dropped: DroppedCounts::default(),
+            stats: Arc::default(),
+            posts: PostChannel::new(),
+            seen_fills: Arc::default(),
+            account: None,
+            replay: None,
+            recorder: None,
         }
     }
     
-    pub async fn connect(&mut self) -> Result<()> {
-        info!("Connecting to WebSocket: {}", self.ws_url);
-        
-        let (ws_stream, _) = connect_async(&self.ws_url).await?;
-        let (mut write, read) = ws_stream.split();
+    /// A client whose connections play `replay` back rather than reaching
+    /// the exchange; see [`ReplayWebSocketClient`]. Nothing can be posted
+    /// over them.
+    pub fn replay(replay: ReplayWebSocketClient) -> Self {
+        Self {
+            replay: Some(replay.clone()),
+            ..Self::new(replay.path().display().to_string())
+        }
+    }
+    
+    /// Streams `address`'s fills, order updates and notifications when its
+    /// connections' `subscribe_user_channels` is called. For an API wallet
+    /// this is the account it trades for, not the wallet's own address.
+    pub fn with_account(mut self, address: impl Into<String>) -> Self {
+        self.account = Some(address.into().to_lowercase());
+        self
+    }
+    
+    /// The account whose channels its connections stream, if it has one
+    pub fn account(&self) -> Option<&str> {
+        self.account.as_deref()
+    }
+    
+    /// Appends every frame received to `recorder`'s file, for replaying
+    pub fn with_recorder(mut self, recorder: FrameRecorder) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+    
+    /// Streams ticker updates into `prices`
+    pub fn with_price_cache(mut self, prices: PriceCache) -> Self {
+        self.prices = Some(prices);
+        self
+    }
+    
+    /// Pushes streamed prices into `cache`, so REST lookups don't serve
+    /// older ones
+    pub fn with_market_data_cache(mut self, cache: Arc<MarketDataCache>) -> Self {
+        self.market_data = Some(cache);
+        self
+    }
+    
+    /// Resubscribes a channel once more than `limit` of its frames in a
+    /// minute fail to parse
+    pub fn with_parse_error_limit(mut self, limit: u32) -> Self {
+        self.max_parse_errors_per_minute = limit;
+        self
+    }
+    
+    /// Pings each connection every `ping_interval`, and drops one that has
+    /// received nothing for `stale_after`
+    pub fn with_heartbeat(mut self, ping_interval: Duration, stale_after: Duration) -> Self {
+        self.ping_interval = ping_interval;
+        self.stale_after = stale_after;
+        self
+    }
+    
+    /// Holds up to `events` decoded messages for each consumer, and up to
+    /// `writes` outgoing ones for the write task; see [`EventQueue`]
+    pub fn with_buffers(mut self, events: usize, writes: usize) -> Self {
+        self.event_buffer = events;
+        self.write_buffer = writes;
+        self
+    }
+    
+    /// Waits up to `timeout` for the response to a posted action
+    pub fn with_post_timeout(mut self, timeout: Duration) -> Self {
+        self.posts.timeout = timeout;
+        self
+    }
+    
+    /// Posts signed exchange actions over whichever connection is open; see
+    /// [`PostChannel::post_action`]
+    pub async fn post_action(&self, action: Value) -> Result<Value> {
+        self.posts.post_action(action).await
+    }
+    
+    /// Posts actions over this client's connections, for handing to whatever
+    /// signs them
+    pub fn post_channel(&self) -> PostChannel {
+        self.posts.clone()
+    }
+    
+    /// Records `fill` as passed on, returning whether it's new. Fills
+    /// recovered some other way, e.g. over REST after a reconnect, are
+    /// marked here so the stream doesn't pass them on again, nor they it.
+    pub fn mark_fill_seen(&self, fill: &UserFill) -> bool {
+        self.seen_fills.lock().unwrap().insert(fill.key())
+    }
+    
+    /// Frames dropped for failing to parse, by channel, over every
+    /// connection this client has made
+    pub fn parse_errors(&self) -> BTreeMap<String, u64> {
+        self.parse_errors.lock().unwrap().clone()
+    }
+    
+    /// Messages dropped for a consumer that fell behind, by channel, over
+    /// every connection this client has made. Outgoing messages refused for
+    /// a full write buffer count under `outgoing`.
+    pub fn dropped_messages(&self) -> BTreeMap<String, u64> {
+        self.dropped.lock().unwrap().clone()
+    }
+    
+    /// Whether the latest connection is still open
+    pub fn is_connected(&self) -> bool {
+        self.stats.open.load(Ordering::Relaxed) != 0
+    }
+    
+    /// When the open connection was made, if there is one
+    pub fn connected_since(&self) -> Option<DateTime<Utc>> {
+        if !self.is_connected() {
+            return None;
+        }
+        Utc.timestamp_millis_opt(self.stats.connected_ms.load(Ordering::Relaxed)).single()
+    }
+    
+    /// Messages of any kind received, pongs included, over every connection
+    pub fn messages_received(&self) -> u64 {
+        self.stats.messages.load(Ordering::Relaxed)
+    }
+    
+    /// Connections opened after the first
+    pub fn reconnect_count(&self) -> u64 {
+        self.stats.connections.load(Ordering::Relaxed).saturating_sub(1)
+    }
+    
+    /// How long ago the last message of any kind arrived, on whichever
+    /// connection, if one has
+    pub fn last_message_age(&self) -> Option<Duration> {
+        let at = match self.stats.last_message_ms.load(Ordering::Relaxed) {
+            0 => return None,
+            ms => Utc.timestamp_millis_opt(ms).single()?,
+        };
+        Some((Utc::now() - at).to_std().unwrap_or_default())
+    }
+    
+    /// Every message decoded from now on, over this and later connections.
+    /// Each receiver gets every message, held in a buffer of its own that
+    /// drops market data once full; see [`EventQueue`] for what a receiver
+    /// that falls behind misses.
+    pub fn events(&self) -> EventReceiver {
+        self.subscribers.subscribe(self.event_buffer, self.dropped.clone())
+    }
+    
+    pub async fn connect(&self) -> Result<WebSocketHandle> {
+        let (mut write, mut read): (FrameSink, FrameStream) = match &self.replay {
+            Some(replay) => {
+                info!("Replaying WebSocket recording: {}", self.ws_url);
+                replay.connect().await?
+            }
+            None => {
+                info!("Connecting to WebSocket: {}", self.ws_url);
+                let (ws_stream, _) = connect_async(&self.ws_url).await?;
+                let (write, read) = ws_stream.split();
+                (Box::pin(write), Box::pin(read))
+            }
+        };
         
-        let (tx, rx) = mpsc::unbounded_channel();
-        self.sender = Some(tx);
-        self.receiver = Some(rx);
+        let (tx, mut rx) = mpsc::channel(self.write_buffer);
+        // Nobody may be reading the handle's own buffer, so it never holds up the stream
+        let events = Arc::new(EventQueue::new(self.event_buffer, false, self.dropped.clone()));
+        let handle_events = QueueWriter(events.clone());
+        let subscribers = self.subscribers.clone();
+        let seen_fills = self.seen_fills.clone();
+        let prices = self.prices.clone();
+        let market_data = self.market_data.clone();
+        let health = Arc::new(ConnectionHealth {
+            last_message_ms: AtomicI64::new(0),
+            open: AtomicBool::new(true),
+            subscriptions: Mutex::new(Vec::new()),
+        });
+        let read_health = health.clone();
+        let number = self.stats.connections.fetch_add(1, Ordering::Relaxed) + 1;
+        self.stats.connected_ms.store(Utc::now().timestamp_millis(), Ordering::Relaxed);
+        self.stats.open.store(number, Ordering::Relaxed);
+        if self.replay.is_none() {
+            self.posts.open(number, tx.clone());
+        }
+        let open = OpenConnection {
+            stats: self.stats.clone(),
+            posts: self.posts.clone(),
+            number,
+        };
+        let resubscribe = tx.clone();
+        let recorder = self.recorder.clone();
+        let mut parser = FrameParser::with_counts(self.max_parse_errors_per_minute, self.parse_errors.clone());
         
-        // Spawn task to handle incoming messages
-        let read_task = tokio::spawn(async move {
-            let mut read = read;
+        // Spawn task to handle incoming messages. Frames that don't parse
+        // are dropped by the parser; only the connection ending stops it.
+        let reader = tokio::spawn(async move {
             while let Some(msg) = read.next().await {
+                if msg.is_ok() {
+                    let now = Utc::now().timestamp_millis();
+                    read_health.last_message_ms.store(now, Ordering::Relaxed);
+                    open.stats.last_message_ms.store(now, Ordering::Relaxed);
+                    open.stats.messages.fetch_add(1, Ordering::Relaxed);
+                }
                 match msg {
                     Ok(Message::Text(text)) => {
                         debug!("Received WebSocket message: {}", text);
-                        // Handle incoming messages here
+                        if let Some(recorder) = &recorder {
+                            recorder.record(&text);
+                        }
+                        match parser.parse(&text, Instant::now()) {
+                            FrameOutcome::Event(event) => {
+                                // Only whoever posted a request wants its response
+                                if let WsEvent::Posted { id, response } = event {
+                                    open.posts.respond(id, response);
+                                    continue;
+                                }
+                                match &event {
+                                    WsEvent::Market(MarketEvent::Ticker(ticker)) => {
+                                        if let Some(cache) = &market_data {
+                                            cache.update_mid(&ticker.symbol, ticker.price);
+                                        }
+                                        if let Some(prices) = &prices {
+                                            prices.update(ticker.clone(), Utc::now());
+                                        }
+                                    }
+                                    WsEvent::AllMids(mids) => {
+                                        if let Some(cache) = &market_data {
+                                            cache.update_mids(mids);
+                                        }
+                                    }
+                                    WsEvent::Unknown { channel, .. } => {
+                                        debug!("Ignoring message on unhandled channel {}", channel);
+                                    }
+                                    _ => {}
+                                }
+                                // A snapshot after reconnecting replays fills already passed on
+                                let events = match event {
+                                    WsEvent::UserFills(update) => seen_fills.lock().unwrap().unseen(update),
+                                    WsEvent::OrderUpdates(entries) => entries.iter().map(order_update).collect(),
+                                    WsEvent::Trades(trades) => trades.iter().map(trade).collect(),
+                                    event => vec![event],
+                                };
+                                for event in events {
+                                    subscribers.push(&event).await;
+                                    handle_events.0.push(event).await;
+                                }
+                            }
+                            FrameOutcome::Dropped => {}
+                            FrameOutcome::Resubscribe(channel) => {
+                                warn!("📡 Too many unreadable {} frames, resubscribing", channel);
+                                let subscriptions = read_health.subscriptions.lock().unwrap().clone();
+                                for subscription in subscriptions.into_iter().filter(|s| s["type"] == channel.as_str()) {
+                                    for method in ["unsubscribe", "subscribe"] {
+                                        let request = json!({ "method": method, "subscription": subscription });
+                                        let _ = resubscribe.try_send(Message::Text(request.to_string()));
+                                    }
+                                }
+                            }
+                        }
                     }
                     Ok(Message::Close(_)) => {
                         info!("WebSocket connection closed");
@@ -53,90 +750,344 @@ impl WebSocketClient {
                     _ => {}
                 }
             }
+            read_health.open.store(false, Ordering::Relaxed);
         });
         
-        // Spawn task to handle outgoing messages
-        let write_task = tokio::spawn(async move {
-            let mut write = write;
-            let mut rx = self.receiver.take().unwrap();
-            
+        // Spawn task to handle outgoing messages; it ends, closing the
+        // channel, once it has sent a close, every handle is dropped or a
+        // send fails
+        let reader_abort = reader.abort_handle();
+        let writer = tokio::spawn(async move {
             while let Some(msg) = rx.recv().await {
+                let closing = matches!(msg, Message::Close(_));
                 if let Err(e) = write.send(msg).await {
                     error!("Failed to send WebSocket message: {}", e);
                     break;
                 }
+                if closing {
+                    break;
+                }
+            }
+        });
+        
+        // Spawn task to keep the connection alive: it pings on an
+        // interval, and drops the connection once nothing has come in for
+        // too long, so the next connect opens a new one
+        let ping = tx.clone();
+        let heartbeat_health = health.clone();
+        let (ping_interval, stale_after) = (self.ping_interval, self.stale_after);
+        let connected_at = Utc::now();
+        let heartbeat = tokio::spawn(async move {
+            let mut ticks = tokio::time::interval_at(Instant::now() + ping_interval, ping_interval);
+            loop {
+                tokio::select! {
+                    _ = ticks.tick() => {}
+                    // The writer has stopped
+                    _ = ping.closed() => break,
+                }
+                if !heartbeat_health.open.load(Ordering::Relaxed) {
+                    break;
+                }
+                
+                let quiet = Utc::now() - heartbeat_health.last_message_at().unwrap_or(connected_at);
+                if quiet.to_std().unwrap_or_default() >= stale_after {
+                    warn!("📡 Nothing received over the websocket for {}s, dropping the connection", quiet.num_seconds());
+                    heartbeat_health.open.store(false, Ordering::Relaxed);
+                    reader_abort.abort();
+                    let _ = ping.try_send(Message::Close(None));
+                    break;
+                }
+                match ping.try_send(Message::Text(json!({ "method": "ping" }).to_string())) {
+                    Ok(()) => {}
+                    // Whatever's queued shows the connection is in use
+                    Err(mpsc::error::TrySendError::Full(_)) => debug!("WebSocket write buffer full, skipping ping"),
+                    Err(mpsc::error::TrySendError::Closed(_)) => break,
+                }
             }
         });
         
         info!("WebSocket connected successfully");
-        Ok(())
+        Ok(WebSocketHandle {
+            sender: tx,
+            events,
+            dropped: self.dropped.clone(),
+            health,
+            account: self.account.clone(),
+            tasks: Arc::new(Mutex::new(vec![reader, writer, heartbeat])),
+        })
+    }
+}
+
+impl WebSocketHandle {
+    pub fn is_connected(&self) -> bool {
+        self.health.open.load(Ordering::Relaxed) && !self.sender.is_closed()
     }
     
-    pub async fn subscribe_to_ticker(&self, symbol: &str) -> Result<()> {
-        if let Some(sender) = &self.sender {
-            let subscription = json!({
-                "method": "subscribe",
-                "subscription": {
-                    "type": "ticker",
-                    "coin": symbol
-                }
-            });
-            
-            let message = Message::Text(serde_json::to_string(&subscription)?);
-            sender.send(message).map_err(|e| Error::WebSocket(e.into()))?;
-            
-            info!("Subscribed to ticker for {}", symbol);
+    /// When the last message of any kind arrived, if one has
+    pub fn last_message_at(&self) -> Option<DateTime<Utc>> {
+        self.health.last_message_at()
+    }
+    
+    /// How long ago the last message of any kind arrived, if one has
+    pub fn last_message_age(&self) -> Option<Duration> {
+        self.last_message_at().map(|at| (Utc::now() - at).to_std().unwrap_or_default())
+    }
+    
+    /// The next message received, waiting for one if none is buffered.
+    /// `None` once the connection has ended and every message before that
+    /// has been read.
+    pub async fn next_message(&self) -> Option<WsEvent> {
+        loop {
+            match self.events.recv().await {
+                Ok(event) => return Some(event),
+                Err(RecvError::Lagged(_)) => {}
+                Err(RecvError::Closed) => return None,
+            }
         }
-        
-        Ok(())
     }
     
-    pub async fn subscribe_to_l2_book(&self, symbol: &str) -> Result<()> {
-        if let Some(sender) = &self.sender {
-            let subscription = json!({
-                "method": "subscribe",
-                "subscription": {
-                    "type": "l2Book",
-                    "coin": symbol
-                }
-            });
-            
-            let message = Message::Text(serde_json::to_string(&subscription)?);
-            sender.send(message).map_err(|e| Error::WebSocket(e.into()))?;
-            
-            info!("Subscribed to L2 book for {}", symbol);
+    /// Queues `message` for the write task. A full write buffer refuses it
+    /// rather than growing, and counts it dropped.
+    fn send(&self, message: Message) -> Result<()> {
+        self.sender.try_send(message).map_err(|e| match e {
+            mpsc::error::TrySendError::Full(message) => {
+                *self.dropped.lock().unwrap().entry(OUTGOING.to_string()).or_default() += 1;
+                Error::from(tungstenite::Error::WriteBufferFull(message))
+            }
+            mpsc::error::TrySendError::Closed(_) => Error::from(tungstenite::Error::ConnectionClosed),
+        })
+    }
+    
+    fn subscribe(&self, subscription: Value) -> Result<()> {
+        let request = json!({
+            "method": "subscribe",
+            "subscription": subscription
+        });
+        self.send(Message::Text(serde_json::to_string(&request)?))?;
+        let mut subscriptions = self.health.subscriptions.lock().unwrap();
+        if !subscriptions.contains(&subscription) {
+            subscriptions.push(subscription);
         }
-        
         Ok(())
     }
     
-    pub async fn subscribe_to_candles(&self, symbol: &str, interval: &str) -> Result<()> {
-        if let Some(sender) = &self.sender {
-            let subscription = json!({
-                "method": "subscribe",
-                "subscription": {
-                    "type": "candle",
-                    "coin": symbol,
-                    "interval": interval
-                }
-            });
-            
-            let message = Message::Text(serde_json::to_string(&subscription)?);
-            sender.send(message).map_err(|e| Error::WebSocket(e.into()))?;
-            
-            info!("Subscribed to candles for {} ({})", symbol, interval);
+    /// Ends a subscription. One that isn't active, or a connection that has
+    /// already closed, leaves nothing to do.
+    fn unsubscribe(&self, subscription: Value) -> Result<()> {
+        {
+            let mut subscriptions = self.health.subscriptions.lock().unwrap();
+            let before = subscriptions.len();
+            subscriptions.retain(|s| *s != subscription);
+            if subscriptions.len() == before {
+                return Ok(());
+            }
+        }
+        if !self.is_connected() {
+            return Ok(());
         }
         
+        let request = json!({
+            "method": "unsubscribe",
+            "subscription": subscription
+        });
+        self.send(Message::Text(serde_json::to_string(&request)?))
+    }
+    
+    /// What this connection is subscribed to, in the order it subscribed
+    pub fn active_subscriptions(&self) -> Vec<Value> {
+        self.health.subscriptions.lock().unwrap().clone()
+    }
+    
+    pub fn subscribe_to_ticker(&self, symbol: &str) -> Result<()> {
+        self.subscribe(json!({
+            "type": "ticker",
+            "coin": symbol
+        }))?;
+        info!("Subscribed to ticker for {}", symbol);
+        Ok(())
+    }
+    
+    pub fn subscribe_to_l2_book(&self, symbol: &str) -> Result<()> {
+        self.subscribe(json!({
+            "type": "l2Book",
+            "coin": symbol
+        }))?;
+        info!("Subscribed to L2 book for {}", symbol);
+        Ok(())
+    }
+    
+    pub fn subscribe_to_candles(&self, symbol: &str, interval: &str) -> Result<()> {
+        self.subscribe(json!({
+            "type": "candle",
+            "coin": symbol,
+            "interval": interval
+        }))?;
+        info!("Subscribed to candles for {} ({})", symbol, interval);
+        Ok(())
+    }
+    
+    /// Streams every coin's mid, pushed whole on each change
+    pub fn subscribe_to_all_mids(&self) -> Result<()> {
+        self.subscribe(json!({
+            "type": "allMids"
+        }))?;
+        info!("Subscribed to all mids");
+        Ok(())
+    }
+    
+    /// Streams every trade on `symbol`
+    pub fn subscribe_to_trades(&self, symbol: &str) -> Result<()> {
+        self.subscribe(json!({
+            "type": "trades",
+            "coin": symbol
+        }))?;
+        info!("Subscribed to trades for {}", symbol);
+        Ok(())
+    }
+    
+    pub fn unsubscribe_ticker(&self, symbol: &str) -> Result<()> {
+        self.unsubscribe(json!({
+            "type": "ticker",
+            "coin": symbol
+        }))?;
+        info!("Unsubscribed from ticker for {}", symbol);
+        Ok(())
+    }
+    
+    pub fn unsubscribe_l2_book(&self, symbol: &str) -> Result<()> {
+        self.unsubscribe(json!({
+            "type": "l2Book",
+            "coin": symbol
+        }))?;
+        info!("Unsubscribed from L2 book for {}", symbol);
+        Ok(())
+    }
+    
+    pub fn unsubscribe_candles(&self, symbol: &str, interval: &str) -> Result<()> {
+        self.unsubscribe(json!({
+            "type": "candle",
+            "coin": symbol,
+            "interval": interval
+        }))?;
+        info!("Unsubscribed from candles for {} ({})", symbol, interval);
+        Ok(())
+    }
+    
+    pub fn unsubscribe_all_mids(&self) -> Result<()> {
+        self.unsubscribe(json!({
+            "type": "allMids"
+        }))?;
+        info!("Unsubscribed from all mids");
+        Ok(())
+    }
+    
+    pub fn unsubscribe_trades(&self, symbol: &str) -> Result<()> {
+        self.unsubscribe(json!({
+            "type": "trades",
+            "coin": symbol
+        }))?;
+        info!("Unsubscribed from trades for {}", symbol);
+        Ok(())
+    }
+    
+    /// Streams `user`'s fills, starting with a snapshot of recent ones
+    pub fn subscribe_to_user_fills(&self, user: &str) -> Result<()> {
+        self.subscribe(json!({
+            "type": "userFills",
+            "user": user
+        }))?;
+        info!("Subscribed to fills for {}", user);
+        Ok(())
+    }
+    
+    /// Streams status changes to `user`'s orders
+    pub fn subscribe_to_order_updates(&self, user: &str) -> Result<()> {
+        self.subscribe(json!({
+            "type": "orderUpdates",
+            "user": user
+        }))?;
+        info!("Subscribed to order updates for {}", user);
+        Ok(())
+    }
+    
+    /// Streams the exchange's messages for `user`, e.g. liquidation warnings
+    pub fn subscribe_to_notifications(&self, user: &str) -> Result<()> {
+        self.subscribe(json!({
+            "type": "notification",
+            "user": user
+        }))?;
+        info!("Subscribed to notifications for {}", user);
         Ok(())
     }
     
-    pub async fn disconnect(&mut self) -> Result<()> {
-        if let Some(sender) = &self.sender {
-            let close_message = Message::Close(None);
-            sender.send(close_message).map_err(|e| Error::WebSocket(e.into()))?;
+    /// Streams `user`'s fills, order updates and notifications
+    pub fn subscribe_account_channels(&self, user: &str) -> Result<()> {
+        self.subscribe_to_user_fills(user)?;
+        self.subscribe_to_order_updates(user)?;
+        self.subscribe_to_notifications(user)
+    }
+    
+    /// Streams the fills, order updates and notifications of the account
+    /// the client was given with `with_account`
+    pub fn subscribe_user_channels(&self) -> Result<()> {
+        match &self.account {
+            Some(account) => self.subscribe_account_channels(account),
+            None => Err(Error::Config(
+                "Set hyperliquid.account_address or a private key to stream the account's channels".to_string(),
+            )),
+        }
+    }
+    
+    /// Closes the connection and waits for its tasks to finish, aborting
+    /// them if the server doesn't close its side in time. The writer stops
+    /// once the close is sent, which closes the outgoing channel and ends
+    /// the heartbeat; the reader ends with the server's close.
+    pub async fn disconnect(&self) -> Result<()> {
+        // Already closed counts as disconnected
+        let closing = self.is_connected() && self.send(Message::Close(None)).is_ok();
+        
+        // One deadline for the lot, so a stuck close costs CLOSE_TIMEOUT once
+        let deadline = Instant::now() + CLOSE_TIMEOUT;
+        let tasks = std::mem::take(&mut *self.tasks.lock().unwrap());
+        let mut late = false;
+        for mut task in tasks {
+            if !closing {
+                task.abort();
+            }
+            if tokio::time::timeout_at(deadline, &mut task).await.is_err() {
+                if !late {
+                    warn!("WebSocket didn't close within {}s, dropping it", CLOSE_TIMEOUT.as_secs());
+                    late = true;
+                }
+                task.abort();
+                let _ = task.await;
+            }
         }
         
         info!("WebSocket disconnected");
         Ok(())
     }
 }
+
+/// What `entry` says became of its order
+fn order_update(entry: &OrderStatusUpdate) -> WsEvent {
+    let order = entry.to_order();
+    WsEvent::OrderUpdate {
+        oid: entry.order.oid,
+        cloid: entry.order.cloid.clone(),
+        status: order.status,
+        filled_sz: order.filled_quantity,
+    }
+}
+
+/// `entry` as an event of its own
+fn trade(entry: &WsTrade) -> WsEvent {
+    let trade = entry.to_market_trade();
+    WsEvent::Trade {
+        coin: trade.symbol,
+        px: trade.price,
+        sz: trade.size,
+        side: trade.side,
+        time: trade.timestamp,
+    }
+}
@@ -0,0 +1,126 @@
+use crate::error::Result;
+use chrono::{DateTime, Utc};
+use std::sync::Mutex;
+use tracing::{info, warn};
+
+use super::websocket::{EventReceiver, PostChannel, WebSocketClient, WebSocketHandle};
+
+/// Several connections to one websocket, for subscription lists longer than
+/// the exchange allows on one connection. Each subscription goes to the
+/// connection its key (a coin, or a user's address) hashes to, so a coin's
+/// channels all arrive on one connection and in order, and a connection
+/// dropping loses only its share. Each connection is reopened on its own,
+/// with no subscriptions; whoever subscribes resubscribes its share.
+///
+/// All the connections are opened through one client, so a receiver from
+/// `events()` sees messages from every one of them, and fills replayed on
+/// one after it reconnects are still recognised. The client's own health
+/// (`is_connected`, `connected_since`) and its post channel follow the most
+/// recently opened connection.
+pub struct WsPool {
+    client: WebSocketClient,
+    connections: Mutex<Vec<Option<WebSocketHandle>>>,
+}
+
+impl WsPool {
+    /// `size` connections through `client`; at least one
+    pub fn new(client: WebSocketClient, size: usize) -> Self {
+        Self {
+            client,
+            connections: Mutex::new(vec![None; size.max(1)]),
+        }
+    }
+    
+    pub fn size(&self) -> usize {
+        self.connections.lock().unwrap().len()
+    }
+    
+    pub fn client(&self) -> &WebSocketClient {
+        &self.client
+    }
+    
+    /// The client, for configuring further before anything is connected
+    pub fn into_client(self) -> WebSocketClient {
+        self.client
+    }
+    
+    /// The connection whose subscriptions `key` belongs with. Stable across
+    /// runs, so a coin's stream lands on the same connection each time.
+    pub fn slot(&self, key: &str) -> usize {
+        // FNV-1a; the std hasher makes no promise to stay the same
+        let hash = key.bytes().fold(0xcbf29ce484222325_u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+        (hash % self.size() as u64) as usize
+    }
+    
+    /// Opens connection `slot` unless it's already up. Returns whether it
+    /// opened a new one, which starts with no subscriptions.
+    pub async fn connect(&self, slot: usize) -> Result<bool> {
+        if self.handle(slot).is_some_and(|ws| ws.is_connected()) {
+            return Ok(false);
+        }
+        let ws = self.client.connect().await?;
+        info!("WebSocket connection {} of {} open", slot + 1, self.size());
+        self.connections.lock().unwrap()[slot] = Some(ws);
+        Ok(true)
+    }
+    
+    /// Connection `slot`, once there's been one, whether or not it's up
+    pub fn handle(&self, slot: usize) -> Option<WebSocketHandle> {
+        self.connections.lock().unwrap().get(slot).cloned().flatten()
+    }
+    
+    /// The connection `key`'s subscriptions go to, once there's been one
+    pub fn handle_for(&self, key: &str) -> Option<WebSocketHandle> {
+        self.handle(self.slot(key))
+    }
+    
+    /// Every connection opened so far
+    pub fn handles(&self) -> Vec<WebSocketHandle> {
+        self.connections.lock().unwrap().iter().flatten().cloned().collect()
+    }
+    
+    /// Connections currently up
+    pub fn connected(&self) -> usize {
+        self.handles().iter().filter(|ws| ws.is_connected()).count()
+    }
+    
+    /// Whether every connection is up, so nothing subscribed is missing
+    pub fn is_connected(&self) -> bool {
+        self.connected() == self.size()
+    }
+    
+    /// When the quietest connection last received anything; the pool's
+    /// data is no fresher than this. `None` until every connection has
+    /// received something.
+    pub fn last_message_at(&self) -> Option<DateTime<Utc>> {
+        let mut oldest: Option<DateTime<Utc>> = None;
+        for ws in self.connections.lock().unwrap().iter() {
+            let at = ws.as_ref().and_then(|ws| ws.last_message_at())?;
+            oldest = Some(oldest.map_or(at, |oldest| oldest.min(at)));
+        }
+        oldest
+    }
+    
+    /// Messages decoded from now on, from every connection; see
+    /// [`WebSocketClient::events`]
+    pub fn events(&self) -> EventReceiver {
+        self.client.events()
+    }
+    
+    pub fn post_channel(&self) -> PostChannel {
+        self.client.post_channel()
+    }
+    
+    /// Closes every connection; the next `connect` of each opens a new one
+    pub async fn disconnect(&self) -> Result<()> {
+        let connections: Vec<WebSocketHandle> = self.connections.lock().unwrap().iter_mut().filter_map(Option::take).collect();
+        for ws in connections {
+            if let Err(e) = ws.disconnect().await {
+                warn!("Failed to close a websocket connection: {}", e);
+            }
+        }
+        Ok(())
+    }
+}
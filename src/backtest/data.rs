@@ -0,0 +1,198 @@
+use crate::{
+    api::{
+        client::TradingClient,
+        types::{interval_duration, Candle},
+    },
+    error::{Error, Result},
+    models::MarketData,
+    recorder::{MarketStream, RecordingReader},
+};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use rust_decimal::Decimal;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+pub(crate) const CSV_HEADER: &str = "time,open,high,low,close,volume";
+const FUNDING_HEADER: &str = "time,rate";
+
+/// Candle files live at `<directory>/<SYMBOL>_<interval>.csv`
+pub fn candle_path(directory: &Path, symbol: &str, interval: &str) -> PathBuf {
+    directory.join(format!("{}_{}.csv", symbol, interval))
+}
+
+/// Reads `time,open,high,low,close,volume` rows, with `time` the candle open
+/// in epoch millis. The header row is optional.
+pub fn read_candles_csv(path: &Path) -> Result<Vec<Candle>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut candles = Vec::new();
+    
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line == CSV_HEADER {
+            continue;
+        }
+        
+        let invalid = || Error::InvalidInput(format!("{}:{}: expected {}", path.display(), index + 1, CSV_HEADER));
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        if fields.len() != 6 {
+            return Err(invalid());
+        }
+        let decimal = |i: usize| fields[i].parse::<Decimal>().map_err(|_| invalid());
+        
+        candles.push(Candle {
+            t: fields[0].parse().map_err(|_| invalid())?,
+            o: decimal(1)?,
+            h: decimal(2)?,
+            l: decimal(3)?,
+            c: decimal(4)?,
+            v: decimal(5)?,
+        });
+    }
+    
+    candles.sort_by_key(|c| c.t);
+    Ok(candles)
+}
+
+/// Writes candles in the format [`read_candles_csv`] reads, header first
+pub fn write_candles_csv<W: Write>(mut writer: W, candles: &[Candle]) -> Result<()> {
+    writeln!(writer, "{}", CSV_HEADER)?;
+    for candle in candles {
+        write_candle_row(&mut writer, candle)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+pub(crate) fn write_candle_row<W: Write>(writer: &mut W, candle: &Candle) -> Result<()> {
+    writeln!(writer, "{},{},{},{},{},{}", candle.t, candle.o, candle.h, candle.l, candle.c, candle.v)?;
+    Ok(())
+}
+
+/// Recorded funding rates live at `<directory>/<SYMBOL>_funding.csv`
+pub fn funding_path(directory: &Path, symbol: &str) -> PathBuf {
+    directory.join(format!("{}_funding.csv", symbol))
+}
+
+/// Reads `time,rate` rows, with `time` in epoch millis. The header row is
+/// optional.
+pub fn read_funding_csv(path: &Path) -> Result<Vec<(DateTime<Utc>, Decimal)>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut rates = Vec::new();
+    
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line == FUNDING_HEADER {
+            continue;
+        }
+        
+        let invalid = || Error::InvalidInput(format!("{}:{}: expected {}", path.display(), index + 1, FUNDING_HEADER));
+        let (time, rate) = line.split_once(',').ok_or_else(invalid)?;
+        let time: i64 = time.trim().parse().map_err(|_| invalid())?;
+        rates.push((
+            Utc.timestamp_millis_opt(time).single().ok_or_else(invalid)?,
+            rate.trim().parse::<Decimal>().map_err(|_| invalid())?,
+        ));
+    }
+    
+    rates.sort_by_key(|(time, _)| *time);
+    Ok(rates)
+}
+
+/// A closed candle as the strategies see it: the close price, stamped at the
+/// candle's close time so nothing is known before it could have been.
+/// Candle high, low and volume stand in for the 24h fields.
+pub fn candle_to_market_data(symbol: &str, candle: &Candle, interval: Duration) -> MarketData {
+    let open_time = Utc.timestamp_millis_opt(candle.t as i64).single().unwrap_or_default();
+    
+    MarketData {
+        symbol: symbol.to_string(),
+        price: candle.c,
+        volume_24h: candle.v,
+        change_24h: Decimal::ZERO,
+        high_24h: candle.h,
+        low_24h: candle.l,
+        timestamp: open_time + interval,
+    }
+}
+
+fn candles_in_range(symbol: &str, candles: &[Candle], interval: Duration, from: DateTime<Utc>, to: DateTime<Utc>) -> Vec<MarketData> {
+    candles
+        .iter()
+        .map(|candle| candle_to_market_data(symbol, candle, interval))
+        .filter(|data| data.timestamp > from && data.timestamp <= to)
+        .collect()
+}
+
+/// Loads market data for `symbols` from a directory holding candle CSVs or,
+/// for symbols without one, market recorder output.
+pub fn load_directory(
+    directory: &Path,
+    symbols: &[String],
+    interval: &str,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<MarketData>> {
+    let duration = interval_duration(interval)?;
+    let mut data = Vec::new();
+    
+    for symbol in symbols {
+        let path = candle_path(directory, symbol, interval);
+        let loaded = if path.exists() {
+            candles_in_range(symbol, &read_candles_csv(&path)?, duration, from, to)
+        } else {
+            read_recorded_tickers(directory, symbol, from, to)?
+        };
+        
+        if loaded.is_empty() {
+            warn!("No market data for {} in {}", symbol, directory.display());
+        }
+        info!("Loaded {} bars for {}", loaded.len(), symbol);
+        data.extend(loaded);
+    }
+    
+    Ok(data)
+}
+
+fn read_recorded_tickers(directory: &Path, symbol: &str, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<Vec<MarketData>> {
+    let reader = RecordingReader::open(directory, from.date_naive(), to.date_naive(), Some(vec![symbol.to_string()]))?;
+    let mut data = Vec::new();
+    
+    for event in reader {
+        let event = event?;
+        if event.stream != MarketStream::Ticker || event.timestamp <= from || event.timestamp > to {
+            continue;
+        }
+        data.push(serde_json::from_value(event.data)?);
+    }
+    
+    Ok(data)
+}
+
+/// Downloads candles for `symbols` via the candleSnapshot info request
+pub async fn fetch(
+    client: &(dyn TradingClient + Send + Sync),
+    symbols: &[String],
+    interval: &str,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<MarketData>> {
+    let duration = interval_duration(interval)?;
+    let mut data = Vec::new();
+    
+    // Include the candle that closes right after `from`
+    let start = (from - duration).timestamp_millis().max(0) as u64;
+    let end = to.timestamp_millis().max(0) as u64;
+    
+    for symbol in symbols {
+        let candles = client.get_candles(symbol, interval, start, end).await?;
+        let fetched = candles_in_range(symbol, &candles, duration, from, to);
+        info!("Fetched {} bars for {}", fetched.len(), symbol);
+        data.extend(fetched);
+    }
+    
+    Ok(data)
+}
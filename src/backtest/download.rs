@@ -0,0 +1,302 @@
+use super::data::{self, candle_path};
+use crate::{
+    api::{
+        types::{interval_duration, Candle},
+        HyperliquidClient,
+    },
+    error::{Error, Result},
+};
+use chrono::{DateTime, TimeZone, Utc};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// candleSnapshot returns at most this many candles per request
+pub const MAX_CHUNK_CANDLES: u64 = 5000;
+const DEFAULT_PAUSE: Duration = Duration::from_millis(500);
+const MAX_ATTEMPTS: u32 = 4;
+
+/// A run of missing candles, from the first missing open time up to the next
+/// candle present or the end of the range
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gap {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub missing: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct DownloadSummary {
+    pub symbol: String,
+    pub path: PathBuf,
+    pub requests: usize,
+    pub rows_written: usize,
+    /// Candles on disk within the requested range
+    pub rows_in_range: usize,
+    pub gaps: Vec<Gap>,
+}
+
+/// Fetches candles into `<SYMBOL>_<interval>.csv` files, the layout
+/// `backtest --data` reads. Ranges already in a file are not fetched again.
+pub struct Downloader<'a> {
+    client: &'a HyperliquidClient,
+    chunk_candles: u64,
+    pause: Duration,
+}
+
+impl<'a> Downloader<'a> {
+    pub fn new(client: &'a HyperliquidClient) -> Self {
+        Self {
+            client,
+            chunk_candles: MAX_CHUNK_CANDLES,
+            pause: DEFAULT_PAUSE,
+        }
+    }
+    
+    /// Candles asked for per request, at most [`MAX_CHUNK_CANDLES`]
+    pub fn with_chunk_candles(mut self, chunk_candles: u64) -> Self {
+        self.chunk_candles = chunk_candles.clamp(1, MAX_CHUNK_CANDLES);
+        self
+    }
+    
+    /// Wait between requests, to stay under the info rate limit
+    pub fn with_pause(mut self, pause: Duration) -> Self {
+        self.pause = pause;
+        self
+    }
+    
+    /// Downloads closed candles opening in `[from, to)` for each symbol
+    pub async fn download(
+        &self,
+        directory: &Path,
+        symbols: &[String],
+        interval: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<DownloadSummary>> {
+        let step = interval_duration(interval)?.num_milliseconds() as u64;
+        let last_closed = Utc::now().timestamp_millis() as u64 / step * step;
+        let start = (from.timestamp_millis().max(0) as u64).div_ceil(step) * step;
+        let end = (to.timestamp_millis().max(0) as u64 / step * step).min(last_closed);
+        if start >= end {
+            return Err(Error::InvalidInput(format!(
+                "No closed {} candles between {} and {}",
+                interval, from, to
+            )));
+        }
+        
+        fs::create_dir_all(directory)?;
+        let mut requests = 0;
+        let mut summaries = Vec::new();
+        
+        for symbol in symbols {
+            let before = requests;
+            let mut summary = self.download_symbol(directory, symbol, interval, step, start, end, &mut requests).await?;
+            summary.requests = requests - before;
+            
+            info!(
+                "📥 {} {}: {} rows written, {} in range, {} gaps",
+                symbol, interval, summary.rows_written, summary.rows_in_range, summary.gaps.len()
+            );
+            summaries.push(summary);
+        }
+        
+        Ok(summaries)
+    }
+    
+    #[allow(clippy::too_many_arguments)]
+    async fn download_symbol(
+        &self,
+        directory: &Path,
+        symbol: &str,
+        interval: &str,
+        step: u64,
+        start: u64,
+        end: u64,
+        requests: &mut usize,
+    ) -> Result<DownloadSummary> {
+        let path = candle_path(directory, symbol, interval);
+        let mut candles = if path.exists() { data::read_candles_csv(&path)? } else { Vec::new() };
+        let mut rows_written = 0;
+        
+        // Candles before what's on disk mean rewriting the file, so they're
+        // gathered first and written in one go
+        if let Some(first) = candles.first().map(|c| c.t) {
+            if start < first {
+                let mut earlier = Vec::new();
+                self.fetch_range(symbol, interval, step, start, first.min(end), requests, |chunk| {
+                    earlier.extend_from_slice(chunk);
+                    Ok(())
+                })
+                .await?;
+                
+                if !earlier.is_empty() {
+                    rows_written += earlier.len();
+                    earlier.append(&mut candles);
+                    candles = earlier;
+                    rewrite(&path, &candles)?;
+                }
+            }
+        }
+        
+        // Later candles are appended a chunk at a time, so an interrupted
+        // download picks up where it stopped
+        let resume_at = candles.last().map_or(start, |c| (c.t + step).max(start));
+        if resume_at < end {
+            let mut writer = open_for_append(&path)?;
+            self.fetch_range(symbol, interval, step, resume_at, end, requests, |chunk| {
+                for candle in chunk {
+                    data::write_candle_row(&mut writer, candle)?;
+                }
+                writer.flush()?;
+                rows_written += chunk.len();
+                candles.extend_from_slice(chunk);
+                Ok(())
+            })
+            .await?;
+        }
+        
+        let in_range: Vec<Candle> = candles.into_iter().filter(|c| (start..end).contains(&c.t)).collect();
+        Ok(DownloadSummary {
+            symbol: symbol.to_string(),
+            path,
+            requests: 0,
+            rows_written,
+            rows_in_range: in_range.len(),
+            gaps: find_gaps(&in_range, start, end, step),
+        })
+    }
+    
+    #[allow(clippy::too_many_arguments)]
+    async fn fetch_range<F>(
+        &self,
+        symbol: &str,
+        interval: &str,
+        step: u64,
+        start: u64,
+        end: u64,
+        requests: &mut usize,
+        mut sink: F,
+    ) -> Result<()>
+    where
+        F: FnMut(&[Candle]) -> Result<()>,
+    {
+        let mut chunk_start = start;
+        
+        while chunk_start < end {
+            let chunk_end = (chunk_start + self.chunk_candles * step).min(end);
+            if *requests > 0 {
+                tokio::time::sleep(self.pause).await;
+            }
+            *requests += 1;
+            
+            let mut candles = self.fetch_chunk(symbol, interval, chunk_start, chunk_end).await?;
+            candles.retain(|c| (chunk_start..chunk_end).contains(&c.t));
+            candles.dedup_by_key(|c| c.t);
+            debug!("Fetched {} {} candles for {} from {}", candles.len(), interval, symbol, chunk_start);
+            
+            sink(&candles)?;
+            chunk_start = chunk_end;
+        }
+        
+        Ok(())
+    }
+    
+    async fn fetch_chunk(&self, symbol: &str, interval: &str, start: u64, end: u64) -> Result<Vec<Candle>> {
+        let mut attempt = 1;
+        loop {
+            // The snapshot's end time is inclusive
+            match self.client.get_candles(symbol, interval, start, end - 1).await {
+                Ok(candles) => return Ok(candles),
+                Err(e) if attempt < MAX_ATTEMPTS && e.is_retryable() => {
+                    let backoff = e.retry_after().unwrap_or(self.pause * 2u32.pow(attempt));
+                    warn!("Candle request for {} failed ({}), retrying in {:?}", symbol, e, backoff);
+                    tokio::time::sleep(backoff).await;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+fn rewrite(path: &Path, candles: &[Candle]) -> Result<()> {
+    let temp = path.with_extension("csv.tmp");
+    data::write_candles_csv(BufWriter::new(File::create(&temp)?), candles)?;
+    fs::rename(&temp, path)?;
+    Ok(())
+}
+
+fn open_for_append(path: &Path) -> Result<BufWriter<File>> {
+    let mut file = OpenOptions::new().read(true).append(true).create(true).open(path)?;
+    let len = file.metadata()?.len();
+    
+    if len == 0 {
+        writeln!(file, "{}", data::CSV_HEADER)?;
+    } else {
+        // Files written by hand may not end with a newline
+        let mut last = [0u8];
+        file.seek(SeekFrom::Start(len - 1))?;
+        file.read_exact(&mut last)?;
+        if last[0] != b'\n' {
+            writeln!(file)?;
+        }
+    }
+    
+    Ok(BufWriter::new(file))
+}
+
+fn millis(t: u64) -> DateTime<Utc> {
+    Utc.timestamp_millis_opt(t as i64).single().unwrap_or_default()
+}
+
+/// Missing candle open times in `[start, end)`, given sorted `candles`
+pub fn find_gaps(candles: &[Candle], start: u64, end: u64, step: u64) -> Vec<Gap> {
+    let gap = |from: u64, to: u64| Gap {
+        from: millis(from),
+        to: millis(to),
+        missing: (to - from).div_ceil(step),
+    };
+    let mut gaps = Vec::new();
+    let mut expected = start;
+    
+    for t in candles.iter().map(|c| c.t).filter(|t| (start..end).contains(t)) {
+        if t > expected {
+            gaps.push(gap(expected, t));
+        }
+        expected = expected.max(t + step);
+    }
+    if expected < end {
+        gaps.push(gap(expected, end));
+    }
+    
+    gaps
+}
+
+/// Prints one line per symbol and one per gap
+pub fn write_summary<W: Write>(summaries: &[DownloadSummary], mut writer: W) -> Result<()> {
+    for summary in summaries {
+        writeln!(
+            writer,
+            "{}: {} rows written, {} in range, {} requests -> {}",
+            summary.symbol,
+            summary.rows_written,
+            summary.rows_in_range,
+            summary.requests,
+            summary.path.display()
+        )?;
+        for gap in &summary.gaps {
+            writeln!(
+                writer,
+                "  ⚠️ gap of {} candles from {} to {}",
+                gap.missing,
+                gap.from.format("%Y-%m-%d %H:%M"),
+                gap.to.format("%Y-%m-%d %H:%M")
+            )?;
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
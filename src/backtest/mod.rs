@@ -0,0 +1,556 @@
+pub mod data;
+pub mod download;
+pub mod optimize;
+pub mod report;
+pub mod walk_forward;
+
+use self::report::BacktestReport;
+use crate::{
+    config::{BacktestConfig, Config},
+    costs::{CostModel, Liquidity, MarketConditions},
+    equity,
+    error::{Error, Result},
+    models::{AccountInfo, MarketData, OrderSide, Position, PositionSide, SignalAction, StrategySignal},
+    storage::EquitySample,
+    strategies::Strategy,
+    trading_bot::RiskManager,
+    utils::MarketSchedule,
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+use tracing::{debug, info};
+
+// Same floor the live bot applies before executing a signal
+const MIN_CONFIDENCE: f64 = 0.5;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BacktestTrade {
+    pub timestamp: DateTime<Utc>,
+    pub strategy: String,
+    pub symbol: String,
+    pub side: OrderSide,
+    pub quantity: Decimal,
+    /// Fill price after slippage
+    pub price: Decimal,
+    pub fee: Decimal,
+    pub slippage: Decimal,
+    pub realized_pnl: Decimal,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StrategyBreakdown {
+    pub trades: u64,
+    pub volume: Decimal,
+    pub fees: Decimal,
+    /// Already in the fill prices, so in realized and unrealized PnL
+    pub slippage: Decimal,
+    /// Funding paid, negative when received
+    pub funding: Decimal,
+    pub realized_pnl: Decimal,
+    pub unrealized_pnl: Decimal,
+}
+
+impl StrategyBreakdown {
+    pub fn net_pnl(&self) -> Decimal {
+        self.realized_pnl + self.unrealized_pnl - self.fees - self.funding
+    }
+    
+    /// PnL at the reference prices, before fees, slippage and funding
+    pub fn gross_pnl(&self) -> Decimal {
+        self.realized_pnl + self.unrealized_pnl + self.slippage
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub(crate) struct SimPosition {
+    /// Signed: negative is short
+    pub(crate) quantity: Decimal,
+    pub(crate) average_price: Decimal,
+}
+
+impl SimPosition {
+    /// Applies a fill and returns the PnL it realized
+    pub(crate) fn apply(&mut self, side: &OrderSide, quantity: Decimal, price: Decimal) -> Decimal {
+        let signed = match side {
+            OrderSide::Buy => quantity,
+            OrderSide::Sell => -quantity,
+        };
+        
+        if self.quantity.is_zero() || self.quantity.is_sign_positive() == signed.is_sign_positive() {
+            let total = self.quantity.abs() + quantity;
+            self.average_price = (self.average_price * self.quantity.abs() + price * quantity) / total;
+            self.quantity += signed;
+            return Decimal::ZERO;
+        }
+        
+        let closed = self.quantity.abs().min(quantity);
+        let direction = if self.quantity.is_sign_positive() { Decimal::ONE } else { -Decimal::ONE };
+        let realized = closed * (price - self.average_price) * direction;
+        
+        let previous = self.quantity;
+        self.quantity += signed;
+        if self.quantity.is_zero() {
+            self.average_price = Decimal::ZERO;
+        } else if self.quantity.is_sign_positive() != previous.is_sign_positive() {
+            // Flipped through flat; the remainder was opened at this fill
+            self.average_price = price;
+        }
+        realized
+    }
+}
+
+/// Virtual account shared by every strategy in a run
+struct SimAccount {
+    config: BacktestConfig,
+    costs: CostModel,
+    cash: Decimal,
+    positions: BTreeMap<(String, String), SimPosition>,
+    marks: HashMap<String, Decimal>,
+    trades: Vec<BacktestTrade>,
+    breakdowns: BTreeMap<String, StrategyBreakdown>,
+    symbol_breakdowns: BTreeMap<String, StrategyBreakdown>,
+    rejected_signals: u64,
+    /// Equity when the current UTC day began, for the daily loss limit
+    day_start: Option<(NaiveDate, Decimal)>,
+    /// Funding has been settled up to this time
+    funded_until: Option<DateTime<Utc>>,
+}
+
+impl SimAccount {
+    fn new(config: BacktestConfig, costs: CostModel) -> Self {
+        Self {
+            cash: config.initial_balance,
+            config,
+            costs,
+            positions: BTreeMap::new(),
+            marks: HashMap::new(),
+            trades: Vec::new(),
+            breakdowns: BTreeMap::new(),
+            symbol_breakdowns: BTreeMap::new(),
+            rejected_signals: 0,
+            day_start: None,
+            funded_until: None,
+        }
+    }
+    
+    /// Fills a signal as a taker at the bar's price, with the cost model's
+    /// slippage and fee. Returns the fill price, or `None` if it was skipped.
+    fn execute(&mut self, signal: &StrategySignal, market_data: &MarketData) -> Option<Decimal> {
+        let side = match signal.action {
+            SignalAction::Buy => OrderSide::Buy,
+            SignalAction::Sell => OrderSide::Sell,
+            _ => return None,
+        };
+        
+        if signal.confidence < MIN_CONFIDENCE || signal.quantity <= Decimal::ZERO {
+            self.rejected_signals += 1;
+            return None;
+        }
+        
+        let fill = self.costs.fill(
+            &side,
+            signal.quantity,
+            market_data.price,
+            Liquidity::Taker,
+            &MarketConditions::from(market_data),
+        );
+        let (price, fee) = (fill.price, fill.fee);
+        let notional = signal.quantity * price;
+        
+        if matches!(side, OrderSide::Buy) && notional + fee > self.cash {
+            debug!("Backtest: insufficient balance for {} {}", signal.quantity, signal.symbol);
+            self.rejected_signals += 1;
+            return None;
+        }
+        
+        match side {
+            OrderSide::Buy => self.cash -= notional + fee,
+            OrderSide::Sell => self.cash += notional - fee,
+        }
+        
+        let realized_pnl = self
+            .positions
+            .entry((signal.strategy_name.clone(), signal.symbol.clone()))
+            .or_default()
+            .apply(&side, signal.quantity, price);
+        
+        for breakdown in [
+            self.breakdowns.entry(signal.strategy_name.clone()).or_default(),
+            self.symbol_breakdowns.entry(signal.symbol.clone()).or_default(),
+        ] {
+            breakdown.trades += 1;
+            breakdown.volume += notional;
+            breakdown.fees += fee;
+            breakdown.slippage += fill.slippage;
+            breakdown.realized_pnl += realized_pnl;
+        }
+        
+        self.trades.push(BacktestTrade {
+            timestamp: market_data.timestamp,
+            strategy: signal.strategy_name.clone(),
+            symbol: signal.symbol.clone(),
+            side,
+            quantity: signal.quantity,
+            price,
+            fee,
+            slippage: fill.slippage,
+            realized_pnl,
+        });
+        
+        Some(price)
+    }
+    
+    /// Settles funding due up to `until` on open positions, at the marks
+    /// they held before `until`'s bars moved them
+    fn accrue_funding(&mut self, until: DateTime<Utc>) {
+        let after = match self.funded_until.replace(until) {
+            Some(after) if after < until => after,
+            _ => return,
+        };
+        
+        for at in self.costs.funding_times(after, until) {
+            for ((strategy, symbol), position) in &self.positions {
+                if position.quantity.is_zero() {
+                    continue;
+                }
+                let payment = self.costs.funding_payment(symbol, position.quantity, self.mark(symbol), at);
+                self.cash -= payment;
+                self.breakdowns.entry(strategy.clone()).or_default().funding += payment;
+                self.symbol_breakdowns.entry(symbol.clone()).or_default().funding += payment;
+            }
+        }
+    }
+    
+    fn mark(&self, symbol: &str) -> Decimal {
+        self.marks.get(symbol).copied().unwrap_or(Decimal::ZERO)
+    }
+    
+    fn equity(&self) -> Decimal {
+        self.cash
+            + self
+                .positions
+                .iter()
+                .map(|((_, symbol), position)| position.quantity * self.mark(symbol))
+                .sum::<Decimal>()
+    }
+    
+    /// The account as the risk manager would see it live, with positions
+    /// netted per symbol across strategies
+    fn account_info(&mut self, timestamp: DateTime<Utc>) -> AccountInfo {
+        let equity = self.equity();
+        let today = timestamp.date_naive();
+        let day_start = match self.day_start {
+            Some((day, day_start)) if day == today => day_start,
+            _ => {
+                self.day_start = Some((today, equity));
+                equity
+            }
+        };
+        
+        let mut netted: BTreeMap<&str, (Decimal, Decimal)> = BTreeMap::new();
+        for ((_, symbol), position) in &self.positions {
+            let (quantity, cost) = netted.entry(symbol.as_str()).or_default();
+            *quantity += position.quantity;
+            *cost += position.quantity * position.average_price;
+        }
+        
+        let positions = netted
+            .into_iter()
+            .filter(|(_, (quantity, _))| !quantity.is_zero())
+            .map(|(symbol, (quantity, cost))| {
+                let mark = self.mark(symbol);
+                Position {
+                    symbol: symbol.to_string(),
+                    side: if quantity.is_sign_positive() { PositionSide::Long } else { PositionSide::Short },
+                    size: quantity.abs(),
+                    entry_price: cost / quantity,
+                    current_price: mark,
+                    unrealized_pnl: quantity * mark - cost,
+                    realized_pnl: Decimal::ZERO,
+                    margin: Decimal::ZERO,
+                    timestamp,
+                }
+            })
+            .collect();
+        
+        AccountInfo {
+            balance: equity,
+            available_balance: self.cash,
+            total_pnl: equity - day_start,
+            total_margin: Decimal::ZERO,
+            positions,
+            open_orders: Vec::new(),
+        }
+    }
+    
+    fn in_market(&self) -> bool {
+        self.positions.values().any(|position| !position.quantity.is_zero())
+    }
+    
+    fn sample(&self, timestamp: DateTime<Utc>) -> EquitySample {
+        let equity = self.equity();
+        EquitySample {
+            timestamp,
+            equity,
+            available_balance: self.cash,
+            total_pnl: equity - self.config.initial_balance,
+        }
+    }
+}
+
+/// Runs strategies over historical market data against a simulated account,
+/// through the same hooks and risk checks as live trading. Strategies are
+/// driven in name order and data in timestamp order, so the same inputs
+/// always produce the same result.
+pub struct Backtester {
+    config: BacktestConfig,
+    costs: CostModel,
+    strategies: Vec<Box<dyn Strategy + Send + Sync>>,
+    risk_manager: Option<RiskManager>,
+}
+
+impl Backtester {
+    pub fn new(config: BacktestConfig, mut strategies: Vec<Box<dyn Strategy + Send + Sync>>) -> Self {
+        strategies.sort_by(|a, b| a.name().cmp(b.name()));
+        Self {
+            config,
+            costs: CostModel::default(),
+            strategies,
+            risk_manager: None,
+        }
+    }
+    
+    /// Fees, slippage and funding to simulate; defaults to `CostModel::default()`
+    pub fn with_cost_model(mut self, costs: CostModel) -> Self {
+        self.costs = costs;
+        self
+    }
+    
+    /// Gates every bar and signal on `risk_manager` as a live cycle would
+    pub fn with_risk_manager(mut self, risk_manager: RiskManager) -> Self {
+        self.risk_manager = Some(risk_manager);
+        self
+    }
+    
+    /// Builds every enabled strategy in the config
+    pub async fn from_config(config: &Config) -> Result<Self> {
+        let mut strategies = Vec::new();
+        
+        for (name, strategy_config) in &config.strategies {
+            if !strategy_config.enabled {
+                continue;
+            }
+            let strategy = crate::strategies::create(name, strategy_config, config).await?.ok_or_else(|| {
+                Error::Config(format!("Unknown strategy type for {}: {}", name, strategy_config.strategy_type))
+            })?;
+            strategies.push(strategy);
+        }
+        
+        if strategies.is_empty() {
+            return Err(Error::Config("No enabled strategies to backtest".to_string()));
+        }
+        
+        let risk_manager = RiskManager::new(config.risk_management.clone(), MarketSchedule::new(config.schedule.clone()));
+        Ok(Self::new(config.backtest.clone(), strategies)
+            .with_cost_model(CostModel::from_config(config))
+            .with_risk_manager(risk_manager))
+    }
+    
+    pub fn symbols(&self) -> Vec<String> {
+        let mut symbols: Vec<String> = self.strategies.iter().map(|s| s.symbol().to_string()).collect();
+        symbols.sort();
+        symbols.dedup();
+        symbols
+    }
+    
+    /// Hands each strategy the first `warmup_bars` bars of its symbol and
+    /// returns the bars left to simulate
+    fn warm_up(&mut self, data: Vec<MarketData>) -> Vec<MarketData> {
+        if self.config.warmup_bars == 0 {
+            return data;
+        }
+        
+        let mut history: HashMap<String, Vec<MarketData>> = HashMap::new();
+        let mut rest = Vec::new();
+        for market_data in data {
+            let bars = history.entry(market_data.symbol.clone()).or_default();
+            if bars.len() < self.config.warmup_bars {
+                bars.push(market_data);
+            } else {
+                rest.push(market_data);
+            }
+        }
+        
+        for strategy in self.strategies.iter_mut() {
+            if let Some(bars) = history.get(strategy.symbol()) {
+                strategy.warm_up(bars);
+            }
+        }
+        rest
+    }
+    
+    pub async fn run(mut self, mut data: Vec<MarketData>) -> Result<BacktestResult> {
+        if data.is_empty() {
+            return Err(Error::InvalidInput("No market data to backtest".to_string()));
+        }
+        
+        data.sort_by(|a, b| a.timestamp.cmp(&b.timestamp).then_with(|| a.symbol.cmp(&b.symbol)));
+        let data = self.warm_up(data);
+        if data.is_empty() {
+            return Err(Error::InvalidInput(format!(
+                "No market data left after {} warm-up bars",
+                self.config.warmup_bars
+            )));
+        }
+        info!("🧪 Backtesting {} strategies over {} bars", self.strategies.len(), data.len());
+        
+        let mut account = SimAccount::new(self.config.clone(), self.costs.clone());
+        let mut equity_curve = Vec::new();
+        let mut exposed_samples = 0;
+        
+        for (index, market_data) in data.iter().enumerate() {
+            account.accrue_funding(market_data.timestamp);
+            account.marks.insert(market_data.symbol.clone(), market_data.price);
+            
+            // Bars a live cycle would sit out still reach on_market_data
+            let paused = match &self.risk_manager {
+                Some(risk_manager) => match risk_manager.trading_paused(market_data.timestamp) {
+                    Some(reason) => {
+                        debug!("Backtest: trading paused at {}: {}", market_data.timestamp, reason);
+                        true
+                    }
+                    None => !risk_manager.check_risk_limits(&account.account_info(market_data.timestamp)).await?,
+                },
+                None => false,
+            };
+            
+            for strategy in self.strategies.iter_mut() {
+                if !strategy.is_enabled() || strategy.symbol() != market_data.symbol {
+                    continue;
+                }
+                
+                strategy.on_market_data(market_data);
+                if paused {
+                    continue;
+                }
+                if let Some(signal) = strategy.analyze(market_data).await? {
+                    if let (Some(risk_manager), SignalAction::Buy | SignalAction::Sell) = (&self.risk_manager, &signal.action) {
+                        let account_info = account.account_info(market_data.timestamp);
+                        if !risk_manager.check_signal_risk(&signal, &account_info).await? {
+                            account.rejected_signals += 1;
+                            continue;
+                        }
+                    }
+                    if let Some(fill_price) = account.execute(&signal, market_data) {
+                        strategy.on_fill(&signal, fill_price, market_data.timestamp);
+                    }
+                }
+            }
+            
+            // One equity sample per timestamp, after every symbol has moved
+            let last_at_timestamp = data
+                .get(index + 1)
+                .is_none_or(|next| next.timestamp != market_data.timestamp);
+            if last_at_timestamp {
+                equity_curve.push(account.sample(market_data.timestamp));
+                if account.in_market() {
+                    exposed_samples += 1;
+                }
+            }
+        }
+        
+        for ((strategy, symbol), position) in &account.positions {
+            let unrealized = position.quantity * (account.mark(symbol) - position.average_price);
+            account.breakdowns.entry(strategy.clone()).or_default().unrealized_pnl += unrealized;
+            account.symbol_breakdowns.entry(symbol.clone()).or_default().unrealized_pnl += unrealized;
+        }
+        
+        Ok(BacktestResult {
+            initial_balance: self.config.initial_balance,
+            final_equity: account.equity(),
+            trades: account.trades,
+            equity_curve,
+            strategies: account.breakdowns,
+            symbols: account.symbol_breakdowns,
+            rejected_signals: account.rejected_signals,
+            exposed_samples,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BacktestResult {
+    pub initial_balance: Decimal,
+    pub final_equity: Decimal,
+    pub trades: Vec<BacktestTrade>,
+    pub equity_curve: Vec<EquitySample>,
+    pub strategies: BTreeMap<String, StrategyBreakdown>,
+    /// The same figures per symbol, across strategies
+    pub symbols: BTreeMap<String, StrategyBreakdown>,
+    pub rejected_signals: u64,
+    /// Equity samples taken while any position was open
+    pub exposed_samples: usize,
+}
+
+impl BacktestResult {
+    pub fn net_pnl(&self) -> Decimal {
+        self.final_equity - self.initial_balance
+    }
+    
+    pub fn total_fees(&self) -> Decimal {
+        self.trades.iter().map(|t| t.fee).sum()
+    }
+    
+    pub fn total_slippage(&self) -> Decimal {
+        self.trades.iter().map(|t| t.slippage).sum()
+    }
+    
+    /// Funding paid over the run, negative when received
+    pub fn total_funding(&self) -> Decimal {
+        self.strategies.values().map(|b| b.funding).sum()
+    }
+    
+    /// PnL before fees, slippage and funding
+    pub fn gross_pnl(&self) -> Decimal {
+        self.net_pnl() + self.total_fees() + self.total_slippage() + self.total_funding()
+    }
+    
+    /// Largest peak-to-trough decline, as a fraction
+    pub fn max_drawdown(&self) -> Decimal {
+        equity::max_drawdown(&self.equity_curve)
+    }
+    
+    /// Annualized from daily closes of the equity curve
+    pub fn sharpe_ratio(&self) -> f64 {
+        equity::sharpe_ratio(&self.equity_curve)
+    }
+    
+    /// Writes the terminal report; see [`BacktestReport`] for JSON and HTML
+    pub fn write_report<W: Write>(&self, writer: W) -> Result<()> {
+        BacktestReport::new(self).write_text(writer)
+    }
+    
+    pub fn write_trades_csv<W: Write>(&self, mut writer: W) -> Result<()> {
+        writeln!(writer, "timestamp,strategy,symbol,side,quantity,price,fee,slippage,realized_pnl")?;
+        for trade in &self.trades {
+            writeln!(
+                writer,
+                "{},{},{},{:?},{},{},{},{},{}",
+                trade.timestamp.to_rfc3339(),
+                trade.strategy,
+                trade.symbol,
+                trade.side,
+                trade.quantity,
+                trade.price,
+                trade.fee,
+                trade.slippage,
+                trade.realized_pnl
+            )?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
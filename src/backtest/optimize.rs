@@ -0,0 +1,442 @@
+use super::{BacktestResult, Backtester};
+use crate::{
+    config::{Config, StrategyConfig},
+    costs::CostModel,
+    error::{Error, Result},
+    metrics::decimal_to_f64,
+    models::MarketData,
+    utils::{format_currency, format_percentage},
+};
+use config::{Config as ConfigFile, File, FileFormat};
+use futures_util::{stream, StreamExt};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::io::Write;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// Grids larger than this are refused unless the limit is raised
+pub const DEFAULT_MAX_COMBINATIONS: usize = 1000;
+
+/// Values to try for one strategy parameter
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ParameterRange {
+    /// Exactly these values, e.g. `lookback_period = [10, 20, 50]`
+    Values(Vec<Value>),
+    /// `min`, `min + step`, ... up to and including `max`
+    Steps { min: Decimal, max: Decimal, step: Decimal },
+}
+
+impl ParameterRange {
+    /// Expands the range, typing stepped values like `current` (the
+    /// parameter's configured value) so strategies parse them as usual
+    fn values(&self, name: &str, current: Option<&Value>) -> Result<Vec<Value>> {
+        let (min, max, step) = match self {
+            ParameterRange::Values(values) => return Ok(values.clone()),
+            ParameterRange::Steps { min, max, step } => (*min, *max, *step),
+        };
+        if step <= Decimal::ZERO {
+            return Err(Error::InvalidInput(format!("{}: step must be positive", name)));
+        }
+        if min > max {
+            return Err(Error::InvalidInput(format!("{}: min {} is above max {}", name, min, max)));
+        }
+        
+        let mut values = Vec::new();
+        let mut value = min;
+        while value <= max {
+            values.push(typed_like(name, value.normalize(), current)?);
+            value += step;
+        }
+        Ok(values)
+    }
+}
+
+fn typed_like(name: &str, value: Decimal, current: Option<&Value>) -> Result<Value> {
+    match current {
+        Some(Value::Number(number)) if number.is_f64() => Ok(Value::from(value.to_f64().unwrap_or_default())),
+        Some(Value::Number(_)) => match value.to_i64() {
+            Some(integer) if value.fract().is_zero() => Ok(Value::from(integer)),
+            _ => Err(Error::InvalidInput(format!("{} takes whole numbers, got {}", name, value))),
+        },
+        _ => Ok(Value::String(value.to_string())),
+    }
+}
+
+/// Parameters to search over, by name
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ParameterSpace {
+    pub parameters: BTreeMap<String, ParameterRange>,
+}
+
+impl ParameterSpace {
+    /// Reads a TOML file of `name = [values]` or `name = { min, max, step }`
+    pub fn load(path: &Path) -> Result<Self> {
+        let space: Self = ConfigFile::builder()
+            .add_source(File::new(&path.display().to_string(), FileFormat::Toml))
+            .build()
+            .and_then(|file| file.try_deserialize())
+            .map_err(|e| Error::Config(format!("Failed to load parameter space {}: {}", path.display(), e)))?;
+        
+        if space.parameters.is_empty() {
+            return Err(Error::Config(format!("Parameter space {} is empty", path.display())));
+        }
+        Ok(space)
+    }
+    
+    pub fn with_values(mut self, name: &str, values: Vec<Value>) -> Self {
+        self.parameters.insert(name.to_string(), ParameterRange::Values(values));
+        self
+    }
+    
+    pub fn with_steps(mut self, name: &str, min: Decimal, max: Decimal, step: Decimal) -> Self {
+        self.parameters.insert(name.to_string(), ParameterRange::Steps { min, max, step });
+        self
+    }
+    
+    /// Every combination of values, varying the last parameter (by name)
+    /// fastest. Fails rather than expanding more than `max` combinations.
+    pub fn combinations(&self, current: &HashMap<String, Value>, max: usize) -> Result<Vec<BTreeMap<String, Value>>> {
+        let mut axes = Vec::new();
+        for (name, range) in &self.parameters {
+            let values = range.values(name, current.get(name))?;
+            if values.is_empty() {
+                return Err(Error::InvalidInput(format!("{} has no values to try", name)));
+            }
+            axes.push((name, values));
+        }
+        
+        let count = axes.iter().try_fold(1usize, |count, (_, values)| count.checked_mul(values.len()));
+        match count {
+            Some(count) if count <= max => {}
+            _ => {
+                return Err(Error::InvalidInput(format!(
+                    "Parameter space has {} combinations, more than the limit of {}",
+                    count.map(|c| c.to_string()).unwrap_or_else(|| "too many".to_string()),
+                    max
+                )))
+            }
+        }
+        
+        let mut combinations = vec![BTreeMap::new()];
+        for (name, values) in axes {
+            combinations = combinations
+                .into_iter()
+                .flat_map(|combination| {
+                    values.iter().map(move |value| {
+                        let mut combination = combination.clone();
+                        combination.insert(name.clone(), value.clone());
+                        combination
+                    })
+                })
+                .collect();
+        }
+        Ok(combinations)
+    }
+}
+
+/// What the optimizer ranks runs by, highest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Objective {
+    NetPnl,
+    Sharpe,
+    /// Return over max drawdown
+    #[serde(rename = "pnl-dd")]
+    PnlToDrawdown,
+}
+
+impl FromStr for Objective {
+    type Err = Error;
+    
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "net-pnl" | "pnl" => Ok(Objective::NetPnl),
+            "sharpe" => Ok(Objective::Sharpe),
+            "pnl-dd" | "pnl-to-drawdown" => Ok(Objective::PnlToDrawdown),
+            other => Err(Error::InvalidInput(format!("Unknown objective: {}", other))),
+        }
+    }
+}
+
+impl fmt::Display for Objective {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Objective::NetPnl => "net-pnl",
+            Objective::Sharpe => "sharpe",
+            Objective::PnlToDrawdown => "pnl-dd",
+        })
+    }
+}
+
+impl Objective {
+    pub fn score(&self, result: &BacktestResult) -> f64 {
+        let score = match self {
+            Objective::NetPnl => decimal_to_f64(result.net_pnl()),
+            Objective::Sharpe => result.sharpe_ratio(),
+            Objective::PnlToDrawdown => {
+                let total_return = if result.initial_balance.is_zero() {
+                    0.0
+                } else {
+                    decimal_to_f64(result.net_pnl() / result.initial_balance)
+                };
+                let drawdown = decimal_to_f64(result.max_drawdown());
+                if drawdown > 0.0 {
+                    total_return / drawdown
+                } else if total_return > 0.0 {
+                    // Made money without ever giving any back
+                    f64::INFINITY
+                } else {
+                    total_return
+                }
+            }
+        };
+        // Runs without a defined score rank last
+        if score.is_nan() {
+            f64::NEG_INFINITY
+        } else {
+            score
+        }
+    }
+}
+
+/// One backtest of the grid
+#[derive(Debug, Clone, Serialize)]
+pub struct OptimizationRun {
+    pub parameters: BTreeMap<String, Value>,
+    pub score: f64,
+    pub net_pnl: Decimal,
+    pub sharpe_ratio: f64,
+    pub max_drawdown: Decimal,
+    pub trades: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OptimizationResult {
+    pub objective: Objective,
+    /// Best first; ties keep the order of the grid
+    pub runs: Vec<OptimizationRun>,
+    /// Combinations whose backtest failed, with the error
+    pub failed: Vec<(BTreeMap<String, Value>, String)>,
+}
+
+impl OptimizationResult {
+    pub fn best(&self) -> Option<&OptimizationRun> {
+        self.runs.first()
+    }
+    
+    /// `base` with the best run's parameters applied
+    pub fn best_config(&self, base: &StrategyConfig) -> Option<StrategyConfig> {
+        let best = self.best()?;
+        let mut config = base.clone();
+        config.parameters.extend(best.parameters.clone());
+        Some(config)
+    }
+    
+    /// Writes the top `limit` runs as a table
+    pub fn write_table<W: Write>(&self, mut writer: W, limit: usize) -> Result<()> {
+        writeln!(writer, "{} runs ranked by {}", self.runs.len(), self.objective)?;
+        writeln!(writer, "{:<5} {:>12} {:>14} {:>8} {:>9} {:>7}  Parameters", "Rank", "Score", "Net PnL", "Sharpe", "Max DD", "Trades")?;
+        for (rank, run) in self.runs.iter().take(limit).enumerate() {
+            let parameters: Vec<String> = run.parameters.iter().map(|(name, value)| format!("{}={}", name, value)).collect();
+            writeln!(
+                writer,
+                "{:<5} {:>12.4} {:>14} {:>8.2} {:>9} {:>7}  {}",
+                rank + 1,
+                run.score,
+                format_currency(run.net_pnl),
+                run.sharpe_ratio,
+                format_percentage(run.max_drawdown * Decimal::from(100)),
+                run.trades,
+                parameters.join(", ")
+            )?;
+        }
+        if self.runs.len() > limit {
+            writeln!(writer, "... {} more", self.runs.len() - limit)?;
+        }
+        for (parameters, error) in &self.failed {
+            let parameters: Vec<String> = parameters.iter().map(|(name, value)| format!("{}={}", name, value)).collect();
+            writeln!(writer, "⚠️ failed: {} ({})", parameters.join(", "), error)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Writes `config` as a `[strategies.<name>]` table to paste into a config file
+pub fn write_strategy_toml<W: Write>(mut writer: W, name: &str, config: &StrategyConfig) -> Result<()> {
+    let mut parameters = Vec::new();
+    let sorted: BTreeMap<&String, &Value> = config.parameters.iter().collect();
+    for (key, value) in sorted {
+        // JSON scalars and arrays of them are valid TOML as written
+        if value.is_object() || value.is_null() {
+            return Err(Error::InvalidInput(format!("Can't write parameter {} as TOML: {}", key, value)));
+        }
+        parameters.push(format!("{} = {}", key, value));
+    }
+    
+    writeln!(writer, "[strategies.{}]", name)?;
+    writeln!(writer, "enabled = {}", config.enabled)?;
+    writeln!(writer, "strategy_type = {}", Value::from(config.strategy_type.as_str()))?;
+    writeln!(writer, "symbol = {}", Value::from(config.symbol.as_str()))?;
+    writeln!(writer, "position_size = {}", config.position_size)?;
+    writeln!(writer, "parameters = {{ {} }}", parameters.join(", "))?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Grid search over one strategy's parameters. Every combination is
+/// backtested on its own account with only that strategy enabled, sharing
+/// the loaded market data.
+pub struct Optimizer {
+    config: Config,
+    strategy: String,
+    objective: Objective,
+    workers: usize,
+    max_combinations: usize,
+    costs: Option<CostModel>,
+}
+
+impl Optimizer {
+    pub fn new(config: Config, strategy: &str) -> Result<Self> {
+        if !config.strategies.contains_key(strategy) {
+            return Err(Error::Config(format!("Unknown strategy: {}", strategy)));
+        }
+        let workers = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+        Ok(Self {
+            config,
+            strategy: strategy.to_string(),
+            objective: Objective::NetPnl,
+            workers,
+            max_combinations: DEFAULT_MAX_COMBINATIONS,
+            costs: None,
+        })
+    }
+    
+    pub fn with_objective(mut self, objective: Objective) -> Self {
+        self.objective = objective;
+        self
+    }
+    
+    /// Backtests run at once; defaults to the number of CPUs
+    pub fn with_workers(mut self, workers: usize) -> Self {
+        self.workers = workers.max(1);
+        self
+    }
+    
+    pub fn with_max_combinations(mut self, max_combinations: usize) -> Self {
+        self.max_combinations = max_combinations;
+        self
+    }
+    
+    /// Replaces the config's cost model in every run, e.g. to add recorded
+    /// funding rates
+    pub fn with_cost_model(mut self, costs: CostModel) -> Self {
+        self.costs = Some(costs);
+        self
+    }
+    
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+    
+    pub fn objective(&self) -> Objective {
+        self.objective
+    }
+    
+    pub fn strategy_config(&self) -> &StrategyConfig {
+        &self.config.strategies[&self.strategy]
+    }
+    
+    /// The config for one combination: only the target strategy, enabled,
+    /// with `parameters` applied
+    pub fn config_for(&self, parameters: &BTreeMap<String, Value>) -> Config {
+        let mut config = self.config.clone();
+        config.strategies.retain(|name, _| *name == self.strategy);
+        if let Some(strategy) = config.strategies.get_mut(&self.strategy) {
+            strategy.enabled = true;
+            strategy.parameters.extend(parameters.clone());
+        }
+        config
+    }
+    
+    /// Backtests one combination on its own
+    pub async fn backtest(&self, parameters: &BTreeMap<String, Value>, data: Vec<MarketData>) -> Result<BacktestResult> {
+        backtest(&self.config_for(parameters), self.costs.clone(), data).await
+    }
+    
+    pub async fn run(&self, space: &ParameterSpace, data: Vec<MarketData>) -> Result<OptimizationResult> {
+        let combinations = space.combinations(&self.strategy_config().parameters, self.max_combinations)?;
+        let total = combinations.len();
+        info!(
+            "🔬 Optimizing {} over {} combinations with {} workers",
+            self.strategy,
+            total,
+            self.workers.min(total)
+        );
+        
+        let data = Arc::new(data);
+        let mut outcomes = stream::iter(combinations.into_iter().enumerate())
+            .map(|(index, parameters)| {
+                let config = self.config_for(&parameters);
+                let costs = self.costs.clone();
+                let data = data.clone();
+                let objective = self.objective;
+                tokio::spawn(async move {
+                    let outcome = backtest(&config, costs, data.to_vec()).await.map(|result| OptimizationRun {
+                        score: objective.score(&result),
+                        net_pnl: result.net_pnl(),
+                        sharpe_ratio: result.sharpe_ratio(),
+                        max_drawdown: result.max_drawdown(),
+                        trades: result.trades.len(),
+                        parameters: parameters.clone(),
+                    });
+                    (index, parameters, outcome)
+                })
+            })
+            .buffer_unordered(self.workers);
+        
+        let mut runs = Vec::new();
+        let mut failed = Vec::new();
+        let mut done = 0;
+        while let Some(outcome) = outcomes.next().await {
+            let (index, parameters, outcome) =
+                outcome.map_err(|e| Error::Strategy(format!("Optimizer run panicked: {}", e)))?;
+            done += 1;
+            match outcome {
+                Ok(run) => {
+                    info!("🔬 [{}/{}] score {:.4}", done, total, run.score);
+                    runs.push((index, run));
+                }
+                Err(e) => {
+                    warn!("🔬 [{}/{}] failed: {}", done, total, e);
+                    failed.push((index, parameters, e.to_string()));
+                }
+            }
+        }
+        
+        runs.sort_by(|(a_index, a), (b_index, b)| b.score.total_cmp(&a.score).then(a_index.cmp(b_index)));
+        failed.sort_by_key(|(index, _, _)| *index);
+        Ok(OptimizationResult {
+            objective: self.objective,
+            runs: runs.into_iter().map(|(_, run)| run).collect(),
+            failed: failed.into_iter().map(|(_, parameters, error)| (parameters, error)).collect(),
+        })
+    }
+}
+
+async fn backtest(config: &Config, costs: Option<CostModel>, data: Vec<MarketData>) -> Result<BacktestResult> {
+    let mut backtester = Backtester::from_config(config).await?;
+    if let Some(costs) = costs {
+        backtester = backtester.with_cost_model(costs);
+    }
+    backtester.run(data).await
+}
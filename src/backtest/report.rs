@@ -0,0 +1,564 @@
+use super::{BacktestResult, BacktestTrade, StrategyBreakdown};
+use crate::{
+    equity,
+    error::Result,
+    metrics::{decimal_to_f64, PerformanceWindow, Window},
+    storage::EquitySample,
+    utils::{format_currency, format_percentage},
+};
+use chrono::{DateTime, Datelike, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::Write;
+
+/// Best and worst trades listed in the report
+const TOP_TRADES: usize = 10;
+const SECONDS_PER_YEAR: f64 = 365.25 * 86_400.0;
+const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportSummary {
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+    pub initial_balance: Decimal,
+    pub final_equity: Decimal,
+    pub net_pnl: Decimal,
+    /// Before fees, slippage and funding
+    pub gross_pnl: Decimal,
+    /// Net PnL as a fraction of the initial balance
+    pub total_return: Decimal,
+    /// Compound annual growth rate over the equity curve's span
+    pub cagr: Option<f64>,
+    pub max_drawdown: Decimal,
+    pub max_drawdown_peak: Option<DateTime<Utc>>,
+    pub max_drawdown_trough: Option<DateTime<Utc>>,
+    pub sharpe_ratio: f64,
+    pub sortino_ratio: f64,
+    /// Gross profit over gross loss of closing trades; `None` without losses
+    pub profit_factor: Option<f64>,
+    /// Share of closing trades that realized a profit
+    pub win_rate: Option<f64>,
+    /// Share of equity samples taken with a position open
+    pub exposure: f64,
+    /// Traded volume over average equity
+    pub turnover: f64,
+    pub fees: Decimal,
+    pub slippage: Decimal,
+    pub funding: Decimal,
+    pub trades: usize,
+    /// Trades that realized PnL by reducing a position
+    pub closed_trades: usize,
+    pub rejected_signals: u64,
+}
+
+/// One strategy's or symbol's line in the breakdown tables
+#[derive(Debug, Clone, Serialize)]
+pub struct BreakdownRow {
+    pub name: String,
+    pub trades: u64,
+    pub volume: Decimal,
+    pub fees: Decimal,
+    pub slippage: Decimal,
+    pub funding: Decimal,
+    pub realized_pnl: Decimal,
+    pub unrealized_pnl: Decimal,
+    pub net_pnl: Decimal,
+    pub win_rate: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MonthlyReturn {
+    pub year: i32,
+    pub month: u32,
+    /// Change in equity over the month, as a fraction of where it began
+    pub change: Decimal,
+}
+
+/// Everything worth reading about a backtest, computed once from a
+/// [`BacktestResult`] and rendered as text, JSON or a standalone HTML page
+#[derive(Debug, Clone, Serialize)]
+pub struct BacktestReport {
+    pub summary: ReportSummary,
+    pub strategies: Vec<BreakdownRow>,
+    pub symbols: Vec<BreakdownRow>,
+    pub monthly_returns: Vec<MonthlyReturn>,
+    pub best_trades: Vec<BacktestTrade>,
+    pub worst_trades: Vec<BacktestTrade>,
+    #[serde(skip)]
+    equity_curve: Vec<EquitySample>,
+}
+
+impl BacktestReport {
+    pub fn new(result: &BacktestResult) -> Self {
+        let closed = closing_trades(result.trades.iter());
+        let start = result.equity_curve.first().map(|s| s.timestamp);
+        let end = result.equity_curve.last().map(|s| s.timestamp);
+        // Measured as of the simulation's end, not the wall clock
+        let stats = PerformanceWindow::from_equity_curve(&result.equity_curve)
+            .stats(Window::All, end.unwrap_or(DateTime::<Utc>::UNIX_EPOCH));
+        let drawdown_period = equity::max_drawdown_period(&result.equity_curve);
+        
+        let profit: Decimal = closed.iter().map(|t| t.realized_pnl).filter(|pnl| *pnl > Decimal::ZERO).sum();
+        let loss: Decimal = -closed.iter().map(|t| t.realized_pnl).filter(|pnl| *pnl < Decimal::ZERO).sum::<Decimal>();
+        
+        let samples = result.equity_curve.len();
+        let average_equity = if samples > 0 {
+            result.equity_curve.iter().map(|s| s.equity).sum::<Decimal>() / Decimal::from(samples)
+        } else {
+            Decimal::ZERO
+        };
+        let volume: Decimal = result.strategies.values().map(|b| b.volume).sum();
+        
+        let summary = ReportSummary {
+            start,
+            end,
+            initial_balance: result.initial_balance,
+            final_equity: result.final_equity,
+            net_pnl: result.net_pnl(),
+            gross_pnl: result.gross_pnl(),
+            total_return: if result.initial_balance > Decimal::ZERO {
+                result.net_pnl() / result.initial_balance
+            } else {
+                Decimal::ZERO
+            },
+            cagr: match (start, end) {
+                (Some(start), Some(end)) => cagr(result.initial_balance, result.final_equity, start, end),
+                _ => None,
+            },
+            max_drawdown: result.max_drawdown(),
+            max_drawdown_peak: drawdown_period.map(|(peak, _)| peak),
+            max_drawdown_trough: drawdown_period.map(|(_, trough)| trough),
+            sharpe_ratio: stats.sharpe_ratio,
+            sortino_ratio: stats.sortino_ratio,
+            profit_factor: (loss > Decimal::ZERO).then(|| decimal_to_f64(profit / loss)),
+            win_rate: win_rate(&closed),
+            exposure: if samples > 0 { result.exposed_samples as f64 / samples as f64 } else { 0.0 },
+            turnover: if average_equity > Decimal::ZERO { decimal_to_f64(volume / average_equity) } else { 0.0 },
+            fees: result.total_fees(),
+            slippage: result.total_slippage(),
+            funding: result.total_funding(),
+            trades: result.trades.len(),
+            closed_trades: closed.len(),
+            rejected_signals: result.rejected_signals,
+        };
+        
+        // Stable sort, so ties stay in the order they happened
+        let mut ranked = closed;
+        ranked.sort_by_key(|trade| Reverse(trade.realized_pnl));
+        let best_trades = ranked
+            .iter()
+            .copied()
+            .filter(|t| t.realized_pnl > Decimal::ZERO)
+            .take(TOP_TRADES)
+            .cloned()
+            .collect();
+        let worst_trades = ranked
+            .iter()
+            .rev()
+            .copied()
+            .filter(|t| t.realized_pnl < Decimal::ZERO)
+            .take(TOP_TRADES)
+            .cloned()
+            .collect();
+        
+        Self {
+            summary,
+            strategies: breakdown_rows(&result.strategies, &result.trades, |t| &t.strategy),
+            symbols: breakdown_rows(&result.symbols, &result.trades, |t| &t.symbol),
+            monthly_returns: monthly_returns(result.initial_balance, &result.equity_curve),
+            best_trades,
+            worst_trades,
+            equity_curve: result.equity_curve.clone(),
+        }
+    }
+    
+    /// Label and formatted value of each headline figure
+    fn summary_lines(&self) -> Vec<(&'static str, String)> {
+        let s = &self.summary;
+        let hundred = Decimal::from(100);
+        let percent = |fraction: f64| format!("{:.2}%", fraction * 100.0);
+        let date = |timestamp: DateTime<Utc>| timestamp.format("%Y-%m-%d %H:%M").to_string();
+        
+        let mut drawdown = format_percentage(s.max_drawdown * hundred);
+        if let (Some(peak), Some(trough)) = (s.max_drawdown_peak, s.max_drawdown_trough) {
+            drawdown = format!("{} ({} to {})", drawdown, date(peak), date(trough));
+        }
+        
+        vec![
+            ("Initial balance", format_currency(s.initial_balance)),
+            ("Final equity", format_currency(s.final_equity)),
+            ("Gross PnL", format_currency(s.gross_pnl)),
+            ("Fees paid", format_currency(s.fees)),
+            ("Slippage", format_currency(s.slippage)),
+            ("Funding paid", format_currency(s.funding)),
+            ("Net PnL", format!("{} ({})", format_currency(s.net_pnl), format_percentage(s.total_return * hundred))),
+            ("CAGR", s.cagr.map_or("n/a".to_string(), percent)),
+            ("Max drawdown", drawdown),
+            ("Sharpe ratio", format!("{:.2}", s.sharpe_ratio)),
+            ("Sortino ratio", format!("{:.2}", s.sortino_ratio)),
+            ("Profit factor", s.profit_factor.map_or("n/a".to_string(), |pf| format!("{:.2}", pf))),
+            (
+                "Win rate",
+                match s.win_rate {
+                    Some(rate) => format!("{} of {} closed trades", percent(rate), s.closed_trades),
+                    None => "n/a (no closed trades)".to_string(),
+                },
+            ),
+            ("Exposure", percent(s.exposure)),
+            ("Turnover", format!("{:.2}x", s.turnover)),
+            ("Trades", format!("{} ({} signals rejected)", s.trades, s.rejected_signals)),
+        ]
+    }
+    
+    pub fn write_text<W: Write>(&self, mut writer: W) -> Result<()> {
+        if let (Some(start), Some(end)) = (self.summary.start, self.summary.end) {
+            writeln!(
+                writer,
+                "Backtest {} to {} ({} samples)",
+                start.format("%Y-%m-%d %H:%M"),
+                end.format("%Y-%m-%d %H:%M"),
+                self.equity_curve.len()
+            )?;
+        }
+        for (label, value) in self.summary_lines() {
+            let label = format!("{}:", label);
+            writeln!(writer, "{:<18}{}", label, value)?;
+        }
+        
+        for (title, rows) in [("Strategy", &self.strategies), ("Symbol", &self.symbols)] {
+            writeln!(writer)?;
+            writeln!(
+                writer,
+                "{:<20} {:>7} {:>14} {:>12} {:>12} {:>12} {:>14} {:>14} {:>14}",
+                title, "Trades", "Volume", "Fees", "Slippage", "Funding", "Realized", "Unrealized", "Net"
+            )?;
+            for row in rows {
+                writeln!(
+                    writer,
+                    "{:<20} {:>7} {:>14} {:>12} {:>12} {:>12} {:>14} {:>14} {:>14}",
+                    row.name,
+                    row.trades,
+                    format_currency(row.volume),
+                    format_currency(row.fees),
+                    format_currency(row.slippage),
+                    format_currency(row.funding),
+                    format_currency(row.realized_pnl),
+                    format_currency(row.unrealized_pnl),
+                    format_currency(row.net_pnl)
+                )?;
+            }
+        }
+        
+        if !self.monthly_returns.is_empty() {
+            writeln!(writer)?;
+            write!(writer, "{:<6}", "Year")?;
+            for month in MONTHS.iter().chain(["Year"].iter()) {
+                write!(writer, " {:>8}", month)?;
+            }
+            writeln!(writer)?;
+            for (year, months) in self.years() {
+                write!(writer, "{:<6}", year)?;
+                for change in months.iter().chain([Some(year_change(&months))].iter()) {
+                    let cell = change.map_or(String::new(), |c| format_percentage(c * Decimal::from(100)));
+                    write!(writer, " {:>8}", cell)?;
+                }
+                writeln!(writer)?;
+            }
+        }
+        
+        for (title, trades) in [("Best trades", &self.best_trades), ("Worst trades", &self.worst_trades)] {
+            if trades.is_empty() {
+                continue;
+            }
+            writeln!(writer)?;
+            writeln!(writer, "{}:", title)?;
+            for trade in trades {
+                let side = format!("{:?}", trade.side);
+                writeln!(
+                    writer,
+                    "  {}  {:<20} {:<8} {:<4} {} @ {}  {}",
+                    trade.timestamp.format("%Y-%m-%d %H:%M"),
+                    trade.strategy,
+                    trade.symbol,
+                    side,
+                    trade.quantity,
+                    trade.price,
+                    format_currency(trade.realized_pnl)
+                )?;
+            }
+        }
+        
+        writer.flush()?;
+        Ok(())
+    }
+    
+    pub fn write_json<W: Write>(&self, mut writer: W) -> Result<()> {
+        serde_json::to_writer_pretty(&mut writer, self)?;
+        writeln!(writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+    
+    /// A single page with everything inline, safe to mail or archive
+    pub fn write_html<W: Write>(&self, mut writer: W) -> Result<()> {
+        writeln!(writer, "<!DOCTYPE html>")?;
+        writeln!(writer, "<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>Backtest report</title>")?;
+        writeln!(writer, "<style>{}</style>\n</head>\n<body>", STYLE)?;
+        writeln!(writer, "<h1>Backtest report</h1>")?;
+        if let (Some(start), Some(end)) = (self.summary.start, self.summary.end) {
+            writeln!(
+                writer,
+                "<p>{} to {}, {} samples</p>",
+                start.format("%Y-%m-%d %H:%M"),
+                end.format("%Y-%m-%d %H:%M"),
+                self.equity_curve.len()
+            )?;
+        }
+        
+        writeln!(writer, "<table class=\"summary\">")?;
+        for (label, value) in self.summary_lines() {
+            writeln!(writer, "<tr><th>{}</th><td>{}</td></tr>", label, escape(&value))?;
+        }
+        writeln!(writer, "</table>")?;
+        
+        writeln!(writer, "<h2>Equity and drawdown</h2>\n{}", self.chart_svg())?;
+        
+        for (title, rows) in [("Strategy", &self.strategies), ("Symbol", &self.symbols)] {
+            writeln!(writer, "<h2>By {}</h2>\n<table>", title.to_lowercase())?;
+            writeln!(
+                writer,
+                "<tr><th>{}</th><th>Trades</th><th>Volume</th><th>Fees</th><th>Slippage</th><th>Funding</th><th>Realized</th><th>Unrealized</th><th>Net</th><th>Win rate</th></tr>",
+                title
+            )?;
+            for row in rows {
+                writeln!(
+                    writer,
+                    "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td class=\"{}\">{}</td><td>{}</td></tr>",
+                    escape(&row.name),
+                    row.trades,
+                    format_currency(row.volume),
+                    format_currency(row.fees),
+                    format_currency(row.slippage),
+                    format_currency(row.funding),
+                    format_currency(row.realized_pnl),
+                    format_currency(row.unrealized_pnl),
+                    sign_class(row.net_pnl),
+                    format_currency(row.net_pnl),
+                    row.win_rate.map_or("n/a".to_string(), |rate| format!("{:.2}%", rate * 100.0))
+                )?;
+            }
+            writeln!(writer, "</table>")?;
+        }
+        
+        if !self.monthly_returns.is_empty() {
+            writeln!(writer, "<h2>Monthly returns</h2>\n<table>\n<tr><th>Year</th>")?;
+            for month in MONTHS.iter().chain(["Year"].iter()) {
+                write!(writer, "<th>{}</th>", month)?;
+            }
+            writeln!(writer, "</tr>")?;
+            for (year, months) in self.years() {
+                write!(writer, "<tr><th>{}</th>", year)?;
+                for change in months.iter().chain([Some(year_change(&months))].iter()) {
+                    match change {
+                        Some(c) => write!(writer, "<td class=\"{}\">{}</td>", sign_class(*c), format_percentage(c * Decimal::from(100)))?,
+                        None => write!(writer, "<td></td>")?,
+                    }
+                }
+                writeln!(writer, "</tr>")?;
+            }
+            writeln!(writer, "</table>")?;
+        }
+        
+        for (title, trades) in [("Best trades", &self.best_trades), ("Worst trades", &self.worst_trades)] {
+            if trades.is_empty() {
+                continue;
+            }
+            writeln!(writer, "<h2>{}</h2>\n<table>", title)?;
+            writeln!(writer, "<tr><th>Time</th><th>Strategy</th><th>Symbol</th><th>Side</th><th>Quantity</th><th>Price</th><th>Realized</th></tr>")?;
+            for trade in trades {
+                writeln!(
+                    writer,
+                    "<tr><td>{}</td><td>{}</td><td>{}</td><td>{:?}</td><td>{}</td><td>{}</td><td class=\"{}\">{}</td></tr>",
+                    trade.timestamp.format("%Y-%m-%d %H:%M"),
+                    escape(&trade.strategy),
+                    escape(&trade.symbol),
+                    trade.side,
+                    trade.quantity,
+                    trade.price,
+                    sign_class(trade.realized_pnl),
+                    format_currency(trade.realized_pnl)
+                )?;
+            }
+            writeln!(writer, "</table>")?;
+        }
+        
+        writeln!(writer, "</body>\n</html>")?;
+        writer.flush()?;
+        Ok(())
+    }
+    
+    /// Monthly changes laid out by calendar year
+    fn years(&self) -> BTreeMap<i32, [Option<Decimal>; 12]> {
+        let mut years: BTreeMap<i32, [Option<Decimal>; 12]> = BTreeMap::new();
+        for month in &self.monthly_returns {
+            years.entry(month.year).or_default()[month.month as usize - 1] = Some(month.change);
+        }
+        years
+    }
+    
+    /// Equity line above a drawdown area, as inline SVG
+    fn chart_svg(&self) -> String {
+        const WIDTH: f64 = 800.0;
+        const EQUITY_HEIGHT: f64 = 240.0;
+        const DRAWDOWN_TOP: f64 = 260.0;
+        const DRAWDOWN_HEIGHT: f64 = 100.0;
+        
+        let equity: Vec<f64> = self.equity_curve.iter().map(|s| decimal_to_f64(s.equity)).collect();
+        if equity.is_empty() {
+            return "<p>No equity samples.</p>".to_string();
+        }
+        
+        let mut peak = f64::MIN;
+        let drawdowns: Vec<f64> = equity
+            .iter()
+            .map(|e| {
+                peak = peak.max(*e);
+                if peak > 0.0 { (peak - e) / peak } else { 0.0 }
+            })
+            .collect();
+        
+        let low = equity.iter().copied().fold(f64::MAX, f64::min);
+        let high = equity.iter().copied().fold(f64::MIN, f64::max);
+        let range = if high > low { high - low } else { 1.0 };
+        let deepest = drawdowns.iter().copied().fold(0.0, f64::max);
+        let depth = if deepest > 0.0 { deepest } else { 1.0 };
+        let x = |i: usize| if equity.len() > 1 { i as f64 / (equity.len() - 1) as f64 * WIDTH } else { WIDTH / 2.0 };
+        
+        let mut line = String::new();
+        for (i, e) in equity.iter().enumerate() {
+            let y = EQUITY_HEIGHT - (e - low) / range * EQUITY_HEIGHT;
+            let _ = write!(line, "{}{:.1},{:.1} ", if i == 0 { "M" } else { "L" }, x(i), y);
+        }
+        let mut area = format!("M0.0,{:.1} ", DRAWDOWN_TOP);
+        for (i, d) in drawdowns.iter().enumerate() {
+            let _ = write!(area, "L{:.1},{:.1} ", x(i), DRAWDOWN_TOP + d / depth * DRAWDOWN_HEIGHT);
+        }
+        let _ = write!(area, "L{:.1},{:.1} Z", WIDTH, DRAWDOWN_TOP);
+        
+        format!(
+            concat!(
+                "<svg class=\"chart\" viewBox=\"-60 -10 {w} {h}\" xmlns=\"http://www.w3.org/2000/svg\">\n",
+                "<text x=\"-6\" y=\"4\" text-anchor=\"end\">{high:.2}</text>\n",
+                "<text x=\"-6\" y=\"{eh}\" text-anchor=\"end\">{low:.2}</text>\n",
+                "<text x=\"-6\" y=\"{dl}\" text-anchor=\"end\">-{dd:.2}%</text>\n",
+                "<path d=\"{line}\" fill=\"none\" stroke=\"#2563eb\" stroke-width=\"1.5\"/>\n",
+                "<path d=\"{area}\" fill=\"#dc2626\" fill-opacity=\"0.3\" stroke=\"#dc2626\" stroke-width=\"1\"/>\n",
+                "</svg>"
+            ),
+            w = WIDTH + 70.0,
+            h = DRAWDOWN_TOP + DRAWDOWN_HEIGHT + 20.0,
+            high = high,
+            low = low,
+            eh = EQUITY_HEIGHT,
+            dl = DRAWDOWN_TOP + DRAWDOWN_HEIGHT,
+            dd = deepest * 100.0,
+            line = line.trim_end(),
+            area = area,
+        )
+    }
+}
+
+const STYLE: &str = "body{font-family:system-ui,sans-serif;margin:2em auto;max-width:1000px;color:#111}\
+table{border-collapse:collapse;margin-bottom:1.5em}\
+th,td{padding:4px 10px;border-bottom:1px solid #ddd;text-align:right}\
+th:first-child,td:first-child{text-align:left}\
+table.summary td{text-align:left}\
+.positive{color:#15803d}.negative{color:#b91c1c}\
+.chart{width:100%;height:auto;font-size:11px}";
+
+/// Trades that reduced a position, which are the ones that realize PnL
+fn closing_trades<'a>(trades: impl Iterator<Item = &'a BacktestTrade>) -> Vec<&'a BacktestTrade> {
+    trades.filter(|t| !t.realized_pnl.is_zero()).collect()
+}
+
+fn win_rate(closed: &[&BacktestTrade]) -> Option<f64> {
+    if closed.is_empty() {
+        return None;
+    }
+    let wins = closed.iter().filter(|t| t.realized_pnl > Decimal::ZERO).count();
+    Some(wins as f64 / closed.len() as f64)
+}
+
+fn breakdown_rows<F>(breakdowns: &BTreeMap<String, StrategyBreakdown>, trades: &[BacktestTrade], key: F) -> Vec<BreakdownRow>
+where
+    F: Fn(&BacktestTrade) -> &String,
+{
+    breakdowns
+        .iter()
+        .map(|(name, breakdown)| BreakdownRow {
+            name: name.clone(),
+            trades: breakdown.trades,
+            volume: breakdown.volume,
+            fees: breakdown.fees,
+            slippage: breakdown.slippage,
+            funding: breakdown.funding,
+            realized_pnl: breakdown.realized_pnl,
+            unrealized_pnl: breakdown.unrealized_pnl,
+            net_pnl: breakdown.net_pnl(),
+            win_rate: win_rate(&closing_trades(trades.iter().filter(|t| key(t) == name))),
+        })
+        .collect()
+}
+
+fn cagr(initial: Decimal, final_equity: Decimal, start: DateTime<Utc>, end: DateTime<Utc>) -> Option<f64> {
+    let years = (end - start).num_seconds() as f64 / SECONDS_PER_YEAR;
+    if years <= 0.0 || initial <= Decimal::ZERO {
+        return None;
+    }
+    let growth = decimal_to_f64(final_equity / initial);
+    Some(if growth > 0.0 { growth.powf(1.0 / years) - 1.0 } else { -1.0 })
+}
+
+/// Month-end equity against the previous month's, the first month against
+/// the initial balance
+fn monthly_returns(initial: Decimal, samples: &[EquitySample]) -> Vec<MonthlyReturn> {
+    let mut closes: BTreeMap<(i32, u32), Decimal> = BTreeMap::new();
+    for sample in samples {
+        closes.insert((sample.timestamp.year(), sample.timestamp.month()), sample.equity);
+    }
+    
+    let mut previous = initial;
+    closes
+        .into_iter()
+        .map(|((year, month), close)| {
+            let change = if previous > Decimal::ZERO { (close - previous) / previous } else { Decimal::ZERO };
+            previous = close;
+            MonthlyReturn { year, month, change }
+        })
+        .collect()
+}
+
+fn year_change(months: &[Option<Decimal>; 12]) -> Decimal {
+    months.iter().flatten().fold(Decimal::ONE, |growth, change| growth * (Decimal::ONE + change)) - Decimal::ONE
+}
+
+fn sign_class(value: Decimal) -> &'static str {
+    if value > Decimal::ZERO {
+        "positive"
+    } else if value < Decimal::ZERO {
+        "negative"
+    } else {
+        ""
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
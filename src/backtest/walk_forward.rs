@@ -0,0 +1,284 @@
+use super::{
+    optimize::{Objective, Optimizer, ParameterSpace},
+    BacktestResult,
+};
+use crate::{
+    config::StrategyConfig,
+    equity,
+    error::{Error, Result},
+    models::MarketData,
+    storage::EquitySample,
+    utils::{format_currency, format_percentage},
+};
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::io::Write;
+use tracing::info;
+
+/// Rolling train/test split: optimize on `train`, then trade the chosen
+/// parameters over the `test` period right after it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WalkForward {
+    pub train: Duration,
+    pub test: Duration,
+    /// How far each window moves on; defaults to `test`
+    pub step: Duration,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct WindowBounds {
+    pub train_start: DateTime<Utc>,
+    /// Also where training ends
+    pub test_start: DateTime<Utc>,
+    pub test_end: DateTime<Utc>,
+}
+
+impl WalkForward {
+    pub fn new(train: Duration, test: Duration) -> Self {
+        Self { train, test, step: test }
+    }
+    
+    pub fn with_step(mut self, step: Duration) -> Self {
+        self.step = step;
+        self
+    }
+    
+    /// Windows from `start` whose test period ends by `end`
+    pub fn windows(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<WindowBounds>> {
+        if self.train <= Duration::zero() || self.test <= Duration::zero() {
+            return Err(Error::InvalidInput("Walk-forward train and test lengths must be positive".to_string()));
+        }
+        // Overlapping test periods couldn't be chained into one equity curve
+        if self.step < self.test {
+            return Err(Error::InvalidInput("Walk-forward step must be at least the test length".to_string()));
+        }
+        
+        let mut windows = Vec::new();
+        let mut train_start = start;
+        while train_start + self.train + self.test <= end {
+            windows.push(WindowBounds {
+                train_start,
+                test_start: train_start + self.train,
+                test_end: train_start + self.train + self.test,
+            });
+            train_start += self.step;
+        }
+        
+        if windows.is_empty() {
+            return Err(Error::InvalidInput("Range is shorter than one train and test window".to_string()));
+        }
+        Ok(windows)
+    }
+}
+
+/// One window's chosen parameters and how they did out of sample
+#[derive(Debug, Clone, Serialize)]
+pub struct WalkForwardWindow {
+    pub bounds: WindowBounds,
+    pub parameters: BTreeMap<String, Value>,
+    /// Objective score on the training period
+    pub in_sample_score: f64,
+    /// Objective score on the test period
+    pub out_of_sample_score: f64,
+    pub net_pnl: Decimal,
+    /// Net PnL as a fraction of the initial balance
+    pub total_return: Decimal,
+    pub max_drawdown: Decimal,
+    pub trades: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WalkForwardResult {
+    pub objective: Objective,
+    pub initial_balance: Decimal,
+    pub windows: Vec<WalkForwardWindow>,
+    /// The test periods chained end to end, each starting from the equity
+    /// the previous one finished with
+    pub equity_curve: Vec<EquitySample>,
+}
+
+impl WalkForwardResult {
+    pub fn final_equity(&self) -> Decimal {
+        self.equity_curve.last().map(|s| s.equity).unwrap_or(self.initial_balance)
+    }
+    
+    pub fn net_pnl(&self) -> Decimal {
+        self.final_equity() - self.initial_balance
+    }
+    
+    pub fn total_return(&self) -> Decimal {
+        if self.initial_balance.is_zero() {
+            return Decimal::ZERO;
+        }
+        self.net_pnl() / self.initial_balance
+    }
+    
+    pub fn max_drawdown(&self) -> Decimal {
+        equity::max_drawdown(&self.equity_curve)
+    }
+    
+    pub fn sharpe_ratio(&self) -> f64 {
+        equity::sharpe_ratio(&self.equity_curve)
+    }
+    
+    pub fn trades(&self) -> usize {
+        self.windows.iter().map(|w| w.trades).sum()
+    }
+    
+    /// Windows that chose different parameters from the window before
+    pub fn parameter_changes(&self) -> usize {
+        self.windows.windows(2).filter(|pair| pair[0].parameters != pair[1].parameters).count()
+    }
+    
+    /// `base` with the latest window's parameters, the ones to trade next
+    pub fn latest_config(&self, base: &StrategyConfig) -> Option<StrategyConfig> {
+        let latest = self.windows.last()?;
+        let mut config = base.clone();
+        config.parameters.extend(latest.parameters.clone());
+        Some(config)
+    }
+    
+    pub fn write_table<W: Write>(&self, mut writer: W) -> Result<()> {
+        let hundred = Decimal::from(100);
+        writeln!(writer, "Walk-forward over {} windows, optimizing {}", self.windows.len(), self.objective)?;
+        writeln!(
+            writer,
+            "{:<3} {:<10} {:<23} {:>10} {:>10} {:>9} {:>9} {:>7}  Parameters",
+            "#", "Train", "Test", "IS score", "OOS score", "Return", "Max DD", "Trades"
+        )?;
+        for (index, window) in self.windows.iter().enumerate() {
+            let bounds = &window.bounds;
+            let test = format!("{} to {}", bounds.test_start.format("%Y-%m-%d"), bounds.test_end.format("%Y-%m-%d"));
+            let parameters: Vec<String> = window.parameters.iter().map(|(name, value)| format!("{}={}", name, value)).collect();
+            writeln!(
+                writer,
+                "{:<3} {:<10} {:<23} {:>10.4} {:>10.4} {:>9} {:>9} {:>7}  {}",
+                index + 1,
+                bounds.train_start.format("%Y-%m-%d"),
+                test,
+                window.in_sample_score,
+                window.out_of_sample_score,
+                format_percentage(window.total_return * hundred),
+                format_percentage(window.max_drawdown * hundred),
+                window.trades,
+                parameters.join(", ")
+            )?;
+        }
+        
+        writeln!(writer)?;
+        writeln!(
+            writer,
+            "Out of sample:    {} ({}), max drawdown {}, Sharpe {:.2}, {} trades",
+            format_currency(self.net_pnl()),
+            format_percentage(self.total_return() * hundred),
+            format_percentage(self.max_drawdown() * hundred),
+            self.sharpe_ratio(),
+            self.trades()
+        )?;
+        writeln!(
+            writer,
+            "Parameters changed in {} of {} windows",
+            self.parameter_changes(),
+            self.windows.len().saturating_sub(1)
+        )?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+impl Optimizer {
+    /// Optimizes on each training period and backtests the winner on the
+    /// test period after it. Every period also gets up to
+    /// `backtest.warmup_bars` earlier bars to warm up on.
+    pub async fn walk_forward(
+        &self,
+        space: &ParameterSpace,
+        mut data: Vec<MarketData>,
+        walk_forward: &WalkForward,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<WalkForwardResult> {
+        let windows = walk_forward.windows(start, end)?;
+        let symbol = self.strategy_config().symbol.clone();
+        data.retain(|bar| bar.symbol == symbol);
+        data.sort_by_key(|bar| bar.timestamp);
+        let warmup = self.config().backtest.warmup_bars;
+        
+        let initial_balance = self.config().backtest.initial_balance;
+        let mut results = Vec::new();
+        let mut equity_curve = Vec::new();
+        for (index, bounds) in windows.iter().enumerate() {
+            info!(
+                "🔬 Window {}/{}: training from {}, testing {} to {}",
+                index + 1,
+                windows.len(),
+                bounds.train_start.format("%Y-%m-%d"),
+                bounds.test_start.format("%Y-%m-%d"),
+                bounds.test_end.format("%Y-%m-%d")
+            );
+            
+            let train = slice(&data, bounds.train_start, bounds.test_start, warmup);
+            let optimized = self.run(space, train).await?;
+            let best = optimized.best().ok_or_else(|| {
+                Error::Strategy(format!("No combination backtested on the training period from {}", bounds.train_start))
+            })?;
+            
+            let test = slice(&data, bounds.test_start, bounds.test_end, warmup);
+            let tested = self.backtest(&best.parameters, test).await.map_err(|e| {
+                Error::Strategy(format!("Test period from {} failed: {}", bounds.test_start, e))
+            })?;
+            chain(&mut equity_curve, initial_balance, &tested);
+            
+            results.push(WalkForwardWindow {
+                bounds: *bounds,
+                parameters: best.parameters.clone(),
+                in_sample_score: best.score,
+                out_of_sample_score: self.objective().score(&tested),
+                net_pnl: tested.net_pnl(),
+                total_return: return_of(&tested),
+                max_drawdown: tested.max_drawdown(),
+                trades: tested.trades.len(),
+            });
+        }
+        
+        Ok(WalkForwardResult {
+            objective: self.objective(),
+            initial_balance,
+            windows: results,
+            equity_curve,
+        })
+    }
+}
+
+/// Bars in `[from, to)` of sorted `data`, plus up to `warmup` bars before
+fn slice(data: &[MarketData], from: DateTime<Utc>, to: DateTime<Utc>, warmup: usize) -> Vec<MarketData> {
+    let first = data.partition_point(|bar| bar.timestamp < from);
+    let last = data.partition_point(|bar| bar.timestamp < to);
+    data[first.saturating_sub(warmup)..last].to_vec()
+}
+
+fn return_of(result: &BacktestResult) -> Decimal {
+    if result.initial_balance.is_zero() {
+        return Decimal::ZERO;
+    }
+    result.net_pnl() / result.initial_balance
+}
+
+/// Appends a test period's equity, rescaled to continue from the curve's end
+fn chain(curve: &mut Vec<EquitySample>, initial_balance: Decimal, result: &BacktestResult) {
+    if result.initial_balance.is_zero() {
+        return;
+    }
+    let start = curve.last().map(|s| s.equity).unwrap_or(initial_balance);
+    let scale = start / result.initial_balance;
+    
+    curve.extend(result.equity_curve.iter().map(|sample| EquitySample {
+        timestamp: sample.timestamp,
+        equity: sample.equity * scale,
+        available_balance: sample.available_balance * scale,
+        total_pnl: sample.equity * scale - initial_balance,
+    }));
+}
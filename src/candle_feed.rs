@@ -0,0 +1,139 @@
+use crate::{
+    api::types::{interval_duration, Candle, CandleUpdate},
+    backtest::data,
+    strategies::base::RingBuffer,
+};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+/// Closed candles kept per series
+pub const DEFAULT_CANDLE_WINDOW: usize = 500;
+
+/// One (symbol, interval)'s candles
+struct Series {
+    /// Oldest first
+    closed: RingBuffer<Candle>,
+    /// The candle still taking trades, last pushed
+    forming: Option<Candle>,
+}
+
+/// Closed candles per (symbol, interval), from the websocket's `candle`
+/// channel. The exchange pushes the forming candle again with every trade,
+/// so a candle only joins its series once one with a later open time
+/// arrives, and never changes after that. Series nobody asked to `track`
+/// are ignored.
+pub struct CandleFeed {
+    window: usize,
+    series: Mutex<BTreeMap<(String, String), Series>>,
+}
+
+impl CandleFeed {
+    /// Keeps the last `window` closed candles of each series
+    pub fn new(window: usize) -> Self {
+        Self {
+            window,
+            series: Mutex::new(BTreeMap::new()),
+        }
+    }
+    
+    pub fn window(&self) -> usize {
+        self.window
+    }
+    
+    /// Starts keeping `symbol`'s candles at `interval`
+    pub fn track(&self, symbol: &str, interval: &str) {
+        self.series
+            .lock()
+            .unwrap()
+            .entry((symbol.to_string(), interval.to_string()))
+            .or_insert_with(|| Series {
+                closed: RingBuffer::new(self.window),
+                forming: None,
+            });
+    }
+    
+    /// Every (symbol, interval) being kept
+    pub fn tracked(&self) -> Vec<(String, String)> {
+        self.series.lock().unwrap().keys().cloned().collect()
+    }
+    
+    /// Adds `candles`, oldest first (e.g. fetched over REST), that had
+    /// closed by `now` and are newer than the series' latest. Returns the
+    /// ones added.
+    pub fn seed(&self, symbol: &str, interval: &str, candles: Vec<Candle>, now: DateTime<Utc>) -> Vec<Candle> {
+        let Ok(duration) = interval_duration(interval) else {
+            return Vec::new();
+        };
+        let mut series = self.series.lock().unwrap();
+        let Some(series) = series.get_mut(&(symbol.to_string(), interval.to_string())) else {
+            return Vec::new();
+        };
+        
+        let mut added = Vec::new();
+        for candle in candles {
+            // Still forming
+            if data::candle_to_market_data(symbol, &candle, duration).timestamp > now {
+                continue;
+            }
+            if series.closed.last().is_some_and(|last| candle.t <= last.t) {
+                continue;
+            }
+            // Streamed while it was forming, and closed since
+            if series.forming.as_ref().is_some_and(|forming| forming.t <= candle.t) {
+                series.forming = None;
+            }
+            series.closed.push(candle.clone());
+            added.push(candle);
+        }
+        added
+    }
+    
+    /// Takes in a candle pushed by the websocket, returning the candle it
+    /// closed, if it's the first of a new one
+    pub fn update(&self, update: &CandleUpdate) -> Option<Candle> {
+        let mut series = self.series.lock().unwrap();
+        let series = series.get_mut(&(update.s.clone(), update.i.clone()))?;
+        let candle = &update.candle;
+        if series.closed.last().is_some_and(|last| candle.t <= last.t) {
+            return None;
+        }
+        
+        match &series.forming {
+            // Late, or the forming candle again
+            Some(forming) if candle.t < forming.t => None,
+            Some(forming) if candle.t == forming.t => {
+                series.forming = Some(candle.clone());
+                None
+            }
+            _ => {
+                let closed = series.forming.replace(candle.clone())?;
+                series.closed.push(closed.clone());
+                Some(closed)
+            }
+        }
+    }
+    
+    /// The last `n` closes of `symbol`'s candles at `interval` (or all of
+    /// them if there are fewer), oldest first
+    pub fn closes(&self, symbol: &str, interval: &str, n: usize) -> Vec<Decimal> {
+        let series = self.series.lock().unwrap();
+        match series.get(&(symbol.to_string(), interval.to_string())) {
+            Some(series) => series.closed.iter_recent(n).map(|candle| candle.c).collect(),
+            None => Vec::new(),
+        }
+    }
+    
+    /// The latest closed candle of `symbol` at `interval`
+    pub fn latest(&self, symbol: &str, interval: &str) -> Option<Candle> {
+        let series = self.series.lock().unwrap();
+        series.get(&(symbol.to_string(), interval.to_string()))?.closed.last().cloned()
+    }
+}
+
+impl Default for CandleFeed {
+    fn default() -> Self {
+        Self::new(DEFAULT_CANDLE_WINDOW)
+    }
+}
@@ -0,0 +1,367 @@
+use crate::{
+    api::{client::TradingClient, types::{Candle, Meta, UserFill}},
+    clock::Clock,
+    config::ChaosConfig,
+    error::{Error, Result},
+    models::{
+        AccountInfo, EquityPoint, FeeSchedule, FundingPayment, MarketData, Order, OrderState, OrderStatus, OrderStatusInfo, Position,
+        Trade,
+    },
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tracing::{info, warn};
+
+/// Failures a chaos run has injected so far, by kind
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChaosStats {
+    pub timeouts: u64,
+    pub rejections: u64,
+    pub disconnects: u64,
+    pub delayed_fills: u64,
+    pub stale_prices: u64,
+    /// Orders held back right now, not yet on the book
+    pub held_orders: u64,
+}
+
+/// Decides which failures a dry run suffers. Every roll comes from one
+/// seeded generator, so a run that makes the same requests in the same
+/// order suffers the same failures.
+pub struct Chaos {
+    config: ChaosConfig,
+    clock: Arc<dyn Clock>,
+    state: Mutex<ChaosState>,
+}
+
+struct ChaosState {
+    rng: StdRng,
+    stats: ChaosStats,
+    /// The end of the websocket outage under way
+    disconnected_until: Option<DateTime<Utc>>,
+}
+
+impl Chaos {
+    pub fn new(config: ChaosConfig, clock: Arc<dyn Clock>) -> Self {
+        info!("🌀 Chaos mode: injecting failures with seed {}", config.seed);
+        Self {
+            state: Mutex::new(ChaosState {
+                rng: StdRng::seed_from_u64(config.seed),
+                stats: ChaosStats::default(),
+                disconnected_until: None,
+            }),
+            config,
+            clock,
+        }
+    }
+    
+    pub fn stats(&self) -> ChaosStats {
+        self.state.lock().unwrap().stats.clone()
+    }
+    
+    /// Whether the websocket should be down now. Outside an outage each
+    /// call may start one lasting `disconnect_secs`.
+    pub fn websocket_down(&self) -> bool {
+        let now = self.clock.now();
+        let mut state = self.state.lock().unwrap();
+        if state.disconnected_until.is_some_and(|until| now < until) {
+            return true;
+        }
+        state.disconnected_until = None;
+        
+        if !roll(&mut state.rng, self.config.disconnect_probability) {
+            return false;
+        }
+        state.stats.disconnects += 1;
+        state.disconnected_until = Some(now + Duration::seconds(self.config.disconnect_secs as i64));
+        warn!("🌀 Chaos: dropping the websocket for {}s", self.config.disconnect_secs);
+        true
+    }
+    
+    fn now(&self) -> DateTime<Utc> {
+        self.clock.now()
+    }
+    
+    fn set_held_orders(&self, held: usize) {
+        self.state.lock().unwrap().stats.held_orders = held as u64;
+    }
+    
+    fn times_out(&self) -> bool {
+        self.inject(self.config.timeout_probability, |stats| stats.timeouts += 1)
+    }
+    
+    fn delays_fill(&self) -> bool {
+        self.inject(self.config.delayed_fill_probability, |stats| stats.delayed_fills += 1)
+    }
+    
+    fn serves_stale_price(&self) -> bool {
+        self.inject(self.config.stale_price_probability, |stats| stats.stale_prices += 1)
+    }
+    
+    /// One of the configured exchange messages, if this order is rejected
+    fn rejection(&self) -> Option<String> {
+        let mut state = self.state.lock().unwrap();
+        if self.config.reject_messages.is_empty() || !roll(&mut state.rng, self.config.reject_probability) {
+            return None;
+        }
+        state.stats.rejections += 1;
+        let pick = state.rng.gen_range(0..self.config.reject_messages.len());
+        Some(self.config.reject_messages[pick].clone())
+    }
+    
+    fn inject(&self, probability: f64, count: impl FnOnce(&mut ChaosStats)) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let hit = roll(&mut state.rng, probability);
+        if hit {
+            count(&mut state.stats);
+        }
+        hit
+    }
+}
+
+fn roll(rng: &mut StdRng, probability: f64) -> bool {
+    probability > 0.0 && rng.gen_bool(probability.min(1.0))
+}
+
+/// An order held back by a delayed fill
+struct DelayedOrder {
+    order: Order,
+    release_at: DateTime<Utc>,
+}
+
+/// Wraps a dry run's exchange and injects the failures [`Chaos`] rolls:
+/// requests that time out, orders rejected with the exchange's own messages
+/// or held back before they reach the book, and prices that don't move.
+///
+/// A placement that times out still reaches the exchange, as one whose
+/// reply was lost would. Held-back orders are listed as open and can be
+/// cancelled until they're released.
+pub struct ChaosClient {
+    inner: Arc<dyn TradingClient + Send + Sync>,
+    chaos: Arc<Chaos>,
+    delayed: Mutex<Vec<DelayedOrder>>,
+    /// The exchange's id for each held-back order since released
+    released: Mutex<HashMap<String, String>>,
+    /// The last price served for each symbol, served again when stale
+    last_prices: Mutex<HashMap<String, MarketData>>,
+    next_order_id: AtomicU64,
+}
+
+impl ChaosClient {
+    pub fn new(inner: Arc<dyn TradingClient + Send + Sync>, chaos: Arc<Chaos>) -> Self {
+        Self {
+            inner,
+            chaos,
+            delayed: Mutex::new(Vec::new()),
+            released: Mutex::new(HashMap::new()),
+            last_prices: Mutex::new(HashMap::new()),
+            next_order_id: AtomicU64::new(1),
+        }
+    }
+    
+    /// Orders held back and not yet released to the exchange
+    pub fn delayed_orders(&self) -> Vec<Order> {
+        self.delayed.lock().unwrap().iter().map(|delayed| delayed.order.clone()).collect()
+    }
+    
+    /// Fails `request` after hanging for `timeout_ms`, if the roll says so
+    async fn maybe_time_out(&self, request: &str) -> Result<()> {
+        if !self.chaos.times_out() {
+            return Ok(());
+        }
+        warn!("🌀 Chaos: {} times out", request);
+        let timeout_ms = self.chaos.config.timeout_ms;
+        tokio::time::sleep(std::time::Duration::from_millis(timeout_ms)).await;
+        Err(Error::Timeout(format!("{} after {}ms (injected)", request, timeout_ms)))
+    }
+    
+    /// Sends the held-back orders whose delay is over on to the exchange
+    async fn release_delayed(&self) {
+        let now = self.chaos.now();
+        let due = {
+            let mut delayed = self.delayed.lock().unwrap();
+            let (due, waiting): (Vec<_>, Vec<_>) = std::mem::take(&mut *delayed).into_iter().partition(|d| d.release_at <= now);
+            *delayed = waiting;
+            self.chaos.set_held_orders(delayed.len());
+            due
+        };
+        
+        for DelayedOrder { order, .. } in due {
+            match self.inner.place_order(&order).await {
+                Ok(order_id) => {
+                    info!("🌀 Chaos: released delayed order {} as {}", order.id, order_id);
+                    self.released.lock().unwrap().insert(order.id, order_id);
+                }
+                Err(e) => warn!("🌀 Chaos: delayed order {} failed on release: {}", order.id, e),
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl TradingClient for ChaosClient {
+    async fn get_market_data(&self, symbol: &str) -> Result<MarketData> {
+        self.release_delayed().await;
+        self.maybe_time_out(&format!("get_market_data {}", symbol)).await?;
+        
+        let last = self.last_prices.lock().unwrap().get(symbol).cloned();
+        if let Some(stale) = last.filter(|_| self.chaos.serves_stale_price()) {
+            warn!("🌀 Chaos: serving a stale {} price from {}", symbol, stale.timestamp);
+            return Ok(stale);
+        }
+        
+        let market_data = self.inner.get_market_data(symbol).await?;
+        self.last_prices.lock().unwrap().insert(symbol.to_string(), market_data.clone());
+        Ok(market_data)
+    }
+    
+    async fn get_account_info(&self) -> Result<AccountInfo> {
+        self.release_delayed().await;
+        self.maybe_time_out("get_account_info").await?;
+        let mut account_info = self.inner.get_account_info().await?;
+        account_info.open_orders.extend(self.delayed_orders());
+        Ok(account_info)
+    }
+    
+    async fn get_positions(&self) -> Result<Vec<Position>> {
+        self.release_delayed().await;
+        self.maybe_time_out("get_positions").await?;
+        self.inner.get_positions().await
+    }
+    
+    async fn get_open_orders(&self) -> Result<Vec<Order>> {
+        self.release_delayed().await;
+        self.maybe_time_out("get_open_orders").await?;
+        let mut orders = self.inner.get_open_orders().await?;
+        orders.extend(self.delayed_orders());
+        Ok(orders)
+    }
+    
+    async fn place_order(&self, order: &Order) -> Result<String> {
+        self.release_delayed().await;
+        
+        if self.chaos.times_out() {
+            // The order still reaches the exchange; only its reply is lost
+            match self.inner.place_order(order).await {
+                Ok(order_id) => warn!("🌀 Chaos: order {} rests as {} but its reply is lost", order.id, order_id),
+                Err(e) => warn!("🌀 Chaos: order {} failed ({}) and its reply is lost", order.id, e),
+            }
+            let timeout_ms = self.chaos.config.timeout_ms;
+            tokio::time::sleep(std::time::Duration::from_millis(timeout_ms)).await;
+            return Err(Error::Timeout(format!("place_order {} after {}ms (injected)", order.symbol, timeout_ms)));
+        }
+        if let Some(message) = self.chaos.rejection() {
+            warn!("🌀 Chaos: rejecting order {}: {}", order.id, message);
+            return Err(Error::from_exchange_message(&message)
+                .unwrap_or_else(|| Error::Trading(format!("Failed to place order: {}", message))));
+        }
+        if self.chaos.delays_fill() {
+            let order_id = format!("chaos-{}", self.next_order_id.fetch_add(1, Ordering::Relaxed));
+            let delay = self.chaos.config.fill_delay_secs;
+            warn!("🌀 Chaos: holding order {} back for {}s", order_id, delay);
+            let now = self.chaos.now();
+            let mut delayed = self.delayed.lock().unwrap();
+            delayed.push(DelayedOrder {
+                order: Order {
+                    id: order_id.clone(),
+                    status: OrderStatus::Open,
+                    created_at: now,
+                    updated_at: Some(now),
+                    ..order.clone()
+                },
+                release_at: now + Duration::seconds(delay as i64),
+            });
+            self.chaos.set_held_orders(delayed.len());
+            return Ok(order_id);
+        }
+        
+        self.inner.place_order(order).await
+    }
+    
+    async fn cancel_order(&self, order_id: &str) -> Result<bool> {
+        self.release_delayed().await;
+        self.maybe_time_out(&format!("cancel_order {}", order_id)).await?;
+        
+        let held = {
+            let mut delayed = self.delayed.lock().unwrap();
+            let before = delayed.len();
+            delayed.retain(|d| d.order.id != order_id);
+            self.chaos.set_held_orders(delayed.len());
+            delayed.len() < before
+        };
+        if held {
+            return Ok(true);
+        }
+        self.inner.cancel_order(order_id).await
+    }
+    
+    async fn get_order_status(&self, order_id: &str) -> Result<OrderStatusInfo> {
+        self.release_delayed().await;
+        self.maybe_time_out(&format!("get_order_status {}", order_id)).await?;
+        
+        if self.delayed.lock().unwrap().iter().any(|delayed| delayed.order.id == order_id) {
+            return Ok(OrderStatusInfo {
+                state: OrderState::Resting,
+                filled_size: Decimal::ZERO,
+                average_price: None,
+                fee: Decimal::ZERO,
+            });
+        }
+        let released = self.released.lock().unwrap().get(order_id).cloned();
+        self.inner.get_order_status(released.as_deref().unwrap_or(order_id)).await
+    }
+    
+    async fn get_trade_history(&self, symbol: Option<&str>) -> Result<Vec<Trade>> {
+        self.inner.get_trade_history(symbol).await
+    }
+    
+    async fn get_trade_history_between(
+        &self,
+        symbol: Option<&str>,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<Vec<Trade>> {
+        self.inner.get_trade_history_between(symbol, since, until).await
+    }
+    
+    async fn get_fills_between(&self, since: DateTime<Utc>, until: DateTime<Utc>) -> Result<Vec<UserFill>> {
+        self.inner.get_fills_between(since, until).await
+    }
+    
+    async fn get_candles(&self, symbol: &str, interval: &str, start: u64, end: u64) -> Result<Vec<Candle>> {
+        self.inner.get_candles(symbol, interval, start, end).await
+    }
+    
+    async fn get_meta(&self) -> Result<Meta> {
+        self.inner.get_meta().await
+    }
+    
+    async fn get_funding_payments(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<FundingPayment>> {
+        self.inner.get_funding_payments(start, end).await
+    }
+    
+    async fn get_user_fees(&self) -> Result<FeeSchedule> {
+        self.inner.get_user_fees().await
+    }
+    
+    async fn get_portfolio_history(&self) -> Result<Vec<EquityPoint>> {
+        self.inner.get_portfolio_history().await
+    }
+    
+    async fn schedule_cancel(&self, at: Option<DateTime<Utc>>) -> Result<()> {
+        self.inner.schedule_cancel(at).await
+    }
+    
+    async fn usd_transfer(&self, destination: &str, amount: Decimal) -> Result<()> {
+        self.inner.usd_transfer(destination, amount).await
+    }
+    
+    async fn withdraw(&self, destination: &str, amount: Decimal) -> Result<()> {
+        self.inner.withdraw(destination, amount).await
+    }
+}
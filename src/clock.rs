@@ -0,0 +1,77 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use std::sync::Arc;
+use tokio::sync::watch;
+
+/// Source of the current time. The live bot runs on [`SystemClock`];
+/// backtests and replays run on a [`SimulatedClock`] driven by the data's
+/// timestamps, so anything time-based behaves as it would have then.
+#[async_trait]
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+    
+    /// Returns once `now()` has reached `deadline`
+    async fn sleep_until(&self, deadline: DateTime<Utc>);
+}
+
+/// The wall clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+#[async_trait]
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+    
+    async fn sleep_until(&self, deadline: DateTime<Utc>) {
+        if let Ok(remaining) = (deadline - Utc::now()).to_std() {
+            tokio::time::sleep(remaining).await;
+        }
+    }
+}
+
+/// A clock that only moves when told to. Clones share the same time, and
+/// sleepers wake as soon as it's advanced past their deadline.
+#[derive(Debug, Clone)]
+pub struct SimulatedClock {
+    time: Arc<watch::Sender<DateTime<Utc>>>,
+}
+
+impl SimulatedClock {
+    pub fn new(start: DateTime<Utc>) -> Self {
+        Self {
+            time: Arc::new(watch::channel(start).0),
+        }
+    }
+    
+    /// Moves the clock to `time`; earlier times are ignored, so it never
+    /// runs backwards
+    pub fn advance_to(&self, time: DateTime<Utc>) {
+        self.time.send_if_modified(|now| {
+            if time > *now {
+                *now = time;
+                true
+            } else {
+                false
+            }
+        });
+    }
+    
+    pub fn advance(&self, by: Duration) {
+        self.advance_to(self.now() + by);
+    }
+}
+
+#[async_trait]
+impl Clock for SimulatedClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.time.borrow()
+    }
+    
+    async fn sleep_until(&self, deadline: DateTime<Utc>) {
+        let mut time = self.time.subscribe();
+        // The sender lives as long as `self`, so this can't fail
+        let _ = time.wait_for(|now| *now >= deadline).await;
+    }
+}
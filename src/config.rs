@@ -1,4 +1,9 @@
-use crate::error::{Error, Result};
+use crate::{
+    api::wallet,
+    error::{Error, Result},
+    notifications::Severity,
+};
+use chrono::{DateTime, NaiveTime, Utc, Weekday};
 use config::{Config as ConfigFile, File, FileFormat};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
@@ -11,15 +16,272 @@ pub struct Config {
     pub strategies: HashMap<String, StrategyConfig>,
     pub risk_management: RiskManagementConfig,
     pub logging: LoggingConfig,
+    #[serde(default)]
+    pub notifications: NotificationsConfig,
+    #[serde(default)]
+    pub journal: JournalConfig,
+    #[serde(default)]
+    pub storage: StorageConfig,
+    #[serde(default)]
+    pub recorder: RecorderConfig,
+    #[serde(default)]
+    pub schedule: ScheduleConfig,
+    #[serde(default)]
+    pub reporting: ReportingConfig,
+    #[serde(default)]
+    pub control: ControlConfig,
+    #[serde(default)]
+    pub backtest: BacktestConfig,
+    #[serde(default)]
+    pub paper: PaperConfig,
+    #[serde(default)]
+    pub costs: CostConfig,
+    #[serde(default)]
+    pub shutdown: ShutdownConfig,
+    #[serde(default)]
+    pub data_source: DataSourceConfig,
+    #[serde(default)]
+    pub preflight: PreflightConfig,
+    #[serde(default)]
+    pub watchdog: WatchdogConfig,
+    #[serde(default)]
+    pub events: EventsConfig,
+    #[serde(default)]
+    pub analysis: AnalysisConfig,
+    #[serde(default)]
+    pub scheduler: SchedulerConfig,
+    #[serde(default)]
+    pub chaos: ChaosConfig,
+    #[serde(default)]
+    pub http: HttpConfig,
+    /// Further accounts traded from this process, each with its own wallet,
+    /// strategies and risk limits. When set, only these accounts trade.
+    #[serde(default)]
+    pub accounts: Vec<AccountConfig>,
+    #[serde(default)]
+    pub reconciliation: ReconciliationConfig,
+    #[serde(default)]
+    pub profit_sweep: ProfitSweepConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HyperliquidConfig {
+    /// The network's REST endpoint when left empty
+    #[serde(default)]
     pub base_url: String,
+    /// The network's websocket endpoint when left empty
+    #[serde(default)]
     pub ws_url: String,
     pub api_key: String,
     pub private_key: String,
+    /// Wallet queried by info requests. Lets read-only commands run without a
+    /// private key, or point an API wallet's key at the account it trades for
+    #[serde(default)]
+    pub account_address: Option<String>,
+    /// Vault to trade for, as its leader. Orders are placed for the vault
+    /// and account data is read from it.
+    #[serde(default)]
+    pub vault_address: Option<String>,
+    /// Subaccount to trade for, as its master; like `vault_address`
+    #[serde(default)]
+    pub subaccount: Option<String>,
     pub testnet: bool,
+    #[serde(default)]
+    pub rate_limit: RateLimitConfig,
+    /// How long one market snapshot serves every symbol's market data
+    #[serde(default = "default_market_data_ttl_ms")]
+    pub market_data_ttl_ms: u64,
+    #[serde(default)]
+    pub http: HttpClientConfig,
+}
+
+impl HyperliquidConfig {
+    pub fn network(&self) -> Network {
+        Network::from_testnet(self.testnet)
+    }
+    
+    /// Points URLs left empty at the network's endpoints
+    fn fill_default_urls(&mut self) {
+        let network = self.network();
+        if self.base_url.is_empty() {
+            self.base_url = network.api_url().to_string();
+        }
+        if self.ws_url.is_empty() {
+            self.ws_url = network.ws_url().to_string();
+        }
+    }
+    
+    /// Fails if either URL is the exchange's on the other network than
+    /// `testnet` says. Other hosts, e.g. a local proxy, can't be told apart
+    /// and pass.
+    fn check_network(&self) -> Result<()> {
+        let network = self.network();
+        for (name, url) in [("base_url", &self.base_url), ("ws_url", &self.ws_url)] {
+            match Network::of_url(url) {
+                Some(url_network) if url_network != network => {
+                    return Err(Error::Config(format!(
+                        "hyperliquid.{} {} is a {} URL but testnet = {}",
+                        name, url, url_network, self.testnet
+                    )))
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+    
+    /// The vault or subaccount traded for, if either is set
+    pub fn vault(&self) -> Option<&str> {
+        [&self.vault_address, &self.subaccount]
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .find(|address| !address.is_empty())
+    }
+    
+    /// The account traded: the vault or subaccount, else `account_address`,
+    /// else the private key's. `None` with neither an address nor a usable
+    /// key.
+    pub fn trading_account(&self) -> Option<String> {
+        if let Some(vault) = self.vault() {
+            return Some(vault.to_lowercase());
+        }
+        match self.account_address.as_deref().filter(|address| !address.is_empty()) {
+            Some(address) => Some(address.to_lowercase()),
+            None => wallet::address_from_private_key(&self.private_key).ok(),
+        }
+    }
+}
+
+/// Which of the exchange's networks an account trades on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Network {
+    Mainnet,
+    Testnet,
+}
+
+impl Network {
+    pub fn from_testnet(testnet: bool) -> Self {
+        if testnet {
+            Network::Testnet
+        } else {
+            Network::Mainnet
+        }
+    }
+    
+    pub fn api_url(&self) -> &'static str {
+        match self {
+            Network::Mainnet => "https://api.hyperliquid.xyz",
+            Network::Testnet => "https://api.hyperliquid-testnet.xyz",
+        }
+    }
+    
+    /// How user-signed actions name the network
+    pub fn chain_name(&self) -> &'static str {
+        match self {
+            Network::Mainnet => "Mainnet",
+            Network::Testnet => "Testnet",
+        }
+    }
+    
+    pub fn ws_url(&self) -> &'static str {
+        match self {
+            Network::Mainnet => "wss://api.hyperliquid.xyz/ws",
+            Network::Testnet => "wss://api.hyperliquid-testnet.xyz/ws",
+        }
+    }
+    
+    /// The network `url` is the exchange's endpoint on, if it's the
+    /// exchange's at all
+    pub fn of_url(url: &str) -> Option<Self> {
+        let url = reqwest::Url::parse(url).ok()?;
+        let host = url.host_str()?.to_ascii_lowercase();
+        let on = |domain: &str| host == domain || host.ends_with(&format!(".{}", domain));
+        if on("hyperliquid-testnet.xyz") {
+            Some(Network::Testnet)
+        } else if on("hyperliquid.xyz") {
+            Some(Network::Mainnet)
+        } else {
+            None
+        }
+    }
+}
+
+impl std::fmt::Display for Network {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Network::Mainnet => write!(f, "mainnet"),
+            Network::Testnet => write!(f, "testnet"),
+        }
+    }
+}
+
+/// Client-side budgets for REST requests, so a busy bot slows down instead
+/// of running into the exchange's limits. Info requests and exchange actions
+/// have separate budgets; requests over budget wait their turn.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateLimitConfig {
+    /// Info requests per second; 0 disables the limit
+    #[serde(default = "default_info_per_second")]
+    pub info_per_second: f64,
+    /// Info requests that may go out at once after a quiet spell
+    #[serde(default = "default_info_burst")]
+    pub info_burst: u32,
+    /// Exchange actions per second; 0 disables the limit
+    #[serde(default = "default_exchange_per_second")]
+    pub exchange_per_second: f64,
+    #[serde(default = "default_exchange_burst")]
+    pub exchange_burst: u32,
+    /// Longest a request waits for its turn; one that would wait longer
+    /// fails with a rate limit error
+    #[serde(default = "default_rate_limit_max_wait_ms")]
+    pub max_wait_ms: u64,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            info_per_second: default_info_per_second(),
+            info_burst: default_info_burst(),
+            exchange_per_second: default_exchange_per_second(),
+            exchange_burst: default_exchange_burst(),
+            max_wait_ms: default_rate_limit_max_wait_ms(),
+        }
+    }
+}
+
+/// How REST requests reach the exchange, e.g. through a proxy in a
+/// restricted network
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpClientConfig {
+    /// Longest a connection may take to open
+    #[serde(default = "default_connect_timeout_ms")]
+    pub connect_timeout_ms: u64,
+    /// Longest a request may take, connecting included
+    #[serde(default = "default_request_timeout_ms")]
+    pub request_timeout_ms: u64,
+    /// Proxy every request goes through, e.g. `http://proxy.internal:3128`
+    #[serde(default)]
+    pub proxy: Option<String>,
+    /// Sent instead of the HTTP library's own
+    #[serde(default)]
+    pub user_agent: Option<String>,
+    /// PEM file of a root certificate to trust besides the system's, for
+    /// proxies that terminate TLS
+    #[serde(default)]
+    pub ca_certificate: Option<String>,
+}
+
+impl Default for HttpClientConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout_ms: default_connect_timeout_ms(),
+            request_timeout_ms: default_request_timeout_ms(),
+            proxy: None,
+            user_agent: None,
+            ca_certificate: None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,9 +289,38 @@ pub struct TradingConfig {
     pub dry_run: bool,
     pub max_positions: u32,
     pub default_slippage: Decimal,
+    /// Resting orders older than this are cancelled; 0 leaves them be
     pub order_timeout_seconds: u64,
+    /// Retries of a failed API request; orders are only retried when they
+    /// can't have reached the exchange
     pub retry_attempts: u32,
+    /// Wait before the first retry, doubling for each retry after
     pub retry_delay_ms: u64,
+    /// Orders for different symbols submitted at once within a cycle
+    #[serde(default = "default_max_concurrent_orders")]
+    pub max_concurrent_orders: usize,
+    /// How far ahead the exchange is told to cancel every order unless a
+    /// cycle pushes the deadline back first, so orders don't outlive a
+    /// crashed or hung bot; 0 turns the dead man's switch off
+    #[serde(default = "default_cancel_deadline_secs")]
+    pub cancel_deadline_secs: u64,
+    /// How often successful cycles push the deadline back
+    #[serde(default = "default_cancel_deadline_refresh_secs")]
+    pub cancel_deadline_refresh_secs: u64,
+    /// How orders and cancels reach the exchange
+    #[serde(default)]
+    pub execution_transport: ExecutionTransport,
+}
+
+/// How orders and cancels are sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExecutionTransport {
+    #[default]
+    Http,
+    /// Posted over the market data websocket, saving an HTTP round trip;
+    /// over HTTP while it's down
+    Ws,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,6 +330,34 @@ pub struct StrategyConfig {
     pub symbol: String,
     pub position_size: Decimal,
     pub parameters: HashMap<String, serde_json::Value>,
+    /// Leverage set on the symbol at startup; left as it is when unset
+    #[serde(default)]
+    pub leverage: Option<u32>,
+    /// Margin mode set along with `leverage`
+    #[serde(default)]
+    pub margin_mode: MarginMode,
+    /// Least time between two evaluations of this strategy, however often
+    /// candles close or its mid moves; unset evaluates it on every trigger
+    #[serde(default)]
+    pub eval_interval_secs: Option<u64>,
+}
+
+/// How a position's margin is held
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MarginMode {
+    /// Shared with every other cross position in the account
+    #[default]
+    Cross,
+    /// Set aside for this position alone
+    Isolated,
+}
+
+/// Strategies whose `enabled` flag was flipped by command-line overrides
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StrategyOverrides {
+    pub enabled: Vec<String>,
+    pub disabled: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -50,32 +369,1001 @@ pub struct RiskManagementConfig {
     pub max_drawdown_percentage: Decimal,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduleConfig {
+    /// Funding is settled every this many hours, aligned to 00:00 UTC
+    #[serde(default = "default_funding_interval_hours")]
+    pub funding_interval_hours: u32,
+    /// Don't trade within this many seconds either side of a funding time
+    #[serde(default)]
+    pub funding_blackout_secs: u64,
+    /// One-off exchange maintenance, as absolute UTC ranges
+    #[serde(default)]
+    pub maintenance_windows: Vec<MaintenanceWindow>,
+    /// Recurring daily no-trade windows in UTC
+    #[serde(default)]
+    pub blackout_windows: Vec<BlackoutWindow>,
+}
+
+impl Default for ScheduleConfig {
+    fn default() -> Self {
+        Self {
+            funding_interval_hours: default_funding_interval_hours(),
+            funding_blackout_secs: 0,
+            maintenance_windows: Vec::new(),
+            blackout_windows: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceWindow {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlackoutWindow {
+    /// "HH:MM:SS" UTC; a window whose end is before its start wraps past midnight
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    /// Days the window applies to (by start day); empty means every day
+    #[serde(default)]
+    pub days: Vec<Weekday>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportingConfig {
+    /// Only takes effect in builds with the `sentry` feature
+    pub enabled: bool,
+    pub dsn: String,
+    #[serde(default = "default_reporting_environment")]
+    pub environment: String,
+}
+
+impl Default for ReportingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            dsn: String::new(),
+            environment: default_reporting_environment(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ControlConfig {
+    pub enabled: bool,
+    /// Unix domain socket used on Linux/macOS
+    #[serde(default = "default_control_socket_path")]
+    pub socket_path: String,
+    /// Localhost TCP port used where unix sockets aren't available
+    #[serde(default = "default_control_tcp_port")]
+    pub tcp_port: u16,
+}
+
+impl Default for ControlConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            socket_path: default_control_socket_path(),
+            tcp_port: default_control_tcp_port(),
+        }
+    }
+}
+
+/// The read-only HTTP status server, for monitoring probes and a quick look
+/// from a browser
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_http_bind")]
+    pub bind: String,
+    /// Required as `Authorization: Bearer <token>` on every request when set
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind: default_http_bind(),
+            token: None,
+        }
+    }
+}
+
+/// Periodically checks the fills recorded in storage against the exchange's
+/// own fill history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReconciliationConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_reconciliation_interval_secs")]
+    pub interval_secs: u64,
+    /// Fills younger than this wait for the next run, so ones still being
+    /// recorded aren't taken for discrepancies
+    #[serde(default = "default_reconciliation_settle_secs")]
+    pub settle_secs: u64,
+    /// How far apart a local and an exchange fill may be to match on symbol,
+    /// side and size when neither id matches
+    #[serde(default = "default_reconciliation_match_window_secs")]
+    pub match_window_secs: u64,
+    /// How far back the first run looks
+    #[serde(default = "default_reconciliation_initial_lookback_hours")]
+    pub initial_lookback_hours: u64,
+    /// Discrepancies in one run that trigger a notification
+    #[serde(default = "default_reconciliation_alert_threshold")]
+    pub alert_threshold: usize,
+}
+
+impl Default for ReconciliationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: default_reconciliation_interval_secs(),
+            settle_secs: default_reconciliation_settle_secs(),
+            match_window_secs: default_reconciliation_match_window_secs(),
+            initial_lookback_hours: default_reconciliation_initial_lookback_hours(),
+            alert_threshold: default_reconciliation_alert_threshold(),
+        }
+    }
+}
+
+/// Periodically moves the USDC the account can withdraw beyond `threshold`
+/// to `destination`, e.g. a cold wallet. Never runs in a dry run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfitSweepConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Withdrawable USDC left in the account
+    #[serde(default)]
+    pub threshold: Decimal,
+    #[serde(default)]
+    pub destination: String,
+    #[serde(default)]
+    pub method: SweepMethod,
+    #[serde(default = "default_profit_sweep_interval_hours")]
+    pub interval_hours: u64,
+}
+
+impl Default for ProfitSweepConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threshold: Decimal::ZERO,
+            destination: String::new(),
+            method: SweepMethod::default(),
+            interval_hours: default_profit_sweep_interval_hours(),
+        }
+    }
+}
+
+/// How swept profits leave the account
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SweepMethod {
+    /// To another account on the exchange
+    #[default]
+    Transfer,
+    /// To an address on Arbitrum, less the exchange's withdrawal fee
+    Withdraw,
+}
+
+/// One account a multi-account process trades. Everything but the wallet,
+/// strategies and risk limits comes from the top-level config; market data
+/// streams from the top-level `hyperliquid.ws_url`, shared by every account,
+/// and requests go out as the top-level `hyperliquid.http` says.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountConfig {
+    /// Labels the account's status, logs and notifications, and keeps its
+    /// storage, journal and recordings apart from the others'
+    pub name: String,
+    pub hyperliquid: HyperliquidConfig,
+    #[serde(default)]
+    pub strategies: HashMap<String, StrategyConfig>,
+    /// The top-level limits when left out
+    #[serde(default)]
+    pub risk_management: Option<RiskManagementConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestConfig {
+    #[serde(default = "default_backtest_initial_balance")]
+    pub initial_balance: Decimal,
+    #[serde(default = "default_backtest_interval")]
+    pub interval: String,
+    /// Bars per symbol handed to `Strategy::warm_up` before trading starts
+    #[serde(default)]
+    pub warmup_bars: usize,
+}
+
+impl Default for BacktestConfig {
+    fn default() -> Self {
+        Self {
+            initial_balance: default_backtest_initial_balance(),
+            interval: default_backtest_interval(),
+            warmup_bars: 0,
+        }
+    }
+}
+
+/// The simulated account dry runs trade, kept in storage across restarts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaperConfig {
+    #[serde(default = "default_backtest_initial_balance")]
+    pub initial_balance: Decimal,
+    /// Discard the saved paper account and start again from `initial_balance`
+    #[serde(default)]
+    pub reset: bool,
+}
+
+impl Default for PaperConfig {
+    fn default() -> Self {
+        Self {
+            initial_balance: default_backtest_initial_balance(),
+            reset: false,
+        }
+    }
+}
+
+/// How simulated fills move away from the reference price
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum SlippageConfig {
+    /// The same adverse move on every fill, in basis points
+    Fixed { bps: Decimal },
+    /// `impact_bps` times the order's share of the bar's volume, capped at
+    /// `max_bps`
+    Volume { impact_bps: Decimal, max_bps: Decimal },
+    /// Fill at the far side of the book when it's known, else `fallback_bps`
+    Spread { fallback_bps: Decimal },
+}
+
+/// Fees, slippage and funding applied to dry-run and backtest fills
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostConfig {
+    /// Fee on fills that rested on the book, in basis points of notional
+    #[serde(default = "default_maker_fee_bps")]
+    pub maker_fee_bps: Decimal,
+    /// Fee on fills that took liquidity, in basis points of notional
+    #[serde(default = "default_taker_fee_bps")]
+    pub taker_fee_bps: Decimal,
+    #[serde(default = "default_slippage")]
+    pub slippage: SlippageConfig,
+    /// Funding rate charged at each settlement (see `schedule`) on the
+    /// notional of held positions; positive means longs pay shorts
+    #[serde(default = "default_funding_rate")]
+    pub funding_rate: Decimal,
+    /// Per-symbol overrides of `funding_rate`
+    #[serde(default)]
+    pub funding_rates: HashMap<String, Decimal>,
+}
+
+impl Default for CostConfig {
+    fn default() -> Self {
+        Self {
+            maker_fee_bps: default_maker_fee_bps(),
+            taker_fee_bps: default_taker_fee_bps(),
+            slippage: default_slippage(),
+            funding_rate: default_funding_rate(),
+            funding_rates: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShutdownConfig {
+    /// Cancel every open order on the account before exiting
+    #[serde(default)]
+    pub cancel_open_orders: bool,
+    /// Time allowed for the whole graceful stop; kept under the usual 30s
+    /// grace period before SIGKILL
+    #[serde(default = "default_shutdown_deadline_secs")]
+    pub deadline_secs: u64,
+}
+
+impl Default for ShutdownConfig {
+    fn default() -> Self {
+        Self {
+            cancel_open_orders: false,
+            deadline_secs: default_shutdown_deadline_secs(),
+        }
+    }
+}
+
+/// When market data comes from the websocket and when from REST polling
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataSourceConfig {
+    /// Fall back to REST once the websocket has been quiet this long
+    #[serde(default = "default_max_message_age_secs")]
+    pub max_message_age_secs: u64,
+    /// How long the websocket must stay healthy before it's used again
+    #[serde(default = "default_stabilization_secs")]
+    pub stabilization_secs: u64,
+    /// Candle interval used to backfill what was missed while on REST
+    #[serde(default = "default_backfill_interval")]
+    pub backfill_interval: String,
+    /// Unreadable frames a websocket channel may send in a minute before
+    /// it's resubscribed
+    #[serde(default = "default_max_parse_errors_per_minute")]
+    pub max_parse_errors_per_minute: u32,
+    /// How often the websocket is pinged to keep the exchange from closing
+    /// it as idle
+    #[serde(default = "default_ping_interval_secs")]
+    pub ping_interval_secs: u64,
+    /// Drop and reconnect a websocket that has received nothing, pongs
+    /// included, for this long
+    #[serde(default = "default_stale_connection_secs")]
+    pub stale_connection_secs: u64,
+    /// Hold back orders while the websocket market data is coming from has
+    /// been quiet this long; 0 never does
+    #[serde(default = "default_max_order_feed_age_secs")]
+    pub max_order_feed_age_secs: u64,
+    /// Decoded messages buffered for each websocket consumer. Once full,
+    /// the oldest market data is dropped; fills and order updates never are.
+    #[serde(default = "default_event_buffer")]
+    pub event_buffer: usize,
+    /// Outgoing websocket messages queued before sending more fails
+    #[serde(default = "default_write_buffer")]
+    pub write_buffer: usize,
+    /// Websocket connections the feed's subscriptions are spread across,
+    /// each symbol's on one of them
+    #[serde(default = "default_ws_connections")]
+    pub ws_connections: usize,
+    /// Play this websocket recording (from `recorder.frames_path`) back
+    /// instead of connecting to the exchange's websocket
+    #[serde(default)]
+    pub replay: Option<String>,
+    /// How many times faster than recorded it plays; 0 plays it as fast as
+    /// it can be read
+    #[serde(default = "default_replay_speed")]
+    pub replay_speed: f64,
+}
+
+impl Default for DataSourceConfig {
+    fn default() -> Self {
+        Self {
+            max_message_age_secs: default_max_message_age_secs(),
+            stabilization_secs: default_stabilization_secs(),
+            backfill_interval: default_backfill_interval(),
+            max_parse_errors_per_minute: default_max_parse_errors_per_minute(),
+            ping_interval_secs: default_ping_interval_secs(),
+            stale_connection_secs: default_stale_connection_secs(),
+            max_order_feed_age_secs: default_max_order_feed_age_secs(),
+            event_buffer: default_event_buffer(),
+            write_buffer: default_write_buffer(),
+            ws_connections: default_ws_connections(),
+            replay: None,
+            replay_speed: default_replay_speed(),
+        }
+    }
+}
+
+/// Checks run before the first trading cycle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreflightConfig {
+    /// Slowest acceptable REST round trip
+    #[serde(default = "default_max_latency_ms")]
+    pub max_latency_ms: u64,
+    /// Available margin must exceed this to start trading
+    #[serde(default = "default_min_available_margin")]
+    pub min_available_margin: Decimal,
+    /// How long to wait for the websocket's first ticker
+    #[serde(default = "default_websocket_timeout_secs")]
+    pub websocket_timeout_secs: u64,
+    /// Largest acceptable gap between exchange and local timestamps
+    #[serde(default = "default_max_clock_skew_secs")]
+    pub max_clock_skew_secs: u64,
+    /// Start anyway, logging failed checks as warnings (`--ignore-preflight`)
+    #[serde(default)]
+    pub ignore_failures: bool,
+}
+
+impl Default for PreflightConfig {
+    fn default() -> Self {
+        Self {
+            max_latency_ms: default_max_latency_ms(),
+            min_available_margin: default_min_available_margin(),
+            websocket_timeout_secs: default_websocket_timeout_secs(),
+            max_clock_skew_secs: default_max_clock_skew_secs(),
+            ignore_failures: false,
+        }
+    }
+}
+
+/// What the watchdog does when the trading loop stops making progress
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WatchdogAction {
+    /// Log the stall and send a critical notification
+    #[default]
+    Alert,
+    /// Also engage the kill switch: cancel open orders and stop trading
+    KillSwitch,
+    /// Also stop the bot with an error, so a supervisor restarts it
+    Exit,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchdogConfig {
+    #[serde(default = "default_watchdog_enabled")]
+    pub enabled: bool,
+    /// Cycle intervals without a heartbeat before the loop counts as stuck
+    #[serde(default = "default_stall_cycles")]
+    pub stall_cycles: u32,
+    #[serde(default)]
+    pub action: WatchdogAction,
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_watchdog_enabled(),
+            stall_cycles: default_stall_cycles(),
+            action: WatchdogAction::default(),
+        }
+    }
+}
+
+/// The internal event bus between the trading loop and its observers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventsConfig {
+    /// Events a subscriber may fall behind by before it misses the oldest
+    #[serde(default = "default_event_capacity")]
+    pub capacity: usize,
+}
+
+impl Default for EventsConfig {
+    fn default() -> Self {
+        Self {
+            capacity: default_event_capacity(),
+        }
+    }
+}
+
+/// Time limits on strategy analysis
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalysisConfig {
+    /// Budget for one strategy's analysis in a cycle
+    #[serde(default = "default_analyze_timeout_ms")]
+    pub timeout_ms: u64,
+    /// Budgets for particular strategies, by name
+    #[serde(default)]
+    pub timeout_overrides: HashMap<String, u64>,
+    /// Timeouts in a row before a strategy is disabled; 0 never disables
+    #[serde(default = "default_max_consecutive_timeouts")]
+    pub max_consecutive_timeouts: u32,
+}
+
+impl AnalysisConfig {
+    /// The analysis budget for strategy `name`
+    pub fn timeout(&self, name: &str) -> std::time::Duration {
+        let ms = self.timeout_overrides.get(name).copied().unwrap_or(self.timeout_ms);
+        std::time::Duration::from_millis(ms)
+    }
+}
+
+impl Default for AnalysisConfig {
+    fn default() -> Self {
+        Self {
+            timeout_ms: default_analyze_timeout_ms(),
+            timeout_overrides: HashMap::new(),
+            max_consecutive_timeouts: default_max_consecutive_timeouts(),
+        }
+    }
+}
+
+/// When the trading loop checks the account and evaluates strategies. The
+/// account is fetched and risk limits checked on a fixed cadence; each
+/// strategy is evaluated when a candle it analyzes closes or its symbol's
+/// mid moves far enough, no sooner than its `eval_interval_secs` allows,
+/// and anyway once it has gone `idle_eval_secs` without.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchedulerConfig {
+    /// Seconds between account fetches and risk checks
+    #[serde(default = "default_account_interval_secs")]
+    pub account_interval_secs: u64,
+    /// Longest a strategy goes without evaluation, or its
+    /// `eval_interval_secs` if that's longer
+    #[serde(default = "default_idle_eval_secs")]
+    pub idle_eval_secs: u64,
+    /// Move in a symbol's mid since its strategies were last evaluated, in
+    /// basis points, that evaluates them again; 0 ignores mids
+    #[serde(default = "default_mid_move_bps")]
+    pub mid_move_bps: Decimal,
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            account_interval_secs: default_account_interval_secs(),
+            idle_eval_secs: default_idle_eval_secs(),
+            mid_move_bps: default_mid_move_bps(),
+        }
+    }
+}
+
+/// Failures injected into a dry run's exchange, to see how the bot copes
+/// when things break. Ignored unless `trading.dry_run` is set. Each chance is
+/// a probability from 0 to 1, rolled per request (per cycle for
+/// disconnects), and the same seed injects the same failures.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChaosConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub seed: u64,
+    /// Chance a request hangs for `timeout_ms` and then fails
+    #[serde(default)]
+    pub timeout_probability: f64,
+    #[serde(default = "default_chaos_timeout_ms")]
+    pub timeout_ms: u64,
+    /// Chance an order is rejected with one of `reject_messages`
+    #[serde(default)]
+    pub reject_probability: f64,
+    #[serde(default = "default_chaos_reject_messages")]
+    pub reject_messages: Vec<String>,
+    /// Chance the websocket drops, staying down for `disconnect_secs`
+    #[serde(default)]
+    pub disconnect_probability: f64,
+    #[serde(default = "default_chaos_disconnect_secs")]
+    pub disconnect_secs: u64,
+    /// Chance an order is held back `fill_delay_secs` before it reaches the
+    /// book
+    #[serde(default)]
+    pub delayed_fill_probability: f64,
+    #[serde(default = "default_chaos_fill_delay_secs")]
+    pub fill_delay_secs: u64,
+    /// Chance a price request gets the previous price again
+    #[serde(default)]
+    pub stale_price_probability: f64,
+}
+
+impl Default for ChaosConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            seed: 0,
+            timeout_probability: 0.0,
+            timeout_ms: default_chaos_timeout_ms(),
+            reject_probability: 0.0,
+            reject_messages: default_chaos_reject_messages(),
+            disconnect_probability: 0.0,
+            disconnect_secs: default_chaos_disconnect_secs(),
+            delayed_fill_probability: 0.0,
+            fill_delay_secs: default_chaos_fill_delay_secs(),
+            stale_price_probability: 0.0,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LoggingConfig {
     pub level: String,
     pub file_path: Option<String>,
     pub max_file_size_mb: u64,
     pub max_files: u32,
+    #[serde(default)]
+    pub format: LogFormat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Human-readable output for terminals
+    #[default]
+    Pretty,
+    /// One JSON object per line, for log pipelines
+    Json,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalConfig {
+    pub enabled: bool,
+    pub directory: String,
+}
+
+impl Default for JournalConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            directory: "data/journal".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageConfig {
+    pub enabled: bool,
+    pub path: String,
+    /// Equity samples older than this are kept as one per minute
+    #[serde(default = "default_equity_raw_retention_days")]
+    pub equity_raw_retention_days: u32,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: "data/bot.db".to_string(),
+            equity_raw_retention_days: default_equity_raw_retention_days(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecorderConfig {
+    pub enabled: bool,
+    pub directory: String,
+    /// Oldest days are pruned once recordings exceed this size
+    #[serde(default = "default_recorder_max_total_mb")]
+    pub max_total_mb: u64,
+    #[serde(default = "default_recorder_queue_size")]
+    pub queue_size: usize,
+    #[serde(default = "default_recorder_flush_interval_secs")]
+    pub flush_interval_secs: u64,
+    /// Also appends every websocket frame received to this newline-JSON
+    /// file, for replaying with `data_source.replay`
+    #[serde(default)]
+    pub frames_path: Option<String>,
+}
+
+impl Default for RecorderConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            directory: "data/recordings".to_string(),
+            max_total_mb: default_recorder_max_total_mb(),
+            queue_size: default_recorder_queue_size(),
+            flush_interval_secs: default_recorder_flush_interval_secs(),
+            frames_path: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NotificationsConfig {
+    pub telegram: Option<TelegramConfig>,
+    pub discord: Option<DiscordConfig>,
+    pub webhook: Option<WebhookConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TelegramConfig {
+    pub enabled: bool,
+    pub bot_token: String,
+    pub chat_id: String,
+    #[serde(default = "default_telegram_api_url")]
+    pub api_url: String,
+    #[serde(default)]
+    pub min_severity: Severity,
+    #[serde(default)]
+    pub events: Vec<String>,
+    #[serde(default = "default_notification_max_retries")]
+    pub max_retries: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiscordConfig {
+    pub enabled: bool,
+    pub webhook_url: String,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub min_severity: Severity,
+    #[serde(default)]
+    pub events: Vec<String>,
+    #[serde(default = "default_notification_max_retries")]
+    pub max_retries: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    pub enabled: bool,
+    pub urls: Vec<String>,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    #[serde(default = "default_webhook_timeout_secs")]
+    pub timeout_secs: u64,
+    #[serde(default = "default_webhook_queue_size")]
+    pub queue_size: usize,
+    #[serde(default)]
+    pub min_severity: Severity,
+    #[serde(default)]
+    pub events: Vec<String>,
+    #[serde(default = "default_notification_max_retries")]
+    pub max_retries: u32,
+}
+
+fn default_equity_raw_retention_days() -> u32 {
+    7
+}
+
+fn default_control_socket_path() -> String {
+    "data/bot.sock".to_string()
+}
+
+fn default_control_tcp_port() -> u16 {
+    7979
+}
+
+fn default_backtest_initial_balance() -> Decimal {
+    Decimal::from(10000)
+}
+
+fn default_backtest_interval() -> String {
+    "1h".to_string()
+}
+
+fn default_maker_fee_bps() -> Decimal {
+    Decimal::new(15, 1) // Hyperliquid base maker fee, 0.015%
+}
+
+fn default_taker_fee_bps() -> Decimal {
+    Decimal::new(45, 1) // Hyperliquid base taker fee, 0.045%
+}
+
+fn default_slippage() -> SlippageConfig {
+    SlippageConfig::Fixed { bps: Decimal::from(5) }
+}
+
+fn default_funding_rate() -> Decimal {
+    Decimal::new(125, 7) // Hyperliquid's hourly baseline, 0.00125%
+}
+
+fn default_shutdown_deadline_secs() -> u64 {
+    25
+}
+
+fn default_max_message_age_secs() -> u64 {
+    30
+}
+
+fn default_stabilization_secs() -> u64 {
+    60
+}
+
+fn default_backfill_interval() -> String {
+    "1m".to_string()
+}
+
+fn default_max_parse_errors_per_minute() -> u32 {
+    10
+}
+
+fn default_ping_interval_secs() -> u64 {
+    20
+}
+
+fn default_stale_connection_secs() -> u64 {
+    60
+}
+
+fn default_max_order_feed_age_secs() -> u64 {
+    15
+}
+
+fn default_event_buffer() -> usize {
+    1024
+}
+
+fn default_write_buffer() -> usize {
+    256
+}
+
+fn default_ws_connections() -> usize {
+    1
+}
+
+fn default_replay_speed() -> f64 {
+    1.0
+}
+
+fn default_max_latency_ms() -> u64 {
+    2000
+}
+
+fn default_min_available_margin() -> Decimal {
+    Decimal::from(10)
+}
+
+fn default_websocket_timeout_secs() -> u64 {
+    10
+}
+
+fn default_max_clock_skew_secs() -> u64 {
+    5
+}
+
+fn default_watchdog_enabled() -> bool {
+    true
+}
+
+fn default_stall_cycles() -> u32 {
+    6
+}
+
+fn default_max_concurrent_orders() -> usize {
+    4
+}
+
+fn default_cancel_deadline_secs() -> u64 {
+    60
+}
+
+fn default_cancel_deadline_refresh_secs() -> u64 {
+    10
+}
+
+fn default_market_data_ttl_ms() -> u64 {
+    1000
+}
+
+fn default_connect_timeout_ms() -> u64 {
+    10_000
+}
+
+fn default_request_timeout_ms() -> u64 {
+    30_000
+}
+
+fn default_info_per_second() -> f64 {
+    10.0
+}
+
+fn default_info_burst() -> u32 {
+    20
+}
+
+fn default_exchange_per_second() -> f64 {
+    10.0
+}
+
+fn default_exchange_burst() -> u32 {
+    10
+}
+
+fn default_rate_limit_max_wait_ms() -> u64 {
+    10_000
+}
+
+fn default_event_capacity() -> usize {
+    1024
+}
+
+fn default_account_interval_secs() -> u64 {
+    5
+}
+
+fn default_idle_eval_secs() -> u64 {
+    5
+}
+
+fn default_mid_move_bps() -> Decimal {
+    Decimal::from(10)
+}
+
+fn default_analyze_timeout_ms() -> u64 {
+    2000
+}
+
+fn default_http_bind() -> String {
+    "127.0.0.1:8080".to_string()
+}
+
+fn default_reconciliation_interval_secs() -> u64 {
+    3600
+}
+
+fn default_reconciliation_settle_secs() -> u64 {
+    60
+}
+
+fn default_reconciliation_match_window_secs() -> u64 {
+    5
+}
+
+fn default_reconciliation_initial_lookback_hours() -> u64 {
+    24
+}
+
+fn default_reconciliation_alert_threshold() -> usize {
+    1
+}
+
+fn default_profit_sweep_interval_hours() -> u64 {
+    24 * 7
+}
+
+fn default_chaos_timeout_ms() -> u64 {
+    5000
+}
+
+/// Rejections Hyperliquid sends for orders it won't take
+fn default_chaos_reject_messages() -> Vec<String> {
+    vec![
+        "Order must have minimum value of $10.".to_string(),
+        "Insufficient margin to place order.".to_string(),
+        "Order price cannot be more than 80% away from the reference price".to_string(),
+        "Too many cumulative requests sent".to_string(),
+    ]
+}
+
+fn default_chaos_disconnect_secs() -> u64 {
+    30
+}
+
+fn default_chaos_fill_delay_secs() -> u64 {
+    10
+}
+
+fn default_max_consecutive_timeouts() -> u32 {
+    3
+}
+
+fn default_reporting_environment() -> String {
+    "production".to_string()
+}
+
+fn default_funding_interval_hours() -> u32 {
+    1
+}
+
+fn default_recorder_max_total_mb() -> u64 {
+    1024
+}
+
+fn default_recorder_queue_size() -> usize {
+    10_000
+}
+
+fn default_recorder_flush_interval_secs() -> u64 {
+    5
+}
+
+fn default_webhook_timeout_secs() -> u64 {
+    10
+}
+
+fn default_webhook_queue_size() -> usize {
+    1000
+}
+
+fn default_telegram_api_url() -> String {
+    "https://api.telegram.org".to_string()
+}
+
+fn default_notification_max_retries() -> u32 {
+    3
 }
 
 impl Config {
     pub fn load(path: &str) -> Result<Self> {
-        let mut config = ConfigFile::new();
+        let mut builder = ConfigFile::builder();
         
         // Load default configuration
-        config = config.add_source(File::new("config/default", FileFormat::Toml).required(false));
+        builder = builder.add_source(File::new("config/default", FileFormat::Toml).required(false));
         
         // Load custom configuration if provided
         if path != "config/default.toml" {
-            config = config.add_source(File::new(path, FileFormat::Toml).required(true));
+            builder = builder.add_source(File::new(path, FileFormat::Toml).required(true));
         }
         
         // Load environment variables
-        config = config.add_source(config::Environment::with_prefix("HYPERLIQUID"));
+        builder = builder.add_source(config::Environment::with_prefix("HYPERLIQUID"));
         
-        let config: Config = config.try_into()
+        let mut config: Config = builder
+            .build()
+            .and_then(|config| config.try_deserialize())
             .map_err(|e| Error::Config(format!("Failed to load configuration: {}", e)))?;
         
+        config.hyperliquid.fill_default_urls();
+        for account in &mut config.accounts {
+            account.hyperliquid.fill_default_urls();
+        }
         config.validate()?;
         Ok(config)
     }
@@ -85,8 +1373,30 @@ impl Config {
             return Err(Error::Config("API key is required".to_string()));
         }
         
-        if self.hyperliquid.private_key.is_empty() {
-            return Err(Error::Config("Private key is required".to_string()));
+        let has_address = self.hyperliquid.account_address.as_deref().is_some_and(|a| !a.is_empty());
+        if self.hyperliquid.private_key.is_empty() && !has_address {
+            return Err(Error::Config("Private key or account address is required".to_string()));
+        }
+        
+        for (name, address) in [("vault_address", &self.hyperliquid.vault_address), ("subaccount", &self.hyperliquid.subaccount)] {
+            if let Some(address) = address.as_deref().filter(|a| !a.is_empty()) {
+                if !crate::api::wallet::is_address(address) {
+                    return Err(Error::Config(format!(
+                        "hyperliquid.{} must be 0x and 40 hex digits, got {:?}",
+                        name, address
+                    )));
+                }
+            }
+        }
+        self.hyperliquid.check_network()?;
+        if self.hyperliquid.http.connect_timeout_ms == 0 || self.hyperliquid.http.request_timeout_ms == 0 {
+            return Err(Error::Config("hyperliquid.http timeouts must be greater than 0".to_string()));
+        }
+        
+        let vault_set = self.hyperliquid.vault_address.as_deref().is_some_and(|a| !a.is_empty());
+        let subaccount_set = self.hyperliquid.subaccount.as_deref().is_some_and(|a| !a.is_empty());
+        if vault_set && subaccount_set {
+            return Err(Error::Config("Set hyperliquid.vault_address or hyperliquid.subaccount, not both".to_string()));
         }
         
         if self.trading.max_positions == 0 {
@@ -97,19 +1407,290 @@ impl Config {
             return Err(Error::Config("Max position size must be greater than 0".to_string()));
         }
         
+        if let Some(telegram) = &self.notifications.telegram {
+            if telegram.enabled && (telegram.bot_token.is_empty() || telegram.chat_id.is_empty()) {
+                return Err(Error::Config("Telegram notifications require bot_token and chat_id".to_string()));
+            }
+        }
+        
+        if let Some(discord) = &self.notifications.discord {
+            if discord.enabled && discord.webhook_url.is_empty() {
+                return Err(Error::Config("Discord notifications require webhook_url".to_string()));
+            }
+        }
+        
+        if let Some(webhook) = &self.notifications.webhook {
+            if webhook.enabled && webhook.urls.is_empty() {
+                return Err(Error::Config("Webhook notifications require at least one URL".to_string()));
+            }
+        }
+        
+        if self.http.enabled {
+            if self.http.bind.parse::<std::net::SocketAddr>().is_err() {
+                return Err(Error::Config(format!("http.bind must be an address and port, got {}", self.http.bind)));
+            }
+            if self.http.token.as_deref() == Some("") {
+                return Err(Error::Config("http.token must not be empty; leave it out to serve without one".to_string()));
+            }
+        }
+        
+        // One symbol has one leverage setting on the exchange
+        let mut leverages: HashMap<&str, (u32, MarginMode)> = HashMap::new();
+        for (name, strategy) in self.strategies.iter().filter(|(_, s)| s.enabled) {
+            let Some(leverage) = strategy.leverage else {
+                continue;
+            };
+            if leverage == 0 {
+                return Err(Error::Config(format!("Strategy {}: leverage must be at least 1", name)));
+            }
+            let setting = (leverage, strategy.margin_mode);
+            if *leverages.entry(strategy.symbol.as_str()).or_insert(setting) != setting {
+                return Err(Error::Config(format!(
+                    "Strategies trading {} ask for different leverage or margin modes",
+                    strategy.symbol
+                )));
+            }
+        }
+        
+        let mut names = std::collections::HashSet::new();
+        for account in &self.accounts {
+            let valid_name = !account.name.is_empty()
+                && account.name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+            if !valid_name {
+                return Err(Error::Config(format!(
+                    "Account names may only use letters, digits, '-' and '_', got {:?}",
+                    account.name
+                )));
+            }
+            if !names.insert(account.name.as_str()) {
+                return Err(Error::Config(format!("Account {} is configured twice", account.name)));
+            }
+            self.for_account(account).validate().map_err(|e| match e {
+                Error::Config(message) => Error::Config(format!("Account {}: {}", account.name, message)),
+                other => other,
+            })?;
+        }
+        
+        if self.reconciliation.enabled {
+            if !self.storage.enabled {
+                return Err(Error::Config("Fill reconciliation needs storage, where fills are recorded".to_string()));
+            }
+            if self.reconciliation.interval_secs == 0 {
+                return Err(Error::Config("reconciliation.interval_secs must be greater than 0".to_string()));
+            }
+        }
+        
+        if self.scheduler.account_interval_secs == 0 {
+            return Err(Error::Config("scheduler.account_interval_secs must be greater than 0".to_string()));
+        }
+        if self.scheduler.idle_eval_secs == 0 {
+            return Err(Error::Config("scheduler.idle_eval_secs must be greater than 0".to_string()));
+        }
+        if self.scheduler.mid_move_bps < Decimal::ZERO {
+            return Err(Error::Config("scheduler.mid_move_bps must not be negative".to_string()));
+        }
+        
+        if self.profit_sweep.enabled {
+            let destination = &self.profit_sweep.destination;
+            if !crate::api::wallet::is_address(destination) {
+                return Err(Error::Config(format!(
+                    "profit_sweep.destination must be 0x and 40 hex digits, got {:?}",
+                    destination
+                )));
+            }
+            if self.profit_sweep.threshold < Decimal::ZERO {
+                return Err(Error::Config("profit_sweep.threshold must not be negative".to_string()));
+            }
+            if self.profit_sweep.interval_hours == 0 {
+                return Err(Error::Config("profit_sweep.interval_hours must be greater than 0".to_string()));
+            }
+        }
+        
+        if self.reporting.enabled && self.reporting.dsn.is_empty() {
+            return Err(Error::Config("Error reporting requires a DSN".to_string()));
+        }
+        
+        if self.schedule.funding_interval_hours == 0 || 24 % self.schedule.funding_interval_hours != 0 {
+            return Err(Error::Config("Funding interval must be a divisor of 24 hours".to_string()));
+        }
+        
+        if self.schedule.maintenance_windows.iter().any(|w| w.end <= w.start) {
+            return Err(Error::Config("Maintenance windows must end after they start".to_string()));
+        }
+        
+        if self.backtest.initial_balance <= Decimal::ZERO {
+            return Err(Error::Config("Backtest initial balance must be greater than 0".to_string()));
+        }
+        
+        // Maker fees may be negative: rebates at higher volume tiers
+        if self.costs.taker_fee_bps < Decimal::ZERO {
+            return Err(Error::Config("Taker fee must not be negative".to_string()));
+        }
+        
+        let slippage_bps = match &self.costs.slippage {
+            SlippageConfig::Fixed { bps } => vec![*bps],
+            SlippageConfig::Volume { impact_bps, max_bps } => vec![*impact_bps, *max_bps],
+            SlippageConfig::Spread { fallback_bps } => vec![*fallback_bps],
+        };
+        if slippage_bps.iter().any(|bps| *bps < Decimal::ZERO) {
+            return Err(Error::Config("Slippage must not be negative".to_string()));
+        }
+        
+        if self.shutdown.deadline_secs == 0 {
+            return Err(Error::Config("Shutdown deadline must be greater than 0".to_string()));
+        }
+        
+        // A failed cycle waits two intervals before retrying
+        if self.watchdog.stall_cycles < 3 {
+            return Err(Error::Config("Watchdog stall_cycles must be at least 3".to_string()));
+        }
+        
+        if self.trading.max_concurrent_orders == 0 {
+            return Err(Error::Config("max_concurrent_orders must be at least 1".to_string()));
+        }
+        
+        // The exchange won't schedule a cancel less than 5s away
+        let (deadline, refresh) = (self.trading.cancel_deadline_secs, self.trading.cancel_deadline_refresh_secs);
+        if deadline > 0 && (deadline < 5 || refresh == 0 || refresh >= deadline) {
+            return Err(Error::Config(format!(
+                "trading.cancel_deadline_secs must be at least 5 and longer than cancel_deadline_refresh_secs, which must be at least 1; got {} and {}",
+                deadline, refresh
+            )));
+        }
+        
+        // A ping must have a chance to be answered before the connection counts as stale
+        let (ping, stale) = (self.data_source.ping_interval_secs, self.data_source.stale_connection_secs);
+        if ping == 0 || stale <= ping {
+            return Err(Error::Config(format!(
+                "data_source.stale_connection_secs must be longer than ping_interval_secs, which must be at least 1; got {} and {}",
+                stale, ping
+            )));
+        }
+        
+        if self.data_source.event_buffer == 0 || self.data_source.write_buffer == 0 {
+            return Err(Error::Config("data_source.event_buffer and write_buffer must be greater than 0".to_string()));
+        }
+        
+        if self.data_source.ws_connections == 0 {
+            return Err(Error::Config("data_source.ws_connections must be at least 1".to_string()));
+        }
+        
+        if !(self.data_source.replay_speed >= 0.0 && self.data_source.replay_speed.is_finite()) {
+            return Err(Error::Config(format!(
+                "data_source.replay_speed must be 0 or more, got {}",
+                self.data_source.replay_speed
+            )));
+        }
+        
+        if self.events.capacity == 0 {
+            return Err(Error::Config("Event bus capacity must be greater than 0".to_string()));
+        }
+        
+        if self.analysis.timeout_ms == 0 || self.analysis.timeout_overrides.values().any(|ms| *ms == 0) {
+            return Err(Error::Config("Analysis timeouts must be greater than 0".to_string()));
+        }
+        
         Ok(())
     }
+    
+    /// The config `account` trades under: this one with the account's
+    /// wallet, strategies and risk limits, and its storage, journal and
+    /// recordings moved to paths of its own
+    pub fn for_account(&self, account: &AccountConfig) -> Config {
+        let mut config = self.clone();
+        config.accounts = Vec::new();
+        config.hyperliquid = account.hyperliquid.clone();
+        config.hyperliquid.ws_url = self.hyperliquid.ws_url.clone();
+        config.hyperliquid.http = self.hyperliquid.http.clone();
+        config.strategies = account.strategies.clone();
+        if let Some(risk_management) = &account.risk_management {
+            config.risk_management = risk_management.clone();
+        }
+        
+        config.storage.path = account_file(&self.storage.path, &account.name);
+        config.journal.directory = format!("{}/{}", self.journal.directory, account.name);
+        config.recorder.directory = format!("{}/{}", self.recorder.directory, account.name);
+        config
+    }
+    
+    /// Restricts the run to the named strategies (enabling them if needed)
+    /// and/or to strategies trading one of `symbols`. Empty lists leave the
+    /// config alone. Unknown names, or overrides that leave nothing enabled,
+    /// are errors.
+    pub fn apply_strategy_overrides(&mut self, names: &[String], symbols: &[String]) -> Result<StrategyOverrides> {
+        let mut unknown: Vec<&str> = names
+            .iter()
+            .filter(|name| !self.strategies.contains_key(name.as_str()))
+            .map(String::as_str)
+            .collect();
+        if !unknown.is_empty() {
+            unknown.sort();
+            let mut known: Vec<&str> = self.strategies.keys().map(String::as_str).collect();
+            known.sort();
+            return Err(Error::Config(format!(
+                "Unknown strateg{} {} (configured: {})",
+                if unknown.len() == 1 { "y" } else { "ies" },
+                unknown.join(", "),
+                known.join(", ")
+            )));
+        }
+        
+        let mut overrides = StrategyOverrides::default();
+        for (name, strategy) in self.strategies.iter_mut() {
+            let mut enabled = strategy.enabled;
+            if !names.is_empty() {
+                enabled = names.contains(name);
+            }
+            if !symbols.is_empty() && !symbols.iter().any(|s| s.eq_ignore_ascii_case(&strategy.symbol)) {
+                enabled = false;
+            }
+            
+            if enabled != strategy.enabled {
+                strategy.enabled = enabled;
+                if enabled {
+                    overrides.enabled.push(name.clone());
+                } else {
+                    overrides.disabled.push(name.clone());
+                }
+            }
+        }
+        overrides.enabled.sort();
+        overrides.disabled.sort();
+        
+        if (!names.is_empty() || !symbols.is_empty()) && !self.strategies.values().any(|s| s.enabled) {
+            return Err(Error::Config("No strategies left enabled after --strategies/--symbols".to_string()));
+        }
+        
+        Ok(overrides)
+    }
+}
+
+/// `path` with `-name` before its extension, e.g. `data/bot-main.db`
+fn account_file(path: &str, name: &str) -> String {
+    let path = std::path::Path::new(path);
+    let stem = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or_default();
+    let file = match path.extension().and_then(|extension| extension.to_str()) {
+        Some(extension) => format!("{}-{}.{}", stem, name, extension),
+        None => format!("{}-{}", stem, name),
+    };
+    path.with_file_name(file).to_string_lossy().into_owned()
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             hyperliquid: HyperliquidConfig {
-                base_url: "https://api.hyperliquid.xyz".to_string(),
-                ws_url: "wss://api.hyperliquid.xyz/ws".to_string(),
+                base_url: Network::Testnet.api_url().to_string(),
+                ws_url: Network::Testnet.ws_url().to_string(),
                 api_key: String::new(),
                 private_key: String::new(),
+                account_address: None,
+                vault_address: None,
+                subaccount: None,
                 testnet: true,
+                rate_limit: RateLimitConfig::default(),
+                market_data_ttl_ms: default_market_data_ttl_ms(),
+                http: HttpClientConfig::default(),
             },
             trading: TradingConfig {
                 dry_run: true,
@@ -118,6 +1699,10 @@ impl Default for Config {
                 order_timeout_seconds: 30,
                 retry_attempts: 3,
                 retry_delay_ms: 1000,
+                max_concurrent_orders: default_max_concurrent_orders(),
+                cancel_deadline_secs: default_cancel_deadline_secs(),
+                cancel_deadline_refresh_secs: default_cancel_deadline_refresh_secs(),
+                execution_transport: ExecutionTransport::default(),
             },
             strategies: HashMap::new(),
             risk_management: RiskManagementConfig {
@@ -132,7 +1717,30 @@ impl Default for Config {
                 file_path: Some("logs/bot.log".to_string()),
                 max_file_size_mb: 100,
                 max_files: 10,
+                format: LogFormat::Pretty,
             },
+            notifications: NotificationsConfig::default(),
+            journal: JournalConfig::default(),
+            storage: StorageConfig::default(),
+            recorder: RecorderConfig::default(),
+            schedule: ScheduleConfig::default(),
+            reporting: ReportingConfig::default(),
+            control: ControlConfig::default(),
+            backtest: BacktestConfig::default(),
+            paper: PaperConfig::default(),
+            costs: CostConfig::default(),
+            shutdown: ShutdownConfig::default(),
+            data_source: DataSourceConfig::default(),
+            preflight: PreflightConfig::default(),
+            watchdog: WatchdogConfig::default(),
+            events: EventsConfig::default(),
+            analysis: AnalysisConfig::default(),
+            scheduler: SchedulerConfig::default(),
+            chaos: ChaosConfig::default(),
+            http: HttpConfig::default(),
+            accounts: Vec::new(),
+            reconciliation: ReconciliationConfig::default(),
+            profit_sweep: ProfitSweepConfig::default(),
         }
     }
 }
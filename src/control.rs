@@ -0,0 +1,374 @@
+use crate::{
+    analysis::AnalysisStats,
+    chaos::ChaosStats,
+    data_source::DataSourceStatus,
+    config::ControlConfig,
+    error::{Error, Result},
+    events::EventStats,
+    models::{BotStatus, Order, Position},
+    snapshot::Snapshot,
+    trading_bot::TradingBot,
+    utils::{format_currency, format_percentage},
+};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tracing::{debug, info, warn};
+
+const STATUS_COMMAND: &str = "status";
+const SNAPSHOT_COMMAND: &str = "snapshot";
+// Requests are a single short command line; anything longer is rejected
+const MAX_REQUEST_LEN: u64 = 256;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyStatus {
+    pub name: String,
+    pub strategy_type: String,
+    pub symbol: String,
+    pub enabled: bool,
+    #[serde(default)]
+    pub analysis: AnalysisStats,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebSocketHealth {
+    /// Always set by current bots; optional so replies from older ones,
+    /// which skipped it while the client was busy, still parse
+    pub connected: Option<bool>,
+    /// Frames dropped for failing to parse, by channel
+    #[serde(default)]
+    pub parse_errors: BTreeMap<String, u64>,
+    /// Messages dropped for a consumer that fell behind, by channel
+    #[serde(default)]
+    pub dropped_messages: BTreeMap<String, u64>,
+}
+
+/// Everything `status` shows, as served by a running bot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusSnapshot {
+    /// The account reported on, when the process trades several
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account: Option<String>,
+    pub status: BotStatus,
+    pub equity: Option<Decimal>,
+    /// Whether `equity` is a dry run's paper account
+    #[serde(default)]
+    pub paper: bool,
+    /// The real account's balance during a dry run
+    #[serde(default)]
+    pub live_balance: Option<Decimal>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub positions: Vec<Position>,
+    pub open_orders: Vec<Order>,
+    pub strategies: Vec<StrategyStatus>,
+    pub websocket: WebSocketHealth,
+    #[serde(default)]
+    pub events: EventStats,
+    /// Failures injected so far, during a chaos run
+    #[serde(default)]
+    pub chaos: Option<ChaosStats>,
+}
+
+impl StatusSnapshot {
+    /// Renders what `status` prints
+    pub fn write<W: Write>(&self, mut writer: W) -> Result<()> {
+        let status = &self.status;
+        let risk = &status.risk_metrics;
+        let uptime = status.uptime_seconds;
+        
+        if let Some(account) = &self.account {
+            writeln!(writer, "Account:        {}", account)?;
+        }
+        writeln!(writer, "Running:        {}", if status.is_running { "yes" } else { "no" })?;
+        writeln!(writer, "Uptime:         {}h {}m {}s", uptime / 3600, uptime % 3600 / 60, uptime % 60)?;
+        let equity_label = if self.paper { "Paper equity:" } else { "Equity:" };
+        match (self.equity, self.updated_at) {
+            (Some(equity), Some(updated_at)) => writeln!(
+                writer,
+                "{:<15} {} (as of {})",
+                equity_label,
+                format_currency(equity),
+                updated_at.format("%Y-%m-%d %H:%M:%S UTC")
+            )?,
+            _ => writeln!(writer, "{:<15} n/a (no account update yet)", equity_label)?,
+        }
+        if self.paper {
+            match self.live_balance {
+                Some(balance) => writeln!(writer, "Live balance:   {} (not traded by this dry run)", format_currency(balance))?,
+                None => writeln!(writer, "Live balance:   n/a (not traded by this dry run)")?,
+            }
+        }
+        writeln!(writer, "Daily PnL:      {}", format_currency(risk.daily_pnl))?;
+        writeln!(writer, "Total PnL:      {}", format_currency(risk.total_pnl))?;
+        writeln!(
+            writer,
+            "Funding paid:   {} ({} today)",
+            format_currency(risk.funding_paid),
+            format_currency(risk.daily_funding_paid)
+        )?;
+        writeln!(writer, "Fees paid:      {}", format_currency(risk.fees_paid))?;
+        writeln!(
+            writer,
+            "Trades:         {} ({} ok, {} failed)",
+            status.total_trades, status.successful_trades, status.failed_trades
+        )?;
+        writeln!(
+            writer,
+            "Drawdown:       {} (max {})",
+            format_percentage(risk.current_drawdown * Decimal::from(100)),
+            format_percentage(risk.max_drawdown * Decimal::from(100))
+        )?;
+        writeln!(writer, "Sharpe 7d/30d:  {:.2} / {:.2}", risk.sharpe_ratio_7d, risk.sharpe_ratio_30d)?;
+        writeln!(
+            writer,
+            "WebSocket:      {}{}",
+            match self.websocket.connected {
+                Some(true) => "connected",
+                Some(false) => "disconnected",
+                None => "unknown",
+            },
+            match status.ws_last_message_secs {
+                Some(secs) => format!(" (last message {}s ago)", secs),
+                None => String::new(),
+            }
+        )?;
+        if status.ws_recovered_fills > 0 {
+            writeln!(writer, "Missed fills:   {} (recovered over REST after reconnecting)", status.ws_recovered_fills)?;
+        }
+        if !self.websocket.parse_errors.is_empty() {
+            let counts: Vec<String> = self
+                .websocket
+                .parse_errors
+                .iter()
+                .map(|(channel, count)| format!("{} {}", channel, count))
+                .collect();
+            writeln!(writer, "Parse errors:   {}", counts.join(", "))?;
+        }
+        if !self.websocket.dropped_messages.is_empty() {
+            let counts: Vec<String> = self
+                .websocket
+                .dropped_messages
+                .iter()
+                .map(|(channel, count)| format!("{} {}", channel, count))
+                .collect();
+            writeln!(writer, "Dropped:        {}", counts.join(", "))?;
+        }
+        let data_source = &status.data_source;
+        match data_source.since {
+            Some(since) => writeln!(
+                writer,
+                "Data source:    {} since {} ({} switches)",
+                data_source.active,
+                since.format("%Y-%m-%d %H:%M:%S UTC"),
+                data_source.switches
+            )?,
+            None => writeln!(writer, "Data source:    {}", data_source.active)?,
+        }
+        let events = &self.events;
+        match events.last_event_at {
+            Some(at) if events.missed > 0 => writeln!(
+                writer,
+                "Events:         {} (last {}, {} missed by the status reporter)",
+                events.last_seq,
+                at.format("%Y-%m-%d %H:%M:%S UTC"),
+                events.missed
+            )?,
+            Some(at) => writeln!(writer, "Events:         {} (last {})", events.last_seq, at.format("%Y-%m-%d %H:%M:%S UTC"))?,
+            None => writeln!(writer, "Events:         none yet")?,
+        }
+        if let Some(chaos) = &self.chaos {
+            writeln!(
+                writer,
+                "Chaos:          {} timeouts, {} rejections, {} disconnects, {} delayed fills, {} stale prices",
+                chaos.timeouts, chaos.rejections, chaos.disconnects, chaos.delayed_fills, chaos.stale_prices
+            )?;
+        }
+        
+        writeln!(writer)?;
+        writeln!(writer, "Strategies:")?;
+        for strategy in &self.strategies {
+            let analysis = &strategy.analysis;
+            writeln!(
+                writer,
+                "  {:<20} {:<10} {:<10} {:<8} analyze avg {}ms, max {}ms, {} timeouts",
+                strategy.name,
+                strategy.strategy_type,
+                strategy.symbol,
+                if strategy.enabled { "enabled" } else { "disabled" },
+                analysis.mean_ms(),
+                analysis.max_ms,
+                analysis.timeouts
+            )?;
+        }
+        
+        writeln!(writer)?;
+        writeln!(writer, "Positions:")?;
+        if self.positions.is_empty() {
+            writeln!(writer, "  none")?;
+        }
+        for position in &self.positions {
+            writeln!(
+                writer,
+                "  {:<10} {:?} {} @ {} (uPnL {})",
+                position.symbol,
+                position.side,
+                position.size,
+                position.entry_price,
+                format_currency(position.unrealized_pnl)
+            )?;
+        }
+        
+        writeln!(writer)?;
+        writeln!(writer, "Open orders:")?;
+        if self.open_orders.is_empty() {
+            writeln!(writer, "  none")?;
+        }
+        for order in &self.open_orders {
+            let price = order.price.map(|p| p.to_string()).unwrap_or_else(|| "market".to_string());
+            writeln!(
+                writer,
+                "  {:<10} {:?} {} @ {} ({:?}, {})",
+                order.symbol, order.side, order.quantity, price, order.order_type, order.id
+            )?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Whether the bot is working, as served to health probes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Health {
+    pub healthy: bool,
+    /// Seconds since the trading loop's last heartbeat
+    pub heartbeat_age_secs: u64,
+    /// What the trading loop is doing
+    pub stage: String,
+    pub data_source: DataSourceStatus,
+    /// What's wrong, when the bot isn't healthy
+    pub problems: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+enum ControlResponse {
+    Status(Box<StatusSnapshot>),
+    Snapshot(Box<Snapshot>),
+    Error { error: String },
+}
+
+/// Serves read-only status and snapshot requests on a local socket until the process
+/// exits. Uses a unix domain socket where available and a localhost-only
+/// TCP port elsewhere.
+pub async fn serve(bot: Arc<TradingBot>, config: ControlConfig) -> Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        use tokio::net::UnixListener;
+        
+        let path = std::path::Path::new(&config.socket_path);
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        // A socket left behind by an unclean exit would block the bind
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+        
+        let listener = UnixListener::bind(path)?;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+        info!("🎛️ Control endpoint listening on {}", config.socket_path);
+        
+        loop {
+            let (stream, _) = listener.accept().await?;
+            tokio::spawn(handle_connection(bot.clone(), stream));
+        }
+    }
+    
+    #[cfg(not(unix))]
+    {
+        let listener = tokio::net::TcpListener::bind(("127.0.0.1", config.tcp_port)).await?;
+        info!("🎛️ Control endpoint listening on 127.0.0.1:{}", config.tcp_port);
+        
+        loop {
+            let (stream, _) = listener.accept().await?;
+            tokio::spawn(handle_connection(bot.clone(), stream));
+        }
+    }
+}
+
+async fn handle_connection<S: AsyncRead + AsyncWrite + Unpin>(bot: Arc<TradingBot>, stream: S) {
+    let (read, mut write) = tokio::io::split(stream);
+    let mut request = String::new();
+    
+    let response = match BufReader::new(read).take(MAX_REQUEST_LEN).read_line(&mut request).await {
+        Ok(_) if request.trim() == STATUS_COMMAND => ControlResponse::Status(Box::new(bot.snapshot().await)),
+        Ok(_) if request.trim() == SNAPSHOT_COMMAND => match bot.export_snapshot().await {
+            Ok(snapshot) => ControlResponse::Snapshot(Box::new(snapshot)),
+            Err(e) => ControlResponse::Error { error: e.to_string() },
+        },
+        Ok(_) => ControlResponse::Error {
+            error: format!("unknown command: {}", request.trim()),
+        },
+        Err(e) => {
+            debug!("Control request read failed: {}", e);
+            return;
+        }
+    };
+    
+    let mut body = match serde_json::to_vec(&response) {
+        Ok(body) => body,
+        Err(e) => {
+            warn!("Failed to encode control response: {}", e);
+            return;
+        }
+    };
+    body.push(b'\n');
+    
+    if let Err(e) = write.write_all(&body).await {
+        debug!("Control response write failed: {}", e);
+    }
+    let _ = write.shutdown().await;
+}
+
+/// Fetches a snapshot from the bot running with this control config
+pub async fn query_status(config: &ControlConfig) -> Result<StatusSnapshot> {
+    match request(config, STATUS_COMMAND).await? {
+        ControlResponse::Status(snapshot) => Ok(*snapshot),
+        ControlResponse::Snapshot(_) => Err(Error::Api("unexpected response to status".to_string())),
+        ControlResponse::Error { error } => Err(Error::Api(error)),
+    }
+}
+
+/// Exports the full state of the bot running with this control config
+pub async fn query_snapshot(config: &ControlConfig) -> Result<Snapshot> {
+    match request(config, SNAPSHOT_COMMAND).await? {
+        ControlResponse::Snapshot(snapshot) => Ok(*snapshot),
+        ControlResponse::Status(_) => Err(Error::Api("unexpected response to snapshot".to_string())),
+        ControlResponse::Error { error } => Err(Error::Api(error)),
+    }
+}
+
+async fn request(config: &ControlConfig, command: &str) -> Result<ControlResponse> {
+    #[cfg(unix)]
+    let stream = tokio::net::UnixStream::connect(&config.socket_path).await.map_err(|e| {
+        Error::Config(format!("Cannot reach bot at {}: {} (is it running with control.enabled = true?)", config.socket_path, e))
+    })?;
+    
+    #[cfg(not(unix))]
+    let stream = tokio::net::TcpStream::connect(("127.0.0.1", config.tcp_port)).await.map_err(|e| {
+        Error::Config(format!("Cannot reach bot on port {}: {} (is it running with control.enabled = true?)", config.tcp_port, e))
+    })?;
+    
+    let (read, mut write) = tokio::io::split(stream);
+    write.write_all(format!("{}\n", command).as_bytes()).await?;
+    
+    let mut line = String::new();
+    BufReader::new(read).read_line(&mut line).await?;
+    
+    Ok(serde_json::from_str(&line)?)
+}
@@ -0,0 +1,179 @@
+use crate::{
+    config::{Config, CostConfig, SlippageConfig},
+    models::{FeeSchedule, MarketData, OrderSide},
+};
+use chrono::{DateTime, TimeZone, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+const BPS: i64 = 10_000;
+
+/// Whether a fill rested on the book or took liquidity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Liquidity {
+    Maker,
+    Taker,
+}
+
+/// What's known about the market when a fill is simulated
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MarketConditions {
+    /// Base units traded over the bar in backtests, or the last 24h live
+    pub volume: Option<Decimal>,
+    pub best_bid: Option<Decimal>,
+    pub best_ask: Option<Decimal>,
+}
+
+impl From<&MarketData> for MarketConditions {
+    fn from(market_data: &MarketData) -> Self {
+        Self {
+            volume: Some(market_data.volume_24h).filter(|volume| *volume > Decimal::ZERO),
+            best_bid: None,
+            best_ask: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SimulatedFill {
+    pub price: Decimal,
+    pub fee: Decimal,
+    /// What slippage cost against the reference price, in quote currency
+    pub slippage: Decimal,
+}
+
+/// Fees, slippage and funding for simulated trading. Shared by dry runs and
+/// the backtester so both see the same costs.
+#[derive(Debug, Clone)]
+pub struct CostModel {
+    config: CostConfig,
+    funding_interval_hours: u32,
+    funding_history: HashMap<String, Vec<(DateTime<Utc>, Decimal)>>,
+}
+
+impl Default for CostModel {
+    fn default() -> Self {
+        Self::new(CostConfig::default(), 1)
+    }
+}
+
+impl CostModel {
+    pub fn new(config: CostConfig, funding_interval_hours: u32) -> Self {
+        Self {
+            config,
+            funding_interval_hours: funding_interval_hours.max(1),
+            funding_history: HashMap::new(),
+        }
+    }
+    
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(config.costs.clone(), config.schedule.funding_interval_hours)
+    }
+    
+    /// Uses recorded funding rates for `symbol`, each applying from its
+    /// timestamp on, instead of the configured rate
+    pub fn with_funding_history(mut self, symbol: &str, mut rates: Vec<(DateTime<Utc>, Decimal)>) -> Self {
+        rates.sort_by_key(|(timestamp, _)| *timestamp);
+        self.funding_history.insert(symbol.to_string(), rates);
+        self
+    }
+    
+    /// The configured fees as rates, for estimating what the exchange charges
+    pub fn fee_schedule(&self) -> FeeSchedule {
+        FeeSchedule {
+            maker_rate: self.config.maker_fee_bps / Decimal::from(BPS),
+            taker_rate: self.config.taker_fee_bps / Decimal::from(BPS),
+        }
+    }
+    
+    pub fn fee_bps(&self, liquidity: Liquidity) -> Decimal {
+        match liquidity {
+            Liquidity::Maker => self.config.maker_fee_bps,
+            Liquidity::Taker => self.config.taker_fee_bps,
+        }
+    }
+    
+    fn slippage_bps(&self, quantity: Decimal, market: &MarketConditions) -> Decimal {
+        match &self.config.slippage {
+            SlippageConfig::Fixed { bps } => *bps,
+            SlippageConfig::Volume { impact_bps, max_bps } => match market.volume {
+                Some(volume) => (*impact_bps * quantity / volume).min(*max_bps),
+                None => *max_bps,
+            },
+            SlippageConfig::Spread { fallback_bps } => *fallback_bps,
+        }
+    }
+    
+    fn book_price(&self, side: &OrderSide, market: &MarketConditions) -> Option<Decimal> {
+        if !matches!(self.config.slippage, SlippageConfig::Spread { .. }) {
+            return None;
+        }
+        match side {
+            OrderSide::Buy => market.best_ask,
+            OrderSide::Sell => market.best_bid,
+        }
+    }
+    
+    /// Fills `quantity` against `reference`, the last or mid price. Maker
+    /// fills get the reference price; taker fills pay slippage.
+    pub fn fill(
+        &self,
+        side: &OrderSide,
+        quantity: Decimal,
+        reference: Decimal,
+        liquidity: Liquidity,
+        market: &MarketConditions,
+    ) -> SimulatedFill {
+        let price = match liquidity {
+            Liquidity::Maker => reference,
+            Liquidity::Taker => self.book_price(side, market).unwrap_or_else(|| {
+                let slippage = self.slippage_bps(quantity, market) / Decimal::from(BPS);
+                match side {
+                    OrderSide::Buy => reference * (Decimal::ONE + slippage),
+                    OrderSide::Sell => reference * (Decimal::ONE - slippage),
+                }
+            }),
+        };
+        
+        let slippage = match side {
+            OrderSide::Buy => (price - reference) * quantity,
+            OrderSide::Sell => (reference - price) * quantity,
+        };
+        
+        SimulatedFill {
+            price,
+            fee: price * quantity * self.fee_bps(liquidity) / Decimal::from(BPS),
+            slippage,
+        }
+    }
+    
+    /// Funding settlements after `after`, up to and including `until`
+    pub fn funding_times(&self, after: DateTime<Utc>, until: DateTime<Utc>) -> Vec<DateTime<Utc>> {
+        let interval = i64::from(self.funding_interval_hours) * 3600;
+        let mut next = (after.timestamp().div_euclid(interval) + 1) * interval;
+        let mut times = Vec::new();
+        
+        while next <= until.timestamp() {
+            times.push(Utc.timestamp_opt(next, 0).single().unwrap_or_default());
+            next += interval;
+        }
+        times
+    }
+    
+    pub fn funding_rate(&self, symbol: &str, at: DateTime<Utc>) -> Decimal {
+        if let Some(rates) = self.funding_history.get(symbol) {
+            let known = rates.partition_point(|(timestamp, _)| *timestamp <= at);
+            if known > 0 {
+                return rates[known - 1].1;
+            }
+        }
+        self.config.funding_rates.get(symbol).copied().unwrap_or(self.config.funding_rate)
+    }
+    
+    /// Funding paid at `at` on a position of signed `quantity` marked at
+    /// `mark`. Negative when the position receives funding.
+    pub fn funding_payment(&self, symbol: &str, quantity: Decimal, mark: Decimal, at: DateTime<Utc>) -> Decimal {
+        quantity * mark * self.funding_rate(symbol, at)
+    }
+}
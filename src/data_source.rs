@@ -0,0 +1,150 @@
+use crate::config::DataSourceConfig;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Where the trading cycle gets its market data
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataSource {
+    /// Polled once a cycle; used until the websocket has proven healthy
+    #[default]
+    Rest,
+    WebSocket,
+}
+
+impl std::fmt::Display for DataSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DataSource::Rest => write!(f, "REST"),
+            DataSource::WebSocket => write!(f, "websocket"),
+        }
+    }
+}
+
+/// The active data source, as shown in the bot's status
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DataSourceStatus {
+    pub active: DataSource,
+    /// When the bot last switched source
+    pub since: Option<DateTime<Utc>>,
+    pub switches: u64,
+}
+
+/// What the supervisor knows about the websocket each cycle
+#[derive(Debug, Clone)]
+pub struct StreamHealth {
+    pub connected: bool,
+    pub last_message_at: Option<DateTime<Utc>>,
+    /// Reconnect attempts that have failed since the last success
+    pub reconnect_failures: u32,
+}
+
+impl StreamHealth {
+    /// Why the stream can't be trusted right now, if it can't
+    pub fn problem(&self, now: DateTime<Utc>, max_message_age: Duration) -> Option<String> {
+        if !self.connected {
+            return Some(match self.reconnect_failures {
+                0 => "websocket disconnected".to_string(),
+                failures => format!("websocket disconnected, {} reconnects failed", failures),
+            });
+        }
+        
+        match self.last_message_at {
+            None => Some("no websocket messages yet".to_string()),
+            Some(at) if now - at > max_message_age => {
+                Some(format!("no websocket messages for {}s", (now - at).num_seconds()))
+            }
+            Some(_) => None,
+        }
+    }
+}
+
+/// A change of data source
+#[derive(Debug, Clone, PartialEq)]
+pub enum SourceSwitch {
+    /// Fell back to REST polling
+    Degraded { reason: String },
+    /// Back on the websocket. `gap` is the stretch the stream missed, which
+    /// wants backfilling; `None` on the first switch after starting up.
+    Recovered { gap: Option<(DateTime<Utc>, DateTime<Utc>)> },
+}
+
+/// Decides each cycle whether market data comes from the websocket or REST
+/// polling. An unhealthy stream switches to REST straight away; a healthy
+/// one is only trusted again after staying healthy for the stabilization
+/// period, so a flapping connection doesn't flip the source every cycle.
+pub struct DataSourceSupervisor {
+    max_message_age: Duration,
+    stabilization: Duration,
+    status: DataSourceStatus,
+    /// When the stream went quiet, while it's being bridged with REST
+    degraded_from: Option<DateTime<Utc>>,
+    healthy_since: Option<DateTime<Utc>>,
+}
+
+impl DataSourceSupervisor {
+    pub fn new(config: &DataSourceConfig) -> Self {
+        Self {
+            max_message_age: Duration::seconds(config.max_message_age_secs as i64),
+            stabilization: Duration::seconds(config.stabilization_secs as i64),
+            status: DataSourceStatus::default(),
+            degraded_from: None,
+            healthy_since: None,
+        }
+    }
+    
+    pub fn active(&self) -> DataSource {
+        self.status.active
+    }
+    
+    pub fn status(&self) -> DataSourceStatus {
+        self.status.clone()
+    }
+    
+    /// Whether REST polling is standing in for a websocket that has failed
+    pub fn degraded(&self) -> bool {
+        self.degraded_from.is_some()
+    }
+    
+    /// How old streamed data may be before REST is used instead
+    pub fn max_message_age(&self) -> Duration {
+        self.max_message_age
+    }
+    
+    /// Takes in the stream's health and switches source if it calls for it
+    pub fn check(&mut self, health: &StreamHealth, now: DateTime<Utc>) -> Option<SourceSwitch> {
+        let problem = health.problem(now, self.max_message_age);
+        
+        match (self.status.active, problem) {
+            (DataSource::WebSocket, Some(reason)) => {
+                self.healthy_since = None;
+                self.degraded_from = Some(health.last_message_at.unwrap_or(now).min(now));
+                self.switch(DataSource::Rest, now);
+                Some(SourceSwitch::Degraded { reason })
+            }
+            (DataSource::WebSocket, None) => None,
+            (DataSource::Rest, Some(_)) => {
+                self.healthy_since = None;
+                None
+            }
+            (DataSource::Rest, None) => {
+                let healthy_since = *self.healthy_since.get_or_insert(now);
+                if now - healthy_since < self.stabilization {
+                    return None;
+                }
+                
+                self.healthy_since = None;
+                self.switch(DataSource::WebSocket, now);
+                Some(SourceSwitch::Recovered {
+                    gap: self.degraded_from.take().map(|from| (from, now)),
+                })
+            }
+        }
+    }
+    
+    fn switch(&mut self, source: DataSource, now: DateTime<Utc>) {
+        self.status.active = source;
+        self.status.since = Some(now);
+        self.status.switches += 1;
+    }
+}
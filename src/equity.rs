@@ -0,0 +1,132 @@
+use crate::{
+    error::{Error, Result},
+    metrics::{PerformanceWindow, Window},
+    models::EquityPoint,
+    storage::EquitySample,
+};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl FromStr for ExportFormat {
+    type Err = Error;
+    
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "csv" => Ok(ExportFormat::Csv),
+            "json" => Ok(ExportFormat::Json),
+            other => Err(Error::InvalidInput(format!("Unknown export format: {}", other))),
+        }
+    }
+}
+
+pub fn export<W: Write>(samples: &[EquitySample], format: ExportFormat, mut writer: W) -> Result<()> {
+    match format {
+        ExportFormat::Csv => {
+            writeln!(writer, "timestamp,equity,available_balance,total_pnl")?;
+            for sample in samples {
+                writeln!(
+                    writer,
+                    "{},{},{},{}",
+                    sample.timestamp.to_rfc3339(),
+                    sample.equity,
+                    sample.available_balance,
+                    sample.total_pnl
+                )?;
+            }
+        }
+        ExportFormat::Json => {
+            serde_json::to_writer_pretty(&mut writer, samples)?;
+            writeln!(writer)?;
+        }
+    }
+    
+    writer.flush()?;
+    Ok(())
+}
+
+/// Largest peak-to-trough decline as a fraction of the peak
+pub fn max_drawdown(samples: &[EquitySample]) -> Decimal {
+    let mut peak = Decimal::ZERO;
+    let mut max_drawdown = Decimal::ZERO;
+    
+    for sample in samples {
+        peak = peak.max(sample.equity);
+        if peak > Decimal::ZERO {
+            max_drawdown = max_drawdown.max((peak - sample.equity) / peak);
+        }
+    }
+    
+    max_drawdown
+}
+
+/// Timestamps of the peak and trough of the largest drawdown, if equity
+/// ever fell
+pub fn max_drawdown_period(samples: &[EquitySample]) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let mut peak: Option<&EquitySample> = None;
+    let mut max_drawdown = Decimal::ZERO;
+    let mut period = None;
+    
+    for sample in samples {
+        let current_peak = match peak {
+            Some(peak) if peak.equity >= sample.equity => peak,
+            _ => {
+                peak = Some(sample);
+                continue;
+            }
+        };
+        if current_peak.equity > Decimal::ZERO {
+            let drawdown = (current_peak.equity - sample.equity) / current_peak.equity;
+            if drawdown > max_drawdown {
+                max_drawdown = drawdown;
+                period = Some((current_peak.timestamp, sample.timestamp));
+            }
+        }
+    }
+    
+    period
+}
+
+/// Decline of the latest sample from the running peak, as a fraction
+pub fn current_drawdown(samples: &[EquitySample]) -> Decimal {
+    let peak = samples.iter().map(|s| s.equity).max().unwrap_or(Decimal::ZERO);
+    match samples.last() {
+        Some(last) if peak > Decimal::ZERO => (peak - last.equity) / peak,
+        _ => Decimal::ZERO,
+    }
+}
+
+/// Current and largest decline of the account value across the exchange's
+/// `history`, each as a fraction of the peak before it
+pub fn history_drawdowns(history: &[EquityPoint]) -> (Decimal, Decimal) {
+    let mut peak = Decimal::ZERO;
+    let mut current = Decimal::ZERO;
+    let mut max = Decimal::ZERO;
+    
+    for point in history {
+        peak = peak.max(point.account_value);
+        if peak > Decimal::ZERO {
+            current = (peak - point.account_value) / peak;
+            max = max.max(current);
+        }
+    }
+    
+    (current, max)
+}
+
+/// Annualized Sharpe ratio of daily returns (last sample of each UTC day)
+/// over the whole series; see [`PerformanceWindow`] for other windows.
+pub fn sharpe_ratio(samples: &[EquitySample]) -> f64 {
+    PerformanceWindow::from_equity_curve(samples)
+        .stats(Window::All, Utc::now())
+        .sharpe_ratio
+}
@@ -1,4 +1,8 @@
+use std::fmt;
+use std::time::Duration;
 use thiserror::Error;
+use tokio_tungstenite::tungstenite::{self, error::ProtocolError};
+use uuid::Uuid;
 
 #[derive(Error, Debug)]
 pub enum Error {
@@ -8,12 +12,20 @@ pub enum Error {
     #[error("API error: {0}")]
     Api(String),
     
+    /// A non-success HTTP status other than 429
+    #[error("API error: HTTP {status}: {body}")]
+    Http { status: u16, body: String },
+    
     #[error("Trading error: {0}")]
     Trading(String),
     
     #[error("Strategy error: {0}")]
     Strategy(String),
     
+    /// A request that got no reply in time
+    #[error("Timed out: {0}")]
+    Timeout(String),
+    
     #[error("Network error: {0}")]
     Network(#[from] reqwest::Error),
     
@@ -23,14 +35,19 @@ pub enum Error {
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
     
+    /// Boxed, as tungstenite's error is several times the size of the rest
     #[error("WebSocket error: {0}")]
-    WebSocket(#[from] tokio_tungstenite::tungstenite::Error),
+    WebSocket(#[source] Box<tungstenite::Error>),
+    
+    #[error("Storage error: {0}")]
+    Storage(#[from] rusqlite::Error),
     
     #[error("Invalid input: {0}")]
     InvalidInput(String),
     
+    /// `retry_after` is how long the server asked us to wait, if it said
     #[error("Rate limit exceeded")]
-    RateLimit,
+    RateLimit { retry_after: Option<Duration> },
     
     #[error("Insufficient balance")]
     InsufficientBalance,
@@ -41,8 +58,246 @@ pub enum Error {
     #[error("Market closed")]
     MarketClosed,
     
+    /// The exchange wouldn't take the signed nonce, e.g. one already used
+    #[error("Invalid nonce: {0}")]
+    InvalidNonce(String),
+    
+    /// A post-only order priced through the book, which would have taken
+    /// liquidity instead of adding it
+    #[error("Post-only order would have immediately matched")]
+    PostOnlyWouldCross,
+    
+    /// An order worth less than the exchange's minimum, with its message
+    #[error("Order below the minimum size: {0}")]
+    MinOrderSize(String),
+    
     #[error("Unknown error: {0}")]
     Unknown(String),
+    
+    /// `source` with where it happened. Each layer that adds context wraps
+    /// the error again, so the message reads from the failing call outwards.
+    #[error("{source} [{context}]")]
+    WithContext { source: Box<Error>, context: ErrorContext },
+}
+
+impl From<tungstenite::Error> for Error {
+    fn from(e: tungstenite::Error) -> Self {
+        Error::WebSocket(Box::new(e))
+    }
+}
+
+/// Where an error happened: the exchange endpoint, the symbol and strategy
+/// involved and the trading cycle it belongs to. Logged with the error and
+/// attached to reports as tags.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ErrorContext {
+    pub strategy: Option<String>,
+    pub symbol: Option<String>,
+    pub endpoint: Option<String>,
+    /// Id of the trading cycle, also on the cycle's log lines as `cycle_id`
+    pub correlation_id: Option<Uuid>,
+}
+
+impl ErrorContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn with_strategy(mut self, strategy: &str) -> Self {
+        self.strategy = Some(strategy.to_string());
+        self
+    }
+    
+    pub fn with_symbol(mut self, symbol: &str) -> Self {
+        self.symbol = Some(symbol.to_string());
+        self
+    }
+    
+    pub fn with_endpoint(mut self, endpoint: &str) -> Self {
+        self.endpoint = Some(endpoint.to_string());
+        self
+    }
+    
+    pub fn with_correlation_id(mut self, correlation_id: Uuid) -> Self {
+        self.correlation_id = Some(correlation_id);
+        self
+    }
+    
+    /// Fills the fields this context doesn't set from `other`
+    pub fn or(self, other: ErrorContext) -> Self {
+        Self {
+            strategy: self.strategy.or(other.strategy),
+            symbol: self.symbol.or(other.symbol),
+            endpoint: self.endpoint.or(other.endpoint),
+            correlation_id: self.correlation_id.or(other.correlation_id),
+        }
+    }
+    
+    pub fn tags(&self) -> Vec<(&'static str, String)> {
+        [
+            ("endpoint", self.endpoint.clone()),
+            ("symbol", self.symbol.clone()),
+            ("strategy", self.strategy.clone()),
+            ("cycle_id", self.correlation_id.map(|id| id.to_string())),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| value.map(|v| (key, v)))
+        .collect()
+    }
+}
+
+impl fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (key, value)) in self.tags().into_iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}={}", key, value)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error {
+    /// This error with `context` around it
+    pub fn with_context(self, context: ErrorContext) -> Self {
+        Error::WithContext {
+            source: Box::new(self),
+            context,
+        }
+    }
+    
+    /// The error underneath any context
+    pub fn root(&self) -> &Error {
+        match self {
+            Error::WithContext { source, .. } => source.root(),
+            error => error,
+        }
+    }
+    
+    /// All the context wrapped around this error, the innermost winning
+    /// where layers disagree
+    pub fn context(&self) -> ErrorContext {
+        match self {
+            Error::WithContext { source, context } => source.context().or(context.clone()),
+            _ => ErrorContext::default(),
+        }
+    }
+    
+    /// The error for an unsuccessful `response`: a rate limit, with the
+    /// wait from its Retry-After header, or its status and body
+    pub async fn from_response(response: reqwest::Response) -> Self {
+        let status = response.status().as_u16();
+        if status == 429 {
+            let retry_after = response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.trim().parse::<u64>().ok())
+                .map(Duration::from_secs);
+            return Error::RateLimit { retry_after };
+        }
+        
+        Error::Http {
+            status,
+            body: response.text().await.unwrap_or_default(),
+        }
+    }
+    
+    /// Whether the same request could succeed if tried again: timeouts,
+    /// failed connections, server errors, rate limits and dropped
+    /// websockets. Anything the exchange or our own checks rejected will
+    /// fail the same way next time.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Error::Network(e) => {
+                e.is_timeout() || e.is_connect() || e.status().is_some_and(|status| is_retryable_status(status.as_u16()))
+            }
+            Error::Http { status, .. } => is_retryable_status(*status),
+            Error::RateLimit { .. } | Error::Timeout(_) => true,
+            Error::WebSocket(e) => matches!(
+                **e,
+                tungstenite::Error::ConnectionClosed
+                    | tungstenite::Error::AlreadyClosed
+                    | tungstenite::Error::Io(_)
+                    | tungstenite::Error::Protocol(ProtocolError::ResetWithoutClosingHandshake)
+            ),
+            Error::Config(_)
+            | Error::Api(_)
+            | Error::Trading(_)
+            | Error::Strategy(_)
+            | Error::Serialization(_)
+            | Error::Io(_)
+            | Error::Storage(_)
+            | Error::InvalidInput(_)
+            | Error::InsufficientBalance
+            | Error::OrderNotFound
+            | Error::MarketClosed
+            | Error::InvalidNonce(_)
+            | Error::PostOnlyWouldCross
+            | Error::MinOrderSize(_)
+            | Error::Unknown(_) => false,
+            Error::WithContext { source, .. } => source.is_retryable(),
+        }
+    }
+    
+    /// The error for a failure `message` from the exchange, e.g. why it
+    /// rejected an order, if it's one callers act on; `None` otherwise
+    pub fn from_exchange_message(message: &str) -> Option<Self> {
+        let lowercase = message.to_ascii_lowercase();
+        EXCHANGE_FAILURES
+            .iter()
+            .find(|(pattern, _)| lowercase.contains(pattern))
+            .map(|(_, error)| error(message))
+    }
+    
+    /// How long the server asked us to wait before trying again
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Error::RateLimit { retry_after } => *retry_after,
+            Error::WithContext { source, .. } => source.retry_after(),
+            _ => None,
+        }
+    }
+}
+
+/// Builds the error a matched exchange failure message means
+type FailureMapper = fn(&str) -> Error;
+
+/// Lowercase fragments of the exchange's failure messages and the errors
+/// they mean, the first match winning
+const EXCHANGE_FAILURES: &[(&str, FailureMapper)] = &[
+    ("post only order would have immediately matched", |_| Error::PostOnlyWouldCross),
+    ("minimum value of", |message| Error::MinOrderSize(message.to_string())),
+    ("nonce", |message| Error::InvalidNonce(message.to_string())),
+    ("insufficient margin", |_| Error::InsufficientBalance),
+    ("insufficient balance", |_| Error::InsufficientBalance),
+    ("insufficient spot balance", |_| Error::InsufficientBalance),
+    ("too many cumulative requests", |_| Error::RateLimit { retry_after: None }),
+    ("rate limited", |_| Error::RateLimit { retry_after: None }),
+    ("never placed, already canceled, or filled", |_| Error::OrderNotFound),
+];
+
+fn is_retryable_status(status: u16) -> bool {
+    status == 429 || (500..600).contains(&status)
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
+
+/// Adds context to the error of a failed [`Result`]
+pub trait ResultExt<T> {
+    fn with_context(self, context: impl FnOnce() -> ErrorContext) -> Result<T>;
+    
+    /// Names the exchange endpoint that failed, e.g. `info:meta`
+    fn context_api(self, endpoint: &str) -> Result<T>;
+}
+
+impl<T> ResultExt<T> for Result<T> {
+    fn with_context(self, context: impl FnOnce() -> ErrorContext) -> Result<T> {
+        self.map_err(|error| error.with_context(context()))
+    }
+    
+    fn context_api(self, endpoint: &str) -> Result<T> {
+        self.with_context(|| ErrorContext::new().with_endpoint(endpoint))
+    }
+}
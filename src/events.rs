@@ -0,0 +1,250 @@
+use crate::{
+    data_source::DataSource,
+    journal::JournalEntry,
+    models::{MarketData, Order, StrategySignal},
+    storage::EquitySample,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast::{self, error::RecvError, error::TryRecvError};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+/// Something the trading loop saw or did
+#[derive(Debug, Clone)]
+pub enum BotEvent {
+    /// Market data a strategy was about to analyze
+    MarketData(MarketData),
+    /// A bar fetched to fill a gap in the stream
+    CandleClosed { interval: String, bar: MarketData },
+    SignalGenerated(StrategySignal),
+    /// A signal that passed the risk checks but wasn't acted on
+    SignalSuppressed { signal: StrategySignal, reason: String },
+    OrderPlaced { strategy: String, order: Order },
+    OrderFilled(JournalEntry),
+    OrderCancelled { order_id: String, symbol: String, reason: String },
+    /// A signal the risk checks turned down, or, without one, the account
+    /// limits tripping and pausing trading
+    RiskRejected { signal: Option<StrategySignal>, reason: String },
+    EquitySample(EquitySample),
+    /// Market data switched source, e.g. to REST while the websocket is down
+    ConnectionStateChanged { source: DataSource, reason: String },
+}
+
+impl BotEvent {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            BotEvent::MarketData(_) => "market_data",
+            BotEvent::CandleClosed { .. } => "candle_closed",
+            BotEvent::SignalGenerated(_) => "signal_generated",
+            BotEvent::SignalSuppressed { .. } => "signal_suppressed",
+            BotEvent::OrderPlaced { .. } => "order_placed",
+            BotEvent::OrderFilled(_) => "order_filled",
+            BotEvent::OrderCancelled { .. } => "order_cancelled",
+            BotEvent::RiskRejected { .. } => "risk_rejected",
+            BotEvent::EquitySample(_) => "equity_sample",
+            BotEvent::ConnectionStateChanged { .. } => "connection_state_changed",
+        }
+    }
+}
+
+/// An event as subscribers receive it
+#[derive(Debug, Clone)]
+pub struct BusEvent {
+    /// Position in publish order, counting from 1 without gaps
+    pub seq: u64,
+    pub timestamp: DateTime<Utc>,
+    pub event: BotEvent,
+}
+
+/// An observer of the trading loop, run on its own task by
+/// [`EventBus::spawn`]. `handle` is called once per event in publish order
+/// and should be quick; a slow subscriber only delays itself.
+pub trait EventSubscriber: Send + 'static {
+    fn name(&self) -> &str;
+    
+    fn handle(&mut self, event: &BusEvent);
+}
+
+struct Publisher {
+    seq: u64,
+    sender: broadcast::Sender<Arc<BusEvent>>,
+}
+
+/// Carries [`BotEvent`]s from the trading loop to its observers.
+///
+/// Ordering: events are numbered and sent under one lock, so every
+/// subscriber sees them in the same order, that of `seq`, and events from
+/// one task arrive in the order it published them.
+///
+/// Lag: each subscriber may fall up to `capacity` events behind. Publishing
+/// never waits for subscribers; one that falls further behind skips the
+/// oldest events it hasn't handled, carries on from the oldest one still
+/// held, and has the skipped events counted in [`Subscription::missed`].
+pub struct EventBus {
+    publisher: Mutex<Publisher>,
+    /// How far each spawned subscriber has got, by `seq`
+    progress: Mutex<Vec<watch::Receiver<u64>>>,
+}
+
+impl EventBus {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            publisher: Mutex::new(Publisher {
+                seq: 0,
+                sender: broadcast::channel(capacity).0,
+            }),
+            progress: Mutex::new(Vec::new()),
+        }
+    }
+    
+    /// Sends `event` to every current subscriber, returning its `seq`
+    pub fn publish(&self, event: BotEvent, timestamp: DateTime<Utc>) -> u64 {
+        let mut publisher = self.publisher.lock().unwrap();
+        publisher.seq += 1;
+        let seq = publisher.seq;
+        // With no subscribers the event simply goes unobserved
+        let _ = publisher.sender.send(Arc::new(BusEvent { seq, timestamp, event }));
+        seq
+    }
+    
+    /// `seq` of the latest event published
+    pub fn published(&self) -> u64 {
+        self.publisher.lock().unwrap().seq
+    }
+    
+    /// Receives every event published from now on
+    pub fn subscribe(&self) -> Subscription {
+        self.subscribe_at().0
+    }
+    
+    fn subscribe_at(&self) -> (Subscription, u64) {
+        let publisher = self.publisher.lock().unwrap();
+        let subscription = Subscription {
+            receiver: publisher.sender.subscribe(),
+            missed: 0,
+        };
+        (subscription, publisher.seq)
+    }
+    
+    /// Runs `subscriber` on its own task until the bus is dropped or the
+    /// task is aborted
+    pub fn spawn(&self, mut subscriber: impl EventSubscriber) -> JoinHandle<()> {
+        let (mut subscription, seq) = self.subscribe_at();
+        let (progress, watcher) = watch::channel(seq);
+        {
+            let mut watchers = self.progress.lock().unwrap();
+            watchers.retain(|watcher| watcher.has_changed().is_ok());
+            watchers.push(watcher);
+        }
+        
+        tokio::spawn(async move {
+            let mut missed = 0;
+            while let Some(event) = subscription.recv().await {
+                if subscription.missed() > missed {
+                    warn!(
+                        "📨 {} fell behind the event bus and missed {} events",
+                        subscriber.name(),
+                        subscription.missed() - missed
+                    );
+                    missed = subscription.missed();
+                }
+                subscriber.handle(&event);
+                progress.send_replace(event.seq);
+            }
+        })
+    }
+    
+    /// Waits until every spawned subscriber has handled, or missed,
+    /// everything published so far
+    pub async fn settled(&self) {
+        let published = self.published();
+        let watchers = self.progress.lock().unwrap().clone();
+        for mut watcher in watchers {
+            // A subscriber that has stopped has nothing left to handle
+            let _ = watcher.wait_for(|seq| *seq >= published).await;
+        }
+    }
+}
+
+/// One subscriber's view of the bus
+pub struct Subscription {
+    receiver: broadcast::Receiver<Arc<BusEvent>>,
+    missed: u64,
+}
+
+impl Subscription {
+    /// The next event, skipping ahead if this subscription has fallen too
+    /// far behind. `None` once the bus is gone.
+    pub async fn recv(&mut self) -> Option<Arc<BusEvent>> {
+        loop {
+            match self.receiver.recv().await {
+                Ok(event) => return Some(event),
+                Err(RecvError::Lagged(skipped)) => self.missed += skipped,
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+    
+    /// The next event if one is waiting
+    pub fn try_recv(&mut self) -> Option<Arc<BusEvent>> {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(event) => return Some(event),
+                Err(TryRecvError::Lagged(skipped)) => self.missed += skipped,
+                Err(TryRecvError::Empty | TryRecvError::Closed) => return None,
+            }
+        }
+    }
+    
+    /// Events skipped for falling behind
+    pub fn missed(&self) -> u64 {
+        self.missed
+    }
+}
+
+/// What the event bus has carried, for the status command
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EventStats {
+    /// Events handled, by kind
+    pub counts: BTreeMap<String, u64>,
+    pub last_seq: u64,
+    pub last_event_at: Option<DateTime<Utc>>,
+    /// Events skipped for falling behind
+    pub missed: u64,
+}
+
+/// Keeps [`EventStats`] up to date; clones share the same stats
+#[derive(Clone, Default)]
+pub struct StatusReporter {
+    stats: Arc<Mutex<EventStats>>,
+}
+
+impl StatusReporter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn stats(&self) -> EventStats {
+        self.stats.lock().unwrap().clone()
+    }
+}
+
+impl EventSubscriber for StatusReporter {
+    fn name(&self) -> &str {
+        "status reporter"
+    }
+    
+    fn handle(&mut self, event: &BusEvent) {
+        let mut stats = self.stats.lock().unwrap();
+        if stats.last_seq > 0 {
+            stats.missed += event.seq.saturating_sub(stats.last_seq + 1);
+        }
+        *stats.counts.entry(event.event.kind().to_string()).or_default() += 1;
+        stats.last_seq = event.seq;
+        stats.last_event_at = Some(event.timestamp);
+    }
+}
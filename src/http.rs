@@ -0,0 +1,104 @@
+use crate::{
+    config::HttpConfig,
+    error::{Error, Result},
+    trading_bot::TradingBot,
+};
+use axum::{
+    extract::State,
+    http::{header, Request, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use serde_json::json;
+use std::net::TcpListener;
+use std::sync::Arc;
+use tracing::info;
+
+#[derive(Clone)]
+struct ServerState {
+    bot: Arc<TradingBot>,
+    token: Option<Arc<str>>,
+}
+
+/// Serves the read-only status endpoints on `config.bind` until the process
+/// exits:
+///
+/// - `GET /healthz`: the bot's [`Health`](crate::control::Health), 200 when
+///   healthy and 503 otherwise
+/// - `GET /status`: the status snapshot `status` shows, with every strategy
+/// - `GET /positions` and `GET /orders`: the account as the last cycle saw it
+///
+/// Everything comes from what the trading loop has already cached, so a
+/// request never waits on the loop or the exchange.
+pub async fn serve(bot: Arc<TradingBot>, config: HttpConfig) -> Result<()> {
+    let listener = TcpListener::bind(&config.bind)?;
+    info!("🌐 HTTP status server listening on {}", config.bind);
+    serve_on(listener, bot, config.token).await
+}
+
+/// Serves on a listener that's already bound, e.g. to an ephemeral port
+pub async fn serve_on(listener: TcpListener, bot: Arc<TradingBot>, token: Option<String>) -> Result<()> {
+    listener.set_nonblocking(true)?;
+    axum::Server::from_tcp(listener)
+        .map_err(io_error)?
+        .serve(router(bot, token).into_make_service())
+        .await
+        .map_err(io_error)
+}
+
+fn router(bot: Arc<TradingBot>, token: Option<String>) -> Router {
+    let state = ServerState {
+        bot,
+        token: token.map(Arc::from),
+    };
+    Router::new()
+        .route("/healthz", get(healthz))
+        .route("/status", get(status))
+        .route("/positions", get(positions))
+        .route("/orders", get(orders))
+        .route_layer(middleware::from_fn_with_state(state.clone(), authorize))
+        .with_state(state)
+}
+
+async fn authorize<B>(State(state): State<ServerState>, request: Request<B>, next: Next<B>) -> Response {
+    if let Some(token) = &state.token {
+        let presented = request
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        if presented != Some(&**token) {
+            let body = Json(json!({ "error": "missing or wrong bearer token" }));
+            return (StatusCode::UNAUTHORIZED, [(header::WWW_AUTHENTICATE, "Bearer")], body).into_response();
+        }
+    }
+    next.run(request).await
+}
+
+async fn healthz(State(state): State<ServerState>) -> Response {
+    let health = state.bot.health();
+    let status = if health.healthy { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status, Json(health)).into_response()
+}
+
+async fn status(State(state): State<ServerState>) -> Response {
+    Json(state.bot.cached_snapshot().await).into_response()
+}
+
+async fn positions(State(state): State<ServerState>) -> Response {
+    let (updated_at, account) = state.bot.last_account().await.unzip();
+    let positions = account.map(|account| account.positions).unwrap_or_default();
+    Json(json!({ "updated_at": updated_at, "positions": positions })).into_response()
+}
+
+async fn orders(State(state): State<ServerState>) -> Response {
+    let (updated_at, account) = state.bot.last_account().await.unzip();
+    let orders = account.map(|account| account.open_orders).unwrap_or_default();
+    Json(json!({ "updated_at": updated_at, "orders": orders })).into_response()
+}
+
+fn io_error(e: impl std::error::Error + Send + Sync + 'static) -> Error {
+    Error::Io(std::io::Error::other(e))
+}
@@ -0,0 +1,163 @@
+use crate::{
+    api::client::TradingClient,
+    error::Result,
+    models::{new_cloid, Order, OrderSide, OrderStatus, OrderType, PositionSide},
+};
+use chrono::Utc;
+use rust_decimal::Decimal;
+use std::io::Write;
+use uuid::Uuid;
+
+/// Shown before any destructive command. Hyperliquid nonces are per-signer
+/// millisecond timestamps, so a bot running on the same key can keep
+/// trading alongside these commands without nonce clashes, but it may also
+/// reopen what they close.
+pub const RUNNING_BOT_NOTE: &str = "ℹ️ Safe to run while the bot is up (actions use their own timestamp nonces), \
+but a running bot may place new orders or reopen positions; stop it first if that matters.";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Confirmation {
+    /// `--yes` was given
+    Proceed,
+    /// Ask on the terminal first
+    Prompt,
+    /// Mainnet requires an explicit `--yes`
+    RefuseMainnet,
+}
+
+pub fn confirmation(testnet: bool, yes: bool) -> Confirmation {
+    match (testnet, yes) {
+        (_, true) => Confirmation::Proceed,
+        (true, false) => Confirmation::Prompt,
+        (false, false) => Confirmation::RefuseMainnet,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ActionOutcome {
+    pub action: String,
+    pub error: Option<String>,
+}
+
+/// What an emergency command did, one line per attempted action
+#[derive(Debug, Clone, Default)]
+pub struct InterventionReport {
+    pub outcomes: Vec<ActionOutcome>,
+}
+
+impl InterventionReport {
+    fn record(&mut self, action: String, result: std::result::Result<(), String>) {
+        self.outcomes.push(ActionOutcome {
+            action,
+            error: result.err(),
+        });
+    }
+    
+    pub fn failures(&self) -> usize {
+        self.outcomes.iter().filter(|o| o.error.is_some()).count()
+    }
+    
+    pub fn write<W: Write>(&self, mut writer: W) -> Result<()> {
+        if self.outcomes.is_empty() {
+            writeln!(writer, "Nothing to do")?;
+        }
+        for outcome in &self.outcomes {
+            match &outcome.error {
+                None => writeln!(writer, "✅ {}", outcome.action)?,
+                Some(error) => writeln!(writer, "❌ {}: {}", outcome.action, error)?,
+            }
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+fn matches_symbol(symbol: &str, filter: Option<&str>) -> bool {
+    filter.is_none_or(|f| f.eq_ignore_ascii_case(symbol))
+}
+
+/// Cancels every open order, or only those for `symbol`. Keeps going after
+/// individual failures.
+pub async fn cancel_all<C>(client: &C, symbol: Option<&str>) -> Result<InterventionReport>
+where
+    C: TradingClient + Sync + ?Sized,
+{
+    let mut report = InterventionReport::default();
+    cancel_orders(client, symbol, &mut report).await?;
+    Ok(report)
+}
+
+async fn cancel_orders<C>(client: &C, symbol: Option<&str>, report: &mut InterventionReport) -> Result<()>
+where
+    C: TradingClient + Sync + ?Sized,
+{
+    let orders = client.get_open_orders().await?;
+    
+    for order in orders.iter().filter(|o| matches_symbol(&o.symbol, symbol)) {
+        let action = format!(
+            "Cancel {:?} {} {} @ {} ({})",
+            order.side,
+            order.quantity,
+            order.symbol,
+            order.price.map(|p| p.to_string()).unwrap_or_else(|| "market".to_string()),
+            order.id
+        );
+        let result = match client.cancel_order(&order.id).await {
+            Ok(true) => Ok(()),
+            Ok(false) => Err("rejected by exchange".to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        report.record(action, result);
+    }
+    
+    Ok(())
+}
+
+/// Cancels open orders and then closes every position (or only `symbol`'s)
+/// with a reduce-only market order, so nothing left resting can reopen it.
+pub async fn flatten<C>(client: &C, symbol: Option<&str>) -> Result<InterventionReport>
+where
+    C: TradingClient + Sync + ?Sized,
+{
+    let mut report = InterventionReport::default();
+    cancel_orders(client, symbol, &mut report).await?;
+    
+    let positions = client.get_positions().await?;
+    for position in positions.iter().filter(|p| matches_symbol(&p.symbol, symbol)) {
+        if position.size.is_zero() {
+            continue;
+        }
+        
+        let side = match position.side {
+            PositionSide::Long => OrderSide::Sell,
+            PositionSide::Short => OrderSide::Buy,
+        };
+        let order = close_order(&position.symbol, side, position.size.abs());
+        let action = format!("Close {:?} {} {} with reduce-only {:?}", position.side, position.size, position.symbol, order.side);
+        
+        let result = client.place_order(&order).await.map(|_| ()).map_err(|e| e.to_string());
+        report.record(action, result);
+    }
+    
+    Ok(report)
+}
+
+fn close_order(symbol: &str, side: OrderSide, quantity: Decimal) -> Order {
+    Order {
+        id: Uuid::new_v4().to_string(),
+        symbol: symbol.to_string(),
+        side,
+        order_type: OrderType::Market,
+        quantity,
+        price: None,
+        status: OrderStatus::Pending,
+        created_at: Utc::now(),
+        updated_at: None,
+        filled_quantity: Decimal::ZERO,
+        average_price: None,
+        reduce_only: true,
+        trigger_price: None,
+        is_take_profit: false,
+        cloid: Some(new_cloid()),
+    }
+}
@@ -0,0 +1,180 @@
+use crate::{
+    config::JournalConfig,
+    error::Result,
+    events::{BotEvent, BusEvent, EventSubscriber},
+    models::OrderSide,
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::{error, info, warn};
+
+const HEADER: &str = "timestamp,strategy,symbol,side,quantity,price,fee,realized_pnl,order_id,cloid,dry_run,reconciled";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub timestamp: DateTime<Utc>,
+    pub strategy: String,
+    pub symbol: String,
+    pub side: OrderSide,
+    pub quantity: Decimal,
+    pub price: Decimal,
+    pub fee: Decimal,
+    pub realized_pnl: Decimal,
+    pub order_id: String,
+    pub cloid: Option<String>,
+    pub dry_run: bool,
+    /// Added by fill reconciliation: the exchange reported the fill but the
+    /// bot never recorded it
+    #[serde(default)]
+    pub reconciled: bool,
+}
+
+impl JournalEntry {
+    pub fn to_csv_row(&self) -> String {
+        let side = match self.side {
+            OrderSide::Buy => "BUY",
+            OrderSide::Sell => "SELL",
+        };
+        
+        [
+            self.timestamp.to_rfc3339(),
+            csv_escape(&self.strategy),
+            csv_escape(&self.symbol),
+            side.to_string(),
+            self.quantity.to_string(),
+            self.price.to_string(),
+            self.fee.to_string(),
+            self.realized_pnl.to_string(),
+            csv_escape(&self.order_id),
+            csv_escape(self.cloid.as_deref().unwrap_or("")),
+            self.dry_run.to_string(),
+            self.reconciled.to_string(),
+        ]
+        .join(",")
+    }
+}
+
+struct OpenJournal {
+    date: NaiveDate,
+    file: File,
+}
+
+/// Append-only CSV record of every execution, one file per UTC day
+/// (`trades-2024-06-01.csv`). Each row is written with a single append so a
+/// crash can at worst leave a partial trailing line, which is trimmed the
+/// next time the file is opened.
+pub struct TradeJournal {
+    directory: PathBuf,
+    current: Mutex<Option<OpenJournal>>,
+}
+
+impl TradeJournal {
+    pub fn new(directory: impl Into<PathBuf>) -> Result<Self> {
+        let directory = directory.into();
+        fs::create_dir_all(&directory)?;
+        info!("Trade journal writing to {}", directory.display());
+        
+        Ok(Self {
+            directory,
+            current: Mutex::new(None),
+        })
+    }
+    
+    pub fn from_config(config: &JournalConfig) -> Result<Option<Self>> {
+        if !config.enabled {
+            return Ok(None);
+        }
+        Self::new(&config.directory).map(Some)
+    }
+    
+    pub fn path_for(&self, date: NaiveDate) -> PathBuf {
+        self.directory.join(format!("trades-{}.csv", date.format("%Y-%m-%d")))
+    }
+    
+    pub fn record(&self, entry: &JournalEntry) -> Result<()> {
+        let date = entry.timestamp.date_naive();
+        let mut current = self.current.lock().unwrap();
+        
+        // Roll over to a new file when the entry belongs to a different day
+        if current.as_ref().map(|open| open.date) != Some(date) {
+            *current = Some(OpenJournal {
+                date,
+                file: open_journal_file(&self.path_for(date))?,
+            });
+        }
+        
+        let open = current.as_mut().expect("journal file opened above");
+        let line = format!("{}\n", entry.to_csv_row());
+        open.file.write_all(line.as_bytes())?;
+        open.file.sync_data()?;
+        
+        Ok(())
+    }
+}
+
+impl EventSubscriber for TradeJournal {
+    fn name(&self) -> &str {
+        "trade journal"
+    }
+    
+    fn handle(&mut self, event: &BusEvent) {
+        if let BotEvent::OrderFilled(entry) = &event.event {
+            if let Err(e) = self.record(entry) {
+                error!("Failed to write trade journal entry: {}", e);
+            }
+        }
+    }
+}
+
+fn open_journal_file(path: &Path) -> Result<File> {
+    let mut file = OpenOptions::new()
+        .read(true)
+        .append(true)
+        .create(true)
+        .open(path)?;
+    
+    let len = file.metadata()?.len();
+    if len == 0 {
+        file.write_all(format!("{}\n", HEADER).as_bytes())?;
+        file.sync_data()?;
+        return Ok(file);
+    }
+    
+    trim_partial_line(&mut file, path)?;
+    Ok(file)
+}
+
+// Drops anything after the last newline left behind by an interrupted write
+fn trim_partial_line(file: &mut File, path: &Path) -> Result<()> {
+    let mut contents = Vec::new();
+    file.seek(SeekFrom::Start(0))?;
+    file.read_to_end(&mut contents)?;
+    
+    if contents.last() == Some(&b'\n') {
+        return Ok(());
+    }
+    
+    let keep = contents.iter().rposition(|&b| b == b'\n').map(|i| i + 1).unwrap_or(0);
+    warn!("Removing partial trailing row from {}", path.display());
+    file.set_len(keep as u64)?;
+    
+    if keep == 0 {
+        file.write_all(format!("{}\n", HEADER).as_bytes())?;
+    }
+    file.sync_data()?;
+    
+    Ok(())
+}
+
+pub fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
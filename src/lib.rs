@@ -1,9 +1,46 @@
+pub mod account;
+pub mod analysis;
 pub mod api;
+pub mod backtest;
+pub mod candle_feed;
+pub mod chaos;
+pub mod clock;
 pub mod config;
+pub mod control;
+pub mod costs;
+pub mod data_source;
+pub mod equity;
 pub mod error;
+pub mod events;
+pub mod http;
+pub mod intervention;
+pub mod journal;
+pub mod market_feed;
+pub mod market_stream;
+pub mod metrics;
 pub mod models;
+pub mod notifications;
+pub mod paper;
+pub mod preflight;
+pub mod price_cache;
+pub mod reconcile;
+pub mod recorder;
+pub mod replay;
+pub mod reporting;
+pub mod runner;
+pub mod scheduler;
+pub mod shutdown;
+pub mod simulated_exchange;
+pub mod snapshot;
+pub mod storage;
 pub mod strategies;
+pub mod supervisor;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod trade_stats;
 pub mod trading_bot;
 pub mod utils;
+pub mod validation;
+pub mod watchdog;
 
 pub use error::{Error, Result};
@@ -1,12 +1,43 @@
-use anyhow::Result;
-use clap::Parser;
+use anyhow::{bail, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::{Parser, Subcommand};
 use hyperliquid_trading_bot::{
+    account,
+    api::{types::interval_duration, HyperliquidClient},
+    backtest::{
+        data,
+        download::{self, Downloader},
+        optimize::{self, Objective, Optimizer, ParameterSpace},
+        report::BacktestReport,
+        walk_forward::WalkForward,
+        BacktestResult, Backtester,
+    },
     config::Config,
+    control,
+    costs::CostModel,
+    equity::{self, ExportFormat},
+    http,
+    intervention::{self, Confirmation, InterventionReport},
+    market_stream::{ReplayMarketStream, ReplaySpeed},
+    journal::TradeJournal,
+    models::{BotStatus, MarketData},
+    reconcile::{self, ReconciliationReport},
+    replay::Replay,
+    reporting,
+    snapshot::Snapshot,
+    storage,
+    supervisor::BotSupervisor,
     trading_bot::TradingBot,
-    utils::setup_logging,
+    runner::RunMode,
+    shutdown::ShutdownSignal,
+    utils::{format_currency, parse_duration, setup_logging_with_format},
+    validation::{self, ValidationReport},
 };
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufWriter, IsTerminal, Write};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tokio::signal;
+use std::time::Duration;
 use tracing::{info, error};
 
 #[derive(Parser)]
@@ -25,57 +56,1037 @@ struct Cli {
     /// Dry run mode (no actual trades)
     #[arg(long)]
     dry_run: bool,
+    
+    /// Start the dry-run paper account over from paper.initial_balance
+    #[arg(long)]
+    reset_paper: bool,
+    
+    /// Start even if pre-flight checks fail, logging the failures as warnings
+    #[arg(long)]
+    ignore_preflight: bool,
+    
+    /// Stream market data from this websocket recording (recorder.frames_path) instead of the exchange
+    #[arg(long)]
+    replay_ws: Option<PathBuf>,
+    
+    /// Run only these strategies (comma-separated names from the config)
+    #[arg(long, value_delimiter = ',')]
+    strategies: Vec<String>,
+    
+    /// Run only strategies trading these symbols (comma-separated)
+    #[arg(long, value_delimiter = ',')]
+    symbols: Vec<String>,
+    
+    /// Run a single trading cycle, print the status report and exit
+    #[arg(long, conflicts_with = "run_for")]
+    once: bool,
+    
+    /// Stop gracefully after this long, e.g. 90m or 2h
+    #[arg(long, value_parser = parse_duration)]
+    run_for: Option<Duration>,
+    
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run the trading bot (default)
+    Run,
+    
+    /// Export the recorded equity curve
+    ExportEquity {
+        /// First day to include (UTC, YYYY-MM-DD)
+        #[arg(long)]
+        from: Option<NaiveDate>,
+        
+        /// Last day to include (UTC, YYYY-MM-DD)
+        #[arg(long)]
+        to: Option<NaiveDate>,
+        
+        /// Output format: csv or json
+        #[arg(long, default_value = "csv")]
+        format: ExportFormat,
+        
+        /// Output file (defaults to stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    
+    /// Run the configured strategies over historical data
+    Backtest {
+        /// First day to simulate (UTC, YYYY-MM-DD)
+        #[arg(long)]
+        from: NaiveDate,
+        
+        /// Last day to simulate, inclusive (UTC, YYYY-MM-DD)
+        #[arg(long)]
+        to: NaiveDate,
+        
+        /// Directory with candle CSVs or market recordings (fetches candles from the API if omitted)
+        #[arg(long)]
+        data: Option<PathBuf>,
+        
+        /// Candle interval: 1m, 5m, 15m, 1h, 4h or 1d (defaults to backtest.interval)
+        #[arg(long)]
+        interval: Option<String>,
+        
+        /// Directory to write trades.csv and equity.csv to
+        #[arg(long)]
+        out: Option<PathBuf>,
+        
+        /// Write the report as a self-contained HTML page with an equity chart
+        #[arg(long)]
+        report_html: Option<PathBuf>,
+        
+        /// Write the report as JSON
+        #[arg(long)]
+        report_json: Option<PathBuf>,
+    },
+    
+    /// Grid-search a strategy's parameters over historical data
+    Optimize {
+        /// Strategy to tune, by its name in the config
+        #[arg(long)]
+        strategy: String,
+        
+        /// TOML file of parameters to try: `name = [values]` or `name = { min = .., max = .., step = .. }`
+        #[arg(long)]
+        space: PathBuf,
+        
+        /// First day to simulate (UTC, YYYY-MM-DD)
+        #[arg(long)]
+        from: NaiveDate,
+        
+        /// Last day to simulate, inclusive (UTC, YYYY-MM-DD)
+        #[arg(long)]
+        to: NaiveDate,
+        
+        /// Directory with candle CSVs or market recordings (fetches candles from the API if omitted)
+        #[arg(long)]
+        data: Option<PathBuf>,
+        
+        /// Candle interval: 1m, 5m, 15m, 1h, 4h or 1d (defaults to backtest.interval)
+        #[arg(long)]
+        interval: Option<String>,
+        
+        /// What to rank by: net-pnl, sharpe or pnl-dd (return over max drawdown)
+        #[arg(long, default_value = "net-pnl")]
+        objective: Objective,
+        
+        /// Backtests to run at once (defaults to the number of CPUs)
+        #[arg(long)]
+        workers: Option<usize>,
+        
+        /// Refuse parameter spaces with more combinations than this
+        #[arg(long, default_value_t = optimize::DEFAULT_MAX_COMBINATIONS)]
+        max_combinations: usize,
+        
+        /// File to write the best strategy config to, or with --walk-forward the latest window's (defaults to <strategy>_optimized.toml)
+        #[arg(long)]
+        out: Option<PathBuf>,
+        
+        /// Runs to list in the results table
+        #[arg(long, default_value_t = 20)]
+        top: usize,
+        
+        /// Optimize on rolling training periods and test each choice on the period after it
+        #[arg(long, requires_all = ["train", "test"])]
+        walk_forward: bool,
+        
+        /// Walk-forward training period, e.g. 90d
+        #[arg(long, value_parser = parse_duration, requires = "walk_forward")]
+        train: Option<Duration>,
+        
+        /// Walk-forward test period, e.g. 30d
+        #[arg(long, value_parser = parse_duration, requires = "walk_forward")]
+        test: Option<Duration>,
+        
+        /// How far each walk-forward window moves on (defaults to --test)
+        #[arg(long, value_parser = parse_duration, requires = "walk_forward")]
+        step: Option<Duration>,
+    },
+    
+    /// Run the trading bot against recorded market data and a simulated exchange
+    Replay {
+        /// Directory the market recorder wrote to (defaults to recorder.directory)
+        #[arg(long)]
+        data: Option<PathBuf>,
+        
+        /// First day to replay (UTC, YYYY-MM-DD)
+        #[arg(long)]
+        from: NaiveDate,
+        
+        /// Last day to replay, inclusive (UTC, YYYY-MM-DD)
+        #[arg(long)]
+        to: NaiveDate,
+        
+        /// Play back in real time sped up by this factor, e.g. 60 for an hour a minute (as fast as possible if omitted)
+        #[arg(long)]
+        speed: Option<f64>,
+        
+        /// Directory to write trades.csv and equity.csv to
+        #[arg(long)]
+        out: Option<PathBuf>,
+        
+        /// Write the report as a self-contained HTML page with an equity chart
+        #[arg(long)]
+        report_html: Option<PathBuf>,
+        
+        /// Write the report as JSON
+        #[arg(long)]
+        report_json: Option<PathBuf>,
+    },
+    
+    /// Download historical candles to CSV files that backtest --data reads
+    DownloadData {
+        /// Symbols to download, comma-separated (defaults to the enabled strategies' symbols)
+        #[arg(long, value_delimiter = ',')]
+        symbols: Vec<String>,
+        
+        /// Candle interval: 1m, 5m, 15m, 1h, 4h or 1d (defaults to backtest.interval)
+        #[arg(long)]
+        interval: Option<String>,
+        
+        /// First day to download (UTC, YYYY-MM-DD)
+        #[arg(long)]
+        from: NaiveDate,
+        
+        /// Last day to download, inclusive (UTC, YYYY-MM-DD)
+        #[arg(long)]
+        to: NaiveDate,
+        
+        /// Directory to write <SYMBOL>_<interval>.csv files to
+        #[arg(long, default_value = "data")]
+        out: PathBuf,
+    },
+    
+    /// Check a config file without starting the bot
+    ValidateConfig {
+        /// Also check connectivity, symbols, leverage and wallet balance against the exchange
+        #[arg(long)]
+        online: bool,
+    },
+    
+    /// Show the state of a running bot
+    Status {
+        /// Print the raw snapshot as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    
+    /// Export or import the bot's full state, e.g. to move it to another host
+    Snapshot {
+        #[command(subcommand)]
+        action: SnapshotAction,
+    },
+    
+    /// Show balances, positions, open orders and recent fills from the exchange
+    Account {
+        /// Print the raw snapshot as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    
+    /// Compare recorded fills with the exchange's and record the ones the bot missed (the bot must be stopped)
+    Reconcile {
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    
+    /// Cancel open orders on the exchange
+    CancelAll {
+        /// Only cancel orders for this symbol
+        #[arg(long)]
+        symbol: Option<String>,
+        
+        /// Skip the confirmation prompt (required on mainnet)
+        #[arg(long)]
+        yes: bool,
+    },
+    
+    /// Cancel open orders and close positions with reduce-only market orders
+    Flatten {
+        /// Only flatten this symbol
+        #[arg(long)]
+        symbol: Option<String>,
+        
+        /// Skip the confirmation prompt (required on mainnet)
+        #[arg(long)]
+        yes: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum SnapshotAction {
+    /// Write the state of the running bot as versioned JSON
+    Export {
+        /// Output file (defaults to stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        
+        /// Read the storage file instead of asking the running bot
+        #[arg(long)]
+        storage: bool,
+    },
+    
+    /// Load a snapshot into the storage file for the bot's next start (the bot must be stopped)
+    Import {
+        /// Snapshot file written by `snapshot export`
+        file: PathBuf,
+    },
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
+    let command = cli.command.unwrap_or(Command::Run);
+    
+    // Reports load errors itself rather than failing on the first one
+    if let Command::ValidateConfig { online } = command {
+        return validate_config(&cli.config, online).await;
+    }
+    
+    // Load configuration (before logging, since it selects the log format)
+    let config = Config::load(&cli.config)?;
     
     // Setup logging
-    setup_logging(cli.debug)?;
+    setup_logging_with_format(cli.debug, config.logging.format)?;
     
+    // Crash and critical-error reporting (no-op unless built with `sentry`)
+    reporting::init(&config)?;
+    
+    match command {
+        Command::Run => {
+            let mode = match (cli.once, cli.run_for) {
+                (true, _) => RunMode::Once,
+                (false, Some(duration)) => RunMode::For(duration),
+                (false, None) => RunMode::Continuous,
+            };
+            let mut config = config;
+            if cli.reset_paper {
+                config.paper.reset = true;
+            }
+            if cli.ignore_preflight {
+                config.preflight.ignore_failures = true;
+            }
+            if let Some(path) = &cli.replay_ws {
+                config.data_source.replay = Some(path.display().to_string());
+            }
+            run(config, cli.dry_run, &cli.strategies, &cli.symbols, mode).await
+        }
+        Command::ExportEquity { from, to, format, output } => export_equity(&config, from, to, format, output),
+        Command::Backtest { from, to, data, interval, out, report_html, report_json } => {
+            backtest(&config, from, to, data, interval, out, report_html, report_json).await
+        }
+        Command::Replay { data, from, to, speed, out, report_html, report_json } => {
+            replay(config, data, from, to, speed, out, report_html, report_json).await
+        }
+        Command::Optimize {
+            strategy,
+            space,
+            from,
+            to,
+            data,
+            interval,
+            objective,
+            workers,
+            max_combinations,
+            out,
+            top,
+            walk_forward,
+            train,
+            test,
+            step,
+        } => {
+            let walk_forward = match (walk_forward, train, test) {
+                (true, Some(train), Some(test)) => Some(walk_forward_windows(train, test, step)?),
+                _ => None,
+            };
+            optimize(
+                config,
+                strategy,
+                space,
+                from,
+                to,
+                data,
+                interval,
+                objective,
+                workers,
+                max_combinations,
+                out,
+                top,
+                walk_forward,
+            )
+            .await
+        }
+        Command::DownloadData { symbols, interval, from, to, out } => {
+            download_data(&config, symbols, interval, from, to, out).await
+        }
+        Command::Status { json } => status(&config, json).await,
+        Command::Snapshot { action: SnapshotAction::Export { output, storage } } => {
+            export_snapshot(&config, output, storage).await
+        }
+        Command::Snapshot { action: SnapshotAction::Import { file } } => import_snapshot(&config, file).await,
+        Command::Account { json } => account(&config, json).await,
+        Command::Reconcile { json } => reconcile_fills(&config, json).await,
+        Command::CancelAll { symbol, yes } => cancel_all(&config, symbol, yes).await,
+        Command::Flatten { symbol, yes } => flatten(&config, symbol, yes).await,
+        Command::ValidateConfig { .. } => unreachable!("handled before loading the config"),
+    }
+}
+
+async fn run(mut config: Config, dry_run: bool, strategies: &[String], symbols: &[String], mode: RunMode) -> Result<()> {
     info!("🚀 Starting Hyperliquid Trading Bot");
     info!("📊 GitHub: https://github.com/topsecretagent007/hyperliquid-trading-bot");
     info!("📱 Telegram: @topsecretagent_007");
     
-    // Load configuration
-    let config = Config::load(&cli.config)?;
-    
     // Override dry run if specified
-    let mut config = config;
-    if cli.dry_run {
+    if dry_run {
         config.trading.dry_run = true;
         info!("🔍 Running in DRY RUN mode - no actual trades will be executed");
     }
     
+    if !config.accounts.is_empty() {
+        if !strategies.is_empty() || !symbols.is_empty() {
+            bail!("--strategies and --symbols only apply to single-account configs");
+        }
+        return run_accounts(config, mode).await;
+    }
+    
+    let overrides = config.apply_strategy_overrides(strategies, symbols)?;
+    if !overrides.enabled.is_empty() {
+        info!("🎛️ Enabled by override: {}", overrides.enabled.join(", "));
+    }
+    if !overrides.disabled.is_empty() {
+        info!("🎛️ Disabled by override: {}", overrides.disabled.join(", "));
+    }
+    
+    if !config.trading.dry_run && config.hyperliquid.private_key.is_empty() {
+        bail!("Live trading needs a private key; account_address alone only allows dry runs and read-only commands");
+    }
+    
+    let control_config = config.control.clone();
+    let http_config = config.http.clone();
+    
     // Create trading bot
     let bot = Arc::new(TradingBot::new(config).await?);
     
-    // Start the bot
-    let bot_handle = {
+    // Local read-only endpoint for `status`
+    if control_config.enabled {
         let bot = bot.clone();
         tokio::spawn(async move {
-            if let Err(e) = bot.start().await {
-                error!("Bot error: {}", e);
+            if let Err(e) = control::serve(bot, control_config).await {
+                error!("Control endpoint error: {}", e);
             }
-        })
+        });
+    }
+    
+    // Read-only HTTP status for monitoring probes
+    if http_config.enabled {
+        let bot = bot.clone();
+        tokio::spawn(async move {
+            if let Err(e) = http::serve(bot, http_config).await {
+                error!("HTTP status server error: {}", e);
+            }
+        });
+    }
+    
+    // Listen before starting so an early SIGTERM isn't missed
+    let mut shutdown_signal = ShutdownSignal::new()?;
+    
+    // Start the bot
+    let mut bot_handle = {
+        let bot = bot.clone();
+        tokio::spawn(async move { bot.run(mode).await })
+    };
+    
+    // Wait for shutdown signal, or for a bounded run to finish
+    let finished = tokio::select! {
+        result = &mut bot_handle => Some(result),
+        name = shutdown_signal.recv() => {
+            info!("🛑 Received {}", name);
+            None
+        }
     };
     
-    // Wait for shutdown signal
-    match signal::ctrl_c().await {
-        Ok(()) => {
-            info!("🛑 Received shutdown signal");
+    // Graceful shutdown: let the current cycle finish rather than aborting it
+    info!("🔄 Shutting down gracefully...");
+    let report = bot.stop().await;
+    log_final_report(&bot.get_status().await);
+    reporting::flush(Duration::from_secs(2));
+    
+    if !report.incomplete.is_empty() {
+        bail!("Shutdown deadline passed; not completed: {}", report.incomplete.join(", "));
+    }
+    let result = match finished {
+        Some(result) => result,
+        None => bot_handle.await,
+    }?;
+    if !report.failed.is_empty() {
+        let failed: Vec<&str> = report.failed.iter().map(|(name, _)| *name).collect();
+        bail!("Shutdown steps failed: {}", failed.join(", "));
+    }
+    info!("✅ Shutdown complete");
+    
+    // Bounded runs report failed cycles through the exit code. A run that
+    // ends in an error, such as a stalled loop, always exits non-zero so a
+    // process supervisor restarts it.
+    let bounded = mode != RunMode::Continuous;
+    match result {
+        Err(e) => bail!("Bot error: {}", e),
+        Ok(summary) if bounded && summary.failed_cycles > 0 => {
+            bail!("{} of {} trading cycles failed", summary.failed_cycles, summary.cycles)
         }
-        Err(err) => {
-            error!("Unable to listen for shutdown signal: {}", err);
+        Ok(_) => {}
+    }
+    Ok(())
+}
+
+/// Trades every configured account until shut down, each on its own
+/// trading loop over one shared market feed
+async fn run_accounts(config: Config, mode: RunMode) -> Result<()> {
+    let accounts: Vec<&str> = config.accounts.iter().map(|account| account.name.as_str()).collect();
+    info!("👥 Trading {} accounts: {}", accounts.len(), accounts.join(", "));
+    for account in &config.accounts {
+        if !config.trading.dry_run && account.hyperliquid.private_key.is_empty() {
+            bail!("Live trading needs a private key; account {} only has an account address", account.name);
         }
     }
+    if config.control.enabled || config.http.enabled {
+        info!("Status endpoints serve single-account runs only; not starting them");
+    }
+    
+    let supervisor = Arc::new(BotSupervisor::from_config(&config).await?);
+    let mut shutdown_signal = ShutdownSignal::new()?;
+    
+    let mut runs = {
+        let supervisor = supervisor.clone();
+        tokio::spawn(async move { supervisor.run(mode).await })
+    };
+    let finished = tokio::select! {
+        results = &mut runs => Some(results),
+        name = shutdown_signal.recv() => {
+            info!("🛑 Received {}", name);
+            None
+        }
+    };
     
-    // Graceful shutdown
     info!("🔄 Shutting down gracefully...");
-    bot.stop().await;
-    bot_handle.abort();
+    let reports = supervisor.stop().await;
+    for bot in supervisor.bots() {
+        info!("👤 Account {}", bot.account().unwrap_or_default());
+        log_final_report(&bot.get_status().await);
+    }
+    reporting::flush(Duration::from_secs(2));
     
+    let results = match finished {
+        Some(results) => results,
+        None => runs.await,
+    }?;
+    let unclean: Vec<&str> = reports
+        .iter()
+        .filter(|(_, report)| !report.is_clean())
+        .map(|(account, _)| account.as_str())
+        .collect();
+    if !unclean.is_empty() {
+        bail!("Shutdown incomplete for accounts: {}", unclean.join(", "));
+    }
     info!("✅ Shutdown complete");
+    
+    let bounded = mode != RunMode::Continuous;
+    let failed: Vec<String> = results
+        .into_iter()
+        .filter_map(|(account, result)| match result {
+            Err(e) => Some(format!("{} ({})", account, e)),
+            Ok(summary) if bounded && summary.failed_cycles > 0 => {
+                Some(format!("{} ({} of {} cycles failed)", account, summary.failed_cycles, summary.cycles))
+            }
+            Ok(_) => None,
+        })
+        .collect();
+    if !failed.is_empty() {
+        bail!("Accounts failed: {}", failed.join(", "));
+    }
+    Ok(())
+}
+
+fn log_final_report(status: &BotStatus) {
+    let risk = &status.risk_metrics;
+    info!("📋 Uptime: {}s", status.uptime_seconds);
+    info!(
+        "📋 Trades: {} ({} ok, {} failed)",
+        status.total_trades, status.successful_trades, status.failed_trades
+    );
+    info!("📋 Daily PnL: {}, total PnL: {}", format_currency(risk.daily_pnl), format_currency(risk.total_pnl));
+}
+
+fn export_equity(
+    config: &Config,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    format: ExportFormat,
+    output: Option<PathBuf>,
+) -> Result<()> {
+    let Some(storage) = storage::open(&config.storage)? else {
+        bail!("Storage is disabled; enable [storage] to record the equity curve");
+    };
+    
+    let from = from.map(start_of_day).unwrap_or(DateTime::<Utc>::MIN_UTC);
+    let to = to.and_then(|d| d.succ_opt()).map(start_of_day).unwrap_or_else(Utc::now);
+    
+    let samples = storage.equity_samples(from, to)?;
+    
+    let writer: Box<dyn Write> = match &output {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(BufWriter::new(io::stdout())),
+    };
+    equity::export(&samples, format, writer)?;
+    
+    if let Some(path) = output {
+        info!("📈 Exported {} equity samples to {}", samples.len(), path.display());
+    }
+    Ok(())
+}
+
+async fn export_snapshot(config: &Config, output: Option<PathBuf>, from_storage: bool) -> Result<()> {
+    let snapshot = if from_storage {
+        let Some(storage) = storage::open(&config.storage)? else {
+            bail!("Storage is disabled; enable [storage] to keep the bot's state");
+        };
+        Snapshot::from_storage(storage.as_ref(), config, Utc::now())?
+    } else {
+        control::query_snapshot(&config.control).await?
+    };
+    
+    let json = snapshot.to_json()?;
+    match &output {
+        Some(path) => {
+            fs::write(path, json + "\n")?;
+            info!("📦 Exported snapshot to {}", path.display());
+        }
+        None => println!("{}", json),
+    }
+    Ok(())
+}
+
+async fn import_snapshot(config: &Config, file: PathBuf) -> Result<()> {
+    let snapshot = Snapshot::from_json(&fs::read_to_string(&file)?)?;
+    
+    // A running bot would overwrite the imported state on its next cycle
+    if control::query_status(&config.control).await.is_ok() {
+        bail!("The bot is running; stop it before importing a snapshot");
+    }
+    
+    let Some(storage) = storage::open(&config.storage)? else {
+        bail!("Storage is disabled; enable [storage] to import a snapshot");
+    };
+    snapshot.save_to_storage(storage.as_ref(), config)?;
+    
+    info!(
+        "📦 Imported snapshot taken at {} into {}",
+        snapshot.created_at.format("%Y-%m-%d %H:%M:%S UTC"),
+        config.storage.path
+    );
+    Ok(())
+}
+
+fn api_client(config: &Config) -> Result<HyperliquidClient> {
+    Ok(HyperliquidClient::builder()
+        .base_url(&config.hyperliquid.base_url)
+        .ws_url(&config.hyperliquid.ws_url)
+        .credentials(&config.hyperliquid.api_key, &config.hyperliquid.private_key)
+        .account_address(config.hyperliquid.account_address.clone())
+        .testnet(config.hyperliquid.testnet)
+        .http(&config.hyperliquid.http)
+        .build()?
+        .with_vault_address(config.hyperliquid.vault().map(str::to_string))
+        .with_rate_limits(&config.hyperliquid.rate_limit)
+        .with_retries(config.trading.retry_attempts, Duration::from_millis(config.trading.retry_delay_ms))
+        .with_market_data_ttl(Duration::from_millis(config.hyperliquid.market_data_ttl_ms)))
+}
+
+fn start_of_day(date: NaiveDate) -> DateTime<Utc> {
+    date.and_hms_opt(0, 0, 0).unwrap().and_utc()
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn backtest(
+    config: &Config,
+    from: NaiveDate,
+    to: NaiveDate,
+    data_dir: Option<PathBuf>,
+    interval: Option<String>,
+    out: Option<PathBuf>,
+    report_html: Option<PathBuf>,
+    report_json: Option<PathBuf>,
+) -> Result<()> {
+    if to < from {
+        bail!("--to must not be before --from");
+    }
+    let interval = interval.unwrap_or_else(|| config.backtest.interval.clone());
+    let start = start_of_day(from);
+    let end = to.succ_opt().map(start_of_day).unwrap_or(DateTime::<Utc>::MAX_UTC);
+    
+    let mut backtester = Backtester::from_config(config).await?;
+    let symbols = backtester.symbols();
+    let market_data = load_market_data(config, &symbols, &interval, start, end, data_dir.as_deref()).await?;
+    if let Some(dir) = &data_dir {
+        backtester = backtester.with_cost_model(recorded_cost_model(config, &symbols, dir)?);
+    }
+    
+    let result = backtester.run(market_data).await?;
+    write_backtest_outputs(&result, out, report_html, report_json)
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn replay(
+    config: Config,
+    data_dir: Option<PathBuf>,
+    from: NaiveDate,
+    to: NaiveDate,
+    speed: Option<f64>,
+    out: Option<PathBuf>,
+    report_html: Option<PathBuf>,
+    report_json: Option<PathBuf>,
+) -> Result<()> {
+    if to < from {
+        bail!("--to must not be before --from");
+    }
+    let speed = match speed {
+        Some(factor) if factor <= 0.0 => bail!("--speed must be positive"),
+        Some(factor) => ReplaySpeed::Accelerated(factor),
+        None => ReplaySpeed::AsFastAsPossible,
+    };
+    let data_dir = data_dir.unwrap_or_else(|| PathBuf::from(&config.recorder.directory));
+    
+    let mut symbols: Vec<String> = config.strategies.values().filter(|s| s.enabled).map(|s| s.symbol.clone()).collect();
+    symbols.sort();
+    symbols.dedup();
+    let stream = ReplayMarketStream::open_days(&data_dir, &symbols, from, to)?.with_speed(speed);
+    
+    let result = Replay::new(config, stream).await?.run().await?;
+    write_backtest_outputs(&result, out, report_html, report_json)
+}
+
+/// Prints the report and writes whichever of the report files and CSVs were asked for
+fn write_backtest_outputs(
+    result: &BacktestResult,
+    out: Option<PathBuf>,
+    report_html: Option<PathBuf>,
+    report_json: Option<PathBuf>,
+) -> Result<()> {
+    let report = BacktestReport::new(result);
+    report.write_text(io::stdout().lock())?;
+    
+    if let Some(path) = report_html {
+        report.write_html(create_file(&path)?)?;
+        info!("🧪 Wrote HTML report to {}", path.display());
+    }
+    if let Some(path) = report_json {
+        report.write_json(create_file(&path)?)?;
+        info!("🧪 Wrote JSON report to {}", path.display());
+    }
+    
+    if let Some(out) = out {
+        fs::create_dir_all(&out)?;
+        result.write_trades_csv(BufWriter::new(File::create(out.join("trades.csv"))?))?;
+        equity::export(&result.equity_curve, ExportFormat::Csv, BufWriter::new(File::create(out.join("equity.csv"))?))?;
+        info!("🧪 Wrote trades.csv and equity.csv to {}", out.display());
+    }
+    Ok(())
+}
+
+/// Candles for `symbols` from `start` (less the warm-up bars, so trading
+/// starts on that day) to `end`, from `data_dir` or else the API
+async fn load_market_data(
+    config: &Config,
+    symbols: &[String],
+    interval: &str,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    data_dir: Option<&Path>,
+) -> Result<Vec<MarketData>> {
+    let warmup = interval_duration(interval)? * config.backtest.warmup_bars as i32;
+    let load_from = start - warmup;
+    Ok(match data_dir {
+        Some(dir) => data::load_directory(dir, symbols, interval, load_from, end)?,
+        None => data::fetch(&api_client(config)?, symbols, interval, load_from, end).await?,
+    })
+}
+
+/// The configured cost model, with recorded funding rates from `dir`
+/// replacing costs.funding_rate where there are any
+fn recorded_cost_model(config: &Config, symbols: &[String], dir: &Path) -> Result<CostModel> {
+    let mut costs = CostModel::from_config(config);
+    for symbol in symbols {
+        let path = data::funding_path(dir, symbol);
+        if path.exists() {
+            costs = costs.with_funding_history(symbol, data::read_funding_csv(&path)?);
+            info!("🧪 Using recorded funding rates for {}", symbol);
+        }
+    }
+    Ok(costs)
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn optimize(
+    config: Config,
+    strategy: String,
+    space: PathBuf,
+    from: NaiveDate,
+    to: NaiveDate,
+    data_dir: Option<PathBuf>,
+    interval: Option<String>,
+    objective: Objective,
+    workers: Option<usize>,
+    max_combinations: usize,
+    out: Option<PathBuf>,
+    top: usize,
+    walk_forward: Option<WalkForward>,
+) -> Result<()> {
+    if to < from {
+        bail!("--to must not be before --from");
+    }
+    let interval = interval.unwrap_or_else(|| config.backtest.interval.clone());
+    let start = start_of_day(from);
+    let end = to.succ_opt().map(start_of_day).unwrap_or(DateTime::<Utc>::MAX_UTC);
+    let space = ParameterSpace::load(&space)?;
+    
+    let mut optimizer = Optimizer::new(config.clone(), &strategy)?
+        .with_objective(objective)
+        .with_max_combinations(max_combinations);
+    if let Some(workers) = workers {
+        optimizer = optimizer.with_workers(workers);
+    }
+    
+    let symbols = vec![optimizer.strategy_config().symbol.clone()];
+    let market_data = load_market_data(&config, &symbols, &interval, start, end, data_dir.as_deref()).await?;
+    if let Some(dir) = &data_dir {
+        optimizer = optimizer.with_cost_model(recorded_cost_model(&config, &symbols, dir)?);
+    }
+    
+    let best = match walk_forward {
+        Some(walk_forward) => {
+            let result = optimizer.walk_forward(&space, market_data, &walk_forward, start, end).await?;
+            result.write_table(io::stdout().lock())?;
+            result.latest_config(optimizer.strategy_config())
+        }
+        None => {
+            let result = optimizer.run(&space, market_data).await?;
+            result.write_table(io::stdout().lock(), top)?;
+            result.best_config(optimizer.strategy_config())
+        }
+    };
+    
+    let Some(best) = best else {
+        bail!("Every combination failed to backtest");
+    };
+    let out = out.unwrap_or_else(|| PathBuf::from(format!("{}_optimized.toml", strategy)));
+    optimize::write_strategy_toml(create_file(&out)?, &strategy, &best)?;
+    info!("🔬 Wrote the chosen parameters to {}", out.display());
+    Ok(())
+}
+
+fn walk_forward_windows(train: Duration, test: Duration, step: Option<Duration>) -> Result<WalkForward> {
+    let walk_forward = WalkForward::new(chrono::Duration::from_std(train)?, chrono::Duration::from_std(test)?);
+    Ok(match step {
+        Some(step) => walk_forward.with_step(chrono::Duration::from_std(step)?),
+        None => walk_forward,
+    })
+}
+
+/// Creates `path` and any missing parent directories
+fn create_file(path: &Path) -> Result<BufWriter<File>> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    Ok(BufWriter::new(File::create(path)?))
+}
+
+async fn download_data(
+    config: &Config,
+    symbols: Vec<String>,
+    interval: Option<String>,
+    from: NaiveDate,
+    to: NaiveDate,
+    out: PathBuf,
+) -> Result<()> {
+    if to < from {
+        bail!("--to must not be before --from");
+    }
+    let interval = interval.unwrap_or_else(|| config.backtest.interval.clone());
+    let symbols = if symbols.is_empty() {
+        let mut configured: Vec<String> = config
+            .strategies
+            .values()
+            .filter(|strategy| strategy.enabled)
+            .map(|strategy| strategy.symbol.clone())
+            .collect();
+        configured.sort();
+        configured.dedup();
+        configured
+    } else {
+        symbols
+    };
+    if symbols.is_empty() {
+        bail!("No symbols to download; pass --symbols or enable a strategy");
+    }
+    
+    let start = start_of_day(from);
+    let end = to.succ_opt().map(start_of_day).unwrap_or(DateTime::<Utc>::MAX_UTC);
+    info!("📥 Downloading {} candles for {} into {}", interval, symbols.join(", "), out.display());
+    
+    let client = api_client(config)?;
+    let summaries = Downloader::new(&client).download(&out, &symbols, &interval, start, end).await?;
+    download::write_summary(&summaries, io::stdout().lock())?;
+    Ok(())
+}
+
+async fn validate_config(path: &str, online: bool) -> Result<()> {
+    let mut report = ValidationReport::new();
+    let config = validation::validate_offline(path, &mut report).await;
+    
+    if online {
+        match &config {
+            Some(config) => match api_client(config) {
+                Ok(client) => validation::validate_online(config, &client, &mut report).await,
+                Err(e) => report.fail("exchange checks", e.to_string()),
+            },
+            None => report.skip("exchange checks", "config did not load"),
+        }
+    }
+    
+    report.write(io::stdout().lock())?;
+    if !report.passed() {
+        bail!("{} config check(s) failed", report.failures());
+    }
+    Ok(())
+}
+
+async fn account(config: &Config, json: bool) -> Result<()> {
+    let snapshot = account::fetch(&api_client(config)?).await?;
+    
+    if json {
+        println!("{}", serde_json::to_string_pretty(&snapshot)?);
+    } else {
+        snapshot.write_table(io::stdout().lock())?;
+    }
+    Ok(())
+}
+
+async fn reconcile_fills(config: &Config, json: bool) -> Result<()> {
+    // The bot reconciles on its own schedule and would record the same fills
+    if control::query_status(&config.control).await.is_ok() {
+        bail!("The bot is running; it reconciles fills itself when reconciliation.enabled is set");
+    }
+    
+    let Some(storage) = storage::open(&config.storage)? else {
+        bail!("Storage is disabled; enable [storage] to reconcile fills");
+    };
+    let report = reconcile::reconcile(&api_client(config)?, storage.as_ref(), &config.reconciliation, Utc::now()).await?;
+    let journal = TradeJournal::from_config(&config.journal)?;
+    reconcile::record(storage.as_ref(), journal.as_ref(), &report)?;
+    
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_reconciliation(&report);
+    }
+    Ok(())
+}
+
+fn print_reconciliation(report: &ReconciliationReport) {
+    println!(
+        "Fills from {} to {}",
+        report.since.format("%Y-%m-%d %H:%M:%S UTC"),
+        report.until.format("%Y-%m-%d %H:%M:%S UTC")
+    );
+    println!("Matched: {}", report.matched);
+    println!("Missing (now recorded): {}", report.missing.len());
+    for fill in &report.missing {
+        println!(
+            "  {} {:?} {} {} @ {} (order {})",
+            fill.timestamp.format("%Y-%m-%d %H:%M:%S"),
+            fill.side,
+            fill.quantity,
+            fill.symbol,
+            fill.price,
+            fill.order_id
+        );
+    }
+    println!("Unknown to the exchange: {}", report.unknown.len());
+    for fill in &report.unknown {
+        println!(
+            "  {} {:?} {} {} @ {} (order {}, strategy {})",
+            fill.timestamp.format("%Y-%m-%d %H:%M:%S"),
+            fill.side,
+            fill.quantity,
+            fill.symbol,
+            fill.price,
+            fill.order_id,
+            fill.strategy
+        );
+    }
+}
+
+/// Gate for destructive commands: mainnet needs `--yes`, testnet asks first
+fn confirm(config: &Config, yes: bool, action: &str) -> Result<()> {
+    let network = if config.hyperliquid.testnet { "testnet" } else { "MAINNET" };
+    println!("{}", intervention::RUNNING_BOT_NOTE);
+    
+    match intervention::confirmation(config.hyperliquid.testnet, yes) {
+        Confirmation::Proceed => Ok(()),
+        Confirmation::RefuseMainnet => bail!("Refusing to {} on mainnet without --yes", action),
+        Confirmation::Prompt => {
+            if !io::stdin().is_terminal() {
+                bail!("Not a terminal; pass --yes to {} without confirming", action);
+            }
+            print!("About to {} on {}. Continue? [y/N] ", action, network);
+            io::stdout().flush()?;
+            
+            let mut answer = String::new();
+            io::stdin().lock().read_line(&mut answer)?;
+            if !matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") {
+                bail!("Aborted");
+            }
+            Ok(())
+        }
+    }
+}
+
+fn finish_intervention(report: &InterventionReport) -> Result<()> {
+    report.write(io::stdout().lock())?;
+    if report.failures() > 0 {
+        bail!("{} of {} action(s) failed", report.failures(), report.outcomes.len());
+    }
+    Ok(())
+}
+
+fn scope(symbol: &Option<String>) -> String {
+    symbol.as_deref().map(|s| format!("for {}", s)).unwrap_or_else(|| "for all symbols".to_string())
+}
+
+async fn cancel_all(config: &Config, symbol: Option<String>, yes: bool) -> Result<()> {
+    confirm(config, yes, &format!("cancel every open order {}", scope(&symbol)))?;
+    let report = intervention::cancel_all(&api_client(config)?, symbol.as_deref()).await?;
+    finish_intervention(&report)
+}
+
+async fn flatten(config: &Config, symbol: Option<String>, yes: bool) -> Result<()> {
+    confirm(config, yes, &format!("cancel open orders and close every position {}", scope(&symbol)))?;
+    let report = intervention::flatten(&api_client(config)?, symbol.as_deref()).await?;
+    finish_intervention(&report)
+}
+
+async fn status(config: &Config, json: bool) -> Result<()> {
+    let snapshot = control::query_status(&config.control).await?;
+    
+    if json {
+        println!("{}", serde_json::to_string_pretty(&snapshot)?);
+    } else {
+        snapshot.write(io::stdout().lock())?;
+    }
     Ok(())
 }
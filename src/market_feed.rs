@@ -0,0 +1,351 @@
+use crate::{
+    api::{
+        types::UserFill, EventReceiver, FrameRecorder, MarketDataCache, PostChannel, ReplayWebSocketClient, WebSocketClient,
+        WebSocketHandle, WsPool,
+    },
+    config::Config,
+    error::Result,
+    price_cache::PriceCache,
+};
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::{error, warn};
+
+/// The websocket connections streaming tickers into a price cache. A bot
+/// normally has one of its own; bots trading several accounts from one
+/// process share one, so each symbol is subscribed once however many
+/// accounts trade it. With `data_source.ws_connections` above 1, each
+/// symbol's (and each account's) subscriptions go to one connection of a
+/// [`WsPool`], and a connection that drops is reopened and resubscribed on
+/// its own.
+pub struct MarketFeed {
+    pool: WsPool,
+    prices: PriceCache,
+    /// Every symbol asked for and who asked for it, subscribed again on
+    /// each new connection
+    wanted: Mutex<BTreeMap<String, BTreeSet<String>>>,
+    /// The symbols subscribed on the connections open now
+    subscribed: Mutex<BTreeSet<String>>,
+    /// Users whose fills, order updates and notifications are streamed,
+    /// subscribed again on each new connection, and whether they are on the
+    /// open one
+    accounts: Mutex<BTreeMap<String, bool>>,
+    /// (symbol, interval) candle series streamed, likewise
+    candles: Mutex<BTreeMap<(String, String), bool>>,
+    /// Symbols whose trades are streamed, likewise
+    trades: Mutex<BTreeMap<String, bool>>,
+    /// Whether every mid is streamed and, if so, whether it is on the
+    /// first connection, where it always goes
+    all_mids: Mutex<Option<bool>>,
+    /// Connections opened so far
+    connections: AtomicU64,
+    reconnect_failures: AtomicU32,
+    /// Held while connecting, so bots noticing a drop together reconnect once
+    connecting: tokio::sync::Mutex<()>,
+}
+
+impl MarketFeed {
+    pub fn new(config: &Config) -> Self {
+        let prices = PriceCache::new();
+        let client = match &config.data_source.replay {
+            Some(path) => WebSocketClient::replay(ReplayWebSocketClient::new(path).with_speed(config.data_source.replay_speed)),
+            None => WebSocketClient::new(config.hyperliquid.ws_url.clone()),
+        };
+        // A replay's frames are in its recording already
+        let client = match config.recorder.frames_path.as_ref().filter(|_| config.data_source.replay.is_none()) {
+            Some(path) => match FrameRecorder::new(path, config.recorder.queue_size) {
+                Ok(recorder) => client.with_recorder(recorder),
+                Err(e) => {
+                    error!("Not recording websocket frames to {}: {}", path, e);
+                    client
+                }
+            },
+            None => client,
+        };
+        let client = match config.hyperliquid.trading_account() {
+            Some(account) => client.with_account(account),
+            None => client,
+        };
+        let client = client
+            .with_price_cache(prices.clone())
+            .with_parse_error_limit(config.data_source.max_parse_errors_per_minute)
+            .with_buffers(config.data_source.event_buffer, config.data_source.write_buffer)
+            .with_heartbeat(
+                Duration::from_secs(config.data_source.ping_interval_secs),
+                Duration::from_secs(config.data_source.stale_connection_secs),
+            );
+        
+        Self {
+            pool: WsPool::new(client, config.data_source.ws_connections),
+            prices,
+            wanted: Mutex::new(BTreeMap::new()),
+            subscribed: Mutex::new(BTreeSet::new()),
+            accounts: Mutex::new(BTreeMap::new()),
+            candles: Mutex::new(BTreeMap::new()),
+            trades: Mutex::new(BTreeMap::new()),
+            all_mids: Mutex::new(None),
+            connections: AtomicU64::new(0),
+            reconnect_failures: AtomicU32::new(0),
+            connecting: tokio::sync::Mutex::new(()),
+        }
+    }
+    
+    /// Also pushes streamed prices into `cache`, the REST client's market
+    /// data snapshot, streaming every mid so the snapshot rarely needs
+    /// fetching
+    pub fn with_market_data_cache(mut self, cache: Arc<MarketDataCache>) -> Self {
+        let size = self.pool.size();
+        self.pool = WsPool::new(self.pool.into_client().with_market_data_cache(cache), size);
+        self.stream_all_mids();
+        self
+    }
+    
+    /// Streams tickers for `symbols` on `owner`'s behalf along with those
+    /// already wanted, connecting first unless the websocket is already up
+    pub async fn connect<'a>(&self, owner: &str, symbols: impl IntoIterator<Item = &'a str>) -> Result<()> {
+        let _connecting = self.connecting.lock().await;
+        {
+            let mut wanted = self.wanted.lock().unwrap();
+            for symbol in symbols {
+                wanted.entry(symbol.to_string()).or_default().insert(owner.to_string());
+            }
+        }
+        
+        // Each connection that's down is reopened on its own, and what
+        // belongs on it subscribed afresh
+        let mut failure = None;
+        for slot in 0..self.pool.size() {
+            match self.pool.connect(slot).await {
+                Ok(true) => {
+                    self.forget_subscriptions(slot);
+                    self.connections.fetch_add(1, Ordering::Relaxed);
+                }
+                Ok(false) => {}
+                Err(e) => {
+                    failure.get_or_insert(e);
+                }
+            }
+        }
+        if failure.is_some() {
+            self.reconnect_failures.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.reconnect_failures.store(0, Ordering::Relaxed);
+        }
+        
+        let wanted: Vec<String> = self.wanted.lock().unwrap().keys().cloned().collect();
+        let mut subscribed = self.subscribed.lock().unwrap();
+        for symbol in wanted {
+            if subscribed.contains(&symbol) {
+                continue;
+            }
+            let Some(ws) = self.open_for(&symbol) else {
+                continue;
+            };
+            match ws.subscribe_to_ticker(&symbol) {
+                Ok(()) => {
+                    subscribed.insert(symbol);
+                }
+                Err(e) => warn!("Failed to subscribe to {} tickers: {}", symbol, e),
+            }
+        }
+        
+        for (user, subscribed) in self.accounts.lock().unwrap().iter_mut().filter(|(_, subscribed)| !**subscribed) {
+            let Some(ws) = self.open_for(user) else {
+                continue;
+            };
+            match ws.subscribe_account_channels(user) {
+                Ok(()) => *subscribed = true,
+                Err(e) => warn!("Failed to subscribe to {}'s fills, orders and notifications: {}", user, e),
+            }
+        }
+        
+        for ((symbol, interval), subscribed) in self.candles.lock().unwrap().iter_mut().filter(|(_, subscribed)| !**subscribed) {
+            let Some(ws) = self.open_for(symbol) else {
+                continue;
+            };
+            match ws.subscribe_to_candles(symbol, interval) {
+                Ok(()) => *subscribed = true,
+                Err(e) => warn!("Failed to subscribe to {} {} candles: {}", symbol, interval, e),
+            }
+        }
+        
+        for (symbol, subscribed) in self.trades.lock().unwrap().iter_mut().filter(|(_, subscribed)| !**subscribed) {
+            let Some(ws) = self.open_for(symbol) else {
+                continue;
+            };
+            match ws.subscribe_to_trades(symbol) {
+                Ok(()) => *subscribed = true,
+                Err(e) => warn!("Failed to subscribe to {} trades: {}", symbol, e),
+            }
+        }
+        
+        if let Some(subscribed) = self.all_mids.lock().unwrap().as_mut().filter(|subscribed| !**subscribed) {
+            if let Some(ws) = self.pool.handle(0).filter(|ws| ws.is_connected()) {
+                match ws.subscribe_to_all_mids() {
+                    Ok(()) => *subscribed = true,
+                    Err(e) => warn!("Failed to subscribe to all mids: {}", e),
+                }
+            }
+        }
+        
+        match failure {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+    
+    /// The open connection `key`'s subscriptions go to, if it's up
+    fn open_for(&self, key: &str) -> Option<WebSocketHandle> {
+        self.pool.handle_for(key).filter(|ws| ws.is_connected())
+    }
+    
+    /// Marks whatever belongs on connection `slot` unsubscribed, now that
+    /// it has been opened afresh
+    fn forget_subscriptions(&self, slot: usize) {
+        let on_slot = |key: &str| self.pool.slot(key) == slot;
+        self.subscribed.lock().unwrap().retain(|symbol| !on_slot(symbol));
+        for (user, subscribed) in self.accounts.lock().unwrap().iter_mut() {
+            *subscribed &= !on_slot(user);
+        }
+        for ((symbol, _), subscribed) in self.candles.lock().unwrap().iter_mut() {
+            *subscribed &= !on_slot(symbol);
+        }
+        for (symbol, subscribed) in self.trades.lock().unwrap().iter_mut() {
+            *subscribed &= !on_slot(symbol);
+        }
+        if let Some(subscribed) = self.all_mids.lock().unwrap().as_mut() {
+            *subscribed &= slot != 0;
+        }
+    }
+    
+    /// Also streams `user`'s fills, order updates and notifications, from
+    /// the next `connect` on
+    pub fn stream_account(&self, user: &str) {
+        self.accounts.lock().unwrap().entry(user.to_lowercase()).or_insert(false);
+    }
+    
+    /// Also streams `symbol`'s candles at `interval`, from the next
+    /// `connect` on
+    pub fn stream_candles(&self, symbol: &str, interval: &str) {
+        self.candles.lock().unwrap().entry((symbol.to_string(), interval.to_string())).or_insert(false);
+    }
+    
+    /// Also streams every trade on `symbol`, from the next `connect` on
+    pub fn stream_trades(&self, symbol: &str) {
+        self.trades.lock().unwrap().entry(symbol.to_string()).or_insert(false);
+    }
+    
+    /// Also streams every coin's mid, from the next `connect` on
+    pub fn stream_all_mids(&self) {
+        self.all_mids.lock().unwrap().get_or_insert(false);
+    }
+    
+    /// How many connections have been opened. A different count means a
+    /// new connection, and that anything pushed while there was none was
+    /// missed.
+    pub fn connections(&self) -> u64 {
+        self.connections.load(Ordering::Relaxed)
+    }
+    
+    /// Stops streaming `symbol` for `owner`. Its tickers keep coming while
+    /// anyone else sharing the feed still wants them.
+    pub fn unsubscribe(&self, owner: &str, symbol: &str) -> Result<()> {
+        {
+            let mut wanted = self.wanted.lock().unwrap();
+            let Some(owners) = wanted.get_mut(symbol) else {
+                return Ok(());
+            };
+            owners.remove(owner);
+            if !owners.is_empty() {
+                return Ok(());
+            }
+            wanted.remove(symbol);
+        }
+        
+        if !self.subscribed.lock().unwrap().remove(symbol) {
+            return Ok(());
+        }
+        match self.pool.handle_for(symbol) {
+            Some(ws) => ws.unsubscribe_ticker(symbol),
+            None => Ok(()),
+        }
+    }
+    
+    /// Closes the connections; the next `connect` opens new ones
+    pub async fn disconnect(&self) -> Result<()> {
+        self.pool.disconnect().await
+    }
+    
+    /// Every message decoded from now on, whichever connection it came in
+    /// on; see [`WebSocketClient::events`]
+    pub fn events(&self) -> EventReceiver {
+        self.pool.events()
+    }
+    
+    /// Posts exchange actions over this feed's connections
+    pub fn post_channel(&self) -> PostChannel {
+        self.pool.post_channel()
+    }
+    
+    /// The first connection, once there's been one; the only one unless
+    /// `data_source.ws_connections` says otherwise
+    pub fn handle(&self) -> Option<WebSocketHandle> {
+        self.pool.handle(0)
+    }
+    
+    /// The connections the feed's subscriptions are spread across
+    pub fn pool(&self) -> &WsPool {
+        &self.pool
+    }
+    
+    /// Whether every connection is up
+    pub fn is_connected(&self) -> bool {
+        self.pool.is_connected()
+    }
+    
+    /// When the quietest connection last received anything
+    pub fn last_message_at(&self) -> Option<DateTime<Utc>> {
+        self.pool.last_message_at()
+    }
+    
+    /// How long ago the quietest connection last received anything; the
+    /// stream's data is no fresher than this
+    pub fn last_message_age(&self) -> Option<Duration> {
+        self.last_message_at().map(|at| (Utc::now() - at).to_std().unwrap_or_default())
+    }
+    
+    /// Failed connection attempts since the last one that worked
+    pub fn reconnect_failures(&self) -> u32 {
+        self.reconnect_failures.load(Ordering::Relaxed)
+    }
+    
+    /// The symbols whose tickers the open connections stream
+    pub fn subscriptions(&self) -> BTreeSet<String> {
+        self.subscribed.lock().unwrap().clone()
+    }
+    
+    /// Records `fill` as applied, returning whether it's new; see
+    /// [`WebSocketClient::mark_fill_seen`]
+    pub fn mark_fill_seen(&self, fill: &UserFill) -> bool {
+        self.pool.client().mark_fill_seen(fill)
+    }
+    
+    /// Frames dropped for failing to parse, by channel
+    pub fn parse_errors(&self) -> BTreeMap<String, u64> {
+        self.pool.client().parse_errors()
+    }
+    
+    /// Messages dropped for a consumer that fell behind, by channel; see
+    /// [`WebSocketClient::dropped_messages`]
+    pub fn dropped_messages(&self) -> BTreeMap<String, u64> {
+        self.pool.client().dropped_messages()
+    }
+    
+    /// Latest prices streamed by the feed and fetched by the bots using it
+    pub fn prices(&self) -> &PriceCache {
+        &self.prices
+    }
+}
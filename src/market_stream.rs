@@ -0,0 +1,167 @@
+use crate::{
+    api::types::Candle,
+    error::Result,
+    models::MarketData,
+    recorder::{self, RecordedEvent, RecordingReader},
+};
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tokio::time::Instant;
+use tracing::debug;
+
+/// Top of the order book for a symbol
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookUpdate {
+    pub symbol: String,
+    pub best_bid: Option<Decimal>,
+    pub best_ask: Option<Decimal>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// One market update, from the exchange or a recording
+#[derive(Debug, Clone)]
+pub enum MarketEvent {
+    Ticker(MarketData),
+    /// A candle, as market data at its close
+    Candle(MarketData),
+    Book(BookUpdate),
+}
+
+impl MarketEvent {
+    pub fn symbol(&self) -> &str {
+        match self {
+            MarketEvent::Ticker(data) | MarketEvent::Candle(data) => &data.symbol,
+            MarketEvent::Book(book) => &book.symbol,
+        }
+    }
+    
+    pub fn timestamp(&self) -> DateTime<Utc> {
+        match self {
+            MarketEvent::Ticker(data) | MarketEvent::Candle(data) => data.timestamp,
+            MarketEvent::Book(book) => book.timestamp,
+        }
+    }
+    
+    /// The price update this event carries, if any
+    pub fn market_data(&self) -> Option<&MarketData> {
+        match self {
+            MarketEvent::Ticker(data) | MarketEvent::Candle(data) => Some(data),
+            MarketEvent::Book(_) => None,
+        }
+    }
+    
+    /// Decodes a recorded event. Recorded fills are the recording bot's own
+    /// output rather than market data, so they give `None`.
+    pub fn from_recorded(event: RecordedEvent) -> Result<Option<Self>> {
+        Ok(match event.stream {
+            recorder::MarketStream::Ticker => Some(MarketEvent::Ticker(serde_json::from_value(event.data)?)),
+            recorder::MarketStream::Candle => {
+                let candle: Candle = serde_json::from_value(event.data)?;
+                Some(MarketEvent::Candle(MarketData {
+                    symbol: event.symbol,
+                    price: candle.c,
+                    volume_24h: candle.v,
+                    change_24h: candle.c - candle.o,
+                    high_24h: candle.h,
+                    low_24h: candle.l,
+                    timestamp: event.timestamp,
+                }))
+            }
+            recorder::MarketStream::Book => Some(MarketEvent::Book(serde_json::from_value(event.data)?)),
+            recorder::MarketStream::Fill => None,
+        })
+    }
+}
+
+/// A source of market events in time order
+#[async_trait]
+pub trait MarketStream: Send {
+    /// The next event, or `None` once the stream has ended
+    async fn next_event(&mut self) -> Result<Option<MarketEvent>>;
+}
+
+/// How fast a replay plays back
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplaySpeed {
+    AsFastAsPossible,
+    /// Event gaps shortened by this factor, e.g. 60 plays an hour a minute
+    Accelerated(f64),
+}
+
+/// Plays the market recorder's files back as a [`MarketStream`]
+pub struct ReplayMarketStream {
+    reader: RecordingReader,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    speed: ReplaySpeed,
+    /// First event's timestamp and when it was emitted
+    started: Option<(DateTime<Utc>, Instant)>,
+}
+
+impl ReplayMarketStream {
+    /// Events in `[from, to)` for `symbols` (all recorded symbols if empty)
+    pub fn open(directory: &Path, symbols: &[String], from: DateTime<Utc>, to: DateTime<Utc>) -> Result<Self> {
+        let symbols = if symbols.is_empty() { None } else { Some(symbols.to_vec()) };
+        let last_day = (to - chrono::Duration::nanoseconds(1)).date_naive().max(from.date_naive());
+        Ok(Self {
+            reader: RecordingReader::open(directory, from.date_naive(), last_day, symbols)?,
+            from,
+            to,
+            speed: ReplaySpeed::AsFastAsPossible,
+            started: None,
+        })
+    }
+    
+    /// Covers whole UTC days, `from` through `to`
+    pub fn open_days(directory: &Path, symbols: &[String], from: NaiveDate, to: NaiveDate) -> Result<Self> {
+        let start = from.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+        let end = to.succ_opt().unwrap_or(to).and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+        Self::open(directory, symbols, start, end)
+    }
+    
+    pub fn with_speed(mut self, speed: ReplaySpeed) -> Self {
+        self.speed = speed;
+        self
+    }
+    
+    /// Waits until `timestamp` is due at the configured speed
+    async fn pace(&mut self, timestamp: DateTime<Utc>) {
+        let ReplaySpeed::Accelerated(factor) = self.speed else {
+            return;
+        };
+        let (first, started) = *self.started.get_or_insert((timestamp, Instant::now()));
+        let elapsed = (timestamp - first).to_std().unwrap_or_default();
+        if factor > 0.0 {
+            tokio::time::sleep_until(started + elapsed.div_f64(factor)).await;
+        }
+    }
+}
+
+#[async_trait]
+impl MarketStream for ReplayMarketStream {
+    async fn next_event(&mut self) -> Result<Option<MarketEvent>> {
+        while let Some(recorded) = self.reader.next() {
+            let recorded = recorded?;
+            if recorded.timestamp < self.from {
+                continue;
+            }
+            if recorded.timestamp >= self.to {
+                return Ok(None);
+            }
+            
+            let stream = recorded.stream;
+            match MarketEvent::from_recorded(recorded) {
+                Ok(Some(event)) => {
+                    self.pace(event.timestamp()).await;
+                    return Ok(Some(event));
+                }
+                Ok(None) => {}
+                Err(e) => debug!("Skipping unreadable recorded {:?} event: {}", stream, e),
+            }
+        }
+        Ok(None)
+    }
+}
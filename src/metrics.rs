@@ -0,0 +1,171 @@
+use crate::storage::EquitySample;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+
+const DAYS_PER_YEAR: f64 = 365.0;
+
+/// Converts a Decimal for statistics. Money stays in Decimal everywhere else;
+/// ratio statistics are computed in f64, where losing digits past the 15th
+/// significant one is harmless. Values f64 can't represent become 0.0 so a
+/// bad input can never poison a whole series with NaN.
+pub fn decimal_to_f64(value: Decimal) -> f64 {
+    value.to_f64().filter(|v| v.is_finite()).unwrap_or(0.0)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Window {
+    Days(u32),
+    All,
+}
+
+impl Window {
+    pub const WEEK: Window = Window::Days(7);
+    pub const MONTH: Window = Window::Days(30);
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PerformanceStats {
+    pub periods: usize,
+    pub annualized_return: f64,
+    pub volatility: f64,
+    pub sharpe_ratio: f64,
+    pub sortino_ratio: f64,
+    pub max_drawdown: f64,
+    pub hit_rate: f64,
+}
+
+/// Time-indexed series of periodic returns (fractions, e.g. 0.01 = +1%)
+/// with windowed statistics. All ratios are annualized with
+/// `periods_per_year` and assume a zero risk-free rate.
+#[derive(Debug, Clone)]
+pub struct PerformanceWindow {
+    returns: VecDeque<(DateTime<Utc>, f64)>,
+    periods_per_year: f64,
+    max_age: Option<Duration>,
+}
+
+impl PerformanceWindow {
+    pub fn new(periods_per_year: f64) -> Self {
+        Self {
+            returns: VecDeque::new(),
+            periods_per_year,
+            max_age: None,
+        }
+    }
+    
+    pub fn daily() -> Self {
+        Self::new(DAYS_PER_YEAR)
+    }
+    
+    /// Drops returns older than `max_age` as new ones arrive
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+    
+    /// Daily returns from the last equity sample of each UTC day
+    pub fn from_equity_curve(samples: &[EquitySample]) -> Self {
+        let mut daily_close: BTreeMap<NaiveDate, Decimal> = BTreeMap::new();
+        for sample in samples {
+            daily_close.insert(sample.timestamp.date_naive(), sample.equity);
+        }
+        
+        let mut window = Self::daily();
+        let closes: Vec<(NaiveDate, Decimal)> = daily_close.into_iter().collect();
+        for pair in closes.windows(2) {
+            let ((_, previous), (date, close)) = (pair[0], pair[1]);
+            if previous > Decimal::ZERO {
+                let timestamp = date.and_hms_opt(0, 0, 0).unwrap().and_utc();
+                window.push(timestamp, (close - previous) / previous);
+            }
+        }
+        window
+    }
+    
+    pub fn push(&mut self, timestamp: DateTime<Utc>, period_return: Decimal) {
+        self.returns.push_back((timestamp, decimal_to_f64(period_return)));
+        
+        if let Some(max_age) = self.max_age {
+            let cutoff = timestamp - max_age;
+            while self.returns.front().is_some_and(|(ts, _)| *ts < cutoff) {
+                self.returns.pop_front();
+            }
+        }
+    }
+    
+    pub fn len(&self) -> usize {
+        self.returns.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.returns.is_empty()
+    }
+    
+    fn in_window(&self, window: Window, now: DateTime<Utc>) -> Vec<f64> {
+        match window {
+            Window::All => self.returns.iter().map(|(_, r)| *r).collect(),
+            Window::Days(days) => {
+                let cutoff = now - Duration::days(days as i64);
+                self.returns
+                    .iter()
+                    .filter(|(ts, _)| *ts > cutoff && *ts <= now)
+                    .map(|(_, r)| *r)
+                    .collect()
+            }
+        }
+    }
+    
+    pub fn stats(&self, window: Window, now: DateTime<Utc>) -> PerformanceStats {
+        let returns = self.in_window(window, now);
+        let n = returns.len();
+        if n == 0 {
+            return PerformanceStats::default();
+        }
+        
+        let mean = returns.iter().sum::<f64>() / n as f64;
+        let std_dev = if n > 1 {
+            (returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (n - 1) as f64).sqrt()
+        } else {
+            0.0
+        };
+        let downside_dev = (returns.iter().map(|r| r.min(0.0).powi(2)).sum::<f64>() / n as f64).sqrt();
+        let annualization = self.periods_per_year.sqrt();
+        
+        let growth: f64 = returns.iter().map(|r| 1.0 + r).product();
+        let annualized_return = if growth > 0.0 {
+            growth.powf(self.periods_per_year / n as f64) - 1.0
+        } else {
+            -1.0
+        };
+        
+        let mut equity = 1.0;
+        let mut peak = 1.0;
+        let mut max_drawdown: f64 = 0.0;
+        for r in &returns {
+            equity *= 1.0 + r;
+            peak = f64::max(peak, equity);
+            max_drawdown = max_drawdown.max((peak - equity) / peak);
+        }
+        
+        let ratio = |numerator: f64, denominator: f64| {
+            if denominator > 0.0 {
+                numerator / denominator * annualization
+            } else {
+                0.0
+            }
+        };
+        
+        PerformanceStats {
+            periods: n,
+            annualized_return,
+            volatility: std_dev * annualization,
+            sharpe_ratio: if n > 1 { ratio(mean, std_dev) } else { 0.0 },
+            sortino_ratio: ratio(mean, downside_dev),
+            max_drawdown,
+            hit_rate: returns.iter().filter(|r| **r > 0.0).count() as f64 / n as f64,
+        }
+    }
+}
@@ -1,7 +1,9 @@
-use chrono::{DateTime, Utc};
+use crate::costs::Liquidity;
+use crate::data_source::DataSourceStatus;
+use chrono::{DateTime, NaiveDate, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MarketData {
@@ -14,6 +16,17 @@ pub struct MarketData {
     pub timestamp: DateTime<Utc>,
 }
 
+/// One trade on the exchange, between any two accounts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketTrade {
+    pub symbol: String,
+    /// The side that crossed the spread
+    pub side: OrderSide,
+    pub price: Decimal,
+    pub size: Decimal,
+    pub timestamp: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Order {
     pub id: String,
@@ -27,9 +40,27 @@ pub struct Order {
     pub updated_at: Option<DateTime<Utc>>,
     pub filled_quantity: Decimal,
     pub average_price: Option<Decimal>,
+    /// Only ever shrinks an existing position
+    #[serde(default)]
+    pub reduce_only: bool,
+    /// Mark price that sets off a `Stop` or `StopLimit` order
+    #[serde(default)]
+    pub trigger_price: Option<Decimal>,
+    /// Whether a trigger order takes profit rather than stopping a loss
+    #[serde(default)]
+    pub is_take_profit: bool,
+    /// Client order id sent with the order, so the exchange can be asked
+    /// about it before its own id is known
+    #[serde(default)]
+    pub cloid: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A fresh client order id: 0x and 128 random bits in hex
+pub fn new_cloid() -> String {
+    format!("0x{}", uuid::Uuid::new_v4().simple())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum OrderSide {
     Buy,
     Sell,
@@ -41,9 +72,11 @@ pub enum OrderType {
     Limit,
     Stop,
     StopLimit,
+    /// Sliced by the exchange into market orders spread over the duration
+    Twap { duration_minutes: u32 },
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum OrderStatus {
     Pending,
     Open,
@@ -86,14 +119,145 @@ pub struct AccountInfo {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Trade {
     pub id: String,
+    /// The exchange's id for the order this filled
+    #[serde(default)]
+    pub order_id: String,
+    /// The client order id the order was placed with, if any
+    #[serde(default)]
+    pub cloid: Option<String>,
     pub symbol: String,
     pub side: OrderSide,
     pub quantity: Decimal,
     pub price: Decimal,
     pub fee: Decimal,
+    /// Profit or loss the fill closed, as the exchange reports it
+    #[serde(default)]
+    pub realized_pnl: Decimal,
     pub timestamp: DateTime<Utc>,
 }
 
+/// A perpetual's hourly funding rate. Longs pay shorts `rate` times their
+/// position's value when it's positive.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FundingRate {
+    pub coin: String,
+    pub rate: Decimal,
+    /// Mark premium over the oracle that the rate follows
+    pub premium: Decimal,
+    pub time: DateTime<Utc>,
+}
+
+/// Funding settled on one of the account's positions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FundingPayment {
+    pub coin: String,
+    /// USDC received, negative when paid
+    pub amount: Decimal,
+    /// Signed position size it was settled on
+    pub size: Decimal,
+    pub rate: Decimal,
+    pub time: DateTime<Utc>,
+}
+
+/// The account's value at one point in its history, from the exchange
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EquityPoint {
+    pub time: DateTime<Utc>,
+    pub account_value: Decimal,
+    /// PnL since the history began
+    pub pnl: Decimal,
+}
+
+/// Funding paid net of funding received (negative when more came in than
+/// went out), in total, per coin and per UTC day
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FundingSummary {
+    pub total: Decimal,
+    pub by_coin: BTreeMap<String, Decimal>,
+    pub by_day: BTreeMap<NaiveDate, Decimal>,
+}
+
+impl FundingSummary {
+    pub fn from_payments(payments: &[FundingPayment]) -> Self {
+        let mut summary = Self::default();
+        for payment in payments {
+            let paid = -payment.amount;
+            summary.total += paid;
+            *summary.by_coin.entry(payment.coin.clone()).or_default() += paid;
+            *summary.by_day.entry(payment.time.date_naive()).or_default() += paid;
+        }
+        summary
+    }
+}
+
+/// Where an order stands on the exchange
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrderState {
+    /// On the book, possibly partly filled
+    Resting,
+    Filled,
+    /// Cancelled by the trader or the exchange, possibly partly filled
+    Cancelled,
+    Rejected,
+}
+
+/// What the exchange says became of an order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderStatusInfo {
+    pub state: OrderState,
+    pub filled_size: Decimal,
+    /// Average price of its fills, if it has any
+    pub average_price: Option<Decimal>,
+    /// Fees charged on its fills so far
+    #[serde(default)]
+    pub fee: Decimal,
+}
+
+/// The fee rates an account pays, as fractions of the notional traded
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FeeSchedule {
+    /// Paid by fills that rested on the book
+    pub maker_rate: Decimal,
+    /// Paid by fills that took liquidity
+    pub taker_rate: Decimal,
+}
+
+impl FeeSchedule {
+    /// The fee on `notional` traded as `liquidity`
+    pub fn fee(&self, notional: Decimal, liquidity: Liquidity) -> Decimal {
+        let rate = match liquidity {
+            Liquidity::Maker => self.maker_rate,
+            Liquidity::Taker => self.taker_rate,
+        };
+        notional.abs() * rate
+    }
+}
+
+/// How far a TWAP order has got
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TwapStatus {
+    pub twap_id: String,
+    pub symbol: String,
+    /// Size asked for
+    pub size: Decimal,
+    pub filled_size: Decimal,
+    /// Quote value of the slices filled so far
+    pub filled_notional: Decimal,
+    pub fee: Decimal,
+    /// Whether it's finished: fully filled, cancelled or out of time
+    pub done: bool,
+}
+
+impl TwapStatus {
+    pub fn average_price(&self) -> Option<Decimal> {
+        (!self.filled_size.is_zero()).then(|| self.filled_notional / self.filled_size)
+    }
+}
+
+/// Signal metadata asking for the order to be sliced by the exchange over
+/// this many minutes, as a number
+pub const TWAP_MINUTES: &str = "twap_minutes";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StrategySignal {
     pub strategy_name: String,
@@ -103,6 +267,10 @@ pub struct StrategySignal {
     pub price: Option<Decimal>,
     pub confidence: f64,
     pub metadata: HashMap<String, serde_json::Value>,
+    /// Only shrink an existing position. `Close` signals always do, and
+    /// their quantity is capped at the position's size (0 closes all of it).
+    #[serde(default)]
+    pub reduce_only: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -122,7 +290,17 @@ pub struct RiskMetrics {
     pub win_rate: f64,
     pub profit_factor: f64,
     pub sharpe_ratio: f64,
+    pub sharpe_ratio_7d: f64,
+    pub sharpe_ratio_30d: f64,
     pub max_position_risk: Decimal,
+    /// Funding paid net of funding received, already taken out of the PnL
+    #[serde(default)]
+    pub funding_paid: Decimal,
+    #[serde(default)]
+    pub daily_funding_paid: Decimal,
+    /// Trading fees paid, estimated until each order's fills are known
+    #[serde(default)]
+    pub fees_paid: Decimal,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -135,4 +313,16 @@ pub struct BotStatus {
     pub failed_trades: u64,
     pub current_positions: u32,
     pub risk_metrics: RiskMetrics,
+    /// Where market data is coming from
+    #[serde(default)]
+    pub data_source: DataSourceStatus,
+    #[serde(default)]
+    pub ws_connected: bool,
+    /// Seconds since the websocket last received anything, if it has
+    #[serde(default)]
+    pub ws_last_message_secs: Option<u64>,
+    /// Fills the websocket missed while down, recovered over REST once it
+    /// reconnected
+    #[serde(default)]
+    pub ws_recovered_fills: u64,
 }
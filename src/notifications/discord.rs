@@ -0,0 +1,215 @@
+use super::{NotificationChannel, NotificationEvent, Severity};
+use crate::{
+    config::DiscordConfig,
+    error::{Error, Result},
+};
+use async_trait::async_trait;
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::time::Duration;
+use tracing::warn;
+
+// Discord embed limits (https://discord.com/developers/docs/resources/message#embed-object-embed-limits)
+const MAX_EMBEDS: usize = 10;
+const MAX_TITLE_LEN: usize = 256;
+const MAX_DESCRIPTION_LEN: usize = 4096;
+const MAX_FIELDS: usize = 25;
+const MAX_FIELD_NAME_LEN: usize = 256;
+const MAX_FIELD_VALUE_LEN: usize = 1024;
+const MAX_TOTAL_LEN: usize = 6000;
+
+const COLOR_GREEN: u32 = 0x2ecc71;
+const COLOR_RED: u32 = 0xe74c3c;
+const COLOR_ORANGE: u32 = 0xe67e22;
+const COLOR_BLUE: u32 = 0x3498db;
+
+#[derive(Debug, Deserialize)]
+struct RateLimitResponse {
+    retry_after: f64,
+}
+
+pub struct DiscordNotifier {
+    client: Client,
+    config: DiscordConfig,
+    /// Shown in every embed's footer, when the process trades several
+    /// accounts
+    account: Option<String>,
+}
+
+impl DiscordNotifier {
+    pub fn new(config: DiscordConfig) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .expect("Failed to create HTTP client");
+        
+        Self { client, config, account: None }
+    }
+    
+    pub fn with_account(mut self, account: Option<&str>) -> Self {
+        self.account = account.map(str::to_string);
+        self
+    }
+    
+    fn color(event: &NotificationEvent) -> u32 {
+        match event {
+            NotificationEvent::TradeExecuted { .. } => COLOR_GREEN,
+            NotificationEvent::RiskLimitTripped { .. } => COLOR_RED,
+            NotificationEvent::KillSwitch { engaged: true, .. } => COLOR_RED,
+            NotificationEvent::KillSwitch { engaged: false, .. } => COLOR_GREEN,
+            NotificationEvent::WebSocketDisconnected { .. } => COLOR_ORANGE,
+            NotificationEvent::WatchdogStall { .. } => COLOR_RED,
+            NotificationEvent::StrategyDisabled { .. } => COLOR_ORANGE,
+            NotificationEvent::FillDiscrepancy { .. } => COLOR_ORANGE,
+            NotificationEvent::ExchangeNotification { .. } => COLOR_ORANGE,
+            NotificationEvent::ProfitSweep { error: None, .. } => COLOR_GREEN,
+            NotificationEvent::ProfitSweep { error: Some(_), .. } => COLOR_ORANGE,
+            NotificationEvent::DailySummary { .. } => COLOR_BLUE,
+        }
+    }
+    
+    fn embed(event: &NotificationEvent) -> Value {
+        // Every structured field except the tag becomes an inline embed field
+        let fields: Vec<Value> = match serde_json::to_value(event) {
+            Ok(Value::Object(map)) => map
+                .into_iter()
+                .filter(|(key, _)| key != "type")
+                .take(MAX_FIELDS)
+                .map(|(key, value)| {
+                    let value = match value {
+                        Value::String(s) => s,
+                        other => other.to_string(),
+                    };
+                    json!({
+                        "name": truncate(&key, MAX_FIELD_NAME_LEN),
+                        "value": truncate(&value, MAX_FIELD_VALUE_LEN),
+                        "inline": true,
+                    })
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        
+        json!({
+            "title": truncate(event.title(), MAX_TITLE_LEN),
+            "description": truncate(&event.to_text(), MAX_DESCRIPTION_LEN),
+            "color": Self::color(event),
+            "fields": fields,
+        })
+    }
+    
+    /// Builds the webhook body for a batch, dropping embeds past Discord's
+    /// per-message count and total character limits instead of erroring
+    pub fn build_payload(&self, events: &[NotificationEvent]) -> Value {
+        let mut embeds = Vec::new();
+        let mut total_len = 0;
+        
+        for event in events.iter().take(MAX_EMBEDS) {
+            let mut embed = Self::embed(event);
+            if let Some(account) = &self.account {
+                embed["footer"] = json!({ "text": format!("Account: {}", account) });
+            }
+            let mut len = embed_text_len(&embed);
+            if total_len + len > MAX_TOTAL_LEN {
+                if !embeds.is_empty() {
+                    break;
+                }
+                // A single oversized event still gets delivered, without fields,
+                // its description taking what the title and footer leave
+                embed["fields"] = json!([]);
+                embed["description"] = json!("");
+                let description_len = MAX_DESCRIPTION_LEN.min(MAX_TOTAL_LEN - embed_text_len(&embed));
+                embed["description"] = json!(truncate(&event.to_text(), description_len));
+                len = embed_text_len(&embed);
+            }
+            total_len += len;
+            embeds.push(embed);
+        }
+        
+        let omitted = events.len() - embeds.len();
+        let mut payload = json!({ "embeds": embeds });
+        if omitted > 0 {
+            payload["content"] = json!(format!("… {} more event(s) omitted", omitted));
+        }
+        if let Some(username) = &self.config.username {
+            payload["username"] = json!(username);
+        }
+        
+        payload
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for DiscordNotifier {
+    fn name(&self) -> &str {
+        "discord"
+    }
+    
+    fn min_severity(&self) -> Severity {
+        self.config.min_severity
+    }
+    
+    fn event_filter(&self) -> &[String] {
+        &self.config.events
+    }
+    
+    async fn send_batch(&self, events: &[NotificationEvent]) -> Result<()> {
+        let payload = self.build_payload(events);
+        let mut attempt = 0;
+        
+        loop {
+            let response = self.client
+                .post(&self.config.webhook_url)
+                .json(&payload)
+                .send()
+                .await;
+            
+            let error = match response {
+                Ok(response) if response.status().is_success() => return Ok(()),
+                Ok(response) if response.status() == StatusCode::TOO_MANY_REQUESTS => {
+                    // Discord reports retry_after in (fractional) seconds
+                    let retry_after = response
+                        .json::<RateLimitResponse>()
+                        .await
+                        .ok()
+                        .map(|r| Duration::from_secs_f64(r.retry_after.max(0.0)));
+                    Error::RateLimit { retry_after }
+                }
+                Ok(response) => Error::from_response(response).await,
+                Err(e) => e.into(),
+            };
+            if attempt >= self.config.max_retries || !error.is_retryable() {
+                return Err(error);
+            }
+            let delay = error
+                .retry_after()
+                .unwrap_or_else(|| Duration::from_millis(500 * (1 << attempt.min(5))));
+            
+            attempt += 1;
+            warn!("Discord webhook delivery failed (attempt {}): {}, retrying in {:?}", attempt, error, delay);
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    
+    let mut truncated: String = text.chars().take(max_chars - 1).collect();
+    truncated.push('…');
+    truncated
+}
+
+fn embed_text_len(embed: &Value) -> usize {
+    let text_len = |v: &Value| v.as_str().map(|s| s.chars().count()).unwrap_or(0);
+    
+    let fields_len: usize = embed["fields"]
+        .as_array()
+        .map(|fields| fields.iter().map(|f| text_len(&f["name"]) + text_len(&f["value"])).sum())
+        .unwrap_or(0);
+    
+    text_len(&embed["title"]) + text_len(&embed["description"]) + text_len(&embed["footer"]["text"]) + fields_len
+}
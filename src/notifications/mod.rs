@@ -0,0 +1,364 @@
+pub mod discord;
+pub mod telegram;
+pub mod webhook;
+
+pub use discord::DiscordNotifier;
+pub use telegram::TelegramNotifier;
+pub use webhook::{WebhookNotifier, WebhookPayload};
+
+use crate::{
+    config::NotificationsConfig,
+    error::Result,
+    events::{BotEvent, BusEvent, EventSubscriber},
+    models::OrderSide,
+    utils::{format_currency, format_decimal},
+};
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::{debug, error, warn};
+
+// Events queued per channel before new ones are dropped
+const CHANNEL_QUEUE_SIZE: usize = 256;
+// How long a channel worker waits for more events to join a batch
+const BATCH_WINDOW: Duration = Duration::from_millis(500);
+const MAX_BATCH_SIZE: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    #[default]
+    Info,
+    Warning,
+    Critical,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NotificationEvent {
+    TradeExecuted {
+        strategy: String,
+        symbol: String,
+        side: OrderSide,
+        quantity: Decimal,
+        price: Decimal,
+        dry_run: bool,
+    },
+    RiskLimitTripped {
+        reason: String,
+    },
+    KillSwitch {
+        engaged: bool,
+        reason: String,
+    },
+    WebSocketDisconnected {
+        down_secs: u64,
+    },
+    WatchdogStall {
+        silent_secs: u64,
+        stage: String,
+        in_flight: Option<String>,
+    },
+    StrategyDisabled {
+        strategy: String,
+        reason: String,
+    },
+    /// Fill reconciliation found fills only the exchange or only the bot
+    /// knows about
+    FillDiscrepancy {
+        /// Exchange fills the bot hadn't recorded, now added
+        missing: usize,
+        /// Recorded fills the exchange doesn't know about
+        unknown: usize,
+    },
+    /// A message the exchange pushed for the account, e.g. that a position
+    /// is close to liquidation
+    ExchangeNotification {
+        message: String,
+    },
+    /// Profits above the sweep threshold were moved out of the account, or
+    /// failed to be
+    ProfitSweep {
+        amount: Decimal,
+        destination: String,
+        error: Option<String>,
+    },
+    DailySummary {
+        date: NaiveDate,
+        daily_pnl: Decimal,
+        total_pnl: Decimal,
+        total_trades: u64,
+        successful_trades: u64,
+        failed_trades: u64,
+        /// Funding paid over the day, already taken out of its PnL
+        funding_paid: Decimal,
+    },
+}
+
+impl NotificationEvent {
+    pub fn severity(&self) -> Severity {
+        match self {
+            NotificationEvent::TradeExecuted { .. }
+            | NotificationEvent::DailySummary { .. }
+            | NotificationEvent::ProfitSweep { error: None, .. } => Severity::Info,
+            NotificationEvent::RiskLimitTripped { .. }
+            | NotificationEvent::ProfitSweep { error: Some(_), .. }
+            | NotificationEvent::WebSocketDisconnected { .. }
+            | NotificationEvent::StrategyDisabled { .. }
+            | NotificationEvent::FillDiscrepancy { .. }
+            | NotificationEvent::ExchangeNotification { .. } => Severity::Warning,
+            NotificationEvent::KillSwitch { .. } | NotificationEvent::WatchdogStall { .. } => Severity::Critical,
+        }
+    }
+    
+    /// Stable event name, matching the serialized `type` tag and the
+    /// `events` filter lists in the channel configs
+    pub fn kind(&self) -> &'static str {
+        match self {
+            NotificationEvent::TradeExecuted { .. } => "trade_executed",
+            NotificationEvent::RiskLimitTripped { .. } => "risk_limit_tripped",
+            NotificationEvent::KillSwitch { .. } => "kill_switch",
+            NotificationEvent::WebSocketDisconnected { .. } => "web_socket_disconnected",
+            NotificationEvent::WatchdogStall { .. } => "watchdog_stall",
+            NotificationEvent::StrategyDisabled { .. } => "strategy_disabled",
+            NotificationEvent::FillDiscrepancy { .. } => "fill_discrepancy",
+            NotificationEvent::ExchangeNotification { .. } => "exchange_notification",
+            NotificationEvent::ProfitSweep { .. } => "profit_sweep",
+            NotificationEvent::DailySummary { .. } => "daily_summary",
+        }
+    }
+    
+    pub fn title(&self) -> &'static str {
+        match self {
+            NotificationEvent::TradeExecuted { .. } => "Trade executed",
+            NotificationEvent::RiskLimitTripped { .. } => "Risk limit tripped",
+            NotificationEvent::KillSwitch { engaged: true, .. } => "Kill switch engaged",
+            NotificationEvent::KillSwitch { engaged: false, .. } => "Kill switch released",
+            NotificationEvent::WebSocketDisconnected { .. } => "WebSocket disconnected",
+            NotificationEvent::WatchdogStall { .. } => "Trading loop stalled",
+            NotificationEvent::StrategyDisabled { .. } => "Strategy disabled",
+            NotificationEvent::FillDiscrepancy { .. } => "Fill discrepancy",
+            NotificationEvent::ExchangeNotification { .. } => "Exchange notification",
+            NotificationEvent::ProfitSweep { error: None, .. } => "Profits swept",
+            NotificationEvent::ProfitSweep { error: Some(_), .. } => "Profit sweep failed",
+            NotificationEvent::DailySummary { .. } => "Daily summary",
+        }
+    }
+    
+    /// Single-line, plain-text rendering shared by the text-based channels
+    pub fn to_text(&self) -> String {
+        match self {
+            NotificationEvent::TradeExecuted { strategy, symbol, side, quantity, price, dry_run } => {
+                let action = match side {
+                    OrderSide::Buy => "BUY",
+                    OrderSide::Sell => "SELL",
+                };
+                format!(
+                    "{}{} {} {} @ {} ({})",
+                    if *dry_run { "[DRY RUN] " } else { "" },
+                    action,
+                    format_decimal(*quantity, 6),
+                    symbol,
+                    format_decimal(*price, 2),
+                    strategy
+                )
+            }
+            NotificationEvent::RiskLimitTripped { reason } => format!("⚠️ Risk limit tripped: {}", reason),
+            NotificationEvent::KillSwitch { engaged, reason } => {
+                if *engaged {
+                    format!("🛑 Kill switch engaged: {}", reason)
+                } else {
+                    format!("✅ Kill switch released: {}", reason)
+                }
+            }
+            NotificationEvent::WebSocketDisconnected { down_secs } => {
+                format!("🔌 WebSocket disconnected for {}s", down_secs)
+            }
+            NotificationEvent::WatchdogStall { silent_secs, stage, in_flight } => match in_flight {
+                Some(request) => format!("⏱️ Trading loop stalled for {}s in {}, awaiting {}", silent_secs, stage, request),
+                None => format!("⏱️ Trading loop stalled for {}s in {}", silent_secs, stage),
+            },
+            NotificationEvent::StrategyDisabled { strategy, reason } => {
+                format!("⏸️ Strategy {} disabled: {}", strategy, reason)
+            }
+            NotificationEvent::FillDiscrepancy { missing, unknown } => format!(
+                "🧾 Fill reconciliation: {} exchange fills were missing and have been recorded, {} recorded fills are unknown to the exchange",
+                missing, unknown
+            ),
+            NotificationEvent::ExchangeNotification { message } => format!("📣 From the exchange: {}", message),
+            NotificationEvent::ProfitSweep { amount, destination, error } => match error {
+                None => format!("💸 Swept {} to {}", format_currency(*amount), destination),
+                Some(error) => format!("⚠️ Failed to sweep {} to {}: {}", format_currency(*amount), destination, error),
+            },
+            NotificationEvent::DailySummary {
+                date,
+                daily_pnl,
+                total_pnl,
+                total_trades,
+                successful_trades,
+                failed_trades,
+                funding_paid,
+            } => format!(
+                "📊 {} — daily PnL {} (funding paid {}), total PnL {}, trades {} ({} ok / {} failed)",
+                date,
+                format_currency(*daily_pnl),
+                format_currency(*funding_paid),
+                format_currency(*total_pnl),
+                total_trades,
+                successful_trades,
+                failed_trades
+            ),
+        }
+    }
+}
+
+#[async_trait]
+pub trait NotificationChannel: Send + Sync {
+    fn name(&self) -> &str;
+    
+    fn min_severity(&self) -> Severity {
+        Severity::Info
+    }
+    
+    /// Event kinds this channel wants; an empty list means all of them
+    fn event_filter(&self) -> &[String] {
+        &[]
+    }
+    
+    fn accepts(&self, event: &NotificationEvent) -> bool {
+        let filter = self.event_filter();
+        event.severity() >= self.min_severity()
+            && (filter.is_empty() || filter.iter().any(|kind| kind == event.kind()))
+    }
+    
+    /// Delivers a batch of already-filtered events. Implementations own their
+    /// rate limiting and retries; errors are logged by the dispatcher.
+    async fn send_batch(&self, events: &[NotificationEvent]) -> Result<()>;
+}
+
+/// Fans events out to every configured channel. Each channel gets its own
+/// bounded queue and worker task, so a slow or failing channel never blocks
+/// the trading loop or the other channels.
+#[derive(Clone, Default)]
+pub struct Notifier {
+    senders: Vec<(String, mpsc::Sender<NotificationEvent>)>,
+}
+
+impl Notifier {
+    pub fn new(channels: Vec<Arc<dyn NotificationChannel>>) -> Self {
+        let senders = channels
+            .into_iter()
+            .map(|channel| {
+                let (tx, rx) = mpsc::channel(CHANNEL_QUEUE_SIZE);
+                let name = channel.name().to_string();
+                tokio::spawn(run_channel(channel, rx));
+                (name, tx)
+            })
+            .collect();
+        
+        Self { senders }
+    }
+    
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+    
+    pub fn from_config(config: &NotificationsConfig) -> Self {
+        Self::build(config, None)
+    }
+    
+    /// The configured channels, with every message labeled as coming from
+    /// `account`
+    pub fn for_account(config: &NotificationsConfig, account: &str) -> Self {
+        Self::build(config, Some(account))
+    }
+    
+    fn build(config: &NotificationsConfig, account: Option<&str>) -> Self {
+        let mut channels: Vec<Arc<dyn NotificationChannel>> = Vec::new();
+        
+        if let Some(telegram) = config.telegram.as_ref().filter(|t| t.enabled) {
+            channels.push(Arc::new(TelegramNotifier::new(telegram.clone()).with_account(account)));
+        }
+        
+        if let Some(discord) = config.discord.as_ref().filter(|d| d.enabled) {
+            channels.push(Arc::new(DiscordNotifier::new(discord.clone()).with_account(account)));
+        }
+        
+        if let Some(webhook) = config.webhook.as_ref().filter(|w| w.enabled) {
+            match WebhookNotifier::new(webhook.clone()) {
+                Ok(notifier) => channels.push(Arc::new(notifier.with_account(account))),
+                Err(e) => error!("Webhook notifications disabled: {}", e),
+            }
+        }
+        
+        Self::new(channels)
+    }
+    
+    pub fn is_enabled(&self) -> bool {
+        !self.senders.is_empty()
+    }
+    
+    /// Queues an event for delivery. Never blocks and never fails: if a
+    /// channel's queue is full the event is dropped for that channel.
+    pub fn notify(&self, event: NotificationEvent) {
+        for (name, sender) in &self.senders {
+            if let Err(e) = sender.try_send(event.clone()) {
+                warn!("Dropping notification for {}: {}", name, e);
+            }
+        }
+    }
+}
+
+/// Announces fills, and account risk limits tripping
+impl EventSubscriber for Notifier {
+    fn name(&self) -> &str {
+        "notifier"
+    }
+    
+    fn handle(&mut self, event: &BusEvent) {
+        match &event.event {
+            // Reconciled fills are announced together, as a discrepancy
+            BotEvent::OrderFilled(fill) if !fill.reconciled => self.notify(NotificationEvent::TradeExecuted {
+                strategy: fill.strategy.clone(),
+                symbol: fill.symbol.clone(),
+                side: fill.side.clone(),
+                quantity: fill.quantity,
+                price: fill.price,
+                dry_run: fill.dry_run,
+            }),
+            BotEvent::RiskRejected { signal: None, reason } => {
+                self.notify(NotificationEvent::RiskLimitTripped { reason: reason.clone() })
+            }
+            _ => {}
+        }
+    }
+}
+
+async fn run_channel(channel: Arc<dyn NotificationChannel>, mut rx: mpsc::Receiver<NotificationEvent>) {
+    while let Some(first) = rx.recv().await {
+        let mut batch = vec![first];
+        
+        // Collect a burst into a single delivery
+        let deadline = tokio::time::Instant::now() + BATCH_WINDOW;
+        while batch.len() < MAX_BATCH_SIZE {
+            match tokio::time::timeout_at(deadline, rx.recv()).await {
+                Ok(Some(event)) => batch.push(event),
+                Ok(None) | Err(_) => break,
+            }
+        }
+        
+        batch.retain(|event| channel.accepts(event));
+        if batch.is_empty() {
+            continue;
+        }
+        
+        debug!("Sending {} notification(s) via {}", batch.len(), channel.name());
+        if let Err(e) = channel.send_batch(&batch).await {
+            error!("Failed to deliver notifications via {}: {}", channel.name(), e);
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use super::{NotificationChannel, NotificationEvent, Severity};
+use crate::{
+    config::TelegramConfig,
+    error::{Error, Result},
+};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::json;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+use tracing::warn;
+
+// Telegram allows roughly one message per second per chat
+const MIN_SEND_INTERVAL: Duration = Duration::from_millis(1100);
+const MAX_MESSAGE_LEN: usize = 4096;
+
+#[derive(Debug, Deserialize)]
+struct TelegramResponse {
+    ok: bool,
+    description: Option<String>,
+    parameters: Option<TelegramResponseParameters>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TelegramResponseParameters {
+    retry_after: Option<u64>,
+}
+
+pub struct TelegramNotifier {
+    client: Client,
+    config: TelegramConfig,
+    /// Prefixed to every message, when the process trades several accounts
+    account: Option<String>,
+    last_sent: Mutex<Option<Instant>>,
+}
+
+impl TelegramNotifier {
+    pub fn new(config: TelegramConfig) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .expect("Failed to create HTTP client");
+        
+        Self {
+            client,
+            config,
+            account: None,
+            last_sent: Mutex::new(None),
+        }
+    }
+    
+    pub fn with_account(mut self, account: Option<&str>) -> Self {
+        self.account = account.map(str::to_string);
+        self
+    }
+    
+    /// Renders a burst of events as one message, labelled with the account
+    /// and truncated to Telegram's limit
+    pub fn format_batch(&self, events: &[NotificationEvent]) -> String {
+        let mut message = if events.len() == 1 {
+            format!("🤖 {}\n{}", events[0].title(), events[0].to_text())
+        } else {
+            let lines: Vec<String> = events.iter().map(|e| format!("• {}", e.to_text())).collect();
+            format!("🤖 {} events\n{}", events.len(), lines.join("\n"))
+        };
+        if let Some(account) = &self.account {
+            message = format!("[{}] {}", account, message);
+        }
+        
+        if message.chars().count() > MAX_MESSAGE_LEN {
+            message = message.chars().take(MAX_MESSAGE_LEN - 1).collect();
+            message.push('…');
+        }
+        
+        message
+    }
+    
+    async fn wait_for_slot(&self) {
+        let mut last_sent = self.last_sent.lock().await;
+        if let Some(last) = *last_sent {
+            let next_slot = last + MIN_SEND_INTERVAL;
+            if next_slot > Instant::now() {
+                tokio::time::sleep_until(next_slot).await;
+            }
+        }
+        *last_sent = Some(Instant::now());
+    }
+    
+    async fn send_message(&self, text: &str) -> Result<()> {
+        self.wait_for_slot().await;
+        
+        let url = format!("{}/bot{}/sendMessage", self.config.api_url, self.config.bot_token);
+        let response = self.client
+            .post(&url)
+            .json(&json!({
+                "chat_id": self.config.chat_id,
+                "text": text,
+                "disable_web_page_preview": true,
+            }))
+            .send()
+            .await?;
+        
+        let status = response.status();
+        let body: Option<TelegramResponse> = response.json().await.ok();
+        
+        if status.is_success() && body.as_ref().map(|b| b.ok).unwrap_or(false) {
+            return Ok(());
+        }
+        
+        if status.as_u16() == 429 {
+            let retry_after = body
+                .and_then(|b| b.parameters)
+                .and_then(|p| p.retry_after)
+                .map(Duration::from_secs);
+            return Err(Error::RateLimit { retry_after });
+        }
+        
+        let description = body
+            .and_then(|b| b.description)
+            .unwrap_or_else(|| "no description".to_string());
+        Err(Error::Http {
+            status: status.as_u16(),
+            body: format!("Telegram: {}", description),
+        })
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for TelegramNotifier {
+    fn name(&self) -> &str {
+        "telegram"
+    }
+    
+    fn min_severity(&self) -> Severity {
+        self.config.min_severity
+    }
+    
+    fn event_filter(&self) -> &[String] {
+        &self.config.events
+    }
+    
+    async fn send_batch(&self, events: &[NotificationEvent]) -> Result<()> {
+        let text = self.format_batch(events);
+        let mut attempt = 0;
+        
+        loop {
+            match self.send_message(&text).await {
+                Ok(()) => return Ok(()),
+                Err(error) => {
+                    if attempt >= self.config.max_retries || !error.is_retryable() {
+                        return Err(error);
+                    }
+                    
+                    attempt += 1;
+                    let delay = error
+                        .retry_after()
+                        .unwrap_or_else(|| Duration::from_millis(500 * (1 << attempt.min(5))));
+                    warn!("Telegram send failed (attempt {}): {}, retrying in {:?}", attempt, error, delay);
+                    tokio::time::sleep(delay).await;
+                }
+            }
+        }
+    }
+}
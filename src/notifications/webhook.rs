@@ -0,0 +1,193 @@
+use super::{NotificationChannel, NotificationEvent, Severity};
+use crate::{
+    config::WebhookConfig,
+    error::{Error, Result},
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::Client;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Notify;
+use tracing::{error, warn};
+
+/// Bumped whenever the payload shape changes in a non-additive way
+pub const WEBHOOK_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookPayload {
+    pub schema_version: u32,
+    pub source: &'static str,
+    pub sent_at: DateTime<Utc>,
+    pub severity: Severity,
+    /// The account the event is about, when the process trades several
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account: Option<String>,
+    pub event: NotificationEvent,
+}
+
+impl WebhookPayload {
+    pub fn new(event: NotificationEvent) -> Self {
+        Self {
+            schema_version: WEBHOOK_SCHEMA_VERSION,
+            source: "hyperliquid-trading-bot",
+            sent_at: Utc::now(),
+            severity: event.severity(),
+            account: None,
+            event,
+        }
+    }
+}
+
+// Bounded outbound queue for one endpoint; when full, the oldest payload is
+// dropped so a dead endpoint can't grow memory without limit
+struct EndpointQueue {
+    url: String,
+    pending: Mutex<VecDeque<WebhookPayload>>,
+    capacity: usize,
+    wakeup: Notify,
+    dropped: AtomicU64,
+}
+
+impl EndpointQueue {
+    fn push(&self, payload: WebhookPayload) {
+        let mut pending = self.pending.lock().unwrap();
+        if pending.len() >= self.capacity {
+            pending.pop_front();
+            let dropped = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+            warn!("Webhook queue for {} full, dropped oldest event ({} dropped so far)", self.url, dropped);
+        }
+        pending.push_back(payload);
+        drop(pending);
+        self.wakeup.notify_one();
+    }
+    
+    fn pop(&self) -> Option<WebhookPayload> {
+        self.pending.lock().unwrap().pop_front()
+    }
+}
+
+pub struct WebhookNotifier {
+    config: WebhookConfig,
+    /// Set on every payload, when the process trades several accounts
+    account: Option<String>,
+    queues: Vec<Arc<EndpointQueue>>,
+}
+
+impl WebhookNotifier {
+    pub fn new(config: WebhookConfig) -> Result<Self> {
+        let mut headers = HeaderMap::new();
+        for (name, value) in &config.headers {
+            let name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|e| Error::Config(format!("Invalid webhook header name {}: {}", name, e)))?;
+            let value = HeaderValue::from_str(value)
+                .map_err(|e| Error::Config(format!("Invalid webhook header value for {}: {}", name, e)))?;
+            headers.insert(name, value);
+        }
+        
+        let client = Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .default_headers(headers)
+            .build()?;
+        
+        let queues = config
+            .urls
+            .iter()
+            .map(|url| {
+                let queue = Arc::new(EndpointQueue {
+                    url: url.clone(),
+                    pending: Mutex::new(VecDeque::new()),
+                    capacity: config.queue_size.max(1),
+                    wakeup: Notify::new(),
+                    dropped: AtomicU64::new(0),
+                });
+                tokio::spawn(run_endpoint(client.clone(), queue.clone(), config.max_retries));
+                queue
+            })
+            .collect();
+        
+        Ok(Self { config, account: None, queues })
+    }
+    
+    pub fn with_account(mut self, account: Option<&str>) -> Self {
+        self.account = account.map(str::to_string);
+        self
+    }
+    
+    /// Total events dropped across all endpoints because their queue was full
+    pub fn dropped_count(&self) -> u64 {
+        self.queues.iter().map(|q| q.dropped.load(Ordering::Relaxed)).sum()
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for WebhookNotifier {
+    fn name(&self) -> &str {
+        "webhook"
+    }
+    
+    fn min_severity(&self) -> Severity {
+        self.config.min_severity
+    }
+    
+    fn event_filter(&self) -> &[String] {
+        &self.config.events
+    }
+    
+    async fn send_batch(&self, events: &[NotificationEvent]) -> Result<()> {
+        // One POST per event keeps the schema simple for receivers; delivery
+        // happens on the per-endpoint workers
+        for event in events {
+            let payload = WebhookPayload {
+                account: self.account.clone(),
+                ..WebhookPayload::new(event.clone())
+            };
+            for queue in &self.queues {
+                queue.push(payload.clone());
+            }
+        }
+        
+        Ok(())
+    }
+}
+
+async fn run_endpoint(client: Client, queue: Arc<EndpointQueue>, max_retries: u32) {
+    loop {
+        let payload = match queue.pop() {
+            Some(payload) => payload,
+            None => {
+                queue.wakeup.notified().await;
+                continue;
+            }
+        };
+        
+        let mut attempt = 0;
+        loop {
+            let error = match client.post(&queue.url).json(&payload).send().await {
+                Ok(response) if response.status().is_success() => break,
+                Ok(response) => Error::from_response(response).await,
+                Err(e) => e.into(),
+            };
+            
+            if !error.is_retryable() {
+                error!("Webhook {} rejected event: {}, not retrying", queue.url, error);
+                break;
+            }
+            if attempt >= max_retries {
+                error!("Giving up on webhook {} after {} attempts: {}", queue.url, attempt + 1, error);
+                break;
+            }
+            
+            attempt += 1;
+            let delay = error
+                .retry_after()
+                .unwrap_or_else(|| Duration::from_millis(500 * (1 << attempt.min(6))));
+            warn!("Webhook {} failed (attempt {}): {}, retrying in {:?}", queue.url, attempt, error, delay);
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use crate::{
+    api::{client::TradingClient, types::{Candle, Meta}},
+    config::Config,
+    costs::CostModel,
+    error::Result,
+    market_stream::MarketEvent,
+    models::{AccountInfo, MarketData, Order, Position, Trade},
+    simulated_exchange::{ExchangeSnapshot, SimulatedExchange},
+    storage::{keys, StorageWrite, StorageWriter},
+};
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// The account dry runs trade: a [`SimulatedExchange`] priced from the live
+/// market and saved to storage after every change, so a forward test can
+/// run for weeks across restarts
+pub struct PaperExchange {
+    market: Arc<dyn TradingClient + Send + Sync>,
+    exchange: SimulatedExchange,
+    storage: Option<StorageWriter>,
+}
+
+impl PaperExchange {
+    /// Restores the saved account unless `paper.reset` is set or there's
+    /// none yet, in which case it starts from `paper.initial_balance`
+    pub fn open(config: &Config, market: Arc<dyn TradingClient + Send + Sync>, storage: Option<StorageWriter>) -> Result<Self> {
+        let costs = CostModel::from_config(config);
+        let saved = match &storage {
+            Some(storage) if !config.paper.reset => storage.storage().get_kv(keys::PAPER_ACCOUNT)?,
+            Some(_) => None,
+            None => {
+                warn!("Storage is disabled, so the paper account won't survive a restart");
+                None
+            }
+        };
+        
+        let exchange = match saved {
+            Some(saved) => {
+                let snapshot: ExchangeSnapshot = serde_json::from_str(&saved)?;
+                let exchange = SimulatedExchange::restore(snapshot, costs);
+                info!("📝 Restored paper account with equity {}", exchange.equity());
+                exchange
+            }
+            None => {
+                info!("📝 Starting paper account with {}", config.paper.initial_balance);
+                SimulatedExchange::new(config.paper.initial_balance, costs)
+            }
+        };
+        
+        let paper = Self { market, exchange, storage };
+        // Saved straight away so a reset sticks even if nothing trades
+        paper.save();
+        Ok(paper)
+    }
+    
+    pub fn exchange(&self) -> &SimulatedExchange {
+        &self.exchange
+    }
+    
+    /// Replaces the paper account with a saved one
+    pub fn load(&self, snapshot: ExchangeSnapshot) {
+        self.exchange.load(snapshot);
+        self.save();
+    }
+    
+    pub fn equity(&self) -> Decimal {
+        self.exchange.equity()
+    }
+    
+    /// Moves the paper account with market data that didn't come through
+    /// `get_market_data`, e.g. streamed prices
+    pub fn update_market(&self, market_data: &MarketData) {
+        self.exchange.update(&MarketEvent::Ticker(market_data.clone()));
+        self.save();
+    }
+    
+    /// The real account's balance, which dry runs leave alone
+    pub async fn live_balance(&self) -> Result<Decimal> {
+        Ok(self.market.get_account_info().await?.balance)
+    }
+    
+    fn save(&self) {
+        let Some(storage) = &self.storage else {
+            return;
+        };
+        
+        match serde_json::to_string(&self.exchange.snapshot()) {
+            Ok(value) => storage.try_write(StorageWrite::Kv {
+                key: keys::PAPER_ACCOUNT.to_string(),
+                value,
+            }),
+            Err(e) => warn!("Failed to encode the paper account: {}", e),
+        }
+    }
+}
+
+#[async_trait]
+impl TradingClient for PaperExchange {
+    /// Live market data, which also moves the paper account: funding
+    /// accrues and resting orders the price reaches fill
+    async fn get_market_data(&self, symbol: &str) -> Result<MarketData> {
+        let market_data = self.market.get_market_data(symbol).await?;
+        self.update_market(&market_data);
+        Ok(market_data)
+    }
+    
+    async fn get_account_info(&self) -> Result<AccountInfo> {
+        self.exchange.get_account_info().await
+    }
+    
+    async fn get_positions(&self) -> Result<Vec<Position>> {
+        self.exchange.get_positions().await
+    }
+    
+    async fn get_open_orders(&self) -> Result<Vec<Order>> {
+        self.exchange.get_open_orders().await
+    }
+    
+    async fn place_order(&self, order: &Order) -> Result<String> {
+        let result = self.exchange.place_order(order).await;
+        // Rejections are counted too
+        self.save();
+        result
+    }
+    
+    async fn cancel_order(&self, order_id: &str) -> Result<bool> {
+        let cancelled = self.exchange.cancel_order(order_id).await?;
+        if cancelled {
+            self.save();
+        }
+        Ok(cancelled)
+    }
+    
+    async fn get_trade_history(&self, symbol: Option<&str>) -> Result<Vec<Trade>> {
+        self.exchange.get_trade_history(symbol).await
+    }
+    
+    async fn get_candles(&self, symbol: &str, interval: &str, start: u64, end: u64) -> Result<Vec<Candle>> {
+        self.market.get_candles(symbol, interval, start, end).await
+    }
+    
+    async fn get_meta(&self) -> Result<Meta> {
+        self.market.get_meta().await
+    }
+}
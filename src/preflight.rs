@@ -0,0 +1,111 @@
+use crate::{
+    api::{client::TradingClient, WebSocketHandle},
+    config::{Config, PreflightConfig},
+    price_cache::{CachedPrice, PriceCache},
+    utils::format_currency,
+    validation::{self, ValidationReport},
+};
+use std::collections::BTreeSet;
+use std::time::{Duration, Instant};
+
+/// Reaches the REST API, timing the round trip, then checks every enabled
+/// strategy's symbol and leverage against the exchange's listings
+pub async fn check_exchange(config: &Config, client: &(dyn TradingClient + Send + Sync), report: &mut ValidationReport) {
+    let started = Instant::now();
+    let meta = match client.get_meta().await {
+        Ok(meta) => meta,
+        Err(e) => {
+            report.fail("connectivity", format!("{}: {}", config.hyperliquid.base_url, e));
+            report.skip("symbols", "exchange unreachable");
+            return;
+        }
+    };
+    let latency = started.elapsed();
+    
+    report.pass(
+        "connectivity",
+        format!("{} reachable, {} assets listed", config.hyperliquid.base_url, meta.universe.len()),
+    );
+    let max_latency = Duration::from_millis(config.preflight.max_latency_ms);
+    if latency > max_latency {
+        report.fail("latency", format!("{}ms exceeds the {}ms maximum", latency.as_millis(), max_latency.as_millis()));
+    } else {
+        report.pass("latency", format!("{}ms", latency.as_millis()));
+    }
+    
+    let mut names: Vec<&String> = config
+        .strategies
+        .iter()
+        .filter(|(_, strategy)| strategy.enabled)
+        .map(|(name, _)| name)
+        .collect();
+    names.sort();
+    validation::validate_symbols(config, &names, &meta, report);
+}
+
+/// Checks the account has more than `min_available_margin` to trade with
+pub async fn check_margin(config: &PreflightConfig, client: &(dyn TradingClient + Send + Sync), report: &mut ValidationReport) {
+    match client.get_account_info().await {
+        Ok(account) if account.available_balance > config.min_available_margin => {
+            report.pass("margin", format!("{} available", format_currency(account.available_balance)));
+        }
+        Ok(account) => report.fail(
+            "margin",
+            format!(
+                "{} available, needs more than {}",
+                format_currency(account.available_balance),
+                format_currency(config.min_available_margin)
+            ),
+        ),
+        Err(e) => report.fail("margin", format!("could not fetch the account: {}", e)),
+    }
+}
+
+/// Waits for the websocket's first ticker for any of `symbols`, then
+/// compares its exchange timestamp with when it arrived. The skew includes
+/// delivery time, so the threshold should leave room for it.
+pub async fn check_market_stream(
+    config: &PreflightConfig,
+    ws: &WebSocketHandle,
+    prices: &PriceCache,
+    symbols: &BTreeSet<&str>,
+    report: &mut ValidationReport,
+) {
+    let timeout = Duration::from_secs(config.websocket_timeout_secs);
+    let deadline = Instant::now() + timeout;
+    let first_ticker = loop {
+        if let Some(cached) = symbols.iter().find_map(|symbol| prices.get(symbol)) {
+            break Some(cached);
+        }
+        if Instant::now() >= deadline || !ws.is_connected() {
+            break None;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    };
+    
+    let Some(CachedPrice { market_data, received_at }) = first_ticker else {
+        if ws.last_message_at().is_some() {
+            report.pass("websocket", "connected and receiving messages");
+            report.skip("clock skew", format!("no ticker within {}s to compare", timeout.as_secs()));
+        } else if ws.is_connected() {
+            report.fail("websocket", format!("no message within {}s", timeout.as_secs()));
+            report.skip("clock skew", "no websocket data");
+        } else {
+            report.fail("websocket", "connection closed before any message");
+            report.skip("clock skew", "no websocket data");
+        }
+        return;
+    };
+    
+    report.pass("websocket", format!("receiving {} tickers", market_data.symbol));
+    let skew = (received_at - market_data.timestamp).abs();
+    let max_skew = chrono::Duration::seconds(config.max_clock_skew_secs as i64);
+    if skew > max_skew {
+        report.fail(
+            "clock skew",
+            format!("local clock is {}ms off the exchange's, over the {}s maximum", skew.num_milliseconds(), max_skew.num_seconds()),
+        );
+    } else {
+        report.pass("clock skew", format!("{}ms", skew.num_milliseconds()));
+    }
+}
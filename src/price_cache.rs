@@ -0,0 +1,101 @@
+use crate::models::MarketData;
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use tokio::sync::watch;
+
+/// The latest market data for a symbol and when the bot received it
+#[derive(Debug, Clone)]
+pub struct CachedPrice {
+    pub market_data: MarketData,
+    pub received_at: DateTime<Utc>,
+}
+
+impl CachedPrice {
+    pub fn age(&self, now: DateTime<Utc>) -> Duration {
+        now - self.received_at
+    }
+    
+    pub fn is_stale(&self, now: DateTime<Utc>, max_age: Duration) -> bool {
+        self.age(now) > max_age
+    }
+}
+
+/// Latest market data per symbol, shared by everything that needs prices:
+/// the trading cycle's REST fetches and the websocket stream write to it,
+/// and any component can read it or watch a symbol for changes. Clones
+/// share the same cache.
+#[derive(Clone, Default)]
+pub struct PriceCache {
+    symbols: Arc<RwLock<HashMap<String, watch::Sender<Option<CachedPrice>>>>>,
+}
+
+impl PriceCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Stores `market_data` unless the cache already holds something newer
+    /// for the symbol, so a slow REST response can't overwrite a later tick
+    pub fn update(&self, market_data: MarketData, received_at: DateTime<Utc>) {
+        let update = |sender: &watch::Sender<Option<CachedPrice>>, market_data: MarketData| {
+            sender.send_if_modified(|cached| {
+                if cached.as_ref().is_some_and(|c| c.market_data.timestamp > market_data.timestamp) {
+                    return false;
+                }
+                *cached = Some(CachedPrice { market_data, received_at });
+                true
+            });
+        };
+        
+        if let Some(sender) = self.symbols.read().unwrap().get(&market_data.symbol) {
+            update(sender, market_data);
+            return;
+        }
+        
+        let mut symbols = self.symbols.write().unwrap();
+        let sender = symbols
+            .entry(market_data.symbol.clone())
+            .or_insert_with(|| watch::channel(None).0);
+        update(sender, market_data);
+    }
+    
+    pub fn get(&self, symbol: &str) -> Option<CachedPrice> {
+        self.symbols.read().unwrap().get(symbol)?.borrow().clone()
+    }
+    
+    pub fn price(&self, symbol: &str) -> Option<Decimal> {
+        self.get(symbol).map(|cached| cached.market_data.price)
+    }
+    
+    /// The symbol's latest data if it was received within `max_age`
+    pub fn fresh(&self, symbol: &str, now: DateTime<Utc>, max_age: Duration) -> Option<CachedPrice> {
+        self.get(symbol).filter(|cached| !cached.is_stale(now, max_age))
+    }
+    
+    /// Every symbol's latest data
+    pub fn all(&self) -> HashMap<String, CachedPrice> {
+        self.symbols
+            .read()
+            .unwrap()
+            .iter()
+            .filter_map(|(symbol, sender)| Some((symbol.clone(), sender.borrow().clone()?)))
+            .collect()
+    }
+    
+    /// Watches a symbol. The receiver sees `None` until its first update
+    /// and is notified on every update after that.
+    pub fn subscribe(&self, symbol: &str) -> watch::Receiver<Option<CachedPrice>> {
+        if let Some(sender) = self.symbols.read().unwrap().get(symbol) {
+            return sender.subscribe();
+        }
+        
+        self.symbols
+            .write()
+            .unwrap()
+            .entry(symbol.to_string())
+            .or_insert_with(|| watch::channel(None).0)
+            .subscribe()
+    }
+}
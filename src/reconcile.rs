@@ -0,0 +1,181 @@
+use crate::{
+    api::client::TradingClient,
+    config::ReconciliationConfig,
+    error::Result,
+    journal::{JournalEntry, TradeJournal},
+    models::Trade,
+    storage::{keys, Storage},
+    trade_stats::{TradeStats, TradeStatsTracker},
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+
+/// Recorded as the strategy of fills the bot only learns about from the
+/// exchange
+pub const UNKNOWN_STRATEGY: &str = "unknown";
+
+/// What one reconciliation run found between the watermark and `until`
+#[derive(Debug, Clone, Serialize)]
+pub struct ReconciliationReport {
+    pub since: DateTime<Utc>,
+    /// The new watermark
+    pub until: DateTime<Utc>,
+    /// Exchange fills that matched a recorded one
+    pub matched: usize,
+    /// Exchange fills the bot never recorded, as the entries that record
+    /// them now
+    pub missing: Vec<JournalEntry>,
+    /// Recorded fills the exchange doesn't know about
+    pub unknown: Vec<JournalEntry>,
+}
+
+impl ReconciliationReport {
+    pub fn discrepancies(&self) -> usize {
+        self.missing.len() + self.unknown.len()
+    }
+}
+
+/// Where the last run left off, if there has been one
+pub fn watermark(storage: &dyn Storage) -> Result<Option<DateTime<Utc>>> {
+    match storage.get_kv(keys::RECONCILED_UNTIL)? {
+        Some(saved) => Ok(Some(serde_json::from_str(&saved)?)),
+        None => Ok(None),
+    }
+}
+
+/// Compares the exchange's fills since the watermark (or the initial
+/// lookback) with the fills in `storage`, up to `settle_secs` before `now`.
+/// Only reads: the caller records the missing fills and moves the watermark
+/// to `until`.
+pub async fn reconcile(
+    client: &(dyn TradingClient + Send + Sync),
+    storage: &dyn Storage,
+    config: &ReconciliationConfig,
+    now: DateTime<Utc>,
+) -> Result<ReconciliationReport> {
+    let until = now - Duration::seconds(config.settle_secs as i64);
+    let since = watermark(storage)?.unwrap_or(until - Duration::hours(config.initial_lookback_hours as i64));
+    if until <= since {
+        return Ok(match_fills(&[], &[], since, since, Duration::zero()));
+    }
+    
+    // Recorded fills just outside the range can still match exchange fills
+    // inside it
+    let window = Duration::seconds(config.match_window_secs as i64);
+    let exchange = client.get_trade_history_between(None, Some(since), Some(until)).await?;
+    let local = storage.fills_between(since - window, until + window)?;
+    Ok(match_fills(&local, &exchange, since, until, window))
+}
+
+/// Applies a report straight to `storage` and `journal` while the bot is
+/// stopped, as the running bot does after its own reconciliations: records
+/// the missing fills, counts them as successful trades and moves the
+/// watermark to `until`
+pub fn record(storage: &dyn Storage, journal: Option<&TradeJournal>, report: &ReconciliationReport) -> Result<()> {
+    for entry in &report.missing {
+        storage.record_fill(entry)?;
+        if let Some(journal) = journal {
+            journal.record(entry)?;
+        }
+    }
+    
+    let mut kv = vec![(keys::RECONCILED_UNTIL, serde_json::to_string(&report.until)?)];
+    if !report.missing.is_empty() {
+        let saved = match storage.get_kv(keys::TRADE_STATS)? {
+            Some(saved) => serde_json::from_str(&saved)?,
+            None => TradeStats::new(report.until.date_naive()),
+        };
+        let stats = TradeStatsTracker::new(saved);
+        for _ in &report.missing {
+            stats.record_success();
+        }
+        kv.push((keys::TRADE_STATS, serde_json::to_string(&stats.snapshot())?));
+    }
+    storage.save_state_batch(&[], &kv)
+}
+
+/// Matches exchange fills in `(since, until]` against `local` fills: by
+/// order id, then client order id, then the same symbol, side and size
+/// within `window` of each other. Every fill of a partially filled order
+/// matches its one recorded fill. Dry-run fills never reach the exchange and
+/// are left out.
+pub fn match_fills(
+    local: &[JournalEntry],
+    exchange: &[Trade],
+    since: DateTime<Utc>,
+    until: DateTime<Utc>,
+    window: Duration,
+) -> ReconciliationReport {
+    let in_range = |timestamp: DateTime<Utc>| since < timestamp && timestamp <= until;
+    let local: Vec<&JournalEntry> = local.iter().filter(|fill| !fill.dry_run).collect();
+    let mut exchange: Vec<&Trade> = exchange.iter().filter(|trade| in_range(trade.timestamp)).collect();
+    exchange.sort_by_key(|trade| trade.timestamp);
+    let mut used = vec![false; local.len()];
+    
+    // Ids first, so a size match never takes a fill an id would have claimed
+    let mut matched = 0;
+    let mut unmatched = Vec::new();
+    for trade in exchange {
+        let by_id = local.iter().position(|fill| {
+            fill.order_id == trade.order_id || (trade.cloid.is_some() && fill.cloid == trade.cloid)
+        });
+        match by_id {
+            Some(index) => {
+                used[index] = true;
+                matched += 1;
+            }
+            None => unmatched.push(trade),
+        }
+    }
+    
+    let mut missing = Vec::new();
+    for trade in unmatched {
+        let by_shape = (0..local.len()).find(|&index| {
+            let fill = local[index];
+            !used[index]
+                && fill.symbol == trade.symbol
+                && fill.side == trade.side
+                && fill.quantity == trade.quantity
+                && (fill.timestamp - trade.timestamp).abs() <= window
+        });
+        match by_shape {
+            Some(index) => {
+                used[index] = true;
+                matched += 1;
+            }
+            None => missing.push(reconciled_entry(trade)),
+        }
+    }
+    
+    let unknown = local
+        .iter()
+        .zip(&used)
+        .filter(|(fill, used)| !**used && in_range(fill.timestamp))
+        .map(|(fill, _)| (*fill).clone())
+        .collect();
+    
+    ReconciliationReport {
+        since,
+        until,
+        matched,
+        missing,
+        unknown,
+    }
+}
+
+fn reconciled_entry(trade: &Trade) -> JournalEntry {
+    JournalEntry {
+        timestamp: trade.timestamp,
+        strategy: UNKNOWN_STRATEGY.to_string(),
+        symbol: trade.symbol.clone(),
+        side: trade.side.clone(),
+        quantity: trade.quantity,
+        price: trade.price,
+        fee: trade.fee,
+        realized_pnl: trade.realized_pnl,
+        order_id: trade.order_id.clone(),
+        cloid: trade.cloid.clone(),
+        dry_run: false,
+        reconciled: true,
+    }
+}
@@ -0,0 +1,388 @@
+use crate::{
+    config::RecorderConfig,
+    error::Result,
+    events::{BotEvent, BusEvent, EventSubscriber},
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, SyncSender, TrySendError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::{debug, error, info, warn};
+
+const FILE_EXTENSION: &str = "ndjson.gz";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MarketStream {
+    Ticker,
+    Candle,
+    Book,
+    Fill,
+}
+
+/// One observation as the bot saw it. `data` holds the parsed payload for
+/// the stream, so the recorder doesn't need to know every message shape.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedEvent {
+    pub timestamp: DateTime<Utc>,
+    pub symbol: String,
+    pub stream: MarketStream,
+    pub data: serde_json::Value,
+}
+
+impl RecordedEvent {
+    pub fn new<T: Serialize>(symbol: &str, stream: MarketStream, data: &T) -> Result<Self> {
+        Ok(Self {
+            timestamp: Utc::now(),
+            symbol: symbol.to_string(),
+            stream,
+            data: serde_json::to_value(data)?,
+        })
+    }
+}
+
+enum RecorderMessage {
+    Event(RecordedEvent),
+    Flush(mpsc::Sender<()>),
+}
+
+/// Writes market events to gzip'd newline-JSON files partitioned as
+/// `<directory>/<YYYY-MM-DD>/<SYMBOL>.ndjson.gz`. All file IO happens on a
+/// dedicated thread behind a bounded queue; when the queue is full events
+/// are dropped and counted instead of slowing the caller.
+#[derive(Clone)]
+pub struct MarketRecorder {
+    tx: SyncSender<RecorderMessage>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl MarketRecorder {
+    pub fn new(config: RecorderConfig) -> Result<Self> {
+        fs::create_dir_all(&config.directory)?;
+        
+        let (tx, rx) = mpsc::sync_channel(config.queue_size.max(1));
+        let mut writer = PartitionWriter::new(&config);
+        thread::Builder::new()
+            .name("market-recorder".to_string())
+            .spawn(move || writer.run(rx))?;
+        
+        info!("🎥 Recording market data to {}", config.directory);
+        Ok(Self {
+            tx,
+            dropped: Arc::new(AtomicU64::new(0)),
+        })
+    }
+    
+    pub fn from_config(config: &RecorderConfig) -> Result<Option<Self>> {
+        if !config.enabled {
+            return Ok(None);
+        }
+        Self::new(config.clone()).map(Some)
+    }
+    
+    pub fn record(&self, event: RecordedEvent) {
+        match self.tx.try_send(RecorderMessage::Event(event)) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => {
+                let dropped = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+                if dropped == 1 || dropped.is_multiple_of(1000) {
+                    warn!("Market recorder queue full, {} events dropped so far", dropped);
+                }
+            }
+            Err(TrySendError::Disconnected(_)) => {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+    
+    pub fn dropped_count(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+    
+    /// Waits until everything queued so far is compressed and on disk
+    pub async fn flush(&self) {
+        let (done_tx, done_rx) = mpsc::channel();
+        let tx = self.tx.clone();
+        let _ = tokio::task::spawn_blocking(move || {
+            if tx.send(RecorderMessage::Flush(done_tx)).is_ok() {
+                let _ = done_rx.recv();
+            }
+        })
+        .await;
+    }
+}
+
+/// Records the market data strategies ran against, backfilled bars at
+/// their own time, and fills
+impl EventSubscriber for MarketRecorder {
+    fn name(&self) -> &str {
+        "market recorder"
+    }
+    
+    fn handle(&mut self, event: &BusEvent) {
+        let (symbol, recorded) = match &event.event {
+            BotEvent::MarketData(data) => (&data.symbol, RecordedEvent::new(&data.symbol, MarketStream::Ticker, data)),
+            BotEvent::CandleClosed { bar, .. } => (
+                &bar.symbol,
+                RecordedEvent::new(&bar.symbol, MarketStream::Ticker, bar).map(|recorded| RecordedEvent {
+                    timestamp: bar.timestamp,
+                    ..recorded
+                }),
+            ),
+            BotEvent::OrderFilled(fill) => (&fill.symbol, RecordedEvent::new(&fill.symbol, MarketStream::Fill, fill)),
+            _ => return,
+        };
+        
+        match recorded {
+            Ok(recorded) => self.record(recorded),
+            Err(e) => debug!("Failed to encode {} event for recording: {}", symbol, e),
+        }
+    }
+}
+
+struct PartitionWriter {
+    directory: PathBuf,
+    flush_interval: Duration,
+    max_total_bytes: u64,
+    open: HashMap<(NaiveDate, String), GzEncoder<BufWriter<File>>>,
+}
+
+impl PartitionWriter {
+    fn new(config: &RecorderConfig) -> Self {
+        Self {
+            directory: PathBuf::from(&config.directory),
+            flush_interval: Duration::from_secs(config.flush_interval_secs.max(1)),
+            max_total_bytes: config.max_total_mb * 1024 * 1024,
+            open: HashMap::new(),
+        }
+    }
+    
+    fn run(&mut self, rx: mpsc::Receiver<RecorderMessage>) {
+        let mut last_flush = Instant::now();
+        
+        loop {
+            let timeout = self.flush_interval.saturating_sub(last_flush.elapsed());
+            match rx.recv_timeout(timeout) {
+                Ok(RecorderMessage::Event(event)) => {
+                    if let Err(e) = self.write(&event) {
+                        error!("Failed to record {} event for {}: {}", stream_name(event.stream), event.symbol, e);
+                    }
+                }
+                Ok(RecorderMessage::Flush(done)) => {
+                    self.flush_all();
+                    last_flush = Instant::now();
+                    let _ = done.send(());
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => {
+                    self.flush_all();
+                    return;
+                }
+            }
+            
+            if last_flush.elapsed() >= self.flush_interval {
+                self.flush_all();
+                last_flush = Instant::now();
+            }
+        }
+    }
+    
+    fn write(&mut self, event: &RecordedEvent) -> Result<()> {
+        let key = (event.timestamp.date_naive(), event.symbol.clone());
+        if !self.open.contains_key(&key) {
+            let path = partition_path(&self.directory, key.0, &key.1);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let file = OpenOptions::new().create(true).append(true).open(&path)?;
+            self.open.insert(key.clone(), GzEncoder::new(BufWriter::new(file), Compression::default()));
+        }
+        
+        let encoder = self.open.get_mut(&key).expect("partition opened above");
+        serde_json::to_writer(&mut *encoder, event)?;
+        encoder.write_all(b"\n")?;
+        Ok(())
+    }
+    
+    // Closing each partition ends its gzip member, so files on disk are always
+    // complete; the next write to the same partition appends a new member.
+    fn flush_all(&mut self) {
+        if self.open.is_empty() {
+            return;
+        }
+        
+        for ((date, symbol), encoder) in self.open.drain() {
+            let result = encoder.finish().and_then(|mut writer| writer.flush());
+            if let Err(e) = result {
+                error!("Failed to flush recording for {} on {}: {}", symbol, date, e);
+            }
+        }
+        
+        if let Err(e) = enforce_retention(&self.directory, self.max_total_bytes) {
+            error!("Failed to apply recording retention: {}", e);
+        }
+    }
+}
+
+fn stream_name(stream: MarketStream) -> &'static str {
+    match stream {
+        MarketStream::Ticker => "ticker",
+        MarketStream::Candle => "candle",
+        MarketStream::Book => "book",
+        MarketStream::Fill => "fill",
+    }
+}
+
+pub fn partition_path(directory: &Path, date: NaiveDate, symbol: &str) -> PathBuf {
+    directory
+        .join(date.format("%Y-%m-%d").to_string())
+        .join(format!("{}.{}", symbol, FILE_EXTENSION))
+}
+
+/// Recording files grouped by day, oldest day first
+fn partitions_by_date(directory: &Path) -> Result<Vec<(NaiveDate, Vec<PathBuf>)>> {
+    let mut days = Vec::new();
+    if !directory.exists() {
+        return Ok(days);
+    }
+    
+    for entry in fs::read_dir(directory)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let Some(date) = name.to_str().and_then(|n| NaiveDate::parse_from_str(n, "%Y-%m-%d").ok()) else {
+            continue;
+        };
+        
+        let mut files: Vec<PathBuf> = fs::read_dir(entry.path())?
+            .filter_map(|f| f.ok().map(|f| f.path()))
+            .filter(|p| p.to_string_lossy().ends_with(FILE_EXTENSION))
+            .collect();
+        files.sort();
+        days.push((date, files));
+    }
+    
+    days.sort_by_key(|(date, _)| *date);
+    Ok(days)
+}
+
+/// Deletes the oldest day partitions until the recordings fit in
+/// `max_total_bytes`. The current day is never removed.
+pub fn enforce_retention(directory: &Path, max_total_bytes: u64) -> Result<()> {
+    let days = partitions_by_date(directory)?;
+    let size_of = |files: &[PathBuf]| -> u64 {
+        files.iter().filter_map(|f| fs::metadata(f).ok()).map(|m| m.len()).sum()
+    };
+    
+    let mut total: u64 = days.iter().map(|(_, files)| size_of(files)).sum();
+    let today = Utc::now().date_naive();
+    
+    for (date, files) in &days {
+        if total <= max_total_bytes || *date >= today {
+            break;
+        }
+        
+        let size = size_of(files);
+        fs::remove_dir_all(directory.join(date.format("%Y-%m-%d").to_string()))?;
+        total = total.saturating_sub(size);
+        info!("Pruned market recordings for {} ({} bytes)", date, size);
+    }
+    
+    Ok(())
+}
+
+/// Reads recordings back in timestamp order, one day at a time, optionally
+/// restricted to a set of symbols. A truncated trailing record (e.g. from a
+/// crash mid-write) ends that file's stream with a warning.
+pub struct RecordingReader {
+    days: VecDeque<(NaiveDate, Vec<PathBuf>)>,
+    symbols: Option<Vec<String>>,
+    buffered: VecDeque<RecordedEvent>,
+}
+
+impl RecordingReader {
+    pub fn open(directory: impl AsRef<Path>, from: NaiveDate, to: NaiveDate, symbols: Option<Vec<String>>) -> Result<Self> {
+        let days = partitions_by_date(directory.as_ref())?
+            .into_iter()
+            .filter(|(date, _)| *date >= from && *date <= to)
+            .collect();
+        
+        Ok(Self {
+            days,
+            symbols,
+            buffered: VecDeque::new(),
+        })
+    }
+    
+    fn wants(&self, path: &Path) -> bool {
+        let Some(symbols) = &self.symbols else {
+            return true;
+        };
+        let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+        let symbol = file_name.trim_end_matches(FILE_EXTENSION).trim_end_matches('.');
+        symbols.iter().any(|s| s == symbol)
+    }
+    
+    fn load_next_day(&mut self) -> Result<bool> {
+        let Some((_, files)) = self.days.pop_front() else {
+            return Ok(false);
+        };
+        
+        let mut events = Vec::new();
+        for path in files.iter().filter(|p| self.wants(p)) {
+            read_file(path, &mut events)?;
+        }
+        
+        // Stable sort keeps per-file arrival order for identical timestamps
+        events.sort_by_key(|e| e.timestamp);
+        self.buffered.extend(events);
+        Ok(true)
+    }
+}
+
+impl Iterator for RecordingReader {
+    type Item = Result<RecordedEvent>;
+    
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.buffered.is_empty() {
+            match self.load_next_day() {
+                Ok(true) => continue,
+                Ok(false) => return None,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        self.buffered.pop_front().map(Ok)
+    }
+}
+
+fn read_file(path: &Path, events: &mut Vec<RecordedEvent>) -> Result<()> {
+    let reader = BufReader::new(MultiGzDecoder::new(File::open(path)?));
+    
+    for line in reader.lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => {
+                warn!("Stopping read of {} at damaged data: {}", path.display(), e);
+                break;
+            }
+        };
+        if line.is_empty() {
+            continue;
+        }
+        match serde_json::from_str(&line) {
+            Ok(event) => events.push(event),
+            Err(e) => warn!("Skipping unreadable record in {}: {}", path.display(), e),
+        }
+    }
+    
+    Ok(())
+}
@@ -0,0 +1,184 @@
+use crate::{
+    backtest::{BacktestResult, BacktestTrade, StrategyBreakdown},
+    clock::SimulatedClock,
+    config::Config,
+    costs::CostModel,
+    error::Result,
+    market_stream::MarketStream,
+    simulated_exchange::SimulatedExchange,
+    storage::EquitySample,
+    trading_bot::TradingBot,
+};
+use chrono::{DateTime, Duration, Utc};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// Drives the real [`TradingBot`] from a [`MarketStream`] against a
+/// [`SimulatedExchange`], so a recorded session goes through the same
+/// cycle, risk checks and order placement as live trading. The bot's clock
+/// follows the stream, and a cycle runs whenever it has moved on by the
+/// cycle interval, as the live loop does on the wall clock.
+pub struct Replay<S: MarketStream> {
+    bot: TradingBot,
+    exchange: Arc<SimulatedExchange>,
+    clock: SimulatedClock,
+    stream: S,
+    cycle_interval: Duration,
+    /// Strategy names by symbol, to attribute fills
+    strategies: HashMap<String, String>,
+}
+
+impl<S: MarketStream> Replay<S> {
+    pub async fn new(config: Config, stream: S) -> Result<Self> {
+        let exchange = SimulatedExchange::new(config.backtest.initial_balance, CostModel::from_config(&config));
+        Self::with_exchange(config, exchange, stream).await
+    }
+    
+    /// Replays against `exchange`, e.g. one with recorded funding rates
+    pub async fn with_exchange(config: Config, exchange: SimulatedExchange, stream: S) -> Result<Self> {
+        let config = replay_config(config);
+        
+        let mut by_symbol: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for (name, strategy) in &config.strategies {
+            if strategy.enabled {
+                by_symbol.entry(strategy.symbol.clone()).or_default().push(name.clone());
+            }
+        }
+        // Orders don't carry their strategy, so symbols traded by several
+        // strategies are attributed to all of them jointly
+        let strategies = by_symbol
+            .into_iter()
+            .map(|(symbol, mut names)| {
+                names.sort();
+                (symbol, names.join("+"))
+            })
+            .collect();
+        
+        // Starts at the epoch and jumps to the first event's time
+        let clock = SimulatedClock::new(DateTime::<Utc>::UNIX_EPOCH);
+        let exchange = Arc::new(exchange);
+        let bot = TradingBot::with_client(config, exchange.clone(), Arc::new(clock.clone())).await?;
+        Ok(Self {
+            bot,
+            exchange,
+            clock,
+            stream,
+            cycle_interval: Duration::seconds(5),
+            strategies,
+        })
+    }
+    
+    /// Stream time between trading cycles; defaults to the live loop's 5s
+    pub fn with_cycle_interval(mut self, cycle_interval: Duration) -> Self {
+        self.cycle_interval = cycle_interval;
+        self
+    }
+    
+    pub fn exchange(&self) -> &Arc<SimulatedExchange> {
+        &self.exchange
+    }
+    
+    pub async fn run(mut self) -> Result<BacktestResult> {
+        info!("🎬 Replaying recorded market data through the trading bot");
+        
+        let mut last_cycle: Option<DateTime<Utc>> = None;
+        let mut equity_curve = Vec::new();
+        let mut exposed_samples = 0;
+        let (mut events, mut cycles, mut failed_cycles) = (0u64, 0u64, 0u64);
+        
+        while let Some(event) = self.stream.next_event().await? {
+            events += 1;
+            let now = event.timestamp();
+            self.clock.advance_to(now);
+            self.exchange.update(&event);
+            
+            // The first cycle waits for a price on every traded symbol
+            let due = last_cycle.is_none_or(|last| now - last >= self.cycle_interval);
+            if !due || !self.strategies.keys().all(|symbol| self.exchange.has_market_data(symbol)) {
+                continue;
+            }
+            
+            last_cycle = Some(now);
+            cycles += 1;
+            if let Err(e) = self.bot.run_cycle().await {
+                warn!("Replay cycle at {} failed: {}", now, e);
+                failed_cycles += 1;
+            }
+            equity_curve.push(self.exchange.sample());
+            if self.exchange.in_market() {
+                exposed_samples += 1;
+            }
+        }
+        
+        info!("🎬 Replayed {} events in {} cycles ({} failed)", events, cycles, failed_cycles);
+        Ok(self.result(equity_curve, exposed_samples))
+    }
+    
+    fn result(&self, equity_curve: Vec<EquitySample>, exposed_samples: usize) -> BacktestResult {
+        let strategy_of = |symbol: &str| self.strategies.get(symbol).cloned().unwrap_or_else(|| symbol.to_string());
+        let mut strategies: BTreeMap<String, StrategyBreakdown> = BTreeMap::new();
+        let mut symbols: BTreeMap<String, StrategyBreakdown> = BTreeMap::new();
+        
+        let trades: Vec<BacktestTrade> = self
+            .exchange
+            .trades()
+            .into_iter()
+            .map(|trade| BacktestTrade {
+                timestamp: trade.timestamp,
+                strategy: strategy_of(&trade.symbol),
+                symbol: trade.symbol,
+                side: trade.side,
+                quantity: trade.quantity,
+                price: trade.price,
+                fee: trade.fee,
+                slippage: trade.slippage,
+                realized_pnl: trade.realized_pnl,
+            })
+            .collect();
+        for trade in &trades {
+            for breakdown in [
+                strategies.entry(trade.strategy.clone()).or_default(),
+                symbols.entry(trade.symbol.clone()).or_default(),
+            ] {
+                breakdown.trades += 1;
+                breakdown.volume += trade.quantity * trade.price;
+                breakdown.fees += trade.fee;
+                breakdown.slippage += trade.slippage;
+                breakdown.realized_pnl += trade.realized_pnl;
+            }
+        }
+        
+        for (symbol, funding) in self.exchange.funding() {
+            strategies.entry(strategy_of(&symbol)).or_default().funding += funding;
+            symbols.entry(symbol).or_default().funding += funding;
+        }
+        for (symbol, unrealized) in self.exchange.unrealized_pnl() {
+            strategies.entry(strategy_of(&symbol)).or_default().unrealized_pnl += unrealized;
+            symbols.entry(symbol).or_default().unrealized_pnl += unrealized;
+        }
+        
+        BacktestResult {
+            initial_balance: self.exchange.initial_balance(),
+            final_equity: self.exchange.equity(),
+            trades,
+            equity_curve,
+            strategies,
+            symbols,
+            rejected_signals: self.exchange.rejected_orders(),
+            exposed_samples,
+        }
+    }
+}
+
+/// Replays trade the simulated exchange for real, and must not write to the
+/// live bot's journal, database or recordings or send notifications
+fn replay_config(mut config: Config) -> Config {
+    config.trading.dry_run = false;
+    config.journal.enabled = false;
+    config.storage.enabled = false;
+    config.recorder.enabled = false;
+    config.control.enabled = false;
+    config.notifications = Default::default();
+    config
+}
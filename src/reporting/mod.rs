@@ -0,0 +1,235 @@
+#[cfg(feature = "sentry")]
+pub mod sentry_reporter;
+
+#[cfg(feature = "sentry")]
+pub use sentry_reporter::SentryReporter;
+
+use crate::{
+    config::Config,
+    error::{Error, Result},
+    notifications::Severity,
+};
+
+pub use crate::error::ErrorContext;
+use std::panic;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tracing::warn;
+
+static REPORTER: OnceLock<Arc<dyn Reporting>> = OnceLock::new();
+
+const REDACTED: &str = "[REDACTED]";
+// Keys whose values are always secrets, wherever they show up in text
+const SECRET_KEYS: &[&str] = &["private_key", "api_key", "secret", "token", "password"];
+
+/// Destination for crash and critical-error reports. Implementations must
+/// scrub secrets before anything leaves the process.
+pub trait Reporting: Send + Sync {
+    fn capture_error(&self, error: &Error, context: &ErrorContext);
+    
+    fn capture_message(&self, message: &str, severity: Severity, context: &ErrorContext);
+    
+    fn capture_panic(&self, message: &str, location: Option<&str>);
+    
+    fn add_breadcrumb(&self, category: &str, message: &str);
+    
+    /// Blocks until queued reports are sent or the timeout elapses
+    fn flush(&self, timeout: Duration);
+}
+
+/// Used when reporting is disabled or compiled out
+pub struct NoopReporter;
+
+impl Reporting for NoopReporter {
+    fn capture_error(&self, _error: &Error, _context: &ErrorContext) {}
+    
+    fn capture_message(&self, _message: &str, _severity: Severity, _context: &ErrorContext) {}
+    
+    fn capture_panic(&self, _message: &str, _location: Option<&str>) {}
+    
+    fn add_breadcrumb(&self, _category: &str, _message: &str) {}
+    
+    fn flush(&self, _timeout: Duration) {}
+}
+
+/// Removes credentials from text bound for an external service: the
+/// configured secret values themselves, anything that looks like a raw
+/// 32-byte hex key, and values following well-known secret key names.
+#[derive(Debug, Clone, Default)]
+pub struct Scrubber {
+    secrets: Vec<String>,
+}
+
+impl Scrubber {
+    pub fn new(secrets: Vec<String>) -> Self {
+        // Very short values would redact unrelated text
+        let secrets = secrets.into_iter().filter(|s| s.len() >= 8).collect();
+        Self { secrets }
+    }
+    
+    pub fn from_config(config: &Config) -> Self {
+        let mut secrets = vec![config.hyperliquid.api_key.clone(), config.hyperliquid.private_key.clone()];
+        if let Some(telegram) = &config.notifications.telegram {
+            secrets.push(telegram.bot_token.clone());
+        }
+        if let Some(discord) = &config.notifications.discord {
+            secrets.push(discord.webhook_url.clone());
+        }
+        Self::new(secrets)
+    }
+    
+    pub fn scrub(&self, text: &str) -> String {
+        let mut scrubbed = text.to_string();
+        for secret in &self.secrets {
+            scrubbed = scrubbed.replace(secret.as_str(), REDACTED);
+        }
+        
+        let scrubbed = redact_hex_keys(&scrubbed);
+        redact_secret_values(&scrubbed)
+    }
+}
+
+// Replaces runs of 64 or more hex digits (optionally 0x-prefixed)
+fn redact_hex_keys(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut result = String::with_capacity(text.len());
+    let mut i = 0;
+    
+    while i < chars.len() {
+        let prefixed = chars[i] == '0' && matches!(chars.get(i + 1), Some('x' | 'X'));
+        let start = if prefixed { i + 2 } else { i };
+        let run = chars[start..].iter().take_while(|c| c.is_ascii_hexdigit()).count();
+        
+        if run >= 64 {
+            result.push_str(REDACTED);
+            i = start + run;
+        } else if run == 0 {
+            result.push(chars[i]);
+            i += 1;
+        } else {
+            result.extend(&chars[i..start + run]);
+            i = start + run;
+        }
+    }
+    
+    result
+}
+
+// Redacts the value after `key=`, `key: ` or `"key":` for secret-looking keys
+fn redact_secret_values(text: &str) -> String {
+    // ASCII lowercasing keeps byte offsets identical to `text`
+    let lower = text.to_ascii_lowercase();
+    let mut result = String::with_capacity(text.len());
+    let mut cursor = 0;
+    
+    while cursor < text.len() {
+        let next = SECRET_KEYS
+            .iter()
+            .filter_map(|key| lower[cursor..].find(key).map(|pos| (cursor + pos, key.len())))
+            .min_by_key(|(pos, _)| *pos);
+        let Some((pos, key_len)) = next else {
+            break;
+        };
+        
+        let after_key = pos + key_len;
+        let rest = &text[after_key..];
+        let separator_len = rest
+            .char_indices()
+            .find(|(_, c)| !matches!(c, '"' | '\'' | ':' | '=' | ' '))
+            .map(|(i, _)| i)
+            .unwrap_or(rest.len());
+        let has_separator = rest[..separator_len].contains(':') || rest[..separator_len].contains('=');
+        
+        if !has_separator {
+            result.push_str(&text[cursor..after_key]);
+            cursor = after_key;
+            continue;
+        }
+        
+        let value_start = after_key + separator_len;
+        let value_len = text[value_start..]
+            .find(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | ',' | '&' | '}' | ';'))
+            .unwrap_or(text.len() - value_start);
+        
+        result.push_str(&text[cursor..value_start]);
+        if value_len > 0 {
+            result.push_str(REDACTED);
+        }
+        cursor = value_start + value_len;
+    }
+    
+    result.push_str(&text[cursor..]);
+    result
+}
+
+/// Installs the configured reporter process-wide, plus a panic hook that
+/// reports before the default hook runs. Safe to call once; later calls are
+/// ignored.
+pub fn init(config: &Config) -> Result<()> {
+    if REPORTER.set(build_reporter(config)?).is_err() {
+        warn!("Error reporting already initialized");
+        return Ok(());
+    }
+    
+    let previous_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |panic_info| {
+        let message = panic_info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| panic_info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "panic with non-string payload".to_string());
+        let location = panic_info.location().map(|l| format!("{}:{}", l.file(), l.line()));
+        
+        let reporter = reporter();
+        reporter.capture_panic(&message, location.as_deref());
+        reporter.flush(Duration::from_secs(2));
+        
+        previous_hook(panic_info);
+    }));
+    
+    Ok(())
+}
+
+#[cfg(feature = "sentry")]
+fn build_reporter(config: &Config) -> Result<Arc<dyn Reporting>> {
+    if !config.reporting.enabled {
+        return Ok(Arc::new(NoopReporter));
+    }
+    
+    let reporter = SentryReporter::new(&config.reporting, Scrubber::from_config(config))?;
+    tracing::info!("🩺 Error reporting enabled ({})", config.reporting.environment);
+    Ok(Arc::new(reporter))
+}
+
+#[cfg(not(feature = "sentry"))]
+fn build_reporter(config: &Config) -> Result<Arc<dyn Reporting>> {
+    if config.reporting.enabled {
+        warn!("Error reporting is enabled in config but this build lacks the `sentry` feature");
+    }
+    Ok(Arc::new(NoopReporter))
+}
+
+pub fn reporter() -> Arc<dyn Reporting> {
+    match REPORTER.get() {
+        Some(reporter) => reporter.clone(),
+        None => Arc::new(NoopReporter),
+    }
+}
+
+/// Reports `error` with `context` and whatever context the error carries
+pub fn report_error(error: &Error, context: &ErrorContext) {
+    reporter().capture_error(error, &error.context().or(context.clone()));
+}
+
+pub fn report_kill_switch(reason: &str) {
+    reporter().capture_message(&format!("Kill switch engaged: {}", reason), Severity::Critical, &ErrorContext::new());
+}
+
+pub fn add_breadcrumb(category: &str, message: &str) {
+    reporter().add_breadcrumb(category, message);
+}
+
+pub fn flush(timeout: Duration) {
+    reporter().flush(timeout);
+}
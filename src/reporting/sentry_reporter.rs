@@ -0,0 +1,129 @@
+use super::{ErrorContext, Reporting, Scrubber};
+use crate::{
+    config::ReportingConfig,
+    error::{Error, Result},
+    notifications::Severity,
+};
+use sentry::protocol::{Breadcrumb, Event, Level};
+use sentry::{ClientInitGuard, ClientOptions};
+use std::sync::Arc;
+use std::time::Duration;
+
+pub struct SentryReporter {
+    guard: ClientInitGuard,
+}
+
+impl SentryReporter {
+    pub fn new(config: &ReportingConfig, scrubber: Scrubber) -> Result<Self> {
+        let dsn = config
+            .dsn
+            .parse()
+            .map_err(|e| Error::Config(format!("Invalid Sentry DSN: {}", e)))?;
+        
+        let options = ClientOptions {
+            dsn: Some(dsn),
+            release: sentry::release_name!(),
+            environment: Some(config.environment.clone().into()),
+            ..Default::default()
+        };
+        
+        Ok(Self::with_options(options, scrubber))
+    }
+    
+    /// Builds a reporter from explicit client options, e.g. with a custom
+    /// transport. Scrubbing hooks are always installed on top.
+    pub fn with_options(mut options: ClientOptions, scrubber: Scrubber) -> Self {
+        let scrubber = Arc::new(scrubber);
+        
+        let breadcrumb_scrubber = scrubber.clone();
+        options.before_breadcrumb = Some(Arc::new(move |breadcrumb| {
+            Some(scrub_breadcrumb(&breadcrumb_scrubber, breadcrumb))
+        }));
+        
+        let event_scrubber = scrubber;
+        options.before_send = Some(Arc::new(move |event| Some(scrub_event(&event_scrubber, event))));
+        
+        Self {
+            guard: sentry::init(options),
+        }
+    }
+}
+
+fn level(severity: Severity) -> Level {
+    match severity {
+        Severity::Info => Level::Info,
+        Severity::Warning => Level::Warning,
+        Severity::Critical => Level::Fatal,
+    }
+}
+
+fn scrub_breadcrumb(scrubber: &Scrubber, mut breadcrumb: Breadcrumb) -> Breadcrumb {
+    breadcrumb.message = breadcrumb.message.map(|m| scrubber.scrub(&m));
+    for value in breadcrumb.data.values_mut() {
+        if let Some(text) = value.as_str() {
+            *value = scrubber.scrub(text).into();
+        }
+    }
+    breadcrumb
+}
+
+fn scrub_event(scrubber: &Scrubber, mut event: Event<'static>) -> Event<'static> {
+    event.message = event.message.map(|m| scrubber.scrub(&m));
+    for exception in event.exception.values.iter_mut() {
+        exception.value = exception.value.as_ref().map(|v| scrubber.scrub(v));
+    }
+    for breadcrumb in event.breadcrumbs.values.iter_mut() {
+        *breadcrumb = scrub_breadcrumb(scrubber, breadcrumb.clone());
+    }
+    for value in event.extra.values_mut() {
+        if let Some(text) = value.as_str() {
+            *value = scrubber.scrub(text).into();
+        }
+    }
+    event
+}
+
+fn capture_with_context(message: &str, level: Level, context: &ErrorContext) {
+    sentry::with_scope(
+        |scope| {
+            for (key, value) in context.tags() {
+                scope.set_tag(key, value);
+            }
+        },
+        || sentry::capture_message(message, level),
+    );
+}
+
+impl Reporting for SentryReporter {
+    fn capture_error(&self, error: &Error, context: &ErrorContext) {
+        capture_with_context(&error.to_string(), Level::Error, context);
+    }
+    
+    fn capture_message(&self, message: &str, severity: Severity, context: &ErrorContext) {
+        capture_with_context(message, level(severity), context);
+    }
+    
+    fn capture_panic(&self, message: &str, location: Option<&str>) {
+        sentry::with_scope(
+            |scope| {
+                scope.set_tag("panic", "true");
+                if let Some(location) = location {
+                    scope.set_extra("location", location.into());
+                }
+            },
+            || sentry::capture_message(&format!("panic: {}", message), Level::Fatal),
+        );
+    }
+    
+    fn add_breadcrumb(&self, category: &str, message: &str) {
+        sentry::add_breadcrumb(Breadcrumb {
+            category: Some(category.to_string()),
+            message: Some(message.to_string()),
+            ..Default::default()
+        });
+    }
+    
+    fn flush(&self, timeout: Duration) {
+        self.guard.flush(Some(timeout));
+    }
+}
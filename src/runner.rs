@@ -0,0 +1,107 @@
+use crate::{
+    error::Result,
+    reporting::{self, ErrorContext},
+};
+use std::future::Future;
+use tokio::sync::watch;
+use tokio::time::{self, Duration, Instant, MissedTickBehavior};
+use tracing::{error, info};
+
+/// How long the trading loop keeps going
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunMode {
+    /// Until shutdown is requested
+    Continuous,
+    /// A single cycle, then stop
+    Once,
+    /// Until shutdown is requested or the duration has elapsed
+    For(Duration),
+}
+
+/// Trading cycles run by one call to [`run_cycles`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RunSummary {
+    pub cycles: u64,
+    pub failed_cycles: u64,
+}
+
+pub(crate) async fn deadline(at: Option<Instant>) {
+    match at {
+        Some(at) => time::sleep_until(at).await,
+        None => std::future::pending().await,
+    }
+}
+
+pub(crate) async fn shutdown_requested(shutdown: &mut watch::Receiver<bool>) {
+    // A dropped sender can never un-request shutdown, so treat it as one
+    while !*shutdown.borrow_and_update() {
+        if shutdown.changed().await.is_err() {
+            return;
+        }
+    }
+}
+
+/// Runs `cycle` every `period` until `mode` says to stop or `shutdown` turns
+/// true. A cycle that's under way always finishes; waits between cycles,
+/// including the `retry_delay` after a failed one (or the error's
+/// [`Error::retry_after`](crate::error::Error::retry_after), if longer), end
+/// as soon as shutdown is requested or the run time is up.
+pub async fn run_cycles<F, Fut>(
+    mode: RunMode,
+    period: Duration,
+    retry_delay: Duration,
+    mut shutdown: watch::Receiver<bool>,
+    mut cycle: F,
+) -> RunSummary
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<()>>,
+{
+    let mut summary = RunSummary::default();
+    let stop_at = match mode {
+        RunMode::For(duration) => Some(Instant::now() + duration),
+        _ => None,
+    };
+    let mut interval = time::interval(period);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    
+    loop {
+        tokio::select! {
+            biased;
+            _ = shutdown_requested(&mut shutdown) => break,
+            _ = deadline(stop_at) => {
+                info!("⏱️ Run time elapsed");
+                break;
+            }
+            _ = interval.tick() => {}
+        }
+        
+        summary.cycles += 1;
+        let result = cycle().await;
+        
+        if let Err(e) = &result {
+            summary.failed_cycles += 1;
+            error!("Error in trading cycle: {}", e);
+            reporting::report_error(e, &ErrorContext::new());
+        }
+        if mode == RunMode::Once {
+            break;
+        }
+        
+        if let Err(e) = &result {
+            // Wait before retrying, longer if the exchange asked us to
+            let retry_delay = e.retry_after().map_or(retry_delay, |wait| wait.max(retry_delay));
+            tokio::select! {
+                biased;
+                _ = shutdown_requested(&mut shutdown) => break,
+                _ = deadline(stop_at) => {
+                    info!("⏱️ Run time elapsed");
+                    break;
+                }
+                _ = time::sleep(retry_delay) => {}
+            }
+        }
+    }
+    
+    summary
+}
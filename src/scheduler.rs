@@ -0,0 +1,151 @@
+use crate::config::SchedulerConfig;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::Notify;
+
+/// When one strategy was last evaluated and whether it's been asked for
+/// another evaluation since
+#[derive(Debug, Clone, Default)]
+struct Schedule {
+    min_interval: Duration,
+    last_evaluated: Option<DateTime<Utc>>,
+    /// The mid moves are measured from: the first seen since the last
+    /// evaluation
+    mid: Option<Decimal>,
+    /// A candle it analyzes closed or its mid moved far enough
+    triggered: bool,
+}
+
+impl Schedule {
+    /// When the strategy can next be evaluated, `None` if it never has been
+    fn allowed_at(&self) -> Option<DateTime<Utc>> {
+        self.last_evaluated.map(|at| at + chrono_duration(self.min_interval))
+    }
+    
+    /// When the strategy is next due, `None` if it never has been evaluated
+    fn due_at(&self, idle: Duration) -> Option<DateTime<Utc>> {
+        let wait = if self.triggered { self.min_interval } else { self.min_interval.max(idle) };
+        self.last_evaluated.map(|at| at + chrono_duration(wait))
+    }
+}
+
+fn chrono_duration(duration: Duration) -> chrono::Duration {
+    chrono::Duration::from_std(duration).unwrap_or_else(|_| chrono::Duration::days(365))
+}
+
+/// Decides which strategies the trading loop evaluates and when. A
+/// strategy is due once a candle it analyzes has closed or its symbol's mid
+/// has moved `mid_move_bps` since its last evaluation, and anyway once it
+/// has gone `idle_eval_secs` without; never sooner than its minimum
+/// interval after the last.
+pub struct EvalScheduler {
+    idle: Duration,
+    /// 0 ignores mids
+    mid_move_bps: Decimal,
+    schedules: Mutex<BTreeMap<String, Schedule>>,
+    /// Wakes whoever waits on [`EvalScheduler::changed`]
+    changed: Notify,
+}
+
+impl EvalScheduler {
+    pub fn new(config: &SchedulerConfig) -> Self {
+        Self {
+            idle: Duration::from_secs(config.idle_eval_secs),
+            mid_move_bps: config.mid_move_bps,
+            schedules: Mutex::new(BTreeMap::new()),
+            changed: Notify::new(),
+        }
+    }
+    
+    /// Schedules strategy `name`, evaluating it at most once every
+    /// `min_interval`
+    pub fn add(&self, name: &str, min_interval: Duration) {
+        self.schedules.lock().unwrap().entry(name.to_string()).or_default().min_interval = min_interval;
+    }
+    
+    /// Asks for strategy `name` to be evaluated as soon as its minimum
+    /// interval allows, e.g. because a candle it analyzes closed
+    pub fn trigger(&self, name: &str) {
+        if let Some(schedule) = self.schedules.lock().unwrap().get_mut(name) {
+            schedule.triggered = true;
+        }
+        self.changed.notify_one();
+    }
+    
+    /// Notes `mid` on strategy `name`'s symbol, triggering it if that's
+    /// moved far enough since its last evaluation. Returns whether it did.
+    pub fn mid(&self, name: &str, mid: Decimal) -> bool {
+        let mut schedules = self.schedules.lock().unwrap();
+        let Some(schedule) = schedules.get_mut(name) else {
+            return false;
+        };
+        let reference = *schedule.mid.get_or_insert(mid);
+        if self.mid_move_bps.is_zero() || reference.is_zero() || schedule.triggered {
+            return false;
+        }
+        
+        let moved_bps = ((mid - reference) / reference).abs() * Decimal::from(10_000);
+        if moved_bps < self.mid_move_bps {
+            return false;
+        }
+        schedule.triggered = true;
+        drop(schedules);
+        self.changed.notify_one();
+        true
+    }
+    
+    /// Takes the strategies due at `now`, counting them as evaluated then
+    pub fn due(&self, now: DateTime<Utc>) -> Vec<String> {
+        self.take(now, |schedule| schedule.due_at(self.idle))
+    }
+    
+    /// Takes the strategies whose minimum interval has passed at `now`,
+    /// triggered or not, counting them as evaluated then. What a cycle run
+    /// outside the loop evaluates.
+    pub fn allowed(&self, now: DateTime<Utc>) -> Vec<String> {
+        self.take(now, Schedule::allowed_at)
+    }
+    
+    fn take(&self, now: DateTime<Utc>, ready_at: impl Fn(&Schedule) -> Option<DateTime<Utc>>) -> Vec<String> {
+        let mut schedules = self.schedules.lock().unwrap();
+        let mut taken = Vec::new();
+        for (name, schedule) in schedules.iter_mut() {
+            if ready_at(schedule).is_none_or(|at| at <= now) {
+                schedule.last_evaluated = Some(now);
+                schedule.mid = None;
+                schedule.triggered = false;
+                taken.push(name.clone());
+            }
+        }
+        taken
+    }
+    
+    /// When the next strategy falls due, `None` if none is scheduled
+    pub fn next_due(&self) -> Option<DateTime<Utc>> {
+        let schedules = self.schedules.lock().unwrap();
+        let mut next: Option<DateTime<Utc>> = None;
+        for schedule in schedules.values() {
+            // A strategy never evaluated is due straight away
+            let Some(at) = schedule.due_at(self.idle) else {
+                return Some(DateTime::<Utc>::MIN_UTC);
+            };
+            next = Some(next.map_or(at, |next| next.min(at)));
+        }
+        next
+    }
+    
+    /// Resolves once a strategy is triggered or [`EvalScheduler::wake`] is
+    /// called, straight away if either happened since it last resolved
+    pub async fn changed(&self) {
+        self.changed.notified().await
+    }
+    
+    /// Wakes whoever waits on [`EvalScheduler::changed`], e.g. once the
+    /// account has been checked and strategies can be evaluated
+    pub fn wake(&self) {
+        self.changed.notify_one();
+    }
+}
@@ -0,0 +1,111 @@
+use crate::error::Result;
+use futures_util::future::BoxFuture;
+use std::time::Duration;
+use tokio::time::{self, Instant};
+use tracing::{error, info, warn};
+
+/// Waits for SIGINT (Ctrl-C) or, on unix, SIGTERM as sent by systemd and
+/// Kubernetes. Handlers are installed when this is created, so a signal that
+/// arrives before `recv` is awaited isn't lost.
+pub struct ShutdownSignal {
+    #[cfg(unix)]
+    terminate: tokio::signal::unix::Signal,
+    #[cfg(unix)]
+    interrupt: tokio::signal::unix::Signal,
+}
+
+impl ShutdownSignal {
+    pub fn new() -> Result<Self> {
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{signal, SignalKind};
+            Ok(Self {
+                terminate: signal(SignalKind::terminate())?,
+                interrupt: signal(SignalKind::interrupt())?,
+            })
+        }
+        #[cfg(not(unix))]
+        {
+            Ok(Self {})
+        }
+    }
+    
+    /// Resolves with the name of the first signal received
+    pub async fn recv(&mut self) -> &'static str {
+        #[cfg(unix)]
+        {
+            tokio::select! {
+                _ = self.terminate.recv() => "SIGTERM",
+                _ = self.interrupt.recv() => "SIGINT",
+            }
+        }
+        #[cfg(not(unix))]
+        {
+            match tokio::signal::ctrl_c().await {
+                Ok(()) => "Ctrl-C",
+                Err(e) => {
+                    error!("Unable to listen for shutdown signal: {}", e);
+                    std::future::pending().await
+                }
+            }
+        }
+    }
+}
+
+/// One named piece of the graceful stop
+pub struct ShutdownStep<'a> {
+    pub name: &'static str,
+    pub task: BoxFuture<'a, Result<()>>,
+}
+
+impl<'a> ShutdownStep<'a> {
+    pub fn new(name: &'static str, task: BoxFuture<'a, Result<()>>) -> Self {
+        Self { name, task }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShutdownReport {
+    pub completed: Vec<&'static str>,
+    pub failed: Vec<(&'static str, String)>,
+    /// Steps cut off by the deadline, or never started because of it
+    pub incomplete: Vec<&'static str>,
+}
+
+impl ShutdownReport {
+    pub fn is_clean(&self) -> bool {
+        self.failed.is_empty() && self.incomplete.is_empty()
+    }
+}
+
+/// Runs `steps` one after another. A failed step is logged and the rest
+/// still run; once `deadline` has passed, the step in progress is dropped
+/// and it and everything after it are reported as incomplete.
+pub async fn run_steps(steps: Vec<ShutdownStep<'_>>, deadline: Duration) -> ShutdownReport {
+    let deadline = Instant::now() + deadline;
+    let mut report = ShutdownReport::default();
+    let mut steps = steps.into_iter();
+    
+    for step in steps.by_ref() {
+        match time::timeout_at(deadline, step.task).await {
+            Ok(Ok(())) => {
+                info!("✅ Shutdown: {}", step.name);
+                report.completed.push(step.name);
+            }
+            Ok(Err(e)) => {
+                error!("Shutdown step '{}' failed: {}", step.name, e);
+                report.failed.push((step.name, e.to_string()));
+            }
+            Err(_) => {
+                report.incomplete.push(step.name);
+                break;
+            }
+        }
+    }
+    
+    report.incomplete.extend(steps.map(|step| step.name));
+    if !report.incomplete.is_empty() {
+        warn!("⏰ Shutdown deadline passed; not completed: {}", report.incomplete.join(", "));
+    }
+    report
+}
@@ -0,0 +1,512 @@
+use crate::{
+    api::client::TradingClient,
+    backtest::SimPosition,
+    costs::{CostModel, Liquidity, MarketConditions},
+    error::{Error, Result},
+    market_stream::MarketEvent,
+    models::{AccountInfo, MarketData, Order, OrderSide, OrderStatus, OrderType, Position, PositionSide, Trade},
+    storage::EquitySample,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Mutex, MutexGuard};
+use tracing::debug;
+
+/// A fill on the simulated exchange
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulatedTrade {
+    pub order_id: String,
+    pub timestamp: DateTime<Utc>,
+    pub symbol: String,
+    pub side: OrderSide,
+    pub quantity: Decimal,
+    /// Fill price after slippage
+    pub price: Decimal,
+    pub fee: Decimal,
+    pub slippage: Decimal,
+    pub liquidity: Liquidity,
+    pub realized_pnl: Decimal,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Market {
+    data: Option<MarketData>,
+    best_bid: Option<Decimal>,
+    best_ask: Option<Decimal>,
+}
+
+impl Market {
+    fn conditions(&self) -> MarketConditions {
+        let mut conditions = self.data.as_ref().map(MarketConditions::from).unwrap_or_default();
+        conditions.best_bid = self.best_bid;
+        conditions.best_ask = self.best_ask;
+        conditions
+    }
+}
+
+struct ExchangeState {
+    initial_balance: Decimal,
+    /// Timestamp of the latest market event
+    now: Option<DateTime<Utc>>,
+    cash: Decimal,
+    positions: BTreeMap<String, SimPosition>,
+    markets: HashMap<String, Market>,
+    /// Limit orders waiting for the price to reach them
+    open_orders: Vec<Order>,
+    trades: Vec<SimulatedTrade>,
+    /// Funding paid per symbol, negative when received
+    funding: BTreeMap<String, Decimal>,
+    funded_until: Option<DateTime<Utc>>,
+    next_order_id: u64,
+    rejected_orders: u64,
+}
+
+/// Everything about a simulated account, for saving it and picking up
+/// where it left off
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExchangeSnapshot {
+    pub initial_balance: Decimal,
+    now: Option<DateTime<Utc>>,
+    /// Latest prices, so positions are marked as they were
+    prices: BTreeMap<String, MarketData>,
+    cash: Decimal,
+    positions: BTreeMap<String, SimPosition>,
+    open_orders: Vec<Order>,
+    trades: Vec<SimulatedTrade>,
+    funding: BTreeMap<String, Decimal>,
+    funded_until: Option<DateTime<Utc>>,
+    next_order_id: u64,
+    rejected_orders: u64,
+}
+
+impl ExchangeSnapshot {
+    pub fn open_orders(&self) -> &[Order] {
+        &self.open_orders
+    }
+}
+
+/// An in-memory exchange that the real bot can trade against through
+/// [`TradingClient`]. Market and marketable limit orders fill as takers at
+/// the latest price, other limits rest until the price reaches them and
+/// fill as makers; fees, slippage and funding come from the cost model, as
+/// in backtests. Time is whatever the latest market event said it was.
+pub struct SimulatedExchange {
+    costs: CostModel,
+    state: Mutex<ExchangeState>,
+}
+
+impl SimulatedExchange {
+    pub fn new(initial_balance: Decimal, costs: CostModel) -> Self {
+        Self {
+            costs,
+            state: Mutex::new(ExchangeState {
+                initial_balance,
+                now: None,
+                cash: initial_balance,
+                positions: BTreeMap::new(),
+                markets: HashMap::new(),
+                open_orders: Vec::new(),
+                trades: Vec::new(),
+                funding: BTreeMap::new(),
+                funded_until: None,
+                next_order_id: 1,
+                rejected_orders: 0,
+            }),
+        }
+    }
+    
+    /// Picks up a saved account where it left off
+    pub fn restore(snapshot: ExchangeSnapshot, costs: CostModel) -> Self {
+        let exchange = Self::new(snapshot.initial_balance, costs);
+        exchange.load(snapshot);
+        exchange
+    }
+    
+    /// Replaces the whole account with a saved one
+    pub fn load(&self, snapshot: ExchangeSnapshot) {
+        let mut state = self.state();
+        state.initial_balance = snapshot.initial_balance;
+        state.now = snapshot.now;
+        state.markets = snapshot
+            .prices
+            .into_iter()
+            .map(|(symbol, data)| (symbol, Market { data: Some(data), ..Market::default() }))
+            .collect();
+        state.cash = snapshot.cash;
+        state.positions = snapshot.positions;
+        state.open_orders = snapshot.open_orders;
+        state.trades = snapshot.trades;
+        state.funding = snapshot.funding;
+        state.funded_until = snapshot.funded_until;
+        state.next_order_id = snapshot.next_order_id;
+        state.rejected_orders = snapshot.rejected_orders;
+    }
+    
+    pub fn snapshot(&self) -> ExchangeSnapshot {
+        let state = self.state();
+        ExchangeSnapshot {
+            initial_balance: state.initial_balance,
+            now: state.now,
+            prices: state
+                .markets
+                .iter()
+                .filter_map(|(symbol, market)| Some((symbol.clone(), market.data.clone()?)))
+                .collect(),
+            cash: state.cash,
+            positions: state.positions.clone(),
+            open_orders: state.open_orders.clone(),
+            trades: state.trades.clone(),
+            funding: state.funding.clone(),
+            funded_until: state.funded_until,
+            next_order_id: state.next_order_id,
+            rejected_orders: state.rejected_orders,
+        }
+    }
+    
+    fn state(&self) -> MutexGuard<'_, ExchangeState> {
+        // The state stays consistent between statements, so a panic
+        // elsewhere doesn't poison it for good
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+    
+    /// Moves the exchange to `event`: settles funding due before it, then
+    /// updates the market and fills resting orders the new price reaches
+    pub fn update(&self, event: &MarketEvent) {
+        let mut state = self.state();
+        let timestamp = event.timestamp();
+        state.accrue_funding(&self.costs, timestamp);
+        state.now = Some(state.now.map_or(timestamp, |now| now.max(timestamp)));
+        
+        let market = state.markets.entry(event.symbol().to_string()).or_default();
+        match event {
+            MarketEvent::Ticker(data) | MarketEvent::Candle(data) => market.data = Some(data.clone()),
+            MarketEvent::Book(book) => {
+                market.best_bid = book.best_bid;
+                market.best_ask = book.best_ask;
+            }
+        }
+        if let Some(data) = event.market_data() {
+            state.fill_resting(&self.costs, &data.symbol, data.price);
+        }
+    }
+    
+    pub fn now(&self) -> Option<DateTime<Utc>> {
+        self.state().now
+    }
+    
+    pub fn has_market_data(&self, symbol: &str) -> bool {
+        self.state().markets.get(symbol).is_some_and(|market| market.data.is_some())
+    }
+    
+    pub fn equity(&self) -> Decimal {
+        self.state().equity()
+    }
+    
+    /// Whether any position is open
+    pub fn in_market(&self) -> bool {
+        self.state().positions.values().any(|position| !position.quantity.is_zero())
+    }
+    
+    pub fn trades(&self) -> Vec<SimulatedTrade> {
+        self.state().trades.clone()
+    }
+    
+    pub fn funding(&self) -> BTreeMap<String, Decimal> {
+        self.state().funding.clone()
+    }
+    
+    /// Unrealized PnL of open positions by symbol, at the latest prices
+    pub fn unrealized_pnl(&self) -> BTreeMap<String, Decimal> {
+        let state = self.state();
+        state
+            .positions
+            .iter()
+            .filter(|(_, position)| !position.quantity.is_zero())
+            .map(|(symbol, position)| (symbol.clone(), position.quantity * (state.mark(symbol) - position.average_price)))
+            .collect()
+    }
+    
+    /// Orders refused for lack of balance or of anything to reduce
+    pub fn rejected_orders(&self) -> u64 {
+        self.state().rejected_orders
+    }
+    
+    pub fn initial_balance(&self) -> Decimal {
+        self.state().initial_balance
+    }
+    
+    /// The account marked to market as of the latest event
+    pub fn sample(&self) -> EquitySample {
+        let state = self.state();
+        let equity = state.equity();
+        EquitySample {
+            timestamp: state.now.unwrap_or_else(Utc::now),
+            equity,
+            available_balance: state.cash,
+            total_pnl: equity - state.initial_balance,
+        }
+    }
+}
+
+impl ExchangeState {
+    fn mark(&self, symbol: &str) -> Decimal {
+        self.markets
+            .get(symbol)
+            .and_then(|market| market.data.as_ref())
+            .map_or(Decimal::ZERO, |data| data.price)
+    }
+    
+    fn equity(&self) -> Decimal {
+        self.cash
+            + self
+                .positions
+                .iter()
+                .map(|(symbol, position)| position.quantity * self.mark(symbol))
+                .sum::<Decimal>()
+    }
+    
+    fn accrue_funding(&mut self, costs: &CostModel, until: DateTime<Utc>) {
+        let after = match self.funded_until {
+            Some(after) if after < until => after,
+            Some(_) => return,
+            None => {
+                self.funded_until = Some(until);
+                return;
+            }
+        };
+        self.funded_until = Some(until);
+        
+        for at in costs.funding_times(after, until) {
+            for (symbol, position) in &self.positions {
+                if position.quantity.is_zero() {
+                    continue;
+                }
+                let payment = costs.funding_payment(symbol, position.quantity, self.mark(symbol), at);
+                self.cash -= payment;
+                *self.funding.entry(symbol.clone()).or_default() += payment;
+            }
+        }
+    }
+    
+    /// Caps a reduce-only order at the position it closes
+    fn reducible(&self, symbol: &str, side: &OrderSide, quantity: Decimal) -> Decimal {
+        let position = self.positions.get(symbol).map_or(Decimal::ZERO, |position| position.quantity);
+        let closes = match side {
+            OrderSide::Buy => position.is_sign_negative(),
+            OrderSide::Sell => position.is_sign_positive(),
+        };
+        if closes {
+            quantity.min(position.abs())
+        } else {
+            Decimal::ZERO
+        }
+    }
+    
+    fn fill(
+        &mut self,
+        costs: &CostModel,
+        order_id: &str,
+        order: &Order,
+        quantity: Decimal,
+        reference: Decimal,
+        liquidity: Liquidity,
+    ) -> Result<()> {
+        let conditions = self.markets.get(&order.symbol).map(Market::conditions).unwrap_or_default();
+        let fill = costs.fill(&order.side, quantity, reference, liquidity, &conditions);
+        let notional = quantity * fill.price;
+        
+        if matches!(order.side, OrderSide::Buy) && notional + fill.fee > self.cash {
+            return Err(Error::InsufficientBalance);
+        }
+        match order.side {
+            OrderSide::Buy => self.cash -= notional + fill.fee,
+            OrderSide::Sell => self.cash += notional - fill.fee,
+        }
+        
+        let realized_pnl = self
+            .positions
+            .entry(order.symbol.clone())
+            .or_default()
+            .apply(&order.side, quantity, fill.price);
+        self.trades.push(SimulatedTrade {
+            order_id: order_id.to_string(),
+            timestamp: self.now.unwrap_or_else(Utc::now),
+            symbol: order.symbol.clone(),
+            side: order.side.clone(),
+            quantity,
+            price: fill.price,
+            fee: fill.fee,
+            slippage: fill.slippage,
+            liquidity,
+            realized_pnl,
+        });
+        Ok(())
+    }
+    
+    /// Fills resting limit orders on `symbol` that `price` reaches, at their
+    /// limit price
+    fn fill_resting(&mut self, costs: &CostModel, symbol: &str, price: Decimal) {
+        let (reached, resting): (Vec<Order>, Vec<Order>) =
+            std::mem::take(&mut self.open_orders).into_iter().partition(|order| {
+                order.symbol == symbol
+                    && match (&order.side, order.price) {
+                        (OrderSide::Buy, Some(limit)) => price <= limit,
+                        (OrderSide::Sell, Some(limit)) => price >= limit,
+                        (_, None) => true,
+                    }
+            });
+        self.open_orders = resting;
+        
+        for order in reached {
+            let mut quantity = order.quantity - order.filled_quantity;
+            if order.reduce_only {
+                quantity = self.reducible(&order.symbol, &order.side, quantity);
+            }
+            let limit = order.price.unwrap_or(price);
+            if quantity <= Decimal::ZERO {
+                debug!("Simulated exchange: dropping reduce-only order {} with nothing to reduce", order.id);
+                continue;
+            }
+            if let Err(e) = self.fill(costs, &order.id, &order, quantity, limit, Liquidity::Maker) {
+                debug!("Simulated exchange: resting order {} rejected: {}", order.id, e);
+                self.rejected_orders += 1;
+            }
+        }
+    }
+    
+    fn positions(&self) -> Vec<Position> {
+        let timestamp = self.now.unwrap_or_else(Utc::now);
+        self.positions
+            .iter()
+            .filter(|(_, position)| !position.quantity.is_zero())
+            .map(|(symbol, position)| {
+                let mark = self.mark(symbol);
+                Position {
+                    symbol: symbol.clone(),
+                    side: if position.quantity.is_sign_positive() { PositionSide::Long } else { PositionSide::Short },
+                    size: position.quantity.abs(),
+                    entry_price: position.average_price,
+                    current_price: mark,
+                    unrealized_pnl: position.quantity * (mark - position.average_price),
+                    realized_pnl: Decimal::ZERO,
+                    margin: Decimal::ZERO,
+                    timestamp,
+                }
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl TradingClient for SimulatedExchange {
+    async fn get_market_data(&self, symbol: &str) -> Result<MarketData> {
+        self.state()
+            .markets
+            .get(symbol)
+            .and_then(|market| market.data.clone())
+            .ok_or_else(|| Error::Api(format!("No market data for {} yet", symbol)))
+    }
+    
+    async fn get_account_info(&self) -> Result<AccountInfo> {
+        let state = self.state();
+        let equity = state.equity();
+        Ok(AccountInfo {
+            balance: equity,
+            available_balance: state.cash,
+            total_pnl: equity - state.initial_balance,
+            total_margin: Decimal::ZERO,
+            positions: state.positions(),
+            open_orders: state.open_orders.clone(),
+        })
+    }
+    
+    async fn get_positions(&self) -> Result<Vec<Position>> {
+        Ok(self.state().positions())
+    }
+    
+    async fn get_open_orders(&self) -> Result<Vec<Order>> {
+        Ok(self.state().open_orders.clone())
+    }
+    
+    async fn place_order(&self, order: &Order) -> Result<String> {
+        let mut state = self.state();
+        let market = state
+            .markets
+            .get(&order.symbol)
+            .filter(|market| market.data.is_some())
+            .cloned()
+            .ok_or_else(|| Error::Trading(format!("No market data for {} yet", order.symbol)))?;
+        if order.quantity <= Decimal::ZERO {
+            return Err(Error::InvalidInput(format!("Order quantity must be positive, got {}", order.quantity)));
+        }
+        
+        let quantity = if order.reduce_only {
+            state.reducible(&order.symbol, &order.side, order.quantity)
+        } else {
+            order.quantity
+        };
+        if quantity <= Decimal::ZERO {
+            state.rejected_orders += 1;
+            return Err(Error::Trading(format!("Reduce-only order has no {} position to reduce", order.symbol)));
+        }
+        
+        let order_id = format!("sim-{}", state.next_order_id);
+        state.next_order_id += 1;
+        
+        let price = market.data.as_ref().map_or(Decimal::ZERO, |data| data.price);
+        let marketable = match (&order.order_type, &order.side, order.price) {
+            (OrderType::Market, _, _) | (_, _, None) => true,
+            (_, OrderSide::Buy, Some(limit)) => limit >= market.best_ask.unwrap_or(price),
+            (_, OrderSide::Sell, Some(limit)) => limit <= market.best_bid.unwrap_or(price),
+        };
+        
+        if marketable {
+            if let Err(e) = state.fill(&self.costs, &order_id, order, quantity, price, Liquidity::Taker) {
+                state.rejected_orders += 1;
+                return Err(e);
+            }
+        } else {
+            let now = state.now.unwrap_or_else(Utc::now);
+            state.open_orders.push(Order {
+                id: order_id.clone(),
+                quantity,
+                status: OrderStatus::Open,
+                created_at: now,
+                updated_at: Some(now),
+                ..order.clone()
+            });
+        }
+        Ok(order_id)
+    }
+    
+    async fn cancel_order(&self, order_id: &str) -> Result<bool> {
+        let mut state = self.state();
+        let before = state.open_orders.len();
+        state.open_orders.retain(|order| order.id != order_id);
+        Ok(state.open_orders.len() < before)
+    }
+    
+    async fn get_trade_history(&self, symbol: Option<&str>) -> Result<Vec<Trade>> {
+        Ok(self
+            .state()
+            .trades
+            .iter()
+            .filter(|trade| symbol.is_none_or(|symbol| trade.symbol == symbol))
+            .map(|trade| Trade {
+                id: trade.order_id.clone(),
+                order_id: trade.order_id.clone(),
+                cloid: None,
+                symbol: trade.symbol.clone(),
+                side: trade.side.clone(),
+                quantity: trade.quantity,
+                price: trade.price,
+                fee: trade.fee,
+                realized_pnl: trade.realized_pnl,
+                timestamp: trade.timestamp,
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,152 @@
+use crate::{
+    config::Config,
+    error::{Error, Result},
+    models::Order,
+    simulated_exchange::ExchangeSnapshot,
+    storage::{keys, Storage, StrategyState},
+    trade_stats::TradeStats,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Bumped whenever the snapshot layout changes incompatibly
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// Everything a bot keeps between cycles, for moving it to another host or
+/// reproducing a user's issue locally
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub version: u32,
+    pub created_at: DateTime<Utc>,
+    /// Enabled strategies by name, with the symbol each trades
+    pub strategies: BTreeMap<String, String>,
+    pub strategy_states: Vec<StrategyState>,
+    /// Trade counts, equity high-water mark and daily PnL anchor
+    pub trade_stats: Option<TradeStats>,
+    /// Orders resting when the snapshot was taken. They live on the
+    /// exchange, so importing doesn't place them again.
+    pub open_orders: Vec<Order>,
+    /// The dry-run paper account, if the bot was trading one
+    pub paper_account: Option<ExchangeSnapshot>,
+}
+
+impl Snapshot {
+    /// Reads a snapshot from the storage file, as a stopped bot left it
+    pub fn from_storage(storage: &dyn Storage, config: &Config, created_at: DateTime<Utc>) -> Result<Self> {
+        let strategies = enabled_strategies(config);
+        
+        let mut strategy_states = Vec::new();
+        for name in strategies.keys() {
+            if let Some(state) = storage.load_strategy_state(name)? {
+                strategy_states.push(state);
+            }
+        }
+        
+        let trade_stats = match storage.get_kv(keys::TRADE_STATS)? {
+            Some(saved) => Some(serde_json::from_str(&saved)?),
+            None => None,
+        };
+        let paper_account = match storage.get_kv(keys::PAPER_ACCOUNT)? {
+            Some(saved) => Some(serde_json::from_str::<ExchangeSnapshot>(&saved)?),
+            None => None,
+        };
+        
+        Ok(Self {
+            version: SNAPSHOT_VERSION,
+            created_at,
+            strategies,
+            strategy_states,
+            trade_stats,
+            open_orders: paper_account.as_ref().map(|a| a.open_orders().to_vec()).unwrap_or_default(),
+            paper_account,
+        })
+    }
+    
+    /// Parses a snapshot, rejecting other versions before looking at the
+    /// rest so a layout change gives a clear error
+    pub fn from_json(json: &str) -> Result<Self> {
+        let value: serde_json::Value = serde_json::from_str(json)?;
+        let version = value.get("version").and_then(|v| v.as_u64());
+        if version != Some(SNAPSHOT_VERSION as u64) {
+            return Err(Error::InvalidInput(format!(
+                "Snapshot version {} is not supported (expected {})",
+                version.map_or_else(|| "missing".to_string(), |v| v.to_string()),
+                SNAPSHOT_VERSION
+            )));
+        }
+        
+        Ok(serde_json::from_value(value)?)
+    }
+    
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+    
+    /// Checks the snapshot can be applied to a bot running `config`: same
+    /// version, and the same strategies trading the same symbols
+    pub fn check_compatible(&self, config: &Config) -> Result<()> {
+        if self.version != SNAPSHOT_VERSION {
+            return Err(Error::InvalidInput(format!(
+                "Snapshot version {} is not supported (expected {})",
+                self.version, SNAPSHOT_VERSION
+            )));
+        }
+        
+        let expected = enabled_strategies(config);
+        if self.strategies != expected {
+            return Err(Error::InvalidInput(format!(
+                "Snapshot strategies {} don't match the configured {}",
+                describe(&self.strategies),
+                describe(&expected)
+            )));
+        }
+        
+        if let Some(state) = self.strategy_states.iter().find(|s| !expected.contains_key(&s.name)) {
+            return Err(Error::InvalidInput(format!(
+                "Snapshot has state for unknown strategy {}",
+                state.name
+            )));
+        }
+        
+        Ok(())
+    }
+    
+    /// Writes the snapshot into the storage file for a stopped bot to pick
+    /// up on its next start. Nothing is written unless all of it is.
+    pub fn save_to_storage(&self, storage: &dyn Storage, config: &Config) -> Result<()> {
+        self.check_compatible(config)?;
+        
+        let mut kv = Vec::new();
+        if let Some(trade_stats) = &self.trade_stats {
+            kv.push((keys::TRADE_STATS, serde_json::to_string(trade_stats)?));
+        }
+        if let Some(paper_account) = &self.paper_account {
+            kv.push((keys::PAPER_ACCOUNT, serde_json::to_string(paper_account)?));
+        }
+        
+        storage.save_state_batch(&self.strategy_states, &kv)
+    }
+}
+
+/// The strategies a bot running `config` would start, with their symbols
+pub fn enabled_strategies(config: &Config) -> BTreeMap<String, String> {
+    config
+        .strategies
+        .iter()
+        .filter(|(_, strategy)| strategy.enabled)
+        .map(|(name, strategy)| (name.clone(), strategy.symbol.clone()))
+        .collect()
+}
+
+fn describe(strategies: &BTreeMap<String, String>) -> String {
+    if strategies.is_empty() {
+        return "(none)".to_string();
+    }
+    
+    strategies
+        .iter()
+        .map(|(name, symbol)| format!("{} ({})", name, symbol))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -0,0 +1,228 @@
+pub mod sqlite;
+
+pub use sqlite::SqliteStorage;
+
+use crate::{
+    config::StorageConfig,
+    error::Result,
+    journal::JournalEntry,
+    models::{AccountInfo, Order},
+};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::{mpsc, oneshot};
+use tracing::{error, info, warn};
+
+// Writes queued for the writer task before new ones wait for room
+const WRITE_QUEUE_SIZE: usize = 4096;
+
+/// Well-known keys in the `kv` table
+pub mod keys {
+    pub const EQUITY_HIGH_WATER_MARK: &str = "equity_high_water_mark";
+    pub const DAILY_SNAPSHOT: &str = "daily_snapshot";
+    /// The dry-run paper account, as JSON
+    pub const PAPER_ACCOUNT: &str = "paper_account";
+    /// Trade counts and PnL anchors, as JSON
+    pub const TRADE_STATS: &str = "trade_stats";
+    /// Fills up to this time have been reconciled with the exchange, as JSON
+    pub const RECONCILED_UNTIL: &str = "reconciled_until";
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredOrder {
+    pub strategy: String,
+    pub order: Order,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EquitySample {
+    pub timestamp: DateTime<Utc>,
+    pub equity: Decimal,
+    pub available_balance: Decimal,
+    pub total_pnl: Decimal,
+}
+
+impl EquitySample {
+    /// Marks the account to market: balance plus unrealized PnL. The account
+    /// endpoint doesn't report accrued-but-unsettled fees yet, so none are
+    /// deducted here.
+    pub fn from_account(account_info: &AccountInfo, timestamp: DateTime<Utc>) -> Self {
+        let unrealized_pnl: Decimal = account_info.positions.iter().map(|p| p.unrealized_pnl).sum();
+        
+        Self {
+            timestamp,
+            equity: account_info.balance + unrealized_pnl,
+            available_balance: account_info.available_balance,
+            total_pnl: account_info.total_pnl,
+        }
+    }
+}
+
+/// Opaque, versioned strategy state. Strategies own the encoding; the version
+/// lets them reject or migrate blobs written by an older build.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyState {
+    pub name: String,
+    pub version: u32,
+    pub data: Vec<u8>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Durable store for orders, fills, equity samples, strategy state and small
+/// key/value records. Implementations are synchronous; the bot goes through
+/// [`StorageWriter`] so writes are applied off the trading loop.
+pub trait Storage: Send + Sync {
+    fn record_order(&self, strategy: &str, order: &Order) -> Result<()>;
+    
+    fn record_fill(&self, fill: &JournalEntry) -> Result<()>;
+    
+    fn record_equity(&self, sample: &EquitySample) -> Result<()>;
+    
+    fn save_strategy_state(&self, state: &StrategyState) -> Result<()>;
+    
+    fn load_strategy_state(&self, name: &str) -> Result<Option<StrategyState>>;
+    
+    fn set_kv(&self, key: &str, value: &str) -> Result<()>;
+    
+    fn get_kv(&self, key: &str) -> Result<Option<String>>;
+    
+    /// Saves strategy states and key/value records together: either all of
+    /// them land or, on error, none do
+    fn save_state_batch(&self, states: &[StrategyState], kv: &[(&str, String)]) -> Result<()>;
+    
+    /// Most recent orders first
+    fn recent_orders(&self, limit: usize) -> Result<Vec<StoredOrder>>;
+    
+    /// Most recent fills first
+    fn recent_fills(&self, limit: usize) -> Result<Vec<JournalEntry>>;
+    
+    /// Fills in `[from, to)`, oldest first
+    fn fills_between(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<Vec<JournalEntry>>;
+    
+    /// Equity samples in `[from, to)`, oldest first
+    fn equity_samples(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<Vec<EquitySample>>;
+    
+    /// Collapses samples older than `older_than` to the last one in each
+    /// minute, returning how many rows were removed
+    fn downsample_equity(&self, older_than: DateTime<Utc>) -> Result<usize>;
+}
+
+pub fn open(config: &StorageConfig) -> Result<Option<Arc<dyn Storage>>> {
+    if !config.enabled {
+        return Ok(None);
+    }
+    
+    let storage = SqliteStorage::open(&config.path)?;
+    info!("💾 Storage opened at {}", config.path);
+    Ok(Some(Arc::new(storage)))
+}
+
+pub enum StorageWrite {
+    Order { strategy: String, order: Order },
+    Fill(JournalEntry),
+    Equity(EquitySample),
+    StrategyState(StrategyState),
+    Kv { key: String, value: String },
+    DownsampleEquity { older_than: DateTime<Utc> },
+    Flush(oneshot::Sender<()>),
+}
+
+/// Applies writes on a background task, in the order they were queued.
+#[derive(Clone)]
+pub struct StorageWriter {
+    storage: Arc<dyn Storage>,
+    tx: mpsc::Sender<StorageWrite>,
+    /// Shared by clones
+    dropped: Arc<AtomicU64>,
+}
+
+impl StorageWriter {
+    pub fn spawn(storage: Arc<dyn Storage>) -> Self {
+        let (tx, rx) = mpsc::channel(WRITE_QUEUE_SIZE);
+        tokio::spawn(run_writer(storage.clone(), rx));
+        Self {
+            storage,
+            tx,
+            dropped: Arc::new(AtomicU64::new(0)),
+        }
+    }
+    
+    /// Direct access for reads, which are cheap enough to run inline
+    pub fn storage(&self) -> &Arc<dyn Storage> {
+        &self.storage
+    }
+    
+    /// Queues a write, waiting for room if the writer has fallen behind so
+    /// that nothing is lost
+    pub async fn write(&self, write: StorageWrite) {
+        if self.tx.send(write).await.is_err() {
+            error!("Storage writer has stopped; dropping write");
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+    
+    /// Queues a write without waiting, for callers that can't and writes
+    /// the next one supersedes. If the queue is full the write is dropped,
+    /// logged and counted.
+    pub fn try_write(&self, write: StorageWrite) {
+        if let Err(e) = self.tx.try_send(write) {
+            let dropped = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+            warn!("Dropping storage write ({} dropped so far): {}", dropped, e);
+        }
+    }
+    
+    /// Writes dropped so far, because the queue was full or the writer had
+    /// stopped
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+    
+    /// Waits until every write queued before this call has been applied
+    pub async fn flush(&self) {
+        let (done_tx, done_rx) = oneshot::channel();
+        if self.tx.send(StorageWrite::Flush(done_tx)).await.is_ok() {
+            let _ = done_rx.await;
+        }
+    }
+}
+
+async fn run_writer(storage: Arc<dyn Storage>, mut rx: mpsc::Receiver<StorageWrite>) {
+    while let Some(write) = rx.recv().await {
+        if let StorageWrite::Flush(done) = write {
+            let _ = done.send(());
+            continue;
+        }
+        
+        let storage = storage.clone();
+        let result = tokio::task::spawn_blocking(move || apply(storage.as_ref(), write)).await;
+        match result {
+            Ok(Err(e)) => error!("Storage write failed: {}", e),
+            Err(e) => error!("Storage writer task panicked: {}", e),
+            Ok(Ok(())) => {}
+        }
+    }
+}
+
+fn apply(storage: &dyn Storage, write: StorageWrite) -> Result<()> {
+    match write {
+        StorageWrite::Order { strategy, order } => storage.record_order(&strategy, &order),
+        StorageWrite::Fill(fill) => storage.record_fill(&fill),
+        StorageWrite::Equity(sample) => storage.record_equity(&sample),
+        StorageWrite::StrategyState(state) => storage.save_strategy_state(&state),
+        StorageWrite::Kv { key, value } => storage.set_kv(&key, &value),
+        StorageWrite::DownsampleEquity { older_than } => {
+            let removed = storage.downsample_equity(older_than)?;
+            if removed > 0 {
+                info!("Downsampled equity history, removed {} samples", removed);
+            }
+            Ok(())
+        }
+        StorageWrite::Flush(done) => {
+            let _ = done.send(());
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,386 @@
+use super::{EquitySample, Storage, StoredOrder, StrategyState};
+use crate::{
+    error::Result,
+    journal::JournalEntry,
+    models::{Order, OrderSide},
+};
+use chrono::{DateTime, SecondsFormat, Utc};
+use rusqlite::{params, types::Type, Connection, OptionalExtension, Row};
+use rust_decimal::Decimal;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Mutex;
+use tracing::info;
+
+// Each entry is applied once, in order, and recorded in schema_migrations.
+// Never edit a released migration; append a new one instead.
+const MIGRATIONS: &[&str] = &[
+    "CREATE TABLE orders (
+        id TEXT PRIMARY KEY,
+        strategy TEXT NOT NULL,
+        symbol TEXT NOT NULL,
+        created_at TEXT NOT NULL,
+        updated_at TEXT NOT NULL,
+        payload TEXT NOT NULL
+    );
+    CREATE INDEX idx_orders_created_at ON orders (created_at);
+    
+    CREATE TABLE fills (
+        seq INTEGER PRIMARY KEY AUTOINCREMENT,
+        timestamp TEXT NOT NULL,
+        strategy TEXT NOT NULL,
+        symbol TEXT NOT NULL,
+        side TEXT NOT NULL,
+        quantity TEXT NOT NULL,
+        price TEXT NOT NULL,
+        fee TEXT NOT NULL,
+        realized_pnl TEXT NOT NULL,
+        order_id TEXT NOT NULL,
+        cloid TEXT,
+        dry_run INTEGER NOT NULL
+    );
+    CREATE INDEX idx_fills_order_id ON fills (order_id);
+    
+    CREATE TABLE equity_samples (
+        seq INTEGER PRIMARY KEY AUTOINCREMENT,
+        timestamp TEXT NOT NULL,
+        equity TEXT NOT NULL,
+        available_balance TEXT NOT NULL,
+        total_pnl TEXT NOT NULL
+    );
+    CREATE INDEX idx_equity_samples_timestamp ON equity_samples (timestamp);
+    
+    CREATE TABLE strategy_state (
+        name TEXT PRIMARY KEY,
+        version INTEGER NOT NULL,
+        data BLOB NOT NULL,
+        updated_at TEXT NOT NULL
+    );
+    
+    CREATE TABLE kv (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL,
+        updated_at TEXT NOT NULL
+    );",
+    "ALTER TABLE fills ADD COLUMN reconciled INTEGER NOT NULL DEFAULT 0;
+    CREATE INDEX idx_fills_timestamp ON fills (timestamp);",
+];
+
+pub struct SqliteStorage {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteStorage {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        
+        let conn = Connection::open(path)?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.pragma_update(None, "synchronous", "NORMAL")?;
+        Self::with_connection(conn)
+    }
+    
+    /// Throwaway database, handy for tests and backtests
+    pub fn in_memory() -> Result<Self> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+    
+    fn with_connection(mut conn: Connection) -> Result<Self> {
+        migrate(&mut conn)?;
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+    
+    pub fn schema_version(&self) -> Result<u32> {
+        let conn = self.conn.lock().unwrap();
+        current_version(&conn)
+    }
+}
+
+fn current_version(conn: &Connection) -> Result<u32> {
+    let version: Option<u32> = conn.query_row("SELECT MAX(version) FROM schema_migrations", [], |row| row.get(0))?;
+    Ok(version.unwrap_or(0))
+}
+
+fn migrate(conn: &mut Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS schema_migrations (
+            version INTEGER PRIMARY KEY,
+            applied_at TEXT NOT NULL
+        )",
+        [],
+    )?;
+    
+    let current = current_version(conn)?;
+    for (index, sql) in MIGRATIONS.iter().enumerate() {
+        let version = index as u32 + 1;
+        if version <= current {
+            continue;
+        }
+        
+        let tx = conn.transaction()?;
+        tx.execute_batch(sql)?;
+        tx.execute(
+            "INSERT INTO schema_migrations (version, applied_at) VALUES (?1, ?2)",
+            params![version, format_timestamp(Utc::now())],
+        )?;
+        tx.commit()?;
+        info!("Applied storage migration {}", version);
+    }
+    
+    Ok(())
+}
+
+impl Storage for SqliteStorage {
+    fn record_order(&self, strategy: &str, order: &Order) -> Result<()> {
+        let payload = serde_json::to_string(order)?;
+        let updated_at = order.updated_at.unwrap_or(order.created_at);
+        
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO orders (id, strategy, symbol, created_at, updated_at, payload)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             ON CONFLICT (id) DO UPDATE SET updated_at = excluded.updated_at, payload = excluded.payload",
+            params![
+                order.id,
+                strategy,
+                order.symbol,
+                format_timestamp(order.created_at),
+                format_timestamp(updated_at),
+                payload,
+            ],
+        )?;
+        Ok(())
+    }
+    
+    fn record_fill(&self, fill: &JournalEntry) -> Result<()> {
+        let side = match fill.side {
+            OrderSide::Buy => "BUY",
+            OrderSide::Sell => "SELL",
+        };
+        
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO fills (timestamp, strategy, symbol, side, quantity, price, fee, realized_pnl, order_id, cloid, dry_run, reconciled)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            params![
+                format_timestamp(fill.timestamp),
+                fill.strategy,
+                fill.symbol,
+                side,
+                fill.quantity.to_string(),
+                fill.price.to_string(),
+                fill.fee.to_string(),
+                fill.realized_pnl.to_string(),
+                fill.order_id,
+                fill.cloid,
+                fill.dry_run,
+                fill.reconciled,
+            ],
+        )?;
+        Ok(())
+    }
+    
+    fn record_equity(&self, sample: &EquitySample) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO equity_samples (timestamp, equity, available_balance, total_pnl) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                format_timestamp(sample.timestamp),
+                sample.equity.to_string(),
+                sample.available_balance.to_string(),
+                sample.total_pnl.to_string(),
+            ],
+        )?;
+        Ok(())
+    }
+    
+    fn save_strategy_state(&self, state: &StrategyState) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO strategy_state (name, version, data, updated_at) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT (name) DO UPDATE SET version = excluded.version, data = excluded.data, updated_at = excluded.updated_at",
+            params![state.name, state.version, state.data, format_timestamp(state.updated_at)],
+        )?;
+        Ok(())
+    }
+    
+    fn load_strategy_state(&self, name: &str) -> Result<Option<StrategyState>> {
+        let conn = self.conn.lock().unwrap();
+        let state = conn
+            .query_row(
+                "SELECT name, version, data, updated_at FROM strategy_state WHERE name = ?1",
+                params![name],
+                |row| {
+                    Ok(StrategyState {
+                        name: row.get(0)?,
+                        version: row.get(1)?,
+                        data: row.get(2)?,
+                        updated_at: timestamp_column(row, 3)?,
+                    })
+                },
+            )
+            .optional()?;
+        Ok(state)
+    }
+    
+    fn set_kv(&self, key: &str, value: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO kv (key, value, updated_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT (key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+            params![key, value, format_timestamp(Utc::now())],
+        )?;
+        Ok(())
+    }
+    
+    fn get_kv(&self, key: &str) -> Result<Option<String>> {
+        let conn = self.conn.lock().unwrap();
+        let value = conn
+            .query_row("SELECT value FROM kv WHERE key = ?1", params![key], |row| row.get(0))
+            .optional()?;
+        Ok(value)
+    }
+    
+    fn save_state_batch(&self, states: &[StrategyState], kv: &[(&str, String)]) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        for state in states {
+            tx.execute(
+                "INSERT INTO strategy_state (name, version, data, updated_at) VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT (name) DO UPDATE SET version = excluded.version, data = excluded.data, updated_at = excluded.updated_at",
+                params![state.name, state.version, state.data, format_timestamp(state.updated_at)],
+            )?;
+        }
+        for (key, value) in kv {
+            tx.execute(
+                "INSERT INTO kv (key, value, updated_at) VALUES (?1, ?2, ?3)
+                 ON CONFLICT (key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+                params![key, value, format_timestamp(Utc::now())],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+    
+    fn recent_orders(&self, limit: usize) -> Result<Vec<StoredOrder>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT strategy, payload FROM orders ORDER BY created_at DESC LIMIT ?1")?;
+        let rows = stmt
+            .query_map(params![limit as i64], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        
+        rows.into_iter()
+            .map(|(strategy, payload)| {
+                Ok(StoredOrder {
+                    strategy,
+                    order: serde_json::from_str(&payload)?,
+                })
+            })
+            .collect()
+    }
+    
+    fn recent_fills(&self, limit: usize) -> Result<Vec<JournalEntry>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT timestamp, strategy, symbol, side, quantity, price, fee, realized_pnl, order_id, cloid, dry_run, reconciled
+             FROM fills ORDER BY seq DESC LIMIT ?1",
+        )?;
+        let fills = stmt
+            .query_map(params![limit as i64], fill_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(fills)
+    }
+    
+    fn fills_between(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<Vec<JournalEntry>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT timestamp, strategy, symbol, side, quantity, price, fee, realized_pnl, order_id, cloid, dry_run, reconciled
+             FROM fills WHERE timestamp >= ?1 AND timestamp < ?2 ORDER BY timestamp ASC, seq ASC",
+        )?;
+        let fills = stmt
+            .query_map(params![format_timestamp(from), format_timestamp(to)], fill_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(fills)
+    }
+    
+    fn equity_samples(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<Vec<EquitySample>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT timestamp, equity, available_balance, total_pnl FROM equity_samples
+             WHERE timestamp >= ?1 AND timestamp < ?2 ORDER BY timestamp ASC, seq ASC",
+        )?;
+        let samples = stmt
+            .query_map(params![format_timestamp(from), format_timestamp(to)], |row| {
+                Ok(EquitySample {
+                    timestamp: timestamp_column(row, 0)?,
+                    equity: decimal_column(row, 1)?,
+                    available_balance: decimal_column(row, 2)?,
+                    total_pnl: decimal_column(row, 3)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(samples)
+    }
+    
+    fn downsample_equity(&self, older_than: DateTime<Utc>) -> Result<usize> {
+        // The first 16 characters of a stored timestamp are "YYYY-MM-DDTHH:MM"
+        let conn = self.conn.lock().unwrap();
+        let removed = conn.execute(
+            "DELETE FROM equity_samples
+             WHERE timestamp < ?1
+               AND seq NOT IN (
+                   SELECT MAX(seq) FROM equity_samples
+                   WHERE timestamp < ?1
+                   GROUP BY substr(timestamp, 1, 16)
+               )",
+            params![format_timestamp(older_than)],
+        )?;
+        Ok(removed)
+    }
+}
+
+// Fixed-width UTC timestamps so text ordering matches time ordering
+fn fill_row(row: &Row) -> rusqlite::Result<JournalEntry> {
+    let side = match row.get::<_, String>(3)?.as_str() {
+        "BUY" => OrderSide::Buy,
+        "SELL" => OrderSide::Sell,
+        other => return Err(conversion_error(3, format!("unknown side {}", other))),
+    };
+    Ok(JournalEntry {
+        timestamp: timestamp_column(row, 0)?,
+        strategy: row.get(1)?,
+        symbol: row.get(2)?,
+        side,
+        quantity: decimal_column(row, 4)?,
+        price: decimal_column(row, 5)?,
+        fee: decimal_column(row, 6)?,
+        realized_pnl: decimal_column(row, 7)?,
+        order_id: row.get(8)?,
+        cloid: row.get(9)?,
+        dry_run: row.get(10)?,
+        reconciled: row.get(11)?,
+    })
+}
+
+fn format_timestamp(ts: DateTime<Utc>) -> String {
+    ts.to_rfc3339_opts(SecondsFormat::Micros, true)
+}
+
+fn conversion_error(index: usize, message: String) -> rusqlite::Error {
+    rusqlite::Error::FromSqlConversionFailure(index, Type::Text, message.into())
+}
+
+fn decimal_column(row: &Row, index: usize) -> rusqlite::Result<Decimal> {
+    let text: String = row.get(index)?;
+    Decimal::from_str(&text).map_err(|e| conversion_error(index, e.to_string()))
+}
+
+fn timestamp_column(row: &Row, index: usize) -> rusqlite::Result<DateTime<Utc>> {
+    let text: String = row.get(index)?;
+    DateTime::parse_from_rfc3339(&text)
+        .map(|ts| ts.with_timezone(&Utc))
+        .map_err(|e| conversion_error(index, e.to_string()))
+}
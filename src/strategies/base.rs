@@ -1,10 +1,11 @@
 use crate::{
     error::Result,
-    models::{MarketData, StrategySignal},
+    models::{MarketData, MarketTrade, StrategySignal},
 };
 use async_trait::async_trait;
-use rust_decimal::Decimal;
-use std::collections::HashMap;
+use chrono::{DateTime, Utc};
+use rust_decimal::{Decimal, MathematicalOps};
+use std::collections::{HashMap, VecDeque};
 
 #[async_trait]
 pub trait Strategy: Send + Sync {
@@ -12,11 +13,67 @@ pub trait Strategy: Send + Sync {
     fn symbol(&self) -> &str;
     fn is_enabled(&self) -> bool;
     
-    async fn analyze(&self, market_data: &MarketData) -> Result<Option<StrategySignal>>;
+    /// Decides on a signal for `market_data`, the latest for this
+    /// strategy's symbol. Whatever it keeps from the update, e.g. price
+    /// history, stays for the next call.
+    async fn analyze(&mut self, market_data: &MarketData) -> Result<Option<StrategySignal>>;
     async fn update_parameters(&mut self, parameters: HashMap<String, serde_json::Value>) -> Result<()>;
     
     fn get_parameters(&self) -> HashMap<String, serde_json::Value>;
     fn validate_parameters(&self, parameters: &HashMap<String, serde_json::Value>) -> Result<()>;
+    
+    /// Called with each market update for this strategy's symbol before
+    /// `analyze`, so the strategy can keep its own history
+    fn on_market_data(&mut self, _market_data: &MarketData) {}
+    
+    /// Called once before a backtest with the bars that precede it, oldest
+    /// first, so indicators start out primed. Replays them through
+    /// `on_market_data` unless overridden.
+    fn warm_up(&mut self, history: &[MarketData]) {
+        for market_data in history {
+            self.on_market_data(market_data);
+        }
+    }
+    
+    /// The candle interval (e.g. "1h") whose closes this strategy
+    /// analyzes, if any. Live runs stream its candles and hand each to
+    /// `on_candle` once it has closed.
+    fn candle_interval(&self) -> Option<&str> {
+        None
+    }
+    
+    /// Called with each candle of `candle_interval` once it has closed, as
+    /// market data stamped at its close
+    fn on_candle(&mut self, _candle: &MarketData) {}
+    
+    /// Whether this strategy wants every trade on its symbol. Live runs
+    /// stream them and hand each to `on_trade`.
+    fn wants_trades(&self) -> bool {
+        false
+    }
+    
+    /// Called with each trade on this strategy's symbol, as it happens
+    fn on_trade(&mut self, _trade: &MarketTrade) {}
+    
+    /// Called once a signal from this strategy has been filled
+    fn on_fill(&mut self, _signal: &StrategySignal, _fill_price: Decimal, _timestamp: DateTime<Utc>) {}
+    
+    /// Encoding version of what `export_state` returns
+    fn state_version(&self) -> u32 {
+        1
+    }
+    
+    /// State to carry over a restart or into a snapshot, or `None` if the
+    /// strategy keeps nothing worth restoring
+    fn export_state(&self) -> Result<Option<Vec<u8>>> {
+        Ok(None)
+    }
+    
+    /// Restores what `export_state` returned. Leaves the strategy untouched
+    /// on error.
+    fn import_state(&mut self, _version: u32, _data: &[u8]) -> Result<()> {
+        Ok(())
+    }
 }
 
 pub struct StrategyConfig {
@@ -47,6 +104,94 @@ impl StrategyConfig {
     }
 }
 
+/// Fixed-capacity history for strategies. Once full, each push drops the
+/// oldest item in constant time instead of shifting the whole history.
+#[derive(Debug, Clone)]
+pub struct RingBuffer<T> {
+    items: VecDeque<T>,
+    capacity: usize,
+}
+
+impl<T> RingBuffer<T> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            items: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+    
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+    
+    /// Changes how much history is kept, dropping the oldest items if there
+    /// are now too many
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        if self.items.len() > capacity {
+            self.items.drain(..self.items.len() - capacity);
+        }
+    }
+    
+    pub fn push(&mut self, item: T) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.items.len() == self.capacity {
+            self.items.pop_front();
+        }
+        self.items.push_back(item);
+    }
+    
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+    
+    pub fn last(&self) -> Option<&T> {
+        self.items.back()
+    }
+    
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+    
+    /// Oldest first
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator {
+        self.items.iter()
+    }
+    
+    /// The latest `n` items (or all of them if there are fewer), oldest first
+    pub fn iter_recent(&self, n: usize) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator {
+        self.items.range(self.items.len().saturating_sub(n)..)
+    }
+    
+    /// The history as one slice, oldest first, for the indicator functions.
+    /// Rearranges the storage if it has wrapped around.
+    pub fn make_contiguous(&mut self) -> &[T] {
+        self.items.make_contiguous()
+    }
+}
+
+impl<T: Clone> RingBuffer<T> {
+    /// A copy of the history, oldest first, for callers that only have
+    /// shared access
+    pub fn to_vec(&self) -> Vec<T> {
+        self.items.iter().cloned().collect()
+    }
+}
+
+impl<T> Extend<T> for RingBuffer<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, items: I) {
+        for item in items {
+            self.push(item);
+        }
+    }
+}
+
 pub fn calculate_sma(prices: &[Decimal], period: usize) -> Option<Decimal> {
     if prices.len() < period {
         return None;
@@ -139,17 +284,25 @@ pub fn calculate_macd(
     slow_period: usize,
     signal_period: usize,
 ) -> Option<(Decimal, Decimal, Decimal)> {
-    if prices.len() < slow_period {
+    if prices.is_empty() || prices.len() < slow_period {
         return None;
     }
     
-    let fast_ema = calculate_ema(prices, fast_period, None)?;
-    let slow_ema = calculate_ema(prices, slow_period, None)?;
-    let macd_line = fast_ema - slow_ema;
+    // MACD at every point, so the signal line can be its EMA
+    let fast_alpha = Decimal::from(2) / (Decimal::from(fast_period) + Decimal::from(1));
+    let slow_alpha = Decimal::from(2) / (Decimal::from(slow_period) + Decimal::from(1));
+    let mut fast_ema = prices[0];
+    let mut slow_ema = prices[0];
+    let mut macd_history = Vec::with_capacity(prices.len());
+    
+    for &price in prices {
+        fast_ema = fast_alpha * price + (Decimal::from(1) - fast_alpha) * fast_ema;
+        slow_ema = slow_alpha * price + (Decimal::from(1) - slow_alpha) * slow_ema;
+        macd_history.push(fast_ema - slow_ema);
+    }
     
-    // For signal line, we'd need to calculate EMA of MACD line
-    // This is simplified - in practice, you'd maintain MACD history
-    let signal_line = macd_line; // Simplified
+    let macd_line = *macd_history.last()?;
+    let signal_line = calculate_ema(&macd_history, signal_period, None)?;
     let histogram = macd_line - signal_line;
     
     Some((macd_line, signal_line, histogram))
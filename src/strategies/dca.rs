@@ -1,10 +1,12 @@
 use crate::{
     error::Result,
-    models::{MarketData, StrategySignal, SignalAction},
-    strategies::base::Strategy,
+    models::{MarketData, StrategySignal, SignalAction, TWAP_MINUTES},
+    strategies::base::{RingBuffer, Strategy},
+    utils::MarketSchedule,
 };
 use async_trait::async_trait;
 use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use tracing::{debug, info};
 
@@ -20,8 +22,23 @@ pub struct DCAStrategy {
     last_buy_time: Option<chrono::DateTime<chrono::Utc>>,
     max_investment: Decimal,
     current_investment: Decimal,
-    price_history: Vec<Decimal>,
+    /// The last `lookback_period` prices
+    price_history: RingBuffer<Decimal>,
+    /// Timestamp of the last update already in the history
+    last_update: Option<chrono::DateTime<chrono::Utc>>,
     lookback_period: usize,
+    /// Buy through the exchange's TWAP over this many minutes instead of
+    /// all at once
+    twap_minutes: Option<u32>,
+    schedule: MarketSchedule,
+}
+
+/// What a DCA plan has done so far, as saved between runs
+#[derive(Serialize, Deserialize)]
+struct DCAState {
+    last_buy_time: Option<chrono::DateTime<chrono::Utc>>,
+    current_investment: Decimal,
+    price_history: Vec<Decimal>,
 }
 
 impl DCAStrategy {
@@ -36,15 +53,29 @@ impl DCAStrategy {
             last_buy_time: None,
             max_investment: Decimal::from(10000), // $10,000 max
             current_investment: Decimal::ZERO,
-            price_history: Vec::new(),
+            price_history: RingBuffer::new(20),
+            last_update: None,
             lookback_period: 20,
+            twap_minutes: None,
+            schedule: MarketSchedule::default(),
         }
     }
     
+    /// Defers buys that fall in maintenance, blackout or funding windows
+    pub fn with_schedule(mut self, schedule: MarketSchedule) -> Self {
+        self.schedule = schedule;
+        self
+    }
+    
     fn should_buy(&self, market_data: &MarketData) -> bool {
+        if let Some(reason) = self.schedule.blocked_reason(market_data.timestamp) {
+            debug!("DCA: deferring buy for {}: {}", self.symbol, reason);
+            return false;
+        }
+        
         // Check if enough time has passed since last buy
         if let Some(last_buy) = self.last_buy_time {
-            let time_since_last = chrono::Utc::now() - last_buy;
+            let time_since_last = market_data.timestamp - last_buy;
             if time_since_last.num_hours() < self.interval_hours as i64 {
                 return false;
             }
@@ -75,9 +106,8 @@ impl DCAStrategy {
             return None;
         }
         
-        let recent_prices = &self.price_history[self.price_history.len() - self.lookback_period..];
-        let sum: Decimal = recent_prices.iter().sum();
-        Some(sum / Decimal::from(recent_prices.len()))
+        let sum: Decimal = self.price_history.iter_recent(self.lookback_period).sum();
+        Some(sum / Decimal::from(self.lookback_period))
     }
     
     fn calculate_confidence(&self, market_data: &MarketData) -> f64 {
@@ -113,13 +143,18 @@ impl Strategy for DCAStrategy {
         self.enabled
     }
     
-    async fn analyze(&self, market_data: &MarketData) -> Result<Option<StrategySignal>> {
+    async fn analyze(&mut self, market_data: &MarketData) -> Result<Option<StrategySignal>> {
         if !self.enabled {
             return Ok(None);
         }
         
         debug!("DCA analyzing {} at price {}", self.symbol, market_data.price);
         
+        // Include this update unless on_market_data already added it
+        if self.last_update != Some(market_data.timestamp) {
+            self.on_market_data(market_data);
+        }
+        
         if self.should_buy(market_data) {
             let confidence = self.calculate_confidence(market_data);
             
@@ -130,6 +165,15 @@ impl Strategy for DCAStrategy {
                 confidence
             );
             
+            let mut metadata = HashMap::from([
+                ("investment_amount".to_string(), serde_json::Value::String(self.investment_amount.to_string())),
+                ("interval_hours".to_string(), serde_json::Value::Number(self.interval_hours.into())),
+                ("current_investment".to_string(), serde_json::Value::String(self.current_investment.to_string())),
+            ]);
+            if let Some(minutes) = self.twap_minutes {
+                metadata.insert(TWAP_MINUTES.to_string(), serde_json::Value::Number(minutes.into()));
+            }
+            
             Ok(Some(StrategySignal {
                 strategy_name: self.name.clone(),
                 symbol: self.symbol.clone(),
@@ -137,11 +181,8 @@ impl Strategy for DCAStrategy {
                 quantity: self.investment_amount / market_data.price,
                 price: Some(market_data.price),
                 confidence,
-                metadata: HashMap::from([
-                    ("investment_amount".to_string(), serde_json::Value::String(self.investment_amount.to_string())),
-                    ("interval_hours".to_string(), serde_json::Value::Number(self.interval_hours.into())),
-                    ("current_investment".to_string(), serde_json::Value::String(self.current_investment.to_string())),
-                ]),
+                metadata,
+                reduce_only: false,
             }))
         } else {
             Ok(None)
@@ -149,7 +190,7 @@ impl Strategy for DCAStrategy {
     }
     
     async fn update_parameters(&mut self, parameters: HashMap<String, serde_json::Value>) -> Result<()> {
-        for (key, value) in parameters {
+        for (key, value) in &parameters {
             match key.as_str() {
                 "investment_amount" => {
                     if let Some(amount) = value.as_str().and_then(|s| s.parse::<Decimal>().ok()) {
@@ -169,8 +210,12 @@ impl Strategy for DCAStrategy {
                 "lookback_period" => {
                     if let Some(period) = value.as_u64() {
                         self.lookback_period = period as usize;
+                        self.price_history.set_capacity(self.lookback_period);
                     }
                 }
+                "twap_minutes" => {
+                    self.twap_minutes = value.as_u64().map(|minutes| minutes as u32);
+                }
                 _ => {
                     debug!("Unknown DCA parameter: {}", key);
                 }
@@ -185,6 +230,39 @@ impl Strategy for DCAStrategy {
         self.parameters.clone()
     }
     
+    fn on_market_data(&mut self, market_data: &MarketData) {
+        self.last_update = Some(market_data.timestamp);
+        self.update_price_history(market_data.price);
+    }
+    
+    fn on_fill(&mut self, signal: &StrategySignal, fill_price: Decimal, timestamp: chrono::DateTime<chrono::Utc>) {
+        self.record_buy(signal.quantity * fill_price, timestamp);
+    }
+    
+    fn export_state(&self) -> Result<Option<Vec<u8>>> {
+        let state = DCAState {
+            last_buy_time: self.last_buy_time,
+            current_investment: self.current_investment,
+            price_history: self.price_history.to_vec(),
+        };
+        Ok(Some(serde_json::to_vec(&state)?))
+    }
+    
+    fn import_state(&mut self, version: u32, data: &[u8]) -> Result<()> {
+        if version != self.state_version() {
+            return Err(crate::error::Error::Strategy(format!(
+                "DCA state version {} is not supported", version
+            )));
+        }
+        
+        let state: DCAState = serde_json::from_slice(data)?;
+        self.last_buy_time = state.last_buy_time;
+        self.current_investment = state.current_investment;
+        self.price_history.clear();
+        self.price_history.extend(state.price_history);
+        Ok(())
+    }
+    
     fn validate_parameters(&self, parameters: &HashMap<String, serde_json::Value>) -> Result<()> {
         for (key, value) in parameters {
             match key.as_str() {
@@ -215,6 +293,11 @@ impl Strategy for DCAStrategy {
                         }
                     }
                 }
+                "twap_minutes" if !value.as_u64().is_some_and(|minutes| (5..=1440).contains(&minutes)) => {
+                    return Err(crate::error::Error::Strategy(
+                        "TWAP minutes must be a whole number from 5 to 1440".to_string()
+                    ));
+                }
                 _ => {}
             }
         }
@@ -226,15 +309,10 @@ impl Strategy for DCAStrategy {
 impl DCAStrategy {
     pub fn update_price_history(&mut self, price: Decimal) {
         self.price_history.push(price);
-        
-        // Keep only recent prices to avoid memory growth
-        if self.price_history.len() > self.lookback_period * 2 {
-            self.price_history.drain(0..self.price_history.len() - self.lookback_period);
-        }
     }
     
-    pub fn record_buy(&mut self, amount: Decimal) {
-        self.last_buy_time = Some(chrono::Utc::now());
+    pub fn record_buy(&mut self, amount: Decimal, timestamp: chrono::DateTime<chrono::Utc>) {
+        self.last_buy_time = Some(timestamp);
         self.current_investment += amount;
     }
     
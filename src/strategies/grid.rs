@@ -76,24 +76,18 @@ impl GridStrategy {
         }
         
         // Find the highest buy level that's above current price
-        for &level in &self.grid_levels {
-            if level > market_data.price && self.active_orders.get(&level) == Some(&true) {
-                return Some(level);
-            }
-        }
-        
-        None
+        self.grid_levels
+            .iter()
+            .copied()
+            .find(|&level| level > market_data.price && self.active_orders.get(&level) == Some(&true))
     }
     
     fn should_place_sell_order(&self, market_data: &MarketData) -> Option<Decimal> {
         // Find the lowest sell level that's below current price
-        for &level in &self.grid_levels {
-            if level < market_data.price && self.active_orders.get(&level) == Some(&false) {
-                return Some(level);
-            }
-        }
-        
-        None
+        self.grid_levels
+            .iter()
+            .copied()
+            .find(|&level| level < market_data.price && self.active_orders.get(&level) == Some(&false))
     }
     
     fn calculate_confidence(&self, action: &SignalAction, price: Decimal) -> f64 {
@@ -147,17 +141,17 @@ impl Strategy for GridStrategy {
         self.enabled
     }
     
-    async fn analyze(&self, market_data: &MarketData) -> Result<Option<StrategySignal>> {
+    async fn analyze(&mut self, market_data: &MarketData) -> Result<Option<StrategySignal>> {
         if !self.enabled {
             return Ok(None);
         }
         
         debug!("Grid analyzing {} at price {}", self.symbol, market_data.price);
         
-        // Initialize grid if not done yet
+        // Lay the grid around the first price seen, unless on_market_data
+        // already did; it trades from the next update on
         if self.base_price.is_none() {
-            // This would need to be handled by the strategy manager
-            // For now, we'll skip analysis until grid is initialized
+            self.initialize_grid(market_data.price);
             return Ok(None);
         }
         
@@ -184,6 +178,7 @@ impl Strategy for GridStrategy {
                     ("position_size".to_string(), serde_json::Value::String(self.position_size.to_string())),
                     ("total_investment".to_string(), serde_json::Value::String(self.total_investment.to_string())),
                 ]),
+                reduce_only: false,
             }));
         }
         
@@ -210,6 +205,7 @@ impl Strategy for GridStrategy {
                     ("position_size".to_string(), serde_json::Value::String(self.position_size.to_string())),
                     ("total_investment".to_string(), serde_json::Value::String(self.total_investment.to_string())),
                 ]),
+                reduce_only: false,
             }));
         }
         
@@ -217,7 +213,7 @@ impl Strategy for GridStrategy {
     }
     
     async fn update_parameters(&mut self, parameters: HashMap<String, serde_json::Value>) -> Result<()> {
-        for (key, value) in parameters {
+        for (key, value) in &parameters {
             match key.as_str() {
                 "grid_spacing" => {
                     if let Some(spacing) = value.as_str().and_then(|s| s.parse::<Decimal>().ok()) {
@@ -253,6 +249,17 @@ impl Strategy for GridStrategy {
         self.parameters.clone()
     }
     
+    fn on_market_data(&mut self, market_data: &MarketData) {
+        if self.base_price.is_none() {
+            self.initialize_grid(market_data.price);
+        }
+    }
+    
+    fn on_fill(&mut self, signal: &StrategySignal, fill_price: Decimal, _timestamp: chrono::DateTime<chrono::Utc>) {
+        let is_buy = matches!(signal.action, SignalAction::Buy);
+        self.mark_order_filled(signal.price.unwrap_or(fill_price), is_buy);
+    }
+    
     fn validate_parameters(&self, parameters: &HashMap<String, serde_json::Value>) -> Result<()> {
         for (key, value) in parameters {
             match key.as_str() {
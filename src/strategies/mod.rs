@@ -7,3 +7,42 @@ pub use base::Strategy;
 pub use dca::DCAStrategy;
 pub use grid::GridStrategy;
 pub use momentum::MomentumStrategy;
+
+use crate::{
+    config::{Config, StrategyConfig},
+    error::Result,
+    utils::MarketSchedule,
+};
+
+/// Builds the strategy a config entry describes, with its parameters
+/// applied. Returns `None` for an unknown strategy type.
+pub async fn create(
+    name: &str,
+    strategy_config: &StrategyConfig,
+    config: &Config,
+) -> Result<Option<Box<dyn Strategy + Send + Sync>>> {
+    let name = name.to_string();
+    let symbol = strategy_config.symbol.clone();
+    let parameters = strategy_config.parameters.clone();
+    
+    let strategy: Box<dyn Strategy + Send + Sync> = match strategy_config.strategy_type.as_str() {
+        "dca" => {
+            let mut dca = DCAStrategy::new(name, symbol).with_schedule(MarketSchedule::new(config.schedule.clone()));
+            dca.update_parameters(parameters).await?;
+            Box::new(dca)
+        }
+        "grid" => {
+            let mut grid = GridStrategy::new(name, symbol);
+            grid.update_parameters(parameters).await?;
+            Box::new(grid)
+        }
+        "momentum" => {
+            let mut momentum = MomentumStrategy::new(name, symbol);
+            momentum.update_parameters(parameters).await?;
+            Box::new(momentum)
+        }
+        _ => return Ok(None),
+    };
+    
+    Ok(Some(strategy))
+}
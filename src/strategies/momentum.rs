@@ -1,9 +1,11 @@
 use crate::{
+    api::types::interval_duration,
     error::Result,
-    models::{MarketData, StrategySignal, SignalAction},
-    strategies::base::{Strategy, calculate_sma, calculate_ema, calculate_rsi, calculate_macd},
+    models::{MarketData, MarketTrade, StrategySignal, SignalAction},
+    strategies::base::{RingBuffer, Strategy, calculate_sma, calculate_rsi, calculate_macd},
 };
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use std::collections::HashMap;
 use tracing::{debug, info};
@@ -21,63 +23,76 @@ pub struct MomentumStrategy {
     rsi_period: usize,
     rsi_oversold: Decimal,
     rsi_overbought: Decimal,
-    price_history: Vec<Decimal>,
-    volume_history: Vec<Decimal>,
+    /// The last `2 * slow_period` prices and volumes
+    price_history: RingBuffer<Decimal>,
+    volume_history: RingBuffer<Decimal>,
+    /// Timestamp of the last update already in the history
+    last_update: Option<DateTime<Utc>>,
     min_confidence: f64,
+    /// When set, the history holds this interval's closed candles rather
+    /// than the price each cycle sampled
+    candle_interval: Option<String>,
+    /// Size traded on the symbol since trades started streaming in, and as
+    /// of the last history entry. Until then the volumes are the day's.
+    traded: Option<Decimal>,
+    traded_at_update: Decimal,
 }
 
 impl MomentumStrategy {
     pub fn new(name: String, symbol: String) -> Self {
+        let slow_period = 26;
         Self {
             name,
             symbol,
             enabled: true,
             parameters: HashMap::new(),
             fast_period: 12,
-            slow_period: 26,
+            slow_period,
             signal_period: 9,
             rsi_period: 14,
             rsi_oversold: Decimal::from(30),
             rsi_overbought: Decimal::from(70),
-            price_history: Vec::new(),
-            volume_history: Vec::new(),
+            price_history: RingBuffer::new(slow_period * 2),
+            volume_history: RingBuffer::new(slow_period * 2),
+            last_update: None,
             min_confidence: 0.6,
+            candle_interval: None,
+            traded: None,
+            traded_at_update: Decimal::ZERO,
         }
     }
     
     fn update_history(&mut self, market_data: &MarketData) {
+        self.last_update = Some(market_data.timestamp);
         self.price_history.push(market_data.price);
-        self.volume_history.push(market_data.volume_24h);
-        
-        // Keep only recent data to avoid memory growth
-        let max_history = self.slow_period * 2;
-        if self.price_history.len() > max_history {
-            self.price_history.drain(0..self.price_history.len() - max_history);
-        }
-        if self.volume_history.len() > max_history {
-            self.volume_history.drain(0..self.volume_history.len() - max_history);
-        }
+        let volume = match self.traded {
+            // What traded since the last entry
+            Some(traded) => traded - std::mem::replace(&mut self.traded_at_update, traded),
+            None => market_data.volume_24h,
+        };
+        self.volume_history.push(volume);
     }
     
-    fn analyze_momentum(&self) -> Option<(SignalAction, f64)> {
+    fn analyze_momentum(&mut self) -> Option<(SignalAction, f64)> {
         if self.price_history.len() < self.slow_period {
             return None;
         }
+        let prices = self.price_history.make_contiguous();
         
         // Calculate MACD
         let (macd_line, signal_line, histogram) = calculate_macd(
-            &self.price_history,
+            prices,
             self.fast_period,
             self.slow_period,
             self.signal_period,
         )?;
         
         // Calculate RSI
-        let rsi = calculate_rsi(&self.price_history, self.rsi_period)?;
+        let rsi = calculate_rsi(prices, self.rsi_period)?;
         
         // Calculate moving averages
-        let fast_sma = calculate_sma(&self.price_history, self.fast_period)?;
-        let slow_sma = calculate_sma(&self.price_history, self.slow_period)?;
+        let fast_sma = calculate_sma(prices, self.fast_period)?;
+        let slow_sma = calculate_sma(prices, self.slow_period)?;
         
         // Momentum signals
         let mut signals = Vec::new();
@@ -108,7 +123,7 @@ impl MomentumStrategy {
         }
         
         // Price above/below moving averages
-        let current_price = self.price_history.last().unwrap();
+        let current_price = *prices.last().unwrap();
         if current_price > fast_sma && fast_sma > slow_sma {
             signals.push("PRICE_ABOVE_MA");
             confidence += 0.2;
@@ -119,7 +134,7 @@ impl MomentumStrategy {
         
         // Volume confirmation
         if self.volume_history.len() >= 2 {
-            let current_volume = self.volume_history.last().unwrap();
+            let current_volume = *self.volume_history.last().unwrap();
             let avg_volume = self.volume_history.iter().sum::<Decimal>() / Decimal::from(self.volume_history.len());
             
             if current_volume > avg_volume * Decimal::new(15, 1) { // 1.5x average volume
@@ -151,6 +166,11 @@ impl MomentumStrategy {
         let confidence_multiplier = Decimal::from_f64_retain(confidence).unwrap_or(Decimal::ONE);
         base_size * confidence_multiplier / market_data.price
     }
+    
+    /// The prices, or candle closes, analyzed so far, oldest first
+    pub fn price_history(&self) -> &RingBuffer<Decimal> {
+        &self.price_history
+    }
 }
 
 #[async_trait]
@@ -167,19 +187,21 @@ impl Strategy for MomentumStrategy {
         self.enabled
     }
     
-    async fn analyze(&self, market_data: &MarketData) -> Result<Option<StrategySignal>> {
+    async fn analyze(&mut self, market_data: &MarketData) -> Result<Option<StrategySignal>> {
         if !self.enabled {
             return Ok(None);
         }
         
         debug!("Momentum analyzing {} at price {}", self.symbol, market_data.price);
         
-        // Update price history
-        let mut strategy = self.clone();
-        strategy.update_history(market_data);
+        // Include this update unless on_market_data already added it. Closed
+        // candles are all that go into a candle-driven history.
+        if self.candle_interval.is_none() && self.last_update != Some(market_data.timestamp) {
+            self.update_history(market_data);
+        }
         
-        if let Some((action, confidence)) = strategy.analyze_momentum() {
-            let quantity = strategy.calculate_position_size(market_data, confidence);
+        if let Some((action, confidence)) = self.analyze_momentum() {
+            let quantity = self.calculate_position_size(market_data, confidence);
             
             info!(
                 "Momentum signal: {:?} {} at {} (confidence: {:.2})",
@@ -200,16 +222,47 @@ impl Strategy for MomentumStrategy {
                     ("fast_period".to_string(), serde_json::Value::Number(self.fast_period.into())),
                     ("slow_period".to_string(), serde_json::Value::Number(self.slow_period.into())),
                     ("rsi_period".to_string(), serde_json::Value::Number(self.rsi_period.into())),
-                    ("signals".to_string(), serde_json::Value::String(format!("{:?}", strategy.analyze_momentum()))),
+                    ("signals".to_string(), serde_json::Value::String(format!("{:?}", self.analyze_momentum()))),
                 ]),
+                reduce_only: false,
             }))
         } else {
             Ok(None)
         }
     }
     
+    fn on_market_data(&mut self, market_data: &MarketData) {
+        self.update_history(market_data);
+    }
+    
+    fn candle_interval(&self) -> Option<&str> {
+        self.candle_interval.as_deref()
+    }
+    
+    fn on_candle(&mut self, candle: &MarketData) {
+        if self.candle_interval.is_some() {
+            self.update_history(candle);
+        }
+    }
+    
+    /// Candles carry their own volume; sampled prices only have the day's
+    fn wants_trades(&self) -> bool {
+        self.candle_interval.is_none()
+    }
+    
+    fn on_trade(&mut self, trade: &MarketTrade) {
+        if self.candle_interval.is_some() {
+            return;
+        }
+        if self.traded.is_none() {
+            // The day's volumes don't compare with what traded between samples
+            self.volume_history.clear();
+        }
+        self.traded = Some(self.traded.unwrap_or_default() + trade.size);
+    }
+    
     async fn update_parameters(&mut self, parameters: HashMap<String, serde_json::Value>) -> Result<()> {
-        for (key, value) in parameters {
+        for (key, value) in &parameters {
             match key.as_str() {
                 "fast_period" => {
                     if let Some(period) = value.as_u64() {
@@ -246,12 +299,26 @@ impl Strategy for MomentumStrategy {
                         self.min_confidence = conf;
                     }
                 }
+                "candle_interval" => {
+                    let interval = value.as_str().filter(|interval| !interval.is_empty()).map(str::to_string);
+                    // Sampled prices and candle closes don't mix
+                    if interval != self.candle_interval {
+                        self.price_history.clear();
+                        self.volume_history.clear();
+                        self.last_update = None;
+                        self.traded = None;
+                        self.traded_at_update = Decimal::ZERO;
+                    }
+                    self.candle_interval = interval;
+                }
                 _ => {
                     debug!("Unknown Momentum parameter: {}", key);
                 }
             }
         }
         
+        self.price_history.set_capacity(self.slow_period * 2);
+        self.volume_history.set_capacity(self.slow_period * 2);
         self.parameters = parameters;
         Ok(())
     }
@@ -283,13 +350,22 @@ impl Strategy for MomentumStrategy {
                 }
                 "min_confidence" => {
                     if let Some(conf) = value.as_f64() {
-                        if conf < 0.0 || conf > 1.0 {
+                        if !(0.0..=1.0).contains(&conf) {
                             return Err(crate::error::Error::Strategy(
                                 "Min confidence must be between 0 and 1".to_string()
                             ));
                         }
                     }
                 }
+                "candle_interval" => {
+                    if let Some(interval) = value.as_str().filter(|interval| !interval.is_empty()) {
+                        if interval_duration(interval).is_err() {
+                            return Err(crate::error::Error::Strategy(
+                                "candle_interval must be one of 1m, 5m, 15m, 1h, 4h or 1d".to_string()
+                            ));
+                        }
+                    }
+                }
                 _ => {}
             }
         }
@@ -314,7 +390,11 @@ impl Clone for MomentumStrategy {
             rsi_overbought: self.rsi_overbought,
             price_history: self.price_history.clone(),
             volume_history: self.volume_history.clone(),
+            last_update: self.last_update,
             min_confidence: self.min_confidence,
+            candle_interval: self.candle_interval.clone(),
+            traded: self.traded,
+            traded_at_update: self.traded_at_update,
         }
     }
 }
@@ -0,0 +1,112 @@
+use crate::{
+    config::Config,
+    control::StatusSnapshot,
+    error::{Error, Result},
+    market_feed::MarketFeed,
+    runner::{RunMode, RunSummary},
+    shutdown::ShutdownReport,
+    trading_bot::TradingBot,
+};
+use futures_util::future::join_all;
+use std::sync::Arc;
+use tracing::{error, info, info_span, Instrument};
+
+/// Trades several accounts from one process: one bot per account, each with
+/// its own client, nonces, strategies, risk limits, stats, storage and
+/// journal, all streaming market data through one shared [`MarketFeed`].
+///
+/// The bots only share what they read. Each runs on its own task, so one
+/// account's failed cycles, stalled loop or kill switch leave the others
+/// trading.
+pub struct BotSupervisor {
+    feed: Arc<MarketFeed>,
+    bots: Vec<Arc<TradingBot>>,
+}
+
+impl BotSupervisor {
+    pub fn new(feed: Arc<MarketFeed>) -> Self {
+        Self { feed, bots: Vec::new() }
+    }
+    
+    /// A bot for each of `config.accounts`
+    pub async fn from_config(config: &Config) -> Result<Self> {
+        let mut supervisor = Self::new(Arc::new(MarketFeed::new(config)));
+        for account in &config.accounts {
+            info!("Initializing account {}", account.name);
+            let bot = TradingBot::new(config.for_account(account)).await?.with_account(&account.name);
+            supervisor = supervisor.with_bot(bot);
+        }
+        Ok(supervisor)
+    }
+    
+    /// Adds an account's bot, moving it onto the shared feed
+    pub fn with_bot(mut self, bot: TradingBot) -> Self {
+        self.bots.push(Arc::new(bot.with_market_feed(self.feed.clone())));
+        self
+    }
+    
+    pub fn feed(&self) -> &Arc<MarketFeed> {
+        &self.feed
+    }
+    
+    pub fn bots(&self) -> &[Arc<TradingBot>] {
+        &self.bots
+    }
+    
+    /// The bot trading `account`
+    pub fn bot(&self, account: &str) -> Option<&Arc<TradingBot>> {
+        self.bots.iter().find(|bot| bot.account() == Some(account))
+    }
+    
+    /// Runs every account's trading loop, each on its own task with its log
+    /// lines under an `account` span, until all of them have finished.
+    /// Returns how each run ended, by account.
+    pub async fn run(&self, mode: RunMode) -> Vec<(String, Result<RunSummary>)> {
+        let runs = self.bots.iter().map(|bot| {
+            let bot = bot.clone();
+            let span = info_span!("account", account = %label(&bot));
+            tokio::spawn(async move { bot.run(mode).await }.instrument(span))
+        });
+        let results = join_all(runs).await;
+        
+        self.bots
+            .iter()
+            .zip(results)
+            .map(|(bot, result)| {
+                let account = label(bot);
+                let result = result.unwrap_or_else(|e| Err(Error::Trading(format!("Trading loop panicked: {}", e))));
+                if let Err(e) = &result {
+                    error!(account = %account, "Account {} stopped trading: {}", account, e);
+                }
+                (account, result)
+            })
+            .collect()
+    }
+    
+    /// One trading cycle for every account at once, as `run` would run them
+    pub async fn run_cycle(&self) -> Vec<(String, Result<()>)> {
+        let cycles = self.bots.iter().map(|bot| {
+            let span = info_span!("account", account = %label(bot));
+            async move { (label(bot), bot.run_cycle().await) }.instrument(span)
+        });
+        join_all(cycles).await
+    }
+    
+    /// Every account's status, labeled with its name
+    pub async fn snapshots(&self) -> Vec<StatusSnapshot> {
+        join_all(self.bots.iter().map(|bot| bot.cached_snapshot())).await
+    }
+    
+    /// Stops every account's bot, then disconnects the shared feed
+    pub async fn stop(&self) -> Vec<(String, ShutdownReport)> {
+        let reports = join_all(self.bots.iter().map(|bot| async move { (label(bot), bot.stop().await) })).await;
+        if let Err(e) = self.feed.disconnect().await {
+            error!("Failed to disconnect the market feed: {}", e);
+        }
+        reports
+    }
+}
+
+fn label(bot: &TradingBot) -> String {
+    bot.account().unwrap_or("default").to_string()
+}
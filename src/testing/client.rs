@@ -0,0 +1,517 @@
+use super::fixtures;
+use crate::{
+    api::{client::TradingClient, types::Candle},
+    clock::{Clock, SimulatedClock},
+    error::{Error, Result},
+    models::{AccountInfo, EquityPoint, FeeSchedule, FundingPayment, MarketData, Order, OrderStatus, Position, Trade},
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// One of the [`TradingClient`] calls, for scripting failures and latency
+/// and reading back what the bot asked for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Call {
+    MarketData,
+    AccountInfo,
+    Positions,
+    OpenOrders,
+    PlaceOrder,
+    CancelOrder,
+    TradeHistory,
+    Candles,
+    Funding,
+    UserFees,
+    UsdTransfer,
+    Withdraw,
+    ScheduleCancel,
+    Portfolio,
+}
+
+/// A call the bot made and when it was in flight
+#[derive(Debug, Clone)]
+pub struct CallRecord {
+    pub call: Call,
+    /// The symbol or order id the call was about, if any
+    pub subject: Option<String>,
+    pub sent: Instant,
+    pub answered: Instant,
+}
+
+/// USDC the bot moved out of the account
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transfer {
+    /// [`Call::UsdTransfer`] or [`Call::Withdraw`]
+    pub call: Call,
+    pub destination: String,
+    pub amount: Decimal,
+}
+
+/// What the mock exchange does with an order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OrderOutcome {
+    /// Fills in full at once, at its limit price or else the market's
+    Fill,
+    /// Rests on the book until cancelled
+    Rest,
+    /// Rejected with this message
+    Reject(String),
+}
+
+/// Replies given in turn, the last one repeating
+#[derive(Debug)]
+struct Script<T>(VecDeque<T>);
+
+impl<T: Clone> Script<T> {
+    fn new(replies: impl IntoIterator<Item = T>) -> Self {
+        Self(replies.into_iter().collect())
+    }
+    
+    fn next(&mut self) -> Option<T> {
+        if self.0.len() > 1 {
+            self.0.pop_front()
+        } else {
+            self.0.front().cloned()
+        }
+    }
+    
+    fn current(&self) -> Option<&T> {
+        self.0.front()
+    }
+}
+
+struct State {
+    prices: HashMap<String, Script<MarketData>>,
+    accounts: Script<AccountInfo>,
+    candles: HashMap<String, Vec<Candle>>,
+    /// Outcomes by symbol; symbols without a script fill
+    outcomes: HashMap<String, Script<OrderOutcome>>,
+    failures: HashMap<Call, VecDeque<Error>>,
+    latency: HashMap<Call, Duration>,
+    open_orders: Vec<Order>,
+    sent_orders: Vec<Order>,
+    cancelled: Vec<String>,
+    fills: Vec<Trade>,
+    funding: Vec<FundingPayment>,
+    fee_schedule: Option<FeeSchedule>,
+    portfolio: Option<Vec<EquityPoint>>,
+    transfers: Vec<Transfer>,
+    scheduled_cancel: Option<DateTime<Utc>>,
+    calls: Vec<CallRecord>,
+    next_order_id: u64,
+}
+
+impl State {
+    /// Moves `amount` out unless the current account can't withdraw it
+    fn transfer(&mut self, call: Call, destination: &str, amount: Decimal) -> Result<()> {
+        let withdrawable = self.accounts.current().expect("an account is always scripted").available_balance;
+        if amount > withdrawable {
+            return Err(Error::Trading(format!("Can't move {} USDC, only {} is withdrawable", amount, withdrawable)));
+        }
+        self.transfers.push(Transfer {
+            call,
+            destination: destination.to_string(),
+            amount,
+        });
+        Ok(())
+    }
+}
+
+/// A [`TradingClient`] whose replies are scripted by the test. Market data
+/// and accounts play back in turn, repeating the last; order outcomes are
+/// scripted per symbol so concurrent orders stay deterministic; and any
+/// call can be made slow or made to fail. Everything the bot asked for is
+/// kept for assertions.
+///
+/// Market data is stamped with the mock's [`SimulatedClock`], which the bot
+/// shares when it's built with [`super::mock_bot`].
+pub struct MockTradingClient {
+    clock: SimulatedClock,
+    state: Mutex<State>,
+}
+
+impl MockTradingClient {
+    /// An exchange holding `balance` in cash and nothing else, with its
+    /// clock at [`fixtures::start`]
+    pub fn new(balance: Decimal) -> Self {
+        Self {
+            clock: SimulatedClock::new(fixtures::start()),
+            state: Mutex::new(State {
+                prices: HashMap::new(),
+                accounts: Script::new([fixtures::account(balance)]),
+                candles: HashMap::new(),
+                outcomes: HashMap::new(),
+                failures: HashMap::new(),
+                latency: HashMap::new(),
+                open_orders: Vec::new(),
+                sent_orders: Vec::new(),
+                cancelled: Vec::new(),
+                fills: Vec::new(),
+                funding: Vec::new(),
+                fee_schedule: None,
+                portfolio: None,
+                transfers: Vec::new(),
+                scheduled_cancel: None,
+                calls: Vec::new(),
+                next_order_id: 1,
+            }),
+        }
+    }
+    
+    pub fn clock(&self) -> &SimulatedClock {
+        &self.clock
+    }
+    
+    /// A steady price for `symbol`
+    pub fn set_price(&self, symbol: &str, price: Decimal) {
+        self.script_prices(symbol, [price]);
+    }
+    
+    /// Prices for `symbol`, one per market data request
+    pub fn script_prices(&self, symbol: &str, prices: impl IntoIterator<Item = Decimal>) {
+        self.script_market_data(prices.into_iter().map(|price| fixtures::market_data(symbol, price)));
+    }
+    
+    /// Full market data, one per request for its symbol. Timestamps are
+    /// replaced by the clock's time when it's served.
+    pub fn script_market_data(&self, data: impl IntoIterator<Item = MarketData>) {
+        let mut by_symbol: HashMap<String, Vec<MarketData>> = HashMap::new();
+        for data in data {
+            by_symbol.entry(data.symbol.clone()).or_default().push(data);
+        }
+        let mut state = self.state();
+        for (symbol, data) in by_symbol {
+            state.prices.insert(symbol, Script::new(data));
+        }
+    }
+    
+    /// Accounts, one per account request. Positions requests see the
+    /// account last served.
+    pub fn script_accounts(&self, accounts: impl IntoIterator<Item = AccountInfo>) {
+        self.state().accounts = Script::new(accounts);
+    }
+    
+    pub fn set_account(&self, account: AccountInfo) {
+        self.script_accounts([account]);
+    }
+    
+    pub fn set_candles(&self, symbol: &str, candles: Vec<Candle>) {
+        self.state().candles.insert(symbol.to_string(), candles);
+    }
+    
+    /// Orders already resting on the book
+    pub fn set_open_orders(&self, orders: Vec<Order>) {
+        self.state().open_orders = orders;
+    }
+    
+    /// Fills `quantity` of resting order `order_id` at `price`, taking it
+    /// off the book once nothing is left
+    pub fn fill_resting(&self, order_id: &str, quantity: Decimal, price: Decimal) {
+        let now = self.clock.now();
+        let mut state = self.state();
+        let fill_id = format!("{}-fill-{}", order_id, state.fills.len() + 1);
+        let index = state
+            .open_orders
+            .iter()
+            .position(|order| order.id == order_id)
+            .unwrap_or_else(|| panic!("order {} isn't resting", order_id));
+        let order = &mut state.open_orders[index];
+        order.filled_quantity += quantity;
+        order.status = OrderStatus::PartiallyFilled;
+        let fill = Trade {
+            id: fill_id,
+            order_id: order_id.to_string(),
+            cloid: order.cloid.clone(),
+            symbol: order.symbol.clone(),
+            side: order.side.clone(),
+            quantity,
+            price,
+            fee: Decimal::ZERO,
+            realized_pnl: Decimal::ZERO,
+            timestamp: now,
+        };
+        if order.filled_quantity >= order.quantity {
+            state.open_orders.remove(index);
+        }
+        state.fills.push(fill);
+    }
+    
+    /// Outcomes for orders on `symbol`, one per order
+    pub fn script_orders(&self, symbol: &str, outcomes: impl IntoIterator<Item = OrderOutcome>) {
+        self.state().outcomes.insert(symbol.to_string(), Script::new(outcomes));
+    }
+    
+    /// Fails the next `call` with `error`. Queued failures are used up one
+    /// per call before it succeeds again.
+    pub fn fail_next(&self, call: Call, error: Error) {
+        self.state().failures.entry(call).or_default().push_back(error);
+    }
+    
+    /// Makes every `call` take `latency` before it's answered
+    pub fn set_latency(&self, call: Call, latency: Duration) {
+        self.state().latency.insert(call, latency);
+    }
+    
+    /// Every call in the order they were answered
+    pub fn calls(&self) -> Vec<CallRecord> {
+        self.state().calls.clone()
+    }
+    
+    /// Every order sent, whatever became of it
+    pub fn sent_orders(&self) -> Vec<Order> {
+        self.state().sent_orders.clone()
+    }
+    
+    /// Ids of orders cancelled
+    pub fn cancelled(&self) -> Vec<String> {
+        self.state().cancelled.clone()
+    }
+    
+    /// Orders that filled
+    pub fn fills(&self) -> Vec<Trade> {
+        self.state().fills.clone()
+    }
+    
+    /// Replaces the fill history, e.g. with fills from orders placed
+    /// elsewhere
+    pub fn set_fills(&self, fills: Vec<Trade>) {
+        self.state().fills = fills;
+    }
+    
+    /// Replaces the funding settled on the account's positions
+    pub fn set_funding(&self, payments: Vec<FundingPayment>) {
+        self.state().funding = payments;
+    }
+    
+    /// Gives the account fee rates; without them the bot estimates fees from
+    /// its configured costs
+    pub fn set_fee_schedule(&self, schedule: FeeSchedule) {
+        self.state().fee_schedule = Some(schedule);
+    }
+    
+    /// The account's value history the exchange reports; without one,
+    /// asking for it fails
+    pub fn set_portfolio_history(&self, history: Vec<EquityPoint>) {
+        self.state().portfolio = Some(history);
+    }
+    
+    /// Transfers and withdrawals made, oldest first
+    pub fn transfers(&self) -> Vec<Transfer> {
+        self.state().transfers.clone()
+    }
+    
+    /// When the bot last asked for every order to be cancelled, unless it
+    /// cleared the schedule since
+    pub fn scheduled_cancel(&self) -> Option<DateTime<Utc>> {
+        self.state().scheduled_cancel
+    }
+    
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap()
+    }
+    
+    /// Waits out the call's latency, then gives its scripted failure if any
+    async fn answer<T>(
+        &self,
+        call: Call,
+        subject: Option<&str>,
+        reply: impl FnOnce(&mut State) -> Result<T>,
+    ) -> Result<T> {
+        let sent = Instant::now();
+        let latency = self.state().latency.get(&call).copied();
+        if let Some(latency) = latency {
+            tokio::time::sleep(latency).await;
+        }
+        
+        let mut state = self.state();
+        let result = match state.failures.get_mut(&call).and_then(|failures| failures.pop_front()) {
+            Some(error) => Err(error),
+            None => reply(&mut state),
+        };
+        state.calls.push(CallRecord {
+            call,
+            subject: subject.map(str::to_string),
+            sent,
+            answered: Instant::now(),
+        });
+        result
+    }
+}
+
+#[async_trait]
+impl TradingClient for MockTradingClient {
+    async fn get_market_data(&self, symbol: &str) -> Result<MarketData> {
+        let now = self.clock.now();
+        self.answer(Call::MarketData, Some(symbol), |state| {
+            let data = state
+                .prices
+                .get_mut(symbol)
+                .and_then(|prices| prices.next())
+                .ok_or_else(|| Error::Api(format!("No price scripted for {}", symbol)))?;
+            Ok(MarketData { timestamp: now, ..data })
+        })
+        .await
+    }
+    
+    async fn get_account_info(&self) -> Result<AccountInfo> {
+        self.answer(Call::AccountInfo, None, |state| {
+            let mut account = state.accounts.next().expect("an account is always scripted");
+            account.open_orders = state.open_orders.clone();
+            Ok(account)
+        })
+        .await
+    }
+    
+    async fn get_positions(&self) -> Result<Vec<Position>> {
+        self.answer(Call::Positions, None, |state| {
+            Ok(state.accounts.current().map(|account| account.positions.clone()).unwrap_or_default())
+        })
+        .await
+    }
+    
+    async fn get_open_orders(&self) -> Result<Vec<Order>> {
+        self.answer(Call::OpenOrders, None, |state| Ok(state.open_orders.clone())).await
+    }
+    
+    async fn place_order(&self, order: &Order) -> Result<String> {
+        let now = self.clock.now();
+        self.answer(Call::PlaceOrder, Some(&order.symbol), |state| {
+            state.sent_orders.push(order.clone());
+            let outcome = state
+                .outcomes
+                .get_mut(&order.symbol)
+                .and_then(|outcomes| outcomes.next())
+                .unwrap_or(OrderOutcome::Fill);
+            
+            let order_id = format!("mock-{}", state.next_order_id);
+            state.next_order_id += 1;
+            match outcome {
+                OrderOutcome::Fill => {
+                    let market_price = state
+                        .prices
+                        .get(&order.symbol)
+                        .and_then(|prices| prices.current())
+                        .map(|data| data.price);
+                    let price = order
+                        .price
+                        .or(market_price)
+                        .ok_or_else(|| Error::Trading(format!("No price to fill {} at", order.symbol)))?;
+                    state.fills.push(Trade {
+                        id: order_id.clone(),
+                        order_id: order_id.clone(),
+                        cloid: None,
+                        symbol: order.symbol.clone(),
+                        side: order.side.clone(),
+                        quantity: order.quantity,
+                        price,
+                        fee: Decimal::ZERO,
+                        realized_pnl: Decimal::ZERO,
+                        timestamp: now,
+                    });
+                }
+                OrderOutcome::Rest => state.open_orders.push(Order {
+                    id: order_id.clone(),
+                    status: OrderStatus::Open,
+                    ..order.clone()
+                }),
+                OrderOutcome::Reject(reason) => return Err(Error::Api(reason)),
+            }
+            Ok(order_id)
+        })
+        .await
+    }
+    
+    async fn cancel_order(&self, order_id: &str) -> Result<bool> {
+        self.answer(Call::CancelOrder, Some(order_id), |state| {
+            let before = state.open_orders.len();
+            state.open_orders.retain(|order| order.id != order_id);
+            let cancelled = state.open_orders.len() < before;
+            if cancelled {
+                state.cancelled.push(order_id.to_string());
+            }
+            Ok(cancelled)
+        })
+        .await
+    }
+    
+    async fn get_trade_history(&self, symbol: Option<&str>) -> Result<Vec<Trade>> {
+        self.answer(Call::TradeHistory, symbol, |state| {
+            Ok(state
+                .fills
+                .iter()
+                .filter(|trade| symbol.is_none_or(|symbol| trade.symbol == symbol))
+                .cloned()
+                .collect())
+        })
+        .await
+    }
+    
+    async fn get_candles(&self, symbol: &str, _interval: &str, start: u64, end: u64) -> Result<Vec<Candle>> {
+        self.answer(Call::Candles, Some(symbol), |state| {
+            Ok(state
+                .candles
+                .get(symbol)
+                .map(|candles| candles.iter().filter(|candle| candle.t >= start && candle.t <= end).cloned().collect())
+                .unwrap_or_default())
+        })
+        .await
+    }
+    
+    async fn get_funding_payments(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<FundingPayment>> {
+        self.answer(Call::Funding, None, |state| {
+            Ok(state
+                .funding
+                .iter()
+                .filter(|payment| payment.time >= start && payment.time <= end)
+                .cloned()
+                .collect())
+        })
+        .await
+    }
+    
+    async fn get_portfolio_history(&self) -> Result<Vec<EquityPoint>> {
+        self.answer(Call::Portfolio, None, |state| {
+            state
+                .portfolio
+                .clone()
+                .ok_or_else(|| Error::Api("No portfolio history scripted".to_string()))
+        })
+        .await
+    }
+    
+    async fn schedule_cancel(&self, at: Option<DateTime<Utc>>) -> Result<()> {
+        let now = self.clock.now();
+        self.answer(Call::ScheduleCancel, None, |state| {
+            if at.is_some_and(|at| at < now + chrono::Duration::seconds(5)) {
+                return Err(Error::InvalidInput("A scheduled cancel must be at least 5s away".to_string()));
+            }
+            state.scheduled_cancel = at;
+            Ok(())
+        })
+        .await
+    }
+    
+    async fn usd_transfer(&self, destination: &str, amount: Decimal) -> Result<()> {
+        self.answer(Call::UsdTransfer, Some(destination), |state| state.transfer(Call::UsdTransfer, destination, amount))
+            .await
+    }
+    
+    async fn withdraw(&self, destination: &str, amount: Decimal) -> Result<()> {
+        self.answer(Call::Withdraw, Some(destination), |state| state.transfer(Call::Withdraw, destination, amount))
+            .await
+    }
+    
+    async fn get_user_fees(&self) -> Result<FeeSchedule> {
+        self.answer(Call::UserFees, None, |state| {
+            state
+                .fee_schedule
+                .ok_or_else(|| Error::Api("No fee schedule scripted".to_string()))
+        })
+        .await
+    }
+}
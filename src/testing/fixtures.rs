@@ -0,0 +1,185 @@
+use crate::{
+    api::types::Candle,
+    config::{BacktestConfig, Config, CostConfig, MarginMode, SlippageConfig, StrategyConfig},
+    models::{AccountInfo, MarketData, Order, OrderSide, OrderStatus, OrderType, Position, PositionSide, Trade},
+};
+use chrono::{DateTime, TimeZone, Utc};
+use rust_decimal::Decimal;
+use std::path::PathBuf;
+
+/// Where fixtures and the mock client's clock start: 2024-01-01 00:00 UTC
+pub fn start() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+}
+
+/// A quiet market at `price`. Change what a test cares about with struct
+/// update syntax: `MarketData { volume_24h: Decimal::ZERO, ..market_data("BTC", price) }`
+pub fn market_data(symbol: &str, price: Decimal) -> MarketData {
+    MarketData {
+        symbol: symbol.to_string(),
+        price,
+        volume_24h: Decimal::from(1000),
+        change_24h: Decimal::ZERO,
+        high_24h: price,
+        low_24h: price,
+        timestamp: start(),
+    }
+}
+
+/// All cash: no positions, orders or P&L
+pub fn account(balance: Decimal) -> AccountInfo {
+    AccountInfo {
+        balance,
+        available_balance: balance,
+        total_pnl: Decimal::ZERO,
+        total_margin: Decimal::ZERO,
+        positions: Vec::new(),
+        open_orders: Vec::new(),
+    }
+}
+
+/// A position marked at its entry price
+pub fn position(symbol: &str, side: PositionSide, size: Decimal, entry_price: Decimal) -> Position {
+    Position {
+        symbol: symbol.to_string(),
+        side,
+        size,
+        entry_price,
+        current_price: entry_price,
+        unrealized_pnl: Decimal::ZERO,
+        realized_pnl: Decimal::ZERO,
+        margin: Decimal::ZERO,
+        timestamp: start(),
+    }
+}
+
+/// An open limit order
+pub fn order(id: &str, symbol: &str, side: OrderSide, quantity: Decimal, price: Decimal) -> Order {
+    Order {
+        id: id.to_string(),
+        symbol: symbol.to_string(),
+        side,
+        order_type: OrderType::Limit,
+        quantity,
+        price: Some(price),
+        status: OrderStatus::Open,
+        created_at: start(),
+        updated_at: None,
+        filled_quantity: Decimal::ZERO,
+        average_price: None,
+        reduce_only: false,
+        trigger_price: None,
+        is_take_profit: false,
+        cloid: None,
+    }
+}
+
+/// A fill of order `order_id` at `timestamp`, without fees
+pub fn trade(order_id: &str, symbol: &str, side: OrderSide, quantity: Decimal, price: Decimal, timestamp: DateTime<Utc>) -> Trade {
+    Trade {
+        id: order_id.to_string(),
+        order_id: order_id.to_string(),
+        cloid: None,
+        symbol: symbol.to_string(),
+        side,
+        quantity,
+        price,
+        fee: Decimal::ZERO,
+        realized_pnl: Decimal::ZERO,
+        timestamp,
+    }
+}
+
+/// A flat candle opening at `t` (epoch millis)
+pub fn candle(t: u64, price: Decimal) -> Candle {
+    Candle {
+        t,
+        o: price,
+        h: price,
+        l: price,
+        c: price,
+        v: Decimal::from(1000),
+    }
+}
+
+/// Flat candles `interval_ms` apart from [`start`], one per close
+pub fn candles(interval_ms: u64, closes: impl IntoIterator<Item = Decimal>) -> Vec<Candle> {
+    let first = start().timestamp_millis() as u64;
+    closes
+        .into_iter()
+        .enumerate()
+        .map(|(i, close)| candle(first + i as u64 * interval_ms, close))
+        .collect()
+}
+
+/// An enabled strategy of `strategy_type` on `symbol`. `parameters` is a
+/// JSON object, e.g. `json!({ "investment_amount": "100" })`.
+pub fn strategy(strategy_type: &str, symbol: &str, parameters: serde_json::Value) -> StrategyConfig {
+    let parameters = match parameters {
+        serde_json::Value::Object(map) => map.into_iter().collect(),
+        _ => Default::default(),
+    };
+    StrategyConfig {
+        enabled: true,
+        strategy_type: strategy_type.to_string(),
+        symbol: symbol.to_string(),
+        position_size: Decimal::from(100),
+        parameters,
+        leverage: None,
+        margin_mode: MarginMode::Cross,
+        eval_interval_secs: None,
+    }
+}
+
+/// A config trading one strategy, `dca_btc`: DCA into BTC with
+/// `parameters`
+pub fn dca_config(parameters: serde_json::Value) -> Config {
+    let mut config = Config::default();
+    config.strategies.insert("dca_btc".to_string(), strategy("dca", "BTC", parameters));
+    config
+}
+
+/// [`dca_config`], placing real orders with whatever client the bot is
+/// given rather than dry running
+pub fn live_dca_config(parameters: serde_json::Value) -> Config {
+    let mut config = dca_config(parameters);
+    config.trading.dry_run = false;
+    config
+}
+
+/// [`dca_config`] backtested from `initial_balance` on `interval` bars,
+/// paying 10bps in taker fees and 10bps of slippage but no funding. The
+/// strategy invests 100 every 24 hours up to 1000, over a 3-bar lookback,
+/// unless `parameters` say otherwise.
+pub fn dca_backtest_config(initial_balance: Decimal, interval: &str, parameters: serde_json::Value) -> Config {
+    let mut merged = serde_json::json!({
+        "investment_amount": "100",
+        "interval_hours": 24,
+        "lookback_period": 3,
+        "max_investment": "1000",
+    });
+    if let (Some(merged), serde_json::Value::Object(parameters)) = (merged.as_object_mut(), parameters) {
+        merged.extend(parameters);
+    }
+    Config {
+        backtest: BacktestConfig {
+            initial_balance,
+            interval: interval.to_string(),
+            warmup_bars: 0,
+        },
+        costs: CostConfig {
+            taker_fee_bps: Decimal::from(10),
+            slippage: SlippageConfig::Fixed { bps: Decimal::from(10) },
+            funding_rate: Decimal::ZERO,
+            ..CostConfig::default()
+        },
+        ..dca_config(merged)
+    }
+}
+
+/// A new, empty directory of its own under the system's temp directory
+pub fn temp_dir() -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("hl-test-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
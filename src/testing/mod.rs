@@ -0,0 +1,65 @@
+mod client;
+pub mod fixtures;
+mod stream;
+
+pub use client::{Call, CallRecord, MockTradingClient, OrderOutcome, Transfer};
+pub use stream::MockMarketStream;
+
+use crate::{
+    clock::SimulatedClock,
+    config::Config,
+    error::Result,
+    trading_bot::TradingBot,
+};
+use std::sync::Arc;
+
+/// A bot trading through `client` on the client's simulated clock.
+/// Storage is turned off so tests leave nothing behind.
+///
+/// A whole strategy-to-order test:
+///
+/// ```
+/// use hyperliquid_trading_bot::{config::Config, testing::{self, fixtures, MockTradingClient}};
+/// use rust_decimal::Decimal;
+/// use serde_json::json;
+/// use std::sync::Arc;
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() -> hyperliquid_trading_bot::Result<()> {
+/// let client = Arc::new(MockTradingClient::new(Decimal::from(10_000)));
+/// client.set_price("BTC", Decimal::from(40_000));
+///
+/// let mut config = Config::default();
+/// config.trading.dry_run = false;
+/// config.strategies.insert(
+///     "dca_btc".to_string(),
+///     fixtures::strategy("dca", "BTC", json!({ "investment_amount": "100" })),
+/// );
+///
+/// let bot = testing::mock_bot(config, client.clone()).await?;
+/// testing::run_bot_cycles(&bot, client.clock(), 3).await?;
+///
+/// // DCA buys once, then waits out its interval
+/// let orders = client.sent_orders();
+/// assert_eq!(orders.len(), 1);
+/// assert_eq!(orders[0].symbol, "BTC");
+/// assert_eq!(client.fills().len(), 1);
+/// # Ok(())
+/// # }
+/// ```
+pub async fn mock_bot(mut config: Config, client: Arc<MockTradingClient>) -> Result<TradingBot> {
+    config.storage.enabled = false;
+    let clock = Arc::new(client.clock().clone());
+    TradingBot::with_client(config, client, clock).await
+}
+
+/// Runs `n` trading cycles, moving `clock` on by the live loop's cycle
+/// interval after each, and stops at the first that fails
+pub async fn run_bot_cycles(bot: &TradingBot, clock: &SimulatedClock, n: usize) -> Result<()> {
+    let interval = chrono::Duration::from_std(bot.cycle_interval()).expect("cycle interval fits");
+    for _ in 0..n {
+        bot.run_cycle().await?;
+        clock.advance(interval);
+    }
+    Ok(())
+}
@@ -0,0 +1,50 @@
+use super::fixtures;
+use crate::{
+    error::Result,
+    market_stream::{MarketEvent, MarketStream},
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use std::collections::VecDeque;
+
+/// A [`MarketStream`] that plays back the events it's given, then ends
+#[derive(Debug, Default)]
+pub struct MockMarketStream {
+    events: VecDeque<MarketEvent>,
+}
+
+impl MockMarketStream {
+    pub fn new(events: impl IntoIterator<Item = MarketEvent>) -> Self {
+        Self {
+            events: events.into_iter().collect(),
+        }
+    }
+    
+    /// Tickers for `symbol`, one per price, `step` apart from `from`
+    pub fn from_prices(
+        symbol: &str,
+        from: DateTime<Utc>,
+        step: Duration,
+        prices: impl IntoIterator<Item = Decimal>,
+    ) -> Self {
+        Self::new(prices.into_iter().enumerate().map(|(i, price)| {
+            MarketEvent::Ticker(crate::models::MarketData {
+                timestamp: from + step * i as i32,
+                ..fixtures::market_data(symbol, price)
+            })
+        }))
+    }
+    
+    /// Adds an event after the others
+    pub fn push(&mut self, event: MarketEvent) {
+        self.events.push_back(event);
+    }
+}
+
+#[async_trait]
+impl MarketStream for MockMarketStream {
+    async fn next_event(&mut self) -> Result<Option<MarketEvent>> {
+        Ok(self.events.pop_front())
+    }
+}
@@ -0,0 +1,222 @@
+use crate::models::FundingSummary;
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// Running trade counts and PnL, saved each cycle so they survive restarts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeStats {
+    pub total_trades: u64,
+    pub successful_trades: u64,
+    pub failed_trades: u64,
+    /// Account PnL less the funding paid and any fees it leaves out
+    pub total_pnl: Decimal,
+    pub daily_pnl: Decimal,
+    /// Total PnL when the current day began; daily PnL counts from here
+    #[serde(default)]
+    pub daily_pnl_anchor: Option<Decimal>,
+    /// Highest equity seen so far
+    #[serde(default)]
+    pub equity_high_water_mark: Decimal,
+    pub last_reset_date: NaiveDate,
+    /// Funding paid net of funding received
+    #[serde(default)]
+    pub funding_paid: Decimal,
+    #[serde(default)]
+    pub daily_funding_paid: Decimal,
+    #[serde(default)]
+    pub funding_by_coin: BTreeMap<String, Decimal>,
+    /// When the last funding payment counted was settled
+    #[serde(default)]
+    pub funding_synced_to: Option<DateTime<Utc>>,
+    /// Trading fees paid, estimated until the exchange reports them
+    #[serde(default)]
+    pub fees_paid: Decimal,
+    /// The part of `fees_paid` the account PnL doesn't already include,
+    /// which is taken out of the total
+    #[serde(default)]
+    pub fees_outside_account_pnl: Decimal,
+}
+
+impl TradeStats {
+    pub fn new(today: NaiveDate) -> Self {
+        Self {
+            total_trades: 0,
+            successful_trades: 0,
+            failed_trades: 0,
+            total_pnl: Decimal::ZERO,
+            daily_pnl: Decimal::ZERO,
+            daily_pnl_anchor: None,
+            equity_high_water_mark: Decimal::ZERO,
+            last_reset_date: today,
+            funding_paid: Decimal::ZERO,
+            daily_funding_paid: Decimal::ZERO,
+            funding_by_coin: BTreeMap::new(),
+            funding_synced_to: None,
+            fees_paid: Decimal::ZERO,
+            fees_outside_account_pnl: Decimal::ZERO,
+        }
+    }
+}
+
+/// PnL tracking, updated once a cycle
+struct PnlStats {
+    total_pnl: Decimal,
+    daily_pnl: Decimal,
+    daily_pnl_anchor: Option<Decimal>,
+    equity_high_water_mark: Decimal,
+    last_reset_date: NaiveDate,
+    funding_paid: Decimal,
+    daily_funding_paid: Decimal,
+    funding_by_coin: BTreeMap<String, Decimal>,
+    funding_synced_to: Option<DateTime<Utc>>,
+    fees_paid: Decimal,
+    fees_outside_account_pnl: Decimal,
+}
+
+/// The bot's live [`TradeStats`]. Order outcomes are counted with atomics,
+/// so executing orders never waits on a reader; PnL sits behind a lock
+/// that's only held for a few field updates. The total is derived from
+/// the two outcome counters, so it always equals their sum.
+pub struct TradeStatsTracker {
+    successful_trades: AtomicU64,
+    failed_trades: AtomicU64,
+    pnl: Mutex<PnlStats>,
+}
+
+impl TradeStatsTracker {
+    pub fn new(stats: TradeStats) -> Self {
+        Self {
+            successful_trades: AtomicU64::new(stats.successful_trades),
+            failed_trades: AtomicU64::new(stats.failed_trades),
+            pnl: Mutex::new(PnlStats::from(&stats)),
+        }
+    }
+    
+    pub fn record_success(&self) {
+        self.successful_trades.fetch_add(1, Ordering::Relaxed);
+    }
+    
+    pub fn record_failure(&self) {
+        self.failed_trades.fetch_add(1, Ordering::Relaxed);
+    }
+    
+    /// Takes in the latest account PnL and equity. If `today` starts a new
+    /// day, returns the stats as the previous day ended, then counts daily
+    /// PnL from here. The funding paid so far, and fees the account PnL
+    /// doesn't include, are taken out of the PnL.
+    pub fn update_pnl(&self, total_pnl: Decimal, equity: Decimal, today: NaiveDate) -> Option<TradeStats> {
+        let mut pnl = self.pnl.lock().unwrap();
+        
+        // Reset daily PnL if new day
+        let finished_day = if today > pnl.last_reset_date {
+            let finished_day = self.combine(&pnl);
+            pnl.daily_pnl = Decimal::ZERO;
+            pnl.daily_pnl_anchor = None;
+            pnl.daily_funding_paid = Decimal::ZERO;
+            pnl.last_reset_date = today;
+            Some(finished_day)
+        } else {
+            None
+        };
+        
+        let total_pnl = total_pnl - pnl.funding_paid - pnl.fees_outside_account_pnl;
+        let anchor = *pnl.daily_pnl_anchor.get_or_insert(total_pnl);
+        pnl.total_pnl = total_pnl;
+        pnl.daily_pnl = total_pnl - anchor;
+        pnl.equity_high_water_mark = pnl.equity_high_water_mark.max(equity);
+        
+        finished_day
+    }
+    
+    /// Counts funding payments settled up to `synced_to`, taking them out of
+    /// the PnL. Only those settled on the current day count toward its
+    /// daily funding.
+    pub fn record_funding(&self, funding: &FundingSummary, synced_to: DateTime<Utc>) {
+        let mut pnl = self.pnl.lock().unwrap();
+        pnl.funding_paid += funding.total;
+        let today = funding.by_day.get(&pnl.last_reset_date).copied().unwrap_or_default();
+        pnl.daily_funding_paid += today;
+        for (coin, paid) in &funding.by_coin {
+            *pnl.funding_by_coin.entry(coin.clone()).or_default() += *paid;
+        }
+        pnl.funding_synced_to = Some(synced_to);
+        
+        pnl.total_pnl -= funding.total;
+        if let Some(anchor) = pnl.daily_pnl_anchor {
+            pnl.daily_pnl = pnl.total_pnl - anchor;
+        }
+    }
+    
+    /// Counts a trading fee, or a correction to one estimated earlier.
+    /// Unless the account PnL already includes it, as a paper account's
+    /// does, it's taken out of the PnL.
+    pub fn record_fee(&self, fee: Decimal, in_account_pnl: bool) {
+        let mut pnl = self.pnl.lock().unwrap();
+        pnl.fees_paid += fee;
+        if in_account_pnl {
+            return;
+        }
+        pnl.fees_outside_account_pnl += fee;
+        pnl.total_pnl -= fee;
+        if let Some(anchor) = pnl.daily_pnl_anchor {
+            pnl.daily_pnl = pnl.total_pnl - anchor;
+        }
+    }
+    
+    pub fn snapshot(&self) -> TradeStats {
+        let pnl = self.pnl.lock().unwrap();
+        self.combine(&pnl)
+    }
+    
+    /// Replaces everything, e.g. with an imported snapshot
+    pub fn restore(&self, stats: TradeStats) {
+        let mut pnl = self.pnl.lock().unwrap();
+        self.successful_trades.store(stats.successful_trades, Ordering::Relaxed);
+        self.failed_trades.store(stats.failed_trades, Ordering::Relaxed);
+        *pnl = PnlStats::from(&stats);
+    }
+    
+    fn combine(&self, pnl: &PnlStats) -> TradeStats {
+        let successful_trades = self.successful_trades.load(Ordering::Relaxed);
+        let failed_trades = self.failed_trades.load(Ordering::Relaxed);
+        
+        TradeStats {
+            total_trades: successful_trades + failed_trades,
+            successful_trades,
+            failed_trades,
+            total_pnl: pnl.total_pnl,
+            daily_pnl: pnl.daily_pnl,
+            daily_pnl_anchor: pnl.daily_pnl_anchor,
+            equity_high_water_mark: pnl.equity_high_water_mark,
+            last_reset_date: pnl.last_reset_date,
+            funding_paid: pnl.funding_paid,
+            daily_funding_paid: pnl.daily_funding_paid,
+            funding_by_coin: pnl.funding_by_coin.clone(),
+            funding_synced_to: pnl.funding_synced_to,
+            fees_paid: pnl.fees_paid,
+            fees_outside_account_pnl: pnl.fees_outside_account_pnl,
+        }
+    }
+}
+
+impl From<&TradeStats> for PnlStats {
+    fn from(stats: &TradeStats) -> Self {
+        Self {
+            total_pnl: stats.total_pnl,
+            daily_pnl: stats.daily_pnl,
+            daily_pnl_anchor: stats.daily_pnl_anchor,
+            equity_high_water_mark: stats.equity_high_water_mark,
+            last_reset_date: stats.last_reset_date,
+            funding_paid: stats.funding_paid,
+            daily_funding_paid: stats.daily_funding_paid,
+            funding_by_coin: stats.funding_by_coin.clone(),
+            funding_synced_to: stats.funding_synced_to,
+            fees_paid: stats.fees_paid,
+            fees_outside_account_pnl: stats.fees_outside_account_pnl,
+        }
+    }
+}
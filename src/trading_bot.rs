@@ -1,82 +1,356 @@
 use crate::{
-    api::{HyperliquidClient, WebSocketClient},
-    config::Config,
-    error::{Error, Result},
-    models::{AccountInfo, BotStatus, MarketData, Order, OrderSide, OrderType, Position, RiskMetrics, StrategySignal},
-    strategies::{DCAStrategy, GridStrategy, MomentumStrategy, Strategy},
-    utils::{log_trade_execution, log_position_update, sleep_seconds},
+    analysis::{self, AnalysisOutcome, AnalysisTracker},
+    api::{
+        client::TradingClient,
+        frames::WsEvent,
+        types::{self, Candle, UserFill},
+        HyperliquidClient, MarketDataCache,
+    },
+    backtest::data,
+    candle_feed::CandleFeed,
+    chaos::{Chaos, ChaosClient, ChaosStats},
+    clock::{Clock, SystemClock},
+    config::{Config, ExecutionTransport, MarginMode, Network, SweepMethod, WatchdogAction},
+    control::{Health, StatusSnapshot, StrategyStatus, WebSocketHealth},
+    costs::{CostModel, Liquidity, MarketConditions},
+    data_source::{DataSource, DataSourceSupervisor, SourceSwitch, StreamHealth},
+    equity,
+    error::{Error, Result, ResultExt},
+    events::{BotEvent, EventBus, StatusReporter},
+    intervention,
+    journal::{JournalEntry, TradeJournal},
+    market_feed::MarketFeed,
+    market_stream::MarketEvent,
+    metrics::{PerformanceWindow, Window},
+    models::{
+        new_cloid, AccountInfo, BotStatus, FeeSchedule, FundingSummary, MarketData, MarketTrade, Order, OrderSide, OrderState,
+        OrderStatus, OrderStatusInfo, OrderType, PositionSide, RiskMetrics, SignalAction, StrategySignal,
+        TWAP_MINUTES,
+    },
+    notifications::{NotificationEvent, Notifier},
+    paper::PaperExchange,
+    preflight,
+    price_cache::PriceCache,
+    reconcile::{self, ReconciliationReport},
+    recorder::MarketRecorder,
+    reporting::{self, ErrorContext},
+    runner::{self, RunMode, RunSummary},
+    scheduler::EvalScheduler,
+    shutdown::{self, ShutdownReport, ShutdownStep},
+    snapshot::{self, Snapshot},
+    storage::{self, keys, EquitySample, Storage, StorageWrite, StorageWriter, StrategyState},
+    strategies::Strategy,
+    trade_stats::{TradeStats, TradeStatsTracker},
+    utils::{log_order_cancelled, log_trade_execution, MarketSchedule},
+    validation::{CheckStatus, ValidationReport},
+    watchdog::{self, Heartbeat, StallReport},
 };
-use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use rust_decimal::Decimal;
-use std::collections::HashMap;
+use futures_util::{FutureExt, StreamExt};
+use rust_decimal::{Decimal, RoundingStrategy};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
-use tokio::sync::{mpsc, Mutex};
-use tracing::{debug, error, info, warn};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{watch, Mutex, RwLock};
+use tracing::{debug, error, info, info_span, warn, Instrument};
 use uuid::Uuid;
 
+/// Wait before retrying after a failed cycle
+const RETRY_DELAY: tokio::time::Duration = tokio::time::Duration::from_secs(10);
+/// Wait between the kill switch's attempts to cancel what's left open
+const KILL_SWITCH_CANCEL_RETRY: tokio::time::Duration = tokio::time::Duration::from_millis(500);
+
+/// Seconds between measurements of drawdowns and Sharpe ratios, each of
+/// which loads the equity curve and the exchange's portfolio history
+const PERFORMANCE_INTERVAL_SECS: i64 = 60;
+
 pub struct TradingBot {
     config: Config,
-    api_client: Arc<HyperliquidClient>,
-    ws_client: Arc<Mutex<WebSocketClient>>,
-    strategies: HashMap<String, Box<dyn Strategy + Send + Sync>>,
+    /// Labels a bot trading one of several accounts from one process
+    account: Option<String>,
+    api_client: Arc<dyn TradingClient + Send + Sync>,
+    /// The paper account dry runs trade, when the bot built it
+    paper: Option<Arc<PaperExchange>>,
+    /// Failures injected into a dry run, when `chaos` is enabled
+    chaos: Option<Arc<Chaos>>,
+    clock: Arc<dyn Clock>,
+    /// The websocket and the latest prices from it and REST fetches
+    feed: Arc<MarketFeed>,
+    /// The live client, when its orders and cancels are posted over `feed`'s
+    /// websocket
+    ws_orders: Option<Arc<HyperliquidClient>>,
+    /// Whether `feed` is shared with other accounts' bots, which then
+    /// outlive this one's shutdown
+    shared_feed: bool,
+    /// Set once the websocket is wanted; cycles reconnect it while it is
+    streaming: AtomicBool,
+    /// The user whose fills and order updates the websocket pushes,
+    /// applied as they come rather than on the next cycle's poll
+    stream_user: Option<String>,
+    /// The feed connection every open order was last polled over. While
+    /// it stays up, the stream tells of their changes and cycles only poll
+    /// for fees.
+    polled_connection: AtomicU64,
+    /// When open orders were last polled, which catches them up on every
+    /// fill until then
+    orders_polled_at: std::sync::Mutex<Option<DateTime<Utc>>>,
+    /// The feed connection the account's fills were last caught up on, and
+    /// when the stream was last heard from on it. On a new connection, the
+    /// fills since then that the stream never passed on are fetched over
+    /// REST.
+    fills_caught_up: std::sync::Mutex<(u64, Option<DateTime<Utc>>)>,
+    /// Fills recovered that way over the run
+    recovered_fills: AtomicU64,
+    /// Orders pushed over the websocket that this run didn't place, logged
+    /// the first time each is seen
+    unknown_orders: std::sync::Mutex<HashSet<u64>>,
+    /// Closed candles for the strategies analyzing candles
+    candles: CandleFeed,
+    /// Picks websocket or REST market data each cycle
+    data_source: std::sync::Mutex<DataSourceSupervisor>,
+    /// Run in name order each cycle
+    strategies: BTreeMap<String, RunningStrategy>,
+    /// Times each strategy's analysis and disables those that keep timing out
+    analysis: AnalysisTracker,
+    /// Decides when each strategy is next evaluated
+    scheduler: EvalScheduler,
     risk_manager: RiskManager,
-    is_running: Arc<Mutex<bool>>,
+    /// Prices dry-run fills the same way backtests do
+    cost_model: CostModel,
+    is_running: watch::Sender<bool>,
+    shutdown: watch::Sender<bool>,
+    /// Held by `run` for as long as the trading loop is going
+    run_lock: Mutex<()>,
     start_time: DateTime<Utc>,
-    trade_stats: Arc<Mutex<TradeStats>>,
+    trade_stats: TradeStatsTracker,
+    /// The journal, recorder, notifier and status reporter follow the
+    /// trading loop through this
+    events: EventBus,
+    event_stats: StatusReporter,
+    /// For alerts outside the event bus, e.g. the watchdog's
+    notifier: Notifier,
+    notifier_subscriber: tokio::task::JoinHandle<()>,
+    risk_limits_tripped: AtomicBool,
+    /// Stamped through each cycle for the watchdog
+    heartbeat: Heartbeat,
+    /// Once engaged, cycles no longer trade
+    kill_switch: AtomicBool,
+    storage: Option<StorageWriter>,
+    recorder: Option<MarketRecorder>,
+    last_account: Mutex<Option<(DateTime<Utc>, AccountInfo)>>,
+    /// Drawdowns and Sharpe ratios as the trading loop last measured them,
+    /// so status never loads the equity curve or asks the exchange
+    performance: std::sync::Mutex<Performance>,
+    /// When `performance` is next measured; the first account check
+    /// measures straight away
+    next_performance: std::sync::Mutex<Option<DateTime<Utc>>>,
+    /// The account's fee rates, fetched before the first order; the
+    /// configured costs stand in if the exchange can't say
+    fee_schedule: Mutex<Option<FeeSchedule>>,
+    /// When fills are next reconciled with the exchange; the first cycle
+    /// reconciles straight away
+    next_reconciliation: std::sync::Mutex<Option<DateTime<Utc>>>,
+    /// When funding payments are next fetched, just after the next funding
+    /// time; the first cycle fetches straight away
+    next_funding_sync: std::sync::Mutex<Option<DateTime<Utc>>>,
+    /// When profits are next swept; the first cycle sweeps straight away
+    next_profit_sweep: std::sync::Mutex<Option<DateTime<Utc>>>,
+    /// When the exchange cancels every order unless a cycle pushes it back;
+    /// none until the first cycle schedules it
+    cancel_deadline: std::sync::Mutex<Option<DateTime<Utc>>>,
+    /// Orders placed this run by client order id, with the strategy and
+    /// signal that placed each
+    orders_by_cloid: std::sync::Mutex<HashMap<String, TrackedOrder>>,
+}
+
+/// A strategy the bot runs. Analyses, which keep what they saw, and fills
+/// take the write lock, so it sees one at a time.
+struct RunningStrategy {
+    /// Fixed for the strategy's life, so it's readable without the lock
+    symbol: String,
+    /// Whether the strategy said it was enabled when last asked, readable
+    /// without waiting on an analysis
+    enabled: AtomicBool,
+    strategy: RwLock<Box<dyn Strategy + Send + Sync>>,
+}
+
+impl RunningStrategy {
+    fn new(strategy: Box<dyn Strategy + Send + Sync>) -> Self {
+        Self {
+            symbol: strategy.symbol().to_string(),
+            enabled: AtomicBool::new(strategy.is_enabled()),
+            strategy: RwLock::new(strategy),
+        }
+    }
+    
+    fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+    
+    /// Takes in whether `strategy`, this one's, is still enabled
+    fn refresh(&self, strategy: &(dyn Strategy + Send + Sync)) {
+        self.enabled.store(strategy.is_enabled(), Ordering::Relaxed);
+    }
 }
 
-struct TradeStats {
-    total_trades: u64,
-    successful_trades: u64,
-    failed_trades: u64,
-    total_pnl: Decimal,
-    daily_pnl: Decimal,
-    last_reset_date: DateTime<Utc>,
+/// Drawdowns and Sharpe ratios for status, measured in the trading loop
+#[derive(Debug, Clone, Copy, Default)]
+struct Performance {
+    current_drawdown: Decimal,
+    max_drawdown: Decimal,
+    sharpe_ratio: f64,
+    sharpe_ratio_7d: f64,
+    sharpe_ratio_30d: f64,
+}
+
+/// An order this run placed, as last seen on the exchange
+#[derive(Clone)]
+struct TrackedOrder {
+    strategy: String,
+    signal: StrategySignal,
+    order: Order,
+    /// Fee counted for its fills so far
+    fee: Decimal,
+    /// Whether `fee` is what the exchange charged rather than an estimate
+    fee_settled: bool,
+    /// Charged for the fills recorded so far
+    recorded_fee: Decimal,
 }
 
 impl TradingBot {
     pub async fn new(config: Config) -> Result<Self> {
-        info!("Initializing Hyperliquid Trading Bot");
+        match config.hyperliquid.network() {
+            Network::Mainnet => warn!(
+                "🔴 ==================== MAINNET: trading real funds at {} ====================",
+                config.hyperliquid.base_url
+            ),
+            Network::Testnet => info!(
+                "🧪 ==================== TESTNET: trading test funds at {} ====================",
+                config.hyperliquid.base_url
+            ),
+        }
         
         // Create API client
-        let api_client = Arc::new(HyperliquidClient::new(
-            config.hyperliquid.base_url.clone(),
-            config.hyperliquid.api_key.clone(),
-            config.hyperliquid.private_key.clone(),
-            config.hyperliquid.testnet,
-        ));
-        
-        // Create WebSocket client
-        let ws_client = Arc::new(Mutex::new(WebSocketClient::new(
-            config.hyperliquid.ws_url.clone(),
-        )));
+        let live_client = Arc::new(
+            HyperliquidClient::builder()
+                .base_url(&config.hyperliquid.base_url)
+                .ws_url(&config.hyperliquid.ws_url)
+                .credentials(&config.hyperliquid.api_key, &config.hyperliquid.private_key)
+                .account_address(config.hyperliquid.account_address.clone())
+                .testnet(config.hyperliquid.testnet)
+                .http(&config.hyperliquid.http)
+                .build()?
+                .with_vault_address(config.hyperliquid.vault().map(str::to_string))
+                .with_rate_limits(&config.hyperliquid.rate_limit)
+                .with_retries(config.trading.retry_attempts, std::time::Duration::from_millis(config.trading.retry_delay_ms))
+                .with_market_data_ttl(std::time::Duration::from_millis(config.hyperliquid.market_data_ttl_ms)),
+        );
+        let market_data = live_client.market_data_cache();
+        
+        // Say whose account this is before anything trades
+        if let Ok(address) = live_client.account_address() {
+            let kind = match config.hyperliquid.vault() {
+                None => "account",
+                Some(vault) if config.hyperliquid.subaccount.as_deref() == Some(vault) => "subaccount",
+                Some(_) => "vault",
+            };
+            info!("Trading {} {}", kind, address);
+        }
+        
+        // Dry runs leave the live account's settings alone
+        if !config.trading.dry_run {
+            apply_leverage(&live_client, &config).await?;
+        }
+        
+        // Open persistent storage; writes go through a background task
+        let storage = storage::open(&config.storage)?.map(StorageWriter::spawn);
+        
+        // Dry runs trade a paper account priced from the live market
+        let paper = if config.trading.dry_run {
+            Some(Arc::new(PaperExchange::open(&config, live_client.clone(), storage.clone())?))
+        } else {
+            None
+        };
+        // Live runs hear of their fills and orders over the websocket too,
+        // and may send their orders that way
+        let stream_user = live_client.account_address().ok().filter(|_| paper.is_none());
+        let ws_orders = match config.trading.execution_transport {
+            ExecutionTransport::Ws if paper.is_none() => Some(live_client.clone()),
+            _ => None,
+        };
+        let api_client: Arc<dyn TradingClient + Send + Sync> = match &paper {
+            Some(paper) => paper.clone(),
+            None => live_client,
+        };
+        
+        let mut bot = Self::build(config, api_client, paper, Arc::new(SystemClock), storage, Some(market_data)).await?;
+        if let Some(client) = ws_orders {
+            bot = bot.with_ws_orders(client);
+        }
+        Ok(match stream_user {
+            Some(user) => bot.with_account_stream(&user),
+            None => bot,
+        })
+    }
+    
+    /// A bot that reads the account and market and places orders through
+    /// `api_client`, e.g. a simulated exchange, and tells the time by `clock`
+    pub async fn with_client(
+        config: Config,
+        api_client: Arc<dyn TradingClient + Send + Sync>,
+        clock: Arc<dyn Clock>,
+    ) -> Result<Self> {
+        // Open persistent storage; writes go through a background task
+        let storage = storage::open(&config.storage)?.map(StorageWriter::spawn);
+        Self::build(config, api_client, None, clock, storage, None).await
+    }
+    
+    /// A dry run trading `paper`, telling the time by `clock`
+    pub async fn with_paper(config: Config, paper: Arc<PaperExchange>, clock: Arc<dyn Clock>) -> Result<Self> {
+        let storage = storage::open(&config.storage)?.map(StorageWriter::spawn);
+        Self::build(config, paper.clone(), Some(paper), clock, storage, None).await
+    }
+    
+    async fn build(
+        config: Config,
+        api_client: Arc<dyn TradingClient + Send + Sync>,
+        paper: Option<Arc<PaperExchange>>,
+        clock: Arc<dyn Clock>,
+        storage: Option<StorageWriter>,
+        market_data: Option<Arc<MarketDataCache>>,
+    ) -> Result<Self> {
+        info!("Initializing Hyperliquid Trading Bot");
+        
+        // Chaos runs break the simulated exchange, never a live one
+        let chaos = match (config.chaos.enabled, config.trading.dry_run) {
+            (true, true) => Some(Arc::new(Chaos::new(config.chaos.clone(), clock.clone()))),
+            (true, false) => {
+                warn!("Ignoring [chaos]: failures are only injected into dry runs");
+                None
+            }
+            (false, _) => None,
+        };
+        let api_client: Arc<dyn TradingClient + Send + Sync> = match &chaos {
+            Some(chaos) => Arc::new(ChaosClient::new(api_client, chaos.clone())),
+            None => api_client,
+        };
+        
+        // Create WebSocket client, its mids superseding the REST client's
+        let feed = match market_data {
+            Some(cache) => MarketFeed::new(&config).with_market_data_cache(cache),
+            None => MarketFeed::new(&config),
+        };
+        let feed = Arc::new(feed);
         
         // Initialize strategies
-        let mut strategies: HashMap<String, Box<dyn Strategy + Send + Sync>> = HashMap::new();
+        let mut strategies: BTreeMap<String, Box<dyn Strategy + Send + Sync>> = BTreeMap::new();
         
         for (name, strategy_config) in &config.strategies {
             if strategy_config.enabled {
-                let strategy: Box<dyn Strategy + Send + Sync> = match strategy_config.strategy_type.as_str() {
-                    "dca" => {
-                        let mut dca = DCAStrategy::new(name.clone(), strategy_config.symbol.clone());
-                        dca.update_parameters(strategy_config.parameters.clone()).await?;
-                        Box::new(dca)
-                    }
-                    "grid" => {
-                        let mut grid = GridStrategy::new(name.clone(), strategy_config.symbol.clone());
-                        grid.update_parameters(strategy_config.parameters.clone()).await?;
-                        Box::new(grid)
-                    }
-                    "momentum" => {
-                        let mut momentum = MomentumStrategy::new(name.clone(), strategy_config.symbol.clone());
-                        momentum.update_parameters(strategy_config.parameters.clone()).await?;
-                        Box::new(momentum)
-                    }
-                    _ => {
-                        warn!("Unknown strategy type: {}", strategy_config.strategy_type);
-                        continue;
-                    }
+                let Some(strategy) = crate::strategies::create(name, strategy_config, &config).await? else {
+                    warn!("Unknown strategy type: {}", strategy_config.strategy_type);
+                    continue;
                 };
                 
                 strategies.insert(name.clone(), strategy);
@@ -85,212 +359,2316 @@ impl TradingBot {
         }
         
         // Initialize risk manager
-        let risk_manager = RiskManager::new(config.risk_management.clone());
+        let risk_manager = RiskManager::new(
+            config.risk_management.clone(),
+            MarketSchedule::new(config.schedule.clone()),
+        );
+        
+        let cost_model = CostModel::from_config(&config);
+        
+        // Observers follow the trading loop on their own tasks
+        let events = EventBus::new(config.events.capacity);
+        let event_stats = StatusReporter::new();
+        events.spawn(event_stats.clone());
+        
+        // Initialize notification channels
+        let notifier = Notifier::from_config(&config.notifications);
+        let notifier_subscriber = events.spawn(notifier.clone());
+        
+        // Open the trade journal
+        if let Some(journal) = TradeJournal::from_config(&config.journal)? {
+            events.spawn(journal);
+        }
+        
+        // Start the market data recorder
+        let recorder = MarketRecorder::from_config(&config.recorder)?;
+        if let Some(recorder) = &recorder {
+            events.spawn(recorder.clone());
+        }
+        
+        // Pick up strategy state and trade stats where the last run left them
+        let mut saved_stats = None;
+        if let Some(storage) = &storage {
+            restore_strategy_states(storage.storage().as_ref(), &mut strategies);
+            saved_stats = match storage.storage().get_kv(keys::TRADE_STATS) {
+                Ok(saved) => saved.and_then(|saved| {
+                    serde_json::from_str(&saved).map_err(|e| warn!("Ignoring saved trade stats: {}", e)).ok()
+                }),
+                Err(e) => {
+                    warn!("Failed to load trade stats: {}", e);
+                    None
+                }
+            };
+        }
         
         // Initialize trade stats
-        let trade_stats = Arc::new(Mutex::new(TradeStats {
-            total_trades: 0,
-            successful_trades: 0,
-            failed_trades: 0,
-            total_pnl: Decimal::ZERO,
-            daily_pnl: Decimal::ZERO,
-            last_reset_date: Utc::now().date_naive(),
-        }));
+        let trade_stats = TradeStatsTracker::new(
+            saved_stats.unwrap_or_else(|| TradeStats::new(clock.now().date_naive())),
+        );
+        
+        let data_source = DataSourceSupervisor::new(&config.data_source);
+        let analysis = AnalysisTracker::new(config.analysis.max_consecutive_timeouts);
+        let scheduler = EvalScheduler::new(&config.scheduler);
+        for name in strategies.keys() {
+            scheduler.add(name, min_eval_interval(&config, name));
+        }
         
         Ok(Self {
             config,
+            account: None,
             api_client,
-            ws_client,
-            strategies,
+            paper,
+            chaos,
+            start_time: clock.now(),
+            clock,
+            feed,
+            ws_orders: None,
+            shared_feed: false,
+            streaming: AtomicBool::new(false),
+            stream_user: None,
+            polled_connection: AtomicU64::new(0),
+            orders_polled_at: std::sync::Mutex::new(None),
+            fills_caught_up: std::sync::Mutex::new((0, None)),
+            recovered_fills: AtomicU64::new(0),
+            unknown_orders: std::sync::Mutex::new(HashSet::new()),
+            candles: CandleFeed::default(),
+            data_source: std::sync::Mutex::new(data_source),
+            analysis,
+            scheduler,
+            strategies: strategies.into_iter().map(|(name, strategy)| (name, RunningStrategy::new(strategy))).collect(),
             risk_manager,
-            is_running: Arc::new(Mutex::new(false)),
-            start_time: Utc::now(),
+            cost_model,
+            is_running: watch::channel(false).0,
+            shutdown: watch::channel(false).0,
+            run_lock: Mutex::new(()),
             trade_stats,
+            events,
+            event_stats,
+            notifier,
+            notifier_subscriber,
+            risk_limits_tripped: AtomicBool::new(false),
+            heartbeat: Heartbeat::new(),
+            kill_switch: AtomicBool::new(false),
+            storage,
+            recorder,
+            last_account: Mutex::new(None),
+            performance: std::sync::Mutex::new(Performance::default()),
+            next_performance: std::sync::Mutex::new(None),
+            fee_schedule: Mutex::new(None),
+            next_reconciliation: std::sync::Mutex::new(None),
+            next_funding_sync: std::sync::Mutex::new(None),
+            next_profit_sweep: std::sync::Mutex::new(None),
+            cancel_deadline: std::sync::Mutex::new(None),
+            orders_by_cloid: std::sync::Mutex::new(HashMap::new()),
         })
     }
     
+    /// Adds a strategy that isn't built from the config, e.g. a custom one
+    pub fn with_strategy(mut self, strategy: Box<dyn Strategy + Send + Sync>) -> Self {
+        self.scheduler.add(strategy.name(), min_eval_interval(&self.config, strategy.name()));
+        self.strategies.insert(strategy.name().to_string(), RunningStrategy::new(strategy));
+        self
+    }
+    
+    /// Sends notifications through `notifier` instead of the configured
+    /// channels
+    pub fn with_notifier(mut self, notifier: Notifier) -> Self {
+        self.notifier_subscriber.abort();
+        self.notifier_subscriber = self.events.spawn(notifier.clone());
+        self.notifier = notifier;
+        self
+    }
+    
+    /// Labels the bot as trading `name`, one of several accounts in this
+    /// process, in its status and in the configured notification channels
+    pub fn with_account(self, name: &str) -> Self {
+        let notifier = Notifier::for_account(&self.config.notifications, name);
+        let mut bot = self.with_notifier(notifier);
+        bot.account = Some(name.to_string());
+        bot
+    }
+    
+    /// Streams market data through `feed`, shared with other accounts' bots,
+    /// instead of a websocket of its own. Its subscriptions add to the
+    /// others', and stopping this bot leaves it connected for them.
+    pub fn with_market_feed(mut self, feed: Arc<MarketFeed>) -> Self {
+        if let Some(client) = &self.ws_orders {
+            client.post_orders_over(Some(feed.post_channel()));
+        }
+        self.feed = feed;
+        self.shared_feed = true;
+        self
+    }
+    
+    /// Posts `client`'s orders and cancels over the feed's websocket while
+    /// it's up
+    fn with_ws_orders(mut self, client: Arc<HyperliquidClient>) -> Self {
+        client.post_orders_over(Some(self.feed.post_channel()));
+        self.ws_orders = Some(client);
+        self
+    }
+    
+    /// Applies `user`'s fills and order updates as the websocket pushes
+    /// them, so a filled order's strategy hears of it straight away and
+    /// cycles stop polling orders while the stream is up
+    pub fn with_account_stream(mut self, user: &str) -> Self {
+        self.stream_user = Some(user.to_lowercase());
+        self
+    }
+    
+    /// The account this bot trades, when it's one of several
+    pub fn account(&self) -> Option<&str> {
+        self.account.as_deref()
+    }
+    
     pub async fn start(&self) -> Result<()> {
+        self.run(RunMode::Continuous).await.map(|_| ())
+    }
+    
+    /// Runs the pre-flight checks, which also connect the websocket, then
+    /// checks the account every `scheduler.account_interval_secs` and
+    /// evaluates strategies as they fall due, until `stop` is called or
+    /// `mode` says to finish. A single run evaluates every strategy right
+    /// after its one account check. Failed checks abort startup unless
+    /// `preflight.ignore_failures` is set, and a stuck loop ends the run
+    /// with an error if `watchdog.action` is `exit`.
+    pub async fn run(&self, mode: RunMode) -> Result<RunSummary> {
         info!("🚀 Starting trading bot");
+        let _running = self.run_lock.lock().await;
+        
+        let report = self.preflight().await;
+        if report.passed() {
+            info!("✈️ Pre-flight checks passed");
+        } else if self.config.preflight.ignore_failures {
+            for check in report.checks.iter().filter(|c| c.status == CheckStatus::Fail) {
+                warn!("✈️ Pre-flight check {} failed: {}", check.name, check.detail);
+            }
+            warn!("✈️ Starting despite {} failed pre-flight checks", report.failures());
+        } else {
+            let mut rendered = Vec::new();
+            report.write(&mut rendered)?;
+            return Err(Error::Config(format!(
+                "Pre-flight checks failed:\n{}",
+                String::from_utf8_lossy(&rendered)
+            )));
+        }
         
         // Set running flag
-        {
-            let mut is_running = self.is_running.lock().await;
-            *is_running = true;
+        self.is_running.send_replace(true);
+        
+        self.schedule_equity_downsampling().await;
+        self.spawn_event_logger();
+        
+        // Main trading loop, watched for stalls. Strategies are evaluated
+        // alongside it until it stops, finishing any evaluation under way.
+        self.heartbeat.stage("idle");
+        let (stop_evaluating, stopped) = watch::channel(false);
+        let cycles = async {
+            let summary = runner::run_cycles(
+                mode,
+                self.cycle_interval(),
+                RETRY_DELAY,
+                self.shutdown.subscribe(),
+                || async {
+                    if mode == RunMode::Once {
+                        self.trading_cycle().await
+                    } else {
+                        self.account_cycle().await
+                    }
+                },
+            )
+            .await;
+            stop_evaluating.send_replace(true);
+            summary
+        };
+        let evaluations = async {
+            if mode != RunMode::Once {
+                self.schedule_strategies(stopped).await;
+            }
+        };
+        let summary = tokio::select! {
+            (summary, ()) = async { tokio::join!(cycles, evaluations) } => summary,
+            stall = self.watchdog() => {
+                self.is_running.send_replace(false);
+                return Err(Error::Trading(format!("Trading loop stalled: {}", stall)));
+            }
+            _ = self.follow_account() => unreachable!("the account is followed for as long as the loop runs"),
+            _ = self.follow_candles() => unreachable!("candles are followed for as long as the loop runs"),
+            _ = self.follow_trades() => unreachable!("trades are followed for as long as the loop runs"),
+        };
+        
+        self.is_running.send_replace(false);
+        info!("Trading bot stopped after {} cycles ({} failed)", summary.cycles, summary.failed_cycles);
+        Ok(summary)
+    }
+    
+    /// Connects the websocket and streams tickers for the traded symbols
+    /// into the price cache. From then on each cycle checks the stream's
+    /// health, reconnecting it and falling back to REST while it's down.
+    pub async fn connect_websocket(&self) -> Result<()> {
+        self.streaming.store(true, Ordering::Relaxed);
+        if let Some(user) = &self.stream_user {
+            self.feed.stream_account(user);
+        }
+        for (symbol, interval) in self.candle_series().await {
+            self.candles.track(&symbol, &interval);
+            self.feed.stream_candles(&symbol, &interval);
+        }
+        for symbol in self.trade_symbols().await {
+            self.feed.stream_trades(&symbol);
+        }
+        self.feed.connect(self.feed_owner(), self.symbols()).await
+    }
+    
+    /// Checks the exchange, account and websocket are ready to trade
+    /// before the first cycle: REST reachability and latency, the
+    /// strategies' symbols and leverage, available margin, a first
+    /// websocket ticker and the clock skew against it. Connects the
+    /// websocket on the way; if that fails, cycles keep retrying it.
+    pub async fn preflight(&self) -> ValidationReport {
+        let mut report = ValidationReport::new();
+        preflight::check_exchange(&self.config, &*self.api_client, &mut report).await;
+        preflight::check_margin(&self.config.preflight, &*self.api_client, &mut report).await;
+        
+        match self.connect_websocket().await {
+            Ok(()) => {
+                if let Some(ws) = self.feed.handle() {
+                    let symbols = self.symbols();
+                    preflight::check_market_stream(&self.config.preflight, &ws, self.feed.prices(), &symbols, &mut report).await;
+                }
+            }
+            Err(e) => {
+                report.fail("websocket", format!("{}: {}", self.config.hyperliquid.ws_url, e));
+                report.skip("clock skew", "no websocket data");
+            }
+        }
+        
+        report
+    }
+    
+    /// The symbols the strategies trade
+    fn symbols(&self) -> BTreeSet<&str> {
+        self.strategies.values().map(|s| s.symbol.as_str()).collect()
+    }
+    
+    /// (symbol, interval) of each candle series a strategy analyzes
+    async fn candle_series(&self) -> BTreeSet<(String, String)> {
+        let mut series = BTreeSet::new();
+        for running in self.strategies.values() {
+            if let Some(interval) = running.strategy.read().await.candle_interval() {
+                series.insert((running.symbol.clone(), interval.to_string()));
+            }
+        }
+        series
+    }
+    
+    /// The symbols whose every trade a strategy wants
+    async fn trade_symbols(&self) -> BTreeSet<String> {
+        let mut symbols = BTreeSet::new();
+        for running in self.strategies.values() {
+            if running.strategy.read().await.wants_trades() {
+                symbols.insert(running.symbol.clone());
+            }
+        }
+        symbols
+    }
+    
+    /// Who this bot's subscriptions are for on a market feed shared with
+    /// other accounts' bots
+    fn feed_owner(&self) -> &str {
+        self.account.as_deref().unwrap_or_default()
+    }
+    
+    /// Stops streaming strategy `name`'s symbol once no other enabled
+    /// strategy trades it
+    async fn release_symbol(&self, name: &str) {
+        let Some(symbol) = self.strategies.get(name).map(|running| running.symbol.as_str()) else {
+            return;
+        };
+        for (other, running) in &self.strategies {
+            if other == name || running.symbol != symbol || self.analysis.is_disabled(other) {
+                continue;
+            }
+            if running.is_enabled() {
+                return;
+            }
+        }
+        
+        if let Err(e) = self.feed.unsubscribe(self.feed_owner(), symbol) {
+            warn!("Failed to unsubscribe from {} tickers: {}", symbol, e);
+        }
+    }
+    
+    /// Graceful stop: ends the trading loop after its current cycle, then
+    /// cancels orders (if configured), disconnects and flushes everything to
+    /// disk, giving up on whatever is left once `shutdown.deadline_secs` has
+    /// passed
+    pub async fn stop(&self) -> ShutdownReport {
+        info!("🛑 Stopping trading bot");
+        
+        self.is_running.send_replace(false);
+        self.shutdown.send_replace(true);
+        
+        let deadline = std::time::Duration::from_secs(self.config.shutdown.deadline_secs);
+        shutdown::run_steps(self.shutdown_steps(), deadline).await
+    }
+    
+    /// The steps `stop` runs, in order
+    pub fn shutdown_steps(&self) -> Vec<ShutdownStep<'_>> {
+        let mut steps = vec![ShutdownStep::new(
+            "trading loop",
+            async {
+                drop(self.run_lock.lock().await);
+                // Let the journal and recorder catch up before they're flushed
+                self.events.settled().await;
+                Ok(())
+            }
+            .boxed(),
+        )];
+        
+        if self.config.shutdown.cancel_open_orders && !self.config.trading.dry_run {
+            steps.push(ShutdownStep::new(
+                "cancel open orders",
+                async {
+                    let cancelled = self.api_client.cancel_all_orders(None).await?;
+                    info!("Cancelled {} open orders", cancelled);
+                    Ok(())
+                }
+                .boxed(),
+            ));
         }
         
-        // Connect to WebSocket
+        // Orders left resting on purpose shouldn't be cancelled from under
+        // the next run. When they're cancelled, the schedule stays in case
+        // that fails.
+        if self.config.trading.cancel_deadline_secs > 0
+            && !self.config.shutdown.cancel_open_orders
+            && !self.config.trading.dry_run
+            && self.paper.is_none()
         {
-            let mut ws_client = self.ws_client.lock().await;
-            ws_client.connect().await?;
+            steps.push(ShutdownStep::new(
+                "clear scheduled cancel",
+                async {
+                    if self.cancel_deadline.lock().unwrap().is_none() {
+                        return Ok(());
+                    }
+                    self.api_client.schedule_cancel(None).await?;
+                    *self.cancel_deadline.lock().unwrap() = None;
+                    info!("⏲️ Cleared the scheduled cancel; open orders stay on the book");
+                    Ok(())
+                }
+                .boxed(),
+            ));
         }
         
-        // Main trading loop
-        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(5));
+        // A shared feed is disconnected by whoever shares it
+        if !self.shared_feed {
+            steps.push(ShutdownStep::new("disconnect websocket", async { self.feed.disconnect().await }.boxed()));
+        }
+        
+        // Make sure queued writes reach disk before exiting
+        if let Some(storage) = &self.storage {
+            steps.push(ShutdownStep::new(
+                "flush storage",
+                async {
+                    storage.flush().await;
+                    Ok(())
+                }
+                .boxed(),
+            ));
+        }
+        if let Some(recorder) = &self.recorder {
+            steps.push(ShutdownStep::new(
+                "flush market recorder",
+                async {
+                    recorder.flush().await;
+                    Ok(())
+                }
+                .boxed(),
+            ));
+        }
+        
+        steps
+    }
+    
+    /// Runs one trading cycle outside the loop, as replays do: checks the
+    /// account, then evaluates every strategy its `eval_interval_secs`
+    /// allows, triggered or not
+    pub async fn run_cycle(&self) -> Result<()> {
+        self.trading_cycle().await
+    }
+    
+    /// Time between account checks, the trading loop's cycles
+    pub fn cycle_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.config.scheduler.account_interval_secs)
+    }
+    
+    /// Watches the trading loop's heartbeat. When it has been silent for
+    /// `watchdog.stall_cycles` cycle intervals, logs where the loop is stuck,
+    /// sends a critical notification and, per `watchdog.action`, engages the
+    /// kill switch or resolves so the caller can stop. Otherwise it waits for
+    /// the loop to recover and keeps watching. Never resolves while the
+    /// watchdog is disabled.
+    pub async fn watchdog(&self) -> StallReport {
+        let config = &self.config.watchdog;
+        if !config.enabled {
+            return std::future::pending().await;
+        }
         
-        while *self.is_running.lock().await {
-            interval.tick().await;
+        let stall_after = self.cycle_interval() * config.stall_cycles;
+        loop {
+            let stall = watchdog::stalled(&self.heartbeat, stall_after).await;
+            error!(
+                event = "watchdog_stall",
+                cycle = stall.cycle,
+                stage = %stall.stage,
+                in_flight = stall.in_flight.as_deref().unwrap_or("none"),
+                "⏱️ Trading loop stalled: {}",
+                stall
+            );
+            self.notifier.notify(NotificationEvent::WatchdogStall {
+                silent_secs: stall.silent_for.as_secs(),
+                stage: stall.stage.clone(),
+                in_flight: stall.in_flight.clone(),
+            });
             
-            if let Err(e) = self.trading_cycle().await {
-                error!("Error in trading cycle: {}", e);
-                sleep_seconds(10).await; // Wait before retrying
+            match config.action {
+                WatchdogAction::Alert => {}
+                WatchdogAction::KillSwitch => {
+                    self.engage_kill_switch(&format!("trading loop stalled in {}", stall.stage)).await
+                }
+                WatchdogAction::Exit => return stall,
             }
+            
+            watchdog::resumed(&self.heartbeat, self.cycle_interval()).await;
+            info!("⏱️ Trading loop resumed");
         }
-        
-        info!("Trading bot stopped");
-        Ok(())
     }
     
-    pub async fn stop(&self) {
-        info!("🛑 Stopping trading bot");
+    /// Stops all trading: cancels open orders and makes every later cycle
+    /// skip its strategies. Stays engaged until the bot restarts.
+    pub async fn engage_kill_switch(&self, reason: &str) {
+        if self.kill_switch.swap(true, Ordering::Relaxed) {
+            return;
+        }
         
-        let mut is_running = self.is_running.lock().await;
-        *is_running = false;
+        error!("🛑 Kill switch engaged: {}", reason);
+        reporting::report_kill_switch(reason);
+        self.notifier.notify(NotificationEvent::KillSwitch {
+            engaged: true,
+            reason: reason.to_string(),
+        });
         
-        // Disconnect WebSocket
-        if let Ok(mut ws_client) = self.ws_client.try_lock() {
-            let _ = ws_client.disconnect().await;
+        // The exchange may be what's hanging, so don't wait on it forever,
+        // but go round again for orders a failed attempt left behind
+        let mut cancelled = 0;
+        let mut last_failure = None;
+        let cancel = async {
+            loop {
+                match intervention::cancel_all(&*self.api_client, None).await {
+                    Ok(report) => {
+                        cancelled += report.outcomes.len() - report.failures();
+                        if report.failures() == 0 {
+                            return;
+                        }
+                        warn!("🛑 {} of {} cancels failed, trying again", report.failures(), report.outcomes.len());
+                        last_failure = Some(format!("{} of {} cancels failed", report.failures(), report.outcomes.len()));
+                    }
+                    Err(e) => {
+                        warn!("🛑 Failed to cancel open orders, trying again: {}", e);
+                        last_failure = Some(format!("Failed to cancel open orders: {}", e));
+                    }
+                }
+                tokio::time::sleep(KILL_SWITCH_CANCEL_RETRY).await;
+            }
+        };
+        let finished = tokio::time::timeout(RETRY_DELAY, cancel).await;
+        match finished {
+            Ok(()) => info!("🛑 Cancelled {} open orders", cancelled),
+            Err(_) => match last_failure {
+                Some(failure) => error!("🛑 Gave up cancelling open orders: {}", failure),
+                None => error!("🛑 Cancelling open orders timed out"),
+            },
         }
     }
     
+    pub fn kill_switch_engaged(&self) -> bool {
+        self.kill_switch.load(Ordering::Relaxed)
+    }
+    
+    /// One cycle outside the loop: the account check, then the strategies
+    /// their minimum intervals allow
     async fn trading_cycle(&self) -> Result<()> {
+        self.account_cycle().await?;
+        let due = self.scheduler.allowed(self.clock.now());
+        self.evaluate(due).await
+    }
+    
+    /// One account check under a fresh correlation id, which its log lines
+    /// carry as `cycle_id` and its errors as context
+    async fn account_cycle(&self) -> Result<()> {
+        let cycle_id = Uuid::new_v4();
+        self.heartbeat.start_cycle();
+        let result = async {
+            let result = self.check_account().await;
+            self.reconcile_if_due().await;
+            self.sweep_profits_if_due().await;
+            if result.is_ok() {
+                self.push_back_cancel_deadline_if_due().await;
+            }
+            result
+        }
+        .instrument(info_span!("cycle", cycle_id = %cycle_id))
+        .await
+        .with_context(|| ErrorContext::new().with_correlation_id(cycle_id));
+        self.heartbeat.stage("idle");
+        // Strategies waiting on a first account check can go now
+        self.scheduler.wake();
+        result
+    }
+    
+    /// Fetches the account, catches up on this run's orders and checks the
+    /// risk limits, which hold back strategy evaluations until they're met
+    async fn check_account(&self) -> Result<()> {
         debug!("Starting trading cycle");
         
+        if self.kill_switch_engaged() {
+            warn!("🛑 Kill switch engaged, skipping trading cycle");
+            return Ok(());
+        }
+        
+        self.heartbeat.stage("checking data source");
+        self.supervise_data_source().await;
+        
         // Get account info
-        let account_info = self.api_client.get_account_info().await?;
+        self.heartbeat.stage("fetching account");
+        let account_info = self
+            .heartbeat
+            .track("get_account_info", self.api_client.get_account_info())
+            .await?;
+        let now = self.clock.now();
+        let sample = EquitySample::from_account(&account_info, now);
+        self.publish(BotEvent::EquitySample(sample.clone()));
+        self.persist(StorageWrite::Equity(sample)).await;
+        *self.last_account.lock().await = Some((now, account_info.clone()));
+        self.measure_performance_if_due(now).await;
+        
+        // Cancel orders left resting longer than the order timeout
+        self.heartbeat.stage("cancelling stale orders");
+        self.cancel_stale_orders(now).await;
+        
+        // Catch up on what became of this run's orders
+        self.heartbeat.stage("polling orders");
+        self.poll_orders(now).await;
         
         // Check risk limits
+        self.heartbeat.stage("checking risk limits");
         if !self.risk_manager.check_risk_limits(&account_info).await? {
             warn!("Risk limits exceeded, skipping trading cycle");
+            
+            // Only publish the transition, not every skipped cycle
+            if !self.risk_limits_tripped.swap(true, Ordering::Relaxed) {
+                self.publish(BotEvent::RiskRejected {
+                    signal: None,
+                    reason: "Account risk limits exceeded; trading paused".to_string(),
+                });
+            }
             return Ok(());
         }
+        self.risk_limits_tripped.store(false, Ordering::Relaxed);
         
         // Update trade stats
-        self.update_trade_stats(&account_info).await;
+        self.update_trade_stats(&account_info, now).await;
         
-        // Run strategies
-        for (name, strategy) in &self.strategies {
-            if !strategy.is_enabled() {
+        Ok(())
+    }
+    
+    /// Evaluates strategies as they fall due until `stop` turns true: when a
+    /// candle they analyze closes, their symbol's mid moves far enough or
+    /// they've been idle too long. None are evaluated before the account's
+    /// first check. An evaluation under way always finishes.
+    async fn schedule_strategies(&self, mut stop: watch::Receiver<bool>) {
+        let mut events = self.feed.events();
+        let mut streaming = true;
+        loop {
+            let checked = self.last_account.lock().await.is_some();
+            let wake_at = self.scheduler.next_due().filter(|_| checked).map(|at| {
+                let wait = (at - self.clock.now()).to_std().unwrap_or_default();
+                tokio::time::Instant::now() + wait
+            });
+            tokio::select! {
+                biased;
+                _ = runner::shutdown_requested(&mut stop) => return,
+                _ = self.scheduler.changed() => {}
+                _ = runner::deadline(wake_at) => {}
+                event = events.recv(), if streaming => {
+                    match event {
+                        Ok(WsEvent::Market(MarketEvent::Ticker(data))) => self.mid_moved(&data.symbol, data.price),
+                        Ok(WsEvent::AllMids(mids)) => {
+                            for (symbol, mid) in mids {
+                                self.mid_moved(&symbol, mid);
+                            }
+                        }
+                        Ok(_) | Err(RecvError::Lagged(_)) => {}
+                        Err(RecvError::Closed) => streaming = false,
+                    }
+                    continue;
+                }
+            }
+            if !checked {
                 continue;
             }
             
-            debug!("Running strategy: {}", name);
-            
-            // Get market data for strategy symbol
-            let market_data = self.api_client.get_market_data(strategy.symbol()).await?;
-            
-            // Analyze with strategy
-            if let Some(signal) = strategy.analyze(&market_data).await? {
-                info!("Strategy {} generated signal: {:?}", name, signal.action);
-                
-                // Check if we should execute the signal
-                if self.should_execute_signal(&signal, &account_info).await? {
-                    if let Err(e) = self.execute_signal(&signal).await {
-                        error!("Failed to execute signal from {}: {}", name, e);
-                    }
-                }
+            let due = self.scheduler.due(self.clock.now());
+            if let Err(e) = self.evaluate(due).await {
+                error!("Error evaluating strategies: {}", e);
+                reporting::report_error(&e, &ErrorContext::new());
             }
         }
-        
-        Ok(())
     }
     
-    async fn should_execute_signal(&self, signal: &StrategySignal, account_info: &AccountInfo) -> Result<bool> {
-        // Check if we have enough balance
-        if signal.quantity * signal.price.unwrap_or(Decimal::ZERO) > account_info.available_balance {
-            warn!("Insufficient balance for signal execution");
-            return Ok(false);
-        }
-        
-        // Check risk limits
-        if !self.risk_manager.check_signal_risk(signal, account_info).await? {
-            warn!("Signal rejected by risk manager");
-            return Ok(false);
+    /// Hands `mid` to the schedules of the strategies trading `symbol`
+    fn mid_moved(&self, symbol: &str, mid: Decimal) {
+        for (name, running) in &self.strategies {
+            if running.symbol == symbol && self.scheduler.mid(name, mid) {
+                debug!("{} mid moved to {}, evaluating strategy {}", symbol, mid, name);
+            }
         }
-        
-        // Check confidence threshold
-        if signal.confidence < 0.5 {
-            warn!("Signal confidence too low: {:.2}", signal.confidence);
-            return Ok(false);
+    }
+    
+    /// Evaluates the strategies named in `due` under a fresh correlation id,
+    /// which its log lines carry as `cycle_id` and its errors as context
+    async fn evaluate(&self, due: Vec<String>) -> Result<()> {
+        if due.is_empty() {
+            return Ok(());
         }
-        
-        Ok(true)
+        let cycle_id = Uuid::new_v4();
+        let result = self
+            .evaluate_strategies(&due, cycle_id)
+            .instrument(info_span!("cycle", cycle_id = %cycle_id))
+            .await
+            .with_context(|| ErrorContext::new().with_correlation_id(cycle_id));
+        self.heartbeat.stage("idle");
+        result
     }
     
-    async fn execute_signal(&self, signal: &StrategySignal) -> Result<()> {
-        info!("Executing signal: {:?} {} {} at {:?}", 
-              signal.action, signal.quantity, signal.symbol, signal.price);
+    /// Runs the strategies named in `due` against the last account check,
+    /// unless it tripped the risk limits, and submits the signals they clear
+    async fn evaluate_strategies(&self, due: &[String], cycle_id: Uuid) -> Result<()> {
+        if self.kill_switch_engaged() || self.risk_limits_tripped.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+        let Some((_, account_info)) = self.last_account.lock().await.clone() else {
+            return Ok(());
+        };
         
-        if self.config.trading.dry_run {
-            info!("DRY RUN: Would execute trade");
+        // Sit out maintenance, blackout and funding windows
+        let now = self.clock.now();
+        if let Some(reason) = self.risk_manager.trading_paused(now) {
+            info!("⏸️ Trading paused: {}", reason);
             return Ok(());
         }
         
-        // Create order
-        let order = Order {
-            id: Uuid::new_v4().to_string(),
-            symbol: signal.symbol.clone(),
-            side: match signal.action {
-                crate::models::SignalAction::Buy => OrderSide::Buy,
-                crate::models::SignalAction::Sell => OrderSide::Sell,
-                _ => return Ok(()), // Skip hold/close signals
-            },
-            order_type: if signal.price.is_some() { OrderType::Limit } else { OrderType::Market },
-            quantity: signal.quantity,
-            price: signal.price,
-            status: crate::models::OrderStatus::Pending,
-            created_at: Utc::now(),
-            updated_at: None,
-            filled_quantity: Decimal::ZERO,
-            average_price: None,
-        };
+        // Fetch every due strategy's symbol up front, in one batch
+        let mut running_due = Vec::new();
+        for name in due {
+            let Some(running) = self.strategies.get(name) else {
+                continue;
+            };
+            if running.is_enabled() && !self.analysis.is_disabled(name) {
+                running_due.push((name, running));
+            }
+        }
+        let symbols: BTreeSet<&str> = running_due.iter().map(|(_, running)| running.symbol.as_str()).collect();
+        self.heartbeat.stage("fetching market data");
+        let market_data = self.market_data(&symbols.into_iter().collect::<Vec<_>>()).await?;
         
-        // Place order
-        match self.api_client.place_order(&order).await {
-            Ok(order_id) => {
-                log_trade_execution(&order.symbol, &order.side, order.quantity, order.price.unwrap_or(Decimal::ZERO), true);
-                
-                // Update trade stats
-                let mut stats = self.trade_stats.lock().await;
-                stats.total_trades += 1;
-                stats.successful_trades += 1;
+        // Run strategies, collecting the signals cleared to trade
+        let mut approved = Vec::new();
+        for (name, running) in running_due {
+            let mut strategy = running.strategy.write().await;
+            if !strategy.is_enabled() || self.analysis.is_disabled(name) {
+                continue;
             }
-            Err(e) => {
-                log_trade_execution(&order.symbol, &order.side, order.quantity, order.price.unwrap_or(Decimal::ZERO), false);
+            let Some(market_data) = market_data.get(&running.symbol).cloned() else {
+                warn!("No market data for {} this cycle, skipping strategy {}", running.symbol, name);
+                continue;
+            };
+            // Later mid moves are measured from the price it's evaluated at
+            self.scheduler.mid(name, market_data.price);
+            
+            let strategy_span = info_span!("strategy", strategy = %name, symbol = %running.symbol);
+            async {
+                debug!("Running strategy: {}", name);
+                self.publish(BotEvent::MarketData(market_data.clone()));
                 
-                // Update trade stats
-                let mut stats = self.trade_stats.lock().await;
-                stats.total_trades += 1;
-                stats.failed_trades += 1;
+                // Analyze with strategy
+                self.heartbeat.stage(format!("strategy {}: analyze", name));
+                let signal = self.analyze(name, &mut **strategy, &market_data).await;
+                running.refresh(&**strategy);
+                if let Some(signal) = signal? {
+                    info!("Strategy {} generated signal: {:?}", name, signal.action);
+                    self.publish(BotEvent::SignalGenerated(signal.clone()));
+                    
+                    // Check if we should execute the signal
+                    self.heartbeat.stage(format!("strategy {}: risk checks", name));
+                    if self.should_execute_signal(&signal, &account_info).await? {
+                        approved.push((name.as_str(), signal, market_data));
+                    }
+                }
                 
-                return Err(e);
+                Ok::<(), Error>(())
+            }
+            .instrument(strategy_span)
+            .await
+            .with_context(|| ErrorContext::new().with_strategy(name).with_symbol(&running.symbol))?;
+        }
+        
+        // Streamed prices may be all the signals saw; trading on them waits
+        // until the stream speaks again or the data source falls back to REST
+        if let Some(quiet) = self.quiet_feed() {
+            if !approved.is_empty() {
+                warn!("📡 WebSocket quiet for {}s, holding back {} orders", quiet.as_secs(), approved.len());
+            }
+            for (_, signal, _) in approved.drain(..) {
+                self.publish(BotEvent::SignalSuppressed {
+                    signal,
+                    reason: format!("websocket quiet for {}s", quiet.as_secs()),
+                });
             }
         }
         
+        self.heartbeat.stage("submitting orders");
+        self.submit_signals(approved, cycle_id).await;
+        
+        self.heartbeat.stage("saving strategy state");
+        self.save_strategy_states().await;
+        
         Ok(())
     }
     
-    async fn update_trade_stats(&self, account_info: &AccountInfo) {
-        let mut stats = self.trade_stats.lock().await;
-        
-        // Reset daily PnL if new day
-        let today = Utc::now().date_naive();
-        if today > stats.last_reset_date {
-            stats.daily_pnl = Decimal::ZERO;
-            stats.last_reset_date = today;
+    /// How long the websocket market data is coming from has been quiet,
+    /// once that's longer than `data_source.max_order_feed_age_secs`
+    fn quiet_feed(&self) -> Option<std::time::Duration> {
+        let limit = self.config.data_source.max_order_feed_age_secs;
+        if limit == 0 || self.data_source.lock().unwrap().active() != DataSource::WebSocket {
+            return None;
         }
-        
-        // Update PnL
-        stats.total_pnl = account_info.total_pnl;
-        stats.daily_pnl = account_info.total_pnl; // Simplified - would need proper daily tracking
+        self.feed.last_message_age().filter(|&age| age > std::time::Duration::from_secs(limit))
     }
     
-    pub async fn get_status(&self) -> BotStatus {
-        let is_running = *self.is_running.lock().await;
-        let uptime = Utc::now() - self.start_time;
-        let stats = self.trade_stats.lock().await;
+    /// Runs one strategy's analysis within its budget. A timeout counts as
+    /// no signal this cycle; too many in a row disable the strategy.
+    async fn analyze(
+        &self,
+        name: &str,
+        strategy: &mut (dyn Strategy + Send + Sync),
+        market_data: &MarketData,
+    ) -> Result<Option<StrategySignal>> {
+        let budget = self.config.analysis.timeout(name);
+        let (output, elapsed) = analysis::timed(budget, strategy.analyze(market_data)).await;
+        match self.analysis.record(name, elapsed, output.is_none()) {
+            AnalysisOutcome::Completed => {}
+            AnalysisOutcome::TimedOut => {
+                warn!(
+                    "🐢 Strategy {} didn't finish analyzing within its {}ms budget; no signal this cycle",
+                    name,
+                    budget.as_millis()
+                );
+            }
+            AnalysisOutcome::Disabled => {
+                let reason = format!(
+                    "analysis overran its {}ms budget {} times in a row",
+                    budget.as_millis(),
+                    self.config.analysis.max_consecutive_timeouts
+                );
+                error!("⏸️ Disabling strategy {}: {}", name, reason);
+                self.notifier.notify(NotificationEvent::StrategyDisabled {
+                    strategy: name.to_string(),
+                    reason,
+                });
+                self.release_symbol(name).await;
+            }
+        }
+        output.unwrap_or(Ok(None))
+    }
+    
+    /// Reconnects a dropped websocket and picks the cycle's data source,
+    /// backfilling what the stream missed when it comes back, fills
+    /// included
+    async fn supervise_data_source(&self) {
+        if !self.streaming.load(Ordering::Relaxed) {
+            return;
+        }
         
-        BotStatus {
-            is_running,
+        // A chaos outage keeps the websocket down until it's over
+        let outage = self.chaos.as_ref().is_some_and(|chaos| chaos.websocket_down());
+        if outage {
+            let _ = self.feed.disconnect().await;
+        }
+        
+        if !self.feed.is_connected() && !outage {
+            match self.connect_websocket().await {
+                Ok(()) => info!("📡 WebSocket reconnected"),
+                Err(e) => warn!("📡 WebSocket reconnect failed: {}", e),
+            }
+        }
+        self.recover_missed_fills().await;
+        
+        let health = StreamHealth {
+            connected: self.feed.is_connected(),
+            last_message_at: self.feed.last_message_at(),
+            reconnect_failures: self.feed.reconnect_failures(),
+        };
+        let now = self.clock.now();
+        let switch = self.data_source.lock().unwrap().check(&health, now);
+        
+        match switch {
+            Some(SourceSwitch::Degraded { reason }) => {
+                warn!(event = "data_source_switch", source = "rest", "📡 Market data falling back to REST polling: {}", reason);
+                self.publish(BotEvent::ConnectionStateChanged { source: DataSource::Rest, reason });
+            }
+            Some(SourceSwitch::Recovered { gap }) => {
+                info!(event = "data_source_switch", source = "websocket", "📡 Market data back on the websocket");
+                self.publish(BotEvent::ConnectionStateChanged {
+                    source: DataSource::WebSocket,
+                    reason: "websocket healthy".to_string(),
+                });
+                if let Some((from, to)) = gap {
+                    self.backfill(from, to).await;
+                }
+            }
+            None => {}
+        }
+    }
+    
+    /// Applies the account's fills the stream never passed on, when it's
+    /// back on a new connection after missing some. Those since the stream
+    /// was last heard from and the open orders last polled are fetched over
+    /// REST; any the stream did pass on, e.g. in the snapshot sent on
+    /// reconnecting, are known by hash and skipped, as the stream will skip
+    /// those recovered here. A failed fetch is tried again next cycle.
+    async fn recover_missed_fills(&self) {
+        if self.stream_user.is_none() || !self.feed.is_connected() {
+            return;
+        }
+        let connection = self.feed.connections();
+        let (caught_up, heard_at) = *self.fills_caught_up.lock().unwrap();
+        // A new connection hasn't necessarily been heard from yet
+        let heard_now = self.feed.last_message_at().or(heard_at);
+        if caught_up == 0 || caught_up == connection {
+            *self.fills_caught_up.lock().unwrap() = (connection, heard_now);
+            return;
+        }
+        
+        let polled_at = *self.orders_polled_at.lock().unwrap();
+        let since = heard_at.max(polled_at).unwrap_or(self.start_time);
+        let now = self.clock.now();
+        let mut fills = match self.api_client.get_fills_between(since, now).await {
+            Ok(fills) => fills,
+            Err(e) => {
+                warn!("📡 Failed to fetch fills missed while the websocket was down: {}", e);
+                return;
+            }
+        };
+        *self.fills_caught_up.lock().unwrap() = (connection, heard_now);
+        
+        fills.sort_by_key(|fill| fill.time);
+        let mut recovered = 0;
+        for fill in fills {
+            if self.feed.mark_fill_seen(&fill) {
+                self.apply_fill(&fill).await;
+                recovered += 1;
+            }
+        }
+        self.recovered_fills.fetch_add(recovered, Ordering::Relaxed);
+        info!(
+            event = "fills_recovered",
+            recovered,
+            "📡 Recovered {} fills missed while the websocket was down (since {})",
+            recovered,
+            since
+        );
+    }
+    
+    /// Fetches candles for the stretch the websocket missed, so the price
+    /// cache and recordings have no hole where REST polling bridged it
+    async fn backfill(&self, from: DateTime<Utc>, to: DateTime<Utc>) {
+        let symbols: Vec<String> = self.symbols().into_iter().map(str::to_string).collect();
+        let interval = &self.config.data_source.backfill_interval;
+        
+        let bars = match data::fetch(&*self.api_client, &symbols, interval, from, to).await {
+            Ok(bars) => bars,
+            Err(e) => {
+                warn!("Failed to backfill market data from {} to {}: {}", from, to, e);
+                return;
+            }
+        };
+        
+        info!("📡 Backfilled {} bars from {} to {}", bars.len(), from, to);
+        let now = self.clock.now();
+        for bar in bars {
+            self.publish(BotEvent::CandleClosed {
+                interval: interval.clone(),
+                bar: bar.clone(),
+            });
+            self.feed.prices().update(bar, now);
+        }
+    }
+    
+    /// Market data for each of `symbols`: the streamed price while the
+    /// websocket is the active source and has a fresh one, polled over REST
+    /// in one concurrent batch otherwise. Symbols whose poll failed are left
+    /// out.
+    async fn market_data(&self, symbols: &[&str]) -> Result<HashMap<String, MarketData>> {
+        let now = self.clock.now();
+        let (active, max_age) = {
+            let supervisor = self.data_source.lock().unwrap();
+            (supervisor.active(), supervisor.max_message_age())
+        };
+        
+        let mut market_data = HashMap::new();
+        let mut polled = Vec::new();
+        for &symbol in symbols {
+            if active == DataSource::WebSocket {
+                if let Some(cached) = self.feed.prices().fresh(symbol, now, max_age) {
+                    // Dry runs still fill resting orders against streamed prices
+                    if let Some(paper) = &self.paper {
+                        paper.update_market(&cached.market_data);
+                    }
+                    market_data.insert(symbol.to_string(), cached.market_data);
+                    continue;
+                }
+                debug!("No fresh streamed price for {}, polling REST", symbol);
+            }
+            polled.push(symbol);
+        }
+        if polled.is_empty() {
+            return Ok(market_data);
+        }
+        
+        let request = self.api_client.get_market_data_batch(&polled);
+        let batch = self.heartbeat.track(format!("get_market_data {}", polled.join(",")), request).await?;
+        for (symbol, data) in batch {
+            self.feed.prices().update(data.clone(), now);
+            market_data.insert(symbol, data);
+        }
+        Ok(market_data)
+    }
+    
+    /// Cancels the orders this run placed that have rested unfilled for
+    /// longer than the order timeout. Orders placed any other way are left
+    /// alone, and the exchange is only asked which are still open once one
+    /// of this run's is due.
+    async fn cancel_stale_orders(&self, now: DateTime<Utc>) {
+        if self.config.trading.order_timeout_seconds == 0 {
+            return;
+        }
+        
+        let timeout = chrono::Duration::seconds(self.config.trading.order_timeout_seconds as i64);
+        let stale: HashSet<String> = self
+            .orders_by_cloid
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|tracked| matches!(tracked.order.status, OrderStatus::Open | OrderStatus::PartiallyFilled))
+            .filter(|tracked| now - tracked.order.created_at >= timeout)
+            .map(|tracked| tracked.order.id.clone())
+            .collect();
+        if stale.is_empty() {
+            return;
+        }
+        
+        let open_orders = match self.heartbeat.track("get_open_orders", self.api_client.get_open_orders()).await {
+            Ok(orders) => orders,
+            Err(e) => {
+                warn!("Failed to list open orders to cancel stale ones: {}", e);
+                return;
+            }
+        };
+        for order in open_orders.iter().filter(|order| stale.contains(&order.id)) {
+            let cancel = self.api_client.cancel_order(&order.id);
+            match self.heartbeat.track(format!("cancel_order {}", order.id), cancel).await {
+                Ok(true) => {
+                    info!("⌛ Cancelled order {} for {} after {}s unfilled", order.id, order.symbol, timeout.num_seconds());
+                    self.publish(BotEvent::OrderCancelled {
+                        order_id: order.id.clone(),
+                        symbol: order.symbol.clone(),
+                        reason: format!("unfilled after {}s", timeout.num_seconds()),
+                    });
+                }
+                Ok(false) => debug!("Stale order {} was already gone", order.id),
+                Err(e) => warn!("Failed to cancel stale order {}: {}", order.id, e),
+            }
+        }
+    }
+    
+    /// Asks the exchange about each order this run placed that was open
+    /// when last seen, or whose fees are still an estimate, and updates it.
+    /// A partial fill only moves its filled quantity; once it's filled in
+    /// full, its strategy is told. Open orders are left to the stream while
+    /// it's been up since they were last polled.
+    async fn poll_orders(&self, now: DateTime<Utc>) {
+        let connection = self.feed.connections();
+        let streamed = self.order_stream_live(connection);
+        let pending: Vec<(String, TrackedOrder)> = self
+            .orders_by_cloid
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, tracked)| match tracked.order.status {
+                OrderStatus::Open | OrderStatus::PartiallyFilled => !streamed,
+                _ => !tracked.fee_settled,
+            })
+            .map(|(cloid, tracked)| (cloid.clone(), tracked.clone()))
+            .collect();
+        
+        for (cloid, tracked) in pending {
+            let request = self.api_client.get_order_status(&tracked.order.id);
+            let info = match self.heartbeat.track(format!("get_order_status {}", tracked.order.id), request).await {
+                Ok(info) => info,
+                Err(e) => {
+                    warn!("Failed to poll order {}: {}", tracked.order.id, e);
+                    continue;
+                }
+            };
+            if !tracked.fee_settled && info.state != OrderState::Resting {
+                self.settle_fee(&cloid, &tracked, info.fee);
+            }
+            
+            // Compared with the order as it is now: a streamed fill may have
+            // finished it while the poll was out
+            let (order, fill) = {
+                let mut orders = self.orders_by_cloid.lock().unwrap();
+                let Some(current) = orders.get_mut(&cloid) else {
+                    continue;
+                };
+                if !matches!(current.order.status, OrderStatus::Open | OrderStatus::PartiallyFilled) {
+                    continue;
+                }
+                let Some(order) = updated_order(&current.order, &info, now) else {
+                    continue;
+                };
+                // The fills since it was last seen, charged whatever the
+                // exchange reports beyond those already recorded
+                let fill = filled_since(&current.order, &order).map(|(quantity, price)| {
+                    let fee = (info.fee - current.recorded_fee).max(Decimal::ZERO);
+                    current.recorded_fee += fee;
+                    (quantity, price, fee)
+                });
+                current.order = order.clone();
+                (order, fill)
+            };
+            if let Some((quantity, price, fee)) = fill {
+                self.record_fill(self.fill_entry(&tracked.signal, &order, quantity, price, fee)).await;
+            }
+            self.order_updated(&tracked, order, now).await;
+        }
+        
+        if !streamed {
+            *self.orders_polled_at.lock().unwrap() = Some(self.clock.now());
+        }
+        
+        // Whatever the stream missed before this connection has been caught up on
+        if self.stream_user.is_some() && self.feed.is_connected() && self.feed.connections() == connection {
+            self.polled_connection.store(connection, Ordering::Relaxed);
+        }
+    }
+    
+    /// Whether every change to this run's open orders since they were last
+    /// polled has come over the stream: it's been on `connection` throughout
+    /// without falling behind
+    fn order_stream_live(&self, connection: u64) -> bool {
+        self.stream_user.is_some()
+            && self.feed.is_connected()
+            && self.polled_connection.load(Ordering::Relaxed) == connection
+    }
+    
+    /// Records `order`'s new state, telling its strategy once it has
+    /// filled in full
+    async fn order_updated(&self, tracked: &TrackedOrder, order: Order, now: DateTime<Utc>) {
+        debug!("Order {} is now {:?}, {} filled", order.id, order.status, order.filled_quantity);
+        if order.status == OrderStatus::Filled {
+            info!("Order {} for {} filled {} @ {:?}", order.id, order.symbol, order.filled_quantity, order.average_price);
+            if let Some(running) = self.strategies.get(&tracked.strategy) {
+                let price = order.average_price.or(order.price).unwrap_or(Decimal::ZERO);
+                running.strategy.write().await.on_fill(&tracked.signal, price, now);
+            }
+        }
+        self.persist(StorageWrite::Order {
+            strategy: tracked.strategy.clone(),
+            order,
+        }).await;
+    }
+    
+    /// Applies the fills and order updates the websocket pushes for as long
+    /// as the trading loop runs, and warns of the exchange's notifications.
+    /// Falling behind only costs market data; account events wait to be read.
+    async fn follow_account(&self) {
+        let Some(user) = &self.stream_user else {
+            return std::future::pending().await;
+        };
+        let mut events = self.feed.events();
+        loop {
+            match events.recv().await {
+                Ok(WsEvent::Fill { user: owner, fill }) if owner.eq_ignore_ascii_case(user) => self.apply_fill(&fill).await,
+                // Updates don't say whose order it is. Those of other accounts
+                // sharing the feed are unknown here, like any order placed elsewhere.
+                Ok(WsEvent::OrderUpdate { oid, cloid, status, filled_sz }) => {
+                    self.apply_order_update(oid, cloid.as_deref(), status, filled_sz).await
+                }
+                // Nor do notifications, so every account sharing the feed
+                // passes each one on
+                Ok(WsEvent::Notification(message)) => {
+                    warn!(event = "exchange_notification", "📣 Exchange notification: {}", message);
+                    self.notifier.notify(NotificationEvent::ExchangeNotification { message });
+                }
+                Ok(_) => {}
+                Err(RecvError::Lagged(skipped)) => debug!("📡 Account stream skipped {} market data messages", skipped),
+                Err(RecvError::Closed) => return std::future::pending().await,
+            }
+        }
+    }
+    
+    /// Adds a streamed fill to the order this run placed that it's for.
+    /// Once the order has filled in full, its strategy is told and its
+    /// estimated fee swapped for what the fills were charged.
+    async fn apply_fill(&self, fill: &UserFill) {
+        let now = self.clock.now();
+        let oid = fill.oid.to_string();
+        let (cloid, tracked) = {
+            let mut orders = self.orders_by_cloid.lock().unwrap();
+            let Some((cloid, tracked)) = orders
+                .iter_mut()
+                .find(|(cloid, tracked)| fill.cloid.as_deref() == Some(cloid.as_str()) || tracked.order.id == oid)
+            else {
+                debug!("Fill {} for order {} isn't for an order this run placed", fill.tid, oid);
+                return;
+            };
+            // Already finished, e.g. by a poll
+            if !matches!(tracked.order.status, OrderStatus::Open | OrderStatus::PartiallyFilled) {
+                return;
+            }
+            
+            let order = &tracked.order;
+            let filled_quantity = order.filled_quantity + fill.sz;
+            let average_price = match order.average_price {
+                Some(average) if !filled_quantity.is_zero() => {
+                    (average * order.filled_quantity + fill.px * fill.sz) / filled_quantity
+                }
+                _ => fill.px,
+            };
+            tracked.order = Order {
+                status: if filled_quantity >= order.quantity { OrderStatus::Filled } else { OrderStatus::PartiallyFilled },
+                filled_quantity,
+                average_price: Some(average_price),
+                updated_at: Some(now),
+                ..order.clone()
+            };
+            tracked.recorded_fee += fill.fee;
+            (cloid.clone(), tracked.clone())
+        };
+        
+        info!("📡 Fill for order {}: {} {} @ {}", oid, fill.sz, fill.coin, fill.px);
+        if tracked.order.status == OrderStatus::Filled && !tracked.fee_settled {
+            self.settle_fee(&cloid, &tracked, tracked.recorded_fee);
+        }
+        let entry = JournalEntry {
+            timestamp: fill.timestamp(),
+            realized_pnl: fill.closed_pnl,
+            ..self.fill_entry(&tracked.signal, &tracked.order, fill.sz, fill.px, fill.fee)
+        };
+        self.record_fill(entry).await;
+        self.order_updated(&tracked, tracked.order.clone(), now).await;
+    }
+    
+    /// Moves the order this run placed that `oid` is to `status`. Fills
+    /// carry the quantity, price and fee, so an update only decides what
+    /// they can't: that the order was cancelled or rejected. Orders this
+    /// run didn't place are logged once and then ignored.
+    async fn apply_order_update(&self, oid: u64, cloid: Option<&str>, status: OrderStatus, filled_sz: Decimal) {
+        let now = self.clock.now();
+        let id = oid.to_string();
+        let tracked = {
+            let mut orders = self.orders_by_cloid.lock().unwrap();
+            let Some((_, tracked)) = orders
+                .iter_mut()
+                .find(|(known, tracked)| cloid == Some(known.as_str()) || tracked.order.id == id)
+            else {
+                drop(orders);
+                if self.unknown_orders.lock().unwrap().insert(oid) {
+                    info!("📡 Order {} isn't one this run placed, ignoring its updates", oid);
+                }
+                return;
+            };
+            if !matches!(status, OrderStatus::Cancelled | OrderStatus::Rejected)
+                || !matches!(tracked.order.status, OrderStatus::Open | OrderStatus::PartiallyFilled)
+            {
+                debug!("Order {} reported {:?}, {} filled", oid, status, filled_sz);
+                return;
+            }
+            
+            tracked.order = Order {
+                status,
+                filled_quantity: tracked.order.filled_quantity.max(filled_sz),
+                updated_at: Some(now),
+                ..tracked.order.clone()
+            };
+            tracked.clone()
+        };
+        
+        let order = &tracked.order;
+        info!("📡 Order {} for {} is now {:?}, {} filled", order.id, order.symbol, order.status, order.filled_quantity);
+        if order.status == OrderStatus::Cancelled {
+            let reason = "cancelled on the exchange";
+            log_order_cancelled(&order.symbol, &order.side, order.quantity, order.filled_quantity, reason);
+            self.publish(BotEvent::OrderCancelled {
+                order_id: order.id.clone(),
+                symbol: order.symbol.clone(),
+                reason: reason.to_string(),
+            });
+        } else {
+            log_trade_execution(&order.symbol, &order.side, order.quantity, order.price.unwrap_or(Decimal::ZERO), false);
+        }
+        self.order_updated(&tracked, tracked.order.clone(), now).await;
+    }
+    
+    /// Hands each candle to the strategies analyzing its series as it
+    /// closes, for as long as the trading loop runs. Each series starts out
+    /// with the closed candles fetched over REST, and is fetched again to
+    /// fill the gap after falling behind the stream.
+    async fn follow_candles(&self) {
+        let series = self.candles.tracked();
+        if series.is_empty() {
+            return std::future::pending().await;
+        }
+        // Subscribed first, so nothing closes unseen while fetching
+        let mut events = self.feed.events();
+        for (symbol, interval) in &series {
+            self.fetch_candles(symbol, interval).await;
+        }
+        
+        loop {
+            match events.recv().await {
+                Ok(WsEvent::Candle(update)) => {
+                    if let Some(candle) = self.candles.update(&update) {
+                        self.candle_closed(&update.s, &update.i, &candle).await;
+                    }
+                }
+                Ok(_) => {}
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("🕯️ Fell behind the websocket, skipped {} messages; fetching candles again", skipped);
+                    for (symbol, interval) in &series {
+                        self.fetch_candles(symbol, interval).await;
+                    }
+                }
+                Err(RecvError::Closed) => return std::future::pending().await,
+            }
+        }
+    }
+    
+    /// Adds the closed candles of `symbol` at `interval` the exchange has
+    /// and the series doesn't, up to its window
+    async fn fetch_candles(&self, symbol: &str, interval: &str) {
+        let Ok(duration) = types::interval_duration(interval) else {
+            return;
+        };
+        let now = self.clock.now();
+        let start = now - duration * self.candles.window() as i32;
+        let fetched = self
+            .api_client
+            .get_candles(symbol, interval, start.timestamp_millis().max(0) as u64, now.timestamp_millis() as u64)
+            .await;
+        match fetched {
+            Ok(candles) => {
+                for candle in self.candles.seed(symbol, interval, candles, now) {
+                    self.candle_closed(symbol, interval, &candle).await;
+                }
+            }
+            Err(e) => warn!("Failed to fetch {} {} candles, waiting on the stream: {}", symbol, interval, e),
+        }
+    }
+    
+    async fn candle_closed(&self, symbol: &str, interval: &str, candle: &Candle) {
+        let Ok(duration) = types::interval_duration(interval) else {
+            return;
+        };
+        let bar = data::candle_to_market_data(symbol, candle, duration);
+        for (name, running) in self.strategies.iter().filter(|(_, running)| running.symbol == symbol) {
+            let mut strategy = running.strategy.write().await;
+            if strategy.candle_interval() == Some(interval) {
+                strategy.on_candle(&bar);
+                self.scheduler.trigger(name);
+            }
+        }
+    }
+    
+    /// Hands each trade on a symbol to the strategies that want its trades,
+    /// for as long as the trading loop runs
+    async fn follow_trades(&self) {
+        let symbols = self.trade_symbols().await;
+        if symbols.is_empty() {
+            return std::future::pending().await;
+        }
+        let mut events = self.feed.events();
+        
+        loop {
+            match events.recv().await {
+                Ok(WsEvent::Trade { coin, px, sz, side, time }) if symbols.contains(&coin) => {
+                    let trade = MarketTrade {
+                        symbol: coin,
+                        side,
+                        price: px,
+                        size: sz,
+                        timestamp: time,
+                    };
+                    for running in self.strategies.values().filter(|running| running.symbol == trade.symbol) {
+                        let mut strategy = running.strategy.write().await;
+                        if strategy.wants_trades() {
+                            strategy.on_trade(&trade);
+                        }
+                    }
+                }
+                Ok(_) => {}
+                // Trades aren't fetched again; what was skipped goes uncounted
+                Err(RecvError::Lagged(skipped)) => warn!("💱 Fell behind the websocket, skipped {} messages of trades", skipped),
+                Err(RecvError::Closed) => return std::future::pending().await,
+            }
+        }
+    }
+    
+    async fn should_execute_signal(&self, signal: &StrategySignal, account_info: &AccountInfo) -> Result<bool> {
+        // Check if we have enough balance
+        if signal.quantity * signal.price.unwrap_or(Decimal::ZERO) > account_info.available_balance {
+            warn!(event = "risk_rejection", reason = "insufficient_balance", "Insufficient balance for signal execution");
+            self.publish(BotEvent::RiskRejected {
+                signal: Some(signal.clone()),
+                reason: "insufficient balance".to_string(),
+            });
+            return Ok(false);
+        }
+        
+        // Check risk limits
+        if !self.risk_manager.check_signal_risk(signal, account_info).await? {
+            warn!(event = "risk_rejection", reason = "risk_manager", "Signal rejected by risk manager");
+            self.publish(BotEvent::RiskRejected {
+                signal: Some(signal.clone()),
+                reason: "rejected by risk manager".to_string(),
+            });
+            return Ok(false);
+        }
+        
+        // Check confidence threshold
+        if signal.confidence < 0.5 {
+            warn!(
+                event = "risk_rejection",
+                reason = "low_confidence",
+                confidence = signal.confidence,
+                "Signal confidence too low: {:.2}",
+                signal.confidence
+            );
+            self.publish(BotEvent::SignalSuppressed {
+                signal: signal.clone(),
+                reason: format!("confidence {:.2} below 0.5", signal.confidence),
+            });
+            return Ok(false);
+        }
+        
+        Ok(true)
+    }
+    
+    /// Executes approved signals, up to `trading.max_concurrent_orders` at
+    /// a time. Signals for one symbol may depend on each other, like an entry
+    /// and its bracket, so each symbol's go out one after another in
+    /// strategy order; only different symbols overlap. A failure is logged
+    /// against the strategy whose signal it was and doesn't stop the rest.
+    async fn submit_signals(&self, approved: Vec<(&str, StrategySignal, MarketData)>, cycle_id: Uuid) {
+        let mut by_symbol: BTreeMap<String, Vec<(&str, StrategySignal, MarketData)>> = BTreeMap::new();
+        for signal in approved {
+            by_symbol.entry(signal.1.symbol.clone()).or_default().push(signal);
+        }
+        
+        futures_util::stream::iter(by_symbol.into_values())
+            .for_each_concurrent(self.config.trading.max_concurrent_orders.max(1), |signals| async move {
+                for (name, signal, market_data) in signals {
+                    let strategy_span = info_span!("strategy", strategy = %name, symbol = %signal.symbol);
+                    let result = self
+                        .execute_signal(&signal, &market_data)
+                        .instrument(strategy_span)
+                        .await
+                        .with_context(|| {
+                            ErrorContext::new()
+                                .with_strategy(name)
+                                .with_symbol(&signal.symbol)
+                                .with_correlation_id(cycle_id)
+                        });
+                    if let Err(e) = result {
+                        error!("Failed to execute signal: {}", e);
+                        reporting::report_error(&e, &ErrorContext::new());
+                    }
+                }
+            })
+            .await;
+    }
+    
+    /// `signal` as an order closing its symbol's position, from the last
+    /// account fetched: the opposite side, for no more than the position
+    /// holds. `None` when there's no position to close.
+    async fn close_signal(&self, signal: &StrategySignal) -> Option<(OrderSide, StrategySignal)> {
+        let last_account = self.last_account.lock().await;
+        let (_, account) = last_account.as_ref()?;
+        let position = account
+            .positions
+            .iter()
+            .find(|position| position.symbol == signal.symbol && !position.size.is_zero())?;
+        let side = match position.side {
+            PositionSide::Long => OrderSide::Sell,
+            PositionSide::Short => OrderSide::Buy,
+        };
+        let size = position.size.abs();
+        let quantity = if signal.quantity.is_zero() { size } else { signal.quantity.min(size) };
+        Some((
+            side,
+            StrategySignal {
+                quantity,
+                reduce_only: true,
+                ..signal.clone()
+            },
+        ))
+    }
+    
+    async fn execute_signal(&self, signal: &StrategySignal, market_data: &MarketData) -> Result<()> {
+        info!("Executing signal: {:?} {} {} at {:?}", 
+              signal.action, signal.quantity, signal.symbol, signal.price);
+        
+        // Closes trade against the open position; holds don't trade at all
+        let closing;
+        let (side, signal) = match signal.action {
+            SignalAction::Close => match self.close_signal(signal).await {
+                Some((side, close)) => {
+                    closing = close;
+                    (side, &closing)
+                }
+                None => {
+                    self.publish(BotEvent::SignalSuppressed {
+                        signal: signal.clone(),
+                        reason: format!("no {} position to close", signal.symbol),
+                    });
+                    return Ok(());
+                }
+            },
+            _ => match signal_side(signal) {
+                Some(side) => (side, signal),
+                None => {
+                    self.publish(BotEvent::SignalSuppressed {
+                        signal: signal.clone(),
+                        reason: format!("{:?} signals don't place orders", signal.action),
+                    });
+                    return Ok(());
+                }
+            },
+        };
+        
+        // Create order. A TWAP has no price of its own; the exchange slices
+        // it into market orders.
+        let twap_minutes = signal.metadata.get(TWAP_MINUTES).and_then(|minutes| minutes.as_u64());
+        let order_type = match (twap_minutes, signal.price) {
+            (Some(minutes), _) => OrderType::Twap {
+                duration_minutes: minutes as u32,
+            },
+            (None, Some(_)) => OrderType::Limit,
+            (None, None) => OrderType::Market,
+        };
+        let order = Order {
+            id: Uuid::new_v4().to_string(),
+            symbol: signal.symbol.clone(),
+            side,
+            order_type,
+            quantity: signal.quantity,
+            price: signal.price.filter(|_| twap_minutes.is_none()),
+            status: crate::models::OrderStatus::Pending,
+            created_at: self.clock.now(),
+            updated_at: None,
+            filled_quantity: Decimal::ZERO,
+            average_price: None,
+            reduce_only: signal.reduce_only,
+            trigger_price: None,
+            is_take_profit: false,
+            cloid: Some(new_cloid()),
+        };
+        
+        // Without a paper account (the caller supplied the client) a dry run
+        // only prices the fill
+        if self.config.trading.dry_run && self.paper.is_none() {
+            info!("DRY RUN: Would execute trade");
+            let fill = self.cost_model.fill(
+                &order.side,
+                signal.quantity,
+                signal.price.unwrap_or(market_data.price),
+                Liquidity::Taker,
+                &MarketConditions::from(market_data),
+            );
+            let order = Order {
+                id: format!("dry-run-{}", Uuid::new_v4()),
+                cloid: None,
+                ..order
+            };
+            self.record_fill(self.fill_entry(signal, &order, signal.quantity, fill.price, fill.fee)).await;
+            self.trade_stats.record_fee(fill.fee, false);
+            return Ok(());
+        }
+        
+        // Place order
+        reporting::add_breadcrumb(
+            "order",
+            &format!("{:?} {} {} @ {:?}", order.side, order.quantity, order.symbol, order.price),
+        );
+        let order_span = info_span!("order", order_id = %order.id);
+        async {
+            let request = self.api_client.place_order(&order);
+            match self.heartbeat.track(format!("place_order {}", order.symbol), request).await {
+                Ok(order_id) => {
+                    log_trade_execution(&order.symbol, &order.side, order.quantity, order.price.unwrap_or(Decimal::ZERO), true);
+                    let placed = Order {
+                        id: order_id.clone(),
+                        status: crate::models::OrderStatus::Open,
+                        updated_at: Some(self.clock.now()),
+                        ..order.clone()
+                    };
+                    self.publish(BotEvent::OrderPlaced {
+                        strategy: signal.strategy_name.clone(),
+                        order: placed.clone(),
+                    });
+                    
+                    // Paper fills are known straight away, with their costs.
+                    // Other fees are estimated as taker fees until the order
+                    // is done, and fills are recorded as they're reported.
+                    let paper_fill = self
+                        .paper
+                        .as_ref()
+                        .and_then(|paper| paper.exchange().trades().into_iter().find(|trade| trade.order_id == order_id));
+                    let fee = match &paper_fill {
+                        Some(trade) => trade.fee,
+                        None => {
+                            let notional = order.quantity * signal.price.unwrap_or(market_data.price);
+                            self.fee_schedule().await.fee(notional, Liquidity::Taker)
+                        }
+                    };
+                    self.trade_stats.record_fee(fee, self.paper.is_some());
+                    
+                    let filled = paper_fill.as_ref().map(|trade| Order {
+                        status: OrderStatus::Filled,
+                        filled_quantity: trade.quantity,
+                        average_price: Some(trade.price),
+                        ..placed.clone()
+                    });
+                    let tracked = TrackedOrder {
+                        strategy: signal.strategy_name.clone(),
+                        signal: signal.clone(),
+                        order: filled.clone().unwrap_or_else(|| placed.clone()),
+                        fee,
+                        fee_settled: paper_fill.is_some(),
+                        recorded_fee: paper_fill.as_ref().map_or(Decimal::ZERO, |trade| trade.fee),
+                    };
+                    if let Some(cloid) = &placed.cloid {
+                        self.orders_by_cloid.lock().unwrap().insert(cloid.clone(), tracked.clone());
+                    }
+                    self.persist(StorageWrite::Order {
+                        strategy: signal.strategy_name.clone(),
+                        order: placed,
+                    }).await;
+                    
+                    if let (Some(trade), Some(filled)) = (paper_fill, filled) {
+                        let entry = JournalEntry {
+                            timestamp: trade.timestamp,
+                            realized_pnl: trade.realized_pnl,
+                            ..self.fill_entry(signal, &filled, trade.quantity, trade.price, trade.fee)
+                        };
+                        self.record_fill(entry).await;
+                        self.order_updated(&tracked, filled, self.clock.now()).await;
+                    }
+                }
+                Err(e) => {
+                    log_trade_execution(&order.symbol, &order.side, order.quantity, order.price.unwrap_or(Decimal::ZERO), false);
+                    
+                    match e.root() {
+                        // The book moved past the post-only price; nothing
+                        // failed, and the next signal is priced afresh
+                        Error::PostOnlyWouldCross => {
+                            info!("Post-only order for {} would have crossed the book; not placed", order.symbol);
+                            return Ok(());
+                        }
+                        // The exchange's own risk checks turned it down
+                        Error::InsufficientBalance | Error::MinOrderSize(_) => {
+                            warn!(event = "risk_rejection", reason = "exchange", "Exchange rejected the order: {}", e.root());
+                            self.publish(BotEvent::RiskRejected {
+                                signal: Some(signal.clone()),
+                                reason: format!("rejected by the exchange: {}", e.root()),
+                            });
+                        }
+                        _ => {}
+                    }
+                    
+                    // Update trade stats
+                    self.trade_stats.record_failure();
+                    
+                    return Err(e);
+                }
+            }
+            
+            Ok(())
+        }
+        .instrument(order_span)
+        .await
+    }
+    
+    /// Swaps the fee estimated for a finished order for what its fills were
+    /// charged
+    fn settle_fee(&self, cloid: &str, tracked: &TrackedOrder, fee: Decimal) {
+        if fee != tracked.fee {
+            debug!("Order {} paid {} in fees, {} estimated", tracked.order.id, fee, tracked.fee);
+            self.trade_stats.record_fee(fee - tracked.fee, self.paper.is_some());
+        }
+        if let Some(tracked) = self.orders_by_cloid.lock().unwrap().get_mut(cloid) {
+            tracked.fee = fee;
+            tracked.fee_settled = true;
+        }
+    }
+    
+    /// The account's fee rates, asked of the exchange the first time
+    async fn fee_schedule(&self) -> FeeSchedule {
+        let mut cached = self.fee_schedule.lock().await;
+        if let Some(schedule) = *cached {
+            return schedule;
+        }
+        
+        let schedule = match self.api_client.get_user_fees().await {
+            Ok(schedule) => {
+                info!("💱 Fee rates: maker {}, taker {}", schedule.maker_rate, schedule.taker_rate);
+                schedule
+            }
+            Err(e) => {
+                let schedule = self.cost_model.fee_schedule();
+                debug!("No fee rates from the exchange ({}), estimating from [costs]", e);
+                schedule
+            }
+        };
+        *cached = Some(schedule);
+        schedule
+    }
+    
+    /// The journal entry for a fill of `quantity` of `order` at `price`,
+    /// made now and closing nothing. Fills the exchange (or the paper
+    /// account) reports carry their own time and realized PnL.
+    fn fill_entry(&self, signal: &StrategySignal, order: &Order, quantity: Decimal, price: Decimal, fee: Decimal) -> JournalEntry {
+        JournalEntry {
+            timestamp: self.clock.now(),
+            strategy: signal.strategy_name.clone(),
+            symbol: order.symbol.clone(),
+            side: order.side.clone(),
+            quantity,
+            price,
+            fee,
+            realized_pnl: Decimal::ZERO,
+            order_id: order.id.clone(),
+            cloid: order.cloid.clone(),
+            dry_run: self.config.trading.dry_run,
+            reconciled: false,
+        }
+    }
+    
+    /// Journals a fill and counts it as a successful trade
+    async fn record_fill(&self, entry: JournalEntry) {
+        self.publish(BotEvent::OrderFilled(entry.clone()));
+        self.persist(StorageWrite::Fill(entry)).await;
+        self.trade_stats.record_success();
+    }
+    
+    /// Reconciles fills once `reconciliation.interval_secs` has passed since
+    /// the last time, whether or not the cycle traded
+    async fn reconcile_if_due(&self) {
+        if !self.config.reconciliation.enabled || self.config.trading.dry_run || self.storage.is_none() {
+            return;
+        }
+        
+        let now = self.clock.now();
+        {
+            let mut next = self.next_reconciliation.lock().unwrap();
+            if next.is_some_and(|next| now < next) {
+                return;
+            }
+            *next = Some(now + chrono::Duration::seconds(self.config.reconciliation.interval_secs as i64));
+        }
+        
+        self.heartbeat.stage("reconciling fills");
+        if let Err(e) = self.reconcile_fills().await {
+            warn!("Fill reconciliation failed: {}", e);
+        }
+    }
+    
+    /// Has the exchange cancel every order `trading.cancel_deadline_secs`
+    /// from now, once `cancel_deadline_refresh_secs` have passed since the
+    /// deadline was last set. Only successful cycles get here, so a bot that
+    /// crashes, hangs or keeps failing leaves nothing resting for long. Dry
+    /// runs and paper accounts have no orders on the exchange.
+    async fn push_back_cancel_deadline_if_due(&self) {
+        let config = &self.config.trading;
+        if config.cancel_deadline_secs == 0 || config.dry_run || self.paper.is_some() {
+            return;
+        }
+        
+        let now = self.clock.now();
+        let set_at = chrono::Duration::seconds(config.cancel_deadline_secs as i64);
+        let refresh = chrono::Duration::seconds(config.cancel_deadline_refresh_secs as i64);
+        if self.cancel_deadline.lock().unwrap().is_some_and(|deadline| now < deadline - set_at + refresh) {
+            return;
+        }
+        
+        self.heartbeat.stage("scheduling cancel");
+        let deadline = now + set_at;
+        match self
+            .heartbeat
+            .track("schedule_cancel", self.api_client.schedule_cancel(Some(deadline)))
+            .await
+        {
+            Ok(()) => {
+                debug!("⏲️ Open orders are cancelled at {} unless the bot checks in", deadline);
+                *self.cancel_deadline.lock().unwrap() = Some(deadline);
+            }
+            // Tried again next cycle; the old deadline still stands
+            Err(e) => warn!("⏲️ Failed to push back the scheduled cancel: {}", e),
+        }
+    }
+    
+    /// Sweeps profits once `profit_sweep.interval_hours` has passed since the
+    /// last time. Dry runs and paper accounts never sweep.
+    async fn sweep_profits_if_due(&self) {
+        if !self.config.profit_sweep.enabled || self.config.trading.dry_run || self.paper.is_some() {
+            return;
+        }
+        
+        let now = self.clock.now();
+        {
+            let mut next = self.next_profit_sweep.lock().unwrap();
+            if next.is_some_and(|next| now < next) {
+                return;
+            }
+            *next = Some(now + chrono::Duration::hours(self.config.profit_sweep.interval_hours as i64));
+        }
+        
+        self.heartbeat.stage("sweeping profits");
+        if let Err(e) = self.sweep_profits().await {
+            warn!("💸 Profit sweep failed: {}", e);
+        }
+    }
+    
+    /// Moves the USDC the account can withdraw beyond
+    /// `profit_sweep.threshold` to `profit_sweep.destination`, in whole
+    /// cents, and returns how much was moved. Refuses on dry runs and paper
+    /// accounts, whose balance isn't real.
+    pub async fn sweep_profits(&self) -> Result<Decimal> {
+        if self.config.trading.dry_run || self.paper.is_some() {
+            return Err(Error::Trading("Dry runs never sweep profits".to_string()));
+        }
+        let config = &self.config.profit_sweep;
+        let account = self
+            .heartbeat
+            .track("get_account_info", self.api_client.get_account_info())
+            .await?;
+        let amount = (account.available_balance - config.threshold).round_dp_with_strategy(2, RoundingStrategy::ToZero);
+        if amount <= Decimal::ZERO {
+            debug!(
+                "💸 Nothing to sweep: {} withdrawable, threshold {}",
+                account.available_balance, config.threshold
+            );
+            return Ok(Decimal::ZERO);
+        }
+        
+        info!("💸 Sweeping {} above the {} threshold to {}", amount, config.threshold, config.destination);
+        let sweep = match config.method {
+            SweepMethod::Transfer => self.api_client.usd_transfer(&config.destination, amount),
+            SweepMethod::Withdraw => self.api_client.withdraw(&config.destination, amount),
+        };
+        let result = self.heartbeat.track(format!("sweep {}", amount), sweep).await;
+        self.notifier.notify(NotificationEvent::ProfitSweep {
+            amount,
+            destination: config.destination.clone(),
+            error: result.as_ref().err().map(ToString::to_string),
+        });
+        result?;
+        Ok(amount)
+    }
+    
+    /// Compares the recorded fills with the exchange's since the last
+    /// reconciliation. Fills the bot missed are recorded, flagged as
+    /// reconciled, and counted as successful trades; recorded fills the
+    /// exchange doesn't know about are reported. Alerts once the
+    /// discrepancies reach `reconciliation.alert_threshold`.
+    ///
+    /// Returns `None` without asking the exchange on dry runs, whose fills
+    /// it never sees, and when storage is disabled.
+    pub async fn reconcile_fills(&self) -> Result<Option<ReconciliationReport>> {
+        let Some(storage) = &self.storage else {
+            return Ok(None);
+        };
+        if self.config.trading.dry_run {
+            return Ok(None);
+        }
+        
+        // Fills recorded this cycle have to be readable before comparing
+        storage.flush().await;
+        let report = reconcile::reconcile(
+            self.api_client.as_ref(),
+            storage.storage().as_ref(),
+            &self.config.reconciliation,
+            self.clock.now(),
+        )
+        .await?;
+        
+        for entry in &report.missing {
+            warn!(
+                "🧾 Recording a missed fill: {:?} {} {} @ {} (order {})",
+                entry.side, entry.quantity, entry.symbol, entry.price, entry.order_id
+            );
+            self.publish(BotEvent::OrderFilled(entry.clone()));
+            self.persist(StorageWrite::Fill(entry.clone())).await;
+            self.trade_stats.record_success();
+            self.trade_stats.record_fee(entry.fee, false);
+        }
+        for entry in &report.unknown {
+            warn!(
+                "🧾 Recorded fill unknown to the exchange: {:?} {} {} @ {} (order {}, strategy {})",
+                entry.side, entry.quantity, entry.symbol, entry.price, entry.order_id, entry.strategy
+            );
+        }
+        
+        if !report.missing.is_empty() {
+            self.persist(StorageWrite::Kv {
+                key: keys::TRADE_STATS.to_string(),
+                value: serde_json::to_string(&self.trade_stats.snapshot())?,
+            }).await;
+        }
+        self.persist(StorageWrite::Kv {
+            key: keys::RECONCILED_UNTIL.to_string(),
+            value: serde_json::to_string(&report.until)?,
+        }).await;
+        
+        let discrepancies = report.discrepancies();
+        info!(
+            "🧾 Reconciled fills up to {}: {} matched, {} missing, {} unknown",
+            report.until,
+            report.matched,
+            report.missing.len(),
+            report.unknown.len()
+        );
+        if discrepancies > 0 && discrepancies >= self.config.reconciliation.alert_threshold {
+            self.notifier.notify(NotificationEvent::FillDiscrepancy {
+                missing: report.missing.len(),
+                unknown: report.unknown.len(),
+            });
+        }
+        
+        Ok(Some(report))
+    }
+    
+    fn publish(&self, event: BotEvent) {
+        self.events.publish(event, self.clock.now());
+    }
+    
+    async fn persist(&self, write: StorageWrite) {
+        if let Some(storage) = &self.storage {
+            storage.write(write).await;
+        }
+    }
+    
+    /// Drains the websocket's messages until the bot stops, logging
+    /// tickers. Falling behind costs the messages skipped, never the task.
+    fn spawn_event_logger(&self) {
+        let mut events = self.feed.events();
+        let mut shutdown = self.shutdown.subscribe();
+        tokio::spawn(async move {
+            loop {
+                let event = tokio::select! {
+                    event = events.recv() => event,
+                    _ = shutdown.wait_for(|&stopping| stopping) => break,
+                };
+                match event {
+                    Ok(WsEvent::Market(MarketEvent::Ticker(ticker))) => {
+                        debug!("📡 {} ticker at {}", ticker.symbol, ticker.price);
+                    }
+                    Ok(_) => {}
+                    Err(RecvError::Lagged(skipped)) => warn!("📡 Event logger fell behind, skipped {} websocket messages", skipped),
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+    
+    async fn schedule_equity_downsampling(&self) {
+        let retention = chrono::Duration::days(self.config.storage.equity_raw_retention_days as i64);
+        self.persist(StorageWrite::DownsampleEquity {
+            older_than: self.clock.now() - retention,
+        }).await;
+    }
+    
+    /// Recorded equity samples in `[from, to)`, oldest first. Empty when
+    /// storage is disabled.
+    pub fn equity_curve(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<Vec<EquitySample>> {
+        match self.storage() {
+            Some(storage) => storage.equity_samples(from, to),
+            None => Ok(Vec::new()),
+        }
+    }
+    
+    /// What the trading loop publishes, for observers beyond the built-in ones
+    pub fn events(&self) -> &EventBus {
+        &self.events
+    }
+    
+    /// The latest prices the bot has seen; clones share the same cache
+    pub fn prices(&self) -> &PriceCache {
+        self.feed.prices()
+    }
+    
+    /// Read access to persisted orders, fills and equity, if storage is enabled
+    pub fn storage(&self) -> Option<&Arc<dyn Storage>> {
+        self.storage.as_ref().map(|writer| writer.storage())
+    }
+    
+    /// Measures drawdowns and Sharpe ratios for status once
+    /// `PERFORMANCE_INTERVAL_SECS` has passed since the last time
+    async fn measure_performance_if_due(&self, now: DateTime<Utc>) {
+        {
+            let mut next = self.next_performance.lock().unwrap();
+            if next.is_some_and(|next| now < next) {
+                return;
+            }
+            *next = Some(now + chrono::Duration::seconds(PERFORMANCE_INTERVAL_SECS));
+        }
+        
+        // Drawdown and Sharpe come from the recorded equity curve so they
+        // agree with exported reports. The sample just taken has to be
+        // readable first.
+        self.heartbeat.stage("measuring performance");
+        if let Some(storage) = &self.storage {
+            storage.flush().await;
+        }
+        let curve = self.equity_curve(DateTime::<Utc>::MIN_UTC, now).unwrap_or_else(|e| {
+            warn!("Failed to load equity curve: {}", e);
+            Vec::new()
+        });
+        let window = PerformanceWindow::from_equity_curve(&curve);
+        
+        // The exchange's history covers the account's whole life; the
+        // recorded curve stands in where there's none, e.g. on paper
+        let history = self
+            .heartbeat
+            .track("get_portfolio_history", self.api_client.get_portfolio_history())
+            .await;
+        let (current_drawdown, max_drawdown) = match history {
+            Ok(history) if !history.is_empty() => equity::history_drawdowns(&history),
+            result => {
+                if let Err(e) = result {
+                    debug!("No portfolio history for drawdowns: {}", e);
+                }
+                (equity::current_drawdown(&curve), equity::max_drawdown(&curve))
+            }
+        };
+        
+        *self.performance.lock().unwrap() = Performance {
+            current_drawdown,
+            max_drawdown,
+            sharpe_ratio: equity::sharpe_ratio(&curve),
+            sharpe_ratio_7d: window.stats(Window::WEEK, now).sharpe_ratio,
+            sharpe_ratio_30d: window.stats(Window::MONTH, now).sharpe_ratio,
+        };
+    }
+    
+    async fn update_trade_stats(&self, account_info: &AccountInfo, now: DateTime<Utc>) {
+        let equity = EquitySample::from_account(account_info, now).equity;
+        if let Some(stats) = self.trade_stats.update_pnl(account_info.total_pnl, equity, now.date_naive()) {
+            self.notifier.notify(NotificationEvent::DailySummary {
+                date: stats.last_reset_date,
+                daily_pnl: stats.daily_pnl,
+                total_pnl: stats.total_pnl,
+                total_trades: stats.total_trades,
+                successful_trades: stats.successful_trades,
+                failed_trades: stats.failed_trades,
+                funding_paid: stats.daily_funding_paid,
+            });
+            self.schedule_equity_downsampling().await;
+        }
+        self.sync_funding(now).await;
+        
+        match serde_json::to_string(&self.trade_stats.snapshot()) {
+            Ok(value) => self.persist(StorageWrite::Kv {
+                key: keys::TRADE_STATS.to_string(),
+                value,
+            }).await,
+            Err(e) => warn!("Failed to encode trade stats: {}", e),
+        }
+    }
+    
+    /// Takes the funding settled since the last sync out of the PnL. Only
+    /// asks the exchange once a funding time has passed; a failed fetch is
+    /// retried next cycle.
+    async fn sync_funding(&self, now: DateTime<Utc>) {
+        {
+            let mut next = self.next_funding_sync.lock().unwrap();
+            if next.is_some_and(|next| now < next) {
+                return;
+            }
+            let schedule = MarketSchedule::new(self.config.schedule.clone());
+            *next = Some(schedule.next_funding(now) + chrono::Duration::minutes(1));
+        }
+        
+        let start = match self.trade_stats.snapshot().funding_synced_to {
+            Some(synced_to) => synced_to + chrono::Duration::milliseconds(1),
+            None => now.date_naive().and_hms_opt(0, 0, 0).unwrap().and_utc(),
+        };
+        let request = self.api_client.get_funding_payments(start, now);
+        match self.heartbeat.track("get_funding_payments".to_string(), request).await {
+            Ok(payments) => {
+                let funding = FundingSummary::from_payments(&payments);
+                if funding.total != Decimal::ZERO {
+                    info!("💸 Funding paid since {}: {}", start, funding.total);
+                }
+                self.trade_stats.record_funding(&funding, now);
+            }
+            Err(e) => {
+                warn!("Failed to fetch funding payments: {}", e);
+                *self.next_funding_sync.lock().unwrap() = None;
+            }
+        }
+    }
+    
+    async fn save_strategy_states(&self) {
+        if self.storage.is_none() {
+            return;
+        }
+        
+        for state in self.strategy_states().await {
+            self.persist(StorageWrite::StrategyState(state)).await;
+        }
+    }
+    
+    async fn strategy_states(&self) -> Vec<StrategyState> {
+        let now = self.clock.now();
+        let mut states = Vec::new();
+        for (name, running) in &self.strategies {
+            let strategy = running.strategy.read().await;
+            match strategy.export_state() {
+                Ok(Some(data)) => states.push(StrategyState {
+                    name: name.clone(),
+                    version: strategy.state_version(),
+                    data,
+                    updated_at: now,
+                }),
+                Ok(None) => {}
+                Err(e) => warn!("Failed to export state of {}: {}", name, e),
+            }
+        }
+        states
+    }
+    
+    /// Strategy states, trade stats and the paper account, as versioned
+    /// JSON for moving the bot to another host
+    pub async fn export_snapshot(&self) -> Result<Snapshot> {
+        Ok(Snapshot {
+            version: snapshot::SNAPSHOT_VERSION,
+            created_at: self.clock.now(),
+            strategies: snapshot::enabled_strategies(&self.config),
+            strategy_states: self.strategy_states().await,
+            trade_stats: Some(self.trade_stats.snapshot()),
+            open_orders: self.api_client.get_open_orders().await?,
+            paper_account: self.paper.as_ref().map(|paper| paper.exchange().snapshot()),
+        })
+    }
+    
+    /// Replaces the bot's state with a snapshot's. Nothing changes unless
+    /// the whole snapshot applies: it must match this bot's version and
+    /// strategies, and a strategy rejecting its state rolls back the rest.
+    pub async fn import_snapshot(&mut self, snapshot: Snapshot) -> Result<()> {
+        snapshot.check_compatible(&self.config)?;
+        if snapshot.paper_account.is_some() && self.paper.is_none() {
+            return Err(Error::InvalidInput(
+                "Snapshot has a paper account but this bot isn't trading one".to_string(),
+            ));
+        }
+        
+        let backup = self.strategy_states().await;
+        for state in &snapshot.strategy_states {
+            let Some(running) = self.strategies.get_mut(&state.name) else {
+                continue;
+            };
+            
+            if let Err(e) = running.strategy.get_mut().import_state(state.version, &state.data) {
+                for saved in &backup {
+                    if let Some(running) = self.strategies.get_mut(&saved.name) {
+                        if let Err(e) = running.strategy.get_mut().import_state(saved.version, &saved.data) {
+                            error!("Failed to roll back state of {}: {}", saved.name, e);
+                        }
+                    }
+                }
+                return Err(Error::InvalidInput(format!(
+                    "Strategy {} rejected its snapshot state: {}",
+                    state.name, e
+                )));
+            }
+            let enabled = running.strategy.get_mut().is_enabled();
+            *running.enabled.get_mut() = enabled;
+        }
+        
+        if let Some(trade_stats) = snapshot.trade_stats {
+            self.trade_stats.restore(trade_stats);
+        }
+        if let (Some(paper), Some(paper_account)) = (&self.paper, snapshot.paper_account) {
+            paper.load(paper_account);
+        }
+        
+        self.save_strategy_states().await;
+        self.persist(StorageWrite::Kv {
+            key: keys::TRADE_STATS.to_string(),
+            value: serde_json::to_string(&self.trade_stats.snapshot())?,
+        }).await;
+        
+        info!("Imported snapshot taken at {}", snapshot.created_at);
+        Ok(())
+    }
+    
+    /// The order this run placed with client order id `cloid`, as placed,
+    /// and the strategy that placed it
+    pub fn order_by_cloid(&self, cloid: &str) -> Option<(String, Order)> {
+        let orders = self.orders_by_cloid.lock().unwrap();
+        orders.get(cloid).map(|tracked| (tracked.strategy.clone(), tracked.order.clone()))
+    }
+    
+    /// The account as the last cycle saw it, and when
+    pub async fn last_account(&self) -> Option<(DateTime<Utc>, AccountInfo)> {
+        self.last_account.lock().await.clone()
+    }
+    
+    /// Status plus the latest account view, as served to `status`
+    pub async fn snapshot(&self) -> StatusSnapshot {
+        let mut snapshot = self.cached_snapshot().await;
+        
+        // A dry run's account is the paper one; the real balance is shown
+        // alongside for comparison
+        if let Some(paper) = &self.paper {
+            snapshot.live_balance = paper.live_balance().await.map_err(|e| debug!("Live balance unavailable: {}", e)).ok();
+        }
+        snapshot
+    }
+    
+    /// `snapshot` from what the trading loop last saw, without asking the
+    /// exchange anything, so it's quick and never waits on the loop
+    pub async fn cached_snapshot(&self) -> StatusSnapshot {
+        let status = self.get_status().await;
+        let last_account = self.last_account.lock().await.clone();
+        
+        let mut enabled = HashMap::new();
+        for (name, running) in &self.strategies {
+            enabled.insert(name.as_str(), running.is_enabled() && !self.analysis.is_disabled(name));
+        }
+        let mut strategies: Vec<StrategyStatus> = self
+            .config
+            .strategies
+            .iter()
+            .map(|(name, strategy_config)| StrategyStatus {
+                name: name.clone(),
+                strategy_type: strategy_config.strategy_type.clone(),
+                symbol: strategy_config.symbol.clone(),
+                enabled: enabled.get(name.as_str()).copied().unwrap_or(false),
+                analysis: self.analysis.stats(name),
+            })
+            .collect();
+        // Strategies added with `with_strategy` aren't in the config
+        strategies.extend(
+            self.strategies
+                .iter()
+                .filter(|(name, _)| !self.config.strategies.contains_key(*name))
+                .map(|(name, running)| StrategyStatus {
+                    name: name.clone(),
+                    strategy_type: "custom".to_string(),
+                    symbol: running.symbol.clone(),
+                    enabled: enabled[name.as_str()],
+                    analysis: self.analysis.stats(name),
+                }),
+        );
+        strategies.sort_by(|a, b| a.name.cmp(&b.name));
+        
+        let connected = Some(self.feed.is_connected());
+        
+        let (updated_at, account_info) = match last_account {
+            Some((updated_at, account_info)) => (Some(updated_at), Some(account_info)),
+            None => (None, None),
+        };
+        
+        StatusSnapshot {
+            account: self.account.clone(),
+            status,
+            equity: account_info.as_ref().map(|a| EquitySample::from_account(a, updated_at.unwrap_or_else(|| self.clock.now())).equity),
+            paper: self.paper.is_some(),
+            live_balance: None,
+            updated_at,
+            positions: account_info.as_ref().map(|a| a.positions.clone()).unwrap_or_default(),
+            open_orders: account_info.map(|a| a.open_orders).unwrap_or_default(),
+            strategies,
+            websocket: WebSocketHealth {
+                connected,
+                parse_errors: self.feed.parse_errors(),
+                dropped_messages: self.feed.dropped_messages(),
+            },
+            events: self.event_stats.stats(),
+            chaos: self.chaos_stats(),
+        }
+    }
+    
+    /// Healthy while the trading loop's heartbeat is fresher than the
+    /// watchdog's stall threshold and market data isn't stuck on the REST
+    /// fallback
+    pub fn health(&self) -> Health {
+        let stale_after = self.cycle_interval() * self.config.watchdog.stall_cycles;
+        let heartbeat = self.heartbeat.report();
+        let data_source = self.data_source.lock().unwrap();
+        
+        let mut problems = Vec::new();
+        if heartbeat.silent_for > stale_after {
+            problems.push(format!(
+                "no heartbeat for {}s; cycle {} is in '{}'",
+                heartbeat.silent_for.as_secs(),
+                heartbeat.cycle,
+                heartbeat.stage
+            ));
+        }
+        if data_source.degraded() {
+            problems.push("websocket down; market data falling back to REST polling".to_string());
+        }
+        
+        Health {
+            healthy: problems.is_empty(),
+            heartbeat_age_secs: heartbeat.silent_for.as_secs(),
+            stage: heartbeat.stage,
+            data_source: data_source.status(),
+            problems,
+        }
+    }
+    
+    /// Failures injected so far, during a chaos run
+    pub fn chaos_stats(&self) -> Option<ChaosStats> {
+        self.chaos.as_ref().map(|chaos| chaos.stats())
+    }
+    
+    pub async fn get_status(&self) -> BotStatus {
+        let is_running = *self.is_running.borrow();
+        let now = self.clock.now();
+        let uptime = now - self.start_time;
+        let stats = self.trade_stats.snapshot();
+        
+        let performance = *self.performance.lock().unwrap();
+        
+        BotStatus {
+            is_running,
             start_time: self.start_time,
             uptime_seconds: uptime.num_seconds() as u64,
             total_trades: stats.total_trades,
@@ -298,30 +2676,127 @@ impl TradingBot {
             failed_trades: stats.failed_trades,
             current_positions: 0, // Would get from account info
             risk_metrics: RiskMetrics {
-                current_drawdown: Decimal::ZERO, // Would calculate from historical data
-                max_drawdown: Decimal::ZERO,
+                current_drawdown: performance.current_drawdown,
+                max_drawdown: performance.max_drawdown,
                 daily_pnl: stats.daily_pnl,
                 total_pnl: stats.total_pnl,
+                funding_paid: stats.funding_paid,
+                daily_funding_paid: stats.daily_funding_paid,
+                fees_paid: stats.fees_paid,
                 win_rate: if stats.total_trades > 0 {
                     stats.successful_trades as f64 / stats.total_trades as f64
                 } else {
                     0.0
                 },
                 profit_factor: 1.0, // Would calculate from trade history
-                sharpe_ratio: 0.0, // Would calculate from returns
+                sharpe_ratio: performance.sharpe_ratio,
+                sharpe_ratio_7d: performance.sharpe_ratio_7d,
+                sharpe_ratio_30d: performance.sharpe_ratio_30d,
                 max_position_risk: Decimal::ZERO,
             },
+            data_source: self.data_source.lock().unwrap().status(),
+            ws_connected: self.feed.is_connected(),
+            ws_last_message_secs: self.feed.last_message_age().map(|age| age.as_secs()),
+            ws_recovered_fills: self.recovered_fills.load(Ordering::Relaxed),
         }
     }
 }
 
+/// Hands each strategy the state it saved last run. A state a strategy
+/// can't read is skipped, leaving it to start fresh.
+fn restore_strategy_states(storage: &dyn Storage, strategies: &mut BTreeMap<String, Box<dyn Strategy + Send + Sync>>) {
+    for (name, strategy) in strategies.iter_mut() {
+        match storage.load_strategy_state(name) {
+            Ok(Some(state)) => match strategy.import_state(state.version, &state.data) {
+                Ok(()) => info!("Restored state of strategy {}", name),
+                Err(e) => warn!("Ignoring saved state of {}: {}", name, e),
+            },
+            Ok(None) => {}
+            Err(e) => warn!("Failed to load state of {}: {}", name, e),
+        }
+    }
+}
+
+/// The least time between evaluations of strategy `name`, per its config
+fn min_eval_interval(config: &Config, name: &str) -> std::time::Duration {
+    let secs = config.strategies.get(name).and_then(|strategy| strategy.eval_interval_secs).unwrap_or(0);
+    std::time::Duration::from_secs(secs)
+}
+
+/// Sets the leverage and margin mode enabled strategies ask for on their
+/// symbols, once per symbol. Config validation has made sure strategies
+/// sharing a symbol agree.
+async fn apply_leverage(client: &HyperliquidClient, config: &Config) -> Result<()> {
+    let mut by_symbol = BTreeMap::new();
+    for (name, strategy) in config.strategies.iter().filter(|(_, s)| s.enabled) {
+        if let Some(leverage) = strategy.leverage {
+            by_symbol.entry(strategy.symbol.as_str()).or_insert((name, leverage, strategy));
+        }
+    }
+    
+    for (name, leverage, strategy) in by_symbol.into_values() {
+        client
+            .set_leverage(&strategy.symbol, leverage, strategy.margin_mode == MarginMode::Cross)
+            .await
+            .with_context(|| ErrorContext::new().with_strategy(name).with_symbol(&strategy.symbol))?;
+    }
+    Ok(())
+}
+
+/// `order` as the exchange now reports it, or `None` if nothing changed
+fn updated_order(order: &Order, info: &OrderStatusInfo, now: DateTime<Utc>) -> Option<Order> {
+    let status = match info.state {
+        OrderState::Resting if info.filled_size > Decimal::ZERO => OrderStatus::PartiallyFilled,
+        OrderState::Resting => OrderStatus::Open,
+        OrderState::Filled => OrderStatus::Filled,
+        OrderState::Cancelled => OrderStatus::Cancelled,
+        OrderState::Rejected => OrderStatus::Rejected,
+    };
+    if status == order.status && info.filled_size == order.filled_quantity {
+        return None;
+    }
+    Some(Order {
+        status,
+        filled_quantity: info.filled_size,
+        average_price: info.average_price.or(order.average_price),
+        updated_at: Some(now),
+        ..order.clone()
+    })
+}
+
+/// The quantity `after` has filled since `before` was seen and what it
+/// filled at on average, if any
+fn filled_since(before: &Order, after: &Order) -> Option<(Decimal, Decimal)> {
+    let quantity = after.filled_quantity - before.filled_quantity;
+    if quantity <= Decimal::ZERO {
+        return None;
+    }
+    let average = after.average_price.or(after.price)?;
+    let before_average = before.average_price.or(before.price).unwrap_or(average);
+    let price = (average * after.filled_quantity - before_average * before.filled_quantity) / quantity;
+    Some((quantity, price))
+}
+
+fn signal_side(signal: &StrategySignal) -> Option<OrderSide> {
+    match signal.action {
+        SignalAction::Buy => Some(OrderSide::Buy),
+        SignalAction::Sell => Some(OrderSide::Sell),
+        _ => None,
+    }
+}
+
 pub struct RiskManager {
     config: crate::config::RiskManagementConfig,
+    schedule: MarketSchedule,
 }
 
 impl RiskManager {
-    pub fn new(config: crate::config::RiskManagementConfig) -> Self {
-        Self { config }
+    pub fn new(config: crate::config::RiskManagementConfig, schedule: MarketSchedule) -> Self {
+        Self { config, schedule }
+    }
+    
+    pub fn trading_paused(&self, now: DateTime<Utc>) -> Option<String> {
+        self.schedule.blocked_reason(now)
     }
     
     pub async fn check_risk_limits(&self, account_info: &AccountInfo) -> Result<bool> {
@@ -343,7 +2818,7 @@ impl RiskManager {
         Ok(true)
     }
     
-    pub async fn check_signal_risk(&self, signal: &StrategySignal, account_info: &AccountInfo) -> Result<bool> {
+    pub async fn check_signal_risk(&self, signal: &StrategySignal, _account_info: &AccountInfo) -> Result<bool> {
         // Check if signal would exceed position size limit
         if let Some(price) = signal.price {
             let position_value = signal.quantity * price;
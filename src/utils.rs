@@ -1,23 +1,59 @@
-use crate::error::Result;
-use rust_decimal::Decimal;
+use crate::{
+    config::{BlackoutWindow, LogFormat, ScheduleConfig},
+    error::{Error, Result},
+};
+use chrono::{DateTime, Datelike, Timelike, Utc, Weekday};
+use rust_decimal::{Decimal, RoundingStrategy};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tracing::{info, warn, error};
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+use tracing::Subscriber;
+use tracing_subscriber::{
+    fmt::MakeWriter, layer::SubscriberExt, registry::LookupSpan, util::SubscriberInitExt, EnvFilter, Layer,
+};
 
 pub fn setup_logging(debug: bool) -> Result<()> {
+    setup_logging_with_format(debug, LogFormat::Pretty)
+}
+
+pub fn setup_logging_with_format(debug: bool, format: LogFormat) -> Result<()> {
     let level = if debug { "debug" } else { "info" };
     
     let filter = EnvFilter::try_from_default_env()
         .unwrap_or_else(|_| EnvFilter::new(level));
     
-    tracing_subscriber::registry()
-        .with(filter)
-        .with(tracing_subscriber::fmt::layer())
-        .init();
+    match format {
+        LogFormat::Pretty => {
+            tracing_subscriber::registry()
+                .with(filter)
+                .with(tracing_subscriber::fmt::layer())
+                .init();
+        }
+        LogFormat::Json => {
+            tracing_subscriber::registry()
+                .with(filter)
+                .with(json_log_layer(std::io::stdout))
+                .init();
+        }
+    }
     
     Ok(())
 }
 
+/// One JSON object per event, written to `writer`. Span fields (cycle_id,
+/// strategy, symbol, order_id) are included with every line so log
+/// pipelines can filter on them directly.
+pub fn json_log_layer<S, W>(writer: W) -> impl Layer<S>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    tracing_subscriber::fmt::layer()
+        .json()
+        .with_current_span(true)
+        .with_span_list(true)
+        .with_writer(writer)
+}
+
 pub fn current_timestamp() -> u64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -67,14 +103,207 @@ pub fn calculate_pnl(
     price_diff * quantity
 }
 
-pub fn is_market_hours() -> bool {
-    // Simple check - in production, you'd want to check actual market hours
-    // and holidays for the specific exchange
-    let now = chrono::Utc::now();
-    let hour = now.hour();
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoundMode {
+    Down,
+    Up,
+    Nearest,
+}
+
+impl RoundMode {
+    fn strategy(self) -> RoundingStrategy {
+        match self {
+            RoundMode::Down => RoundingStrategy::ToZero,
+            RoundMode::Up => RoundingStrategy::AwayFromZero,
+            RoundMode::Nearest => RoundingStrategy::MidpointAwayFromZero,
+        }
+    }
+}
+
+// Prices and sizes are never negative; rejecting them here keeps the
+// Down/Up modes from silently meaning "toward/away from zero"
+fn check_rounding_input(value: Decimal, step: Decimal) -> Result<()> {
+    if step <= Decimal::ZERO {
+        return Err(Error::InvalidInput(format!("Rounding step must be positive, got {}", step)));
+    }
+    if value.is_sign_negative() && !value.is_zero() {
+        return Err(Error::InvalidInput(format!("Cannot round negative value {}", value)));
+    }
+    Ok(())
+}
+
+fn round_to_step(value: Decimal, step: Decimal, mode: RoundMode) -> Result<Decimal> {
+    check_rounding_input(value, step)?;
+    
+    let steps = (value / step).round_dp_with_strategy(0, mode.strategy());
+    Ok(steps * step)
+}
+
+/// Rounds a price to a multiple of the tick size, entirely in Decimal
+pub fn round_price(price: Decimal, tick: Decimal, mode: RoundMode) -> Result<Decimal> {
+    round_to_step(price, tick, mode)
+}
+
+/// Rounds an order size to a multiple of the lot size, entirely in Decimal
+pub fn round_size(size: Decimal, lot: Decimal, mode: RoundMode) -> Result<Decimal> {
+    round_to_step(size, lot, mode)
+}
+
+pub fn is_aligned(value: Decimal, step: Decimal) -> bool {
+    step > Decimal::ZERO && (value % step).is_zero()
+}
+
+/// Applies Hyperliquid's price rule: at most `sig_figs` significant figures
+/// and at most `max_decimals` decimal places (6 - szDecimals for perps).
+/// Integer prices are always valid regardless of significant figures.
+pub fn round_to_significant_figures(
+    price: Decimal,
+    sig_figs: u32,
+    max_decimals: u32,
+    mode: RoundMode,
+) -> Result<Decimal> {
+    if sig_figs == 0 {
+        return Err(Error::InvalidInput("Significant figures must be at least 1".to_string()));
+    }
+    check_rounding_input(price, Decimal::ONE)?;
+    if price.is_zero() {
+        return Ok(Decimal::ZERO);
+    }
+    
+    // Number of digits before the decimal point (negative for leading zeros
+    // after it), found by exact scaling rather than a float log10
+    let ten = Decimal::TEN;
+    let mut magnitude: i64 = 1;
+    let mut scaled = price;
+    while scaled >= ten {
+        scaled /= ten;
+        magnitude += 1;
+    }
+    while scaled < Decimal::ONE {
+        scaled *= ten;
+        magnitude -= 1;
+    }
+    
+    let decimals = (sig_figs as i64 - magnitude).clamp(0, max_decimals as i64) as u32;
+    Ok(price.round_dp_with_strategy(decimals, mode.strategy()).normalize())
+}
+
+/// Significant figures a perp price may have, unless it's an integer
+pub const PRICE_SIG_FIGS: u32 = 5;
+/// Decimal places a perp price may have, less the asset's szDecimals
+pub const MAX_PRICE_DECIMALS: u32 = 6;
+
+/// Rounds a perp order size down to the `sz_decimals` its asset trades in
+pub fn round_order_size(size: Decimal, sz_decimals: u32) -> Result<Decimal> {
+    Ok(round_size(size, Decimal::new(1, sz_decimals), RoundMode::Down)?.normalize())
+}
+
+/// Rounds a perp price to the nearest one the exchange accepts for an asset
+/// with `sz_decimals`
+pub fn round_order_price(price: Decimal, sz_decimals: u32) -> Result<Decimal> {
+    round_to_significant_figures(
+        price,
+        PRICE_SIG_FIGS,
+        MAX_PRICE_DECIMALS.saturating_sub(sz_decimals),
+        RoundMode::Nearest,
+    )
+}
+
+/// Knows when the exchange settles funding and when trading should pause
+/// for maintenance or user-defined blackout windows.
+#[derive(Debug, Clone)]
+pub struct MarketSchedule {
+    config: ScheduleConfig,
+}
+
+impl MarketSchedule {
+    pub fn new(config: ScheduleConfig) -> Self {
+        Self { config }
+    }
+    
+    fn funding_interval(&self) -> chrono::Duration {
+        chrono::Duration::hours(self.config.funding_interval_hours.max(1) as i64)
+    }
+    
+    /// The first funding time strictly after `now`
+    pub fn next_funding(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        let interval_secs = self.funding_interval().num_seconds();
+        let day_start = now.date_naive().and_hms_opt(0, 0, 0).unwrap().and_utc();
+        let periods = now.num_seconds_from_midnight() as i64 / interval_secs + 1;
+        day_start + chrono::Duration::seconds(periods * interval_secs)
+    }
+    
+    pub fn time_to_next_funding(&self, now: DateTime<Utc>) -> chrono::Duration {
+        self.next_funding(now) - now
+    }
+    
+    /// Why trading is paused at `now`, if it is
+    pub fn blocked_reason(&self, now: DateTime<Utc>) -> Option<String> {
+        if let Some(window) = self.config.maintenance_windows.iter().find(|w| now >= w.start && now < w.end) {
+            return Some(format!("exchange maintenance until {}", window.end));
+        }
+        
+        if let Some(window) = self.config.blackout_windows.iter().find(|w| in_blackout(w, now)) {
+            return Some(format!("blackout window {}-{} UTC", window.start, window.end));
+        }
+        
+        let blackout = chrono::Duration::seconds(self.config.funding_blackout_secs as i64);
+        if blackout > chrono::Duration::zero() {
+            let next = self.next_funding(now);
+            let previous = next - self.funding_interval();
+            if next - now <= blackout || now - previous < blackout {
+                return Some(format!("within {}s of funding at {}", self.config.funding_blackout_secs, next));
+            }
+        }
+        
+        None
+    }
+    
+    pub fn is_tradable(&self, now: DateTime<Utc>) -> bool {
+        self.blocked_reason(now).is_none()
+    }
+}
+
+impl Default for MarketSchedule {
+    fn default() -> Self {
+        Self::new(ScheduleConfig::default())
+    }
+}
+
+fn in_blackout(window: &BlackoutWindow, now: DateTime<Utc>) -> bool {
+    let time = now.time();
+    let applies_on = |day: Weekday| window.days.is_empty() || window.days.contains(&day);
     
-    // Assume market is open 24/7 for crypto, but you can add specific hours here
-    true
+    if window.start <= window.end {
+        applies_on(now.weekday()) && time >= window.start && time < window.end
+    } else {
+        // Wraps past midnight: the late part belongs to today's window, the
+        // early part to yesterday's
+        (time >= window.start && applies_on(now.weekday()))
+            || (time < window.end && applies_on(now.weekday().pred()))
+    }
+}
+
+/// Parses durations like `90s`, `30m`, `2h` or `1d`
+pub fn parse_duration(value: &str) -> Result<Duration> {
+    let value = value.trim();
+    let invalid = || Error::InvalidInput(format!("Invalid duration '{}': expected e.g. 90s, 30m, 2h or 1d", value));
+    
+    let split = value.find(|c: char| !c.is_ascii_digit()).ok_or_else(invalid)?;
+    let (amount, unit) = value.split_at(split);
+    let amount: u64 = amount.parse().map_err(|_| invalid())?;
+    let seconds = match unit {
+        "s" => amount,
+        "m" => amount * 60,
+        "h" => amount * 3600,
+        "d" => amount * 86400,
+        _ => return Err(invalid()),
+    };
+    
+    if seconds == 0 {
+        return Err(invalid());
+    }
+    Ok(Duration::from_secs(seconds))
 }
 
 pub fn sleep_ms(ms: u64) -> tokio::time::Sleep {
@@ -100,6 +329,12 @@ pub fn log_trade_execution(
     let status = if success { "✅" } else { "❌" };
     
     info!(
+        event = "trade_execution",
+        symbol,
+        side = action,
+        quantity = %quantity,
+        price = %price,
+        success,
         "{} {} {} {} @ {}",
         status,
         action,
@@ -109,6 +344,36 @@ pub fn log_trade_execution(
     );
 }
 
+/// A resting order taken off the book, by the bot or on the exchange,
+/// rather than a trade that failed
+pub fn log_order_cancelled(
+    symbol: &str,
+    side: &crate::models::OrderSide,
+    quantity: Decimal,
+    filled: Decimal,
+    reason: &str,
+) {
+    let action = match side {
+        crate::models::OrderSide::Buy => "BUY",
+        crate::models::OrderSide::Sell => "SELL",
+    };
+    
+    info!(
+        event = "order_cancelled",
+        symbol,
+        side = action,
+        quantity = %quantity,
+        filled = %filled,
+        reason,
+        "🚫 Cancelled {} {} {} ({} filled): {}",
+        action,
+        format_decimal(quantity, 6),
+        symbol,
+        format_decimal(filled, 6),
+        reason
+    );
+}
+
 pub fn log_position_update(
     symbol: &str,
     side: &crate::models::PositionSide,
@@ -123,6 +388,11 @@ pub fn log_position_update(
     let pnl_emoji = if pnl >= Decimal::ZERO { "📈" } else { "📉" };
     
     info!(
+        event = "position_update",
+        symbol,
+        side = position_type,
+        size = %size,
+        pnl = %pnl,
         "{} {} {} {} PnL: {}",
         pnl_emoji,
         position_type,
@@ -0,0 +1,241 @@
+use crate::{
+    api::{types::Meta, HyperliquidClient},
+    config::{Config, MarginMode},
+    error::Result,
+    utils::format_currency,
+};
+use rust_decimal::Decimal;
+use std::io::Write;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Pass,
+    Fail,
+    Skip,
+}
+
+#[derive(Debug, Clone)]
+pub struct Check {
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+/// Outcome of every check run against a config. Checks keep going after a
+/// failure so one run shows everything that needs fixing.
+#[derive(Debug, Clone, Default)]
+pub struct ValidationReport {
+    pub checks: Vec<Check>,
+}
+
+impl ValidationReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    fn push(&mut self, name: &str, status: CheckStatus, detail: String) {
+        self.checks.push(Check {
+            name: name.to_string(),
+            status,
+            detail,
+        });
+    }
+    
+    pub fn pass(&mut self, name: &str, detail: impl Into<String>) {
+        self.push(name, CheckStatus::Pass, detail.into());
+    }
+    
+    pub fn fail(&mut self, name: &str, detail: impl Into<String>) {
+        self.push(name, CheckStatus::Fail, detail.into());
+    }
+    
+    pub fn skip(&mut self, name: &str, detail: impl Into<String>) {
+        self.push(name, CheckStatus::Skip, detail.into());
+    }
+    
+    pub fn failures(&self) -> usize {
+        self.checks.iter().filter(|c| c.status == CheckStatus::Fail).count()
+    }
+    
+    pub fn passed(&self) -> bool {
+        self.failures() == 0
+    }
+    
+    pub fn find(&self, name: &str) -> Option<&Check> {
+        self.checks.iter().find(|c| c.name == name)
+    }
+    
+    pub fn write<W: Write>(&self, mut writer: W) -> Result<()> {
+        for check in &self.checks {
+            let status = match check.status {
+                CheckStatus::Pass => "PASS",
+                CheckStatus::Fail => "FAIL",
+                CheckStatus::Skip => "SKIP",
+            };
+            writeln!(writer, "[{}] {:<28} {}", status, check.name, check.detail)?;
+        }
+        
+        writeln!(writer)?;
+        if self.passed() {
+            writeln!(writer, "✅ All {} checks passed", self.checks.len())?;
+        } else {
+            writeln!(writer, "❌ {} of {} checks failed", self.failures(), self.checks.len())?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Loads the config exactly as the bot would and validates it, including
+/// each strategy's parameters. Never touches the network. Returns the config
+/// if it loaded, so online checks can follow.
+pub async fn validate_offline(path: &str, report: &mut ValidationReport) -> Option<Config> {
+    let config = match Config::load(path) {
+        Ok(config) => {
+            report.pass("config", format!("{} loaded and passed range checks", path));
+            config
+        }
+        Err(e) => {
+            report.fail("config", e.to_string());
+            return None;
+        }
+    };
+    
+    validate_strategies(&config, report).await;
+    Some(config)
+}
+
+async fn validate_strategies(config: &Config, report: &mut ValidationReport) {
+    if config.strategies.is_empty() {
+        report.skip("strategies", "no strategies configured");
+        return;
+    }
+    
+    let mut names: Vec<&String> = config.strategies.keys().collect();
+    names.sort();
+    
+    for name in names {
+        let strategy_config = &config.strategies[name];
+        let check = format!("strategy {}", name);
+        
+        if strategy_config.symbol.is_empty() {
+            report.fail(&check, "symbol is empty");
+            continue;
+        }
+        if strategy_config.position_size <= Decimal::ZERO {
+            report.fail(&check, "position_size must be greater than 0");
+            continue;
+        }
+        
+        let strategy = match crate::strategies::create(name, strategy_config, config).await {
+            Ok(Some(strategy)) => strategy,
+            Ok(None) => {
+                report.fail(&check, format!("unknown strategy type '{}'", strategy_config.strategy_type));
+                continue;
+            }
+            Err(e) => {
+                report.fail(&check, e.to_string());
+                continue;
+            }
+        };
+        
+        match strategy.validate_parameters(&strategy_config.parameters) {
+            Ok(()) => report.pass(
+                &check,
+                format!(
+                    "{} on {}{}",
+                    strategy_config.strategy_type,
+                    strategy_config.symbol,
+                    if strategy_config.enabled { "" } else { " (disabled)" }
+                ),
+            ),
+            Err(e) => report.fail(&check, e.to_string()),
+        }
+    }
+}
+
+/// Read-only checks against the exchange: connectivity, listed symbols,
+/// leverage limits and the wallet balance. Never places orders.
+pub async fn validate_online(config: &Config, client: &HyperliquidClient, report: &mut ValidationReport) {
+    let meta = match client.get_meta().await {
+        Ok(meta) => {
+            report.pass(
+                "connectivity",
+                format!("{} reachable, {} assets listed", config.hyperliquid.base_url, meta.universe.len()),
+            );
+            meta
+        }
+        Err(e) => {
+            report.fail("connectivity", format!("{}: {}", config.hyperliquid.base_url, e));
+            report.skip("exchange checks", "exchange unreachable");
+            return;
+        }
+    };
+    
+    let mut names: Vec<&String> = config.strategies.keys().collect();
+    names.sort();
+    validate_symbols(config, &names, &meta, report);
+    
+    validate_wallet(client, report).await;
+}
+
+/// Checks the named strategies trade listed symbols, at leverage the
+/// exchange allows
+pub fn validate_symbols(config: &Config, names: &[&String], meta: &Meta, report: &mut ValidationReport) {
+    let mut leverage_checked = false;
+    
+    for &name in names {
+        let strategy_config = &config.strategies[name];
+        let symbol = &strategy_config.symbol;
+        
+        let Some(asset) = meta.asset(symbol) else {
+            report.fail(&format!("symbol {}", name), format!("{} is not listed on the exchange", symbol));
+            continue;
+        };
+        report.pass(&format!("symbol {}", name), format!("{} listed, max leverage {}x", symbol, asset.max_leverage));
+        
+        let Some(leverage) = strategy_config.leverage else {
+            continue;
+        };
+        leverage_checked = true;
+        let check = format!("leverage {}", name);
+        if leverage == 0 {
+            report.fail(&check, "leverage must be greater than 0");
+        } else if leverage > asset.max_leverage {
+            report.fail(&check, format!("{}x exceeds the {}x maximum for {}", leverage, asset.max_leverage, symbol));
+        } else if strategy_config.margin_mode == MarginMode::Cross && asset.only_isolated {
+            report.fail(&check, format!("{} only trades on isolated margin", symbol));
+        } else {
+            report.pass(&check, format!("{}x within the {}x maximum", leverage, asset.max_leverage));
+        }
+    }
+    
+    if !leverage_checked {
+        report.skip("leverage", "no strategy configures leverage");
+    }
+}
+
+async fn validate_wallet(client: &HyperliquidClient, report: &mut ValidationReport) {
+    let address = match client.account_address() {
+        Ok(address) => address,
+        Err(e) => {
+            report.fail("wallet", e.to_string());
+            return;
+        }
+    };
+    
+    match client.get_user_state(&address).await {
+        Ok(state) => {
+            let account_value = state
+                .margin_summary
+                .map(|summary| summary.account_value)
+                .unwrap_or(state.withdrawable);
+            if account_value > Decimal::ZERO {
+                report.pass("wallet", format!("{} holds {}", address, format_currency(account_value)));
+            } else {
+                report.fail("wallet", format!("{} has no balance", address));
+            }
+        }
+        Err(e) => report.fail("wallet", format!("could not fetch balance for {}: {}", address, e)),
+    }
+}
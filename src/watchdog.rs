@@ -0,0 +1,144 @@
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tokio::time::{Duration, Instant};
+
+/// Where the trading loop was when it stopped making progress
+#[derive(Debug, Clone)]
+pub struct StallReport {
+    /// Time since the last heartbeat
+    pub silent_for: Duration,
+    pub cycle: u64,
+    pub stage: String,
+    pub stage_for: Duration,
+    /// The requests the loop was awaiting, if any, comma-separated
+    pub in_flight: Option<String>,
+}
+
+impl std::fmt::Display for StallReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "no heartbeat for {}s; cycle {} has been in '{}' for {}s",
+            self.silent_for.as_secs(),
+            self.cycle,
+            self.stage,
+            self.stage_for.as_secs()
+        )?;
+        match &self.in_flight {
+            Some(request) => write!(f, ", awaiting {}", request),
+            None => write!(f, ", no request in flight"),
+        }
+    }
+}
+
+struct Activity {
+    stage: String,
+    since: Instant,
+    /// Tracked requests by id; orders can be in flight together
+    in_flight: Vec<(u64, String)>,
+}
+
+/// Stamped by the trading loop as it moves through a cycle, and read by
+/// the watchdog. Each stage change counts as a heartbeat, so a long cycle
+/// that keeps making progress isn't mistaken for a stuck one.
+pub struct Heartbeat {
+    started: Instant,
+    /// Milliseconds after `started` of the last heartbeat
+    last_beat_ms: AtomicU64,
+    cycle: AtomicU64,
+    next_request: AtomicU64,
+    activity: Mutex<Activity>,
+}
+
+impl Heartbeat {
+    pub fn new() -> Self {
+        let started = Instant::now();
+        Self {
+            started,
+            last_beat_ms: AtomicU64::new(0),
+            cycle: AtomicU64::new(0),
+            next_request: AtomicU64::new(0),
+            activity: Mutex::new(Activity {
+                stage: "starting".to_string(),
+                since: started,
+                in_flight: Vec::new(),
+            }),
+        }
+    }
+    
+    pub fn beat(&self) {
+        self.last_beat_ms.store(self.started.elapsed().as_millis() as u64, Ordering::Relaxed);
+    }
+    
+    pub fn start_cycle(&self) {
+        self.cycle.fetch_add(1, Ordering::Relaxed);
+        self.stage("starting cycle");
+    }
+    
+    /// Records what the loop is doing now
+    pub fn stage(&self, stage: impl Into<String>) {
+        let mut activity = self.activity.lock().unwrap();
+        activity.stage = stage.into();
+        activity.since = Instant::now();
+        activity.in_flight.clear();
+        drop(activity);
+        self.beat();
+    }
+    
+    /// Awaits `request`, noting it as in flight until it completes
+    pub async fn track<F: Future>(&self, request: impl Into<String>, future: F) -> F::Output {
+        let id = self.next_request.fetch_add(1, Ordering::Relaxed);
+        self.activity.lock().unwrap().in_flight.push((id, request.into()));
+        let output = future.await;
+        self.activity.lock().unwrap().in_flight.retain(|(other, _)| *other != id);
+        output
+    }
+    
+    /// Milliseconds after start-up of the last heartbeat
+    pub fn last_beat(&self) -> u64 {
+        self.last_beat_ms.load(Ordering::Relaxed)
+    }
+    
+    pub fn silent_for(&self) -> Duration {
+        self.started.elapsed().saturating_sub(Duration::from_millis(self.last_beat()))
+    }
+    
+    pub fn report(&self) -> StallReport {
+        let activity = self.activity.lock().unwrap();
+        let in_flight: Vec<&str> = activity.in_flight.iter().map(|(_, request)| request.as_str()).collect();
+        StallReport {
+            silent_for: self.silent_for(),
+            cycle: self.cycle.load(Ordering::Relaxed),
+            stage: activity.stage.clone(),
+            stage_for: activity.since.elapsed(),
+            in_flight: (!in_flight.is_empty()).then(|| in_flight.join(", ")),
+        }
+    }
+}
+
+impl Default for Heartbeat {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Resolves once `heartbeat` has been silent for `stall_after`
+pub async fn stalled(heartbeat: &Heartbeat, stall_after: Duration) -> StallReport {
+    loop {
+        let silent_for = heartbeat.silent_for();
+        if silent_for >= stall_after {
+            return heartbeat.report();
+        }
+        tokio::time::sleep(stall_after - silent_for).await;
+    }
+}
+
+/// Resolves once `heartbeat` beats again after a stall, checking every
+/// `poll`
+pub async fn resumed(heartbeat: &Heartbeat, poll: Duration) {
+    let stalled_at = heartbeat.last_beat();
+    while heartbeat.last_beat() == stalled_at {
+        tokio::time::sleep(poll).await;
+    }
+}
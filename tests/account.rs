@@ -0,0 +1,188 @@
+use hyperliquid_trading_bot::{
+    account::{self, AccountSnapshot},
+    api::HyperliquidClient,
+    config::Config,
+    models::PositionSide,
+};
+use rust_decimal::Decimal;
+use serde_json::json;
+use std::str::FromStr;
+use wiremock::matchers::{body_partial_json, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const ADDRESS: &str = "0x8ba1f109551bd432803012645ac136ddd64dba72";
+
+fn dec(value: &str) -> Decimal {
+    Decimal::from_str(value).unwrap()
+}
+
+fn fixture_snapshot() -> AccountSnapshot {
+    let raw = std::fs::read_to_string("tests/fixtures/account/snapshot.json").unwrap();
+    serde_json::from_str(&raw).unwrap()
+}
+
+fn render(snapshot: &AccountSnapshot) -> String {
+    let mut output = Vec::new();
+    snapshot.write_table(&mut output).unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+fn row<'a>(output: &'a str, first: &str) -> Vec<&'a str> {
+    output
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>())
+        .find(|tokens| tokens.first() == Some(&first))
+        .unwrap_or_else(|| panic!("no row starting with {} in\n{}", first, output))
+}
+
+#[test]
+fn table_shows_balances_positions_orders_and_fills() {
+    let output = render(&fixture_snapshot());
+    
+    assert!(output.contains(&format!("Account:        {}", ADDRESS)), "{}", output);
+    assert!(output.contains("Account value:  $12500.50"), "{}", output);
+    assert!(output.contains("Withdrawable:   $9000.00"), "{}", output);
+    assert!(output.contains("Margin used:    $3500.50"), "{}", output);
+    
+    assert_eq!(row(&output, "BTC"), vec!["BTC", "Long", "0.5", "40000", "42000", "$1000.00", "33600", "20.00%"]);
+    assert_eq!(row(&output, "ETH"), vec!["ETH", "Short", "2", "2300", "n/a", "$-25.50", "n/a", "n/a"]);
+    assert!(output.contains("ETH        Buy 1 @ 2200 (Limit, 91490942)"), "{}", output);
+    assert!(output.contains("2024-01-01 10:30:00 BTC        Buy 0.5 @ 40000 (fee 9)"), "{}", output);
+}
+
+#[test]
+fn empty_sections_say_none() {
+    let mut snapshot = fixture_snapshot();
+    snapshot.positions.clear();
+    snapshot.open_orders.clear();
+    snapshot.recent_fills.clear();
+    
+    let output = render(&snapshot);
+    assert_eq!(output.matches("  none").count(), 3, "{}", output);
+}
+
+#[test]
+fn json_output_round_trips() {
+    let snapshot = fixture_snapshot();
+    let value = serde_json::to_value(&snapshot).unwrap();
+    
+    assert_eq!(value["address"], ADDRESS);
+    assert_eq!(value["positions"][0]["liquidation_distance"], "20.00");
+    assert_eq!(value["positions"][1]["mark_price"], serde_json::Value::Null);
+    assert_eq!(value["open_orders"][0]["id"], "91490942");
+    assert_eq!(value["recent_fills"][0]["fee"], "9");
+    
+    let parsed: AccountSnapshot = serde_json::from_value(value).unwrap();
+    assert_eq!(parsed.account_value, dec("12500.5"));
+}
+
+#[test]
+fn address_only_config_loads_without_private_key() {
+    let config = Config::load("tests/fixtures/config/address_only.toml").unwrap();
+    assert!(config.hyperliquid.private_key.is_empty());
+    
+    let client = client_for(&config, "http://localhost");
+    assert_eq!(client.account_address().unwrap(), ADDRESS);
+}
+
+#[test]
+fn no_address_and_no_key_is_an_error() {
+    let client = HyperliquidClient::new("http://localhost".to_string(), String::new(), String::new(), true);
+    assert!(client.account_address().is_err());
+}
+
+fn client_for(config: &Config, base_url: &str) -> HyperliquidClient {
+    HyperliquidClient::new(
+        base_url.to_string(),
+        config.hyperliquid.api_key.clone(),
+        config.hyperliquid.private_key.clone(),
+        config.hyperliquid.testnet,
+    )
+    .with_account_address(config.hyperliquid.account_address.clone())
+}
+
+async fn mock_info(server: &MockServer, request: serde_json::Value, data: serde_json::Value) {
+    Mock::given(method("POST"))
+        .and(path("/info"))
+        .and(body_partial_json(request))
+        .respond_with(ResponseTemplate::new(200).set_body_json(data))
+        .expect(1)
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn fetches_account_by_configured_address() {
+    let server = MockServer::start().await;
+    
+    mock_info(
+        &server,
+        json!({ "type": "clearinghouseState", "user": ADDRESS }),
+        json!({
+            "assetPositions": [{
+                "position": {
+                    "coin": "BTC",
+                    "entryPx": "40000",
+                    "leverage": { "type": "cross", "value": 5 },
+                    "liquidationPx": "33600",
+                    "marginUsed": "4200",
+                    "maxLeverage": 50,
+                    "positionValue": "21000",
+                    "returnOnEquity": "0.25",
+                    "szi": "0.5",
+                    "unrealizedPnl": "1000"
+                },
+                "type": "oneWay"
+            }],
+            "crossMarginSummary": null,
+            "marginSummary": {
+                "accountValue": "12500.5",
+                "totalMarginUsed": "4200",
+                "totalNtlPos": "21000",
+                "totalRawUsd": "12500.5"
+            },
+            "time": 1704110400000u64,
+            "withdrawable": "8300.5"
+        }),
+    )
+    .await;
+    mock_info(&server, json!({ "type": "allMids" }), json!({ "BTC": "42000", "ETH": "2250" })).await;
+    mock_info(
+        &server,
+        json!({ "type": "openOrders", "user": ADDRESS }),
+        json!([{ "coin": "ETH", "side": "B", "limitPx": "2200", "sz": "0.4", "origSz": "1", "oid": 91490942u64, "timestamp": 1704106800000u64 }]),
+    )
+    .await;
+    mock_info(
+        &server,
+        json!({ "type": "userFills", "user": ADDRESS }),
+        json!([
+            { "coin": "BTC", "px": "39000", "sz": "0.25", "side": "B", "time": 1704000000000u64, "oid": 1, "tid": 11, "fee": "4" },
+            { "coin": "BTC", "px": "41000", "sz": "0.25", "side": "B", "time": 1704100000000u64, "oid": 2, "tid": 12, "fee": "5" }
+        ]),
+    )
+    .await;
+    
+    let config = Config::load("tests/fixtures/config/address_only.toml").unwrap();
+    let snapshot = account::fetch(&client_for(&config, &server.uri())).await.unwrap();
+    
+    assert_eq!(snapshot.address, ADDRESS);
+    assert_eq!(snapshot.account_value, dec("12500.5"));
+    assert_eq!(snapshot.withdrawable, dec("8300.5"));
+    assert_eq!(snapshot.margin_used, dec("4200"));
+    
+    let btc = &snapshot.positions[0];
+    assert!(matches!(btc.side, PositionSide::Long));
+    assert_eq!(btc.mark_price, Some(dec("42000")));
+    assert_eq!(btc.liquidation_distance, Some(dec("20")));
+    
+    let order = &snapshot.open_orders[0];
+    assert_eq!(order.id, "91490942");
+    assert_eq!(order.quantity, dec("1"));
+    assert_eq!(order.filled_quantity, dec("0.6"));
+    
+    // Most recent first
+    assert_eq!(snapshot.recent_fills.len(), 2);
+    assert_eq!(snapshot.recent_fills[0].id, "12");
+    assert_eq!(snapshot.recent_fills[0].price, dec("41000"));
+}
@@ -0,0 +1,192 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use hyperliquid_trading_bot::{
+    clock::SystemClock,
+    config::Config,
+    costs::CostModel,
+    market_stream::MarketEvent,
+    models::{MarketData, SignalAction, StrategySignal},
+    notifications::{NotificationChannel, NotificationEvent, Notifier},
+    simulated_exchange::SimulatedExchange,
+    strategies::Strategy,
+    trading_bot::TradingBot,
+    Result,
+};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const BUDGET: Duration = Duration::from_millis(100);
+
+/// A strategy that buys every cycle, taking a second over it while `slow`
+/// is set
+struct SlowStrategy {
+    slow: Arc<AtomicBool>,
+}
+
+#[async_trait]
+impl Strategy for SlowStrategy {
+    fn name(&self) -> &str {
+        "slow"
+    }
+    
+    fn symbol(&self) -> &str {
+        "BTC"
+    }
+    
+    fn is_enabled(&self) -> bool {
+        true
+    }
+    
+    async fn analyze(&mut self, _market_data: &MarketData) -> Result<Option<StrategySignal>> {
+        if self.slow.load(Ordering::SeqCst) {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+        Ok(Some(StrategySignal {
+            strategy_name: "slow".to_string(),
+            symbol: "BTC".to_string(),
+            action: SignalAction::Buy,
+            quantity: Decimal::new(1, 3),
+            price: None,
+            confidence: 1.0,
+            metadata: HashMap::new(),
+            reduce_only: false,
+        }))
+    }
+    
+    async fn update_parameters(&mut self, _parameters: HashMap<String, serde_json::Value>) -> Result<()> {
+        Ok(())
+    }
+    
+    fn get_parameters(&self) -> HashMap<String, serde_json::Value> {
+        HashMap::new()
+    }
+    
+    fn validate_parameters(&self, _parameters: &HashMap<String, serde_json::Value>) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Keeps every notification it's sent
+#[derive(Default)]
+struct Recorder {
+    events: Mutex<Vec<NotificationEvent>>,
+}
+
+#[async_trait]
+impl NotificationChannel for Recorder {
+    fn name(&self) -> &str {
+        "recorder"
+    }
+    
+    async fn send_batch(&self, events: &[NotificationEvent]) -> Result<()> {
+        self.events.lock().unwrap().extend_from_slice(events);
+        Ok(())
+    }
+}
+
+fn exchange() -> Arc<SimulatedExchange> {
+    let exchange = SimulatedExchange::new(Decimal::from(100_000), CostModel::default());
+    let price = Decimal::from(40_000);
+    exchange.update(&MarketEvent::Ticker(MarketData {
+        symbol: "BTC".to_string(),
+        price,
+        volume_24h: Decimal::from(1000),
+        change_24h: Decimal::ZERO,
+        high_24h: price,
+        low_24h: price,
+        timestamp: Utc::now(),
+    }));
+    Arc::new(exchange)
+}
+
+async fn bot(
+    exchange: Arc<SimulatedExchange>,
+    slow: &Arc<AtomicBool>,
+    max_consecutive_timeouts: u32,
+    recorder: &Arc<Recorder>,
+) -> TradingBot {
+    let mut config = Config::default();
+    config.trading.dry_run = false;
+    config.risk_management.max_position_size = Decimal::from(1_000_000);
+    config.analysis.timeout_ms = BUDGET.as_millis() as u64;
+    config.analysis.max_consecutive_timeouts = max_consecutive_timeouts;
+    TradingBot::with_client(config, exchange, Arc::new(SystemClock))
+        .await
+        .unwrap()
+        .with_strategy(Box::new(SlowStrategy { slow: slow.clone() }))
+        .with_notifier(Notifier::new(vec![recorder.clone()]))
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_timeout_is_no_signal_for_that_cycle() {
+    let recorder = Arc::new(Recorder::default());
+    let exchange = exchange();
+    let slow = Arc::new(AtomicBool::new(true));
+    let bot = bot(exchange.clone(), &slow, 3, &recorder).await;
+    
+    tokio::time::timeout(BUDGET * 5, bot.run_cycle())
+        .await
+        .expect("the cycle doesn't wait out the slow strategy")
+        .unwrap();
+    assert!(exchange.trades().is_empty());
+    
+    let status = bot.snapshot().await;
+    let strategy = status.strategies.iter().find(|s| s.name == "slow").unwrap();
+    assert!(strategy.enabled);
+    assert_eq!(strategy.analysis.runs, 1);
+    assert_eq!(strategy.analysis.timeouts, 1);
+    assert_eq!(strategy.analysis.consecutive_timeouts, 1);
+    
+    // Once it's quick again it trades as usual
+    slow.store(false, Ordering::SeqCst);
+    bot.run_cycle().await.unwrap();
+    assert_eq!(exchange.trades().len(), 1);
+    
+    let status = bot.snapshot().await;
+    let strategy = status.strategies.iter().find(|s| s.name == "slow").unwrap();
+    assert_eq!(strategy.analysis.runs, 2);
+    assert_eq!(strategy.analysis.timeouts, 1);
+    assert_eq!(strategy.analysis.consecutive_timeouts, 0);
+    assert!(strategy.analysis.max_ms >= BUDGET.as_millis() as u64);
+}
+
+#[tokio::test(start_paused = true)]
+async fn repeated_timeouts_disable_the_strategy() {
+    let recorder = Arc::new(Recorder::default());
+    let exchange = exchange();
+    let slow = Arc::new(AtomicBool::new(true));
+    let bot = bot(exchange.clone(), &slow, 2, &recorder).await;
+    
+    bot.run_cycle().await.unwrap();
+    bot.run_cycle().await.unwrap();
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    
+    let disabled: Vec<_> = recorder
+        .events
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|event| matches!(event, NotificationEvent::StrategyDisabled { .. }))
+        .cloned()
+        .collect();
+    assert_eq!(disabled.len(), 1, "{:?}", disabled);
+    let NotificationEvent::StrategyDisabled { strategy, .. } = &disabled[0] else {
+        unreachable!()
+    };
+    assert_eq!(strategy, "slow");
+    
+    // It stays off even once it would be quick
+    slow.store(false, Ordering::SeqCst);
+    bot.run_cycle().await.unwrap();
+    assert!(exchange.trades().is_empty());
+    
+    let status = bot.snapshot().await;
+    let strategy = status.strategies.iter().find(|s| s.name == "slow").unwrap();
+    assert!(!strategy.enabled);
+    assert!(strategy.analysis.disabled);
+    assert_eq!(strategy.analysis.runs, 2);
+    assert_eq!(strategy.analysis.timeouts, 2);
+}
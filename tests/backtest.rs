@@ -0,0 +1,179 @@
+use chrono::{Duration, NaiveDate, TimeZone, Utc};
+use hyperliquid_trading_bot::{
+    backtest::{data, BacktestResult, Backtester},
+    config::{Config, MarginMode, SlippageConfig, StrategyConfig},
+    models::{MarketData, OrderSide},
+    testing::fixtures,
+};
+use rust_decimal::Decimal;
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::Path;
+use std::str::FromStr;
+
+fn dec(value: &str) -> Decimal {
+    Decimal::from_str(value).unwrap()
+}
+
+async fn run_fixture() -> BacktestResult {
+    run_fixture_with(fixtures::dca_backtest_config(dec("1000"), "1d", json!({}))).await
+}
+
+async fn run_fixture_with(config: Config) -> BacktestResult {
+    let backtester = Backtester::from_config(&config).await.unwrap();
+    
+    let from = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap().and_utc();
+    let to = NaiveDate::from_ymd_opt(2024, 1, 7).unwrap().and_hms_opt(0, 0, 0).unwrap().and_utc();
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/candles");
+    let market_data = data::load_directory(&fixtures, &backtester.symbols(), "1d", from, to).unwrap();
+    
+    backtester.run(market_data).await.unwrap()
+}
+
+// Closes 100, 125, 80, 200, 100, 250 with a 3-bar lookback: the first two
+// bars always buy, then DCA buys only below the 3-bar average (bars 3 and 5).
+// Each buy is $100 of BTC filled 10bps worse, so $100.10 notional plus a
+// $0.1001 fee.
+#[tokio::test]
+async fn dca_backtest_over_fixture_candles() {
+    let result = run_fixture().await;
+    
+    assert_eq!(result.trades.len(), 4);
+    assert_eq!(result.rejected_signals, 0);
+    assert_eq!(result.trades[0].timestamp, Utc.with_ymd_and_hms(2024, 1, 2, 0, 0, 0).unwrap());
+    assert_eq!(result.trades[0].price, dec("100.1"));
+    assert_eq!(result.trades[2].quantity, dec("1.25"));
+    
+    assert_eq!(result.total_fees(), dec("0.4004"));
+    assert_eq!(result.final_equity, dec("1611.6996"));
+    assert_eq!(result.net_pnl(), dec("611.6996"));
+    
+    let equity: Vec<Decimal> = result.equity_curve.iter().map(|s| s.equity).collect();
+    assert_eq!(
+        equity,
+        vec![dec("999.7999"), dec("1024.5998"), dec("943.3997"), dec("1309.3997"), dec("1004.1996"), dec("1611.6996")]
+    );
+    // Peak after bar 4, trough at bar 5
+    assert_eq!(result.max_drawdown().round_dp(12), (dec("305.2001") / dec("1309.3997")).round_dp(12));
+    
+    let breakdown = &result.strategies["dca_btc"];
+    assert_eq!(breakdown.trades, 4);
+    assert_eq!(breakdown.realized_pnl, Decimal::ZERO);
+    assert_eq!(breakdown.unrealized_pnl.round_dp(8), dec("612.1"));
+    assert_eq!(breakdown.net_pnl().round_dp(8), dec("611.6996"));
+}
+
+#[tokio::test]
+async fn backtest_is_deterministic() {
+    let first = run_fixture().await;
+    let second = run_fixture().await;
+    
+    let mut first_trades = Vec::new();
+    let mut second_trades = Vec::new();
+    first.write_trades_csv(&mut first_trades).unwrap();
+    second.write_trades_csv(&mut second_trades).unwrap();
+    
+    assert_eq!(first_trades, second_trades);
+    assert_eq!(first.final_equity, second.final_equity);
+    assert_eq!(first.sharpe_ratio(), second.sharpe_ratio());
+}
+
+// Investing the whole balance on the first bar and never again is buy and
+// hold: with no costs, equity is 10 BTC marked at each close.
+#[tokio::test]
+async fn dca_with_one_buy_matches_buy_and_hold() {
+    let mut config = fixtures::dca_backtest_config(dec("1000"), "1d", json!({}));
+    config.costs.taker_fee_bps = Decimal::ZERO;
+    config.costs.slippage = SlippageConfig::Fixed { bps: Decimal::ZERO };
+    let parameters = &mut config.strategies.get_mut("dca_btc").unwrap().parameters;
+    parameters.insert("investment_amount".to_string(), serde_json::json!("1000"));
+    parameters.insert("max_investment".to_string(), serde_json::json!("1000"));
+    
+    let result = run_fixture_with(config).await;
+    
+    assert_eq!(result.trades.len(), 1);
+    assert_eq!(result.trades[0].quantity, dec("10"));
+    assert_eq!(result.trades[0].price, dec("100"));
+    let equity: Vec<Decimal> = result.equity_curve.iter().map(|s| s.equity).collect();
+    assert_eq!(
+        equity,
+        vec![dec("1000"), dec("1250"), dec("800"), dec("2000"), dec("1000"), dec("2500")]
+    );
+    // Same return as holding from the first close (100) to the last (250)
+    assert_eq!(result.net_pnl(), dec("1500"));
+}
+
+// The first three bars (closes 100, 125, 80) only warm the strategy up. With
+// its 3-bar lookback already full, DCA then buys only below the average:
+// 200 is above (125 + 80 + 200) / 3, 100 is below (80 + 200 + 100) / 3.
+#[tokio::test]
+async fn warm_up_bars_prime_strategies_without_trading() {
+    let mut config = fixtures::dca_backtest_config(dec("1000"), "1d", json!({}));
+    config.backtest.warmup_bars = 3;
+    
+    let result = run_fixture_with(config).await;
+    
+    assert_eq!(result.equity_curve.len(), 3);
+    assert_eq!(result.equity_curve[0].timestamp, Utc.with_ymd_and_hms(2024, 1, 5, 0, 0, 0).unwrap());
+    assert_eq!(result.trades.len(), 1);
+    assert_eq!(result.trades[0].timestamp, Utc.with_ymd_and_hms(2024, 1, 6, 0, 0, 0).unwrap());
+    assert_eq!(result.trades[0].price, dec("100.1"));
+}
+
+fn hourly_bars(closes: &[i64]) -> Vec<MarketData> {
+    let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    closes
+        .iter()
+        .enumerate()
+        .map(|(i, &close)| MarketData {
+            symbol: "ETH".to_string(),
+            price: Decimal::from(close),
+            volume_24h: Decimal::from(1000),
+            change_24h: Decimal::ZERO,
+            high_24h: Decimal::from(close),
+            low_24h: Decimal::from(close),
+            timestamp: start + Duration::hours(i as i64 + 1),
+        })
+        .collect()
+}
+
+// 50 bars climbing 100 -> 149, then 40 falling 148 -> 109. After a 26-bar
+// warm-up (the slow period), momentum buys into the uptrend while MACD is
+// above its signal line and sells once the trend turns.
+#[tokio::test]
+async fn momentum_follows_an_engineered_trend() {
+    let mut config = Config::default();
+    config.backtest.warmup_bars = 26;
+    config.strategies.insert(
+        "momentum_eth".to_string(),
+        StrategyConfig {
+            enabled: true,
+            strategy_type: "momentum".to_string(),
+            symbol: "ETH".to_string(),
+            position_size: dec("100"),
+            parameters: HashMap::new(),
+            leverage: None,
+            margin_mode: MarginMode::Cross,
+            eval_interval_secs: None,
+        },
+    );
+    let closes: Vec<i64> = (100..150).chain((109..149).rev()).collect();
+    let peak = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap() + Duration::hours(50);
+    
+    let backtester = Backtester::from_config(&config).await.unwrap();
+    let result = backtester.run(hourly_bars(&closes)).await.unwrap();
+    
+    let buys: Vec<_> = result.trades.iter().filter(|t| matches!(t.side, OrderSide::Buy)).collect();
+    let sells: Vec<_> = result.trades.iter().filter(|t| matches!(t.side, OrderSide::Sell)).collect();
+    
+    // Every bar from the end of the warm-up to the peak buys
+    assert_eq!(buys.len(), 24);
+    assert!(buys.iter().all(|t| t.timestamp <= peak));
+    assert_eq!(buys[0].timestamp, Utc.with_ymd_and_hms(2024, 1, 2, 3, 0, 0).unwrap());
+    // Then it sells from the first lower close on, pausing while RSI and the
+    // moving averages still disagree with MACD
+    assert_eq!(sells.len(), 31);
+    assert!(sells.iter().all(|t| t.timestamp > peak));
+    assert_eq!(sells[0].timestamp, peak + Duration::hours(1));
+    assert_eq!(result.rejected_signals, 0);
+}
@@ -0,0 +1,188 @@
+use chrono::{DateTime, TimeZone, Utc};
+use hyperliquid_trading_bot::{
+    backtest::{report::BacktestReport, BacktestResult, BacktestTrade, StrategyBreakdown},
+    models::OrderSide,
+    storage::EquitySample,
+};
+use rust_decimal::Decimal;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::str::FromStr;
+
+fn dec(value: &str) -> Decimal {
+    Decimal::from_str(value).unwrap()
+}
+
+fn day(month: u32, day: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, month, day, 0, 0, 0).unwrap()
+}
+
+fn trade(at: DateTime<Utc>, strategy: &str, symbol: &str, side: OrderSide, quantity: &str, price: &str, realized: &str) -> BacktestTrade {
+    BacktestTrade {
+        timestamp: at,
+        strategy: strategy.to_string(),
+        symbol: symbol.to_string(),
+        side,
+        quantity: dec(quantity),
+        price: dec(price),
+        fee: Decimal::ONE,
+        slippage: dec("0.5"),
+        realized_pnl: dec(realized),
+    }
+}
+
+fn breakdown(trades: u64, volume: &str, funding: &str, realized: &str) -> StrategyBreakdown {
+    StrategyBreakdown {
+        trades,
+        volume: dec(volume),
+        fees: Decimal::from(trades),
+        slippage: Decimal::from(trades) / Decimal::from(2),
+        funding: dec(funding),
+        realized_pnl: dec(realized),
+        unrealized_pnl: Decimal::ZERO,
+    }
+}
+
+// Strategy "a" trades BTC twice for +100 and +30; "b" trades ETH once for
+// -50. Every fill pays $1 in fees and $0.50 in slippage, and "a" pays $1
+// of funding on its last hold.
+fn fixture_result() -> BacktestResult {
+    let trades = vec![
+        trade(day(1, 10), "a", "BTC", OrderSide::Buy, "1", "100", "0"),
+        trade(day(1, 20), "a", "BTC", OrderSide::Sell, "1", "200", "100"),
+        trade(day(1, 31), "b", "ETH", OrderSide::Buy, "10", "10", "0"),
+        trade(day(2, 10), "b", "ETH", OrderSide::Sell, "10", "5", "-50"),
+        trade(day(2, 20), "a", "BTC", OrderSide::Buy, "1", "100", "0"),
+        trade(day(2, 29), "a", "BTC", OrderSide::Sell, "1", "130", "30"),
+    ];
+    let equity_curve = [
+        (day(1, 10), "999"),
+        (day(1, 20), "1098"),
+        (day(1, 31), "1097"),
+        (day(2, 10), "1046"),
+        (day(2, 20), "1045"),
+        (day(2, 29), "1073"),
+    ]
+    .into_iter()
+    .map(|(timestamp, equity)| EquitySample {
+        timestamp,
+        equity: dec(equity),
+        available_balance: dec(equity),
+        total_pnl: dec(equity) - dec("1000"),
+    })
+    .collect();
+    
+    BacktestResult {
+        initial_balance: dec("1000"),
+        final_equity: dec("1073"),
+        trades,
+        equity_curve,
+        strategies: BTreeMap::from([
+            ("a".to_string(), breakdown(4, "530", "1", "130")),
+            ("b".to_string(), breakdown(2, "150", "0", "-50")),
+        ]),
+        symbols: BTreeMap::from([
+            ("BTC".to_string(), breakdown(4, "530", "1", "130")),
+            ("ETH".to_string(), breakdown(2, "150", "0", "-50")),
+        ]),
+        rejected_signals: 2,
+        exposed_samples: 3,
+    }
+}
+
+/// Replaces every value with its JSON type, keeping one element per array
+fn structure(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(map.iter().map(|(key, value)| (key.clone(), structure(value))).collect()),
+        Value::Array(items) => Value::Array(items.first().map(structure).into_iter().collect()),
+        Value::String(_) => json!("string"),
+        Value::Number(_) => json!("number"),
+        Value::Bool(_) => json!("bool"),
+        Value::Null => json!("null"),
+    }
+}
+
+#[test]
+fn json_matches_golden_structure() {
+    let mut output = Vec::new();
+    BacktestReport::new(&fixture_result()).write_json(&mut output).unwrap();
+    let report: Value = serde_json::from_slice(&output).unwrap();
+    
+    let golden = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/backtest/report_structure.json");
+    let golden: Value = serde_json::from_str(&std::fs::read_to_string(golden).unwrap()).unwrap();
+    assert_eq!(structure(&report), golden);
+}
+
+#[test]
+fn headline_stats() {
+    let summary = BacktestReport::new(&fixture_result()).summary;
+    
+    assert_eq!(summary.net_pnl, dec("73"));
+    // Six fills at $1 fee and $0.50 slippage, plus $1 of funding
+    assert_eq!(summary.gross_pnl, dec("83"));
+    assert_eq!(summary.total_return, dec("0.073"));
+    assert_eq!((summary.fees, summary.slippage, summary.funding), (dec("6"), dec("3"), dec("1")));
+    
+    // 1098 on Jan 20 down to 1045 on Feb 20
+    assert_eq!(summary.max_drawdown.round_dp(6), dec("0.04827"));
+    assert_eq!(summary.max_drawdown_peak, Some(day(1, 20)));
+    assert_eq!(summary.max_drawdown_trough, Some(day(2, 20)));
+    
+    // +7.3% over the 50 days from the first sample to the last
+    assert!((summary.cagr.unwrap() - 0.673135).abs() < 1e-6);
+    assert!(summary.sharpe_ratio > 0.0);
+    
+    // Closed trades made +100, -50 and +30
+    assert_eq!(summary.closed_trades, 3);
+    assert_eq!(summary.win_rate, Some(2.0 / 3.0));
+    assert!((summary.profit_factor.unwrap() - 2.6).abs() < 1e-9);
+    assert_eq!(summary.exposure, 0.5);
+    // $680 traded against $1059.67 average equity
+    assert!((summary.turnover - 0.641711).abs() < 1e-6);
+    assert_eq!((summary.trades, summary.rejected_signals), (6, 2));
+}
+
+#[test]
+fn breakdowns_monthly_returns_and_top_trades() {
+    let report = BacktestReport::new(&fixture_result());
+    
+    let strategies: Vec<(&str, Decimal, Option<f64>)> =
+        report.strategies.iter().map(|row| (row.name.as_str(), row.net_pnl, row.win_rate)).collect();
+    assert_eq!(strategies, vec![("a", dec("125"), Some(1.0)), ("b", dec("-52"), Some(0.0))]);
+    let symbols: Vec<&str> = report.symbols.iter().map(|row| row.name.as_str()).collect();
+    assert_eq!(symbols, vec!["BTC", "ETH"]);
+    
+    // January against the initial balance, February against January's close
+    let months: Vec<(i32, u32, Decimal)> =
+        report.monthly_returns.iter().map(|m| (m.year, m.month, m.change.round_dp(6))).collect();
+    assert_eq!(months, vec![(2024, 1, dec("0.097")), (2024, 2, dec("-0.021878"))]);
+    
+    let best: Vec<Decimal> = report.best_trades.iter().map(|t| t.realized_pnl).collect();
+    assert_eq!(best, vec![dec("100"), dec("30")]);
+    let worst: Vec<Decimal> = report.worst_trades.iter().map(|t| t.realized_pnl).collect();
+    assert_eq!(worst, vec![dec("-50")]);
+}
+
+#[test]
+fn renders_text_and_standalone_html() {
+    let report = BacktestReport::new(&fixture_result());
+    
+    let mut text = Vec::new();
+    report.write_text(&mut text).unwrap();
+    let text = String::from_utf8(text).unwrap();
+    assert!(text.contains("Net PnL:          $73.00 (7.30%)"));
+    assert!(text.contains("Max drawdown:     4.82% (2024-01-20 00:00 to 2024-02-20 00:00)"));
+    assert!(text.contains("Win rate:         66.67% of 3 closed trades"));
+    assert!(text.contains("Profit factor:    2.60"));
+    
+    let mut html = Vec::new();
+    report.write_html(&mut html).unwrap();
+    let html = String::from_utf8(html).unwrap();
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<svg"));
+    // Nothing is loaded from elsewhere
+    assert!(!html.contains("<script"));
+    assert!(!html.contains("<link"));
+    assert!(!html.contains("src="));
+}
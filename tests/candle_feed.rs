@@ -0,0 +1,254 @@
+use async_trait::async_trait;
+use chrono::Duration as ChronoDuration;
+use futures_util::{SinkExt, StreamExt};
+use hyperliquid_trading_bot::{
+    api::types::{Candle, CandleUpdate},
+    candle_feed::CandleFeed,
+    config::Config,
+    models::{MarketData, SignalAction, StrategySignal},
+    runner::RunMode,
+    strategies::{MomentumStrategy, Strategy},
+    testing::{self, fixtures, MockTradingClient},
+    Result,
+};
+use rust_decimal::Decimal;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+use tokio_tungstenite::tungstenite::Message;
+
+const HOUR_MS: u64 = 3_600_000;
+
+fn open(hours: u64) -> u64 {
+    fixtures::start().timestamp_millis() as u64 + hours * HOUR_MS
+}
+
+fn update(symbol: &str, interval: &str, hours: u64, close: i64) -> CandleUpdate {
+    CandleUpdate {
+        s: symbol.to_string(),
+        i: interval.to_string(),
+        candle: fixtures::candle(open(hours), Decimal::from(close)),
+    }
+}
+
+fn closes(candles: &[Candle]) -> Vec<Decimal> {
+    candles.iter().map(|candle| candle.c).collect()
+}
+
+fn decimals(values: &[i64]) -> Vec<Decimal> {
+    values.iter().copied().map(Decimal::from).collect()
+}
+
+#[test]
+fn candles_join_the_window_once_they_close() {
+    let feed = CandleFeed::default();
+    feed.track("SOL", "1h");
+    
+    // Pushed again with each trade until the next candle opens
+    assert_eq!(feed.update(&update("SOL", "1h", 0, 100)), None);
+    assert_eq!(feed.update(&update("SOL", "1h", 0, 102)), None);
+    assert!(feed.closes("SOL", "1h", 10).is_empty());
+    assert_eq!(feed.latest("SOL", "1h"), None);
+    
+    let closed = feed.update(&update("SOL", "1h", 1, 103)).unwrap();
+    assert_eq!((closed.t, closed.c), (open(0), Decimal::from(102)));
+    assert_eq!(feed.latest("SOL", "1h").map(|candle| candle.c), Some(Decimal::from(102)));
+    
+    // A late push for a closed candle changes nothing
+    assert_eq!(feed.update(&update("SOL", "1h", 0, 90)), None);
+    assert_eq!(feed.update(&update("SOL", "1h", 1, 104)), None);
+    assert_eq!(feed.update(&update("SOL", "1h", 2, 105)).map(|candle| candle.c), Some(Decimal::from(104)));
+    assert_eq!(feed.closes("SOL", "1h", 10), decimals(&[102, 104]));
+    assert_eq!(feed.closes("SOL", "1h", 1), decimals(&[104]));
+    
+    // Series nobody tracks are ignored
+    assert_eq!(feed.update(&update("SOL", "5m", 0, 1)), None);
+    assert_eq!(feed.update(&update("SOL", "5m", 1, 1)), None);
+    assert_eq!(feed.update(&update("ETH", "1h", 3, 1)), None);
+    assert!(feed.closes("ETH", "1h", 10).is_empty());
+    assert_eq!(feed.tracked(), vec![("SOL".to_string(), "1h".to_string())]);
+}
+
+#[test]
+fn seeded_candles_leave_out_the_one_still_forming() {
+    let feed = CandleFeed::new(3);
+    feed.track("SOL", "1h");
+    let now = fixtures::start() + ChronoDuration::minutes(4 * 60 + 30);
+    
+    let history = fixtures::candles(HOUR_MS, decimals(&[1, 2, 3, 4, 5]));
+    assert_eq!(closes(&feed.seed("SOL", "1h", history.clone(), now)), decimals(&[1, 2, 3, 4]));
+    // The window holds the last three
+    assert_eq!(feed.closes("SOL", "1h", 10), decimals(&[2, 3, 4]));
+    
+    // Fetching again only adds what closed since
+    assert!(feed.seed("SOL", "1h", history.clone(), now).is_empty());
+    assert_eq!(closes(&feed.seed("SOL", "1h", history, now + ChronoDuration::hours(1))), decimals(&[5]));
+    
+    // The stream carries on from there
+    assert_eq!(feed.update(&update("SOL", "1h", 4, 6)), None);
+    assert_eq!(feed.update(&update("SOL", "1h", 5, 7)), None);
+    assert_eq!(feed.update(&update("SOL", "1h", 6, 8)).map(|candle| candle.c), Some(Decimal::from(7)));
+    assert_eq!(feed.closes("SOL", "1h", 10), decimals(&[4, 5, 7]));
+}
+
+fn tick(price: i64) -> MarketData {
+    MarketData {
+        symbol: "SOL".to_string(),
+        price: Decimal::from(price),
+        volume_24h: Decimal::ZERO,
+        change_24h: Decimal::ZERO,
+        high_24h: Decimal::from(price),
+        low_24h: Decimal::from(price),
+        timestamp: fixtures::start(),
+    }
+}
+
+#[tokio::test]
+async fn momentum_can_run_on_candle_closes() {
+    let parameters = |interval: &str| HashMap::from([("candle_interval".to_string(), json!(interval))]);
+    let mut candles = MomentumStrategy::new("momentum".to_string(), "SOL".to_string());
+    assert!(candles.validate_parameters(&parameters("2h")).is_err());
+    candles.validate_parameters(&parameters("1h")).unwrap();
+    candles.update_parameters(parameters("1h")).await.unwrap();
+    assert_eq!(candles.candle_interval(), Some("1h"));
+    let mut ticks = MomentumStrategy::new("momentum".to_string(), "SOL".to_string());
+    assert_eq!(ticks.candle_interval(), None);
+    
+    for (hour, close) in (100..140).enumerate() {
+        let bar = MarketData {
+            timestamp: fixtures::start() + ChronoDuration::hours(hour as i64 + 1),
+            ..tick(close)
+        };
+        candles.on_candle(&bar);
+        ticks.on_candle(&bar);
+    }
+    
+    // Forty rising closes call for a buy; without opting in there's no history
+    let signal = candles.analyze(&tick(139)).await.unwrap().unwrap();
+    assert!(matches!(signal.action, SignalAction::Buy));
+    assert_eq!(signal.price, Some(Decimal::from(139)));
+    assert!(ticks.analyze(&tick(139)).await.unwrap().is_none());
+    
+    // Going back to sampled prices starts the history over
+    candles.update_parameters(parameters("")).await.unwrap();
+    assert_eq!(candles.candle_interval(), None);
+    assert!(candles.analyze(&tick(139)).await.unwrap().is_none());
+}
+
+/// A websocket sending whatever's pushed into the returned sender
+async fn stream() -> (String, mpsc::UnboundedSender<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    let (frames, mut pushed) = mpsc::unbounded_channel::<String>();
+    
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+        loop {
+            tokio::select! {
+                frame = pushed.recv() => match frame {
+                    Some(frame) => ws.send(Message::Text(frame)).await.unwrap(),
+                    None => break,
+                },
+                message = ws.next() => if !matches!(message, Some(Ok(_))) {
+                    break;
+                },
+            }
+        }
+    });
+    
+    (url, frames)
+}
+
+/// Keeps the closes of the hourly SOL candles it's handed
+struct Recorder {
+    closes: Arc<Mutex<Vec<Decimal>>>,
+}
+
+#[async_trait]
+impl Strategy for Recorder {
+    fn name(&self) -> &str {
+        "recorder"
+    }
+    
+    fn symbol(&self) -> &str {
+        "SOL"
+    }
+    
+    fn is_enabled(&self) -> bool {
+        true
+    }
+    
+    async fn analyze(&mut self, _market_data: &MarketData) -> Result<Option<StrategySignal>> {
+        Ok(None)
+    }
+    
+    async fn update_parameters(&mut self, _parameters: HashMap<String, serde_json::Value>) -> Result<()> {
+        Ok(())
+    }
+    
+    fn get_parameters(&self) -> HashMap<String, serde_json::Value> {
+        HashMap::new()
+    }
+    
+    fn validate_parameters(&self, _parameters: &HashMap<String, serde_json::Value>) -> Result<()> {
+        Ok(())
+    }
+    
+    fn candle_interval(&self) -> Option<&str> {
+        Some("1h")
+    }
+    
+    fn on_candle(&mut self, candle: &MarketData) {
+        self.closes.lock().unwrap().push(candle.price);
+    }
+}
+
+#[tokio::test]
+async fn strategies_are_handed_candles_as_they_close() {
+    let (url, frames) = stream().await;
+    let exchange = Arc::new(MockTradingClient::new(Decimal::from(10_000)));
+    exchange.set_price("SOL", Decimal::from(100));
+    exchange.set_candles("SOL", fixtures::candles(HOUR_MS, decimals(&[1, 2, 3, 4])));
+    exchange.clock().advance(ChronoDuration::minutes(3 * 60 + 30));
+    let mut config = Config::default();
+    config.hyperliquid.ws_url = url;
+    config.preflight.ignore_failures = true;
+    config.preflight.websocket_timeout_secs = 1;
+    let closes = Arc::new(Mutex::new(Vec::new()));
+    let recorder = Recorder { closes: closes.clone() };
+    let bot = testing::mock_bot(config, exchange).await.unwrap().with_strategy(Box::new(recorder));
+    
+    let wait_for = |expected: Vec<Decimal>| {
+        let closes = closes.clone();
+        async move {
+            let deadline = Instant::now() + Duration::from_secs(5);
+            while *closes.lock().unwrap() != expected && Instant::now() < deadline {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        }
+    };
+    let pushed = async {
+        // The candles closed before starting, fetched over REST
+        wait_for(decimals(&[1, 2, 3])).await;
+        
+        for (hours, close) in [(3, 4), (3, 5), (4, 6)] {
+            let candle = fixtures::candle(open(hours), Decimal::from(close));
+            let frame = json!({ "channel": "candle", "data": { "s": "SOL", "i": "1h", "t": candle.t, "o": candle.o,
+                "h": candle.h, "l": candle.l, "c": candle.c, "v": candle.v } });
+            frames.send(frame.to_string()).unwrap();
+        }
+        wait_for(decimals(&[1, 2, 3, 5])).await;
+    };
+    tokio::select! {
+        () = pushed => {}
+        result = bot.run(RunMode::Continuous) => panic!("the bot stopped: {:?}", result),
+    }
+    
+    // The candle still forming isn't handed over
+    assert_eq!(*closes.lock().unwrap(), decimals(&[1, 2, 3, 5]));
+}
@@ -0,0 +1,194 @@
+use async_trait::async_trait;
+use futures_util::StreamExt;
+use hyperliquid_trading_bot::{
+    api::client::TradingClient,
+    chaos::{Chaos, ChaosClient},
+    clock::SimulatedClock,
+    config::{ChaosConfig, Config},
+    costs::CostModel,
+    market_stream::MarketEvent,
+    models::{MarketData, OrderSide, SignalAction, StrategySignal},
+    paper::PaperExchange,
+    simulated_exchange::SimulatedExchange,
+    strategies::Strategy,
+    testing::{self, fixtures, MockTradingClient},
+    trading_bot::TradingBot,
+    Result,
+};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+
+const CYCLES: usize = 120;
+
+/// Bids 1% under the market every cycle, so some bids fill as the price
+/// swings down and others rest until the stale-order sweep cancels them
+struct Quoter;
+
+#[async_trait]
+impl Strategy for Quoter {
+    fn name(&self) -> &str {
+        "quoter"
+    }
+    
+    fn symbol(&self) -> &str {
+        "BTC"
+    }
+    
+    fn is_enabled(&self) -> bool {
+        true
+    }
+    
+    async fn analyze(&mut self, market_data: &MarketData) -> Result<Option<StrategySignal>> {
+        Ok(Some(StrategySignal {
+            strategy_name: "quoter".to_string(),
+            symbol: "BTC".to_string(),
+            action: SignalAction::Buy,
+            quantity: Decimal::new(1, 3),
+            price: Some((market_data.price * Decimal::new(99, 2)).round_dp(1)),
+            confidence: 1.0,
+            metadata: HashMap::new(),
+            reduce_only: false,
+        }))
+    }
+    
+    async fn update_parameters(&mut self, _parameters: HashMap<String, serde_json::Value>) -> Result<()> {
+        Ok(())
+    }
+    
+    fn get_parameters(&self) -> HashMap<String, serde_json::Value> {
+        HashMap::new()
+    }
+    
+    fn validate_parameters(&self, _parameters: &HashMap<String, serde_json::Value>) -> Result<()> {
+        Ok(())
+    }
+}
+
+fn chaos_config(seed: u64) -> ChaosConfig {
+    ChaosConfig {
+        enabled: true,
+        seed,
+        timeout_probability: 0.1,
+        timeout_ms: 5,
+        reject_probability: 0.1,
+        disconnect_probability: 0.1,
+        disconnect_secs: 30,
+        delayed_fill_probability: 0.2,
+        fill_delay_secs: 10,
+        stale_price_probability: 0.1,
+        ..ChaosConfig::default()
+    }
+}
+
+/// A price swinging 2% either side of 40,000 and back every ten cycles
+fn swing(cycle: usize) -> Decimal {
+    const STEPS: [i64; 10] = [0, 1, 2, 1, 0, -1, -2, -3, -2, -1];
+    Decimal::from(40_000 + 400 * STEPS[cycle % STEPS.len()])
+}
+
+/// A websocket that takes every connection and never says anything
+async fn quiet_websocket() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                if let Ok(mut ws) = tokio_tungstenite::accept_async(stream).await {
+                    while let Some(Ok(_)) = ws.next().await {}
+                }
+            });
+        }
+    });
+    url
+}
+
+#[tokio::test]
+async fn the_same_seed_injects_the_same_failures() {
+    async fn run(seed: u64) -> Vec<String> {
+        let clock = Arc::new(SimulatedClock::new(fixtures::start()));
+        let exchange = SimulatedExchange::new(Decimal::from(100_000), CostModel::default());
+        exchange.update(&MarketEvent::Ticker(fixtures::market_data("BTC", Decimal::from(40_000))));
+        let client = ChaosClient::new(Arc::new(exchange), Arc::new(Chaos::new(chaos_config(seed), clock.clone())));
+        
+        let mut outcomes = Vec::new();
+        for i in 0..50 {
+            let order = fixtures::order(&i.to_string(), "BTC", OrderSide::Buy, Decimal::new(1, 3), Decimal::from(39_000));
+            outcomes.push(format!("{:?}", client.place_order(&order).await.map_err(|e| e.to_string())));
+            let price = client.get_market_data("BTC").await.map(|data| data.price);
+            outcomes.push(format!("{:?}", price.map_err(|e| e.to_string())));
+            clock.advance(chrono::Duration::seconds(5));
+        }
+        outcomes
+    }
+    
+    assert_eq!(run(7).await, run(7).await);
+    assert_ne!(run(7).await, run(8).await);
+}
+
+#[tokio::test]
+async fn chaos_is_ignored_outside_dry_runs() {
+    let client = Arc::new(MockTradingClient::new(Decimal::from(10_000)));
+    let mut config = Config {
+        chaos: chaos_config(7),
+        ..Config::default()
+    };
+    
+    config.trading.dry_run = false;
+    assert_eq!(testing::mock_bot(config.clone(), client.clone()).await.unwrap().chaos_stats(), None);
+    
+    config.trading.dry_run = true;
+    assert!(testing::mock_bot(config, client).await.unwrap().chaos_stats().is_some());
+}
+
+#[tokio::test]
+async fn a_chaos_run_leaks_no_orders() {
+    let market = Arc::new(MockTradingClient::new(Decimal::from(10_000)));
+    market.script_prices("BTC", (0..CYCLES * 2).map(swing));
+    let clock = market.clock().clone();
+    
+    let mut config = Config::default();
+    config.trading.dry_run = true;
+    config.storage.enabled = false;
+    config.hyperliquid.ws_url = quiet_websocket().await;
+    config.chaos = chaos_config(7);
+    
+    let paper = Arc::new(PaperExchange::open(&config, market.clone(), None).unwrap());
+    let bot = TradingBot::with_paper(config, paper.clone(), Arc::new(clock.clone()))
+        .await
+        .unwrap()
+        .with_strategy(Box::new(Quoter));
+    bot.connect_websocket().await.unwrap();
+    
+    let mut failed_cycles = 0;
+    for _ in 0..CYCLES {
+        if let Err(e) = bot.run_cycle().await {
+            // Injected failures are all worth retrying
+            assert!(e.is_retryable(), "{}", e);
+            failed_cycles += 1;
+        }
+        clock.advance(chrono::Duration::seconds(5));
+    }
+    
+    let stats = bot.chaos_stats().unwrap();
+    assert!(stats.timeouts > 0, "{:?}", stats);
+    assert!(stats.rejections > 0, "{:?}", stats);
+    assert!(stats.disconnects > 0, "{:?}", stats);
+    assert!(stats.delayed_fills > 0, "{:?}", stats);
+    assert!(stats.stale_prices > 0, "{:?}", stats);
+    assert!(failed_cycles > 0 && failed_cycles < CYCLES, "{} cycles failed", failed_cycles);
+    
+    // The bot rode the failures out: it kept trading and never stopped itself
+    let status = bot.get_status().await;
+    assert!(status.successful_trades > 0 && status.failed_trades > 0, "{:?}", status);
+    assert!(!paper.exchange().trades().is_empty());
+    assert!(!bot.kill_switch_engaged());
+    
+    // Nothing it placed is left behind once the kill switch cleans up, on
+    // the book or held back in flight
+    bot.engage_kill_switch("chaos run over").await;
+    assert!(bot.kill_switch_engaged());
+    assert!(paper.exchange().get_open_orders().await.unwrap().is_empty());
+    assert_eq!(bot.chaos_stats().unwrap().held_orders, 0);
+}
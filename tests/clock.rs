@@ -0,0 +1,151 @@
+use async_trait::async_trait;
+use chrono::{Duration, TimeZone, Utc};
+use hyperliquid_trading_bot::{
+    api::client::TradingClient,
+    clock::{Clock, SimulatedClock},
+    config::Config,
+    costs::CostModel,
+    market_stream::MarketEvent,
+    models::{MarketData, SignalAction, StrategySignal},
+    simulated_exchange::SimulatedExchange,
+    strategies::{DCAStrategy, Strategy},
+    trading_bot::TradingBot,
+    Result,
+};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration as StdDuration;
+
+fn ticker(price: i64, clock: &SimulatedClock) -> MarketData {
+    let price = Decimal::from(price);
+    MarketData {
+        symbol: "BTC".to_string(),
+        price,
+        volume_24h: Decimal::from(1000),
+        change_24h: Decimal::ZERO,
+        high_24h: price,
+        low_24h: price,
+        timestamp: clock.now(),
+    }
+}
+
+#[tokio::test]
+async fn dca_buys_once_per_interval_across_simulated_days() {
+    let clock = SimulatedClock::new(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap());
+    let mut dca = DCAStrategy::new("dca_btc".to_string(), "BTC".to_string());
+    dca.update_parameters(HashMap::from([
+        ("interval_hours".to_string(), serde_json::json!(24)),
+        ("lookback_period".to_string(), serde_json::json!(1000)),
+    ]))
+    .await
+    .unwrap();
+    
+    // Three days of hourly ticks, in no time at all
+    let mut buys = Vec::new();
+    for _ in 0..72 {
+        let market_data = ticker(40_000, &clock);
+        dca.on_market_data(&market_data);
+        if let Some(signal) = dca.analyze(&market_data).await.unwrap() {
+            dca.on_fill(&signal, market_data.price, clock.now());
+            buys.push(clock.now());
+        }
+        clock.advance(Duration::hours(1));
+    }
+    
+    let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    assert_eq!(buys, vec![start, start + Duration::days(1), start + Duration::days(2)]);
+}
+
+/// Bids for 0.01 BTC at 39,000, below the market, on its first cycle
+#[derive(Default)]
+struct Bidder {
+    bid: bool,
+}
+
+#[async_trait]
+impl Strategy for Bidder {
+    fn name(&self) -> &str {
+        "bidder"
+    }
+    
+    fn symbol(&self) -> &str {
+        "BTC"
+    }
+    
+    fn is_enabled(&self) -> bool {
+        true
+    }
+    
+    async fn analyze(&mut self, _market_data: &MarketData) -> Result<Option<StrategySignal>> {
+        if std::mem::replace(&mut self.bid, true) {
+            return Ok(None);
+        }
+        Ok(Some(StrategySignal {
+            strategy_name: "bidder".to_string(),
+            symbol: "BTC".to_string(),
+            action: SignalAction::Buy,
+            quantity: Decimal::new(1, 2),
+            price: Some(Decimal::from(39_000)),
+            confidence: 1.0,
+            metadata: HashMap::new(),
+            reduce_only: false,
+        }))
+    }
+    
+    async fn update_parameters(&mut self, _parameters: HashMap<String, serde_json::Value>) -> Result<()> {
+        Ok(())
+    }
+    
+    fn get_parameters(&self) -> HashMap<String, serde_json::Value> {
+        HashMap::new()
+    }
+    
+    fn validate_parameters(&self, _parameters: &HashMap<String, serde_json::Value>) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn resting_orders_time_out_on_the_bot_clock() {
+    let clock = SimulatedClock::new(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap());
+    let exchange = Arc::new(SimulatedExchange::new(Decimal::from(10_000), CostModel::default()));
+    exchange.update(&MarketEvent::Ticker(ticker(40_000, &clock)));
+    
+    let mut config = Config::default();
+    config.trading.dry_run = false;
+    config.trading.order_timeout_seconds = 30;
+    let bot = TradingBot::with_client(config, exchange.clone(), Arc::new(clock.clone()))
+        .await
+        .unwrap()
+        .with_strategy(Box::new(Bidder::default()));
+    
+    // The bid rests on the book
+    bot.run_cycle().await.unwrap();
+    assert_eq!(exchange.get_open_orders().await.unwrap().len(), 1);
+    
+    clock.advance(Duration::seconds(29));
+    bot.run_cycle().await.unwrap();
+    assert_eq!(exchange.get_open_orders().await.unwrap().len(), 1);
+    
+    clock.advance(Duration::seconds(1));
+    bot.run_cycle().await.unwrap();
+    assert!(exchange.get_open_orders().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn simulated_sleepers_wake_when_the_clock_passes_their_deadline() {
+    let clock = SimulatedClock::new(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap());
+    let deadline = clock.now() + Duration::days(365);
+    
+    let sleeper = {
+        let clock = clock.clone();
+        tokio::spawn(async move { clock.sleep_until(deadline).await })
+    };
+    clock.advance(Duration::days(364));
+    tokio::time::sleep(StdDuration::from_millis(10)).await;
+    assert!(!sleeper.is_finished());
+    
+    clock.advance(Duration::days(1));
+    tokio::time::timeout(StdDuration::from_secs(1), sleeper).await.unwrap().unwrap();
+}
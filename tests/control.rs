@@ -0,0 +1,134 @@
+use hyperliquid_trading_bot::{
+    config::ControlConfig,
+    control::{self, StatusSnapshot},
+    models::{OrderSide, PositionSide},
+    testing::{self, fixtures, MockTradingClient},
+    Error,
+};
+use rust_decimal::Decimal;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn control_config() -> ControlConfig {
+    let directory = fixtures::temp_dir();
+    ControlConfig {
+        enabled: true,
+        socket_path: directory.join("bot.sock").to_string_lossy().into_owned(),
+        tcp_port: 0,
+    }
+}
+
+/// Serves `config`'s control endpoint for a bot trading against a mock
+/// exchange, once it's listening
+async fn serve(config: &ControlConfig) {
+    let exchange = Arc::new(MockTradingClient::new(Decimal::from(10_000)));
+    exchange.set_price("BTC", Decimal::from(40_000));
+    let bot = Arc::new(testing::mock_bot(fixtures::dca_config(json!({ "investment_amount": "100" })), exchange).await.unwrap());
+    tokio::spawn(control::serve(bot, config.clone()));
+    
+    for _ in 0..100 {
+        if control::query_status(config).await.is_ok() {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("the control endpoint never came up");
+}
+
+#[tokio::test]
+async fn status_is_served_to_the_status_subcommand() {
+    let config = control_config();
+    serve(&config).await;
+    
+    let status = control::query_status(&config).await.unwrap();
+    assert_eq!(status.strategies.len(), 1);
+    let strategy = &status.strategies[0];
+    assert_eq!((strategy.name.as_str(), strategy.symbol.as_str(), strategy.enabled), ("dca_btc", "BTC", true));
+    assert!(status.open_orders.is_empty());
+}
+
+/// What the `status` subcommand prints for `snapshot`
+fn rendered(snapshot: &StatusSnapshot) -> String {
+    let mut output = Vec::new();
+    snapshot.write(&mut output).unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+#[tokio::test]
+async fn the_status_subcommand_renders_the_served_status() {
+    let config = control_config();
+    serve(&config).await;
+    
+    let output = rendered(&control::query_status(&config).await.unwrap());
+    assert!(output.contains("Running:        no\n"), "{}", output);
+    assert!(output.contains("Equity:         n/a (no account update yet)\n"), "{}", output);
+    assert!(output.contains("Trades:         0 (0 ok, 0 failed)\n"), "{}", output);
+    assert!(output.contains("WebSocket:      disconnected\n"), "{}", output);
+    assert!(output.contains("Strategies:\n  dca_btc              dca        BTC        enabled  "), "{}", output);
+    assert!(output.contains("Positions:\n  none\n"), "{}", output);
+    assert!(output.contains("Open orders:\n  none\n"), "{}", output);
+    assert!(!output.contains("Account:"), "{}", output);
+    assert!(!output.contains("Live balance:"), "{}", output);
+}
+
+#[tokio::test]
+async fn status_renders_paper_equity_positions_and_orders() {
+    let config = control_config();
+    serve(&config).await;
+    let mut snapshot = control::query_status(&config).await.unwrap();
+    snapshot.account = Some("main".to_string());
+    snapshot.paper = true;
+    snapshot.equity = Some(Decimal::new(1_050_025, 2));
+    snapshot.updated_at = Some(fixtures::start());
+    snapshot.live_balance = Some(Decimal::from(2_500));
+    snapshot.positions = vec![fixtures::position("BTC", PositionSide::Long, Decimal::new(25, 2), Decimal::from(40_000))];
+    snapshot.open_orders = vec![fixtures::order("17", "ETH", OrderSide::Sell, Decimal::ONE, Decimal::from(2_500))];
+    snapshot.websocket.parse_errors.insert("l2Book".to_string(), 3);
+    
+    let output = rendered(&snapshot);
+    assert!(output.starts_with("Account:        main\n"), "{}", output);
+    assert!(output.contains("Paper equity:   $10500.25 (as of 2024-01-01 00:00:00 UTC)\n"), "{}", output);
+    assert!(output.contains("Live balance:   $2500.00 (not traded by this dry run)\n"), "{}", output);
+    assert!(output.contains("Parse errors:   l2Book 3\n"), "{}", output);
+    assert!(output.contains("Positions:\n  BTC        Long 0.25 @ 40000 (uPnL $0.00)\n"), "{}", output);
+    assert!(output.contains("Open orders:\n  ETH        Sell 1 @ 2500 (Limit, 17)\n"), "{}", output);
+}
+
+#[tokio::test]
+async fn snapshots_are_served_too() {
+    let config = control_config();
+    serve(&config).await;
+    
+    let snapshot = control::query_snapshot(&config).await.unwrap();
+    assert_eq!(snapshot.strategies.get("dca_btc").map(String::as_str), Some("BTC"));
+}
+
+#[tokio::test]
+async fn asking_without_a_running_bot_says_how_to_reach_one() {
+    let error = control::query_status(&control_config()).await.unwrap_err();
+    assert!(matches!(&error, Error::Config(message) if message.contains("control.enabled = true")), "{}", error);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn unknown_commands_get_an_error_reply() {
+    use std::os::unix::fs::PermissionsExt;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    
+    let config = control_config();
+    // Left behind by a bot that didn't exit cleanly
+    std::fs::create_dir_all(std::path::Path::new(&config.socket_path).parent().unwrap()).unwrap();
+    std::fs::write(&config.socket_path, "").unwrap();
+    serve(&config).await;
+    
+    // Only the bot's own user can connect
+    let mode = std::fs::metadata(&config.socket_path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+    
+    let mut stream = tokio::net::UnixStream::connect(&config.socket_path).await.unwrap();
+    stream.write_all(b"cancel_all\n").await.unwrap();
+    let mut reply = String::new();
+    BufReader::new(stream).read_line(&mut reply).await.unwrap();
+    assert_eq!(serde_json::from_str::<serde_json::Value>(&reply).unwrap(), json!({ "error": "unknown command: cancel_all" }));
+}
@@ -0,0 +1,226 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use hyperliquid_trading_bot::{
+    backtest::{BacktestResult, Backtester},
+    config::{BacktestConfig, CostConfig, SlippageConfig},
+    costs::{CostModel, Liquidity, MarketConditions},
+    error::Result,
+    models::{MarketData, OrderSide, SignalAction, StrategySignal},
+    strategies::Strategy,
+};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+fn dec(value: &str) -> Decimal {
+    Decimal::from_str(value).unwrap()
+}
+
+fn hour(n: i64) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap() + Duration::hours(n)
+}
+
+fn model(slippage: SlippageConfig) -> CostModel {
+    CostModel::new(
+        CostConfig {
+            maker_fee_bps: dec("1.5"),
+            taker_fee_bps: dec("4.5"),
+            slippage,
+            funding_rate: Decimal::ZERO,
+            funding_rates: HashMap::new(),
+        },
+        1,
+    )
+}
+
+/// Buys then sells `quantity` with the price unchanged at 100, returning
+/// the net PnL of the round trip
+fn round_trip(costs: &CostModel, quantity: &str, liquidity: Liquidity, market: MarketConditions) -> Decimal {
+    let quantity = dec(quantity);
+    let buy = costs.fill(&OrderSide::Buy, quantity, dec("100"), liquidity, &market);
+    let sell = costs.fill(&OrderSide::Sell, quantity, dec("100"), liquidity, &market);
+    (sell.price - buy.price) * quantity - buy.fee - sell.fee
+}
+
+#[test]
+fn fixed_slippage_round_trip() {
+    let costs = model(SlippageConfig::Fixed { bps: dec("10") });
+    
+    let buy = costs.fill(&OrderSide::Buy, dec("1"), dec("100"), Liquidity::Taker, &MarketConditions::default());
+    assert_eq!(buy.price, dec("100.1"));
+    assert_eq!(buy.fee, dec("0.045045"));
+    assert_eq!(buy.slippage, dec("0.1"));
+    
+    // 20 cents of slippage plus 4.5bps on each side
+    assert_eq!(round_trip(&costs, "1", Liquidity::Taker, MarketConditions::default()), dec("-0.29"));
+    // Maker fills rest at the reference price and pay the maker fee
+    assert_eq!(round_trip(&costs, "1", Liquidity::Maker, MarketConditions::default()), dec("-0.03"));
+}
+
+#[test]
+fn volume_slippage_scales_with_order_size() {
+    let costs = model(SlippageConfig::Volume { impact_bps: dec("100"), max_bps: dec("50") });
+    let market = MarketConditions { volume: Some(dec("10")), ..Default::default() };
+    
+    // A tenth of the volume moves the price 10bps
+    assert_eq!(round_trip(&costs, "1", Liquidity::Taker, market), dec("-0.29"));
+    // All of it would be 100bps, capped at 50
+    let buy = costs.fill(&OrderSide::Buy, dec("10"), dec("100"), Liquidity::Taker, &market);
+    assert_eq!(buy.price, dec("100.5"));
+    assert_eq!(round_trip(&costs, "10", Liquidity::Taker, market), dec("-10.9"));
+    // Unknown volume assumes the worst
+    assert_eq!(round_trip(&costs, "1", Liquidity::Taker, MarketConditions::default()), dec("-1.09"));
+}
+
+#[test]
+fn spread_slippage_crosses_the_book() {
+    let costs = model(SlippageConfig::Spread { fallback_bps: dec("10") });
+    let book = MarketConditions {
+        volume: None,
+        best_bid: Some(dec("99.95")),
+        best_ask: Some(dec("100.05")),
+    };
+    
+    let buy = costs.fill(&OrderSide::Buy, dec("1"), dec("100"), Liquidity::Taker, &book);
+    let sell = costs.fill(&OrderSide::Sell, dec("1"), dec("100"), Liquidity::Taker, &book);
+    assert_eq!((buy.price, sell.price), (dec("100.05"), dec("99.95")));
+    assert_eq!(round_trip(&costs, "1", Liquidity::Taker, book), dec("-0.19"));
+    
+    // Without a book, the fallback applies
+    assert_eq!(round_trip(&costs, "1", Liquidity::Taker, MarketConditions::default()), dec("-0.29"));
+}
+
+#[test]
+fn funding_settles_on_the_interval() {
+    let costs = CostModel::new(CostConfig::default(), 8);
+    
+    assert_eq!(costs.funding_times(hour(0), hour(24)), vec![hour(8), hour(16), hour(24)]);
+    assert_eq!(costs.funding_times(hour(3), hour(8)), vec![hour(8)]);
+    assert!(costs.funding_times(hour(8), hour(15)).is_empty());
+    
+    // Longs pay a positive rate, shorts receive it
+    let rate = CostConfig::default().funding_rate;
+    assert_eq!(costs.funding_payment("BTC", dec("2"), dec("100"), hour(8)), dec("200") * rate);
+    assert_eq!(costs.funding_payment("BTC", dec("-2"), dec("100"), hour(8)), dec("-200") * rate);
+}
+
+/// Buys one unit at the `buy_at` bar and sells it at the `sell_at` bar
+struct HoldBetween {
+    buy_at: DateTime<Utc>,
+    sell_at: DateTime<Utc>,
+}
+
+#[async_trait]
+impl Strategy for HoldBetween {
+    fn name(&self) -> &str {
+        "hold"
+    }
+    
+    fn symbol(&self) -> &str {
+        "ETH"
+    }
+    
+    fn is_enabled(&self) -> bool {
+        true
+    }
+    
+    async fn analyze(&mut self, market_data: &MarketData) -> Result<Option<StrategySignal>> {
+        let action = if market_data.timestamp == self.buy_at {
+            SignalAction::Buy
+        } else if market_data.timestamp == self.sell_at {
+            SignalAction::Sell
+        } else {
+            return Ok(None);
+        };
+        
+        Ok(Some(StrategySignal {
+            strategy_name: "hold".to_string(),
+            symbol: "ETH".to_string(),
+            action,
+            quantity: Decimal::ONE,
+            price: None,
+            confidence: 1.0,
+            metadata: HashMap::new(),
+            reduce_only: false,
+        }))
+    }
+    
+    async fn update_parameters(&mut self, _parameters: HashMap<String, serde_json::Value>) -> Result<()> {
+        Ok(())
+    }
+    
+    fn get_parameters(&self) -> HashMap<String, serde_json::Value> {
+        HashMap::new()
+    }
+    
+    fn validate_parameters(&self, _parameters: &HashMap<String, serde_json::Value>) -> Result<()> {
+        Ok(())
+    }
+}
+
+fn flat_bars(hours: i64) -> Vec<MarketData> {
+    (1..=hours)
+        .map(|h| MarketData {
+            symbol: "ETH".to_string(),
+            price: dec("100"),
+            volume_24h: dec("1000"),
+            change_24h: Decimal::ZERO,
+            high_24h: dec("100"),
+            low_24h: dec("100"),
+            timestamp: hour(h),
+        })
+        .collect()
+}
+
+fn funding_only(rate: &str) -> CostModel {
+    CostModel::new(
+        CostConfig {
+            maker_fee_bps: Decimal::ZERO,
+            taker_fee_bps: Decimal::ZERO,
+            slippage: SlippageConfig::Fixed { bps: Decimal::ZERO },
+            funding_rate: dec(rate),
+            funding_rates: HashMap::new(),
+        },
+        1,
+    )
+}
+
+async fn hold_from_1_to_5(costs: CostModel) -> BacktestResult {
+    let config = BacktestConfig {
+        initial_balance: dec("1000"),
+        interval: "1h".to_string(),
+        warmup_bars: 0,
+    };
+    let strategy = HoldBetween { buy_at: hour(1), sell_at: hour(5) };
+    Backtester::new(config, vec![Box::new(strategy)])
+        .with_cost_model(costs)
+        .run(flat_bars(6))
+        .await
+        .unwrap()
+}
+
+// Bought at 01:00 and sold at 05:00: the 02:00 to 05:00 settlements each
+// charge 1bp of the $100 position
+#[tokio::test]
+async fn funding_accrues_over_a_multi_hour_hold() {
+    let result = hold_from_1_to_5(funding_only("0.0001")).await;
+    
+    assert_eq!(result.trades.len(), 2);
+    assert_eq!(result.total_funding(), dec("0.04"));
+    assert_eq!(result.strategies["hold"].funding, dec("0.04"));
+    assert_eq!(result.net_pnl(), dec("-0.04"));
+    assert_eq!(result.gross_pnl(), Decimal::ZERO);
+    
+    let equity: Vec<Decimal> = result.equity_curve.iter().map(|s| s.equity).collect();
+    assert_eq!(equity, vec![dec("1000"), dec("999.99"), dec("999.98"), dec("999.97"), dec("999.96"), dec("999.96")]);
+}
+
+#[tokio::test]
+async fn recorded_funding_rates_replace_the_configured_one() {
+    let costs = funding_only("0.0001").with_funding_history("ETH", vec![(hour(3), dec("0.0002"))]);
+    let result = hold_from_1_to_5(costs).await;
+    
+    // 02:00 predates the history and uses the configured rate
+    assert_eq!(result.total_funding(), dec("0.07"));
+    assert_eq!(result.net_pnl(), dec("-0.07"));
+}
@@ -0,0 +1,295 @@
+use async_trait::async_trait;
+use chrono::{Duration, TimeZone, Utc};
+use futures_util::SinkExt;
+use hyperliquid_trading_bot::{
+    api::{client::TradingClient, types::Candle},
+    clock::SystemClock,
+    config::{Config, DataSourceConfig},
+    costs::CostModel,
+    data_source::{DataSource, DataSourceSupervisor, SourceSwitch, StreamHealth},
+    market_stream::MarketEvent,
+    models::{AccountInfo, MarketData, Order, Position, Trade},
+    simulated_exchange::SimulatedExchange,
+    testing::fixtures,
+    trading_bot::TradingBot,
+    Result,
+};
+use rust_decimal::Decimal;
+use serde_json::json;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpListener;
+use tokio_tungstenite::tungstenite::Message;
+
+fn health(connected: bool, last_message_secs_ago: Option<i64>, now: chrono::DateTime<Utc>) -> StreamHealth {
+    StreamHealth {
+        connected,
+        last_message_at: last_message_secs_ago.map(|secs| now - Duration::seconds(secs)),
+        reconnect_failures: if connected { 0 } else { 2 },
+    }
+}
+
+#[test]
+fn supervisor_falls_back_to_rest_and_waits_out_stabilization() {
+    let mut supervisor = DataSourceSupervisor::new(&DataSourceConfig {
+        max_message_age_secs: 30,
+        stabilization_secs: 60,
+        backfill_interval: "1m".to_string(),
+        ..DataSourceConfig::default()
+    });
+    let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    let at = |secs: i64| start + Duration::seconds(secs);
+    
+    // Starts on REST until the stream has proven itself
+    assert_eq!(supervisor.active(), DataSource::Rest);
+    assert_eq!(supervisor.check(&health(true, Some(1), at(0)), at(0)), None);
+    assert_eq!(supervisor.check(&health(true, Some(1), at(60)), at(60)), Some(SourceSwitch::Recovered { gap: None }));
+    assert_eq!(supervisor.active(), DataSource::WebSocket);
+    
+    // A quiet stream is as bad as a dropped one
+    let quiet = health(true, Some(45), at(100));
+    let Some(SourceSwitch::Degraded { reason }) = supervisor.check(&quiet, at(100)) else {
+        panic!("expected a fallback to REST");
+    };
+    assert!(reason.contains("45s"), "{}", reason);
+    assert_eq!(supervisor.active(), DataSource::Rest);
+    
+    // Flapping restarts the stabilization period
+    assert_eq!(supervisor.check(&health(true, Some(1), at(110)), at(110)), None);
+    assert_eq!(supervisor.check(&health(false, None, at(140)), at(140)), None);
+    assert_eq!(supervisor.check(&health(true, Some(1), at(150)), at(150)), None);
+    assert_eq!(supervisor.check(&health(true, Some(1), at(200)), at(200)), None);
+    
+    // The gap runs from the stream's last message to the switch back
+    assert_eq!(
+        supervisor.check(&health(true, Some(1), at(210)), at(210)),
+        Some(SourceSwitch::Recovered { gap: Some((at(55), at(210))) })
+    );
+    
+    let status = supervisor.status();
+    assert_eq!(status.active, DataSource::WebSocket);
+    assert_eq!(status.switches, 3);
+    assert_eq!(status.since, Some(at(210)));
+}
+
+/// A websocket endpoint streaming BTC tickers that the test can take down
+/// and bring back
+struct TickerServer {
+    url: String,
+    up: Arc<AtomicBool>,
+}
+
+impl TickerServer {
+    async fn start(price: i64) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let up = Arc::new(AtomicBool::new(true));
+        
+        let accepting = up.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                // While down, connections are dropped before the handshake
+                if !accepting.load(Ordering::Relaxed) {
+                    continue;
+                }
+                
+                let up = accepting.clone();
+                tokio::spawn(async move {
+                    let Ok(mut ws) = tokio_tungstenite::accept_async(stream).await else {
+                        return;
+                    };
+                    while up.load(Ordering::Relaxed) {
+                        let ticker = serde_json::json!({
+                            "channel": "ticker",
+                            "data": {"coin": "BTC", "px": price.to_string(), "sz": "1", "time": Utc::now().timestamp_millis()}
+                        });
+                        if ws.send(Message::Text(ticker.to_string())).await.is_err() {
+                            return;
+                        }
+                        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+                    }
+                    // Dropped without a close frame, like a dead connection
+                });
+            }
+        });
+        
+        Self { url, up }
+    }
+    
+    fn set_up(&self, up: bool) {
+        self.up.store(up, Ordering::Relaxed);
+    }
+}
+
+/// A simulated exchange that also serves candles, noting each request
+struct CandleExchange {
+    exchange: SimulatedExchange,
+    candle_requests: Mutex<Vec<(u64, u64)>>,
+}
+
+#[async_trait]
+impl TradingClient for CandleExchange {
+    async fn get_market_data(&self, symbol: &str) -> Result<MarketData> {
+        self.exchange.get_market_data(symbol).await
+    }
+    
+    async fn get_account_info(&self) -> Result<AccountInfo> {
+        self.exchange.get_account_info().await
+    }
+    
+    async fn get_positions(&self) -> Result<Vec<Position>> {
+        self.exchange.get_positions().await
+    }
+    
+    async fn get_open_orders(&self) -> Result<Vec<Order>> {
+        self.exchange.get_open_orders().await
+    }
+    
+    async fn place_order(&self, order: &Order) -> Result<String> {
+        self.exchange.place_order(order).await
+    }
+    
+    async fn cancel_order(&self, order_id: &str) -> Result<bool> {
+        self.exchange.cancel_order(order_id).await
+    }
+    
+    async fn get_trade_history(&self, symbol: Option<&str>) -> Result<Vec<Trade>> {
+        self.exchange.get_trade_history(symbol).await
+    }
+    
+    async fn get_candles(&self, _symbol: &str, _interval: &str, start: u64, end: u64) -> Result<Vec<Candle>> {
+        self.candle_requests.lock().unwrap().push((start, end));
+        Ok(Vec::new())
+    }
+}
+
+fn rest_ticker(price: i64) -> MarketEvent {
+    let price = Decimal::from(price);
+    MarketEvent::Ticker(MarketData {
+        symbol: "BTC".to_string(),
+        price,
+        volume_24h: Decimal::from(1000),
+        change_24h: Decimal::ZERO,
+        high_24h: price,
+        low_24h: price,
+        timestamp: Utc::now(),
+    })
+}
+
+/// DCA into BTC for real, streaming from `ws_url` and switching to it
+/// as soon as it's up
+fn live_config(ws_url: &str) -> Config {
+    let mut config = fixtures::live_dca_config(json!({ "investment_amount": "100" }));
+    config.hyperliquid.ws_url = ws_url.to_string();
+    config.data_source.stabilization_secs = 0;
+    config.risk_management.max_position_size = Decimal::from(1_000_000);
+    config
+}
+
+#[tokio::test]
+async fn strategies_keep_running_through_a_websocket_outage() {
+    let server = TickerServer::start(40_100).await;
+    let exchange = Arc::new(CandleExchange {
+        exchange: SimulatedExchange::new(Decimal::from(1_000_000), CostModel::default()),
+        candle_requests: Mutex::new(Vec::new()),
+    });
+    exchange.exchange.update(&rest_ticker(40_000));
+    let bot = TradingBot::with_client(live_config(&server.url), exchange.clone(), Arc::new(SystemClock))
+        .await
+        .unwrap();
+    
+    // Runs cycles until the bot is on `source`, checking each one still
+    // evaluated the strategy
+    let analyses = || async { bot.cached_snapshot().await.strategies[0].analysis.runs };
+    let cycles_until = |source: DataSource| {
+        let bot = &bot;
+        async move {
+            for _ in 0..50 {
+                let runs = analyses().await;
+                bot.run_cycle().await.unwrap();
+                assert_eq!(analyses().await, runs + 1, "a cycle skipped the strategy");
+                if bot.get_status().await.data_source.active == source {
+                    return;
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            }
+            panic!("never switched to {}", source);
+        }
+    };
+    
+    // Streamed prices drive the strategy once the websocket is healthy
+    bot.connect_websocket().await.unwrap();
+    cycles_until(DataSource::WebSocket).await;
+    bot.run_cycle().await.unwrap();
+    assert_eq!(bot.prices().price("BTC"), Some(Decimal::from(40_100)));
+    
+    // Outage: the connection drops and reconnects fail, so REST takes over
+    server.set_up(false);
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    exchange.exchange.update(&rest_ticker(39_900));
+    let outage_started = Utc::now();
+    cycles_until(DataSource::Rest).await;
+    assert_eq!(bot.prices().price("BTC"), Some(Decimal::from(39_900)));
+    cycles_until(DataSource::Rest).await;
+    assert!(exchange.candle_requests.lock().unwrap().is_empty());
+    
+    // Recovery: back on the websocket, with the gap backfilled
+    server.set_up(true);
+    let recovered_before = Utc::now();
+    cycles_until(DataSource::WebSocket).await;
+    
+    let status = bot.get_status().await.data_source;
+    assert_eq!(status.switches, 3);
+    let requests = exchange.candle_requests.lock().unwrap().clone();
+    assert_eq!(requests.len(), 1);
+    let (start, end) = requests[0];
+    assert!(start <= outage_started.timestamp_millis() as u64, "backfill starts after the outage");
+    assert!(end >= recovered_before.timestamp_millis() as u64, "backfill ends before the recovery");
+}
+
+#[tokio::test]
+async fn orders_wait_while_the_stream_is_quiet() {
+    // One ticker on connecting, then another each time one's pushed
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    let (push, mut pushed) = tokio::sync::mpsc::unbounded_channel::<()>();
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+        loop {
+            let ticker = serde_json::json!({
+                "channel": "ticker",
+                "data": {"coin": "BTC", "px": "40100", "sz": "1", "time": Utc::now().timestamp_millis()}
+            });
+            ws.send(Message::Text(ticker.to_string())).await.unwrap();
+            if pushed.recv().await.is_none() {
+                return;
+            }
+        }
+    });
+    let exchange = Arc::new(CandleExchange {
+        exchange: SimulatedExchange::new(Decimal::from(1_000_000), CostModel::default()),
+        candle_requests: Mutex::new(Vec::new()),
+    });
+    exchange.exchange.update(&rest_ticker(40_000));
+    let mut config = live_config(&url);
+    config.data_source.max_order_feed_age_secs = 1;
+    let bot = TradingBot::with_client(config, exchange.clone(), Arc::new(SystemClock)).await.unwrap();
+    bot.connect_websocket().await.unwrap();
+    
+    // Still fresh enough to trade on, but not to place orders on
+    tokio::time::sleep(std::time::Duration::from_millis(1500)).await;
+    bot.run_cycle().await.unwrap();
+    let status = bot.get_status().await;
+    assert_eq!(status.data_source.active, DataSource::WebSocket);
+    assert!(status.ws_connected);
+    assert_eq!(status.ws_last_message_secs, Some(1));
+    assert!(exchange.exchange.trades().is_empty());
+    
+    // The DCA buy goes out once the stream speaks again
+    push.send(()).unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    bot.run_cycle().await.unwrap();
+    assert_eq!(exchange.exchange.trades().len(), 1);
+    assert_eq!(bot.get_status().await.ws_last_message_secs, Some(0));
+}
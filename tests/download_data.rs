@@ -0,0 +1,190 @@
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use hyperliquid_trading_bot::{
+    api::{types::Candle, HyperliquidClient},
+    backtest::{
+        data,
+        download::{self, DownloadSummary, Downloader, Gap},
+    },
+    testing::fixtures,
+};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::path::Path;
+use wiremock::matchers::{body_partial_json, method, path};
+use wiremock::{Mock, MockServer, Request, Respond, ResponseTemplate};
+
+const HOUR: u64 = 3_600_000;
+
+fn day() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+}
+
+fn hour(n: i64) -> DateTime<Utc> {
+    day() + Duration::hours(n)
+}
+
+/// Serves hourly candles for whatever window is asked for, leaving out
+/// the hours in `missing`
+struct HourlyCandles {
+    missing: HashSet<i64>,
+}
+
+impl Respond for HourlyCandles {
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        let body: Value = serde_json::from_slice(&request.body).unwrap();
+        let start = body["req"]["startTime"].as_u64().unwrap();
+        let end = body["req"]["endTime"].as_u64().unwrap();
+        let first = day().timestamp_millis() as u64;
+        
+        let candles: Vec<Value> = (start.div_ceil(HOUR) * HOUR..=end)
+            .step_by(HOUR as usize)
+            .filter(|t| !self.missing.contains(&(((t - first) / HOUR) as i64)))
+            .map(|t| {
+                let close = (100 + (t - first) / HOUR).to_string();
+                json!({ "t": t, "o": "100", "h": "150", "l": "90", "c": close, "v": "10" })
+            })
+            .collect();
+        ResponseTemplate::new(200).set_body_json(candles)
+    }
+}
+
+async fn candle_server(missing: &[i64]) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/info"))
+        .and(body_partial_json(json!({ "type": "candleSnapshot", "req": { "coin": "BTC", "interval": "1h" } })))
+        .respond_with(HourlyCandles { missing: missing.iter().copied().collect() })
+        .mount(&server)
+        .await;
+    server
+}
+
+async fn download(server: &MockServer, dir: &Path, from: i64, to: i64) -> DownloadSummary {
+    let client = HyperliquidClient::new(server.uri(), String::new(), String::new(), true);
+    let summaries = Downloader::new(&client)
+        .with_chunk_candles(4)
+        .with_pause(std::time::Duration::ZERO)
+        .download(dir, &["BTC".to_string()], "1h", hour(from), hour(to))
+        .await
+        .unwrap();
+    summaries.into_iter().next().unwrap()
+}
+
+/// `(startTime, endTime)` of each candle request, as hour offsets
+async fn requested_windows(server: &MockServer) -> Vec<(i64, i64)> {
+    let first = day().timestamp_millis() as u64;
+    server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .map(|request| {
+            let body: Value = serde_json::from_slice(&request.body).unwrap();
+            let offset = |key: &str| ((body["req"][key].as_u64().unwrap() + 1 - first) / HOUR) as i64;
+            (offset("startTime"), offset("endTime"))
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn downloads_in_chunks() {
+    let server = candle_server(&[]).await;
+    let dir = fixtures::temp_dir();
+    let summary = download(&server, &dir, 0, 10).await;
+    
+    // Four candles per request: hours 0-3, 4-7 and 8-9
+    assert_eq!(requested_windows(&server).await, vec![(0, 4), (4, 8), (8, 10)]);
+    assert_eq!(summary.requests, 3);
+    assert_eq!(summary.rows_written, 10);
+    assert_eq!(summary.rows_in_range, 10);
+    assert!(summary.gaps.is_empty());
+    
+    let path = data::candle_path(&dir, "BTC", "1h");
+    assert_eq!(summary.path, path);
+    let candles = data::read_candles_csv(&path).unwrap();
+    let times: Vec<u64> = candles.iter().map(|c| c.t).collect();
+    let expected: Vec<u64> = (0..10).map(|h| hour(h).timestamp_millis() as u64).collect();
+    assert_eq!(times, expected);
+    
+    // The backtest reads the same layout; each bar is stamped at its close
+    let bars = data::load_directory(&dir, &["BTC".to_string()], "1h", hour(0), hour(10)).unwrap();
+    assert_eq!(bars.len(), 10);
+    assert_eq!(bars[0].timestamp, hour(1));
+    assert_eq!(bars[9].timestamp, hour(10));
+    
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[tokio::test]
+async fn resume_fetches_only_missing_ranges() {
+    let dir = fixtures::temp_dir();
+    let first = candle_server(&[]).await;
+    download(&first, &dir, 3, 6).await;
+    
+    let server = candle_server(&[]).await;
+    let summary = download(&server, &dir, 0, 10).await;
+    
+    // Hours 3-5 are already on disk
+    assert_eq!(requested_windows(&server).await, vec![(0, 3), (6, 10)]);
+    assert_eq!(summary.rows_written, 7);
+    assert_eq!(summary.rows_in_range, 10);
+    
+    let candles = data::read_candles_csv(&data::candle_path(&dir, "BTC", "1h")).unwrap();
+    let times: Vec<u64> = candles.iter().map(|c| c.t).collect();
+    let expected: Vec<u64> = (0..10).map(|h| hour(h).timestamp_millis() as u64).collect();
+    assert_eq!(times, expected);
+    
+    // Nothing left to fetch
+    let idle = candle_server(&[]).await;
+    let again = download(&idle, &dir, 0, 10).await;
+    assert!(idle.received_requests().await.unwrap().is_empty());
+    assert_eq!(again.requests, 0);
+    assert_eq!(again.rows_written, 0);
+    assert_eq!(again.rows_in_range, 10);
+    
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[tokio::test]
+async fn reports_gaps() {
+    let server = candle_server(&[3, 4, 9]).await;
+    let dir = fixtures::temp_dir();
+    let summary = download(&server, &dir, 0, 10).await;
+    
+    assert_eq!(summary.rows_written, 7);
+    assert_eq!(
+        summary.gaps,
+        vec![
+            Gap { from: hour(3), to: hour(5), missing: 2 },
+            Gap { from: hour(9), to: hour(10), missing: 1 },
+        ]
+    );
+    
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[test]
+fn finds_leading_and_inner_gaps() {
+    let step = HOUR;
+    let start = day().timestamp_millis() as u64;
+    let candles: Vec<_> = [2, 3, 6]
+        .iter()
+        .map(|h| Candle {
+            t: start + h * step,
+            o: 1.into(),
+            h: 1.into(),
+            l: 1.into(),
+            c: 1.into(),
+            v: 1.into(),
+        })
+        .collect();
+    
+    let gaps = download::find_gaps(&candles, start, start + 7 * step, step);
+    assert_eq!(
+        gaps,
+        vec![
+            Gap { from: hour(0), to: hour(2), missing: 2 },
+            Gap { from: hour(4), to: hour(6), missing: 2 },
+        ]
+    );
+}
@@ -0,0 +1,101 @@
+use chrono::{DateTime, Duration, Utc};
+use hyperliquid_trading_bot::{
+    equity::{self, ExportFormat},
+    models::EquityPoint,
+    storage::EquitySample,
+    testing::fixtures,
+};
+use rust_decimal::Decimal;
+use std::str::FromStr;
+
+fn at(minutes: i64) -> DateTime<Utc> {
+    fixtures::start() + Duration::minutes(minutes)
+}
+
+fn curve(equity: &[i64]) -> Vec<EquitySample> {
+    equity
+        .iter()
+        .enumerate()
+        .map(|(i, &equity)| EquitySample {
+            timestamp: at(i as i64),
+            equity: Decimal::from(equity),
+            available_balance: Decimal::from(equity),
+            total_pnl: Decimal::from(equity - 1_000),
+        })
+        .collect()
+}
+
+fn dec(s: &str) -> Decimal {
+    Decimal::from_str(s).unwrap()
+}
+
+#[test]
+fn drawdowns_are_fractions_of_the_peak_before_them() {
+    // A 20% fall, a recovery to a new high, then a 10% fall
+    let samples = curve(&[1_000, 800, 900, 1_200, 1_080]);
+    
+    assert_eq!(equity::max_drawdown(&samples), dec("0.2"));
+    assert_eq!(equity::max_drawdown_period(&samples), Some((at(0), at(1))));
+    assert_eq!(equity::current_drawdown(&samples), dec("0.1"));
+}
+
+#[test]
+fn the_largest_drawdown_runs_from_the_first_of_equal_peaks() {
+    let samples = curve(&[1_000, 900, 1_000, 700]);
+    
+    assert_eq!(equity::max_drawdown(&samples), dec("0.3"));
+    assert_eq!(equity::max_drawdown_period(&samples), Some((at(0), at(3))));
+}
+
+#[test]
+fn curves_that_never_fall_have_no_drawdown() {
+    for samples in [curve(&[]), curve(&[1_000]), curve(&[1_000, 1_000, 1_100]), curve(&[0, 0])] {
+        assert_eq!(equity::max_drawdown(&samples), Decimal::ZERO);
+        assert_eq!(equity::max_drawdown_period(&samples), None);
+        assert_eq!(equity::current_drawdown(&samples), Decimal::ZERO);
+    }
+}
+
+#[test]
+fn history_drawdowns_are_the_latest_and_the_largest() {
+    let history: Vec<EquityPoint> = [500, 400, 600, 450, 540]
+        .iter()
+        .enumerate()
+        .map(|(i, &value)| EquityPoint {
+            time: at(i as i64),
+            account_value: Decimal::from(value),
+            pnl: Decimal::from(value - 500),
+        })
+        .collect();
+    
+    assert_eq!(equity::history_drawdowns(&history), (dec("0.1"), dec("0.25")));
+    assert_eq!(equity::history_drawdowns(&[]), (Decimal::ZERO, Decimal::ZERO));
+}
+
+#[test]
+fn a_flat_curve_has_no_sharpe_ratio() {
+    assert_eq!(equity::sharpe_ratio(&curve(&[1_000; 3])), 0.0);
+    assert_eq!(equity::sharpe_ratio(&[]), 0.0);
+}
+
+#[test]
+fn exports_write_every_sample() {
+    let samples = curve(&[1_000, 950]);
+    
+    let mut csv = Vec::new();
+    equity::export(&samples, "CSV".parse().unwrap(), &mut csv).unwrap();
+    assert_eq!(
+        String::from_utf8(csv).unwrap(),
+        "timestamp,equity,available_balance,total_pnl\n\
+         2024-01-01T00:00:00+00:00,1000,1000,0\n\
+         2024-01-01T00:01:00+00:00,950,950,-50\n"
+    );
+    
+    let mut json = Vec::new();
+    equity::export(&samples, ExportFormat::Json, &mut json).unwrap();
+    let parsed: Vec<EquitySample> = serde_json::from_slice(&json).unwrap();
+    assert_eq!(parsed.iter().map(|sample| sample.equity).collect::<Vec<_>>(), [1_000, 950].map(Decimal::from));
+    assert_eq!(parsed[1].timestamp, at(1));
+    
+    assert!("xlsx".parse::<ExportFormat>().is_err());
+}
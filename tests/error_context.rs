@@ -0,0 +1,143 @@
+use hyperliquid_trading_bot::{
+    api::{client::TradingClient, HyperliquidClient},
+    error::{ErrorContext, ResultExt},
+    testing::{self, fixtures, Call, MockTradingClient},
+    Error, Result,
+};
+use rust_decimal::Decimal;
+use serde_json::json;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Log output captured in memory
+#[derive(Clone, Default)]
+struct Logs(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Logs {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+    
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Logs {
+    /// Captures this thread's logs until the guard drops
+    fn capture(&self) -> tracing::subscriber::DefaultGuard {
+        let logs = self.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::DEBUG)
+            .with_ansi(false)
+            .with_writer(move || logs.clone())
+            .finish();
+        tracing::subscriber::set_default(subscriber)
+    }
+    
+    fn lines(&self) -> Vec<String> {
+        String::from_utf8_lossy(&self.0.lock().unwrap()).lines().map(str::to_string).collect()
+    }
+}
+
+fn exchange() -> Arc<MockTradingClient> {
+    let exchange = Arc::new(MockTradingClient::new(Decimal::from(10_000)));
+    exchange.set_price("BTC", Decimal::from(40_000));
+    exchange
+}
+
+#[test]
+fn display_reads_from_the_failing_call_outwards() {
+    let cycle = Uuid::new_v4();
+    let error: Result<()> = Err(Error::Http { status: 500, body: "boom".to_string() });
+    let error = error
+        .context_api("info:meta")
+        .with_context(|| ErrorContext::new().with_strategy("dca_btc").with_symbol("BTC"))
+        .with_context(|| ErrorContext::new().with_correlation_id(cycle))
+        .unwrap_err();
+    
+    assert_eq!(
+        error.to_string(),
+        format!(
+            "API error: HTTP 500: boom [endpoint=info:meta] [symbol=BTC strategy=dca_btc] [cycle_id={}]",
+            cycle
+        )
+    );
+    assert!(matches!(error.root(), Error::Http { status: 500, .. }));
+    assert!(error.is_retryable());
+    assert_eq!(
+        error.context(),
+        ErrorContext::new()
+            .with_endpoint("info:meta")
+            .with_strategy("dca_btc")
+            .with_symbol("BTC")
+            .with_correlation_id(cycle)
+    );
+}
+
+#[tokio::test]
+async fn client_errors_name_the_request() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/info"))
+        .respond_with(ResponseTemplate::new(503).set_body_string("maintenance"))
+        .mount(&server)
+        .await;
+    let client = HyperliquidClient::new(server.uri(), "key".to_string(), "secret".to_string(), true)
+        .with_account_address(Some("0x8ba1f109551bd432803012645ac136ddd64dba72".to_string()));
+    
+    let error = client.get_account_info().await.unwrap_err();
+    assert_eq!(error.to_string(), "API error: HTTP 503: maintenance [endpoint=info:clearinghouseState]");
+    assert!(matches!(error.root(), Error::Http { status: 503, .. }));
+}
+
+#[tokio::test(start_paused = true)]
+async fn failed_cycles_carry_their_correlation_id() {
+    let logs = Logs::default();
+    let _capture = logs.capture();
+    let exchange = exchange();
+    let config = fixtures::live_dca_config(json!({ "investment_amount": "100" }));
+    let bot = testing::mock_bot(config, exchange.clone()).await.unwrap();
+    
+    exchange.fail_next(Call::AccountInfo, Error::Api("exchange unavailable".to_string()));
+    let error = bot.run_cycle().await.unwrap_err();
+    
+    let cycle = error.context().correlation_id.expect("errors carry the cycle id");
+    assert!(error.to_string().ends_with(&format!("[cycle_id={}]", cycle)), "{}", error);
+    let tagged = format!("cycle{{cycle_id={}}}", cycle);
+    assert!(logs.lines().iter().any(|line| line.contains(&tagged) && line.contains("Starting trading cycle")));
+}
+
+#[tokio::test(start_paused = true)]
+async fn order_log_lines_and_errors_share_the_cycle_id() {
+    let logs = Logs::default();
+    let _capture = logs.capture();
+    let exchange = exchange();
+    let config = fixtures::live_dca_config(json!({ "investment_amount": "100" }));
+    let bot = testing::mock_bot(config, exchange.clone()).await.unwrap();
+    
+    exchange.fail_next(Call::PlaceOrder, Error::Api("order rejected".to_string()));
+    bot.run_cycle().await.unwrap();
+    
+    let lines = logs.lines();
+    let failure = lines
+        .iter()
+        .find(|line| line.contains("Failed to execute signal"))
+        .expect("the failure is logged");
+    assert!(failure.contains("API error: order rejected [symbol=BTC strategy=dca_btc cycle_id="), "{}", failure);
+    
+    // The cycle id in the error is the one on every log line of that cycle,
+    // down to the order's
+    let cycle = failure.split("cycle_id=").last().unwrap().trim_end_matches(']');
+    let tagged = format!("cycle{{cycle_id={}}}", cycle);
+    let order_line = lines
+        .iter()
+        .find(|line| line.contains("❌ BUY"))
+        .expect("the order is logged");
+    assert!(order_line.contains(&tagged), "{}", order_line);
+    assert!(order_line.contains("strategy{strategy=dca_btc symbol=BTC}:order{order_id="), "{}", order_line);
+    assert!(failure.contains(&tagged), "{}", failure);
+}
@@ -0,0 +1,139 @@
+use hyperliquid_trading_bot::Error;
+use std::time::Duration;
+use tokio_tungstenite::tungstenite::{self, error::ProtocolError};
+use wiremock::matchers::method;
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+async fn server(response: ResponseTemplate) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET")).respond_with(response).mount(&server).await;
+    server
+}
+
+async fn response(template: ResponseTemplate) -> reqwest::Response {
+    let server = server(template).await;
+    reqwest::get(server.uri()).await.unwrap()
+}
+
+fn http(status: u16) -> Error {
+    Error::Http { status, body: String::new() }
+}
+
+#[test]
+fn classifies_local_and_exchange_errors() {
+    let retryable = [
+        http(500),
+        http(502),
+        http(503),
+        http(429),
+        Error::RateLimit { retry_after: None },
+        Error::Timeout("get_account_info after 5000ms".to_string()),
+        Error::from(tungstenite::Error::ConnectionClosed),
+        Error::from(tungstenite::Error::AlreadyClosed),
+        Error::from(tungstenite::Error::Io(std::io::ErrorKind::ConnectionReset.into())),
+        Error::from(tungstenite::Error::Protocol(ProtocolError::ResetWithoutClosingHandshake)),
+    ];
+    for error in &retryable {
+        assert!(error.is_retryable(), "{:?} should be retryable", error);
+    }
+    
+    let permanent = [
+        http(400),
+        http(401),
+        http(404),
+        Error::Config("missing key".to_string()),
+        Error::Api("User or API Wallet does not exist.".to_string()),
+        Error::Trading("Failed to place order: Order must have minimum value of $10.".to_string()),
+        Error::Strategy("no data".to_string()),
+        Error::Serialization(serde_json::from_str::<serde_json::Value>("{").unwrap_err()),
+        Error::Io(std::io::ErrorKind::NotFound.into()),
+        Error::from(tungstenite::Error::Utf8),
+        Error::Storage(rusqlite::Error::QueryReturnedNoRows),
+        Error::InvalidInput("DOGE is not listed".to_string()),
+        Error::InsufficientBalance,
+        Error::OrderNotFound,
+        Error::MarketClosed,
+        Error::InvalidNonce("duplicate nonce".to_string()),
+        Error::PostOnlyWouldCross,
+        Error::MinOrderSize("Order must have minimum value of $10.".to_string()),
+        Error::Unknown("?".to_string()),
+    ];
+    for error in &permanent {
+        assert!(!error.is_retryable(), "{:?} shouldn't be retryable", error);
+    }
+}
+
+#[test]
+fn exchange_messages_map_to_typed_errors() {
+    let cases = [
+        ("Post only order would have immediately matched, bbo was 97.1@97.2. asset=5", "PostOnlyWouldCross"),
+        ("Order must have minimum value of $10.", "MinOrderSize"),
+        ("Invalid nonce: duplicate nonce", "InvalidNonce"),
+        ("Insufficient margin to place order. asset=0", "InsufficientBalance"),
+        ("Insufficient spot balance asset=10000", "InsufficientBalance"),
+        ("Too many cumulative requests sent (10012 > 10000)", "RateLimit"),
+        ("Order was never placed, already canceled, or filled. asset=0", "OrderNotFound"),
+    ];
+    for (message, expected) in cases {
+        let error = Error::from_exchange_message(message).unwrap_or_else(|| panic!("{} wasn't recognized", message));
+        assert!(format!("{:?}", error).starts_with(expected), "{}: {:?}", message, error);
+    }
+    
+    assert!(Error::from_exchange_message("Order has invalid price.").is_none());
+    assert!(Error::from_exchange_message("").is_none());
+}
+
+#[test]
+fn only_rate_limits_say_when_to_retry() {
+    let wait = Duration::from_secs(3);
+    assert_eq!(Error::RateLimit { retry_after: Some(wait) }.retry_after(), Some(wait));
+    assert_eq!(Error::RateLimit { retry_after: None }.retry_after(), None);
+    assert_eq!(http(503).retry_after(), None);
+}
+
+#[tokio::test]
+async fn connection_failures_are_retryable() {
+    // Nothing listens on the discard port
+    let error: Error = reqwest::get("http://127.0.0.1:9").await.unwrap_err().into();
+    assert!(error.is_retryable(), "{:?}", error);
+}
+
+#[tokio::test]
+async fn timeouts_are_retryable() {
+    let server = server(ResponseTemplate::new(200).set_delay(Duration::from_secs(5))).await;
+    let client = reqwest::Client::builder().timeout(Duration::from_millis(50)).build().unwrap();
+    
+    let error: Error = client.get(server.uri()).send().await.unwrap_err().into();
+    assert!(error.is_retryable(), "{:?}", error);
+}
+
+#[tokio::test]
+async fn malformed_requests_and_responses_are_not() {
+    let error: Error = reqwest::get("not a url").await.unwrap_err().into();
+    assert!(!error.is_retryable(), "{:?}", error);
+    
+    let body = response(ResponseTemplate::new(200).set_body_string("<html>")).await;
+    let error: Error = body.json::<serde_json::Value>().await.unwrap_err().into();
+    assert!(!error.is_retryable(), "{:?}", error);
+}
+
+#[tokio::test]
+async fn status_errors_follow_the_status() {
+    let error: Error = response(ResponseTemplate::new(503)).await.error_for_status().unwrap_err().into();
+    assert!(error.is_retryable(), "{:?}", error);
+    
+    let error: Error = response(ResponseTemplate::new(403)).await.error_for_status().unwrap_err().into();
+    assert!(!error.is_retryable(), "{:?}", error);
+}
+
+#[tokio::test]
+async fn responses_become_rate_limits_or_http_errors() {
+    let limited = response(ResponseTemplate::new(429).insert_header("Retry-After", "7")).await;
+    let error = Error::from_response(limited).await;
+    assert!(matches!(&error, Error::RateLimit { retry_after: Some(wait) } if *wait == Duration::from_secs(7)), "{:?}", error);
+    
+    let unavailable = response(ResponseTemplate::new(502).set_body_string("bad gateway")).await;
+    let error = Error::from_response(unavailable).await;
+    assert!(matches!(&error, Error::Http { status: 502, body } if body == "bad gateway"), "{:?}", error);
+    assert!(error.is_retryable());
+}
@@ -0,0 +1,183 @@
+use chrono::Utc;
+use hyperliquid_trading_bot::{
+    clock::SystemClock,
+    costs::CostModel,
+    events::{BotEvent, BusEvent, EventBus, EventSubscriber, Subscription},
+    market_stream::MarketEvent,
+    models::MarketData,
+    simulated_exchange::SimulatedExchange,
+    testing::fixtures,
+    trading_bot::TradingBot,
+};
+use rust_decimal::Decimal;
+use std::sync::{Arc, Mutex};
+
+/// Keeps every event it handles
+#[derive(Clone, Default)]
+struct Sequence {
+    events: Arc<Mutex<Vec<BusEvent>>>,
+}
+
+impl EventSubscriber for Sequence {
+    fn name(&self) -> &str {
+        "sequence"
+    }
+    
+    fn handle(&mut self, event: &BusEvent) {
+        self.events.lock().unwrap().push(event.clone());
+    }
+}
+
+fn cancelled(id: String) -> BotEvent {
+    BotEvent::OrderCancelled {
+        order_id: id,
+        symbol: "BTC".to_string(),
+        reason: "test".to_string(),
+    }
+}
+
+fn order_id(event: &BusEvent) -> &str {
+    match &event.event {
+        BotEvent::OrderCancelled { order_id, .. } => order_id,
+        other => panic!("unexpected event {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn signal_to_fill_publishes_events_in_order() {
+    let exchange = Arc::new(SimulatedExchange::new(Decimal::from(1_000_000), CostModel::default()));
+    let price = Decimal::from(40_000);
+    exchange.update(&MarketEvent::Ticker(MarketData {
+        symbol: "BTC".to_string(),
+        price,
+        volume_24h: Decimal::from(1000),
+        change_24h: Decimal::ZERO,
+        high_24h: price,
+        low_24h: price,
+        timestamp: Utc::now(),
+    }));
+    
+    let mut config = fixtures::live_dca_config(serde_json::json!({ "investment_amount": "100" }));
+    config.risk_management.max_position_size = Decimal::from(1_000_000);
+    let bot = TradingBot::with_client(config, exchange.clone(), Arc::new(SystemClock)).await.unwrap();
+    
+    let sequence = Sequence::default();
+    bot.events().spawn(sequence.clone());
+    bot.run_cycle().await.unwrap();
+    bot.events().settled().await;
+    
+    let events = sequence.events.lock().unwrap().clone();
+    let kinds: Vec<&str> = events.iter().map(|event| event.event.kind()).collect();
+    // Placing the order doesn't fill it; the fill is seen once the order is polled
+    assert_eq!(kinds, vec!["equity_sample", "market_data", "signal_generated", "order_placed"]);
+    let seqs: Vec<u64> = events.iter().map(|event| event.seq).collect();
+    assert_eq!(seqs, vec![1, 2, 3, 4]);
+    
+    bot.run_cycle().await.unwrap();
+    bot.events().settled().await;
+    let events = sequence.events.lock().unwrap().clone();
+    let fills: Vec<&BusEvent> = events.iter().filter(|event| event.event.kind() == "order_filled").collect();
+    assert_eq!(fills.len(), 1);
+    assert!(fills[0].seq > 4);
+    
+    let BotEvent::MarketData(market_data) = &events[1].event else { unreachable!() };
+    assert_eq!(market_data.price, price);
+    let BotEvent::SignalGenerated(signal) = &events[2].event else { unreachable!() };
+    assert_eq!(signal.strategy_name, "dca_btc");
+    let BotEvent::OrderPlaced { strategy, order } = &events[3].event else { unreachable!() };
+    assert_eq!(strategy, "dca_btc");
+    assert_eq!(order.quantity, signal.quantity);
+    let BotEvent::OrderFilled(fill) = &fills[0].event else { unreachable!() };
+    assert_eq!(fill.order_id, order.id);
+    assert_eq!(fill.strategy, "dca_btc");
+    assert_eq!(exchange.trades().len(), 1);
+    let trade = &exchange.trades()[0];
+    assert_eq!((fill.quantity, fill.price, fill.fee), (trade.quantity, trade.price, trade.fee));
+    
+    // The status reporter follows the same bus
+    let stats = bot.snapshot().await.events;
+    assert_eq!(stats.last_seq, events.last().unwrap().seq);
+    assert_eq!(stats.counts.get("order_filled"), Some(&1));
+    assert_eq!(stats.missed, 0);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn every_subscriber_sees_one_order() {
+    let bus = Arc::new(EventBus::new(1024));
+    let mut first = bus.subscribe();
+    let mut second = bus.subscribe();
+    
+    let publishers: Vec<_> = (0..4)
+        .map(|task| {
+            let bus = bus.clone();
+            tokio::spawn(async move {
+                for i in 0..100 {
+                    bus.publish(cancelled(format!("{}-{}", task, i)), Utc::now());
+                    tokio::task::yield_now().await;
+                }
+            })
+        })
+        .collect();
+    for publisher in publishers {
+        publisher.await.unwrap();
+    }
+    
+    let drain = |subscription: &mut Subscription| std::iter::from_fn(|| subscription.try_recv()).collect::<Vec<_>>();
+    let first = drain(&mut first);
+    let second = drain(&mut second);
+    
+    // Numbered without gaps, in delivery order
+    let seqs: Vec<u64> = first.iter().map(|event| event.seq).collect();
+    assert_eq!(seqs, (1..=400).collect::<Vec<_>>());
+    
+    // Both subscribers agree on the interleaving
+    let first_ids: Vec<&str> = first.iter().map(|event| order_id(event)).collect();
+    let second_ids: Vec<&str> = second.iter().map(|event| order_id(event)).collect();
+    assert_eq!(first_ids, second_ids);
+    
+    // Each publisher's events keep the order it published them in
+    for task in 0..4 {
+        let prefix = format!("{}-", task);
+        let own: Vec<u32> = first_ids
+            .iter()
+            .filter_map(|id| id.strip_prefix(&prefix))
+            .map(|i| i.parse().unwrap())
+            .collect();
+        assert_eq!(own, (0..100).collect::<Vec<_>>());
+    }
+}
+
+#[tokio::test]
+async fn lagging_subscriber_skips_the_oldest_events() {
+    let bus = EventBus::new(4);
+    let mut subscription = bus.subscribe();
+    
+    // Publishing never waits on the subscriber
+    for i in 1..=10 {
+        bus.publish(cancelled(i.to_string()), Utc::now());
+    }
+    
+    let received: Vec<u64> = std::iter::from_fn(|| subscription.try_recv()).map(|event| event.seq).collect();
+    assert_eq!(received, vec![7, 8, 9, 10]);
+    assert_eq!(subscription.missed(), 6);
+    
+    // Caught up, it carries on without further loss
+    bus.publish(cancelled("11".to_string()), Utc::now());
+    assert_eq!(subscription.recv().await.unwrap().seq, 11);
+    assert_eq!(subscription.missed(), 6);
+}
+
+#[tokio::test]
+async fn settled_waits_for_spawned_subscribers() {
+    let bus = EventBus::new(16);
+    let sequence = Sequence::default();
+    bus.spawn(sequence.clone());
+    
+    for i in 0..3 {
+        bus.publish(cancelled(i.to_string()), Utc::now());
+    }
+    bus.settled().await;
+    
+    let seqs: Vec<u64> = sequence.events.lock().unwrap().iter().map(|event| event.seq).collect();
+    assert_eq!(seqs, vec![1, 2, 3]);
+}
@@ -0,0 +1,136 @@
+use hyperliquid_trading_bot::{
+    http,
+    models::{AccountInfo, OrderSide, PositionSide},
+    testing::{self, fixtures, MockTradingClient},
+    trading_bot::TradingBot,
+};
+use reqwest::StatusCode;
+use rust_decimal::Decimal;
+use serde_json::{json, Value};
+use std::net::TcpListener;
+use std::sync::Arc;
+use std::time::Duration;
+
+async fn dry_run_bot() -> Arc<TradingBot> {
+    let client = Arc::new(MockTradingClient::new(Decimal::from(10_000)));
+    client.set_price("BTC", Decimal::from(40_000));
+    client.set_account(AccountInfo {
+        positions: vec![fixtures::position("BTC", PositionSide::Long, Decimal::new(5, 1), Decimal::from(39_000))],
+        ..fixtures::account(Decimal::from(10_000))
+    });
+    client.set_open_orders(vec![fixtures::order(
+        "resting-1",
+        "BTC",
+        OrderSide::Buy,
+        Decimal::new(1, 2),
+        Decimal::from(38_000),
+    )]);
+    
+    let mut config = fixtures::dca_config(json!({ "investment_amount": "100" }));
+    config.trading.dry_run = true;
+    let bot = testing::mock_bot(config, client).await.unwrap();
+    bot.run_cycle().await.unwrap();
+    Arc::new(bot)
+}
+
+/// Serves `bot` on an ephemeral port and returns its base URL
+fn serve(bot: Arc<TradingBot>, token: Option<&str>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(http::serve_on(listener, bot, token.map(str::to_string)));
+    url
+}
+
+async fn get(url: &str) -> (StatusCode, Value) {
+    let response = reqwest::get(url).await.unwrap();
+    (response.status(), response.json().await.unwrap())
+}
+
+#[tokio::test]
+async fn endpoints_serve_the_cached_state() {
+    let url = serve(dry_run_bot().await, None);
+    
+    let (status, health) = get(&format!("{}/healthz", url)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(health["healthy"], true);
+    assert_eq!(health["problems"], json!([]));
+    assert_eq!(health["data_source"]["active"], "rest");
+    
+    let (status, snapshot) = get(&format!("{}/status", url)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(snapshot["status"]["is_running"], false);
+    assert_eq!(snapshot["strategies"][0]["name"], "dca_btc");
+    assert_eq!(snapshot["strategies"][0]["strategy_type"], "dca");
+    assert_eq!(snapshot["strategies"][0]["enabled"], true);
+    assert_eq!(snapshot["updated_at"], "2024-01-01T00:00:00Z");
+    
+    let (status, positions) = get(&format!("{}/positions", url)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(positions["updated_at"], "2024-01-01T00:00:00Z");
+    assert_eq!(positions["positions"].as_array().unwrap().len(), 1);
+    assert_eq!(positions["positions"][0]["symbol"], "BTC");
+    assert_eq!(positions["positions"][0]["side"], "Long");
+    
+    let (status, orders) = get(&format!("{}/orders", url)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(orders["orders"].as_array().unwrap().len(), 1);
+    assert_eq!(orders["orders"][0]["id"], "resting-1");
+}
+
+#[tokio::test]
+async fn nothing_can_be_changed() {
+    let url = serve(dry_run_bot().await, None);
+    let client = reqwest::Client::new();
+    
+    for path in ["/status", "/positions", "/orders"] {
+        let response = client.post(format!("{}{}", url, path)).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED, "{}", path);
+        let response = client.delete(format!("{}{}", url, path)).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED, "{}", path);
+    }
+    let response = client.get(format!("{}/orders/resting-1", url)).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn healthz_fails_while_the_heartbeat_is_stale() {
+    let bot = dry_run_bot().await;
+    let url = serve(bot.clone(), None);
+    let (status, _) = get(&format!("{}/healthz", url)).await;
+    assert_eq!(status, StatusCode::OK);
+    
+    // Past the watchdog's default threshold of six 5s cycles
+    tokio::time::pause();
+    tokio::time::advance(Duration::from_secs(31)).await;
+    tokio::time::resume();
+    
+    let (status, health) = get(&format!("{}/healthz", url)).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(health["healthy"], false);
+    assert!(health["heartbeat_age_secs"].as_u64().unwrap() >= 31, "{}", health);
+    assert_eq!(health["stage"], "idle");
+    assert!(health["problems"][0].as_str().unwrap().starts_with("no heartbeat for 31s"), "{}", health);
+    
+    // The next cycle brings it back
+    bot.run_cycle().await.unwrap();
+    let (status, health) = get(&format!("{}/healthz", url)).await;
+    assert_eq!(status, StatusCode::OK, "{}", health);
+}
+
+#[tokio::test]
+async fn a_configured_token_is_required() {
+    let url = serve(dry_run_bot().await, Some("s3cret"));
+    let client = reqwest::Client::new();
+    
+    for path in ["/healthz", "/status", "/positions", "/orders"] {
+        let response = client.get(format!("{}{}", url, path)).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED, "{}", path);
+        assert_eq!(response.headers()["www-authenticate"], "Bearer");
+        
+        let response = client.get(format!("{}{}", url, path)).bearer_auth("wrong").send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED, "{}", path);
+        
+        let response = client.get(format!("{}{}", url, path)).bearer_auth("s3cret").send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK, "{}", path);
+    }
+}
@@ -0,0 +1,159 @@
+use hyperliquid_trading_bot::{
+    api::client::TradingClient,
+    error::Error,
+    intervention::{self, Confirmation},
+    models::{AccountInfo, OrderSide, PositionSide},
+    testing::{fixtures, Call, MockTradingClient},
+};
+use rust_decimal::Decimal;
+use std::str::FromStr;
+
+fn dec(value: &str) -> Decimal {
+    Decimal::from_str(value).unwrap()
+}
+
+/// Three resting orders and positions in BTC, ETH and (empty) SOL
+fn client() -> MockTradingClient {
+    let client = MockTradingClient::new(dec("10000"));
+    for symbol in ["BTC", "ETH", "SOL"] {
+        client.set_price(symbol, dec("100"));
+    }
+    client.set_open_orders(vec![
+        fixtures::order("o-btc", "BTC", OrderSide::Buy, dec("1"), dec("100")),
+        fixtures::order("o-eth", "ETH", OrderSide::Sell, dec("1"), dec("100")),
+        fixtures::order("o-btc-2", "BTC", OrderSide::Sell, dec("1"), dec("100")),
+    ]);
+    client.set_account(AccountInfo {
+        positions: vec![
+            fixtures::position("BTC", PositionSide::Long, dec("0.5"), dec("100")),
+            fixtures::position("ETH", PositionSide::Short, dec("2"), dec("100")),
+            fixtures::position("SOL", PositionSide::Long, dec("0"), dec("100")),
+        ],
+        ..fixtures::account(dec("10000"))
+    });
+    client
+}
+
+/// Every exchange call in order, with what it was about
+fn calls(client: &MockTradingClient) -> Vec<String> {
+    let sent = client.sent_orders();
+    let mut sent = sent.iter();
+    client
+        .calls()
+        .into_iter()
+        .map(|record| match record.call {
+            Call::OpenOrders => "get_open_orders".to_string(),
+            Call::Positions => "get_positions".to_string(),
+            Call::CancelOrder => format!("cancel_order {}", record.subject.unwrap_or_default()),
+            Call::PlaceOrder => {
+                let order = sent.next().expect("a sent order for each placement");
+                format!(
+                    "place_order {} {:?} {:?} {} reduce_only={}",
+                    order.symbol, order.side, order.order_type, order.quantity, order.reduce_only
+                )
+            }
+            call => panic!("unexpected {:?} request", call),
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn cancel_all_cancels_every_open_order() {
+    let client = client();
+    let report = intervention::cancel_all(&client, None).await.unwrap();
+    
+    assert_eq!(
+        calls(&client),
+        vec!["get_open_orders", "cancel_order o-btc", "cancel_order o-eth", "cancel_order o-btc-2"]
+    );
+    assert_eq!(report.outcomes.len(), 3);
+    assert_eq!(report.failures(), 0);
+}
+
+#[tokio::test]
+async fn cancel_all_with_symbol_only_touches_that_symbol() {
+    let client = client();
+    intervention::cancel_all(&client, Some("btc")).await.unwrap();
+    
+    assert_eq!(calls(&client), vec!["get_open_orders", "cancel_order o-btc", "cancel_order o-btc-2"]);
+}
+
+#[tokio::test]
+async fn flatten_cancels_orders_then_closes_positions_reduce_only() {
+    let client = client();
+    let report = intervention::flatten(&client, None).await.unwrap();
+    
+    assert_eq!(
+        calls(&client),
+        vec![
+            "get_open_orders",
+            "cancel_order o-btc",
+            "cancel_order o-eth",
+            "cancel_order o-btc-2",
+            "get_positions",
+            "place_order BTC Sell Market 0.5 reduce_only=true",
+            "place_order ETH Buy Market 2 reduce_only=true",
+        ]
+    );
+    assert_eq!(report.outcomes.len(), 5);
+}
+
+#[tokio::test]
+async fn flatten_with_symbol_only_touches_that_symbol() {
+    let client = client();
+    intervention::flatten(&client, Some("ETH")).await.unwrap();
+    
+    assert_eq!(
+        calls(&client),
+        vec![
+            "get_open_orders",
+            "cancel_order o-eth",
+            "get_positions",
+            "place_order ETH Buy Market 2 reduce_only=true",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn cancel_all_orders_counts_what_it_cancelled() {
+    let client = client();
+    assert_eq!(client.cancel_all_orders(Some("eth")).await.unwrap(), 1);
+    
+    // A failed cancel fails the call, after trying the rest
+    client.fail_next(Call::CancelOrder, Error::Api("order already filled".to_string()));
+    let error = client.cancel_all_orders(None).await.unwrap_err();
+    assert!(matches!(&error, Error::Trading(message) if message.starts_with("1 of 2 cancels failed")), "{}", error);
+    assert_eq!(client.cancelled(), ["o-eth", "o-btc-2"]);
+}
+
+#[tokio::test]
+async fn failures_are_reported_and_the_rest_still_run() {
+    let client = client();
+    client.fail_next(Call::CancelOrder, Error::Api("order already filled".to_string()));
+    let report = intervention::flatten(&client, Some("BTC")).await.unwrap();
+    
+    assert_eq!(
+        calls(&client),
+        vec![
+            "get_open_orders",
+            "cancel_order o-btc",
+            "cancel_order o-btc-2",
+            "get_positions",
+            "place_order BTC Sell Market 0.5 reduce_only=true",
+        ]
+    );
+    assert_eq!(report.failures(), 1);
+    
+    let mut output = Vec::new();
+    report.write(&mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("❌ Cancel Buy 1 BTC @ 100 (o-btc): API error: order already filled"), "{}", output);
+}
+
+#[test]
+fn destructive_commands_need_yes_on_mainnet() {
+    assert_eq!(intervention::confirmation(false, false), Confirmation::RefuseMainnet);
+    assert_eq!(intervention::confirmation(false, true), Confirmation::Proceed);
+    assert_eq!(intervention::confirmation(true, false), Confirmation::Prompt);
+    assert_eq!(intervention::confirmation(true, true), Confirmation::Proceed);
+}
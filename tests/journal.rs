@@ -0,0 +1,97 @@
+use chrono::{Duration, NaiveDate};
+use hyperliquid_trading_bot::{
+    journal::{JournalEntry, TradeJournal},
+    models::OrderSide,
+    testing::fixtures,
+};
+use rust_decimal::Decimal;
+
+const HEADER: &str = "timestamp,strategy,symbol,side,quantity,price,fee,realized_pnl,order_id,cloid,dry_run,reconciled";
+
+fn entry(order_id: &str, hours: i64) -> JournalEntry {
+    JournalEntry {
+        timestamp: fixtures::start() + Duration::hours(hours),
+        strategy: "dca_btc".to_string(),
+        symbol: "BTC".to_string(),
+        side: OrderSide::Buy,
+        quantity: Decimal::new(25, 4),
+        price: Decimal::from(40_000),
+        fee: Decimal::new(45, 3),
+        realized_pnl: Decimal::ZERO,
+        order_id: order_id.to_string(),
+        cloid: None,
+        dry_run: false,
+        reconciled: false,
+    }
+}
+
+fn day(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 1, day).unwrap()
+}
+
+fn lines(journal: &TradeJournal, date: NaiveDate) -> Vec<String> {
+    std::fs::read_to_string(journal.path_for(date))
+        .unwrap()
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+#[test]
+fn entries_roll_over_to_a_file_per_utc_day() {
+    let journal = TradeJournal::new(fixtures::temp_dir()).unwrap();
+    
+    journal.record(&entry("1", 0)).unwrap();
+    journal.record(&entry("2", 23)).unwrap();
+    journal.record(&entry("3", 24)).unwrap();
+    
+    let first = lines(&journal, day(1));
+    assert_eq!(first[0], HEADER);
+    assert_eq!(first.len(), 3);
+    assert_eq!(first[1], entry("1", 0).to_csv_row());
+    let second = lines(&journal, day(2));
+    assert_eq!(second, vec![HEADER.to_string(), entry("3", 24).to_csv_row()]);
+    
+    // A late entry for the earlier day goes back to its file
+    journal.record(&entry("4", 12)).unwrap();
+    assert_eq!(lines(&journal, day(1)).len(), 4);
+}
+
+#[test]
+fn reopening_trims_a_partial_trailing_row() {
+    let dir = fixtures::temp_dir();
+    let journal = TradeJournal::new(&dir).unwrap();
+    journal.record(&entry("1", 0)).unwrap();
+    
+    // A crash mid-write leaves half a row behind
+    let path = journal.path_for(day(1));
+    let written = std::fs::read_to_string(&path).unwrap();
+    std::fs::write(&path, format!("{}2024-01-01T01:00:00+00:00,dca_b", written)).unwrap();
+    
+    let reopened = TradeJournal::new(&dir).unwrap();
+    reopened.record(&entry("2", 2)).unwrap();
+    assert_eq!(
+        lines(&reopened, day(1)),
+        vec![HEADER.to_string(), entry("1", 0).to_csv_row(), entry("2", 2).to_csv_row()]
+    );
+}
+
+#[test]
+fn a_file_holding_only_a_partial_header_is_started_again() {
+    let dir = fixtures::temp_dir();
+    let journal = TradeJournal::new(&dir).unwrap();
+    std::fs::write(journal.path_for(day(1)), "timestamp,strat").unwrap();
+    
+    journal.record(&entry("1", 0)).unwrap();
+    assert_eq!(lines(&journal, day(1)), vec![HEADER.to_string(), entry("1", 0).to_csv_row()]);
+}
+
+#[test]
+fn fields_with_separators_are_quoted() {
+    let row = JournalEntry {
+        strategy: "grid, \"wide\"".to_string(),
+        ..entry("1", 0)
+    }
+    .to_csv_row();
+    assert!(row.contains(",\"grid, \"\"wide\"\"\",BTC,BUY,"), "{}", row);
+}
@@ -0,0 +1,74 @@
+use hyperliquid_trading_bot::{
+    testing::{self, fixtures, MockTradingClient},
+    utils::json_log_layer,
+};
+use rust_decimal::Decimal;
+use serde_json::{json, Value};
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use tracing_subscriber::layer::SubscriberExt;
+
+/// Log output captured in memory
+#[derive(Clone, Default)]
+struct Logs(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Logs {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+    
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Logs {
+    /// Captures this thread's logs as JSON until the guard drops
+    fn capture(&self) -> tracing::subscriber::DefaultGuard {
+        let logs = self.clone();
+        let subscriber = tracing_subscriber::registry().with(json_log_layer(move || logs.clone()));
+        tracing::subscriber::set_default(subscriber)
+    }
+    
+    fn lines(&self) -> Vec<Value> {
+        String::from_utf8_lossy(&self.0.lock().unwrap())
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap_or_else(|e| panic!("{}: {}", e, line)))
+            .collect()
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn every_line_is_a_json_object_carrying_its_spans() {
+    let logs = Logs::default();
+    let _capture = logs.capture();
+    let exchange = Arc::new(MockTradingClient::new(Decimal::from(10_000)));
+    exchange.set_price("BTC", Decimal::from(40_000));
+    let config = fixtures::live_dca_config(json!({ "investment_amount": "100" }));
+    let bot = testing::mock_bot(config, exchange.clone()).await.unwrap();
+    
+    bot.run_cycle().await.unwrap();
+    
+    let lines = logs.lines();
+    assert!(lines.iter().all(|line| line["timestamp"].is_string() && line["level"].is_string()));
+    let placed = lines
+        .iter()
+        .find(|line| line["fields"]["event"] == "trade_execution")
+        .expect("the order is logged");
+    
+    // Structured fields sit alongside the rendered message
+    let fields = &placed["fields"];
+    assert_eq!(fields["symbol"], "BTC");
+    assert_eq!(fields["side"], "BUY");
+    assert_eq!(fields["success"], true);
+    assert!(fields["message"].as_str().unwrap().contains("BUY"), "{}", placed);
+    
+    // The cycle, strategy and order it happened in, outermost first
+    let spans = placed["spans"].as_array().unwrap();
+    let names: Vec<&str> = spans.iter().map(|span| span["name"].as_str().unwrap()).collect();
+    assert_eq!(names, ["cycle", "strategy", "order"]);
+    assert!(spans[0]["cycle_id"].is_string());
+    assert_eq!((&spans[1]["strategy"], &spans[1]["symbol"]), (&json!("dca_btc"), &json!("BTC")));
+    assert_eq!(placed["span"]["name"], "order");
+    assert_eq!(placed["span"]["order_id"], spans[2]["order_id"]);
+}
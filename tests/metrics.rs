@@ -0,0 +1,114 @@
+use chrono::{DateTime, Duration, Utc};
+use hyperliquid_trading_bot::{
+    metrics::{decimal_to_f64, PerformanceWindow, Window},
+    storage::EquitySample,
+    testing::fixtures,
+};
+use rust_decimal::Decimal;
+use std::str::FromStr;
+
+fn day(n: i64) -> DateTime<Utc> {
+    fixtures::start() + Duration::days(n)
+}
+
+fn dec(value: &str) -> Decimal {
+    Decimal::from_str(value).unwrap()
+}
+
+fn daily(returns: &[&str]) -> PerformanceWindow {
+    let mut window = PerformanceWindow::daily();
+    for (i, r) in returns.iter().enumerate() {
+        window.push(day(i as i64), dec(r));
+    }
+    window
+}
+
+fn close(actual: f64, expected: f64) -> bool {
+    (actual - expected).abs() < 1e-9 * expected.abs().max(1.0)
+}
+
+#[test]
+fn ratios_are_annualized_from_the_mean_and_deviation_of_returns() {
+    let stats = daily(&["0.01", "0.03"]).stats(Window::All, day(1));
+    
+    // Mean 2%, sample deviation sqrt(2) %
+    let deviation = 0.02f64.sqrt() / 10.0;
+    assert_eq!(stats.periods, 2);
+    assert!(close(stats.volatility, deviation * 365f64.sqrt()), "{:?}", stats);
+    assert!(close(stats.sharpe_ratio, 0.02 / deviation * 365f64.sqrt()), "{:?}", stats);
+    assert!(close(stats.annualized_return, (1.01f64 * 1.03).powf(365.0 / 2.0) - 1.0), "{:?}", stats);
+    assert_eq!((stats.sortino_ratio, stats.max_drawdown, stats.hit_rate), (0.0, 0.0, 1.0));
+}
+
+#[test]
+fn losses_set_the_drawdown_sortino_and_hit_rate() {
+    let stats = daily(&["0.1", "-0.1", "0.05", "-0.2"]).stats(Window::All, day(3));
+    
+    let mean = -0.0375;
+    let downside = ((0.01f64 + 0.04) / 4.0).sqrt();
+    assert!(close(stats.sortino_ratio, mean / downside * 365f64.sqrt()), "{:?}", stats);
+    assert!(stats.sharpe_ratio < 0.0);
+    assert_eq!(stats.hit_rate, 0.5);
+    // From 1.1 down to 1.1 * 0.9 * 1.05 * 0.8
+    assert!(close(stats.max_drawdown, 1.0 - 0.9 * 1.05 * 0.8), "{:?}", stats);
+}
+
+#[test]
+fn degenerate_series_have_zero_ratios_rather_than_nan() {
+    let empty = PerformanceWindow::daily().stats(Window::All, day(0));
+    assert_eq!((empty.periods, empty.sharpe_ratio, empty.annualized_return), (0, 0.0, 0.0));
+    
+    // One return has no deviation
+    let single = daily(&["0.05"]).stats(Window::All, day(0));
+    assert_eq!((single.periods, single.sharpe_ratio, single.volatility), (1, 0.0, 0.0));
+    
+    // Nor does a constant one
+    let flat = daily(&["0.01", "0.01", "0.01"]).stats(Window::All, day(2));
+    assert_eq!((flat.sharpe_ratio, flat.sortino_ratio), (0.0, 0.0));
+    
+    // Losing everything can't be annualized
+    let wiped = daily(&["0.5", "-1"]).stats(Window::All, day(1));
+    assert_eq!((wiped.annualized_return, wiped.max_drawdown), (-1.0, 1.0));
+}
+
+#[test]
+fn windows_count_back_from_now() {
+    let window = daily(&["0.01", "0.02", "0.03", "0.04", "0.05", "0.06", "0.07", "0.08", "0.09", "0.10"]);
+    
+    // The seven days up to and including now
+    assert_eq!(window.stats(Window::WEEK, day(9)).periods, 7);
+    assert_eq!(window.stats(Window::WEEK, day(7)).periods, 7);
+    assert_eq!(window.stats(Window::Days(1), day(4)).hit_rate, 1.0);
+    assert_eq!(window.stats(Window::MONTH, day(9)).periods, 10);
+    assert_eq!(window.stats(Window::WEEK, day(30)).periods, 0);
+    
+    let mut recent = PerformanceWindow::daily().with_max_age(Duration::days(3));
+    for n in 0..10 {
+        recent.push(day(n), dec("0.01"));
+    }
+    assert_eq!(recent.len(), 4);
+}
+
+#[test]
+fn equity_curves_give_a_return_per_day_from_each_days_last_sample() {
+    let sample = |hours: i64, equity: i64| EquitySample {
+        timestamp: fixtures::start() + Duration::hours(hours),
+        equity: Decimal::from(equity),
+        available_balance: Decimal::from(equity),
+        total_pnl: Decimal::ZERO,
+    };
+    let curve = [sample(0, 500), sample(23, 1_000), sample(30, 900), sample(47, 1_100), sample(50, 990)];
+    
+    let window = PerformanceWindow::from_equity_curve(&curve);
+    assert_eq!(window.len(), 2);
+    let stats = window.stats(Window::All, day(2));
+    assert_eq!(stats.hit_rate, 0.5);
+    assert!(close(stats.max_drawdown, 0.1), "{:?}", stats);
+    assert!(PerformanceWindow::from_equity_curve(&curve[..2]).is_empty());
+}
+
+#[test]
+fn decimals_convert_for_statistics() {
+    assert_eq!(decimal_to_f64(dec("0.015")), 0.015);
+    assert_eq!(decimal_to_f64(Decimal::MAX), 7.922816251426434e28);
+}
@@ -0,0 +1,213 @@
+use async_trait::async_trait;
+use futures_util::StreamExt;
+use hyperliquid_trading_bot::{
+    config::Config,
+    market_feed::MarketFeed,
+    models::{MarketData, SignalAction, StrategySignal},
+    strategies::Strategy,
+    supervisor::BotSupervisor,
+    testing::{self, Call, MockTradingClient},
+    Error, Result,
+};
+use rust_decimal::Decimal;
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+
+/// Market-buys a little of its symbol every cycle
+struct Buyer {
+    name: String,
+    symbol: String,
+}
+
+fn buyer(symbol: &str) -> Box<Buyer> {
+    Box::new(Buyer {
+        name: format!("buy_{}", symbol.to_lowercase()),
+        symbol: symbol.to_string(),
+    })
+}
+
+#[async_trait]
+impl Strategy for Buyer {
+    fn name(&self) -> &str {
+        &self.name
+    }
+    
+    fn symbol(&self) -> &str {
+        &self.symbol
+    }
+    
+    fn is_enabled(&self) -> bool {
+        true
+    }
+    
+    async fn analyze(&mut self, _market_data: &MarketData) -> Result<Option<StrategySignal>> {
+        Ok(Some(StrategySignal {
+            strategy_name: self.name.clone(),
+            symbol: self.symbol.clone(),
+            action: SignalAction::Buy,
+            quantity: Decimal::new(1, 3),
+            price: None,
+            confidence: 1.0,
+            metadata: HashMap::new(),
+            reduce_only: false,
+        }))
+    }
+    
+    async fn update_parameters(&mut self, _parameters: HashMap<String, Value>) -> Result<()> {
+        Ok(())
+    }
+    
+    fn get_parameters(&self) -> HashMap<String, Value> {
+        HashMap::new()
+    }
+    
+    fn validate_parameters(&self, _parameters: &HashMap<String, Value>) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// A websocket that never sends anything, and counts the connections and
+/// subscribe requests it gets
+async fn counting_websocket() -> (String, Arc<AtomicUsize>, mpsc::UnboundedReceiver<Value>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    let connections = Arc::new(AtomicUsize::new(0));
+    let (requests, received) = mpsc::unbounded_channel();
+    
+    let counted = connections.clone();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            counted.fetch_add(1, Ordering::SeqCst);
+            let requests = requests.clone();
+            tokio::spawn(async move {
+                let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+                while let Some(Ok(Message::Text(text))) = ws.next().await {
+                    let _ = requests.send(serde_json::from_str(&text).unwrap());
+                }
+            });
+        }
+    });
+    
+    (url, connections, received)
+}
+
+fn client() -> Arc<MockTradingClient> {
+    let client = Arc::new(MockTradingClient::new(Decimal::from(10_000)));
+    client.set_price("BTC", Decimal::from(40_000));
+    client.set_price("ETH", Decimal::from(2_500));
+    client
+}
+
+/// `alpha` buys BTC and `beta` buys BTC and ETH, each through its own
+/// client, sharing one feed from `ws_url`
+async fn supervisor(ws_url: &str, alpha: Arc<MockTradingClient>, beta: Arc<MockTradingClient>) -> BotSupervisor {
+    let mut config = Config::default();
+    config.trading.dry_run = false;
+    config.hyperliquid.ws_url = ws_url.to_string();
+    
+    let alpha = testing::mock_bot(config.clone(), alpha).await.unwrap().with_account("alpha").with_strategy(buyer("BTC"));
+    let beta = testing::mock_bot(config.clone(), beta)
+        .await
+        .unwrap()
+        .with_account("beta")
+        .with_strategy(buyer("BTC"))
+        .with_strategy(buyer("ETH"));
+    
+    BotSupervisor::new(Arc::new(MarketFeed::new(&config))).with_bot(alpha).with_bot(beta)
+}
+
+#[tokio::test]
+async fn accounts_share_one_subscription_per_symbol() {
+    let (url, connections, mut requests) = counting_websocket().await;
+    let supervisor = supervisor(&url, client(), client()).await;
+    
+    for bot in supervisor.bots() {
+        bot.connect_websocket().await.unwrap();
+    }
+    
+    let mut subscribed = Vec::new();
+    while let Ok(Some(request)) = tokio::time::timeout(Duration::from_millis(200), requests.recv()).await {
+        assert_eq!(request["method"], "subscribe");
+        subscribed.push(request["subscription"]["coin"].as_str().unwrap().to_string());
+    }
+    subscribed.sort();
+    assert_eq!(subscribed, ["BTC", "ETH"]);
+    assert_eq!(connections.load(Ordering::SeqCst), 1);
+    assert_eq!(supervisor.feed().subscriptions(), BTreeSet::from(["BTC".to_string(), "ETH".to_string()]));
+    
+    // Stopping one account leaves the feed up for the other
+    supervisor.bot("alpha").unwrap().stop().await;
+    assert!(supervisor.feed().is_connected());
+    
+    supervisor.stop().await;
+    assert!(!supervisor.feed().is_connected());
+}
+
+#[tokio::test]
+async fn accounts_keep_their_own_orders_and_stats() {
+    let (url, _, _) = counting_websocket().await;
+    let (alpha, beta) = (client(), client());
+    let supervisor = supervisor(&url, alpha.clone(), beta.clone()).await;
+    
+    for (account, result) in supervisor.run_cycle().await {
+        result.unwrap_or_else(|e| panic!("{}: {}", account, e));
+    }
+    
+    let symbols = |client: &MockTradingClient| -> Vec<String> {
+        let mut symbols: Vec<String> = client.sent_orders().into_iter().map(|order| order.symbol).collect();
+        symbols.sort();
+        symbols
+    };
+    assert_eq!(symbols(&alpha), ["BTC"]);
+    assert_eq!(symbols(&beta), ["BTC", "ETH"]);
+    
+    // Each account counts its own fills once its next cycle sees them
+    for (account, result) in supervisor.run_cycle().await {
+        result.unwrap_or_else(|e| panic!("{}: {}", account, e));
+    }
+    let snapshots = supervisor.snapshots().await;
+    let labeled: Vec<(Option<&str>, u64)> = snapshots
+        .iter()
+        .map(|snapshot| (snapshot.account.as_deref(), snapshot.status.successful_trades))
+        .collect();
+    assert_eq!(labeled, [(Some("alpha"), 1), (Some("beta"), 2)]);
+    
+    let strategies: Vec<&str> = snapshots[0].strategies.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(strategies, ["buy_btc"]);
+}
+
+#[tokio::test]
+async fn a_failing_or_halted_account_leaves_the_other_trading() {
+    let (url, _, _) = counting_websocket().await;
+    let (alpha, beta) = (client(), client());
+    let supervisor = supervisor(&url, alpha.clone(), beta.clone()).await;
+    
+    // A failed cycle in one account
+    alpha.fail_next(Call::AccountInfo, Error::Api("exchange unavailable".to_string()));
+    let results = supervisor.run_cycle().await;
+    assert_eq!(results[0].0, "alpha");
+    assert!(results[0].1.is_err());
+    assert_eq!(results[1].0, "beta");
+    assert!(results[1].1.is_ok());
+    assert!(alpha.sent_orders().is_empty());
+    assert_eq!(beta.sent_orders().len(), 2);
+    
+    // The kill switch in one account
+    supervisor.bot("alpha").unwrap().engage_kill_switch("test").await;
+    for _ in 0..2 {
+        alpha.clock().advance(chrono::Duration::seconds(5));
+        beta.clock().advance(chrono::Duration::seconds(5));
+        supervisor.run_cycle().await;
+    }
+    
+    assert!(supervisor.bot("alpha").unwrap().kill_switch_engaged());
+    assert!(!supervisor.bot("beta").unwrap().kill_switch_engaged());
+    assert!(alpha.sent_orders().is_empty());
+    assert_eq!(beta.sent_orders().len(), 6);
+}
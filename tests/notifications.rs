@@ -0,0 +1,371 @@
+use hyperliquid_trading_bot::{
+    config::{DiscordConfig, TelegramConfig, WebhookConfig},
+    notifications::{
+        webhook::WEBHOOK_SCHEMA_VERSION, DiscordNotifier, NotificationChannel, NotificationEvent, Severity,
+        TelegramNotifier, WebhookNotifier,
+    },
+    Error,
+};
+use serde_json::json;
+use std::collections::HashMap;
+use std::time::Duration;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn kill_switch(reason: &str) -> NotificationEvent {
+    NotificationEvent::KillSwitch {
+        engaged: true,
+        reason: reason.to_string(),
+    }
+}
+
+fn telegram(server: &MockServer) -> TelegramNotifier {
+    TelegramNotifier::new(TelegramConfig {
+        enabled: true,
+        bot_token: "123:abc".to_string(),
+        chat_id: "-100200".to_string(),
+        api_url: server.uri(),
+        min_severity: Severity::Info,
+        events: Vec::new(),
+        max_retries: 1,
+    })
+}
+
+async fn telegram_server(responses: Vec<ResponseTemplate>) -> MockServer {
+    let server = MockServer::start().await;
+    for response in responses {
+        Mock::given(method("POST"))
+            .and(path("/bot123:abc/sendMessage"))
+            .respond_with(response)
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+    }
+    server
+}
+
+fn telegram_ok() -> ResponseTemplate {
+    ResponseTemplate::new(200).set_body_json(json!({ "ok": true, "result": {} }))
+}
+
+async fn bodies(server: &MockServer) -> Vec<serde_json::Value> {
+    server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .map(|request| request.body_json().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn telegram_sends_the_chat_a_plain_text_message() {
+    let server = telegram_server(vec![telegram_ok(), telegram_ok()]).await;
+    let notifier = telegram(&server);
+    
+    notifier.send_batch(&[kill_switch("manual")]).await.unwrap();
+    assert_eq!(
+        bodies(&server).await,
+        [json!({
+            "chat_id": "-100200",
+            "text": "🤖 Kill switch engaged\n🛑 Kill switch engaged: manual",
+            "disable_web_page_preview": true,
+        })]
+    );
+    
+    // Several accounts label their messages
+    let notifier = telegram(&server).with_account(Some("main"));
+    notifier.send_batch(&[kill_switch("manual"), kill_switch("drawdown")]).await.unwrap();
+    assert_eq!(
+        bodies(&server).await[1]["text"],
+        "[main] 🤖 2 events\n• 🛑 Kill switch engaged: manual\n• 🛑 Kill switch engaged: drawdown"
+    );
+}
+
+#[test]
+fn telegram_messages_are_truncated_to_its_limit() {
+    let notifier = TelegramNotifier::new(TelegramConfig {
+        enabled: true,
+        bot_token: "123:abc".to_string(),
+        chat_id: "-100200".to_string(),
+        api_url: "http://127.0.0.1:9".to_string(),
+        min_severity: Severity::Info,
+        events: Vec::new(),
+        max_retries: 0,
+    });
+    let message = notifier.format_batch(&[kill_switch(&"x".repeat(5_000))]);
+    assert_eq!(message.chars().count(), 4096);
+    assert!(message.ends_with("x…"));
+    
+    // The account label counts toward the limit
+    let message = notifier.with_account(Some("main")).format_batch(&[kill_switch(&"x".repeat(5_000))]);
+    assert_eq!(message.chars().count(), 4096);
+    assert!(message.starts_with("[main] 🤖 Kill switch engaged"));
+    assert!(message.ends_with("x…"));
+}
+
+#[tokio::test]
+async fn telegram_retries_after_the_delay_it_asks_for() {
+    let server = telegram_server(vec![
+        ResponseTemplate::new(429).set_body_json(json!({
+            "ok": false,
+            "description": "Too Many Requests: retry after 0",
+            "parameters": { "retry_after": 0 },
+        })),
+        telegram_ok(),
+    ])
+    .await;
+    
+    telegram(&server).send_batch(&[kill_switch("manual")]).await.unwrap();
+    let sent = bodies(&server).await;
+    assert_eq!(sent.len(), 2);
+    assert_eq!(sent[0], sent[1]);
+}
+
+#[tokio::test]
+async fn telegram_rejections_are_not_retried() {
+    let server = telegram_server(vec![ResponseTemplate::new(400).set_body_json(json!({
+        "ok": false,
+        "description": "Bad Request: chat not found",
+    }))])
+    .await;
+    
+    let error = telegram(&server).send_batch(&[kill_switch("manual")]).await.unwrap_err();
+    assert!(
+        matches!(&error, Error::Http { status: 400, body } if body == "Telegram: Bad Request: chat not found"),
+        "{}",
+        error
+    );
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+}
+
+fn discord(server: &MockServer) -> DiscordNotifier {
+    DiscordNotifier::new(DiscordConfig {
+        enabled: true,
+        webhook_url: format!("{}/api/webhooks/1/token", server.uri()),
+        username: Some("bot".to_string()),
+        min_severity: Severity::Info,
+        events: Vec::new(),
+        max_retries: 1,
+    })
+}
+
+async fn discord_server(responses: Vec<ResponseTemplate>) -> MockServer {
+    let server = MockServer::start().await;
+    for response in responses {
+        Mock::given(method("POST"))
+            .and(path("/api/webhooks/1/token"))
+            .respond_with(response)
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+    }
+    server
+}
+
+fn stall(stage: &str, in_flight: &str) -> NotificationEvent {
+    NotificationEvent::WatchdogStall {
+        silent_secs: 90,
+        stage: stage.to_string(),
+        in_flight: Some(in_flight.to_string()),
+    }
+}
+
+#[tokio::test]
+async fn discord_posts_an_embed_per_event() {
+    let server = discord_server(vec![ResponseTemplate::new(204)]).await;
+    let notifier = discord(&server).with_account(Some("main"));
+    
+    notifier.send_batch(&[kill_switch("manual")]).await.unwrap();
+    assert_eq!(
+        bodies(&server).await,
+        [json!({
+            "username": "bot",
+            "embeds": [{
+                "title": "Kill switch engaged",
+                "description": "🛑 Kill switch engaged: manual",
+                "color": 0xe74c3c,
+                "fields": [
+                    { "name": "engaged", "value": "true", "inline": true },
+                    { "name": "reason", "value": "manual", "inline": true },
+                ],
+                "footer": { "text": "Account: main" },
+            }],
+        })]
+    );
+}
+
+#[test]
+fn discord_embeds_are_kept_within_its_limits() {
+    let notifier = DiscordNotifier::new(DiscordConfig {
+        enabled: true,
+        webhook_url: "http://127.0.0.1:9/api/webhooks/1/token".to_string(),
+        username: None,
+        min_severity: Severity::Info,
+        events: Vec::new(),
+        max_retries: 0,
+    });
+    let chars = |value: &serde_json::Value| value.as_str().unwrap().chars().count();
+    
+    let payload = notifier.build_payload(&[kill_switch(&"x".repeat(5_000))]);
+    let embed = &payload["embeds"][0];
+    assert_eq!(chars(&embed["description"]), 4096);
+    assert!(embed["description"].as_str().unwrap().ends_with("x…"));
+    assert_eq!(chars(&embed["fields"][1]["value"]), 1024);
+    
+    // An event too big for one message loses its fields, and its
+    // description still fits an embed
+    let payload = notifier.build_payload(&[stall(&"s".repeat(5_000), &"r".repeat(5_000))]);
+    let embed = &payload["embeds"][0];
+    assert_eq!(embed["fields"], json!([]));
+    assert_eq!(chars(&embed["description"]), 4096);
+    
+    // Past ten events, the rest are only counted
+    let payload = notifier.build_payload(&vec![kill_switch("manual"); 12]);
+    assert_eq!(payload["embeds"].as_array().unwrap().len(), 10);
+    assert_eq!(payload["content"], "… 2 more event(s) omitted");
+    
+    // Two of these fill the 6000 character budget exactly, until each
+    // embed also carries the account footer
+    let full = vec![kill_switch(&"x".repeat(1_917)); 2];
+    let payload = notifier.build_payload(&full);
+    assert_eq!(payload["embeds"].as_array().unwrap().len(), 2);
+    assert!(payload.get("content").is_none());
+    
+    let payload = notifier.with_account(Some("main")).build_payload(&full);
+    assert_eq!(payload["embeds"].as_array().unwrap().len(), 1);
+    assert_eq!(payload["embeds"][0]["footer"]["text"], "Account: main");
+    assert_eq!(payload["content"], "… 1 more event(s) omitted");
+}
+
+#[tokio::test]
+async fn discord_retries_after_the_delay_it_asks_for() {
+    let server = discord_server(vec![
+        ResponseTemplate::new(429).set_body_json(json!({ "message": "You are being rate limited.", "retry_after": 0.05, "global": false })),
+        ResponseTemplate::new(204),
+    ])
+    .await;
+    
+    discord(&server).send_batch(&[kill_switch("manual")]).await.unwrap();
+    let sent = bodies(&server).await;
+    assert_eq!(sent.len(), 2);
+    assert_eq!(sent[0], sent[1]);
+}
+
+#[tokio::test]
+async fn discord_rejections_are_not_retried() {
+    let server = discord_server(vec![ResponseTemplate::new(400).set_body_json(json!({ "message": "Invalid Form Body" }))]).await;
+    
+    let error = discord(&server).send_batch(&[kill_switch("manual")]).await.unwrap_err();
+    assert!(matches!(&error, Error::Http { status: 400, .. }), "{}", error);
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+}
+
+fn webhook(server: &MockServer, queue_size: usize) -> WebhookNotifier {
+    WebhookNotifier::new(WebhookConfig {
+        enabled: true,
+        urls: vec![format!("{}/hook", server.uri())],
+        headers: HashMap::from([("X-Token".to_string(), "secret".to_string())]),
+        timeout_secs: 5,
+        queue_size,
+        min_severity: Severity::Info,
+        events: Vec::new(),
+        max_retries: 1,
+    })
+    .unwrap()
+}
+
+async fn webhook_server(responses: Vec<ResponseTemplate>) -> MockServer {
+    let server = MockServer::start().await;
+    for response in responses {
+        Mock::given(method("POST"))
+            .and(path("/hook"))
+            .and(header("X-Token", "secret"))
+            .respond_with(response)
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+    }
+    server
+}
+
+/// Waits for the endpoint workers, which deliver in the background
+async fn received(server: &MockServer, count: usize) -> Vec<serde_json::Value> {
+    for _ in 0..100 {
+        if server.received_requests().await.unwrap().len() >= count {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    bodies(server).await
+}
+
+#[tokio::test]
+async fn webhooks_post_one_versioned_payload_per_event() {
+    let server = webhook_server(vec![ResponseTemplate::new(200), ResponseTemplate::new(200)]).await;
+    let notifier = webhook(&server, 16).with_account(Some("main"));
+    
+    notifier
+        .send_batch(&[kill_switch("manual"), NotificationEvent::WebSocketDisconnected { down_secs: 30 }])
+        .await
+        .unwrap();
+    let mut sent = received(&server, 2).await;
+    for payload in &mut sent {
+        assert!(payload["sent_at"].as_str().unwrap().parse::<chrono::DateTime<chrono::Utc>>().is_ok());
+        payload.as_object_mut().unwrap().remove("sent_at");
+    }
+    assert_eq!(
+        sent,
+        [
+            json!({
+                "schema_version": WEBHOOK_SCHEMA_VERSION,
+                "source": "hyperliquid-trading-bot",
+                "severity": "critical",
+                "account": "main",
+                "event": { "type": "kill_switch", "engaged": true, "reason": "manual" },
+            }),
+            json!({
+                "schema_version": WEBHOOK_SCHEMA_VERSION,
+                "source": "hyperliquid-trading-bot",
+                "severity": "warning",
+                "account": "main",
+                "event": { "type": "web_socket_disconnected", "down_secs": 30 },
+            }),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn webhooks_retry_server_errors_but_not_rejections() {
+    let server = webhook_server(vec![
+        ResponseTemplate::new(503),
+        ResponseTemplate::new(200),
+        ResponseTemplate::new(422),
+    ])
+    .await;
+    let notifier = webhook(&server, 16);
+    
+    notifier.send_batch(&[kill_switch("first")]).await.unwrap();
+    let sent = received(&server, 2).await;
+    assert_eq!(sent[0], sent[1]);
+    
+    notifier.send_batch(&[kill_switch("second")]).await.unwrap();
+    assert_eq!(received(&server, 3).await[2]["event"]["reason"], "second");
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(server.received_requests().await.unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn a_full_webhook_queue_drops_its_oldest_events() {
+    let server = webhook_server(vec![ResponseTemplate::new(200)]).await;
+    let notifier = webhook(&server, 1);
+    
+    notifier
+        .send_batch(&[kill_switch("first"), kill_switch("second"), kill_switch("third")])
+        .await
+        .unwrap();
+    assert_eq!(notifier.dropped_count(), 2);
+    let sent = received(&server, 1).await;
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0]["event"]["reason"], "third");
+}
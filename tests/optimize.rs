@@ -0,0 +1,107 @@
+use chrono::NaiveDate;
+use hyperliquid_trading_bot::{
+    backtest::{
+        data,
+        optimize::{Objective, Optimizer, ParameterSpace},
+        Backtester,
+    },
+    models::MarketData,
+    testing::fixtures,
+};
+use rust_decimal::Decimal;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::str::FromStr;
+
+fn dec(value: &str) -> Decimal {
+    Decimal::from_str(value).unwrap()
+}
+
+fn fixture_candles() -> Vec<MarketData> {
+    let from = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap().and_utc();
+    let to = NaiveDate::from_ymd_opt(2024, 1, 7).unwrap().and_hms_opt(0, 0, 0).unwrap().and_utc();
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/candles");
+    data::load_directory(&fixtures, &["BTC".to_string()], "1d", from, to).unwrap()
+}
+
+fn two_by_two() -> ParameterSpace {
+    ParameterSpace::default()
+        .with_values("investment_amount", vec![json!("100"), json!("200")])
+        .with_steps("lookback_period", dec("3"), dec("4"), dec("1"))
+}
+
+#[tokio::test]
+async fn runs_every_combination_and_ranks_by_the_objective() {
+    let optimizer = Optimizer::new(fixtures::dca_backtest_config(dec("1000"), "1d", json!({})), "dca_btc").unwrap().with_workers(2);
+    let result = optimizer.run(&two_by_two(), fixture_candles()).await.unwrap();
+    
+    assert!(result.failed.is_empty());
+    assert_eq!(result.runs.len(), 4);
+    let mut tried: Vec<(Value, Value)> = result
+        .runs
+        .iter()
+        .map(|run| (run.parameters["investment_amount"].clone(), run.parameters["lookback_period"].clone()))
+        .collect();
+    tried.sort_by_key(|(amount, lookback)| (amount.to_string(), lookback.to_string()));
+    // Stepped values are integers, like the configured lookback_period
+    assert_eq!(
+        tried,
+        vec![
+            (json!("100"), json!(3)),
+            (json!("100"), json!(4)),
+            (json!("200"), json!(3)),
+            (json!("200"), json!(4)),
+        ]
+    );
+    
+    // Each score is that combination's own backtest, best first
+    for run in &result.runs {
+        let config = optimizer.config_for(&run.parameters);
+        let alone = Backtester::from_config(&config).await.unwrap().run(fixture_candles()).await.unwrap();
+        assert_eq!(run.net_pnl, alone.net_pnl());
+        assert_eq!(run.score, Objective::NetPnl.score(&alone));
+    }
+    assert!(result.runs.windows(2).all(|pair| pair[0].score >= pair[1].score));
+    
+    // Doubling the amount doubles every buy, and so the PnL
+    let pnl = |amount: &str, lookback: i64| {
+        result
+            .runs
+            .iter()
+            .find(|run| run.parameters["investment_amount"] == json!(amount) && run.parameters["lookback_period"] == json!(lookback))
+            .unwrap()
+            .net_pnl
+    };
+    assert_eq!(pnl("100", 3), dec("611.6996"));
+    assert_eq!(pnl("200", 3), dec("1223.3992"));
+    // A 4-bar lookback buys the same bars, so ties keep the grid's order
+    assert_eq!(pnl("200", 4), pnl("200", 3));
+    let best = &result.best().unwrap().parameters;
+    assert_eq!((&best["investment_amount"], &best["lookback_period"]), (&json!("200"), &json!(3)));
+    
+    let best = result.best_config(optimizer.strategy_config()).unwrap();
+    assert_eq!(best.parameters["investment_amount"], json!("200"));
+    assert_eq!(best.parameters["max_investment"], json!("1000"));
+}
+
+#[tokio::test]
+async fn refuses_spaces_over_the_combination_limit() {
+    let optimizer = Optimizer::new(fixtures::dca_backtest_config(dec("1000"), "1d", json!({})), "dca_btc").unwrap().with_max_combinations(3);
+    let error = optimizer.run(&two_by_two(), fixture_candles()).await.unwrap_err();
+    assert!(error.to_string().contains("4 combinations"));
+}
+
+#[test]
+fn stepped_values_follow_the_configured_type() {
+    let current = HashMap::from([("investment_amount".to_string(), json!("100"))]);
+    let space = ParameterSpace::default().with_steps("investment_amount", dec("50"), dec("100"), dec("25"));
+    
+    let values: Vec<Value> = space
+        .combinations(&current, 10)
+        .unwrap()
+        .into_iter()
+        .map(|combination: BTreeMap<String, Value>| combination["investment_amount"].clone())
+        .collect();
+    assert_eq!(values, vec![json!("50"), json!("75"), json!("100")]);
+}
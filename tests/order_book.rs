@@ -0,0 +1,121 @@
+use futures_util::SinkExt;
+use hyperliquid_trading_bot::api::{
+    frames::{FrameOutcome, FrameParser, WsEvent},
+    orderbook::Depth,
+    OrderBookTracker, WebSocketClient,
+};
+use rust_decimal::Decimal;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::time::Instant;
+use tokio_tungstenite::tungstenite::Message;
+
+fn dec(value: &str) -> Decimal {
+    value.parse().unwrap()
+}
+
+/// The captured frames, one per line
+fn frames() -> String {
+    let path = format!("{}/tests/fixtures/websocket/l2_book_sequence.jsonl", env!("CARGO_MANIFEST_DIR"));
+    std::fs::read_to_string(path).unwrap()
+}
+
+/// Book snapshots captured from the exchange, in the order they came in
+fn recorded() -> Vec<WsEvent> {
+    let mut parser = FrameParser::new(0);
+    frames()
+        .lines()
+        .map(|line| match parser.parse(line, Instant::now()) {
+            FrameOutcome::Event(event) => event,
+            other => panic!("{} didn't decode: {:?}", line, other),
+        })
+        .collect()
+}
+
+fn top(books: &OrderBookTracker, symbol: &str) -> (Option<Decimal>, Option<Decimal>) {
+    (books.best_bid(symbol), books.best_ask(symbol))
+}
+
+#[test]
+fn replayed_snapshots_keep_the_top_of_book() {
+    let books = OrderBookTracker::default();
+    let some = |value| Some(dec(value));
+    let expected = [
+        (some("2287.6"), some("2287.7")),
+        (some("2287.7"), some("2287.8")),
+        // Another coin's snapshot leaves ETH's alone
+        (some("2287.7"), some("2287.8")),
+        // One older than the book held is ignored
+        (some("2287.7"), some("2287.8")),
+        // The asks emptied out
+        (some("2287.4"), None),
+        (some("2287.4"), some("2287.5")),
+    ];
+    
+    for (event, expected) in recorded().iter().zip(expected) {
+        books.apply(event);
+        assert_eq!(top(&books, "ETH"), expected);
+    }
+    assert_eq!(top(&books, "BTC"), (some("43251.0"), some("43252.0")));
+    assert_eq!(books.mid("ETH"), some("2287.45"));
+    assert_eq!(top(&books, "SOL"), (None, None));
+}
+
+#[test]
+fn depth_counts_what_rests_near_the_mid() {
+    let books = OrderBookTracker::default();
+    for event in recorded() {
+        books.apply(&event);
+    }
+    
+    // Mid 2287.45: nothing further than a tick out on either side
+    assert_eq!(books.depth_at("ETH", dec("0.05")), Some(Depth { bids: dec("5.0"), asks: dec("0.8") }));
+    assert_eq!(books.depth_at("ETH", dec("0.15")), Some(Depth { bids: dec("6.2"), asks: dec("2.8") }));
+    assert_eq!(books.depth_at("ETH", Decimal::ZERO), Some(Depth::default()));
+    assert_eq!(books.depth_at("SOL", dec("1")), None);
+    
+    // No mid without both sides
+    let books = OrderBookTracker::default();
+    for event in recorded().iter().take(5) {
+        books.apply(event);
+    }
+    assert_eq!(books.mid("ETH"), None);
+    assert_eq!(books.depth_at("ETH", dec("1")), None);
+}
+
+#[tokio::test(start_paused = true)]
+async fn books_gone_quiet_are_invalid_until_the_next_snapshot() {
+    let books = OrderBookTracker::new(Duration::from_secs(10));
+    let recorded = recorded();
+    books.apply(&recorded[0]);
+    assert!(books.is_valid("ETH"));
+    
+    tokio::time::advance(Duration::from_secs(10)).await;
+    assert!(!books.is_valid("ETH"));
+    assert_eq!((books.best_bid("ETH"), books.mid("ETH"), books.depth_at("ETH", dec("1"))), (None, None, None));
+    
+    books.apply(&recorded[1]);
+    assert!(books.is_valid("ETH"));
+    assert_eq!(books.best_bid("ETH"), Some(dec("2287.7")));
+}
+
+#[tokio::test]
+async fn books_follow_the_websocket_until_it_closes() {
+    let books = OrderBookTracker::default();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let client = WebSocketClient::new(format!("ws://{}", listener.local_addr().unwrap()));
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+        for frame in frames().lines() {
+            ws.send(Message::Text(frame.to_string())).await.unwrap();
+        }
+        ws.close(None).await.unwrap();
+    });
+    
+    let events = client.events();
+    let _ws = client.connect().await.unwrap();
+    drop(client);
+    books.follow(events).await;
+    assert_eq!(top(&books, "ETH"), (Some(dec("2287.4")), Some(dec("2287.5"))));
+}
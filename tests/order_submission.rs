@@ -0,0 +1,843 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use hyperliquid_trading_bot::{
+    api::{client::TradingClient, HyperliquidClient, NonceManager},
+    config::{Config, MarginMode, StrategyConfig},
+    events::{BotEvent, BusEvent, EventSubscriber},
+    models::{AccountInfo, MarketData, Order, OrderSide, OrderStatus, OrderType, PositionSide, SignalAction, StrategySignal},
+    notifications::{NotificationChannel, NotificationEvent, Notifier},
+    runner::RunMode,
+    strategies::Strategy,
+    testing::{self, fixtures, Call, MockTradingClient, OrderOutcome},
+    trading_bot::TradingBot,
+    Result,
+};
+use futures_util::{SinkExt, StreamExt};
+use rust_decimal::Decimal;
+use serde_json::json;
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+use tokio_tungstenite::tungstenite::Message;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const ORDER_LATENCY: Duration = Duration::from_millis(200);
+
+/// A mock exchange quoting each of `symbols` that takes `ORDER_LATENCY` to
+/// answer each order
+fn exchange(symbols: &[&str]) -> Arc<MockTradingClient> {
+    let exchange = Arc::new(MockTradingClient::new(Decimal::from(1_000_000)));
+    for symbol in symbols {
+        exchange.set_price(symbol, Decimal::from(100));
+    }
+    exchange.set_latency(Call::PlaceOrder, ORDER_LATENCY);
+    exchange
+}
+
+/// (symbol, sent, answered) for each order
+fn orders(exchange: &MockTradingClient) -> Vec<(String, Instant, Instant)> {
+    exchange
+        .calls()
+        .into_iter()
+        .filter(|record| record.call == Call::PlaceOrder)
+        .map(|record| (record.subject.unwrap_or_default(), record.sent, record.answered))
+        .collect()
+}
+
+/// One DCA strategy per (name, symbol), each buying on its first cycle
+fn config(strategies: &[(&str, &str)], max_concurrent_orders: usize) -> Config {
+    let mut config = Config::default();
+    config.trading.dry_run = false;
+    config.trading.max_concurrent_orders = max_concurrent_orders;
+    config.risk_management.max_position_size = Decimal::from(1_000_000);
+    for (name, symbol) in strategies {
+        config.strategies.insert(
+            name.to_string(),
+            StrategyConfig {
+                enabled: true,
+                strategy_type: "dca".to_string(),
+                symbol: symbol.to_string(),
+                position_size: Decimal::from(100),
+                parameters: HashMap::from([("investment_amount".to_string(), json!("100"))]),
+                leverage: None,
+                margin_mode: MarginMode::Cross,
+                eval_interval_secs: None,
+            },
+        );
+    }
+    config
+}
+
+/// How long one cycle takes
+async fn cycle(config: Config, exchange: Arc<MockTradingClient>) -> (TradingBot, Duration) {
+    let bot = testing::mock_bot(config, exchange).await.unwrap();
+    let started = Instant::now();
+    bot.run_cycle().await.unwrap();
+    (bot, started.elapsed())
+}
+
+#[tokio::test(start_paused = true)]
+async fn orders_for_different_symbols_go_out_together() {
+    let strategies = [("dca_btc", "BTC"), ("dca_eth", "ETH"), ("dca_sol", "SOL")];
+    
+    let serial = exchange(&["BTC", "ETH", "SOL"]);
+    let (_, serial_time) = cycle(config(&strategies, 1), serial.clone()).await;
+    assert_eq!(orders(&serial).len(), 3);
+    assert!(serial_time >= ORDER_LATENCY * 3, "{:?}", serial_time);
+    
+    let concurrent = exchange(&["BTC", "ETH", "SOL"]);
+    let (_, concurrent_time) = cycle(config(&strategies, 4), concurrent.clone()).await;
+    assert_eq!(orders(&concurrent).len(), 3);
+    assert!(concurrent_time < ORDER_LATENCY * 2, "{:?}", concurrent_time);
+    assert_eq!(concurrent.fills().len(), 3);
+}
+
+#[tokio::test(start_paused = true)]
+async fn parallelism_is_capped() {
+    let strategies = [("dca_btc", "BTC"), ("dca_eth", "ETH"), ("dca_sol", "SOL"), ("dca_avax", "AVAX")];
+    let exchange = exchange(&["BTC", "ETH", "SOL", "AVAX"]);
+    let (_, elapsed) = cycle(config(&strategies, 2), exchange.clone()).await;
+    
+    assert_eq!(orders(&exchange).len(), 4);
+    assert!(elapsed >= ORDER_LATENCY * 2 && elapsed < ORDER_LATENCY * 3, "{:?}", elapsed);
+}
+
+#[tokio::test(start_paused = true)]
+async fn orders_for_one_symbol_stay_sequential() {
+    let strategies = [("dca_a", "BTC"), ("dca_b", "BTC"), ("dca_eth", "ETH")];
+    let exchange = exchange(&["BTC", "ETH"]);
+    cycle(config(&strategies, 4), exchange.clone()).await;
+    
+    let btc: Vec<(Instant, Instant)> = orders(&exchange)
+        .into_iter()
+        .filter(|(symbol, _, _)| symbol == "BTC")
+        .map(|(_, sent, answered)| (sent, answered))
+        .collect();
+    assert_eq!(btc.len(), 2);
+    assert!(btc[1].0 >= btc[0].1, "the second BTC order went out before the first was answered");
+}
+
+#[tokio::test(start_paused = true)]
+async fn failures_are_attributed_to_their_signal() {
+    let strategies = [("dca_btc", "BTC"), ("dca_eth", "ETH"), ("dca_sol", "SOL")];
+    let exchange = exchange(&["BTC", "ETH", "SOL"]);
+    exchange.script_orders("ETH", [OrderOutcome::Reject("ETH is halted".to_string())]);
+    let (bot, _) = cycle(config(&strategies, 4), exchange.clone()).await;
+    
+    let mut traded: Vec<String> = exchange.fills().into_iter().map(|trade| trade.symbol).collect();
+    traded.sort();
+    assert_eq!(traded, vec!["BTC", "SOL"]);
+    
+    let status = bot.get_status().await;
+    assert_eq!(status.failed_trades, 1);
+    
+    // The fills count once the bot sees them
+    bot.run_cycle().await.unwrap();
+    let status = bot.get_status().await;
+    assert_eq!(status.successful_trades, 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn market_data_for_every_symbol_is_fetched_at_once() {
+    let strategies = [("dca_a", "BTC"), ("dca_b", "BTC"), ("dca_eth", "ETH"), ("dca_sol", "SOL")];
+    let exchange = exchange(&["BTC", "ETH", "SOL"]);
+    exchange.set_latency(Call::MarketData, ORDER_LATENCY);
+    cycle(config(&strategies, 4), exchange.clone()).await;
+    
+    // One request per symbol, all in flight together
+    let fetches: Vec<_> = exchange.calls().into_iter().filter(|record| record.call == Call::MarketData).collect();
+    let mut symbols: Vec<String> = fetches.iter().filter_map(|record| record.subject.clone()).collect();
+    symbols.sort();
+    assert_eq!(symbols, vec!["BTC", "ETH", "SOL"]);
+    assert!(fetches.iter().all(|record| record.sent < fetches[0].answered));
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_symbol_without_market_data_sits_the_cycle_out() {
+    let strategies = [("dca_btc", "BTC"), ("dca_eth", "ETH"), ("dca_sol", "SOL")];
+    let partial = exchange(&["BTC", "SOL"]);
+    cycle(config(&strategies, 4), partial.clone()).await;
+    
+    let mut traded: Vec<String> = partial.fills().into_iter().map(|trade| trade.symbol).collect();
+    traded.sort();
+    assert_eq!(traded, vec!["BTC", "SOL"]);
+    
+    // With nothing to go on at all, the cycle fails
+    let bot = testing::mock_bot(config(&[("dca_eth", "ETH")], 4), exchange(&[])).await.unwrap();
+    assert!(bot.run_cycle().await.is_err());
+}
+
+#[tokio::test(start_paused = true)]
+async fn placed_orders_are_found_by_their_client_order_id() {
+    let strategies = [("dca_btc", "BTC"), ("dca_eth", "ETH")];
+    let exchange = exchange(&["BTC", "ETH"]);
+    let (bot, _) = cycle(config(&strategies, 2), exchange.clone()).await;
+    
+    let sent = exchange.sent_orders();
+    assert_eq!(sent.len(), 2);
+    assert_ne!(sent[0].cloid, sent[1].cloid);
+    for order in sent {
+        let cloid = order.cloid.unwrap();
+        assert!(cloid.starts_with("0x") && cloid.len() == 34, "{}", cloid);
+        let (strategy, placed) = bot.order_by_cloid(&cloid).unwrap();
+        assert_eq!(strategy, format!("dca_{}", order.symbol.to_lowercase()));
+        assert!(matches!(placed.status, OrderStatus::Open), "{:?}", placed.status);
+    }
+    assert!(bot.order_by_cloid("0x00000000000000000000000000000000").is_none());
+}
+
+#[tokio::test(start_paused = true)]
+async fn dca_buys_can_be_sliced_by_the_exchange() {
+    let exchange = exchange(&["BTC"]);
+    let mut config = config(&[("dca_btc", "BTC")], 1);
+    let parameters = &mut config.strategies.get_mut("dca_btc").unwrap().parameters;
+    parameters.insert("twap_minutes".to_string(), json!(30));
+    cycle(config, exchange.clone()).await;
+    
+    let sent = exchange.sent_orders();
+    assert_eq!(sent.len(), 1);
+    assert!(matches!(sent[0].order_type, OrderType::Twap { duration_minutes: 30 }), "{:?}", sent[0].order_type);
+    assert_eq!(sent[0].price, None);
+}
+
+/// Asks to close more BTC than any position holds, every cycle
+struct Closer;
+
+#[async_trait]
+impl Strategy for Closer {
+    fn name(&self) -> &str {
+        "closer"
+    }
+    
+    fn symbol(&self) -> &str {
+        "BTC"
+    }
+    
+    fn is_enabled(&self) -> bool {
+        true
+    }
+    
+    async fn analyze(&mut self, _market_data: &MarketData) -> Result<Option<StrategySignal>> {
+        Ok(Some(StrategySignal {
+            strategy_name: "closer".to_string(),
+            symbol: "BTC".to_string(),
+            action: SignalAction::Close,
+            quantity: Decimal::from(5),
+            price: None,
+            confidence: 1.0,
+            metadata: HashMap::new(),
+            reduce_only: false,
+        }))
+    }
+    
+    async fn update_parameters(&mut self, _parameters: HashMap<String, serde_json::Value>) -> Result<()> {
+        Ok(())
+    }
+    
+    fn get_parameters(&self) -> HashMap<String, serde_json::Value> {
+        HashMap::new()
+    }
+    
+    fn validate_parameters(&self, _parameters: &HashMap<String, serde_json::Value>) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn close_signals_only_ever_reduce_the_position() {
+    let exchange = exchange(&["BTC"]);
+    exchange.set_account(AccountInfo {
+        positions: vec![fixtures::position("BTC", PositionSide::Short, Decimal::from(2), Decimal::from(100))],
+        ..fixtures::account(Decimal::from(1_000_000))
+    });
+    let bot = testing::mock_bot(config(&[], 1), exchange.clone()).await.unwrap().with_strategy(Box::new(Closer));
+    bot.run_cycle().await.unwrap();
+    
+    // A buy of the short's size, not the five asked for
+    let sent = exchange.sent_orders();
+    assert_eq!(sent.len(), 1);
+    assert_eq!((&sent[0].side, sent[0].quantity, sent[0].reduce_only), (&OrderSide::Buy, Decimal::from(2), true));
+    
+    // Nothing to close, nothing sent
+    exchange.set_account(fixtures::account(Decimal::from(1_000_000)));
+    bot.run_cycle().await.unwrap();
+    assert_eq!(exchange.sent_orders().len(), 1);
+}
+
+/// Bids for 2 BTC at 95 on its first cycle, and keeps the fill prices it's
+/// told about
+struct Bidder {
+    bid: AtomicBool,
+    fills: Arc<Mutex<Vec<Decimal>>>,
+}
+
+#[async_trait]
+impl Strategy for Bidder {
+    fn name(&self) -> &str {
+        "bidder"
+    }
+    
+    fn symbol(&self) -> &str {
+        "BTC"
+    }
+    
+    fn is_enabled(&self) -> bool {
+        true
+    }
+    
+    async fn analyze(&mut self, _market_data: &MarketData) -> Result<Option<StrategySignal>> {
+        if self.bid.swap(true, Ordering::Relaxed) {
+            return Ok(None);
+        }
+        Ok(Some(StrategySignal {
+            strategy_name: "bidder".to_string(),
+            symbol: "BTC".to_string(),
+            action: SignalAction::Buy,
+            quantity: Decimal::from(2),
+            price: Some(Decimal::from(95)),
+            confidence: 1.0,
+            metadata: HashMap::new(),
+            reduce_only: false,
+        }))
+    }
+    
+    async fn update_parameters(&mut self, _parameters: HashMap<String, serde_json::Value>) -> Result<()> {
+        Ok(())
+    }
+    
+    fn get_parameters(&self) -> HashMap<String, serde_json::Value> {
+        HashMap::new()
+    }
+    
+    fn validate_parameters(&self, _parameters: &HashMap<String, serde_json::Value>) -> Result<()> {
+        Ok(())
+    }
+    
+    fn on_fill(&mut self, _signal: &StrategySignal, fill_price: Decimal, _timestamp: DateTime<Utc>) {
+        self.fills.lock().unwrap().push(fill_price);
+    }
+}
+
+/// The fills the bot journals, as quantity and price
+#[derive(Clone, Default)]
+struct Journaled {
+    fills: Arc<Mutex<Vec<(Decimal, Decimal)>>>,
+}
+
+impl EventSubscriber for Journaled {
+    fn name(&self) -> &str {
+        "journaled"
+    }
+    
+    fn handle(&mut self, event: &BusEvent) {
+        if let BotEvent::OrderFilled(entry) = &event.event {
+            self.fills.lock().unwrap().push((entry.quantity, entry.price));
+        }
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn resting_orders_are_polled_until_they_fill() {
+    let exchange = exchange(&["BTC"]);
+    exchange.script_orders("BTC", [OrderOutcome::Rest]);
+    let fills = Arc::new(Mutex::new(Vec::new()));
+    let bidder = Bidder {
+        bid: AtomicBool::new(false),
+        fills: fills.clone(),
+    };
+    let bot = testing::mock_bot(config(&[], 1), exchange.clone()).await.unwrap().with_strategy(Box::new(bidder));
+    let journaled = Journaled::default();
+    bot.events().spawn(journaled.clone());
+    bot.run_cycle().await.unwrap();
+    let cloid = exchange.sent_orders()[0].cloid.clone().unwrap();
+    let (_, order) = bot.order_by_cloid(&cloid).unwrap();
+    
+    // Resting isn't filling: nothing is journaled or counted as traded
+    bot.events().settled().await;
+    assert!(journaled.fills.lock().unwrap().is_empty());
+    assert_eq!(bot.get_status().await.successful_trades, 0);
+    
+    // Partly filled: still open, and the strategy hears nothing yet
+    exchange.fill_resting(&order.id, Decimal::new(5, 1), Decimal::from(95));
+    bot.run_cycle().await.unwrap();
+    let (_, order) = bot.order_by_cloid(&cloid).unwrap();
+    assert_eq!((order.status, order.filled_quantity), (OrderStatus::PartiallyFilled, Decimal::new(5, 1)));
+    assert!(fills.lock().unwrap().is_empty());
+    
+    exchange.fill_resting(&order.id, Decimal::new(15, 1), Decimal::from(94));
+    bot.run_cycle().await.unwrap();
+    let (_, order) = bot.order_by_cloid(&cloid).unwrap();
+    assert_eq!((order.status, order.filled_quantity), (OrderStatus::Filled, Decimal::from(2)));
+    assert_eq!(order.average_price, Some(Decimal::new(9425, 2)));
+    assert_eq!(*fills.lock().unwrap(), vec![Decimal::new(9425, 2)]);
+    
+    // Filled orders aren't asked about again
+    bot.run_cycle().await.unwrap();
+    assert_eq!(fills.lock().unwrap().len(), 1);
+    
+    // Each fill is journaled at what it filled at
+    bot.events().settled().await;
+    assert_eq!(
+        *journaled.fills.lock().unwrap(),
+        vec![(Decimal::new(5, 1), Decimal::from(95)), (Decimal::new(15, 1), Decimal::from(94))]
+    );
+    assert_eq!(bot.get_status().await.successful_trades, 2);
+}
+
+#[tokio::test]
+async fn only_this_runs_orders_time_out() {
+    let exchange = exchange(&["BTC"]);
+    exchange.script_orders("BTC", [OrderOutcome::Rest]);
+    let mut config = config(&[], 1);
+    config.trading.order_timeout_seconds = 30;
+    let bidder = Bidder {
+        bid: AtomicBool::new(false),
+        fills: Arc::default(),
+    };
+    let bot = testing::mock_bot(config, exchange.clone()).await.unwrap().with_strategy(Box::new(bidder));
+    bot.run_cycle().await.unwrap();
+    let placed = exchange.get_open_orders().await.unwrap();
+    assert_eq!(placed.len(), 1);
+    
+    // Resting alongside orders placed by hand and by another bot, just as old
+    let mut open_orders = placed.clone();
+    open_orders.push(fixtures::order("manual", "BTC", OrderSide::Buy, Decimal::ONE, Decimal::from(90)));
+    open_orders.push(Order {
+        cloid: Some("0x00000000000000000000000000000001".to_string()),
+        ..fixtures::order("other-bot", "BTC", OrderSide::Sell, Decimal::ONE, Decimal::from(110))
+    });
+    exchange.set_open_orders(open_orders);
+    let cancelled = || -> Vec<Option<String>> {
+        exchange
+            .calls()
+            .into_iter()
+            .filter(|record| record.call == Call::CancelOrder)
+            .map(|record| record.subject)
+            .collect()
+    };
+    
+    exchange.clock().advance(chrono::Duration::seconds(29));
+    bot.run_cycle().await.unwrap();
+    assert!(cancelled().is_empty());
+    
+    exchange.clock().advance(chrono::Duration::seconds(1));
+    bot.run_cycle().await.unwrap();
+    assert_eq!(cancelled(), [Some(placed[0].id.clone())]);
+}
+
+const USER: &str = "0x8ba1f109551bd432803012645ac136ddd64dba72";
+
+/// A websocket sending whatever's pushed into the returned sender
+async fn stream() -> (String, mpsc::UnboundedSender<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    let (frames, mut pushed) = mpsc::unbounded_channel::<String>();
+    
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+        loop {
+            tokio::select! {
+                frame = pushed.recv() => match frame {
+                    Some(frame) => ws.send(Message::Text(frame)).await.unwrap(),
+                    None => break,
+                },
+                message = ws.next() => if !matches!(message, Some(Ok(_))) {
+                    break;
+                },
+            }
+        }
+    });
+    
+    (url, frames)
+}
+
+/// Config for a bot following the websocket at `url`
+fn streaming_config(url: String) -> Config {
+    let mut config = config(&[], 1);
+    config.hyperliquid.ws_url = url;
+    config.preflight.ignore_failures = true;
+    config.preflight.websocket_timeout_secs = 1;
+    config
+}
+
+/// A bot bidding through a mock exchange that rests its orders, following
+/// `USER`'s account over the websocket `config` points it at
+async fn streaming_bot(config: Config) -> (TradingBot, Arc<MockTradingClient>) {
+    let exchange = exchange(&["BTC"]);
+    exchange.script_orders("BTC", [OrderOutcome::Rest]);
+    let bidder = Bidder {
+        bid: AtomicBool::new(false),
+        fills: Arc::default(),
+    };
+    let bot = testing::mock_bot(config, exchange.clone())
+        .await
+        .unwrap()
+        .with_strategy(Box::new(bidder))
+        .with_account_stream(USER);
+    (bot, exchange)
+}
+
+fn polls(exchange: &MockTradingClient) -> usize {
+    exchange.calls().iter().filter(|record| record.call == Call::OpenOrders).count()
+}
+
+#[tokio::test]
+async fn open_orders_are_left_to_the_stream_while_it_is_up() {
+    let (url, _frames) = stream().await;
+    let (bot, exchange) = streaming_bot(streaming_config(url)).await;
+    bot.connect_websocket().await.unwrap();
+    bot.run_cycle().await.unwrap();
+    let cloid = exchange.sent_orders()[0].cloid.clone().unwrap();
+    let (_, order) = bot.order_by_cloid(&cloid).unwrap();
+    
+    // A partial fill the stream would have told of isn't polled for
+    let polled = polls(&exchange);
+    exchange.fill_resting(&order.id, Decimal::new(5, 1), Decimal::from(95));
+    bot.run_cycle().await.unwrap();
+    assert_eq!(polls(&exchange), polled);
+    assert_eq!(bot.order_by_cloid(&cloid).unwrap().1.status, OrderStatus::Open);
+}
+
+/// Log output captured in memory
+#[derive(Clone, Default)]
+struct Logs(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Logs {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+    
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Logs {
+    /// Captures this thread's logs until the guard drops
+    fn capture(&self) -> tracing::subscriber::DefaultGuard {
+        let logs = self.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_ansi(false)
+            .with_writer(move || logs.clone())
+            .finish();
+        tracing::subscriber::set_default(subscriber)
+    }
+    
+    fn lines(&self) -> Vec<String> {
+        String::from_utf8_lossy(&self.0.lock().unwrap()).lines().map(str::to_string).collect()
+    }
+}
+
+#[tokio::test]
+async fn cancels_pushed_over_the_stream_close_the_order() {
+    let logs = Logs::default();
+    let _capture = logs.capture();
+    let (url, frames) = stream().await;
+    let (bot, exchange) = streaming_bot(streaming_config(url)).await;
+    
+    let pushed = async {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while exchange.sent_orders().is_empty() && Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let cloid = exchange.sent_orders()[0].cloid.clone().unwrap();
+        let update = |oid: u64, cloid: Option<&str>| {
+            json!({
+                "channel": "orderUpdates",
+                "data": [{
+                    "order": {
+                        "coin": "BTC", "side": "B", "limitPx": "95", "sz": "1.5", "oid": oid,
+                        "timestamp": 1704067200000u64, "origSz": "2", "cloid": cloid
+                    },
+                    "status": "canceled",
+                    "statusTimestamp": 1704067201000u64
+                }]
+            })
+            .to_string()
+        };
+        // Someone else's order, then ours, known by its client order id
+        frames.send(update(1, None)).unwrap();
+        frames.send(update(2, Some(&cloid))).unwrap();
+        
+        while bot.order_by_cloid(&cloid).unwrap().1.status == OrderStatus::Open && Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        cloid
+    };
+    let cloid = tokio::select! {
+        cloid = pushed => cloid,
+        result = bot.run(RunMode::Continuous) => panic!("the bot stopped: {:?}", result),
+    };
+    
+    // Well before the next cycle could have polled it
+    let (_, order) = bot.order_by_cloid(&cloid).unwrap();
+    assert_eq!((order.status, order.filled_quantity), (OrderStatus::Cancelled, Decimal::new(5, 1)));
+    
+    // Logged as a cancel, not as a trade that failed
+    let lines = logs.lines();
+    assert!(lines.iter().any(|line| line.contains("Cancelled BUY") && line.contains("0.500000 filled")), "{:#?}", lines);
+    assert!(!lines.iter().any(|line| line.contains('❌')), "{:#?}", lines);
+}
+
+#[tokio::test]
+async fn streamed_fills_are_journaled_with_their_realized_pnl_and_time() {
+    let dir = fixtures::temp_dir();
+    let (url, frames) = stream().await;
+    let mut config = streaming_config(url);
+    config.journal.enabled = true;
+    config.journal.directory = dir.display().to_string();
+    let (bot, exchange) = streaming_bot(config).await;
+    
+    let pushed = async {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while exchange.sent_orders().is_empty() && Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let cloid = exchange.sent_orders()[0].cloid.clone().unwrap();
+        // Closing part of a short, a minute after the bot's clock
+        let fill = json!({
+            "channel": "userFills",
+            "data": {
+                "user": USER,
+                "fills": [{
+                    "coin": "BTC", "px": "95", "sz": "0.5", "side": "B", "time": 1704067260000u64,
+                    "oid": 1, "cloid": cloid, "tid": 1, "fee": "0.02", "closedPnl": "12.5", "hash": "0x01"
+                }]
+            }
+        });
+        frames.send(fill.to_string()).unwrap();
+        
+        while bot.order_by_cloid(&cloid).unwrap().1.filled_quantity.is_zero() && Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    };
+    tokio::select! {
+        _ = pushed => {}
+        result = bot.run(RunMode::Continuous) => panic!("the bot stopped: {:?}", result),
+    }
+    bot.events().settled().await;
+    
+    let journal = std::fs::read_to_string(dir.join("trades-2024-01-01.csv")).unwrap();
+    let rows: Vec<&str> = journal.lines().skip(1).collect();
+    assert_eq!(rows.len(), 1, "{}", journal);
+    assert!(rows[0].starts_with("2024-01-01T00:01:00+00:00,"), "{}", rows[0]);
+    assert!(rows[0].contains(",BUY,0.5,95,0.02,12.5,"), "{}", rows[0]);
+}
+
+/// A websocket that drops whichever connection it has each time the
+/// returned sender is sent to, then takes the next
+async fn dropping_stream() -> (String, mpsc::UnboundedSender<()>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    let (hang_up, mut hang_ups) = mpsc::unbounded_channel::<()>();
+    
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            loop {
+                tokio::select! {
+                    _ = hang_ups.recv() => break,
+                    message = ws.next() => if !matches!(message, Some(Ok(_))) {
+                        break;
+                    },
+                }
+            }
+        }
+    });
+    
+    (url, hang_up)
+}
+
+#[tokio::test]
+async fn fills_missed_while_the_stream_was_down_are_recovered_on_reconnecting() {
+    let (url, hang_up) = dropping_stream().await;
+    let (bot, exchange) = streaming_bot(streaming_config(url)).await;
+    bot.connect_websocket().await.unwrap();
+    bot.run_cycle().await.unwrap();
+    let cloid = exchange.sent_orders()[0].cloid.clone().unwrap();
+    let (_, order) = bot.order_by_cloid(&cloid).unwrap();
+    
+    // Filled in part while the connection is down
+    hang_up.send(()).unwrap();
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while bot.get_status().await.ws_connected && Instant::now() < deadline {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    exchange.clock().advance(chrono::Duration::seconds(5));
+    exchange.fill_resting(&order.id, Decimal::new(5, 1), Decimal::from(95));
+    
+    // Reconnecting applies it once, before the cycle's poll could
+    bot.run_cycle().await.unwrap();
+    let status = bot.get_status().await;
+    assert!(status.ws_connected);
+    assert_eq!(status.ws_recovered_fills, 1);
+    let (_, order) = bot.order_by_cloid(&cloid).unwrap();
+    assert_eq!((order.status, order.filled_quantity), (OrderStatus::PartiallyFilled, Decimal::new(5, 1)));
+    
+    // Nor is it recovered again on the next reconnect
+    hang_up.send(()).unwrap();
+    while bot.get_status().await.ws_connected && Instant::now() < deadline {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    bot.run_cycle().await.unwrap();
+    assert!(bot.get_status().await.ws_connected);
+    assert_eq!(bot.get_status().await.ws_recovered_fills, 1);
+    assert_eq!(bot.order_by_cloid(&cloid).unwrap().1.filled_quantity, Decimal::new(5, 1));
+}
+
+/// Keeps every notification it's sent
+#[derive(Default)]
+struct Recorder {
+    events: Mutex<Vec<NotificationEvent>>,
+}
+
+#[async_trait]
+impl NotificationChannel for Recorder {
+    fn name(&self) -> &str {
+        "recorder"
+    }
+    
+    async fn send_batch(&self, events: &[NotificationEvent]) -> Result<()> {
+        self.events.lock().unwrap().extend_from_slice(events);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn the_exchanges_notifications_are_passed_on() {
+    let (url, frames) = stream().await;
+    let (bot, exchange) = streaming_bot(streaming_config(url)).await;
+    let recorder = Arc::new(Recorder::default());
+    let bot = bot.with_notifier(Notifier::new(vec![recorder.clone()]));
+    
+    let passed_on = async {
+        // Once the bot is trading, and so following the account
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while exchange.sent_orders().is_empty() && Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let notification = json!({
+            "channel": "notification",
+            "data": { "notification": "Your position in BTC is close to liquidation." }
+        });
+        frames.send(notification.to_string()).unwrap();
+        
+        loop {
+            let notified: Vec<NotificationEvent> = recorder.events.lock().unwrap().clone();
+            let forwarded = notified.into_iter().find_map(|event| match event {
+                NotificationEvent::ExchangeNotification { message } => Some(message),
+                _ => None,
+            });
+            if forwarded.is_some() || Instant::now() >= deadline {
+                return forwarded;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    };
+    let forwarded = tokio::select! {
+        forwarded = passed_on => forwarded,
+        result = bot.run(RunMode::Continuous) => panic!("the bot stopped: {:?}", result),
+    };
+    assert_eq!(forwarded.as_deref(), Some("Your position in BTC is close to liquidation."));
+}
+
+#[test]
+fn nonces_increase_in_signing_order() {
+    let nonces = Arc::new(NonceManager::new());
+    let signed = Arc::new(Mutex::new(Vec::new()));
+    
+    let threads: Vec<_> = (0..8)
+        .map(|_| {
+            let nonces = nonces.clone();
+            let signed = signed.clone();
+            std::thread::spawn(move || {
+                for _ in 0..100 {
+                    nonces.sign(|nonce| signed.lock().unwrap().push(nonce));
+                }
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+    
+    let signed = signed.lock().unwrap();
+    assert_eq!(signed.len(), 800);
+    assert!(signed.windows(2).all(|pair| pair[0] < pair[1]), "nonces out of signing order");
+}
+
+#[tokio::test]
+async fn client_signs_concurrent_orders_with_distinct_nonces() {
+    let server = MockServer::start().await;
+    let universe: Vec<_> = ["BTC", "ETH", "SOL", "AVAX"]
+        .into_iter()
+        .map(|name| json!({ "name": name, "szDecimals": 4, "maxLeverage": 20 }))
+        .collect();
+    Mock::given(method("POST"))
+        .and(path("/info"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "universe": universe })))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/exchange"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({
+                    "status": "ok",
+                    "response": { "type": "order", "data": { "statuses": [{ "resting": { "oid": 1 } }] } }
+                }))
+                .set_delay(ORDER_LATENCY),
+        )
+        .mount(&server)
+        .await;
+    let client = HyperliquidClient::new(
+        server.uri(),
+        "key".to_string(),
+        "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318".to_string(),
+        true,
+    );
+    
+    let order = |symbol: &str| Order {
+        id: symbol.to_string(),
+        symbol: symbol.to_string(),
+        side: OrderSide::Buy,
+        order_type: OrderType::Limit,
+        quantity: Decimal::ONE,
+        price: Some(Decimal::from(100)),
+        status: OrderStatus::Pending,
+        created_at: Utc::now(),
+        updated_at: None,
+        filled_quantity: Decimal::ZERO,
+        average_price: None,
+        reduce_only: false,
+        trigger_price: None,
+        is_take_profit: false,
+        cloid: None,
+    };
+    let orders: Vec<Order> = ["BTC", "ETH", "SOL", "AVAX"].into_iter().map(order).collect();
+    
+    let started = std::time::Instant::now();
+    let results = futures_util::future::join_all(orders.iter().map(|order| client.place_order(order))).await;
+    let elapsed = started.elapsed();
+    assert!(results.iter().all(|result| result.is_ok()), "{:?}", results);
+    assert!(elapsed < ORDER_LATENCY * 2, "requests didn't overlap: {:?}", elapsed);
+    
+    let mut nonces: Vec<u64> = server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .filter(|request| request.url.path() == "/exchange")
+        .map(|request| serde_json::from_slice::<serde_json::Value>(&request.body).unwrap()["nonce"].as_u64().unwrap())
+        .collect();
+    nonces.sort();
+    nonces.dedup();
+    assert_eq!(nonces.len(), 4, "nonces repeated");
+}
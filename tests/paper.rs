@@ -0,0 +1,147 @@
+use chrono::{TimeZone, Utc};
+use hyperliquid_trading_bot::{
+    api::client::TradingClient,
+    config::{Config, CostConfig, SlippageConfig},
+    costs::CostModel,
+    market_stream::MarketEvent,
+    models::{MarketData, Order, OrderSide, OrderStatus, OrderType},
+    paper::PaperExchange,
+    simulated_exchange::SimulatedExchange,
+    storage::{self, StorageWriter},
+    testing::fixtures,
+};
+use rust_decimal::Decimal;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+
+fn dec(value: &str) -> Decimal {
+    Decimal::from_str(value).unwrap()
+}
+
+fn paper_config(dir: &Path) -> Config {
+    let mut config = Config::default();
+    config.storage.enabled = true;
+    config.storage.path = dir.join("bot.db").to_string_lossy().into_owned();
+    config.paper.initial_balance = dec("10000");
+    config.costs = CostConfig {
+        taker_fee_bps: dec("10"),
+        slippage: SlippageConfig::Fixed { bps: dec("10") },
+        funding_rate: Decimal::ZERO,
+        ..CostConfig::default()
+    };
+    config
+}
+
+/// Stands in for the live exchange's market data
+fn live_market(price: &str) -> Arc<SimulatedExchange> {
+    let market = SimulatedExchange::new(Decimal::ZERO, CostModel::default());
+    let price = dec(price);
+    market.update(&MarketEvent::Ticker(MarketData {
+        symbol: "BTC".to_string(),
+        price,
+        volume_24h: dec("1000"),
+        change_24h: Decimal::ZERO,
+        high_24h: price,
+        low_24h: price,
+        timestamp: Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
+    }));
+    Arc::new(market)
+}
+
+fn order(side: OrderSide, order_type: OrderType, quantity: &str, price: Option<&str>) -> Order {
+    Order {
+        id: uuid::Uuid::new_v4().to_string(),
+        symbol: "BTC".to_string(),
+        side,
+        order_type,
+        quantity: dec(quantity),
+        price: price.map(dec),
+        status: OrderStatus::Pending,
+        created_at: Utc::now(),
+        updated_at: None,
+        filled_quantity: Decimal::ZERO,
+        average_price: None,
+        reduce_only: false,
+        trigger_price: None,
+        is_take_profit: false,
+        cloid: None,
+    }
+}
+
+/// Opens the paper account the way a fresh process would
+async fn start(config: &Config, market: Arc<SimulatedExchange>) -> (PaperExchange, StorageWriter) {
+    let storage = StorageWriter::spawn(storage::open(&config.storage).unwrap().unwrap());
+    let paper = PaperExchange::open(config, market, Some(storage.clone())).unwrap();
+    (paper, storage)
+}
+
+#[tokio::test]
+async fn paper_account_survives_a_restart() {
+    let dir = fixtures::temp_dir();
+    let config = paper_config(&dir);
+    
+    let (paper, storage) = start(&config, live_market("40000")).await;
+    paper.get_market_data("BTC").await.unwrap();
+    paper.place_order(&order(OrderSide::Buy, OrderType::Market, "0.1", None)).await.unwrap();
+    paper.place_order(&order(OrderSide::Buy, OrderType::Limit, "0.05", Some("39000"))).await.unwrap();
+    
+    let equity = paper.equity();
+    let account = paper.get_account_info().await.unwrap();
+    let trades = paper.get_trade_history(None).await.unwrap();
+    assert!(equity < dec("10000"));
+    assert_eq!(account.positions.len(), 1);
+    assert_eq!(account.open_orders.len(), 1);
+    
+    // Killed: nothing more is saved
+    storage.flush().await;
+    drop(paper);
+    drop(storage);
+    
+    let (restarted, storage) = start(&config, live_market("40000")).await;
+    let restored = restarted.get_account_info().await.unwrap();
+    assert_eq!(restarted.equity(), equity);
+    assert_eq!(restored.available_balance, account.available_balance);
+    assert_eq!(restored.positions[0].size, account.positions[0].size);
+    assert_eq!(restored.positions[0].entry_price, account.positions[0].entry_price);
+    assert_eq!(restored.open_orders[0].id, account.open_orders[0].id);
+    assert_eq!(restarted.get_trade_history(None).await.unwrap().len(), trades.len());
+    storage.flush().await;
+    drop(restarted);
+    
+    // Trading picks up where it left off: the resting bid fills once the
+    // price comes down to it
+    let (dipped, _storage) = start(&config, live_market("38900")).await;
+    dipped.get_market_data("BTC").await.unwrap();
+    let account = dipped.get_account_info().await.unwrap();
+    assert!(account.open_orders.is_empty());
+    assert_eq!(account.positions[0].size, dec("0.15"));
+    
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[tokio::test]
+async fn reset_starts_the_paper_account_over() {
+    let dir = fixtures::temp_dir();
+    let mut config = paper_config(&dir);
+    
+    let (paper, storage) = start(&config, live_market("40000")).await;
+    paper.get_market_data("BTC").await.unwrap();
+    paper.place_order(&order(OrderSide::Buy, OrderType::Market, "0.1", None)).await.unwrap();
+    storage.flush().await;
+    
+    config.paper.reset = true;
+    config.paper.initial_balance = dec("5000");
+    let (reset, storage) = start(&config, live_market("40000")).await;
+    assert_eq!(reset.equity(), dec("5000"));
+    assert!(reset.get_positions().await.unwrap().is_empty());
+    storage.flush().await;
+    
+    // The reset sticks after the next restart
+    config.paper.reset = false;
+    let (restarted, _storage) = start(&config, live_market("40000")).await;
+    assert_eq!(restarted.equity(), dec("5000"));
+    assert!(restarted.get_trade_history(None).await.unwrap().is_empty());
+    
+    let _ = std::fs::remove_dir_all(dir);
+}
@@ -0,0 +1,287 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use futures_util::SinkExt;
+use hyperliquid_trading_bot::{
+    api::{
+        client::TradingClient,
+        types::{AssetInfo, Meta},
+    },
+    clock::SystemClock,
+    config::{Config, MarginMode, StrategyConfig},
+    models::{AccountInfo, MarketData, Order, Position, Trade},
+    runner::RunMode,
+    trading_bot::TradingBot,
+    validation::{CheckStatus, ValidationReport},
+    Error, Result,
+};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio_tungstenite::tungstenite::Message;
+
+/// An exchange listing BTC up to 10x, answering after `delay`
+struct MockClient {
+    reachable: bool,
+    delay: Duration,
+    available_balance: Decimal,
+}
+
+impl Default for MockClient {
+    fn default() -> Self {
+        Self {
+            reachable: true,
+            delay: Duration::ZERO,
+            available_balance: Decimal::from(1000),
+        }
+    }
+}
+
+#[async_trait]
+impl TradingClient for MockClient {
+    async fn get_market_data(&self, symbol: &str) -> Result<MarketData> {
+        Ok(MarketData {
+            symbol: symbol.to_string(),
+            price: Decimal::from(40_000),
+            volume_24h: Decimal::ZERO,
+            change_24h: Decimal::ZERO,
+            high_24h: Decimal::from(40_000),
+            low_24h: Decimal::from(40_000),
+            timestamp: Utc::now(),
+        })
+    }
+    
+    async fn get_account_info(&self) -> Result<AccountInfo> {
+        Ok(AccountInfo {
+            balance: self.available_balance,
+            available_balance: self.available_balance,
+            total_pnl: Decimal::ZERO,
+            total_margin: Decimal::ZERO,
+            positions: Vec::new(),
+            open_orders: Vec::new(),
+        })
+    }
+    
+    async fn get_positions(&self) -> Result<Vec<Position>> {
+        Ok(Vec::new())
+    }
+    
+    async fn get_open_orders(&self) -> Result<Vec<Order>> {
+        Ok(Vec::new())
+    }
+    
+    async fn place_order(&self, order: &Order) -> Result<String> {
+        Ok(order.id.clone())
+    }
+    
+    async fn cancel_order(&self, _order_id: &str) -> Result<bool> {
+        Ok(false)
+    }
+    
+    async fn get_trade_history(&self, _symbol: Option<&str>) -> Result<Vec<Trade>> {
+        Ok(Vec::new())
+    }
+    
+    async fn get_meta(&self) -> Result<Meta> {
+        tokio::time::sleep(self.delay).await;
+        if !self.reachable {
+            return Err(Error::Api("connection refused".to_string()));
+        }
+        
+        Ok(Meta {
+            universe: vec![AssetInfo {
+                name: "BTC".to_string(),
+                sz_decimals: 5,
+                wei_decimals: 0,
+                max_leverage: 10,
+                only_isolated: false,
+            }],
+            amms: Vec::new(),
+            open_interest: HashMap::new(),
+        })
+    }
+}
+
+/// How the test websocket endpoint behaves
+enum Stream {
+    /// Streams BTC tickers stamped this far behind the local clock
+    Tickers { behind: chrono::Duration },
+    /// Accepts connections but never sends anything
+    Silent,
+    /// Nothing is listening
+    Down,
+}
+
+async fn serve(stream: Stream) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    
+    let behind = match stream {
+        Stream::Tickers { behind } => Some(behind),
+        Stream::Silent => None,
+        Stream::Down => return url, // The listener is dropped
+    };
+    
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let Ok(mut ws) = tokio_tungstenite::accept_async(stream).await else {
+                    return;
+                };
+                loop {
+                    if let Some(behind) = behind {
+                        let ticker = serde_json::json!({
+                            "channel": "ticker",
+                            "data": {"coin": "BTC", "px": "40000", "sz": "1", "time": (Utc::now() - behind).timestamp_millis()}
+                        });
+                        if ws.send(Message::Text(ticker.to_string())).await.is_err() {
+                            return;
+                        }
+                    }
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                }
+            });
+        }
+    });
+    
+    url
+}
+
+fn config(ws_url: String, leverage: u32) -> Config {
+    let mut config = Config::default();
+    config.trading.dry_run = false;
+    config.hyperliquid.ws_url = ws_url;
+    config.preflight.max_latency_ms = 500;
+    config.preflight.websocket_timeout_secs = 1;
+    config.strategies.insert(
+        "dca_btc".to_string(),
+        StrategyConfig {
+            enabled: true,
+            strategy_type: "dca".to_string(),
+            symbol: "BTC".to_string(),
+            position_size: Decimal::from(100),
+            parameters: HashMap::new(),
+            leverage: Some(leverage),
+            margin_mode: MarginMode::Cross,
+            eval_interval_secs: None,
+        },
+    );
+    config
+}
+
+async fn bot(config: Config, client: MockClient) -> TradingBot {
+    TradingBot::with_client(config, std::sync::Arc::new(client), std::sync::Arc::new(SystemClock))
+        .await
+        .unwrap()
+}
+
+fn healthy_stream() -> Stream {
+    Stream::Tickers { behind: chrono::Duration::zero() }
+}
+
+fn status(report: &ValidationReport, name: &str) -> CheckStatus {
+    report.find(name).unwrap_or_else(|| panic!("no check named {}", name)).status
+}
+
+/// The one check expected to fail, with everything else passing
+fn assert_only_failure(report: &ValidationReport, name: &str) {
+    assert_eq!(status(report, name), CheckStatus::Fail, "{:?}", report.checks);
+    assert_eq!(report.failures(), 1, "{:?}", report.checks);
+}
+
+#[tokio::test]
+async fn healthy_setup_passes_every_check() {
+    let report = bot(config(serve(healthy_stream()).await, 5), MockClient::default()).await.preflight().await;
+    
+    assert!(report.passed(), "{:?}", report.checks);
+    for check in ["connectivity", "latency", "symbol dca_btc", "leverage dca_btc", "margin", "websocket", "clock skew"] {
+        assert_eq!(status(&report, check), CheckStatus::Pass, "{}", check);
+    }
+}
+
+#[tokio::test]
+async fn unreachable_api_fails_connectivity() {
+    let client = MockClient { reachable: false, ..MockClient::default() };
+    let report = bot(config(serve(healthy_stream()).await, 5), client).await.preflight().await;
+    
+    assert_only_failure(&report, "connectivity");
+    assert_eq!(status(&report, "symbols"), CheckStatus::Skip);
+}
+
+#[tokio::test]
+async fn slow_api_fails_latency() {
+    let client = MockClient { delay: Duration::from_millis(50), ..MockClient::default() };
+    let mut config = config(serve(healthy_stream()).await, 5);
+    config.preflight.max_latency_ms = 10;
+    let report = bot(config, client).await.preflight().await;
+    
+    assert_only_failure(&report, "latency");
+}
+
+#[tokio::test]
+async fn unlisted_symbol_fails() {
+    let mut config = config(serve(healthy_stream()).await, 5);
+    config.strategies.get_mut("dca_btc").unwrap().symbol = "DOGE".to_string();
+    let report = bot(config, MockClient::default()).await.preflight().await;
+    
+    assert_only_failure(&report, "symbol dca_btc");
+}
+
+#[tokio::test]
+async fn excessive_leverage_fails() {
+    let report = bot(config(serve(healthy_stream()).await, 20), MockClient::default()).await.preflight().await;
+    
+    assert_only_failure(&report, "leverage dca_btc");
+    assert!(report.find("leverage dca_btc").unwrap().detail.contains("10x maximum"));
+}
+
+#[tokio::test]
+async fn thin_margin_fails() {
+    let client = MockClient { available_balance: Decimal::from(5), ..MockClient::default() };
+    let report = bot(config(serve(healthy_stream()).await, 5), client).await.preflight().await;
+    
+    assert_only_failure(&report, "margin");
+}
+
+#[tokio::test]
+async fn unreachable_websocket_fails() {
+    let report = bot(config(serve(Stream::Down).await, 5), MockClient::default()).await.preflight().await;
+    
+    assert_only_failure(&report, "websocket");
+    assert_eq!(status(&report, "clock skew"), CheckStatus::Skip);
+}
+
+#[tokio::test]
+async fn silent_websocket_fails() {
+    let report = bot(config(serve(Stream::Silent).await, 5), MockClient::default()).await.preflight().await;
+    
+    assert_only_failure(&report, "websocket");
+    assert!(report.find("websocket").unwrap().detail.contains("no message"));
+}
+
+#[tokio::test]
+async fn skewed_clock_fails() {
+    let stream = Stream::Tickers { behind: chrono::Duration::seconds(60) };
+    let report = bot(config(serve(stream).await, 5), MockClient::default()).await.preflight().await;
+    
+    assert_only_failure(&report, "clock skew");
+}
+
+#[tokio::test]
+async fn failed_checks_abort_startup_unless_ignored() {
+    let client = || MockClient { available_balance: Decimal::from(5), ..MockClient::default() };
+    
+    let error = bot(config(serve(healthy_stream()).await, 5), client())
+        .await
+        .run(RunMode::Once)
+        .await
+        .unwrap_err();
+    let message = error.to_string();
+    assert!(message.contains("[FAIL] margin"), "{}", message);
+    assert!(message.contains("[PASS] connectivity"), "{}", message);
+    
+    let mut config = config(serve(healthy_stream()).await, 5);
+    config.preflight.ignore_failures = true;
+    let summary = bot(config, client()).await.run(RunMode::Once).await.unwrap();
+    assert_eq!(summary.cycles, 1);
+}
@@ -0,0 +1,139 @@
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use hyperliquid_trading_bot::{models::MarketData, price_cache::PriceCache};
+use rust_decimal::Decimal;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration as StdDuration;
+
+fn at(seconds: i64) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap() + Duration::seconds(seconds)
+}
+
+/// The price is tied to the timestamp, so a reader can tell a torn update
+fn ticker(symbol: &str, seconds: i64) -> MarketData {
+    let price = Decimal::from(40_000 + seconds);
+    MarketData {
+        symbol: symbol.to_string(),
+        price,
+        volume_24h: Decimal::from(1000),
+        change_24h: Decimal::ZERO,
+        high_24h: price,
+        low_24h: price,
+        timestamp: at(seconds),
+    }
+}
+
+#[test]
+fn readers_see_whole_updates_in_order_while_writers_run() {
+    let prices = PriceCache::new();
+    let done = Arc::new(AtomicBool::new(false));
+    let symbols = ["BTC", "ETH", "SOL"];
+    
+    let readers: Vec<_> = (0..4)
+        .map(|_| {
+            let prices = prices.clone();
+            let done = done.clone();
+            thread::spawn(move || {
+                let mut last_seen = [None; 3];
+                while !done.load(Ordering::Relaxed) {
+                    for (i, symbol) in symbols.iter().enumerate() {
+                        let Some(cached) = prices.get(symbol) else {
+                            continue;
+                        };
+                        let seconds = (cached.market_data.timestamp - at(0)).num_seconds();
+                        assert_eq!(cached.market_data.price, Decimal::from(40_000 + seconds));
+                        assert_eq!(cached.received_at, cached.market_data.timestamp);
+                        assert!(last_seen[i] <= Some(seconds), "{} went backwards", symbol);
+                        last_seen[i] = Some(seconds);
+                    }
+                }
+            })
+        })
+        .collect();
+    
+    // Two writers per symbol, like REST and the websocket racing
+    let writers: Vec<_> = symbols
+        .iter()
+        .flat_map(|symbol| [(symbol.to_string(), 0), (symbol.to_string(), 1)])
+        .map(|(symbol, offset)| {
+            let prices = prices.clone();
+            thread::spawn(move || {
+                for seconds in (offset..20_000).step_by(2) {
+                    prices.update(ticker(&symbol, seconds), at(seconds));
+                }
+            })
+        })
+        .collect();
+    for writer in writers {
+        writer.join().unwrap();
+    }
+    done.store(true, Ordering::Relaxed);
+    for reader in readers {
+        reader.join().unwrap();
+    }
+    
+    for symbol in symbols {
+        assert_eq!(prices.price(symbol), Some(Decimal::from(40_000 + 19_999)));
+    }
+    assert_eq!(prices.all().len(), 3);
+}
+
+#[test]
+fn older_data_never_replaces_newer() {
+    let prices = PriceCache::new();
+    prices.update(ticker("BTC", 10), at(10));
+    prices.update(ticker("BTC", 5), at(11));
+    
+    let cached = prices.get("BTC").unwrap();
+    assert_eq!(cached.market_data.timestamp, at(10));
+    assert_eq!(cached.received_at, at(10));
+}
+
+#[test]
+fn staleness_is_measured_from_receipt() {
+    let prices = PriceCache::new();
+    prices.update(ticker("BTC", 0), at(0));
+    
+    let cached = prices.get("BTC").unwrap();
+    assert_eq!(cached.age(at(30)), Duration::seconds(30));
+    assert!(!cached.is_stale(at(30), Duration::seconds(30)));
+    assert!(cached.is_stale(at(31), Duration::seconds(30)));
+    assert!(prices.fresh("BTC", at(30), Duration::seconds(30)).is_some());
+    assert!(prices.fresh("BTC", at(31), Duration::seconds(30)).is_none());
+    assert!(prices.fresh("ETH", at(0), Duration::seconds(30)).is_none());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn subscribers_hear_about_every_new_price() {
+    let prices = PriceCache::new();
+    
+    // Subscribing before the first price is fine
+    let mut btc = prices.subscribe("BTC");
+    assert!(btc.borrow().is_none());
+    
+    let writer = {
+        let prices = prices.clone();
+        tokio::spawn(async move {
+            for seconds in 1..=3 {
+                prices.update(ticker("ETH", seconds), at(seconds));
+                prices.update(ticker("BTC", seconds), at(seconds));
+                tokio::time::sleep(StdDuration::from_millis(5)).await;
+            }
+        })
+    };
+    
+    let mut seen = Vec::new();
+    while seen.last() != Some(&3) {
+        tokio::time::timeout(StdDuration::from_secs(1), btc.changed()).await.unwrap().unwrap();
+        let cached = btc.borrow_and_update().clone().unwrap();
+        assert_eq!(cached.market_data.symbol, "BTC");
+        seen.push((cached.market_data.timestamp - at(0)).num_seconds());
+    }
+    writer.await.unwrap();
+    assert!(seen.windows(2).all(|pair| pair[0] < pair[1]), "{:?}", seen);
+    
+    // Stale data doesn't wake anyone
+    prices.update(ticker("BTC", 1), at(4));
+    assert!(!btc.has_changed().unwrap());
+}
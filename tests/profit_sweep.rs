@@ -0,0 +1,78 @@
+use hyperliquid_trading_bot::{
+    config::{Config, ProfitSweepConfig, SweepMethod},
+    testing::{self, fixtures, Call, MockTradingClient, Transfer},
+    Error,
+};
+use rust_decimal::Decimal;
+use std::str::FromStr;
+use std::sync::Arc;
+
+const COLD: &str = "0x0d4a11d5eeaac28ec3f61d100daf4d40471f1852";
+
+fn config(dry_run: bool) -> Config {
+    let mut config = Config::default();
+    config.trading.dry_run = dry_run;
+    config.profit_sweep = ProfitSweepConfig {
+        enabled: true,
+        threshold: Decimal::from(8_000),
+        destination: COLD.to_string(),
+        method: SweepMethod::Transfer,
+        interval_hours: 24 * 7,
+    };
+    config
+}
+
+fn swept(amount: &str) -> Transfer {
+    Transfer {
+        call: Call::UsdTransfer,
+        destination: COLD.to_string(),
+        amount: Decimal::from_str(amount).unwrap(),
+    }
+}
+
+#[tokio::test]
+async fn profits_above_the_threshold_are_swept_weekly() {
+    let exchange = Arc::new(MockTradingClient::new(Decimal::from_str("10250.759").unwrap()));
+    let bot = testing::mock_bot(config(false), exchange.clone()).await.unwrap();
+    
+    // Whole cents only, so it never rounds past what's withdrawable
+    testing::run_bot_cycles(&bot, exchange.clock(), 3).await.unwrap();
+    assert_eq!(exchange.transfers(), vec![swept("2250.75")]);
+    
+    exchange.script_accounts([fixtures::account(Decimal::from(9_000))]);
+    exchange.clock().advance(chrono::Duration::days(7));
+    testing::run_bot_cycles(&bot, exchange.clock(), 1).await.unwrap();
+    assert_eq!(exchange.transfers(), vec![swept("2250.75"), swept("1000")]);
+    
+    // Nothing above the threshold, nothing to sweep
+    exchange.script_accounts([fixtures::account(Decimal::from(7_500))]);
+    exchange.clock().advance(chrono::Duration::days(7));
+    testing::run_bot_cycles(&bot, exchange.clock(), 1).await.unwrap();
+    assert_eq!(exchange.transfers().len(), 2);
+}
+
+#[tokio::test]
+async fn dry_runs_never_sweep() {
+    let exchange = Arc::new(MockTradingClient::new(Decimal::from(20_000)));
+    let bot = testing::mock_bot(config(true), exchange.clone()).await.unwrap();
+    
+    testing::run_bot_cycles(&bot, exchange.clock(), 2).await.unwrap();
+    assert!(matches!(bot.sweep_profits().await, Err(Error::Trading(_))));
+    
+    assert!(exchange.transfers().is_empty());
+    assert!(!exchange.calls().iter().any(|call| matches!(call.call, Call::UsdTransfer | Call::Withdraw)));
+}
+
+#[tokio::test]
+async fn a_failed_sweep_waits_for_the_next_interval() {
+    let exchange = Arc::new(MockTradingClient::new(Decimal::from(10_000)));
+    exchange.fail_next(Call::UsdTransfer, Error::Trading("Insufficient balance for withdrawal".to_string()));
+    let bot = testing::mock_bot(config(false), exchange.clone()).await.unwrap();
+    
+    testing::run_bot_cycles(&bot, exchange.clock(), 2).await.unwrap();
+    assert!(exchange.transfers().is_empty());
+    
+    exchange.clock().advance(chrono::Duration::days(7));
+    testing::run_bot_cycles(&bot, exchange.clock(), 1).await.unwrap();
+    assert_eq!(exchange.transfers(), vec![swept("2000")]);
+}
@@ -0,0 +1,106 @@
+use hyperliquid_trading_bot::{
+    api::{rate_limit::RateLimiter, HyperliquidClient},
+    config::RateLimitConfig,
+    Error,
+};
+use serde_json::json;
+use std::time::Duration;
+use tokio::time::Instant;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+async fn mids_server() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/info"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "BTC": "43251.5" })))
+        .mount(&server)
+        .await;
+    server
+}
+
+fn limited_client(server: &MockServer, limits: RateLimitConfig) -> HyperliquidClient {
+    HyperliquidClient::new(server.uri(), "key".to_string(), String::new(), true).with_rate_limits(&limits)
+}
+
+#[tokio::test]
+async fn concurrent_requests_wait_their_turn() {
+    let server = mids_server().await;
+    let client = limited_client(
+        &server,
+        RateLimitConfig {
+            info_per_second: 50.0,
+            info_burst: 10,
+            ..RateLimitConfig::default()
+        },
+    );
+    
+    let started = std::time::Instant::now();
+    let results = futures_util::future::join_all((0..50).map(|_| client.get_all_mids())).await;
+    let elapsed = started.elapsed();
+    
+    // Ten go at once, the other forty at 50 a second
+    assert!(results.iter().all(|result| result.is_ok()));
+    assert!(elapsed >= Duration::from_millis(750), "not paced: {:?}", elapsed);
+    assert!(elapsed < Duration::from_secs(3), "paced too slowly: {:?}", elapsed);
+    assert_eq!(server.received_requests().await.unwrap().len(), 50);
+}
+
+#[tokio::test]
+async fn requests_that_would_wait_too_long_fail() {
+    let server = mids_server().await;
+    let client = limited_client(
+        &server,
+        RateLimitConfig {
+            info_per_second: 1.0,
+            info_burst: 1,
+            max_wait_ms: 0,
+            ..RateLimitConfig::default()
+        },
+    );
+    
+    client.get_all_mids().await.unwrap();
+    let error = client.get_all_mids().await.unwrap_err();
+    assert!(matches!(error.root(), Error::RateLimit { retry_after: Some(_) }), "{}", error);
+    assert!(error.is_retryable());
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn the_bucket_refills_at_its_rate() {
+    let limiter = RateLimiter::new(10.0, 2, Duration::from_millis(500));
+    let started = Instant::now();
+    
+    // The burst goes straight out; then each request waits 100ms more than
+    // the one before, until the wait would pass the maximum
+    let waits = futures_util::future::join_all((0..8).map(|_| async {
+        limiter.acquire().await.map(|_| started.elapsed().as_millis())
+    }))
+    .await;
+    let (granted, refused) = waits.split_at(7);
+    let granted: Vec<u128> = granted.iter().map(|wait| *wait.as_ref().unwrap()).collect();
+    assert_eq!(granted, [0, 0, 100, 200, 300, 400, 500]);
+    assert!(
+        matches!(&refused[0], Err(Error::RateLimit { retry_after: Some(wait) }) if *wait > Duration::from_millis(500)),
+        "{:?}",
+        refused[0]
+    );
+    
+    // A refusal doesn't take a token, and tokens come back over time
+    tokio::time::advance(Duration::from_millis(700)).await;
+    let started = Instant::now();
+    limiter.acquire().await.unwrap();
+    limiter.acquire().await.unwrap();
+    assert_eq!(started.elapsed(), Duration::ZERO);
+}
+
+#[test]
+fn non_positive_rates_disable_the_limit() {
+    let limiter = RateLimiter::new(0.0, 1, Duration::ZERO);
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_time().build().unwrap();
+    runtime.block_on(async {
+        for _ in 0..100 {
+            limiter.acquire().await.unwrap();
+        }
+    });
+}
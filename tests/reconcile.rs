@@ -0,0 +1,248 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use hyperliquid_trading_bot::{
+    config::{Config, ReconciliationConfig},
+    journal::JournalEntry,
+    models::{OrderSide, Trade},
+    notifications::{NotificationChannel, NotificationEvent, Notifier},
+    reconcile::{self, ReconciliationReport},
+    storage::{keys, SqliteStorage, Storage},
+    testing::{self, fixtures, MockTradingClient},
+    trading_bot::TradingBot,
+    Result,
+};
+use rust_decimal::Decimal;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// Keeps every notification it's sent
+#[derive(Default)]
+struct Recorder {
+    events: Mutex<Vec<NotificationEvent>>,
+}
+
+#[async_trait]
+impl NotificationChannel for Recorder {
+    fn name(&self) -> &str {
+        "recorder"
+    }
+    
+    async fn send_batch(&self, events: &[NotificationEvent]) -> Result<()> {
+        self.events.lock().unwrap().extend_from_slice(events);
+        Ok(())
+    }
+}
+
+fn at(seconds: i64) -> DateTime<Utc> {
+    fixtures::start() + Duration::seconds(seconds)
+}
+
+fn recorded(order_id: &str, symbol: &str, side: OrderSide, quantity: Decimal, timestamp: DateTime<Utc>) -> JournalEntry {
+    JournalEntry {
+        timestamp,
+        strategy: "grid_btc".to_string(),
+        symbol: symbol.to_string(),
+        side,
+        quantity,
+        price: Decimal::from(100),
+        fee: Decimal::ZERO,
+        realized_pnl: Decimal::ZERO,
+        order_id: order_id.to_string(),
+        cloid: None,
+        dry_run: false,
+        reconciled: false,
+    }
+}
+
+fn order_ids(entries: &[JournalEntry]) -> Vec<&str> {
+    entries.iter().map(|entry| entry.order_id.as_str()).collect()
+}
+
+#[test]
+fn match_fills_sorts_out_divergent_histories() {
+    let qty = |n: i64| Decimal::new(n, 2);
+    let local = vec![
+        // Filled in two parts on the exchange
+        recorded("1", "BTC", OrderSide::Buy, qty(10), at(10)),
+        // Recorded under the bot's own id, a couple of seconds off
+        recorded("local-3", "ETH", OrderSide::Sell, qty(100), at(30)),
+        // Recorded under the bot's own id, with a client order id
+        JournalEntry {
+            cloid: Some("0xabc".to_string()),
+            ..recorded("local-4", "SOL", OrderSide::Buy, qty(500), at(40))
+        },
+        // Never reached the exchange
+        recorded("5", "BTC", OrderSide::Sell, qty(20), at(50)),
+        // Dry runs never do
+        JournalEntry {
+            dry_run: true,
+            ..recorded("6", "BTC", OrderSide::Buy, qty(10), at(60))
+        },
+        // Before the range
+        recorded("7", "BTC", OrderSide::Buy, qty(10), at(-5)),
+    ];
+    let exchange = vec![
+        fixtures::trade("1", "BTC", OrderSide::Buy, qty(5), Decimal::from(100), at(10)),
+        fixtures::trade("1", "BTC", OrderSide::Buy, qty(5), Decimal::from(100), at(11)),
+        fixtures::trade("3", "ETH", OrderSide::Sell, qty(100), Decimal::from(100), at(32)),
+        Trade {
+            cloid: Some("0xabc".to_string()),
+            ..fixtures::trade("4", "SOL", OrderSide::Buy, qty(499), Decimal::from(100), at(41))
+        },
+        // Never recorded
+        fixtures::trade("8", "BTC", OrderSide::Sell, qty(30), Decimal::from(101), at(70)),
+        // The wrong side for the ETH fill, and too late for it
+        fixtures::trade("9", "ETH", OrderSide::Buy, qty(100), Decimal::from(100), at(31)),
+        // After the range
+        fixtures::trade("10", "BTC", OrderSide::Buy, qty(10), Decimal::from(100), at(200)),
+    ];
+    
+    let report = reconcile::match_fills(&local, &exchange, at(0), at(100), Duration::seconds(5));
+    
+    assert_eq!(report.matched, 4);
+    assert_eq!(order_ids(&report.missing), ["9", "8"]);
+    assert_eq!(order_ids(&report.unknown), ["5"]);
+    assert_eq!(report.discrepancies(), 3);
+    
+    let missed = &report.missing[1];
+    assert!(missed.reconciled);
+    assert!(!missed.dry_run);
+    assert_eq!(missed.strategy, reconcile::UNKNOWN_STRATEGY);
+    assert_eq!((missed.quantity, missed.price, missed.timestamp), (qty(30), Decimal::from(101), at(70)));
+}
+
+#[tokio::test]
+async fn reconcile_runs_from_the_watermark_to_the_settle_time() {
+    let storage = SqliteStorage::in_memory().unwrap();
+    let client = MockTradingClient::new(Decimal::from(10_000));
+    let config = ReconciliationConfig {
+        settle_secs: 60,
+        initial_lookback_hours: 1,
+        ..ReconciliationConfig::default()
+    };
+    let one = Decimal::ONE;
+    storage.record_fill(&recorded("1", "BTC", OrderSide::Buy, one, at(-600))).unwrap();
+    storage.record_fill(&recorded("2", "BTC", OrderSide::Buy, one, at(-300))).unwrap();
+    client.set_fills(vec![
+        // Before the first run's lookback
+        fixtures::trade("0", "BTC", OrderSide::Buy, one, Decimal::from(100), at(-7200)),
+        fixtures::trade("1", "BTC", OrderSide::Buy, one, Decimal::from(100), at(-600)),
+        fixtures::trade("3", "BTC", OrderSide::Sell, one, Decimal::from(100), at(-120)),
+        // Not settled yet
+        fixtures::trade("4", "BTC", OrderSide::Sell, one, Decimal::from(100), at(-30)),
+    ]);
+    
+    let report = reconcile::reconcile(&client, &storage, &config, at(0)).await.unwrap();
+    assert_eq!((report.since, report.until), (at(-3660), at(-60)));
+    assert_eq!(report.matched, 1);
+    assert_eq!(order_ids(&report.missing), ["3"]);
+    assert_eq!(order_ids(&report.unknown), ["2"]);
+    
+    reconcile::record(&storage, None, &report).unwrap();
+    assert_eq!(reconcile::watermark(&storage).unwrap(), Some(at(-60)));
+    let stats: serde_json::Value = serde_json::from_str(&storage.get_kv(keys::TRADE_STATS).unwrap().unwrap()).unwrap();
+    assert_eq!(stats["successful_trades"], 1);
+    
+    // The next run picks up where this one stopped, once the last fill has
+    // settled
+    let report = reconcile::reconcile(&client, &storage, &config, at(600)).await.unwrap();
+    assert_eq!((report.since, report.until), (at(-60), at(540)));
+    assert_eq!(report.matched, 0);
+    assert_eq!(order_ids(&report.missing), ["4"]);
+    assert!(report.unknown.is_empty());
+}
+
+async fn live_bot(client: Arc<MockTradingClient>, dir: &Path, recorder: &Arc<Recorder>) -> TradingBot {
+    let mut config = Config::default();
+    config.trading.dry_run = false;
+    config.storage.enabled = true;
+    config.storage.path = dir.join("bot.db").display().to_string();
+    config.journal.enabled = true;
+    config.journal.directory = dir.join("journal").display().to_string();
+    config.reconciliation.enabled = true;
+    config.reconciliation.alert_threshold = 2;
+    
+    let clock = Arc::new(client.clock().clone());
+    TradingBot::with_client(config, client, clock)
+        .await
+        .unwrap()
+        .with_notifier(Notifier::new(vec![recorder.clone()]))
+}
+
+/// The fill notifications sent, as (missing, unknown) for discrepancies
+/// and `None` for trades
+fn fill_notifications(recorder: &Recorder) -> Vec<Option<(usize, usize)>> {
+    recorder
+        .events
+        .lock()
+        .unwrap()
+        .iter()
+        .filter_map(|event| match event {
+            NotificationEvent::FillDiscrepancy { missing, unknown } => Some(Some((*missing, *unknown))),
+            NotificationEvent::TradeExecuted { .. } => Some(None),
+            _ => None,
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn the_bot_records_missed_fills_and_reports_unknown_ones() {
+    let dir = fixtures::temp_dir();
+    let one = Decimal::ONE;
+    {
+        let storage = SqliteStorage::open(dir.join("bot.db")).unwrap();
+        storage.record_fill(&recorded("1", "BTC", OrderSide::Buy, one, at(-3600))).unwrap();
+        storage.record_fill(&recorded("2", "BTC", OrderSide::Sell, one, at(-1800))).unwrap();
+    }
+    let client = Arc::new(MockTradingClient::new(Decimal::from(10_000)));
+    client.set_fills(vec![
+        fixtures::trade("1", "BTC", OrderSide::Buy, one, Decimal::from(100), at(-3600)),
+        Trade {
+            realized_pnl: Decimal::new(-75, 1),
+            ..fixtures::trade("3", "ETH", OrderSide::Buy, one, Decimal::from(100), at(-900))
+        },
+    ]);
+    let recorder = Arc::new(Recorder::default());
+    let bot = live_bot(client.clone(), &dir, &recorder).await;
+    
+    // The first cycle reconciles, and two discrepancies reach the threshold
+    bot.run_cycle().await.unwrap();
+    tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+    assert_eq!(fill_notifications(&recorder), [Some((1, 1))]);
+    assert_eq!(bot.cached_snapshot().await.status.successful_trades, 1);
+    
+    // Later cycles wait out the interval
+    client.clock().advance(Duration::seconds(600));
+    client.set_fills(vec![fixtures::trade("4", "BTC", OrderSide::Sell, one, Decimal::from(100), at(300))]);
+    bot.run_cycle().await.unwrap();
+    
+    // One discrepancy is recorded without an alert
+    let report = bot.reconcile_fills().await.unwrap().unwrap();
+    assert_eq!(order_ids(&report.missing), ["4"]);
+    assert!(report.unknown.is_empty());
+    bot.stop().await;
+    assert_eq!(fill_notifications(&recorder), [Some((1, 1))]);
+    
+    let storage = SqliteStorage::open(dir.join("bot.db")).unwrap();
+    assert_eq!(reconcile::watermark(&storage).unwrap(), Some(at(540)));
+    let fills = storage.fills_between(at(-7200), at(600)).unwrap();
+    let reconciled: Vec<(&str, &str)> = fills
+        .iter()
+        .filter(|fill| fill.reconciled)
+        .map(|fill| (fill.order_id.as_str(), fill.symbol.as_str()))
+        .collect();
+    assert_eq!(reconciled, [("3", "ETH"), ("4", "BTC")]);
+    
+    let journal = std::fs::read_to_string(dir.join("journal").join("trades-2023-12-31.csv")).unwrap();
+    let rows: Vec<&str> = journal.lines().filter(|line| line.ends_with(",true")).collect();
+    assert_eq!(rows.len(), 1, "{}", journal);
+    assert!(rows[0].contains(",unknown,ETH,BUY,1,100,0,-7.5,3,"), "{}", rows[0]);
+}
+
+#[tokio::test]
+async fn dry_runs_are_not_reconciled() {
+    let client = Arc::new(MockTradingClient::new(Decimal::from(10_000)));
+    let bot = testing::mock_bot(Config::default(), client).await.unwrap();
+    let report: Option<ReconciliationReport> = bot.reconcile_fills().await.unwrap();
+    assert!(report.is_none());
+}
@@ -0,0 +1,141 @@
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use hyperliquid_trading_bot::{
+    config::RecorderConfig,
+    events::{BotEvent, BusEvent, EventSubscriber},
+    recorder::{self, MarketRecorder, MarketStream, RecordedEvent, RecordingReader},
+    testing::fixtures,
+};
+use rust_decimal::Decimal;
+use serde_json::json;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+fn at(hours: i64) -> DateTime<Utc> {
+    fixtures::start() + Duration::hours(hours)
+}
+
+fn day(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 1, day).unwrap()
+}
+
+fn event(symbol: &str, hours: i64) -> RecordedEvent {
+    RecordedEvent {
+        timestamp: at(hours),
+        ..RecordedEvent::new(symbol, MarketStream::Ticker, &json!({ "hours": hours })).unwrap()
+    }
+}
+
+fn recorder(directory: &Path) -> MarketRecorder {
+    MarketRecorder::new(RecorderConfig {
+        enabled: true,
+        directory: directory.to_string_lossy().into_owned(),
+        ..RecorderConfig::default()
+    })
+    .unwrap()
+}
+
+fn read(directory: &Path, from: u32, to: u32, symbols: Option<&[&str]>) -> Vec<(String, i64)> {
+    let symbols = symbols.map(|symbols| symbols.iter().map(|s| s.to_string()).collect());
+    RecordingReader::open(directory, day(from), day(to), symbols)
+        .unwrap()
+        .map(|event| {
+            let event = event.unwrap();
+            (event.symbol, event.data["hours"].as_i64().unwrap())
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn recordings_read_back_in_time_order_by_day_and_symbol() {
+    let directory = fixtures::temp_dir();
+    let recorder = recorder(&directory);
+    for (symbol, hours) in [("BTC", 1), ("ETH", 0), ("BTC", 25), ("ETH", 2), ("BTC", 48)] {
+        recorder.record(event(symbol, hours));
+    }
+    recorder.flush().await;
+    
+    assert!(recorder::partition_path(&directory, day(2), "BTC").exists());
+    assert_eq!(
+        read(&directory, 1, 3, None),
+        [("ETH", 0), ("BTC", 1), ("ETH", 2), ("BTC", 25), ("BTC", 48)].map(|(s, h)| (s.to_string(), h))
+    );
+    assert_eq!(read(&directory, 2, 2, None), [("BTC".to_string(), 25)]);
+    assert_eq!(read(&directory, 1, 1, Some(&["ETH"])), [("ETH".to_string(), 0), ("ETH".to_string(), 2)]);
+    assert_eq!(recorder.dropped_count(), 0);
+}
+
+#[tokio::test]
+async fn each_flush_appends_to_the_days_file() {
+    let directory = fixtures::temp_dir();
+    let recorder = recorder(&directory);
+    
+    recorder.record(event("BTC", 0));
+    recorder.flush().await;
+    recorder.record(event("BTC", 1));
+    recorder.flush().await;
+    
+    assert_eq!(read(&directory, 1, 1, None), [("BTC".to_string(), 0), ("BTC".to_string(), 1)]);
+}
+
+#[tokio::test]
+async fn a_damaged_tail_ends_the_file_without_losing_what_came_before() {
+    let directory = fixtures::temp_dir();
+    let recorder = recorder(&directory);
+    recorder.record(event("BTC", 0));
+    recorder.record(event("BTC", 1));
+    recorder.flush().await;
+    
+    // A crash part way through compressing the next batch
+    let path = recorder::partition_path(&directory, day(1), "BTC");
+    OpenOptions::new().append(true).open(&path).unwrap().write_all(&[0x1f, 0x8b, 0x08, 0x00, 0x13]).unwrap();
+    
+    assert_eq!(read(&directory, 1, 1, None), [("BTC".to_string(), 0), ("BTC".to_string(), 1)]);
+}
+
+#[test]
+fn retention_prunes_the_oldest_days_first() {
+    let directory = fixtures::temp_dir();
+    for date in [day(1), day(2), day(3)] {
+        let path = recorder::partition_path(&directory, date, "BTC");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, vec![0u8; 1_000]).unwrap();
+    }
+    // Newer than anything recorded, so never pruned
+    let today = recorder::partition_path(&directory, Utc::now().date_naive(), "BTC");
+    fs::create_dir_all(today.parent().unwrap()).unwrap();
+    fs::write(&today, vec![0u8; 1_000]).unwrap();
+    
+    recorder::enforce_retention(&directory, 2_500).unwrap();
+    assert!(!recorder::partition_path(&directory, day(1), "BTC").exists());
+    assert!(!recorder::partition_path(&directory, day(2), "BTC").exists());
+    assert!(recorder::partition_path(&directory, day(3), "BTC").exists());
+    
+    recorder::enforce_retention(&directory, 0).unwrap();
+    assert!(!recorder::partition_path(&directory, day(3), "BTC").exists());
+    assert!(today.exists());
+}
+
+#[tokio::test]
+async fn closed_candles_are_recorded_at_their_own_time() {
+    let directory = fixtures::temp_dir();
+    let mut recorder = recorder(&directory);
+    let bar = fixtures::market_data("BTC", Decimal::from(40_000));
+    let publish = |seq, event| BusEvent { seq, timestamp: at(30), event };
+    
+    recorder.handle(&publish(1, BotEvent::CandleClosed { interval: "1m".to_string(), bar: bar.clone() }));
+    recorder.handle(&publish(2, BotEvent::OrderCancelled {
+        order_id: "1".to_string(),
+        symbol: "BTC".to_string(),
+        reason: "manual".to_string(),
+    }));
+    recorder.flush().await;
+    
+    let events: Vec<RecordedEvent> = RecordingReader::open(&directory, day(1), day(1), None)
+        .unwrap()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(events.len(), 1);
+    assert_eq!((events[0].timestamp, events[0].stream), (bar.timestamp, MarketStream::Ticker));
+    assert_eq!(events[0].data["price"], json!(bar.price));
+}
@@ -0,0 +1,91 @@
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use flate2::{write::GzEncoder, Compression};
+use hyperliquid_trading_bot::{
+    backtest::BacktestResult,
+    market_stream::ReplayMarketStream,
+    models::MarketData,
+    recorder::{self, MarketStream, RecordedEvent},
+    replay::Replay,
+    testing::fixtures,
+};
+use rust_decimal::Decimal;
+use serde_json::json;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+use std::str::FromStr;
+
+fn dec(value: &str) -> Decimal {
+    Decimal::from_str(value).unwrap()
+}
+
+fn minute(n: i64) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap() + Duration::minutes(n)
+}
+
+/// An hour of BTC tickers a minute apart, swinging around $40,000, written
+/// the way the market recorder writes them
+fn write_recording(directory: &Path) {
+    let date = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+    let path = recorder::partition_path(directory, date, "BTC");
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    
+    let mut writer = GzEncoder::new(File::create(&path).unwrap(), Compression::default());
+    for n in 0..60 {
+        let price = Decimal::from(40_000 + [0, 150, -200, 50, -100][n as usize % 5]);
+        let ticker = MarketData {
+            symbol: "BTC".to_string(),
+            price,
+            volume_24h: dec("1000"),
+            change_24h: Decimal::ZERO,
+            high_24h: price,
+            low_24h: price,
+            timestamp: minute(n),
+        };
+        let event = RecordedEvent {
+            timestamp: minute(n),
+            ..RecordedEvent::new("BTC", MarketStream::Ticker, &ticker).unwrap()
+        };
+        writeln!(writer, "{}", serde_json::to_string(&event).unwrap()).unwrap();
+    }
+    writer.finish().unwrap();
+}
+
+async fn replay(directory: &Path) -> BacktestResult {
+    let stream = ReplayMarketStream::open(directory, &["BTC".to_string()], minute(0), minute(60)).unwrap();
+    Replay::new(fixtures::dca_backtest_config(dec("10000"), "1m", json!({ "interval_hours": 1 })), stream)
+        .await
+        .unwrap()
+        .with_cycle_interval(Duration::minutes(5))
+        .run()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn replaying_a_recording_is_deterministic() {
+    let directory = fixtures::temp_dir();
+    write_recording(&directory);
+    
+    let first = replay(&directory).await;
+    let second = replay(&directory).await;
+    fs::remove_dir_all(&directory).unwrap();
+    
+    // A cycle every 5 recorded minutes, starting with the first ticker
+    assert_eq!(first.equity_curve.len(), 12);
+    assert_eq!(first.equity_curve[0].timestamp, minute(0));
+    assert!(!first.trades.is_empty());
+    assert!(first.trades.iter().all(|t| t.strategy == "dca_btc" && t.symbol == "BTC"));
+    
+    let fills = |result: &BacktestResult| -> Vec<String> {
+        result
+            .trades
+            .iter()
+            .map(|t| format!("{} {:?} {} @ {} fee {}", t.timestamp, t.side, t.quantity, t.price, t.fee))
+            .collect()
+    };
+    assert_eq!(fills(&first), fills(&second));
+    assert_eq!(first.final_equity, second.final_equity);
+    let equity = |result: &BacktestResult| -> Vec<Decimal> { result.equity_curve.iter().map(|s| s.equity).collect() };
+    assert_eq!(equity(&first), equity(&second));
+}
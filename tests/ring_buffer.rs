@@ -0,0 +1,85 @@
+use hyperliquid_trading_bot::strategies::base::{
+    calculate_bollinger_bands, calculate_macd, calculate_rsi, calculate_sma, RingBuffer,
+};
+use rust_decimal::Decimal;
+
+/// A few hundred prices that trend, chop and gap, so every indicator moves
+fn prices() -> Vec<Decimal> {
+    (0..400i64)
+        .map(|i| Decimal::from(40_000 + i * 3 + [0, 120, -80, 45, -150, 60, 10][(i % 7) as usize]))
+        .collect()
+}
+
+#[test]
+fn ring_buffer_keeps_the_latest_items() {
+    let mut buffer = RingBuffer::new(3);
+    assert!(buffer.is_empty());
+    assert_eq!(buffer.last(), None);
+    
+    buffer.extend(1..=5);
+    assert_eq!(buffer.len(), 3);
+    assert_eq!(buffer.to_vec(), vec![3, 4, 5]);
+    assert_eq!(buffer.last(), Some(&5));
+    assert_eq!(buffer.iter_recent(2).copied().collect::<Vec<_>>(), vec![4, 5]);
+    assert_eq!(buffer.iter_recent(10).count(), 3);
+    assert_eq!(buffer.make_contiguous(), &[3, 4, 5]);
+    
+    buffer.set_capacity(2);
+    assert_eq!(buffer.to_vec(), vec![4, 5]);
+    buffer.set_capacity(4);
+    buffer.extend([6, 7]);
+    assert_eq!(buffer.to_vec(), vec![4, 5, 6, 7]);
+}
+
+#[test]
+fn momentum_indicators_match_the_trimmed_vec() {
+    // Momentum's defaults: history of twice the slow period
+    let (fast, slow, signal, rsi_period) = (12, 26, 9, 14);
+    let max_history = slow * 2;
+    
+    let mut old: Vec<Decimal> = Vec::new();
+    let mut new = RingBuffer::new(max_history);
+    for price in prices() {
+        old.push(price);
+        if old.len() > max_history {
+            old.drain(0..old.len() - max_history);
+        }
+        new.push(price);
+        
+        let contiguous = new.make_contiguous();
+        assert_eq!(contiguous, old.as_slice());
+        assert_eq!(calculate_macd(contiguous, fast, slow, signal), calculate_macd(&old, fast, slow, signal));
+        assert_eq!(calculate_rsi(contiguous, rsi_period), calculate_rsi(&old, rsi_period));
+        assert_eq!(calculate_sma(contiguous, fast), calculate_sma(&old, fast));
+        assert_eq!(calculate_sma(contiguous, slow), calculate_sma(&old, slow));
+        assert_eq!(
+            calculate_bollinger_bands(contiguous, slow, Decimal::from(2)),
+            calculate_bollinger_bands(&old, slow, Decimal::from(2))
+        );
+    }
+}
+
+#[test]
+fn dca_average_matches_the_trimmed_vec() {
+    // DCA used to keep up to twice the lookback and average the latest
+    // lookback; the buffer keeps just the lookback
+    let lookback = 20;
+    
+    let mut old: Vec<Decimal> = Vec::new();
+    let mut new = RingBuffer::new(lookback);
+    for price in prices() {
+        old.push(price);
+        if old.len() > lookback * 2 {
+            old.drain(0..old.len() - lookback);
+        }
+        new.push(price);
+        
+        assert_eq!(old.len() >= lookback, new.len() >= lookback);
+        if new.len() >= lookback {
+            let old_average = old[old.len() - lookback..].iter().sum::<Decimal>() / Decimal::from(lookback);
+            let new_average = new.iter_recent(lookback).sum::<Decimal>() / Decimal::from(lookback);
+            assert_eq!(new_average, old_average);
+        }
+        assert_eq!(new.last(), old.last());
+    }
+}
@@ -0,0 +1,161 @@
+use hyperliquid_trading_bot::{
+    config::Config,
+    models::{AccountInfo, EquityPoint, FeeSchedule, FundingPayment, Trade},
+    testing::{self, fixtures, Call, MockTradingClient},
+    Error,
+};
+use rust_decimal::Decimal;
+use serde_json::json;
+use std::str::FromStr;
+use std::sync::Arc;
+
+fn config(investment_amount: &str) -> Config {
+    let mut config = fixtures::live_dca_config(json!({ "investment_amount": investment_amount }));
+    config.risk_management.max_daily_loss = Decimal::from(500);
+    config.risk_management.max_position_size = Decimal::from(1_000);
+    config
+}
+
+fn exchange() -> Arc<MockTradingClient> {
+    let exchange = Arc::new(MockTradingClient::new(Decimal::from(10_000)));
+    exchange.set_price("BTC", Decimal::from(40_000));
+    exchange
+}
+
+#[tokio::test(start_paused = true)]
+async fn daily_loss_limit_pauses_trading_until_the_account_recovers() {
+    let exchange = exchange();
+    let losing = AccountInfo {
+        total_pnl: Decimal::from(-600),
+        ..fixtures::account(Decimal::from(9_400))
+    };
+    exchange.script_accounts([losing.clone(), losing, fixtures::account(Decimal::from(10_000))]);
+    let bot = testing::mock_bot(config("100"), exchange.clone()).await.unwrap();
+    
+    testing::run_bot_cycles(&bot, exchange.clock(), 2).await.unwrap();
+    assert!(exchange.sent_orders().is_empty());
+    
+    testing::run_bot_cycles(&bot, exchange.clock(), 1).await.unwrap();
+    assert_eq!(exchange.sent_orders().len(), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn oversized_signals_are_rejected() {
+    let exchange = exchange();
+    let bot = testing::mock_bot(config("5000"), exchange.clone()).await.unwrap();
+    
+    testing::run_bot_cycles(&bot, exchange.clock(), 1).await.unwrap();
+    
+    assert!(exchange.sent_orders().is_empty());
+    assert_eq!(bot.get_status().await.failed_trades, 0);
+}
+
+#[tokio::test]
+async fn funding_paid_comes_out_of_the_reported_pnl() {
+    let exchange = exchange();
+    let payment = |amount: i64, hours: i64| FundingPayment {
+        coin: "BTC".to_string(),
+        amount: Decimal::from(amount),
+        size: Decimal::ONE,
+        rate: Decimal::ZERO,
+        time: fixtures::start() + chrono::Duration::hours(hours),
+    };
+    exchange.set_funding(vec![payment(-4, 0)]);
+    exchange.set_fee_schedule(FeeSchedule {
+        maker_rate: Decimal::ZERO,
+        taker_rate: Decimal::ZERO,
+    });
+    let bot = testing::mock_bot(config("100"), exchange.clone()).await.unwrap();
+    
+    testing::run_bot_cycles(&bot, exchange.clock(), 1).await.unwrap();
+    let risk = bot.get_status().await.risk_metrics;
+    assert_eq!(risk.funding_paid, Decimal::from(4));
+    assert_eq!(risk.total_pnl, Decimal::from(-4));
+    
+    // Only payments settled since the last sync are counted
+    exchange.set_funding(vec![payment(-4, 0), payment(1, 1)]);
+    exchange.clock().advance(chrono::Duration::hours(2));
+    testing::run_bot_cycles(&bot, exchange.clock(), 1).await.unwrap();
+    let risk = bot.get_status().await.risk_metrics;
+    assert_eq!(risk.funding_paid, Decimal::from(3));
+    assert_eq!(risk.daily_funding_paid, Decimal::from(3));
+    assert_eq!(risk.total_pnl, Decimal::from(-3));
+}
+
+#[tokio::test]
+async fn drawdowns_come_from_the_exchange_portfolio_history() {
+    let exchange = exchange();
+    let bot = testing::mock_bot(config("100"), exchange.clone()).await.unwrap();
+    
+    // Nothing recorded and no history, so nothing to measure against
+    let risk = bot.get_status().await.risk_metrics;
+    assert_eq!((risk.current_drawdown, risk.max_drawdown), (Decimal::ZERO, Decimal::ZERO));
+    
+    let point = |hours: i64, account_value: i64| EquityPoint {
+        time: fixtures::start() + chrono::Duration::hours(hours),
+        account_value: Decimal::from(account_value),
+        pnl: Decimal::from(account_value - 10_000),
+    };
+    exchange.set_portfolio_history(vec![point(0, 10_000), point(1, 12_000), point(2, 9_000), point(3, 10_800)]);
+    testing::run_bot_cycles(&bot, exchange.clock(), 1).await.unwrap();
+    let risk = bot.get_status().await.risk_metrics;
+    assert_eq!(risk.max_drawdown, Decimal::from_str("0.25").unwrap());
+    assert_eq!(risk.current_drawdown, Decimal::from_str("0.1").unwrap());
+    
+    // Status reads what the loop measured, and the loop measures once a minute
+    let asked = |exchange: &MockTradingClient| {
+        exchange.calls().iter().filter(|record| record.call == Call::Portfolio).count()
+    };
+    bot.get_status().await;
+    bot.cached_snapshot().await;
+    assert_eq!(asked(&exchange), 1);
+    testing::run_bot_cycles(&bot, exchange.clock(), 11).await.unwrap();
+    assert_eq!(asked(&exchange), 1);
+    testing::run_bot_cycles(&bot, exchange.clock(), 1).await.unwrap();
+    assert_eq!(asked(&exchange), 2);
+}
+
+#[tokio::test]
+async fn order_fees_are_estimated_until_the_fills_are_known() {
+    let exchange = exchange();
+    exchange.set_fee_schedule(FeeSchedule {
+        maker_rate: Decimal::from_str("0.0001").unwrap(),
+        taker_rate: Decimal::from_str("0.0005").unwrap(),
+    });
+    let bot = testing::mock_bot(config("100"), exchange.clone()).await.unwrap();
+    
+    // $100 at the taker rate
+    testing::run_bot_cycles(&bot, exchange.clock(), 1).await.unwrap();
+    let risk = bot.get_status().await.risk_metrics;
+    assert_eq!(risk.fees_paid, Decimal::from_str("0.05").unwrap());
+    assert_eq!(risk.total_pnl, Decimal::from_str("-0.05").unwrap());
+    
+    let fills = exchange
+        .fills()
+        .into_iter()
+        .map(|fill| Trade {
+            fee: Decimal::from_str("0.01").unwrap(),
+            ..fill
+        })
+        .collect();
+    exchange.set_fills(fills);
+    testing::run_bot_cycles(&bot, exchange.clock(), 1).await.unwrap();
+    let risk = bot.get_status().await.risk_metrics;
+    assert_eq!(risk.fees_paid, Decimal::from_str("0.01").unwrap());
+    assert_eq!(risk.total_pnl, Decimal::from_str("-0.01").unwrap());
+}
+
+#[tokio::test(start_paused = true)]
+async fn exchange_rejections_count_unless_a_post_only_order_crossed() {
+    let crossed = exchange();
+    crossed.fail_next(Call::PlaceOrder, Error::PostOnlyWouldCross);
+    let bot = testing::mock_bot(config("100"), crossed.clone()).await.unwrap();
+    testing::run_bot_cycles(&bot, crossed.clock(), 1).await.unwrap();
+    assert_eq!(bot.get_status().await.failed_trades, 0);
+    
+    let short = exchange();
+    short.fail_next(Call::PlaceOrder, Error::InsufficientBalance);
+    let bot = testing::mock_bot(config("100"), short.clone()).await.unwrap();
+    testing::run_bot_cycles(&bot, short.clock(), 1).await.unwrap();
+    assert_eq!(bot.get_status().await.failed_trades, 1);
+}
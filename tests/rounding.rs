@@ -0,0 +1,103 @@
+use hyperliquid_trading_bot::{
+    utils::{
+        is_aligned, round_order_price, round_order_size, round_price, round_size, round_to_significant_figures,
+        RoundMode,
+    },
+    Error,
+};
+use rust_decimal::Decimal;
+use std::str::FromStr;
+
+fn dec(value: &str) -> Decimal {
+    Decimal::from_str(value).unwrap()
+}
+
+#[test]
+fn prices_round_to_a_multiple_of_the_tick_in_each_direction() {
+    let tick = dec("0.5");
+    
+    assert_eq!(round_price(dec("100.3"), tick, RoundMode::Down).unwrap(), dec("100"));
+    assert_eq!(round_price(dec("100.1"), tick, RoundMode::Up).unwrap(), dec("100.5"));
+    assert_eq!(round_price(dec("100.2"), tick, RoundMode::Nearest).unwrap(), dec("100"));
+    // Halfway goes up
+    assert_eq!(round_price(dec("100.25"), tick, RoundMode::Nearest).unwrap(), dec("100.5"));
+    
+    // Values already on the tick, and zero, are left alone
+    for mode in [RoundMode::Down, RoundMode::Up, RoundMode::Nearest] {
+        assert_eq!(round_price(dec("100.5"), tick, mode).unwrap(), dec("100.5"));
+        assert_eq!(round_price(Decimal::ZERO, tick, mode).unwrap(), Decimal::ZERO);
+    }
+}
+
+#[test]
+fn sizes_keep_full_precision_at_tiny_and_huge_steps() {
+    let lot = dec("0.00000001");
+    assert_eq!(round_size(dec("0.123456789"), lot, RoundMode::Down).unwrap(), dec("0.12345678"));
+    assert_eq!(round_size(dec("0.123456781"), lot, RoundMode::Up).unwrap(), dec("0.12345679"));
+    
+    // No float round trip to lose the low digits
+    let huge = dec("79228162514264.337593543950");
+    assert_eq!(round_size(huge, dec("0.01"), RoundMode::Down).unwrap(), dec("79228162514264.33"));
+    assert_eq!(round_size(dec("1234567"), dec("1000"), RoundMode::Nearest).unwrap(), dec("1235000"));
+}
+
+#[test]
+fn rounding_rejects_negative_values_and_steps_that_are_not_positive() {
+    for (value, step) in [("-1", "0.1"), ("1", "0"), ("1", "-0.1")] {
+        let error = round_price(dec(value), dec(step), RoundMode::Down).unwrap_err();
+        assert!(matches!(error, Error::InvalidInput(_)), "{} / {}: {}", value, step, error);
+        assert!(round_size(dec(value), dec(step), RoundMode::Up).is_err());
+    }
+    // Negative zero is still zero
+    assert_eq!(round_price(dec("-0"), dec("0.1"), RoundMode::Down).unwrap(), Decimal::ZERO);
+}
+
+#[test]
+fn alignment_is_checked_exactly() {
+    assert!(is_aligned(dec("100.5"), dec("0.5")));
+    assert!(is_aligned(dec("0.30"), dec("0.1")));
+    assert!(is_aligned(Decimal::ZERO, dec("0.1")));
+    assert!(!is_aligned(dec("100.25"), dec("0.5")));
+    assert!(!is_aligned(dec("0.300000001"), dec("0.1")));
+    assert!(!is_aligned(dec("1"), Decimal::ZERO));
+    assert!(!is_aligned(dec("1"), dec("-0.5")));
+}
+
+#[test]
+fn prices_keep_their_significant_figures_within_the_decimal_limit() {
+    let round = |price, decimals, mode| round_to_significant_figures(dec(price), 5, decimals, mode).unwrap();
+    
+    assert_eq!(round("1.23456", 6, RoundMode::Nearest), dec("1.2346"));
+    assert_eq!(round("1.23456", 6, RoundMode::Down), dec("1.2345"));
+    assert_eq!(round("1.23451", 6, RoundMode::Up), dec("1.2346"));
+    // Leading zeros aren't significant, but the decimal limit still applies
+    assert_eq!(round("0.000123456", 8, RoundMode::Nearest), dec("0.00012346"));
+    assert_eq!(round("0.000123456", 6, RoundMode::Nearest), dec("0.000123"));
+    // Integers are always valid, however many figures they have
+    assert_eq!(round("123456.7", 6, RoundMode::Nearest), dec("123457"));
+    assert_eq!(round("123456", 6, RoundMode::Down), dec("123456"));
+    // Rounding up across a power of ten
+    assert_eq!(round("9.99996", 6, RoundMode::Nearest), dec("10"));
+    assert_eq!(round("10", 6, RoundMode::Nearest), dec("10"));
+    assert_eq!(round("0", 6, RoundMode::Nearest), Decimal::ZERO);
+}
+
+#[test]
+fn significant_figures_reject_nonsense() {
+    assert!(round_to_significant_figures(dec("1.5"), 0, 6, RoundMode::Nearest).is_err());
+    assert!(round_to_significant_figures(dec("-1.5"), 5, 6, RoundMode::Nearest).is_err());
+}
+
+#[test]
+fn order_prices_and_sizes_follow_the_assets_size_decimals() {
+    // BTC trades in 5 size decimals, so prices get at most 1 decimal
+    assert_eq!(round_order_price(dec("43251.56"), 5).unwrap(), dec("43252"));
+    assert_eq!(round_order_price(dec("1234.567"), 5).unwrap(), dec("1234.6"));
+    assert_eq!(round_order_price(dec("0.0123456"), 0).unwrap(), dec("0.012346"));
+    // More size decimals than the price limit leaves integer prices only
+    assert_eq!(round_order_price(dec("12.345"), 7).unwrap(), dec("12"));
+    
+    assert_eq!(round_order_size(dec("1.23456789"), 3).unwrap(), dec("1.234"));
+    assert_eq!(round_order_size(dec("0.0009"), 3).unwrap(), Decimal::ZERO);
+    assert_eq!(round_order_size(dec("7.9"), 0).unwrap(), dec("7"));
+}
@@ -0,0 +1,121 @@
+use hyperliquid_trading_bot::{
+    error::{Error, Result},
+    runner::{self, RunMode, RunSummary},
+    utils::parse_duration,
+};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::watch;
+use tokio::time::{Duration, Instant};
+
+const PERIOD: Duration = Duration::from_secs(5);
+const RETRY_DELAY: Duration = Duration::from_secs(10);
+
+async fn run(mode: RunMode, shutdown: watch::Receiver<bool>, fail: bool) -> (RunSummary, u64) {
+    let calls = AtomicU64::new(0);
+    let summary = runner::run_cycles(mode, PERIOD, RETRY_DELAY, shutdown, || {
+        calls.fetch_add(1, Ordering::SeqCst);
+        async move {
+            if fail {
+                Err(Error::Api("exchange unavailable".to_string()))
+            } else {
+                Ok::<(), Error>(())
+            }
+        }
+    })
+    .await;
+    (summary, calls.load(Ordering::SeqCst))
+}
+
+#[tokio::test(start_paused = true)]
+async fn once_runs_exactly_one_cycle() {
+    let (_tx, rx) = watch::channel(false);
+    let started = Instant::now();
+    let (summary, calls) = run(RunMode::Once, rx, false).await;
+    
+    assert_eq!(calls, 1);
+    assert_eq!(summary, RunSummary { cycles: 1, failed_cycles: 0 });
+    // The first cycle starts immediately
+    assert_eq!(started.elapsed(), Duration::ZERO);
+}
+
+#[tokio::test(start_paused = true)]
+async fn once_reports_a_failed_cycle_without_retrying() {
+    let (_tx, rx) = watch::channel(false);
+    let started = Instant::now();
+    let (summary, calls) = run(RunMode::Once, rx, true).await;
+    
+    assert_eq!(calls, 1);
+    assert_eq!(summary, RunSummary { cycles: 1, failed_cycles: 1 });
+    assert_eq!(started.elapsed(), Duration::ZERO);
+}
+
+#[tokio::test(start_paused = true)]
+async fn run_for_stops_when_time_is_up() {
+    let (_tx, rx) = watch::channel(false);
+    let started = Instant::now();
+    let (summary, _) = run(RunMode::For(Duration::from_secs(2 * 3600)), rx, false).await;
+    
+    assert_eq!(started.elapsed(), Duration::from_secs(2 * 3600));
+    // One cycle every 5 seconds from t=0 up to, not including, the deadline
+    assert_eq!(summary, RunSummary { cycles: 1440, failed_cycles: 0 });
+}
+
+#[tokio::test(start_paused = true)]
+async fn run_for_waits_out_retry_delay_after_failures() {
+    let (_tx, rx) = watch::channel(false);
+    let (summary, _) = run(RunMode::For(Duration::from_secs(60)), rx, true).await;
+    
+    // Failures at t=0, 10, 20, 30, 40, 50; the retry wait at t=60 ends with the run
+    assert_eq!(summary, RunSummary { cycles: 6, failed_cycles: 6 });
+}
+
+#[tokio::test(start_paused = true)]
+async fn shutdown_ends_a_continuous_run_between_cycles() {
+    let (tx, rx) = watch::channel(false);
+    let calls = Arc::new(AtomicU64::new(0));
+    
+    let handle = {
+        let calls = calls.clone();
+        tokio::spawn(async move {
+            runner::run_cycles(RunMode::Continuous, PERIOD, RETRY_DELAY, rx, move || {
+                calls.fetch_add(1, Ordering::SeqCst);
+                async { Ok::<(), Error>(()) }
+            })
+            .await
+        })
+    };
+    
+    tokio::time::sleep(Duration::from_secs(12)).await;
+    let stopped_at = Instant::now();
+    tx.send_replace(true);
+    let summary = handle.await.unwrap();
+    
+    // Cycles at t=0, 5 and 10, and no waiting for the next tick
+    assert_eq!(summary.cycles, 3);
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+    assert_eq!(stopped_at.elapsed(), Duration::ZERO);
+}
+
+#[tokio::test(start_paused = true)]
+async fn shutdown_requested_before_start_runs_nothing() {
+    let (tx, rx) = watch::channel(false);
+    tx.send_replace(true);
+    let (summary, calls) = run(RunMode::Continuous, rx, false).await;
+    
+    assert_eq!(calls, 0);
+    assert_eq!(summary, RunSummary::default());
+}
+
+#[test]
+fn parses_run_for_durations() -> Result<()> {
+    assert_eq!(parse_duration("90s")?, Duration::from_secs(90));
+    assert_eq!(parse_duration("30m")?, Duration::from_secs(1800));
+    assert_eq!(parse_duration("2h")?, Duration::from_secs(7200));
+    assert_eq!(parse_duration("1d")?, Duration::from_secs(86400));
+    
+    for invalid in ["", "2", "h", "0h", "2w", "1.5h", "-1h"] {
+        assert!(parse_duration(invalid).is_err(), "{} should be rejected", invalid);
+    }
+    Ok(())
+}
@@ -0,0 +1,131 @@
+use chrono::{DateTime, NaiveTime, TimeZone, Utc, Weekday};
+use hyperliquid_trading_bot::{
+    config::{BlackoutWindow, MaintenanceWindow, ScheduleConfig},
+    utils::MarketSchedule,
+};
+
+/// A time in the week of Monday 2024-01-01
+fn at(day: u32, hour: u32, minute: u32, second: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 1, day, hour, minute, second).unwrap()
+}
+
+fn time(hour: u32, minute: u32) -> NaiveTime {
+    NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+}
+
+fn schedule(config: ScheduleConfig) -> MarketSchedule {
+    MarketSchedule::new(config)
+}
+
+#[test]
+fn funding_is_the_next_interval_boundary_strictly_after_now() {
+    let hourly = MarketSchedule::default();
+    assert_eq!(hourly.next_funding(at(1, 0, 0, 0)), at(1, 1, 0, 0));
+    assert_eq!(hourly.next_funding(at(1, 0, 59, 59)), at(1, 1, 0, 0));
+    assert_eq!(hourly.time_to_next_funding(at(1, 0, 59, 59)), chrono::Duration::seconds(1));
+    
+    let eight_hourly = schedule(ScheduleConfig {
+        funding_interval_hours: 8,
+        ..ScheduleConfig::default()
+    });
+    assert_eq!(eight_hourly.next_funding(at(1, 7, 59, 0)), at(1, 8, 0, 0));
+    assert_eq!(eight_hourly.next_funding(at(1, 16, 0, 0)), at(2, 0, 0, 0));
+    assert_eq!(eight_hourly.next_funding(at(1, 23, 30, 0)), at(2, 0, 0, 0));
+    
+    // A zero interval is treated as hourly rather than dividing by zero
+    let zero = schedule(ScheduleConfig {
+        funding_interval_hours: 0,
+        ..ScheduleConfig::default()
+    });
+    assert_eq!(zero.next_funding(at(1, 3, 10, 0)), at(1, 4, 0, 0));
+}
+
+#[test]
+fn the_funding_blackout_covers_both_sides_of_funding() {
+    let schedule = schedule(ScheduleConfig {
+        funding_blackout_secs: 60,
+        ..ScheduleConfig::default()
+    });
+    
+    assert!(schedule.is_tradable(at(1, 0, 58, 59)));
+    assert!(!schedule.is_tradable(at(1, 0, 59, 0)));
+    assert!(!schedule.is_tradable(at(1, 1, 0, 0)));
+    assert!(!schedule.is_tradable(at(1, 1, 0, 59)));
+    assert!(schedule.is_tradable(at(1, 1, 1, 0)));
+    assert_eq!(
+        schedule.blocked_reason(at(1, 0, 59, 30)).unwrap(),
+        "within 60s of funding at 2024-01-01 01:00:00 UTC"
+    );
+    
+    // Without a blackout, funding doesn't pause trading
+    assert!(MarketSchedule::default().is_tradable(at(1, 1, 0, 0)));
+}
+
+#[test]
+fn maintenance_includes_its_start_and_excludes_its_end() {
+    let schedule = schedule(ScheduleConfig {
+        maintenance_windows: vec![MaintenanceWindow {
+            start: at(2, 6, 0, 0),
+            end: at(2, 7, 30, 0),
+        }],
+        ..ScheduleConfig::default()
+    });
+    
+    assert!(schedule.is_tradable(at(2, 5, 59, 59)));
+    assert_eq!(
+        schedule.blocked_reason(at(2, 6, 0, 0)).unwrap(),
+        "exchange maintenance until 2024-01-02 07:30:00 UTC"
+    );
+    assert!(!schedule.is_tradable(at(2, 7, 29, 59)));
+    assert!(schedule.is_tradable(at(2, 7, 30, 0)));
+}
+
+#[test]
+fn blackout_windows_apply_on_their_days() {
+    let schedule = schedule(ScheduleConfig {
+        blackout_windows: vec![BlackoutWindow {
+            start: time(9, 0),
+            end: time(10, 0),
+            days: vec![Weekday::Mon],
+        }],
+        ..ScheduleConfig::default()
+    });
+    
+    assert_eq!(schedule.blocked_reason(at(1, 9, 0, 0)).unwrap(), "blackout window 09:00:00-10:00:00 UTC");
+    assert!(!schedule.is_tradable(at(1, 9, 59, 59)));
+    assert!(schedule.is_tradable(at(1, 10, 0, 0)));
+    assert!(schedule.is_tradable(at(1, 8, 59, 59)));
+    assert!(schedule.is_tradable(at(2, 9, 30, 0)));
+}
+
+#[test]
+fn blackout_windows_wrap_past_midnight_from_their_start_day() {
+    // Friday night into Saturday morning
+    let friday_nights = schedule(ScheduleConfig {
+        blackout_windows: vec![BlackoutWindow {
+            start: time(22, 0),
+            end: time(2, 0),
+            days: vec![Weekday::Fri],
+        }],
+        ..ScheduleConfig::default()
+    });
+    
+    assert!(friday_nights.is_tradable(at(5, 21, 59, 59)));
+    assert!(!friday_nights.is_tradable(at(5, 22, 0, 0)));
+    assert!(!friday_nights.is_tradable(at(6, 1, 59, 59)));
+    assert!(friday_nights.is_tradable(at(6, 2, 0, 0)));
+    // The early hours of Friday belong to Thursday's window, which isn't set
+    assert!(friday_nights.is_tradable(at(5, 1, 0, 0)));
+    assert!(friday_nights.is_tradable(at(6, 23, 0, 0)));
+    
+    let every_day = schedule(ScheduleConfig {
+        blackout_windows: vec![BlackoutWindow {
+            start: time(22, 0),
+            end: time(2, 0),
+            days: Vec::new(),
+        }],
+        ..ScheduleConfig::default()
+    });
+    assert!(!every_day.is_tradable(at(1, 0, 30, 0)));
+    assert!(!every_day.is_tradable(at(3, 23, 0, 0)));
+}
@@ -0,0 +1,96 @@
+use hyperliquid_trading_bot::{
+    config::Config,
+    testing::{self, fixtures, Call, MockTradingClient},
+    Error,
+};
+use rust_decimal::Decimal;
+use std::sync::Arc;
+
+fn config(dry_run: bool) -> Config {
+    let mut config = Config::default();
+    config.trading.dry_run = dry_run;
+    config.shutdown.cancel_open_orders = false;
+    config
+}
+
+fn seconds(secs: i64) -> chrono::Duration {
+    chrono::Duration::seconds(secs)
+}
+
+fn schedules(exchange: &MockTradingClient) -> usize {
+    exchange.calls().iter().filter(|record| record.call == Call::ScheduleCancel).count()
+}
+
+#[tokio::test]
+async fn cycles_keep_pushing_the_cancel_deadline_back() {
+    let exchange = Arc::new(MockTradingClient::new(Decimal::from(10_000)));
+    let bot = testing::mock_bot(config(false), exchange.clone()).await.unwrap();
+    
+    testing::run_bot_cycles(&bot, exchange.clock(), 1).await.unwrap();
+    assert_eq!(exchange.scheduled_cancel(), Some(fixtures::start() + seconds(60)));
+    
+    // Pushed back every 10s, not every cycle
+    testing::run_bot_cycles(&bot, exchange.clock(), 2).await.unwrap();
+    assert_eq!(exchange.scheduled_cancel(), Some(fixtures::start() + seconds(70)));
+    assert_eq!(schedules(&exchange), 2);
+}
+
+#[tokio::test]
+async fn failing_cycles_leave_the_deadline_to_run_out() {
+    let exchange = Arc::new(MockTradingClient::new(Decimal::from(10_000)));
+    exchange.fail_next(Call::ScheduleCancel, Error::Api("unavailable".to_string()));
+    let bot = testing::mock_bot(config(false), exchange.clone()).await.unwrap();
+    
+    // A schedule that didn't take is tried again next cycle
+    testing::run_bot_cycles(&bot, exchange.clock(), 1).await.unwrap();
+    assert_eq!(exchange.scheduled_cancel(), None);
+    testing::run_bot_cycles(&bot, exchange.clock(), 1).await.unwrap();
+    assert_eq!(exchange.scheduled_cancel(), Some(fixtures::start() + seconds(65)));
+    
+    exchange.clock().advance(seconds(20));
+    exchange.fail_next(Call::AccountInfo, Error::Api("unavailable".to_string()));
+    assert!(bot.run_cycle().await.is_err());
+    assert_eq!(exchange.scheduled_cancel(), Some(fixtures::start() + seconds(65)));
+    
+    bot.run_cycle().await.unwrap();
+    assert_eq!(exchange.scheduled_cancel(), Some(fixtures::start() + seconds(90)));
+}
+
+#[tokio::test]
+async fn a_clean_stop_clears_the_schedule_only_when_orders_stay() {
+    let exchange = Arc::new(MockTradingClient::new(Decimal::from(10_000)));
+    let bot = testing::mock_bot(config(false), exchange.clone()).await.unwrap();
+    testing::run_bot_cycles(&bot, exchange.clock(), 1).await.unwrap();
+    
+    let report = bot.stop().await;
+    assert!(report.is_clean(), "{:?}", report);
+    assert!(report.completed.contains(&"clear scheduled cancel"));
+    assert_eq!(exchange.scheduled_cancel(), None);
+    
+    // Cancelling on the way out keeps the schedule, in case the cancels fail
+    let exchange = Arc::new(MockTradingClient::new(Decimal::from(10_000)));
+    let mut cancelling = config(false);
+    cancelling.shutdown.cancel_open_orders = true;
+    let bot = testing::mock_bot(cancelling, exchange.clone()).await.unwrap();
+    testing::run_bot_cycles(&bot, exchange.clock(), 1).await.unwrap();
+    
+    let report = bot.stop().await;
+    assert!(!report.completed.contains(&"clear scheduled cancel"));
+    assert_eq!(exchange.scheduled_cancel(), Some(fixtures::start() + seconds(60)));
+}
+
+#[tokio::test]
+async fn nothing_is_scheduled_on_dry_runs_or_when_turned_off() {
+    let exchange = Arc::new(MockTradingClient::new(Decimal::from(10_000)));
+    let bot = testing::mock_bot(config(true), exchange.clone()).await.unwrap();
+    testing::run_bot_cycles(&bot, exchange.clock(), 3).await.unwrap();
+    assert_eq!(schedules(&exchange), 0);
+    
+    let exchange = Arc::new(MockTradingClient::new(Decimal::from(10_000)));
+    let mut off = config(false);
+    off.trading.cancel_deadline_secs = 0;
+    let bot = testing::mock_bot(off, exchange.clone()).await.unwrap();
+    testing::run_bot_cycles(&bot, exchange.clock(), 3).await.unwrap();
+    assert_eq!(schedules(&exchange), 0);
+    assert!(!bot.shutdown_steps().iter().any(|step| step.name == "clear scheduled cancel"));
+}
@@ -0,0 +1,148 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use hyperliquid_trading_bot::{
+    config::{Config, SchedulerConfig},
+    models::{MarketData, StrategySignal},
+    scheduler::EvalScheduler,
+    strategies::Strategy,
+    testing::{self, fixtures, MockTradingClient},
+    Result,
+};
+use rust_decimal::Decimal;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn scheduler(idle_eval_secs: u64, mid_move_bps: i64) -> EvalScheduler {
+    EvalScheduler::new(&SchedulerConfig {
+        account_interval_secs: 5,
+        idle_eval_secs,
+        mid_move_bps: Decimal::from(mid_move_bps),
+    })
+}
+
+fn at(secs: i64) -> DateTime<Utc> {
+    fixtures::start() + chrono::Duration::seconds(secs)
+}
+
+#[test]
+fn strategies_are_due_straight_away_then_once_idle() {
+    let scheduler = scheduler(30, 0);
+    scheduler.add("dca", Duration::from_secs(3600));
+    scheduler.add("grid", Duration::ZERO);
+    
+    assert_eq!(scheduler.due(at(0)), vec!["dca", "grid"]);
+    assert!(scheduler.due(at(29)).is_empty());
+    assert_eq!(scheduler.next_due(), Some(at(30)));
+    assert_eq!(scheduler.due(at(30)), vec!["grid"]);
+    
+    // A minimum interval longer than the idle time holds the strategy back
+    assert_eq!(scheduler.due(at(3600)), vec!["dca", "grid"]);
+}
+
+#[test]
+fn triggers_evaluate_once_the_minimum_interval_has_passed() {
+    let scheduler = scheduler(300, 0);
+    scheduler.add("momentum", Duration::from_secs(10));
+    scheduler.due(at(0));
+    
+    scheduler.trigger("momentum");
+    assert!(scheduler.due(at(5)).is_empty());
+    assert_eq!(scheduler.next_due(), Some(at(10)));
+    assert_eq!(scheduler.due(at(10)), vec!["momentum"]);
+    
+    // Taking it clears the trigger
+    assert!(scheduler.due(at(25)).is_empty());
+}
+
+#[test]
+fn mids_trigger_once_they_move_far_enough() {
+    let scheduler = scheduler(300, 10);
+    scheduler.add("momentum", Duration::ZERO);
+    scheduler.due(at(0));
+    
+    // Measured from the first mid after the evaluation
+    assert!(!scheduler.mid("momentum", Decimal::from(100)));
+    assert!(!scheduler.mid("momentum", "100.09".parse().unwrap()));
+    assert!(scheduler.mid("momentum", "99.9".parse().unwrap()));
+    assert_eq!(scheduler.due(at(1)), vec!["momentum"]);
+    
+    // 0 bps ignores mids
+    let ignoring = self::scheduler(300, 0);
+    ignoring.add("momentum", Duration::ZERO);
+    ignoring.due(at(0));
+    ignoring.mid("momentum", Decimal::from(100));
+    assert!(!ignoring.mid("momentum", Decimal::from(200)));
+}
+
+#[test]
+fn cycles_outside_the_loop_only_wait_out_minimum_intervals() {
+    let scheduler = scheduler(300, 0);
+    scheduler.add("dca", Duration::from_secs(60));
+    scheduler.add("grid", Duration::ZERO);
+    
+    assert_eq!(scheduler.allowed(at(0)), vec!["dca", "grid"]);
+    assert_eq!(scheduler.allowed(at(5)), vec!["grid"]);
+    assert_eq!(scheduler.allowed(at(60)), vec!["dca", "grid"]);
+}
+
+/// Counts its analyses and never signals
+struct Counting {
+    analyses: Arc<AtomicU64>,
+}
+
+#[async_trait]
+impl Strategy for Counting {
+    fn name(&self) -> &str {
+        "counting"
+    }
+    
+    fn symbol(&self) -> &str {
+        "BTC"
+    }
+    
+    fn is_enabled(&self) -> bool {
+        true
+    }
+    
+    async fn analyze(&mut self, _market_data: &MarketData) -> Result<Option<StrategySignal>> {
+        self.analyses.fetch_add(1, Ordering::SeqCst);
+        Ok(None)
+    }
+    
+    async fn update_parameters(&mut self, _parameters: HashMap<String, serde_json::Value>) -> Result<()> {
+        Ok(())
+    }
+    
+    fn get_parameters(&self) -> HashMap<String, serde_json::Value> {
+        HashMap::new()
+    }
+    
+    fn validate_parameters(&self, _parameters: &HashMap<String, serde_json::Value>) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn cycles_evaluate_a_strategy_no_more_often_than_its_interval() {
+    let exchange = Arc::new(MockTradingClient::new(Decimal::from(10_000)));
+    exchange.set_price("BTC", Decimal::from(100));
+    let mut config = Config::default();
+    let mut strategy = fixtures::strategy("custom", "BTC", json!({}));
+    strategy.eval_interval_secs = Some(60);
+    config.strategies.insert("counting".to_string(), strategy);
+    let analyses = Arc::new(AtomicU64::new(0));
+    let bot = testing::mock_bot(config, exchange.clone())
+        .await
+        .unwrap()
+        .with_strategy(Box::new(Counting { analyses: analyses.clone() }));
+    
+    // A minute of cycles, each checking the account
+    testing::run_bot_cycles(&bot, exchange.clock(), 12).await.unwrap();
+    assert_eq!(analyses.load(Ordering::SeqCst), 1);
+    
+    testing::run_bot_cycles(&bot, exchange.clock(), 1).await.unwrap();
+    assert_eq!(analyses.load(Ordering::SeqCst), 2);
+}
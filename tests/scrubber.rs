@@ -0,0 +1,77 @@
+use hyperliquid_trading_bot::{
+    config::{Config, TelegramConfig},
+    notifications::Severity,
+    reporting::Scrubber,
+};
+
+const KEY: &str = "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+
+#[test]
+fn configured_secrets_are_redacted_wherever_they_appear() {
+    let mut config = Config::default();
+    config.hyperliquid.api_key = "hl-api-key-0001".to_string();
+    config.notifications.telegram = Some(TelegramConfig {
+        enabled: true,
+        bot_token: "123456:telegram-bot-token".to_string(),
+        chat_id: "1".to_string(),
+        api_url: "https://api.telegram.org".to_string(),
+        min_severity: Severity::Info,
+        events: Vec::new(),
+        max_retries: 0,
+    });
+    let scrubber = Scrubber::from_config(&config);
+    
+    assert_eq!(
+        scrubber.scrub("POST https://api.telegram.org/bot123456:telegram-bot-token/sendMessage failed (hl-api-key-0001)"),
+        "POST https://api.telegram.org/bot[REDACTED]/sendMessage failed ([REDACTED])"
+    );
+}
+
+#[test]
+fn short_secrets_are_not_redacted() {
+    // They'd match ordinary text
+    let scrubber = Scrubber::new(vec!["BTC".to_string(), String::new(), "1234567".to_string()]);
+    assert_eq!(scrubber.scrub("BTC order 1234567 placed"), "BTC order 1234567 placed");
+}
+
+#[test]
+fn raw_keys_are_redacted_but_shorter_hex_is_kept() {
+    let scrubber = Scrubber::default();
+    
+    assert_eq!(scrubber.scrub(&format!("loaded 0x{} ok", KEY)), "loaded [REDACTED] ok");
+    assert_eq!(scrubber.scrub(&format!("loaded {}", KEY.to_uppercase())), "loaded [REDACTED]");
+    // A signature is longer than a key, and goes in one piece
+    assert_eq!(scrubber.scrub(&format!("sig {}{}1b", KEY, KEY)), "sig [REDACTED]");
+    
+    // Addresses, hashes shorter than a key and cloids stay readable
+    let address = "0x8ba1f109551bd432803012645ac136ddd64dba72";
+    let short = &KEY[..63];
+    let cloid = "0x00000000000000000000000000000001";
+    for text in [address, short, cloid] {
+        assert_eq!(scrubber.scrub(text), text);
+    }
+}
+
+#[test]
+fn values_after_secret_key_names_are_redacted() {
+    let scrubber = Scrubber::default();
+    let cases = [
+        ("private_key=hunter2 loaded", "private_key=[REDACTED] loaded"),
+        ("API_KEY: abc123, retrying", "API_KEY: [REDACTED], retrying"),
+        (r#"{"secret":"s3cr3t","symbol":"BTC"}"#, r#"{"secret":"[REDACTED]","symbol":"BTC"}"#),
+        (r#"{"password": "pw", "user": "me"}"#, r#"{"password": "[REDACTED]", "user": "me"}"#),
+        ("GET /hook?bot_token=abc&chat=1", "GET /hook?bot_token=[REDACTED]&chat=1"),
+        ("token='quoted';", "token='[REDACTED]';"),
+    ];
+    for (text, scrubbed) in cases {
+        assert_eq!(scrubber.scrub(text), scrubbed, "{}", text);
+    }
+}
+
+#[test]
+fn key_names_without_a_value_are_left_alone() {
+    let scrubber = Scrubber::default();
+    for text in ["the token expired", "password=", "secret ", "rotate the api_key"] {
+        assert_eq!(scrubber.scrub(text), text);
+    }
+}
@@ -0,0 +1,171 @@
+use futures_util::FutureExt;
+use hyperliquid_trading_bot::{
+    config::Config,
+    error::Error,
+    shutdown::{self, ShutdownStep},
+    testing::fixtures,
+    trading_bot::TradingBot,
+};
+use serde_json::json;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use wiremock::matchers::{body_partial_json, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const PRIVATE_KEY: &str = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+const WALLET: &str = "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266";
+
+fn live_config(server: &MockServer, dir: &Path) -> Config {
+    let mut config = Config::default();
+    config.hyperliquid.base_url = server.uri();
+    config.hyperliquid.private_key = PRIVATE_KEY.to_string();
+    config.trading.dry_run = false;
+    config.shutdown.cancel_open_orders = true;
+    config.storage.enabled = true;
+    config.storage.path = dir.join("bot.db").display().to_string();
+    config.recorder.enabled = true;
+    config.recorder.directory = dir.join("recordings").display().to_string();
+    config
+}
+
+async fn mock_open_orders(server: &MockServer) {
+    Mock::given(method("POST"))
+        .and(path("/info"))
+        .and(body_partial_json(json!({ "type": "openOrders", "user": WALLET })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            { "coin": "BTC", "side": "B", "limitPx": "40000", "sz": "0.1", "oid": 42, "timestamp": 1704067200000u64 }
+        ])))
+        .expect(1)
+        .mount(server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/info"))
+        .and(body_partial_json(json!({ "type": "meta" })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "universe": [{ "name": "BTC", "szDecimals": 5, "maxLeverage": 50 }]
+        })))
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn stop_runs_steps_in_order() {
+    let server = MockServer::start().await;
+    mock_open_orders(&server).await;
+    Mock::given(method("POST"))
+        .and(path("/exchange"))
+        .and(body_partial_json(json!({ "action": { "type": "cancel" } })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "status": "ok",
+            "response": { "type": "cancel", "data": { "statuses": ["success"] } }
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    
+    let dir = fixtures::temp_dir();
+    let bot = TradingBot::new(live_config(&server, &dir)).await.unwrap();
+    let report = bot.stop().await;
+    
+    assert!(report.is_clean(), "{:?}", report);
+    assert_eq!(
+        report.completed,
+        vec!["trading loop", "cancel open orders", "disconnect websocket", "flush storage", "flush market recorder"]
+    );
+    
+    // Orders, then the asset they trade, are looked up before being cancelled
+    let paths: Vec<String> = server.received_requests().await.unwrap().iter().map(|r| r.url.path().to_string()).collect();
+    assert_eq!(paths, vec!["/info", "/info", "/exchange"]);
+    
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[tokio::test]
+async fn failed_cancel_does_not_stop_later_steps() {
+    let server = MockServer::start().await;
+    mock_open_orders(&server).await;
+    Mock::given(method("POST"))
+        .and(path("/exchange"))
+        .respond_with(ResponseTemplate::new(500).set_body_string("unavailable"))
+        .mount(&server)
+        .await;
+    
+    let dir = fixtures::temp_dir();
+    let bot = TradingBot::new(live_config(&server, &dir)).await.unwrap();
+    let report = bot.stop().await;
+    
+    assert_eq!(report.failed.len(), 1);
+    assert_eq!(report.failed[0].0, "cancel open orders");
+    assert_eq!(report.completed, vec!["trading loop", "disconnect websocket", "flush storage", "flush market recorder"]);
+    assert!(report.incomplete.is_empty());
+    
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[tokio::test]
+async fn dry_run_never_cancels_orders() {
+    let mut config = Config::default();
+    // A dry run only reads the account, so an address will do for a key
+    config.hyperliquid.account_address = Some(WALLET.to_string());
+    config.shutdown.cancel_open_orders = true;
+    let bot = TradingBot::new(config).await.unwrap();
+    
+    let steps: Vec<&str> = bot.shutdown_steps().iter().map(|step| step.name).collect();
+    assert_eq!(steps, vec!["trading loop", "disconnect websocket"]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn deadline_cuts_off_remaining_steps() {
+    let started: Arc<Mutex<Vec<&'static str>>> = Arc::default();
+    let step = |name: &'static str, outcome: Option<bool>| {
+        let started = started.clone();
+        ShutdownStep::new(
+            name,
+            async move {
+                started.lock().unwrap().push(name);
+                match outcome {
+                    Some(true) => Ok(()),
+                    Some(false) => Err(Error::Trading("cancel rejected".to_string())),
+                    // Hangs, like a stuck API call
+                    None => std::future::pending().await,
+                }
+            }
+            .boxed(),
+        )
+    };
+    let steps = vec![
+        step("trading loop", Some(true)),
+        step("cancel open orders", Some(false)),
+        step("disconnect websocket", None),
+        step("flush storage", Some(true)),
+    ];
+    
+    let begin = tokio::time::Instant::now();
+    let report = shutdown::run_steps(steps, Duration::from_secs(25)).await;
+    
+    assert_eq!(begin.elapsed(), Duration::from_secs(25));
+    assert_eq!(report.completed, vec!["trading loop"]);
+    assert_eq!(report.failed, vec![("cancel open orders", "Trading error: cancel rejected".to_string())]);
+    assert_eq!(report.incomplete, vec!["disconnect websocket", "flush storage"]);
+    assert!(!report.is_clean());
+    // Nothing runs after the deadline
+    assert_eq!(*started.lock().unwrap(), vec!["trading loop", "cancel open orders", "disconnect websocket"]);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn receives_sigterm_and_sigint() {
+    let mut signal = shutdown::ShutdownSignal::new().unwrap();
+    
+    for (flag, expected) in [("-TERM", "SIGTERM"), ("-INT", "SIGINT")] {
+        let status = std::process::Command::new("kill")
+            .args([flag, &std::process::id().to_string()])
+            .status()
+            .unwrap();
+        assert!(status.success());
+        
+        let received = tokio::time::timeout(Duration::from_secs(5), signal.recv()).await.unwrap();
+        assert_eq!(received, expected);
+    }
+}
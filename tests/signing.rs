@@ -0,0 +1,147 @@
+// Exchange action signing against the official Python SDK's own signing
+// test vectors (tests/signing_test.py in hyperliquid-python-sdk), which use
+// the key below. signed_order.json is the request body the SDK builds for
+// its order vector on mainnet.
+
+use chrono::Utc;
+use hyperliquid_trading_bot::{
+    api::{signing, types::Action, wallet, HyperliquidClient},
+    models::{Order, OrderSide, OrderStatus, OrderType},
+};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use serde_json::{json, Value};
+use wiremock::matchers::{body_partial_json, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const SDK_KEY: &str = "0x0123456789012345678901234567890123456789012345678901234567890123";
+
+fn fixture(name: &str) -> Value {
+    let raw = std::fs::read_to_string(format!("tests/fixtures/hyperliquid/{}.json", name)).unwrap();
+    serde_json::from_str(&raw).unwrap()
+}
+
+/// The SDK's `{"type": "dummy", "num": float_to_int_for_hashing(1000)}`
+#[derive(Serialize)]
+struct Dummy {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    num: u64,
+}
+
+const DUMMY: Dummy = Dummy { kind: "dummy", num: 100_000_000_000 };
+
+fn sig(r: &str, s: &str, v: u8) -> signing::Signature {
+    signing::Signature {
+        r: r.to_string(),
+        s: s.to_string(),
+        v,
+    }
+}
+
+#[test]
+fn l1_action_signatures_match_the_sdk() {
+    assert_eq!(
+        signing::sign_l1_action(SDK_KEY, &DUMMY, None, 0, true).unwrap(),
+        sig(
+            // The SDK writes r and s without leading zeros
+            "0x53749d5b30552aeb2fca34b530185976545bb22d0b3ce6f62e31be961a59298",
+            "0x755c40ba9bf05223521753995abb2f73ab3229be8ec921f350cb447e384d8ed8",
+            27
+        )
+    );
+    assert_eq!(
+        signing::sign_l1_action(SDK_KEY, &DUMMY, None, 0, false).unwrap(),
+        sig(
+            "0x542af61ef1f429707e3c76c5293c80d01f74ef853e34b76efffcb57e574f9510",
+            "0x17b8b32f086e8cdede991f1e2c529f5dd5297cbe8128500e00cbaf766204a613",
+            28
+        )
+    );
+}
+
+#[test]
+fn signatures_recover_to_the_signing_wallet() {
+    let signature = signing::sign_l1_action(SDK_KEY, &DUMMY, None, 0, true).unwrap();
+    let digest = signing::agent_digest("a", &signing::action_hash(&DUMMY, None, 0).unwrap());
+    assert_eq!(
+        signing::recover_signer(&digest, &signature).unwrap(),
+        wallet::address_from_private_key(SDK_KEY).unwrap()
+    );
+    
+    // Signed for the other network, it recovers to someone else
+    let testnet = signing::sign_l1_action(SDK_KEY, &DUMMY, None, 0, false).unwrap();
+    assert_ne!(
+        signing::recover_signer(&digest, &testnet).unwrap(),
+        wallet::address_from_private_key(SDK_KEY).unwrap()
+    );
+}
+
+fn order(symbol: &str, quantity: &str, price: &str, order_type: OrderType) -> Order {
+    Order {
+        id: "local".to_string(),
+        symbol: symbol.to_string(),
+        side: OrderSide::Buy,
+        order_type,
+        quantity: quantity.parse().unwrap(),
+        price: Some(price.parse().unwrap()),
+        status: OrderStatus::Pending,
+        created_at: Utc::now(),
+        updated_at: None,
+        filled_quantity: Decimal::ZERO,
+        average_price: None,
+        reduce_only: false,
+        trigger_price: None,
+        is_take_profit: false,
+        cloid: None,
+    }
+}
+
+async fn sdk_client(testnet: bool) -> (MockServer, HyperliquidClient) {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/info"))
+        .and(body_partial_json(json!({ "type": "meta" })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(fixture("meta")))
+        .mount(&server)
+        .await;
+    let client = HyperliquidClient::new(server.uri(), String::new(), SDK_KEY.to_string(), testnet);
+    (server, client)
+}
+
+#[tokio::test]
+async fn order_payload_matches_the_sdk() {
+    let (_server, client) = sdk_client(false).await;
+    
+    // ETH is asset 1, as in the SDK's vector
+    let action = client.order_action(&order("ETH", "100", "100", OrderType::Limit)).await.unwrap();
+    assert_eq!(client.signed_action(&action, 0).unwrap(), fixture("signed_order"));
+    
+    let (_server, testnet) = sdk_client(true).await;
+    let action = testnet.order_action(&order("ETH", "100", "100", OrderType::Limit)).await.unwrap();
+    assert_eq!(
+        testnet.signed_action(&action, 0).unwrap()["signature"],
+        json!({
+            "r": "0x82b2ba28e76b3d761093aaded1b1cdad4960b3af30212b343fb2e6cdfa4e3d54",
+            "s": "0x6b53878fc99d26047f4d7e8c90eb98955a109f44209163f52d8dc4278cbbd9f5",
+            "v": 27
+        })
+    );
+}
+
+#[tokio::test]
+async fn action_hash_matches_a_production_order() {
+    let (_server, client) = sdk_client(false).await;
+    
+    // The SDK's phantom agent check: an IOC buy of 0.0147 @ 1670.1 of asset 4.
+    // Trailing zeros don't change what's hashed.
+    let mut action = client.order_action(&order("ETH", "0.01470", "1670.10", OrderType::Market)).await.unwrap();
+    let Action::Order { orders, .. } = &mut action else {
+        unreachable!()
+    };
+    orders[0].a = 4;
+    assert_eq!(
+        hex::encode(signing::action_hash(&action, None, 1677777606040).unwrap()),
+        "0fcbeda5ae3c4950a548021552a4fea2226858c4453571bf3f24ba017eac2908"
+    );
+}
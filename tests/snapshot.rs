@@ -0,0 +1,127 @@
+use chrono::{Duration, TimeZone, Utc};
+use hyperliquid_trading_bot::{
+    clock::{Clock, SimulatedClock},
+    config::Config,
+    costs::CostModel,
+    market_stream::MarketEvent,
+    models::MarketData,
+    simulated_exchange::SimulatedExchange,
+    snapshot::{Snapshot, SNAPSHOT_VERSION},
+    storage::SqliteStorage,
+    strategies::{DCAStrategy, Strategy},
+    testing::fixtures,
+    trading_bot::TradingBot,
+};
+use rust_decimal::Decimal;
+use serde_json::json;
+use std::sync::Arc;
+
+fn ticker(price: i64, clock: &SimulatedClock) -> MarketData {
+    let price = Decimal::from(price);
+    MarketData {
+        symbol: "BTC".to_string(),
+        price,
+        volume_24h: Decimal::from(1000),
+        change_24h: Decimal::ZERO,
+        high_24h: price,
+        low_24h: price,
+        timestamp: clock.now(),
+    }
+}
+
+/// Buys 100 of BTC a day, for real
+fn daily_dca() -> Config {
+    fixtures::live_dca_config(json!({ "investment_amount": "100", "interval_hours": 24 }))
+}
+
+/// A bot trading DCA against its own simulated exchange
+async fn bot(config: Config, clock: &SimulatedClock) -> TradingBot {
+    let exchange = Arc::new(SimulatedExchange::new(Decimal::from(10_000), CostModel::default()));
+    exchange.update(&MarketEvent::Ticker(ticker(40_000, clock)));
+    TradingBot::with_client(config, exchange, Arc::new(clock.clone())).await.unwrap()
+}
+
+#[tokio::test]
+async fn snapshots_round_trip_through_json_a_bot_and_storage() {
+    let clock = SimulatedClock::new(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap());
+    let original = bot(daily_dca(), &clock).await;
+    original.run_cycle().await.unwrap();
+    // Sees the order filled
+    original.run_cycle().await.unwrap();
+    
+    let exported = original.export_snapshot().await.unwrap();
+    assert_eq!(exported.version, SNAPSHOT_VERSION);
+    assert_eq!(exported.strategy_states.len(), 1);
+    let stats = exported.trade_stats.clone().unwrap();
+    assert_eq!(stats.total_trades, 1);
+    assert!(stats.equity_high_water_mark > Decimal::ZERO);
+    
+    // Through JSON and into a fresh bot
+    let parsed = Snapshot::from_json(&exported.to_json().unwrap()).unwrap();
+    let mut moved = bot(daily_dca(), &clock).await;
+    moved.import_snapshot(parsed).await.unwrap();
+    assert_eq!(moved.export_snapshot().await.unwrap().to_json().unwrap(), exported.to_json().unwrap());
+    assert_eq!(moved.get_status().await.total_trades, 1);
+    
+    // Through a storage file, as `snapshot import` and `export --storage` do
+    let storage = SqliteStorage::in_memory().unwrap();
+    exported.save_to_storage(&storage, &daily_dca()).unwrap();
+    let stored = Snapshot::from_storage(&storage, &daily_dca(), exported.created_at).unwrap();
+    assert_eq!(stored.to_json().unwrap(), exported.to_json().unwrap());
+}
+
+#[tokio::test]
+async fn dca_picks_up_its_plan_from_exported_state() {
+    let clock = SimulatedClock::new(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap());
+    let mut dca = DCAStrategy::new("dca_btc".to_string(), "BTC".to_string());
+    let market_data = ticker(40_000, &clock);
+    let signal = dca.analyze(&market_data).await.unwrap().unwrap();
+    dca.on_fill(&signal, market_data.price, clock.now());
+    
+    let mut restored = DCAStrategy::new("dca_btc".to_string(), "BTC".to_string());
+    restored
+        .import_state(dca.state_version(), &dca.export_state().unwrap().unwrap())
+        .unwrap();
+    
+    // Already bought today, so the next buy waits a day
+    clock.advance(Duration::hours(1));
+    assert!(restored.analyze(&ticker(40_000, &clock)).await.unwrap().is_none());
+    clock.advance(Duration::hours(23));
+    assert!(restored.analyze(&ticker(40_000, &clock)).await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn other_snapshot_versions_are_rejected() {
+    let clock = SimulatedClock::new(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap());
+    let original = bot(daily_dca(), &clock).await;
+    original.run_cycle().await.unwrap();
+    // Sees the order filled
+    original.run_cycle().await.unwrap();
+    let mut snapshot = original.export_snapshot().await.unwrap();
+    
+    snapshot.version = SNAPSHOT_VERSION + 1;
+    let error = Snapshot::from_json(&snapshot.to_json().unwrap()).unwrap_err();
+    assert!(error.to_string().contains("version"), "{}", error);
+    
+    // Nothing is applied, not even the parts that would fit
+    let mut other = bot(daily_dca(), &clock).await;
+    let before = other.export_snapshot().await.unwrap().to_json().unwrap();
+    assert!(other.import_snapshot(snapshot.clone()).await.is_err());
+    assert_eq!(other.export_snapshot().await.unwrap().to_json().unwrap(), before);
+    
+    let storage = SqliteStorage::in_memory().unwrap();
+    assert!(snapshot.save_to_storage(&storage, &daily_dca()).is_err());
+    assert!(Snapshot::from_storage(&storage, &daily_dca(), clock.now()).unwrap().trade_stats.is_none());
+}
+
+#[tokio::test]
+async fn snapshots_from_other_strategies_are_rejected() {
+    let clock = SimulatedClock::new(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap());
+    let snapshot = bot(daily_dca(), &clock).await.export_snapshot().await.unwrap();
+    
+    let mut config = daily_dca();
+    config.strategies.get_mut("dca_btc").unwrap().symbol = "ETH".to_string();
+    let mut other = bot(config, &clock).await;
+    let error = other.import_snapshot(snapshot).await.unwrap_err();
+    assert!(error.to_string().contains("dca_btc (BTC)"), "{}", error);
+}
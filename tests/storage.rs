@@ -0,0 +1,269 @@
+use chrono::{DateTime, Duration, Utc};
+use hyperliquid_trading_bot::{
+    journal::JournalEntry,
+    models::{Order, OrderSide, OrderStatus},
+    storage::{EquitySample, SqliteStorage, Storage, StorageWrite, StorageWriter, StoredOrder, StrategyState},
+    testing::fixtures,
+    Result,
+};
+use rust_decimal::Decimal;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+fn at(seconds: i64) -> DateTime<Utc> {
+    fixtures::start() + Duration::seconds(seconds)
+}
+
+fn fill(order_id: &str, seconds: i64) -> JournalEntry {
+    JournalEntry {
+        timestamp: at(seconds),
+        strategy: "dca_btc".to_string(),
+        symbol: "BTC".to_string(),
+        side: OrderSide::Sell,
+        quantity: Decimal::from_str("0.00125").unwrap(),
+        price: Decimal::from_str("40123.5").unwrap(),
+        fee: Decimal::from_str("0.0225").unwrap(),
+        realized_pnl: Decimal::from_str("-1.75").unwrap(),
+        order_id: order_id.to_string(),
+        cloid: Some("0x00000000000000000000000000000001".to_string()),
+        dry_run: false,
+        reconciled: true,
+    }
+}
+
+fn sample(seconds: i64, equity: i64) -> EquitySample {
+    EquitySample {
+        timestamp: at(seconds),
+        equity: Decimal::from(equity),
+        available_balance: Decimal::from(equity / 2),
+        total_pnl: Decimal::from(equity - 10_000),
+    }
+}
+
+fn state(name: &str, version: u32, data: &[u8]) -> StrategyState {
+    StrategyState {
+        name: name.to_string(),
+        version,
+        data: data.to_vec(),
+        updated_at: at(0),
+    }
+}
+
+#[test]
+fn orders_are_updated_in_place_and_read_back_newest_placed_first() {
+    let storage = SqliteStorage::in_memory().unwrap();
+    let first = fixtures::order("1", "BTC", OrderSide::Buy, Decimal::ONE, Decimal::from(100));
+    let second = Order {
+        created_at: at(60),
+        ..fixtures::order("2", "ETH", OrderSide::Sell, Decimal::from(2), Decimal::from(50))
+    };
+    storage.record_order("dca_btc", &first).unwrap();
+    storage.record_order("grid_eth", &second).unwrap();
+    
+    let filled = Order {
+        status: OrderStatus::Filled,
+        filled_quantity: Decimal::ONE,
+        average_price: Some(Decimal::from_str("99.5").unwrap()),
+        updated_at: Some(at(120)),
+        ..first
+    };
+    storage.record_order("dca_btc", &filled).unwrap();
+    
+    let orders: Vec<(String, String, OrderStatus)> = storage
+        .recent_orders(10)
+        .unwrap()
+        .into_iter()
+        .map(|StoredOrder { strategy, order }| (strategy, order.id, order.status))
+        .collect();
+    assert_eq!(
+        orders,
+        vec![
+            ("grid_eth".to_string(), "2".to_string(), OrderStatus::Open),
+            ("dca_btc".to_string(), "1".to_string(), OrderStatus::Filled),
+        ]
+    );
+    let stored = &storage.recent_orders(2).unwrap()[1].order;
+    assert_eq!(stored.average_price, filled.average_price);
+}
+
+#[test]
+fn fills_round_trip_every_field() {
+    let storage = SqliteStorage::in_memory().unwrap();
+    for (order_id, seconds) in [("1", 0), ("2", 60), ("3", 120)] {
+        storage.record_fill(&fill(order_id, seconds)).unwrap();
+    }
+    let unlabeled = JournalEntry {
+        cloid: None,
+        dry_run: true,
+        reconciled: false,
+        side: OrderSide::Buy,
+        ..fill("4", 180)
+    };
+    storage.record_fill(&unlabeled).unwrap();
+    
+    let recent = storage.recent_fills(2).unwrap();
+    assert_eq!(recent.iter().map(|fill| fill.order_id.as_str()).collect::<Vec<_>>(), ["4", "3"]);
+    assert_eq!(serde_json::to_value(&recent[0]).unwrap(), serde_json::to_value(&unlabeled).unwrap());
+    assert_eq!(serde_json::to_value(&recent[1]).unwrap(), serde_json::to_value(fill("3", 120)).unwrap());
+    
+    // Half-open and oldest first
+    let between = storage.fills_between(at(60), at(180)).unwrap();
+    assert_eq!(between.iter().map(|fill| fill.order_id.as_str()).collect::<Vec<_>>(), ["2", "3"]);
+}
+
+#[test]
+fn equity_is_read_by_range_and_downsampled_to_a_sample_a_minute() {
+    let storage = SqliteStorage::in_memory().unwrap();
+    for (seconds, equity) in [(0, 10_000), (20, 10_010), (40, 10_020), (60, 10_030), (80, 10_040), (120, 10_050)] {
+        storage.record_equity(&sample(seconds, equity)).unwrap();
+    }
+    
+    let equity = |from, to| -> Vec<Decimal> {
+        storage.equity_samples(from, to).unwrap().into_iter().map(|sample| sample.equity).collect()
+    };
+    assert_eq!(equity(at(20), at(80)), [10_010, 10_020, 10_030].map(Decimal::from));
+    
+    // Only minutes wholly before the cutoff are collapsed, to their last sample
+    assert_eq!(storage.downsample_equity(at(60)).unwrap(), 2);
+    assert_eq!(equity(at(0), at(600)), [10_020, 10_030, 10_040, 10_050].map(Decimal::from));
+    let kept = &storage.equity_samples(at(0), at(60)).unwrap()[0];
+    assert_eq!((kept.available_balance, kept.total_pnl), (Decimal::from(5_010), Decimal::from(20)));
+}
+
+#[test]
+fn strategy_state_and_key_values_are_overwritten_by_name() {
+    let storage = SqliteStorage::in_memory().unwrap();
+    assert!(storage.load_strategy_state("dca_btc").unwrap().is_none());
+    assert!(storage.get_kv("trade_stats").unwrap().is_none());
+    
+    storage.save_strategy_state(&state("dca_btc", 1, b"first")).unwrap();
+    storage.save_strategy_state(&state("dca_btc", 2, b"second")).unwrap();
+    storage.set_kv("trade_stats", "{}").unwrap();
+    storage.set_kv("trade_stats", "{\"total_trades\":3}").unwrap();
+    
+    let loaded = storage.load_strategy_state("dca_btc").unwrap().unwrap();
+    assert_eq!((loaded.version, loaded.data), (2, b"second".to_vec()));
+    assert_eq!(storage.get_kv("trade_stats").unwrap().as_deref(), Some("{\"total_trades\":3}"));
+    
+    storage
+        .save_state_batch(&[state("grid_eth", 1, b"grid")], &[("reconciled_until", "\"2024-01-01\"".to_string())])
+        .unwrap();
+    assert_eq!(storage.load_strategy_state("grid_eth").unwrap().unwrap().data, b"grid".to_vec());
+    assert_eq!(storage.get_kv("reconciled_until").unwrap().as_deref(), Some("\"2024-01-01\""));
+}
+
+#[test]
+fn a_reopened_file_keeps_what_was_written() {
+    let path = fixtures::temp_dir().join("bot.db");
+    {
+        let storage = SqliteStorage::open(&path).unwrap();
+        storage.record_fill(&fill("1", 0)).unwrap();
+        storage.record_equity(&sample(0, 10_000)).unwrap();
+    }
+    
+    let storage = SqliteStorage::open(&path).unwrap();
+    assert!(storage.schema_version().unwrap() > 0);
+    assert_eq!(storage.recent_fills(10).unwrap().len(), 1);
+    assert_eq!(storage.equity_samples(at(0), at(1)).unwrap().len(), 1);
+}
+
+/// SQLite storage whose fill writes wait while `gate` is held, to back up
+/// the writer's queue
+struct Stalled {
+    inner: SqliteStorage,
+    gate: Arc<Mutex<()>>,
+}
+
+impl Storage for Stalled {
+    fn record_order(&self, strategy: &str, order: &Order) -> Result<()> {
+        self.inner.record_order(strategy, order)
+    }
+    
+    fn record_fill(&self, fill: &JournalEntry) -> Result<()> {
+        drop(self.gate.lock().unwrap());
+        self.inner.record_fill(fill)
+    }
+    
+    fn record_equity(&self, sample: &EquitySample) -> Result<()> {
+        self.inner.record_equity(sample)
+    }
+    
+    fn save_strategy_state(&self, state: &StrategyState) -> Result<()> {
+        self.inner.save_strategy_state(state)
+    }
+    
+    fn load_strategy_state(&self, name: &str) -> Result<Option<StrategyState>> {
+        self.inner.load_strategy_state(name)
+    }
+    
+    fn set_kv(&self, key: &str, value: &str) -> Result<()> {
+        self.inner.set_kv(key, value)
+    }
+    
+    fn get_kv(&self, key: &str) -> Result<Option<String>> {
+        self.inner.get_kv(key)
+    }
+    
+    fn save_state_batch(&self, states: &[StrategyState], kv: &[(&str, String)]) -> Result<()> {
+        self.inner.save_state_batch(states, kv)
+    }
+    
+    fn recent_orders(&self, limit: usize) -> Result<Vec<StoredOrder>> {
+        self.inner.recent_orders(limit)
+    }
+    
+    fn recent_fills(&self, limit: usize) -> Result<Vec<JournalEntry>> {
+        self.inner.recent_fills(limit)
+    }
+    
+    fn fills_between(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<Vec<JournalEntry>> {
+        self.inner.fills_between(from, to)
+    }
+    
+    fn equity_samples(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<Vec<EquitySample>> {
+        self.inner.equity_samples(from, to)
+    }
+    
+    fn downsample_equity(&self, older_than: DateTime<Utc>) -> Result<usize> {
+        self.inner.downsample_equity(older_than)
+    }
+}
+
+/// More writes than the writer's queue holds
+const BACKLOG: usize = 5_000;
+
+// Holding the gate across awaits is the point: it stalls the writer's thread
+#[allow(clippy::await_holding_lock)]
+#[tokio::test(flavor = "multi_thread")]
+async fn writes_wait_for_a_backed_up_writer_rather_than_being_dropped() {
+    let gate = Arc::new(Mutex::new(()));
+    let held = gate.lock().unwrap();
+    let writer = StorageWriter::spawn(Arc::new(Stalled {
+        inner: SqliteStorage::in_memory().unwrap(),
+        gate: gate.clone(),
+    }));
+    
+    let writing = tokio::spawn({
+        let writer = writer.clone();
+        async move {
+            for i in 0..BACKLOG {
+                writer.write(StorageWrite::Fill(fill(&i.to_string(), i as i64))).await;
+            }
+        }
+    });
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    assert!(!writing.is_finished(), "the queue should have filled up");
+    
+    // Writes that can't wait are dropped and counted instead
+    writer.try_write(StorageWrite::Kv {
+        key: "paper_account".to_string(),
+        value: "{}".to_string(),
+    });
+    assert_eq!(writer.dropped(), 1);
+    
+    drop(held);
+    writing.await.unwrap();
+    writer.flush().await;
+    assert_eq!(writer.storage().recent_fills(BACKLOG * 2).unwrap().len(), BACKLOG);
+    assert_eq!(writer.dropped(), 1);
+}
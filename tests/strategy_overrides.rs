@@ -0,0 +1,99 @@
+use hyperliquid_trading_bot::config::{Config, MarginMode, StrategyConfig, StrategyOverrides};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+
+fn strategy(symbol: &str, enabled: bool) -> StrategyConfig {
+    StrategyConfig {
+        enabled,
+        strategy_type: "dca".to_string(),
+        symbol: symbol.to_string(),
+        position_size: Decimal::from(100),
+        parameters: HashMap::new(),
+        leverage: None,
+        margin_mode: MarginMode::Cross,
+        eval_interval_secs: None,
+    }
+}
+
+fn config() -> Config {
+    Config {
+        strategies: HashMap::from([
+            ("dca_btc".to_string(), strategy("BTC", true)),
+            ("grid_eth".to_string(), strategy("ETH", true)),
+            ("momentum_sol".to_string(), strategy("SOL", false)),
+        ]),
+        ..Config::default()
+    }
+}
+
+fn list(values: &[&str]) -> Vec<String> {
+    values.iter().map(|v| v.to_string()).collect()
+}
+
+fn enabled(config: &Config) -> Vec<String> {
+    let mut names: Vec<String> = config.strategies.iter().filter(|(_, s)| s.enabled).map(|(n, _)| n.clone()).collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn no_overrides_leave_config_untouched() {
+    let mut config = config();
+    let overrides = config.apply_strategy_overrides(&[], &[]).unwrap();
+    
+    assert_eq!(overrides, StrategyOverrides::default());
+    assert_eq!(enabled(&config), list(&["dca_btc", "grid_eth"]));
+}
+
+#[test]
+fn strategies_runs_only_named_ones() {
+    let mut config = config();
+    let overrides = config.apply_strategy_overrides(&list(&["momentum_sol"]), &[]).unwrap();
+    
+    assert_eq!(enabled(&config), list(&["momentum_sol"]));
+    assert_eq!(overrides.enabled, list(&["momentum_sol"]));
+    assert_eq!(overrides.disabled, list(&["dca_btc", "grid_eth"]));
+}
+
+#[test]
+fn symbols_filters_by_symbol() {
+    let mut config = config();
+    let overrides = config.apply_strategy_overrides(&[], &list(&["btc", "SOL"])).unwrap();
+    
+    // Filtering never enables a strategy the config disabled
+    assert_eq!(enabled(&config), list(&["dca_btc"]));
+    assert!(overrides.enabled.is_empty());
+    assert_eq!(overrides.disabled, list(&["grid_eth"]));
+}
+
+#[test]
+fn strategies_and_symbols_combine() {
+    let mut config = config();
+    config
+        .apply_strategy_overrides(&list(&["dca_btc", "momentum_sol"]), &list(&["SOL"]))
+        .unwrap();
+    
+    assert_eq!(enabled(&config), list(&["momentum_sol"]));
+}
+
+#[test]
+fn unknown_strategy_names_are_rejected() {
+    let mut config = config();
+    let error = config
+        .apply_strategy_overrides(&list(&["dca_btc", "arb_eth", "scalp"]), &[])
+        .unwrap_err()
+        .to_string();
+    
+    assert!(error.contains("Unknown strategies arb_eth, scalp"), "{}", error);
+    assert!(error.contains("configured: dca_btc, grid_eth, momentum_sol"), "{}", error);
+    // Nothing changed
+    assert_eq!(enabled(&config), list(&["dca_btc", "grid_eth"]));
+}
+
+#[test]
+fn overrides_that_leave_nothing_enabled_are_rejected() {
+    let mut config = config();
+    let error = config.apply_strategy_overrides(&[], &list(&["DOGE"])).unwrap_err().to_string();
+    
+    assert!(error.contains("No strategies left enabled"), "{}", error);
+}
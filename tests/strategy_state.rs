@@ -0,0 +1,111 @@
+use async_trait::async_trait;
+use chrono::Duration as ChronoDuration;
+use hyperliquid_trading_bot::{
+    config::Config,
+    models::{MarketData, SignalAction, StrategySignal},
+    strategies::{GridStrategy, MomentumStrategy, Strategy},
+    testing::{self, fixtures, MockTradingClient},
+    Result,
+};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+
+fn tick(price: i64, seconds: i64) -> MarketData {
+    MarketData {
+        timestamp: fixtures::start() + ChronoDuration::seconds(seconds),
+        ..fixtures::market_data("SOL", Decimal::from(price))
+    }
+}
+
+#[tokio::test]
+async fn momentum_keeps_the_prices_it_analyzed() {
+    let mut momentum = MomentumStrategy::new("momentum".to_string(), "SOL".to_string());
+    let mut signal = None;
+    
+    for (seconds, price) in (100..130).enumerate() {
+        signal = momentum.analyze(&tick(price, seconds as i64 * 5)).await.unwrap();
+    }
+    assert_eq!(momentum.price_history().len(), 30);
+    let signal = signal.expect("a steady climb signals once the history is long enough");
+    assert!(matches!(signal.action, SignalAction::Buy));
+    
+    // The same update analyzed again isn't added twice
+    momentum.analyze(&tick(129, 29 * 5)).await.unwrap();
+    assert_eq!(momentum.price_history().len(), 30);
+}
+
+#[tokio::test]
+async fn grid_trades_from_the_update_after_the_one_it_was_laid_at() {
+    let mut grid = GridStrategy::new("grid".to_string(), "SOL".to_string());
+    
+    assert!(grid.analyze(&tick(100, 0)).await.unwrap().is_none());
+    let signal = grid.analyze(&tick(90, 5)).await.unwrap().expect("the price fell through a level");
+    assert!(matches!(signal.action, SignalAction::Buy));
+}
+
+/// Holds each analysis until `release` is notified
+struct Gated {
+    started: Arc<Notify>,
+    release: Arc<Notify>,
+}
+
+#[async_trait]
+impl Strategy for Gated {
+    fn name(&self) -> &str {
+        "gated"
+    }
+    
+    fn symbol(&self) -> &str {
+        "BTC"
+    }
+    
+    fn is_enabled(&self) -> bool {
+        true
+    }
+    
+    async fn analyze(&mut self, _market_data: &MarketData) -> Result<Option<StrategySignal>> {
+        self.started.notify_one();
+        self.release.notified().await;
+        Ok(None)
+    }
+    
+    async fn update_parameters(&mut self, _parameters: HashMap<String, serde_json::Value>) -> Result<()> {
+        Ok(())
+    }
+    
+    fn get_parameters(&self) -> HashMap<String, serde_json::Value> {
+        HashMap::new()
+    }
+    
+    fn validate_parameters(&self, _parameters: &HashMap<String, serde_json::Value>) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn status_does_not_wait_on_an_analysis() {
+    let exchange = Arc::new(MockTradingClient::new(Decimal::from(10_000)));
+    exchange.set_price("BTC", Decimal::from(100));
+    let (started, release) = (Arc::new(Notify::new()), Arc::new(Notify::new()));
+    let gated = Gated {
+        started: started.clone(),
+        release: release.clone(),
+    };
+    let bot = Arc::new(testing::mock_bot(Config::default(), exchange).await.unwrap().with_strategy(Box::new(gated)));
+    
+    let cycle = tokio::spawn({
+        let bot = bot.clone();
+        async move { bot.run_cycle().await }
+    });
+    started.notified().await;
+    let snapshot = tokio::time::timeout(Duration::from_millis(500), bot.cached_snapshot())
+        .await
+        .expect("status waited on the analysis");
+    assert!(snapshot.strategies.iter().any(|strategy| strategy.name == "gated" && strategy.enabled));
+    
+    release.notify_one();
+    cycle.await.unwrap().unwrap();
+}
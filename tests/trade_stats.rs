@@ -0,0 +1,159 @@
+use chrono::{NaiveDate, TimeZone, Utc};
+use hyperliquid_trading_bot::{
+    models::{FundingPayment, FundingSummary},
+    trade_stats::{TradeStats, TradeStatsTracker},
+};
+use rust_decimal::Decimal;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const WRITERS: usize = 8;
+const TRADES_PER_WRITER: u64 = 50_000;
+
+fn day(n: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 1, n).unwrap()
+}
+
+#[test]
+fn concurrent_updates_are_never_lost_and_reads_stay_fast() {
+    let tracker = Arc::new(TradeStatsTracker::new(TradeStats::new(day(1))));
+    let done = Arc::new(AtomicBool::new(false));
+    
+    // Reads the stats the way `status` does, for as long as writers run
+    let reader = {
+        let tracker = tracker.clone();
+        let done = done.clone();
+        thread::spawn(move || {
+            let mut slowest = Duration::ZERO;
+            let mut last_total = 0;
+            let mut reads = 0u64;
+            while !done.load(Ordering::Relaxed) {
+                let start = Instant::now();
+                let stats = tracker.snapshot();
+                slowest = slowest.max(start.elapsed());
+                
+                assert_eq!(stats.total_trades, stats.successful_trades + stats.failed_trades);
+                assert!(stats.total_trades >= last_total, "counts went backwards");
+                last_total = stats.total_trades;
+                reads += 1;
+            }
+            (slowest, reads)
+        })
+    };
+    
+    // Order outcomes from every strategy at once, with PnL updates mixed in
+    let writers: Vec<_> = (0..WRITERS)
+        .map(|writer| {
+            let tracker = tracker.clone();
+            thread::spawn(move || {
+                for i in 0..TRADES_PER_WRITER {
+                    if (i + writer as u64).is_multiple_of(4) {
+                        tracker.record_failure();
+                    } else {
+                        tracker.record_success();
+                    }
+                    if i % 1000 == 0 {
+                        tracker.update_pnl(Decimal::from(i), Decimal::from(10_000 + i), day(1));
+                    }
+                }
+            })
+        })
+        .collect();
+    for writer in writers {
+        writer.join().unwrap();
+    }
+    done.store(true, Ordering::Relaxed);
+    let (slowest, reads) = reader.join().unwrap();
+    
+    let stats = tracker.snapshot();
+    assert_eq!(stats.total_trades, WRITERS as u64 * TRADES_PER_WRITER);
+    assert_eq!(stats.failed_trades, WRITERS as u64 * TRADES_PER_WRITER / 4);
+    assert_eq!(stats.successful_trades, stats.total_trades - stats.failed_trades);
+    assert!(reads > 0);
+    assert!(slowest < Duration::from_millis(50), "slowest read took {:?}", slowest);
+}
+
+#[test]
+fn daily_pnl_counts_from_the_start_of_each_day() {
+    let tracker = TradeStatsTracker::new(TradeStats::new(day(1)));
+    
+    assert!(tracker.update_pnl(Decimal::from(100), Decimal::from(10_100), day(1)).is_none());
+    assert!(tracker.update_pnl(Decimal::from(150), Decimal::from(10_150), day(1)).is_none());
+    tracker.record_success();
+    assert_eq!(tracker.snapshot().daily_pnl, Decimal::from(50));
+    
+    // The new day hands back how the old one ended
+    let finished = tracker.update_pnl(Decimal::from(120), Decimal::from(10_120), day(2)).unwrap();
+    assert_eq!(finished.last_reset_date, day(1));
+    assert_eq!(finished.daily_pnl, Decimal::from(50));
+    assert_eq!(finished.total_trades, 1);
+    
+    tracker.update_pnl(Decimal::from(90), Decimal::from(10_090), day(2));
+    let stats = tracker.snapshot();
+    assert_eq!(stats.daily_pnl, Decimal::from(-30));
+    assert_eq!(stats.total_pnl, Decimal::from(90));
+    assert_eq!(stats.equity_high_water_mark, Decimal::from(10_150));
+}
+
+#[test]
+fn funding_paid_comes_out_of_the_pnl() {
+    let tracker = TradeStatsTracker::new(TradeStats::new(day(1)));
+    let payment = |coin: &str, amount: i64, day: u32| FundingPayment {
+        coin: coin.to_string(),
+        amount: Decimal::from(amount),
+        size: Decimal::ONE,
+        rate: Decimal::ZERO,
+        time: Utc.with_ymd_and_hms(2024, 1, day, 8, 0, 0).unwrap(),
+    };
+    
+    tracker.update_pnl(Decimal::from(100), Decimal::from(10_100), day(1));
+    let funding = FundingSummary::from_payments(&[payment("BTC", -7, 1), payment("ETH", 2, 1)]);
+    tracker.record_funding(&funding, Utc.with_ymd_and_hms(2024, 1, 1, 8, 1, 0).unwrap());
+    let stats = tracker.snapshot();
+    assert_eq!(stats.funding_paid, Decimal::from(5));
+    assert_eq!(stats.daily_funding_paid, Decimal::from(5));
+    assert_eq!(stats.funding_by_coin["ETH"], Decimal::from(-2));
+    assert_eq!(stats.total_pnl, Decimal::from(95));
+    assert_eq!(stats.daily_pnl, Decimal::from(-5));
+    
+    // Later account PnL still has the funding taken out
+    tracker.update_pnl(Decimal::from(110), Decimal::from(10_110), day(1));
+    assert_eq!(tracker.snapshot().total_pnl, Decimal::from(105));
+    
+    // A new day starts its daily funding over
+    let finished = tracker.update_pnl(Decimal::from(110), Decimal::from(10_110), day(2)).unwrap();
+    assert_eq!(finished.daily_funding_paid, Decimal::from(5));
+    let funding = FundingSummary::from_payments(&[payment("BTC", -3, 2)]);
+    tracker.record_funding(&funding, Utc.with_ymd_and_hms(2024, 1, 2, 8, 1, 0).unwrap());
+    let stats = tracker.snapshot();
+    assert_eq!(stats.daily_funding_paid, Decimal::from(3));
+    assert_eq!(stats.funding_paid, Decimal::from(8));
+    assert_eq!(stats.funding_by_coin["BTC"], Decimal::from(10));
+    assert_eq!(stats.daily_pnl, Decimal::from(-3));
+}
+
+#[test]
+fn fees_come_out_of_the_pnl_unless_the_account_counted_them() {
+    let tracker = TradeStatsTracker::new(TradeStats::new(day(1)));
+    tracker.update_pnl(Decimal::from(100), Decimal::from(10_100), day(1));
+    
+    // An estimate, then the fills' actual fee
+    tracker.record_fee(Decimal::from(4), false);
+    tracker.record_fee(Decimal::from(-1), false);
+    let stats = tracker.snapshot();
+    assert_eq!(stats.fees_paid, Decimal::from(3));
+    assert_eq!(stats.total_pnl, Decimal::from(97));
+    assert_eq!(stats.daily_pnl, Decimal::from(-3));
+    
+    // Later account PnL still has the fees taken out
+    tracker.update_pnl(Decimal::from(110), Decimal::from(10_110), day(1));
+    assert_eq!(tracker.snapshot().total_pnl, Decimal::from(107));
+    
+    // A paper account's PnL already has its fees in it
+    tracker.record_fee(Decimal::from(2), true);
+    let stats = tracker.snapshot();
+    assert_eq!(stats.fees_paid, Decimal::from(5));
+    assert_eq!(stats.total_pnl, Decimal::from(107));
+}
@@ -0,0 +1,196 @@
+use async_trait::async_trait;
+use chrono::Duration as ChronoDuration;
+use futures_util::{SinkExt, StreamExt};
+use hyperliquid_trading_bot::{
+    config::Config,
+    models::{MarketData, MarketTrade, OrderSide, StrategySignal},
+    runner::RunMode,
+    strategies::{MomentumStrategy, Strategy},
+    testing::{self, fixtures, MockTradingClient},
+    Result,
+};
+use rust_decimal::Decimal;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+use tokio_tungstenite::tungstenite::Message;
+
+fn tick(price: i64, seconds: i64) -> MarketData {
+    MarketData {
+        symbol: "SOL".to_string(),
+        price: Decimal::from(price),
+        volume_24h: Decimal::from(1_000_000),
+        change_24h: Decimal::ZERO,
+        high_24h: Decimal::from(price),
+        low_24h: Decimal::from(price),
+        timestamp: fixtures::start() + ChronoDuration::seconds(seconds),
+    }
+}
+
+fn trade(size: i64) -> MarketTrade {
+    MarketTrade {
+        symbol: "SOL".to_string(),
+        side: OrderSide::Buy,
+        price: Decimal::from(100),
+        size: Decimal::from(size),
+        timestamp: fixtures::start(),
+    }
+}
+
+#[tokio::test]
+async fn momentum_confirms_on_the_size_traded_between_samples() {
+    let mut quiet = MomentumStrategy::new("momentum".to_string(), "SOL".to_string());
+    let mut busy = quiet.clone();
+    assert!(quiet.wants_trades());
+    
+    for (seconds, price) in (100..139).enumerate() {
+        for strategy in [&mut quiet, &mut busy] {
+            strategy.on_trade(&trade(1));
+            strategy.on_market_data(&tick(price, seconds as i64 * 5));
+        }
+    }
+    // Ten times the usual size trades before the last sample
+    quiet.on_trade(&trade(1));
+    busy.on_trade(&trade(10));
+    
+    let last = tick(139, 39 * 5);
+    let quiet = quiet.analyze(&last).await.unwrap().unwrap();
+    let busy = busy.analyze(&last).await.unwrap().unwrap();
+    assert!((busy.confidence - quiet.confidence - 0.1).abs() < 1e-9);
+    
+    // Candles carry their own volume
+    let mut candles = MomentumStrategy::new("momentum".to_string(), "SOL".to_string());
+    candles
+        .update_parameters(HashMap::from([("candle_interval".to_string(), json!("1h"))]))
+        .await
+        .unwrap();
+    assert!(!candles.wants_trades());
+}
+
+/// A websocket sending whatever's pushed into the returned sender, and
+/// passing on the requests it gets
+async fn stream() -> (String, mpsc::UnboundedSender<String>, mpsc::UnboundedReceiver<Value>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    let (frames, mut pushed) = mpsc::unbounded_channel::<String>();
+    let (requests, received) = mpsc::unbounded_channel();
+    
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+        loop {
+            tokio::select! {
+                frame = pushed.recv() => match frame {
+                    Some(frame) => ws.send(Message::Text(frame)).await.unwrap(),
+                    None => break,
+                },
+                message = ws.next() => match message {
+                    Some(Ok(Message::Text(text))) => {
+                        let _ = requests.send(serde_json::from_str::<Value>(&text).unwrap());
+                    }
+                    Some(Ok(_)) => {}
+                    _ => break,
+                },
+            }
+        }
+    });
+    
+    (url, frames, received)
+}
+
+/// Keeps the side and size of each trade it's handed
+struct Recorder {
+    symbol: &'static str,
+    trades: Arc<Mutex<Vec<(OrderSide, Decimal)>>>,
+}
+
+#[async_trait]
+impl Strategy for Recorder {
+    fn name(&self) -> &str {
+        self.symbol
+    }
+    
+    fn symbol(&self) -> &str {
+        self.symbol
+    }
+    
+    fn is_enabled(&self) -> bool {
+        true
+    }
+    
+    async fn analyze(&mut self, _market_data: &MarketData) -> Result<Option<StrategySignal>> {
+        Ok(None)
+    }
+    
+    async fn update_parameters(&mut self, _parameters: HashMap<String, serde_json::Value>) -> Result<()> {
+        Ok(())
+    }
+    
+    fn get_parameters(&self) -> HashMap<String, serde_json::Value> {
+        HashMap::new()
+    }
+    
+    fn validate_parameters(&self, _parameters: &HashMap<String, serde_json::Value>) -> Result<()> {
+        Ok(())
+    }
+    
+    fn wants_trades(&self) -> bool {
+        true
+    }
+    
+    fn on_trade(&mut self, trade: &MarketTrade) {
+        assert_eq!(trade.symbol, self.symbol);
+        self.trades.lock().unwrap().push((trade.side.clone(), trade.size));
+    }
+}
+
+#[tokio::test]
+async fn strategies_are_handed_their_symbols_trades() {
+    let (url, frames, mut requests) = stream().await;
+    let exchange = Arc::new(MockTradingClient::new(Decimal::from(10_000)));
+    exchange.set_price("SOL", Decimal::from(100));
+    exchange.set_price("ETH", Decimal::from(2_000));
+    let mut config = Config::default();
+    config.hyperliquid.ws_url = url;
+    config.preflight.ignore_failures = true;
+    config.preflight.websocket_timeout_secs = 1;
+    let (sol, eth) = (Arc::new(Mutex::new(Vec::new())), Arc::new(Mutex::new(Vec::new())));
+    let bot = testing::mock_bot(config, exchange)
+        .await
+        .unwrap()
+        .with_strategy(Box::new(Recorder { symbol: "SOL", trades: sol.clone() }))
+        .with_strategy(Box::new(Recorder { symbol: "ETH", trades: eth.clone() }));
+    
+    let pushed = async {
+        let mut subscribed = Vec::new();
+        while subscribed.len() < 2 {
+            let request = requests.recv().await.unwrap();
+            if request["subscription"]["type"] == "trades" {
+                subscribed.push(request["subscription"]["coin"].clone());
+            }
+        }
+        assert_eq!(subscribed, vec![json!("ETH"), json!("SOL")]);
+        
+        // Pushed until the bot is following along
+        let frame = std::fs::read_to_string(format!("{}/tests/fixtures/websocket/trades.json", env!("CARGO_MANIFEST_DIR"))).unwrap();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while sol.lock().unwrap().len() < 4 && Instant::now() < deadline {
+            frames.send(frame.clone()).unwrap();
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    };
+    tokio::select! {
+        () = pushed => {}
+        result = bot.run(RunMode::Continuous) => panic!("the bot stopped: {:?}", result),
+    }
+    
+    let sol = sol.lock().unwrap();
+    assert!(sol.len() >= 4);
+    assert!(sol.contains(&(OrderSide::Buy, "12.5".parse().unwrap())));
+    assert!(sol.contains(&(OrderSide::Sell, "0.8".parse().unwrap())));
+    assert!(eth.lock().unwrap().is_empty());
+}
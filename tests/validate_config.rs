@@ -0,0 +1,182 @@
+use hyperliquid_trading_bot::{
+    api::{wallet, HyperliquidClient},
+    config::{Config, Network},
+    validation::{self, CheckStatus, ValidationReport},
+};
+use serde_json::json;
+use wiremock::matchers::{body_partial_json, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const VALID: &str = "tests/fixtures/config/valid.toml";
+const WALLET: &str = "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266";
+
+fn status(report: &ValidationReport, name: &str) -> CheckStatus {
+    report.find(name).unwrap_or_else(|| panic!("no check named {}", name)).status
+}
+
+#[tokio::test]
+async fn valid_config_passes_offline() {
+    let mut report = ValidationReport::new();
+    let config = validation::validate_offline(VALID, &mut report).await;
+    
+    assert!(config.is_some());
+    assert!(report.passed(), "{:?}", report.checks);
+    assert_eq!(status(&report, "strategy dca_btc"), CheckStatus::Pass);
+}
+
+#[tokio::test]
+async fn bad_strategy_parameters_and_types_fail_offline() {
+    let mut report = ValidationReport::new();
+    validation::validate_offline("tests/fixtures/config/invalid_strategy.toml", &mut report).await;
+    
+    assert_eq!(status(&report, "config"), CheckStatus::Pass);
+    assert_eq!(status(&report, "strategy dca_btc"), CheckStatus::Fail);
+    assert_eq!(status(&report, "strategy arbitrage_eth"), CheckStatus::Fail);
+    assert!(report.find("strategy arbitrage_eth").unwrap().detail.contains("unknown strategy type"));
+    assert_eq!(report.failures(), 2);
+}
+
+#[tokio::test]
+async fn out_of_range_values_fail_offline() {
+    let mut report = ValidationReport::new();
+    let config = validation::validate_offline("tests/fixtures/config/invalid_range.toml", &mut report).await;
+    
+    assert!(config.is_none());
+    assert_eq!(status(&report, "config"), CheckStatus::Fail);
+    assert!(report.find("config").unwrap().detail.contains("Max positions"));
+}
+
+#[tokio::test]
+async fn malformed_vault_address_fails_offline() {
+    let mut report = ValidationReport::new();
+    let config = validation::validate_offline("tests/fixtures/config/invalid_vault.toml", &mut report).await;
+    
+    assert!(config.is_none());
+    assert!(report.find("config").unwrap().detail.contains("vault_address"));
+}
+
+#[tokio::test]
+async fn urls_follow_the_testnet_flag() {
+    let mut report = ValidationReport::new();
+    let config = validation::validate_offline(VALID, &mut report).await.unwrap();
+    assert_eq!(config.hyperliquid.network(), Network::Testnet);
+    assert_eq!(config.hyperliquid.base_url, "https://api.hyperliquid-testnet.xyz");
+    assert_eq!(config.hyperliquid.ws_url, "wss://api.hyperliquid-testnet.xyz/ws");
+    
+    let mut report = ValidationReport::new();
+    let config = validation::validate_offline("tests/fixtures/config/mainnet_url_on_testnet.toml", &mut report).await;
+    assert!(config.is_none());
+    let detail = &report.find("config").unwrap().detail;
+    assert!(detail.contains("base_url https://api.hyperliquid.xyz is a mainnet URL but testnet = true"), "{}", detail);
+}
+
+#[tokio::test]
+async fn profit_sweep_needs_a_destination_address() {
+    let mut report = ValidationReport::new();
+    let config = validation::validate_offline("tests/fixtures/config/profit_sweep_without_destination.toml", &mut report).await;
+    assert!(config.is_none());
+    let detail = &report.find("config").unwrap().detail;
+    assert!(detail.contains("profit_sweep.destination must be 0x and 40 hex digits"), "{}", detail);
+}
+
+#[test]
+fn networks_are_told_apart_by_host() {
+    assert_eq!(Network::of_url("https://api.hyperliquid.xyz"), Some(Network::Mainnet));
+    assert_eq!(Network::of_url("wss://api.hyperliquid-testnet.xyz/ws"), Some(Network::Testnet));
+    assert_eq!(Network::of_url("http://127.0.0.1:8080"), None);
+    assert_eq!(Network::of_url("https://hyperliquid.xyz.example.com"), None);
+    
+    // The client falls back to the network's endpoint
+    let client = HyperliquidClient::new(String::new(), String::new(), String::new(), false);
+    assert_eq!(client.base_url(), "https://api.hyperliquid.xyz");
+}
+
+#[test]
+fn derives_wallet_address_from_private_key() {
+    let address = wallet::address_from_private_key("0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80").unwrap();
+    assert_eq!(address, WALLET);
+    assert!(wallet::address_from_private_key("not-a-key").is_err());
+}
+
+async fn mock_exchange(symbols: &[(&str, u32)], account_value: &str) -> MockServer {
+    let server = MockServer::start().await;
+    let universe: Vec<_> = symbols
+        .iter()
+        .map(|(name, max_leverage)| json!({ "name": name, "szDecimals": 4, "maxLeverage": max_leverage }))
+        .collect();
+    
+    Mock::given(method("POST"))
+        .and(path("/info"))
+        .and(body_partial_json(json!({ "type": "meta" })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "universe": universe })))
+        .mount(&server)
+        .await;
+    
+    Mock::given(method("POST"))
+        .and(path("/info"))
+        .and(body_partial_json(json!({ "type": "clearinghouseState", "user": WALLET })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "assetPositions": [],
+            "crossMarginSummary": null,
+            "marginSummary": {
+                "accountValue": account_value,
+                "totalMarginUsed": "0",
+                "totalNtlPos": "0",
+                "totalRawUsd": account_value
+            },
+            "time": 1704067200000u64,
+            "withdrawable": account_value
+        })))
+        .mount(&server)
+        .await;
+    
+    server
+}
+
+async fn validate_against(server: &MockServer) -> ValidationReport {
+    let mut config = Config::load(VALID).unwrap();
+    config.hyperliquid.base_url = server.uri();
+    let client = HyperliquidClient::new(
+        config.hyperliquid.base_url.clone(),
+        config.hyperliquid.api_key.clone(),
+        config.hyperliquid.private_key.clone(),
+        config.hyperliquid.testnet,
+    );
+    
+    let mut report = ValidationReport::new();
+    validation::validate_online(&config, &client, &mut report).await;
+    report
+}
+
+#[tokio::test]
+async fn online_checks_pass_against_exchange() {
+    let server = mock_exchange(&[("BTC", 50), ("ETH", 25), ("SOL", 20)], "1234.5").await;
+    let report = validate_against(&server).await;
+    
+    assert!(report.passed(), "{:?}", report.checks);
+    assert_eq!(status(&report, "connectivity"), CheckStatus::Pass);
+    assert_eq!(status(&report, "leverage dca_btc"), CheckStatus::Pass);
+    assert!(report.find("wallet").unwrap().detail.contains(WALLET));
+}
+
+#[tokio::test]
+async fn online_checks_report_each_failure() {
+    // SOL unlisted, BTC capped below the configured 5x, empty wallet
+    let server = mock_exchange(&[("BTC", 3), ("ETH", 25)], "0").await;
+    let report = validate_against(&server).await;
+    
+    assert_eq!(status(&report, "connectivity"), CheckStatus::Pass);
+    assert_eq!(status(&report, "symbol momentum_sol"), CheckStatus::Fail);
+    assert_eq!(status(&report, "leverage dca_btc"), CheckStatus::Fail);
+    assert_eq!(status(&report, "wallet"), CheckStatus::Fail);
+    assert_eq!(report.failures(), 3);
+}
+
+#[tokio::test]
+async fn unreachable_exchange_fails_connectivity() {
+    let server = MockServer::start().await;
+    let report = validate_against(&server).await;
+    
+    assert_eq!(status(&report, "connectivity"), CheckStatus::Fail);
+    assert!(!report.passed());
+}
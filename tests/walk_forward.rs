@@ -0,0 +1,93 @@
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use hyperliquid_trading_bot::{
+    backtest::{
+        optimize::{Optimizer, ParameterSpace},
+        walk_forward::WalkForward,
+    },
+    models::MarketData,
+    testing::fixtures,
+};
+use rust_decimal::Decimal;
+use serde_json::json;
+use std::str::FromStr;
+
+fn dec(value: &str) -> Decimal {
+    Decimal::from_str(value).unwrap()
+}
+
+fn day(n: i64) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap() + Duration::days(n)
+}
+
+/// Daily closes rising $10 a day for 30 days, then falling $10 a day. DCA
+/// only buys its first bars while prices rise, so bigger buys win; once
+/// they fall it buys every bar, and smaller buys lose less.
+fn rise_then_fall() -> Vec<MarketData> {
+    (0..60)
+        .map(|n| {
+            let price = if n < 30 { 100 + 10 * n } else { 390 - 10 * (n - 29) };
+            let price = Decimal::from(price);
+            MarketData {
+                symbol: "BTC".to_string(),
+                price,
+                volume_24h: dec("1000"),
+                change_24h: Decimal::ZERO,
+                high_24h: price,
+                low_24h: price,
+                timestamp: day(n),
+            }
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn windows_follow_the_best_parameter_as_it_changes() {
+    let space = ParameterSpace::default().with_values("investment_amount", vec![json!("50"), json!("200")]);
+    let walk_forward = WalkForward::new(Duration::days(10), Duration::days(5));
+    let optimizer = Optimizer::new(fixtures::dca_backtest_config(dec("10000"), "1d", json!({ "max_investment": "100000" })), "dca_btc").unwrap().with_workers(2);
+    
+    let result = optimizer.walk_forward(&space, rise_then_fall(), &walk_forward, day(0), day(60)).await.unwrap();
+    
+    // Train 10 days then test 5, moving on 5 days at a time
+    assert_eq!(result.windows.len(), 10);
+    assert_eq!(result.windows[1].bounds.train_start, day(5));
+    assert_eq!(result.windows[1].bounds.test_start, day(15));
+    assert_eq!(result.windows[9].bounds.test_end, day(60));
+    
+    for window in &result.windows {
+        let amount = &window.parameters["investment_amount"];
+        if window.bounds.test_start <= day(30) {
+            // Trained only on rising prices
+            assert_eq!(amount, &json!("200"), "window from {}", window.bounds.train_start);
+        } else if window.bounds.train_start >= day(30) {
+            // Trained only on falling prices
+            assert_eq!(amount, &json!("50"), "window from {}", window.bounds.train_start);
+        }
+    }
+    assert!(result.parameter_changes() >= 1);
+    
+    // Test periods are stitched end to end, each compounding on the last
+    assert!(result.equity_curve.first().unwrap().timestamp >= day(10));
+    assert!(result.equity_curve.windows(2).all(|pair| pair[0].timestamp < pair[1].timestamp));
+    let compounded = result
+        .windows
+        .iter()
+        .fold(result.initial_balance, |equity, window| equity * (Decimal::ONE + window.total_return));
+    assert_eq!(result.final_equity().round_dp(6), compounded.round_dp(6));
+    assert_eq!(result.trades(), result.windows.iter().map(|w| w.trades).sum::<usize>());
+    
+    let latest = result.latest_config(optimizer.strategy_config()).unwrap();
+    assert_eq!(latest.parameters["investment_amount"], json!("50"));
+}
+
+#[test]
+fn windows_must_fit_the_range_without_overlapping_tests() {
+    let walk_forward = WalkForward::new(Duration::days(10), Duration::days(5));
+    assert_eq!(walk_forward.windows(day(0), day(20)).unwrap().len(), 2);
+    assert!(walk_forward.windows(day(0), day(14)).is_err());
+    assert!(walk_forward.with_step(Duration::days(2)).windows(day(0), day(60)).is_err());
+    
+    let spaced = walk_forward.with_step(Duration::days(10)).windows(day(0), day(40)).unwrap();
+    let test_starts: Vec<DateTime<Utc>> = spaced.iter().map(|w| w.test_start).collect();
+    assert_eq!(test_starts, vec![day(10), day(20), day(30)]);
+}
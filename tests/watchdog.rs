@@ -0,0 +1,238 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use hyperliquid_trading_bot::{
+    api::client::TradingClient,
+    clock::SystemClock,
+    config::{Config, WatchdogAction},
+    costs::CostModel,
+    market_stream::MarketEvent,
+    models::{AccountInfo, MarketData, Order, OrderSide, OrderStatus, OrderType, Position, StrategySignal, Trade},
+    notifications::{NotificationChannel, NotificationEvent, Notifier},
+    simulated_exchange::SimulatedExchange,
+    strategies::Strategy,
+    trading_bot::TradingBot,
+    Result,
+};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// A strategy whose analysis never finishes
+struct StuckStrategy;
+
+#[async_trait]
+impl Strategy for StuckStrategy {
+    fn name(&self) -> &str {
+        "stuck"
+    }
+    
+    fn symbol(&self) -> &str {
+        "BTC"
+    }
+    
+    fn is_enabled(&self) -> bool {
+        true
+    }
+    
+    async fn analyze(&mut self, _market_data: &MarketData) -> Result<Option<StrategySignal>> {
+        std::future::pending().await
+    }
+    
+    async fn update_parameters(&mut self, _parameters: HashMap<String, serde_json::Value>) -> Result<()> {
+        Ok(())
+    }
+    
+    fn get_parameters(&self) -> HashMap<String, serde_json::Value> {
+        HashMap::new()
+    }
+    
+    fn validate_parameters(&self, _parameters: &HashMap<String, serde_json::Value>) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// A simulated exchange whose account endpoint can be made to hang
+struct Exchange {
+    exchange: SimulatedExchange,
+    hang_account: bool,
+}
+
+#[async_trait]
+impl TradingClient for Exchange {
+    async fn get_market_data(&self, symbol: &str) -> Result<MarketData> {
+        self.exchange.get_market_data(symbol).await
+    }
+    
+    async fn get_account_info(&self) -> Result<AccountInfo> {
+        if self.hang_account {
+            std::future::pending::<()>().await;
+        }
+        self.exchange.get_account_info().await
+    }
+    
+    async fn get_positions(&self) -> Result<Vec<Position>> {
+        self.exchange.get_positions().await
+    }
+    
+    async fn get_open_orders(&self) -> Result<Vec<Order>> {
+        self.exchange.get_open_orders().await
+    }
+    
+    async fn place_order(&self, order: &Order) -> Result<String> {
+        self.exchange.place_order(order).await
+    }
+    
+    async fn cancel_order(&self, order_id: &str) -> Result<bool> {
+        self.exchange.cancel_order(order_id).await
+    }
+    
+    async fn get_trade_history(&self, symbol: Option<&str>) -> Result<Vec<Trade>> {
+        self.exchange.get_trade_history(symbol).await
+    }
+}
+
+/// Keeps every notification it's sent
+#[derive(Default)]
+struct Recorder {
+    events: Mutex<Vec<NotificationEvent>>,
+}
+
+#[async_trait]
+impl NotificationChannel for Recorder {
+    fn name(&self) -> &str {
+        "recorder"
+    }
+    
+    async fn send_batch(&self, events: &[NotificationEvent]) -> Result<()> {
+        self.events.lock().unwrap().extend_from_slice(events);
+        Ok(())
+    }
+}
+
+fn exchange(hang_account: bool) -> Arc<Exchange> {
+    let exchange = SimulatedExchange::new(Decimal::from(100_000), CostModel::default());
+    let price = Decimal::from(40_000);
+    exchange.update(&MarketEvent::Ticker(MarketData {
+        symbol: "BTC".to_string(),
+        price,
+        volume_24h: Decimal::from(1000),
+        change_24h: Decimal::ZERO,
+        high_24h: price,
+        low_24h: price,
+        timestamp: Utc::now(),
+    }));
+    Arc::new(Exchange { exchange, hang_account })
+}
+
+async fn bot(action: WatchdogAction, exchange: Arc<Exchange>, recorder: &Arc<Recorder>) -> TradingBot {
+    let mut config = Config::default();
+    config.trading.dry_run = false;
+    config.watchdog.action = action;
+    // Long enough that the stuck analysis outlasts the watchdog
+    config.analysis.timeout_ms = 600_000;
+    TradingBot::with_client(config, exchange, Arc::new(SystemClock))
+        .await
+        .unwrap()
+        .with_strategy(Box::new(StuckStrategy))
+        .with_notifier(Notifier::new(vec![recorder.clone()]))
+}
+
+fn stalls(recorder: &Recorder) -> Vec<NotificationEvent> {
+    recorder
+        .events
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|event| matches!(event, NotificationEvent::WatchdogStall { .. }))
+        .cloned()
+        .collect()
+}
+
+#[tokio::test(start_paused = true)]
+async fn exit_action_reports_where_the_loop_is_stuck() {
+    let recorder = Arc::new(Recorder::default());
+    let bot = bot(WatchdogAction::Exit, exchange(false), &recorder).await;
+    
+    let stall = tokio::select! {
+        _ = bot.run_cycle() => panic!("the cycle should hang"),
+        stall = bot.watchdog() => stall,
+    };
+    
+    assert_eq!(stall.cycle, 1);
+    assert_eq!(stall.stage, "strategy stuck: analyze");
+    assert!(stall.silent_for >= Duration::from_secs(30), "{:?}", stall.silent_for);
+    assert_eq!(stall.in_flight, None);
+    assert!(stall.to_string().contains("no request in flight"), "{}", stall);
+}
+
+#[tokio::test(start_paused = true)]
+async fn alert_names_the_hung_request() {
+    let recorder = Arc::new(Recorder::default());
+    let bot = bot(WatchdogAction::Alert, exchange(true), &recorder).await;
+    
+    tokio::select! {
+        _ = bot.run_cycle() => panic!("the cycle should hang"),
+        _ = bot.watchdog() => panic!("alerts don't stop the watchdog"),
+        _ = tokio::time::sleep(Duration::from_secs(60)) => {}
+    }
+    
+    let stalls = stalls(&recorder);
+    assert_eq!(stalls.len(), 1, "one alert per stall: {:?}", stalls);
+    let NotificationEvent::WatchdogStall { stage, in_flight, .. } = &stalls[0] else {
+        unreachable!()
+    };
+    assert_eq!(stage, "fetching account");
+    assert_eq!(in_flight.as_deref(), Some("get_account_info"));
+    assert!(!bot.kill_switch_engaged());
+}
+
+#[tokio::test(start_paused = true)]
+async fn kill_switch_action_cancels_orders_and_stops_trading() {
+    let recorder = Arc::new(Recorder::default());
+    let exchange = exchange(false);
+    exchange
+        .place_order(&Order {
+            id: "resting".to_string(),
+            symbol: "BTC".to_string(),
+            side: OrderSide::Buy,
+            order_type: OrderType::Limit,
+            quantity: Decimal::new(1, 2),
+            price: Some(Decimal::from(30_000)),
+            status: OrderStatus::Pending,
+            created_at: Utc::now(),
+            updated_at: None,
+            filled_quantity: Decimal::ZERO,
+            average_price: None,
+            reduce_only: false,
+            trigger_price: None,
+            is_take_profit: false,
+            cloid: None,
+        })
+        .await
+        .unwrap();
+    assert_eq!(exchange.get_open_orders().await.unwrap().len(), 1);
+    
+    let bot = bot(WatchdogAction::KillSwitch, exchange.clone(), &recorder).await;
+    tokio::select! {
+        _ = bot.run_cycle() => panic!("the cycle should hang"),
+        _ = bot.watchdog() => panic!("the kill switch doesn't stop the watchdog"),
+        _ = tokio::time::sleep(Duration::from_secs(60)) => {}
+    }
+    
+    assert!(bot.kill_switch_engaged());
+    assert!(exchange.get_open_orders().await.unwrap().is_empty());
+    let engaged = recorder
+        .events
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|event| matches!(event, NotificationEvent::KillSwitch { engaged: true, .. }));
+    assert!(engaged);
+    
+    // Later cycles skip the strategies rather than hanging in them again
+    tokio::time::timeout(Duration::from_secs(1), bot.run_cycle())
+        .await
+        .expect("cycle skipped")
+        .unwrap();
+}
@@ -0,0 +1,946 @@
+use futures_util::{SinkExt, StreamExt};
+use hyperliquid_trading_bot::{
+    api::{
+        frames::{FrameOutcome, FrameParser, WsEvent, MALFORMED},
+        MarketDataCache, WebSocketClient,
+    },
+    config::Config,
+    market_feed::MarketFeed,
+    market_stream::MarketEvent,
+    models::{OrderSide, OrderStatus},
+    price_cache::PriceCache,
+};
+use rust_decimal::Decimal;
+use serde_json::{json, Value};
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+use tokio_tungstenite::tungstenite::Message;
+
+fn ticker(px: Value) -> String {
+    json!({
+        "channel": "ticker",
+        "data": { "coin": "BTC", "px": px, "sz": "0.5", "time": 1704067200000u64 }
+    })
+    .to_string()
+}
+
+/// A frame captured from the exchange
+fn captured(name: &str) -> String {
+    std::fs::read_to_string(format!("{}/tests/fixtures/websocket/{}.json", env!("CARGO_MANIFEST_DIR"), name)).unwrap()
+}
+
+/// `name`'s captured frame, decoded without errors
+fn decode(name: &str) -> WsEvent {
+    let mut parser = FrameParser::new(0);
+    match parser.parse(&captured(name), Instant::now()) {
+        FrameOutcome::Event(event) => {
+            assert!(parser.parse_errors().is_empty());
+            event
+        }
+        other => panic!("{} didn't decode: {:?}", name, other),
+    }
+}
+
+fn dec(value: &str) -> Decimal {
+    value.parse().unwrap()
+}
+
+fn assert_ticker(outcome: FrameOutcome, price: &str) {
+    match outcome {
+        FrameOutcome::Event(WsEvent::Market(MarketEvent::Ticker(data))) => {
+            assert_eq!(data.price, price.parse::<Decimal>().unwrap())
+        }
+        other => panic!("expected a ticker, got {:?}", other),
+    }
+}
+
+/// Frames the server at `url` sends, and the requests it got back
+async fn server(frames: Vec<String>) -> (String, mpsc::UnboundedReceiver<Value>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    let (requests, received) = mpsc::unbounded_channel();
+    
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+        // Answer the first subscription with every frame, then just listen
+        if let Some(Ok(Message::Text(text))) = ws.next().await {
+            let _ = requests.send(serde_json::from_str(&text).unwrap());
+        }
+        for frame in frames {
+            ws.send(Message::Text(frame)).await.unwrap();
+        }
+        while let Some(Ok(Message::Text(text))) = ws.next().await {
+            let _ = requests.send(serde_json::from_str(&text).unwrap());
+        }
+    });
+    
+    (url, received)
+}
+
+#[test]
+fn every_truncation_is_dropped_and_counted() {
+    let mut parser = FrameParser::new(u32::MAX);
+    let frame = ticker(json!("43251.5"));
+    let now = Instant::now();
+    
+    for end in 0..frame.len() {
+        assert!(matches!(parser.parse(&frame[..end], now), FrameOutcome::Dropped), "{}", &frame[..end]);
+    }
+    assert_ticker(parser.parse(&frame, now), "43251.5");
+    assert_eq!(parser.parse_errors()[MALFORMED], frame.len() as u64);
+}
+
+#[test]
+fn mismatched_types_are_counted_against_their_channel() {
+    let mut parser = FrameParser::new(u32::MAX);
+    let now = Instant::now();
+    let frames = [
+        ticker(json!(true)),
+        ticker(json!({ "nested": "object" })),
+        ticker(json!("not a number")),
+        json!({ "channel": "ticker", "data": [1, 2, 3] }).to_string(),
+        json!({ "channel": "ticker", "data": { "coin": "BTC", "px": "1", "sz": "1", "time": -5 } }).to_string(),
+        json!({ "channel": "l2Book", "data": { "coin": "BTC", "levels": [[]], "time": 1 } }).to_string(),
+        json!({ "channel": "candle", "data": { "s": "BTC", "t": 1 } }).to_string(),
+        json!({ "data": {} }).to_string(),
+        "[]".to_string(),
+    ];
+    
+    for frame in &frames {
+        assert!(matches!(parser.parse(frame, now), FrameOutcome::Dropped), "{}", frame);
+    }
+    assert_ticker(parser.parse(&ticker(json!("43251.5")), now), "43251.5");
+    
+    let errors = parser.parse_errors();
+    assert_eq!(errors["ticker"], 5);
+    assert_eq!(errors["l2Book"], 1);
+    assert_eq!(errors["candle"], 1);
+    assert_eq!(errors[MALFORMED], 2);
+}
+
+#[test]
+fn oversized_frames_dont_stop_the_next() {
+    let mut parser = FrameParser::new(u32::MAX);
+    let now = Instant::now();
+    let huge = "9".repeat(4 << 20);
+    
+    assert!(matches!(parser.parse(&ticker(json!(format!("{}x", huge))), now), FrameOutcome::Dropped));
+    assert!(matches!(parser.parse(&format!("{{\"channel\":\"ticker\",\"data\":\"{}", huge), now), FrameOutcome::Dropped));
+    assert_ticker(parser.parse(&ticker(json!("43251.5")), now), "43251.5");
+    assert_eq!(parser.parse_errors().values().sum::<u64>(), 2);
+}
+
+#[test]
+fn other_channels_pass_through_undecoded() {
+    let mut parser = FrameParser::new(0);
+    let data = json!({ "coin": "BTC", "ctx": { "markPx": "1" } });
+    
+    let outcome = parser.parse(&json!({ "channel": "activeAssetCtx", "data": data }).to_string(), Instant::now());
+    match outcome {
+        FrameOutcome::Event(WsEvent::Unknown { channel, data: raw }) => {
+            assert_eq!(channel, "activeAssetCtx");
+            assert_eq!(raw, data);
+        }
+        other => panic!("expected an unknown event, got {:?}", other),
+    }
+    assert!(parser.parse_errors().is_empty());
+    
+    let book = json!({
+        "channel": "l2Book",
+        "data": {
+            "coin": "ETH",
+            "time": 1704067200000u64,
+            "levels": [[{ "px": "2287.6", "sz": "3.1", "n": 2 }], [{ "px": "2287.7", "sz": "0.4", "n": 1 }]]
+        }
+    });
+    match parser.parse(&book.to_string(), Instant::now()) {
+        FrameOutcome::Event(WsEvent::L2Book(book)) => {
+            assert_eq!(book.best_bid(), Some("2287.6".parse().unwrap()));
+            assert_eq!(book.best_ask(), Some("2287.7".parse().unwrap()));
+        }
+        other => panic!("expected a book, got {:?}", other),
+    }
+}
+
+#[test]
+fn captured_market_frames_decode() {
+    let WsEvent::Market(MarketEvent::Ticker(ticker)) = decode("ticker") else { panic!("expected a ticker") };
+    assert_eq!((ticker.symbol.as_str(), ticker.price), ("BTC", "43251.5".parse().unwrap()));
+    assert_eq!(ticker.timestamp.timestamp_millis(), 1704067200512);
+    
+    let WsEvent::L2Book(book) = decode("l2_book") else { panic!("expected a book") };
+    assert_eq!(book.coin, "ETH");
+    assert_eq!(book.best_bid(), Some("2287.6".parse().unwrap()));
+    assert_eq!(book.best_ask(), Some("2287.7".parse().unwrap()));
+    assert_eq!((book.bids.len(), book.asks.len()), (2, 2));
+    
+    let WsEvent::Candle(update) = decode("candle") else { panic!("expected a candle") };
+    assert_eq!((update.s.as_str(), update.i.as_str()), ("SOL", "1h"));
+    let candle = update.candle;
+    assert_eq!((candle.o, candle.h, candle.l, candle.c), (dec("101.42"), dec("102.51"), dec("101.15"), dec("102.07")));
+    assert_eq!((candle.t, candle.v), (1704063600000, dec("48211.3")));
+    
+    let WsEvent::AllMids(mids) = decode("all_mids") else { panic!("expected mids") };
+    assert_eq!(mids.len(), 4);
+    assert_eq!((mids["ETH"], mids["@107"]), (dec("2287.65"), dec("0.41012")));
+    
+    let WsEvent::Trades(trades) = decode("trades") else { panic!("expected trades") };
+    let trade = trades[1].to_market_trade();
+    assert_eq!((trade.symbol.as_str(), trade.side, trade.price, trade.size), ("SOL", OrderSide::Sell, dec("102.06"), dec("0.8")));
+    assert_eq!(trade.timestamp.timestamp_millis(), 1704067201327);
+}
+
+#[test]
+fn captured_account_frames_decode() {
+    let WsEvent::UserFills(update) = decode("user_fills") else { panic!("expected fills") };
+    assert!(update.is_snapshot);
+    assert_eq!(update.user, "0x8ba1f109551bd432803012645ac136ddd64dba72");
+    let trade = update.fills[0].to_trade();
+    assert_eq!((trade.id.as_str(), trade.order_id.as_str()), ("118906512648541", "77738308"));
+    assert_eq!((trade.side, trade.quantity, trade.price, trade.fee), (OrderSide::Buy, dec("0.01"), dec("43251.5"), dec("0.15138")));
+    
+    let WsEvent::OrderUpdates(updates) = decode("order_updates") else { panic!("expected order updates") };
+    let order = updates[0].to_order();
+    assert_eq!((order.id.as_str(), order.symbol.as_str()), ("77738311", "ETH"));
+    assert_eq!((order.status, order.quantity, order.filled_quantity), (OrderStatus::Cancelled, dec("0.5"), dec("0.25")));
+    assert_eq!(order.cloid.as_deref(), Some("0x1234567890abcdef1234567890abcdef"));
+    
+    let WsEvent::Notification(message) = decode("notification") else { panic!("expected a notification") };
+    assert!(message.starts_with("Your position in BTC is close to liquidation"));
+    
+    assert!(matches!(decode("pong"), WsEvent::Pong));
+    let WsEvent::Subscribed(data) = decode("subscription_response") else { panic!("expected a subscription response") };
+    assert_eq!(data["subscription"]["type"], "userFills");
+}
+
+#[test]
+fn account_frames_that_dont_fit_are_dropped() {
+    let mut parser = FrameParser::new(u32::MAX);
+    let now = Instant::now();
+    let frames = [
+        json!({ "channel": "userFills", "data": { "user": "0x1", "fills": [{ "coin": "BTC" }] } }),
+        json!({ "channel": "userFills", "data": [] }),
+        json!({ "channel": "orderUpdates", "data": { "order": {} } }),
+        json!({ "channel": "orderUpdates", "data": [{ "order": { "coin": "ETH" }, "status": "open" }] }),
+    ];
+    
+    for frame in &frames {
+        assert!(matches!(parser.parse(&frame.to_string(), now), FrameOutcome::Dropped), "{}", frame);
+    }
+    let errors = parser.parse_errors();
+    assert_eq!((errors["userFills"], errors["orderUpdates"]), (2, 2));
+}
+
+#[test]
+fn a_burst_of_errors_resubscribes_once() {
+    let mut parser = FrameParser::new(3);
+    let bad = ticker(json!(true));
+    let start = Instant::now();
+    
+    // Spread out, the errors never exceed the limit in any minute
+    for i in 0..6 {
+        let outcome = parser.parse(&bad, start + Duration::from_secs(25 * i));
+        assert!(matches!(outcome, FrameOutcome::Dropped), "{:?}", outcome);
+    }
+    
+    let later = start + Duration::from_secs(3600);
+    for _ in 0..3 {
+        assert!(matches!(parser.parse(&bad, later), FrameOutcome::Dropped));
+    }
+    assert!(matches!(parser.parse(&bad, later), FrameOutcome::Resubscribe(channel) if channel == "ticker"));
+    assert!(matches!(parser.parse(&bad, later), FrameOutcome::Dropped));
+    
+    // Unreadable frames without a channel have nothing to resubscribe
+    for _ in 0..10 {
+        assert!(matches!(parser.parse("{", later), FrameOutcome::Dropped));
+    }
+}
+
+#[tokio::test]
+async fn the_stream_keeps_flowing_past_bad_frames() {
+    let frames = vec![
+        "{\"channel\":\"tick".to_string(),
+        ticker(json!(["43000"])),
+        format!("{{\"junk\":\"{}\"}}", "x".repeat(1 << 20)),
+        json!({ "channel": "trades", "data": [] }).to_string(),
+        ticker(json!("43251.5")),
+    ];
+    let (url, _requests) = server(frames).await;
+    let prices = PriceCache::new();
+    let client = WebSocketClient::new(url).with_price_cache(prices.clone());
+    
+    let ws = client.connect().await.unwrap();
+    ws.subscribe_to_ticker("BTC").unwrap();
+    
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while prices.price("BTC").is_none() && Instant::now() < deadline {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(prices.price("BTC"), Some("43251.5".parse().unwrap()));
+    assert!(ws.is_connected());
+    
+    let errors = client.parse_errors();
+    assert_eq!(errors["ticker"], 1);
+    assert_eq!(errors[MALFORMED], 2);
+}
+
+#[tokio::test]
+async fn a_failing_channel_is_resubscribed() {
+    let (url, mut requests) = server(vec![ticker(json!(true)); 3]).await;
+    let client = WebSocketClient::new(url).with_parse_error_limit(2);
+    
+    let ws = client.connect().await.unwrap();
+    ws.subscribe_to_ticker("BTC").unwrap();
+    
+    let subscription = json!({ "type": "ticker", "coin": "BTC" });
+    let mut received = Vec::new();
+    for _ in 0..3 {
+        let request = tokio::time::timeout(Duration::from_secs(5), requests.recv()).await.unwrap().unwrap();
+        assert_eq!(request["subscription"], subscription);
+        received.push(request["method"].as_str().unwrap().to_string());
+    }
+    assert_eq!(received, vec!["subscribe", "unsubscribe", "subscribe"]);
+    assert_eq!(client.parse_errors()["ticker"], 3);
+}
+
+/// Answers every request with a subscription response carrying it back,
+/// as the exchange does, on each connection
+async fn echo_server() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+                while let Some(Ok(message)) = ws.next().await {
+                    if let Message::Text(text) = message {
+                        let request: Value = serde_json::from_str(&text).unwrap();
+                        let reply = json!({ "channel": "subscriptionResponse", "data": request });
+                        ws.send(Message::Text(reply.to_string())).await.unwrap();
+                    }
+                }
+            });
+        }
+    });
+    
+    url
+}
+
+#[tokio::test]
+async fn messages_received_are_read_back_from_the_handle() {
+    let client = WebSocketClient::new(echo_server().await);
+    let ws = client.connect().await.unwrap();
+    ws.subscribe_to_ticker("BTC").unwrap();
+    ws.subscribe_to_l2_book("ETH").unwrap();
+    
+    let mut subscriptions = Vec::new();
+    for _ in 0..2 {
+        match tokio::time::timeout(Duration::from_secs(5), ws.next_message()).await.unwrap() {
+            Some(WsEvent::Subscribed(data)) => subscriptions.push(data["subscription"].clone()),
+            other => panic!("expected a subscription response, got {:?}", other),
+        }
+    }
+    assert_eq!(
+        subscriptions,
+        vec![json!({ "type": "ticker", "coin": "BTC" }), json!({ "type": "l2Book", "coin": "ETH" })]
+    );
+    
+    // Disconnecting waits for the server to close its side
+    tokio::time::timeout(Duration::from_secs(5), ws.disconnect()).await.unwrap().unwrap();
+    assert!(!ws.is_connected());
+    assert!(ws.next_message().await.is_none());
+    assert!(ws.subscribe_to_ticker("SOL").is_err());
+}
+
+#[tokio::test]
+async fn the_client_keeps_count_across_connections() {
+    let client = WebSocketClient::new(echo_server().await);
+    assert!(!client.is_connected());
+    assert_eq!((client.connected_since(), client.last_message_age()), (None, None));
+    
+    let before = chrono::Utc::now();
+    let ws = client.connect().await.unwrap();
+    assert!(client.is_connected());
+    assert!(client.connected_since().unwrap() >= before - chrono::Duration::milliseconds(1));
+    ws.subscribe_to_ticker("BTC").unwrap();
+    ws.subscribe_to_ticker("ETH").unwrap();
+    for _ in 0..2 {
+        tokio::time::timeout(Duration::from_secs(5), ws.next_message()).await.unwrap().unwrap();
+    }
+    assert_eq!(client.messages_received(), 2);
+    assert!(client.last_message_age().unwrap() < Duration::from_secs(5));
+    assert_eq!(client.reconnect_count(), 0);
+    
+    ws.disconnect().await.unwrap();
+    assert!(!client.is_connected());
+    assert_eq!(client.connected_since(), None);
+    
+    // Counting carries on over the next connection; the server's close
+    // counted too
+    let ws = client.connect().await.unwrap();
+    ws.subscribe_to_ticker("SOL").unwrap();
+    tokio::time::timeout(Duration::from_secs(5), ws.next_message()).await.unwrap().unwrap();
+    assert_eq!((client.messages_received(), client.reconnect_count()), (4, 1));
+    assert!(client.is_connected());
+    ws.disconnect().await.unwrap();
+}
+
+/// Tasks alive on this test's runtime
+fn alive_tasks() -> usize {
+    tokio::runtime::Handle::current().metrics().num_alive_tasks()
+}
+
+#[tokio::test]
+async fn reconnecting_leaves_no_tasks_behind() {
+    let client = WebSocketClient::new(echo_server().await);
+    let idle = alive_tasks();
+    
+    for round in 0..10 {
+        let ws = client.connect().await.unwrap();
+        ws.subscribe_to_ticker("BTC").unwrap();
+        tokio::time::timeout(Duration::from_secs(5), ws.next_message()).await.unwrap().unwrap();
+        
+        // The server closes back straight away, so nothing waits out the timeout
+        tokio::time::timeout(Duration::from_secs(1), ws.disconnect()).await.unwrap().unwrap();
+        assert!(!ws.is_connected(), "round {}", round);
+        // The reader, writer and heartbeat have finished; at most the
+        // server's side is still going
+        assert!(alive_tasks() <= idle + 1, "round {}: {} tasks", round, alive_tasks());
+        assert!(ws.subscribe_to_ticker("ETH").is_err());
+    }
+    assert_eq!(client.reconnect_count(), 9);
+    
+    // The server's side of each connection winds down on its own time
+    let settled = async {
+        while alive_tasks() > idle {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    };
+    tokio::time::timeout(Duration::from_secs(5), settled).await.unwrap();
+}
+
+#[tokio::test]
+async fn every_subscriber_gets_every_message() {
+    let client = WebSocketClient::new(echo_server().await);
+    let mut first = client.events();
+    let mut second = client.events();
+    
+    let ws = client.connect().await.unwrap();
+    ws.subscribe_to_ticker("BTC").unwrap();
+    ws.subscribe_to_candles("ETH", "1h").unwrap();
+    
+    for events in [&mut first, &mut second] {
+        let mut channels = Vec::new();
+        for _ in 0..2 {
+            match tokio::time::timeout(Duration::from_secs(5), events.recv()).await.unwrap() {
+                Ok(WsEvent::Subscribed(data)) => channels.push(data["subscription"]["type"].clone()),
+                other => panic!("expected a subscription response, got {:?}", other),
+            }
+        }
+        assert_eq!(channels, vec![json!("ticker"), json!("candle")]);
+    }
+    
+    // Reading from the broadcast leaves the handle's own stream alone
+    assert!(matches!(ws.next_message().await, Some(WsEvent::Subscribed(_))));
+    ws.disconnect().await.unwrap();
+}
+
+/// Records every request; answers pings with a pong only if `answer_pings`
+async fn ping_server(answer_pings: bool) -> (String, mpsc::UnboundedReceiver<Value>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    let (requests, received) = mpsc::unbounded_channel();
+    
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+        while let Some(Ok(Message::Text(text))) = ws.next().await {
+            let request: Value = serde_json::from_str(&text).unwrap();
+            if answer_pings && request["method"] == "ping" {
+                ws.send(Message::Text(json!({ "channel": "pong" }).to_string())).await.unwrap();
+            }
+            let _ = requests.send(request);
+        }
+    });
+    
+    (url, received)
+}
+
+const PING_INTERVAL: Duration = Duration::from_millis(100);
+const STALE_AFTER: Duration = Duration::from_millis(350);
+
+#[tokio::test]
+async fn pings_answered_keep_the_connection_up() {
+    let (url, mut requests) = ping_server(true).await;
+    let client = WebSocketClient::new(url).with_heartbeat(PING_INTERVAL, STALE_AFTER);
+    let ws = client.connect().await.unwrap();
+    
+    tokio::time::sleep(STALE_AFTER * 3).await;
+    assert!(ws.is_connected());
+    assert!(ws.last_message_age().unwrap() < STALE_AFTER);
+    
+    let request = requests.recv().await.unwrap();
+    assert_eq!(request, json!({ "method": "ping" }));
+    assert!(matches!(ws.next_message().await, Some(WsEvent::Pong)));
+    ws.disconnect().await.unwrap();
+}
+
+#[tokio::test]
+async fn a_server_gone_quiet_is_dropped() {
+    let (url, mut requests) = ping_server(false).await;
+    let client = WebSocketClient::new(url).with_heartbeat(PING_INTERVAL, STALE_AFTER);
+    let ws = client.connect().await.unwrap();
+    
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while ws.is_connected() && Instant::now() < deadline {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert!(!ws.is_connected());
+    assert_eq!(ws.last_message_age(), None);
+    
+    // Pinged until it was given up on
+    assert_eq!(requests.recv().await.unwrap(), json!({ "method": "ping" }));
+    assert!(tokio::time::timeout(Duration::from_secs(1), ws.next_message()).await.unwrap().is_none());
+    assert!(ws.subscribe_to_ticker("BTC").is_err());
+}
+
+#[tokio::test]
+async fn unsubscribing_ends_a_subscription_once() {
+    let (url, mut requests) = server(Vec::new()).await;
+    let client = WebSocketClient::new(url);
+    let ws = client.connect().await.unwrap();
+    ws.subscribe_to_ticker("BTC").unwrap();
+    ws.subscribe_to_l2_book("ETH").unwrap();
+    ws.subscribe_to_candles("SOL", "1h").unwrap();
+    
+    ws.unsubscribe_ticker("BTC").unwrap();
+    ws.unsubscribe_ticker("BTC").unwrap();
+    ws.unsubscribe_candles("SOL", "15m").unwrap();
+    assert_eq!(
+        ws.active_subscriptions(),
+        vec![json!({ "type": "l2Book", "coin": "ETH" }), json!({ "type": "candle", "coin": "SOL", "interval": "1h" })]
+    );
+    
+    let mut sent = Vec::new();
+    for _ in 0..4 {
+        let request = tokio::time::timeout(Duration::from_secs(5), requests.recv()).await.unwrap().unwrap();
+        sent.push((request["method"].as_str().unwrap().to_string(), request["subscription"]["type"].clone()));
+    }
+    let method = |m: &str| m.to_string();
+    assert_eq!(
+        sent,
+        vec![
+            (method("subscribe"), json!("ticker")),
+            (method("subscribe"), json!("l2Book")),
+            (method("subscribe"), json!("candle")),
+            (method("unsubscribe"), json!("ticker")),
+        ]
+    );
+    
+    // Nothing to send once the connection is gone
+    ws.disconnect().await.unwrap();
+    ws.unsubscribe_l2_book("ETH").unwrap();
+    assert_eq!(ws.active_subscriptions().len(), 1);
+    assert!(tokio::time::timeout(Duration::from_millis(200), requests.recv()).await.map_or(true, |r| r.is_none()));
+}
+
+#[tokio::test]
+async fn a_shared_feed_keeps_symbols_someone_still_wants() {
+    let (url, _requests) = server(Vec::new()).await;
+    let mut config = Config::default();
+    config.hyperliquid.ws_url = url;
+    let feed = MarketFeed::new(&config);
+    
+    feed.connect("alpha", ["BTC"]).await.unwrap();
+    feed.connect("beta", ["BTC", "ETH"]).await.unwrap();
+    
+    feed.unsubscribe("alpha", "BTC").unwrap();
+    feed.unsubscribe("alpha", "ETH").unwrap();
+    assert_eq!(feed.subscriptions(), BTreeSet::from(["BTC".to_string(), "ETH".to_string()]));
+    
+    feed.unsubscribe("beta", "BTC").unwrap();
+    assert_eq!(feed.subscriptions(), BTreeSet::from(["ETH".to_string()]));
+    let active = feed.handle().unwrap().active_subscriptions();
+    assert_eq!(active, vec![json!({ "type": "ticker", "coin": "ETH" })]);
+}
+
+/// A userFills frame carrying the captured fill once per trade id
+fn user_fills(snapshot: bool, tids: &[u64]) -> String {
+    let mut frame: Value = serde_json::from_str(&captured("user_fills")).unwrap();
+    let fill = frame["data"]["fills"][0].clone();
+    frame["data"]["isSnapshot"] = json!(snapshot);
+    frame["data"]["fills"] = tids
+        .iter()
+        .map(|tid| {
+            let mut fill = fill.clone();
+            fill["tid"] = json!(tid);
+            fill
+        })
+        .collect();
+    frame.to_string()
+}
+
+#[tokio::test]
+async fn fills_are_passed_on_once_each() {
+    let frames = vec![user_fills(true, &[1, 2]), user_fills(false, &[3]), user_fills(true, &[1, 2, 3, 4])];
+    let (url, _requests) = server(frames).await;
+    let client = WebSocketClient::new(url);
+    let ws = client.connect().await.unwrap();
+    ws.subscribe_to_user_fills("0x8ba1f109551bd432803012645ac136ddd64dba72").unwrap();
+    
+    // The replayed snapshot only adds the fill it hadn't sent before
+    let mut tids = Vec::new();
+    while tids.len() < 4 {
+        match tokio::time::timeout(Duration::from_secs(5), ws.next_message()).await.unwrap() {
+            Some(WsEvent::Fill { user, fill }) => {
+                assert_eq!(user, "0x8ba1f109551bd432803012645ac136ddd64dba72");
+                tids.push(fill.tid);
+            }
+            other => panic!("expected a fill, got {:?}", other),
+        }
+    }
+    assert_eq!(tids, vec![1, 2, 3, 4]);
+    assert!(tokio::time::timeout(Duration::from_millis(200), ws.next_message()).await.is_err());
+    ws.disconnect().await.unwrap();
+}
+
+#[tokio::test]
+async fn order_updates_are_passed_on_an_order_at_a_time() {
+    let mut frame: Value = serde_json::from_str(&captured("order_updates")).unwrap();
+    let mut resting = frame["data"][0].clone();
+    resting["order"]["oid"] = json!(77738312);
+    resting["order"]["cloid"] = Value::Null;
+    resting["order"]["sz"] = json!("0.4");
+    resting["status"] = json!("open");
+    frame["data"].as_array_mut().unwrap().push(resting);
+    let (url, _requests) = server(vec![frame.to_string()]).await;
+    let client = WebSocketClient::new(url);
+    let ws = client.connect().await.unwrap();
+    ws.subscribe_to_order_updates("0x8ba1f109551bd432803012645ac136ddd64dba72").unwrap();
+    
+    let mut updates = Vec::new();
+    while updates.len() < 2 {
+        match tokio::time::timeout(Duration::from_secs(5), ws.next_message()).await.unwrap() {
+            Some(WsEvent::OrderUpdate { oid, cloid, status, filled_sz }) => updates.push((oid, cloid, status, filled_sz)),
+            other => panic!("expected an order update, got {:?}", other),
+        }
+    }
+    assert_eq!(
+        updates,
+        vec![
+            (77738311, Some("0x1234567890abcdef1234567890abcdef".to_string()), OrderStatus::Cancelled, dec("0.25")),
+            (77738312, None, OrderStatus::PartiallyFilled, dec("0.1")),
+        ]
+    );
+    ws.disconnect().await.unwrap();
+}
+
+#[tokio::test]
+async fn the_accounts_channels_are_subscribed_together() {
+    let (url, mut requests) = server(vec![captured("notification")]).await;
+    let client = WebSocketClient::new(url).with_account("0x8BA1F109551BD432803012645AC136DDD64DBA72");
+    let ws = client.connect().await.unwrap();
+    ws.subscribe_user_channels().unwrap();
+    
+    let mut subscribed = Vec::new();
+    while subscribed.len() < 3 {
+        let request = tokio::time::timeout(Duration::from_secs(5), requests.recv()).await.unwrap().unwrap();
+        subscribed.push(request["subscription"].clone());
+    }
+    let user = "0x8ba1f109551bd432803012645ac136ddd64dba72";
+    assert_eq!(
+        subscribed,
+        vec![
+            json!({ "type": "userFills", "user": user }),
+            json!({ "type": "orderUpdates", "user": user }),
+            json!({ "type": "notification", "user": user }),
+        ]
+    );
+    match tokio::time::timeout(Duration::from_secs(5), ws.next_message()).await.unwrap() {
+        Some(WsEvent::Notification(message)) => assert!(message.contains("close to liquidation")),
+        other => panic!("expected a notification, got {:?}", other),
+    }
+    ws.disconnect().await.unwrap();
+    
+    // Without an account there's no one to subscribe for
+    let (url, _requests) = server(Vec::new()).await;
+    let ws = WebSocketClient::new(url).connect().await.unwrap();
+    assert!(ws.subscribe_user_channels().is_err());
+    ws.disconnect().await.unwrap();
+}
+
+#[tokio::test]
+async fn trades_are_passed_on_one_at_a_time() {
+    let (url, mut requests) = server(vec![captured("trades")]).await;
+    let client = WebSocketClient::new(url);
+    let ws = client.connect().await.unwrap();
+    ws.subscribe_to_trades("SOL").unwrap();
+    assert_eq!(requests.recv().await.unwrap()["subscription"], json!({ "type": "trades", "coin": "SOL" }));
+    
+    let mut trades = Vec::new();
+    while trades.len() < 2 {
+        match tokio::time::timeout(Duration::from_secs(5), ws.next_message()).await.unwrap() {
+            Some(WsEvent::Trade { coin, px, sz, side, time }) => {
+                assert_eq!((coin.as_str(), time.timestamp_millis()), ("SOL", 1704067201327));
+                trades.push((side, px, sz));
+            }
+            other => panic!("expected a trade, got {:?}", other),
+        }
+    }
+    assert_eq!(trades, vec![(OrderSide::Buy, dec("102.08"), dec("12.5")), (OrderSide::Sell, dec("102.06"), dec("0.8"))]);
+    ws.disconnect().await.unwrap();
+}
+
+/// Takes every connection made to it, recording each request with the
+/// number of the connection it came in on
+async fn reconnecting_server() -> (String, mpsc::UnboundedReceiver<(usize, Value)>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    let (requests, received) = mpsc::unbounded_channel();
+    
+    tokio::spawn(async move {
+        for connection in 1.. {
+            let (stream, _) = listener.accept().await.unwrap();
+            let requests = requests.clone();
+            tokio::spawn(async move {
+                let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+                while let Some(Ok(Message::Text(text))) = ws.next().await {
+                    let _ = requests.send((connection, serde_json::from_str(&text).unwrap()));
+                }
+            });
+        }
+    });
+    
+    (url, received)
+}
+
+#[tokio::test]
+async fn mids_and_trades_are_streamed_again_on_each_connection() {
+    let (url, mut requests) = reconnecting_server().await;
+    let mut config = Config::default();
+    config.hyperliquid.ws_url = url;
+    // A feed pushing into the REST client's snapshot streams every mid
+    let feed = MarketFeed::new(&config).with_market_data_cache(Arc::new(MarketDataCache::default()));
+    feed.stream_trades("SOL");
+    let expected = vec![
+        json!({ "type": "ticker", "coin": "BTC" }),
+        json!({ "type": "trades", "coin": "SOL" }),
+        json!({ "type": "allMids" }),
+    ];
+    
+    for connection in 1..=2 {
+        feed.connect("", ["BTC"]).await.unwrap();
+        assert_eq!(feed.handle().unwrap().active_subscriptions(), expected);
+        let mut subscribed = Vec::new();
+        while subscribed.len() < expected.len() {
+            let (on, request) = tokio::time::timeout(Duration::from_secs(5), requests.recv()).await.unwrap().unwrap();
+            assert_eq!((on, request["method"].as_str()), (connection, Some("subscribe")));
+            subscribed.push(request["subscription"].clone());
+        }
+        assert_eq!(subscribed, expected);
+        feed.disconnect().await.unwrap();
+    }
+    assert_eq!(feed.connections(), 2);
+}
+
+#[tokio::test]
+async fn a_pool_spreads_symbols_and_reopens_each_connection_alone() {
+    let (url, mut requests) = reconnecting_server().await;
+    let mut config = Config::default();
+    config.hyperliquid.ws_url = url;
+    config.data_source.ws_connections = 2;
+    let feed = MarketFeed::new(&config);
+    let symbols = ["BTC", "ETH", "SOL", "ARB", "DOGE", "AVAX"];
+    
+    feed.connect("", symbols).await.unwrap();
+    let pool = feed.pool();
+    assert_eq!((pool.connected(), feed.connections()), (2, 2));
+    // Each symbol on the connection it hashes to, and only there
+    let on_slot = |slot: usize| -> BTreeSet<String> {
+        symbols.iter().filter(|symbol| pool.slot(symbol) == slot).map(|symbol| symbol.to_string()).collect()
+    };
+    for slot in 0..2 {
+        let active = pool.handle(slot).unwrap().active_subscriptions();
+        let coins: BTreeSet<String> = active.iter().map(|s| s["coin"].as_str().unwrap().to_string()).collect();
+        assert!(!coins.is_empty(), "nothing on connection {}", slot);
+        assert_eq!(coins, on_slot(slot));
+    }
+    for _ in symbols {
+        tokio::time::timeout(Duration::from_secs(5), requests.recv()).await.unwrap().unwrap();
+    }
+    
+    // The second connection dropping leaves the first be; only its own
+    // symbols are subscribed again, on the new connection
+    pool.handle(1).unwrap().disconnect().await.unwrap();
+    assert!(!feed.is_connected());
+    feed.connect("", symbols).await.unwrap();
+    assert!(feed.is_connected());
+    assert_eq!(feed.connections(), 3);
+    let mut resubscribed = BTreeSet::new();
+    for _ in on_slot(1) {
+        let (on, request) = tokio::time::timeout(Duration::from_secs(5), requests.recv()).await.unwrap().unwrap();
+        assert_eq!(on, 3);
+        resubscribed.insert(request["subscription"]["coin"].as_str().unwrap().to_string());
+    }
+    assert_eq!(resubscribed, on_slot(1));
+    assert!(tokio::time::timeout(Duration::from_millis(200), requests.recv()).await.is_err());
+    assert_eq!(feed.subscriptions().len(), symbols.len());
+    feed.disconnect().await.unwrap();
+}
+
+#[tokio::test]
+async fn a_pools_messages_come_out_of_one_stream() {
+    let mut config = Config::default();
+    config.hyperliquid.ws_url = echo_server().await;
+    config.data_source.ws_connections = 3;
+    let feed = MarketFeed::new(&config);
+    let mut events = feed.events();
+    let symbols = ["BTC", "ETH", "SOL", "ARB", "DOGE", "AVAX"];
+    
+    feed.connect("", symbols).await.unwrap();
+    assert_eq!(feed.pool().connected(), 3);
+    let mut echoed = BTreeSet::new();
+    while echoed.len() < symbols.len() {
+        match tokio::time::timeout(Duration::from_secs(5), events.recv()).await.unwrap().unwrap() {
+            WsEvent::Subscribed(request) => echoed.insert(request["subscription"]["coin"].as_str().unwrap().to_string()),
+            other => panic!("unexpected {:?}", other),
+        };
+    }
+    assert_eq!(echoed, symbols.iter().map(|symbol| symbol.to_string()).collect());
+    feed.disconnect().await.unwrap();
+}
+
+/// The captured book frame, stamped `time`
+fn l2_book(time: u64) -> String {
+    let mut frame: Value = serde_json::from_str(&captured("l2_book")).unwrap();
+    frame["data"]["time"] = json!(time);
+    frame.to_string()
+}
+
+#[tokio::test]
+async fn a_slow_consumer_holds_no_more_than_its_buffer() {
+    // A fill every thousand book updates
+    let mut frames = Vec::new();
+    for time in 1..=5000 {
+        frames.push(l2_book(time));
+        if time % 1000 == 0 {
+            frames.push(user_fills(false, &[time / 1000]));
+        }
+    }
+    let (url, _requests) = server(frames).await;
+    let client = WebSocketClient::new(url).with_buffers(64, 16);
+    let mut events = client.events();
+    let ws = client.connect().await.unwrap();
+    ws.subscribe_to_l2_book("ETH").unwrap();
+    
+    // Nobody reads while the flood comes in; the handle's buffer drops as many
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let dropped = || client.dropped_messages().get("l2Book").copied().unwrap_or(0);
+    while dropped() < 2 * (5000 - 59) && Instant::now() < deadline {
+        assert!(events.len() <= 64);
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    assert_eq!(dropped(), 2 * (5000 - 59));
+    assert_eq!(events.len(), 64);
+    assert!(!client.dropped_messages().contains_key("userFills"));
+    
+    // Told what was missed, then handed every fill and the newest books
+    assert!(matches!(events.recv().await, Err(RecvError::Lagged(4941))));
+    let (mut tids, mut times) = (Vec::new(), Vec::new());
+    for _ in 0..64 {
+        match events.recv().await.unwrap() {
+            WsEvent::Fill { fill, .. } => tids.push(fill.tid),
+            WsEvent::L2Book(book) => times.push(book.time),
+            other => panic!("expected a fill or a book, got {:?}", other),
+        }
+    }
+    assert_eq!(tids, vec![1, 2, 3, 4, 5]);
+    assert_eq!(times, (4942..=5000).collect::<Vec<u64>>());
+    assert!(events.is_empty());
+    ws.disconnect().await.unwrap();
+}
+
+#[tokio::test]
+async fn fills_wait_for_a_consumer_with_no_room() {
+    let frames = (1..=4).map(|tid| user_fills(false, &[tid])).collect();
+    let (url, _requests) = server(frames).await;
+    let client = WebSocketClient::new(url).with_buffers(2, 16);
+    let mut events = client.events();
+    let ws = client.connect().await.unwrap();
+    ws.subscribe_to_user_fills("0x8ba1f109551bd432803012645ac136ddd64dba72").unwrap();
+    
+    // Held up rather than dropped, however long the consumer takes
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(events.len(), 2);
+    let mut tids = Vec::new();
+    while tids.len() < 4 {
+        match tokio::time::timeout(Duration::from_secs(5), events.recv()).await.unwrap() {
+            Ok(WsEvent::Fill { fill, .. }) => tids.push(fill.tid),
+            other => panic!("expected a fill, got {:?}", other),
+        }
+    }
+    assert_eq!(tids, vec![1, 2, 3, 4]);
+    ws.disconnect().await.unwrap();
+}
+
+/// Holds posts until two are in, then answers them in the opposite order;
+/// later ones are refused
+async fn post_server() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+        let mut held = Vec::new();
+        while let Some(Ok(Message::Text(text))) = ws.next().await {
+            let request: Value = serde_json::from_str(&text).unwrap();
+            assert_eq!((request["method"].as_str(), request["request"]["type"].as_str()), (Some("post"), Some("action")));
+            held.push(request.clone());
+            let replies = match held.len() {
+                1 => continue,
+                2 => held[..2]
+                    .iter()
+                    .rev()
+                    .map(|request| {
+                        let payload = json!({ "status": "ok", "response": { "echo": request["request"]["payload"] } });
+                        json!({ "id": request["id"], "response": { "type": "action", "payload": payload } })
+                    })
+                    .collect(),
+                _ => vec![json!({ "id": request["id"], "response": { "type": "error", "payload": "Too many posts" } })],
+            };
+            for data in replies {
+                ws.send(Message::Text(json!({ "channel": "post", "data": data }).to_string())).await.unwrap();
+            }
+        }
+    });
+    
+    url
+}
+
+#[tokio::test]
+async fn posted_actions_get_the_response_with_their_id() {
+    let client = WebSocketClient::new(post_server().await);
+    assert!(client.post_action(json!({ "nonce": 0 })).await.is_err());
+    let mut events = client.events();
+    let ws = client.connect().await.unwrap();
+    
+    // Answered the other way round, each still gets its own
+    let (first, second) = tokio::join!(client.post_action(json!({ "nonce": 1 })), client.post_action(json!({ "nonce": 2 })));
+    assert_eq!(first.unwrap()["response"]["echo"], json!({ "nonce": 1 }));
+    assert_eq!(second.unwrap()["response"]["echo"], json!({ "nonce": 2 }));
+    
+    let error = client.post_action(json!({ "nonce": 3 })).await.unwrap_err();
+    assert!(error.to_string().contains("Too many posts"), "{}", error);
+    
+    // Responses go to whoever posted, not to every consumer
+    assert!(tokio::time::timeout(Duration::from_millis(100), events.recv()).await.is_err());
+    ws.disconnect().await.unwrap();
+}
@@ -0,0 +1,128 @@
+use chrono::{Duration as ChronoDuration, TimeZone, Utc};
+use futures_util::{SinkExt, StreamExt};
+use hyperliquid_trading_bot::{
+    api::{frames::WsEvent, recording::RecordedFrame, FrameRecorder, ReplayWebSocketClient, WebSocketClient, WebSocketHandle},
+    config::Config,
+    market_feed::MarketFeed,
+    market_stream::MarketEvent,
+    testing::fixtures,
+};
+use rust_decimal::Decimal;
+use serde_json::json;
+use std::fs;
+use std::io::Write;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::time::Instant;
+use tokio_tungstenite::tungstenite::Message;
+
+/// A BTC ticker frame at `price`
+fn ticker(price: &str, time: u64) -> String {
+    json!({ "channel": "ticker", "data": { "coin": "BTC", "px": price, "sz": "0.01", "time": time } }).to_string()
+}
+
+/// Sends `frames` to the first connection, then holds it open
+async fn server(frames: Vec<String>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+        for frame in frames {
+            ws.send(Message::Text(frame)).await.unwrap();
+        }
+        while let Some(Ok(_)) = ws.next().await {}
+    });
+    
+    url
+}
+
+/// The next ticker's price
+async fn next_price(ws: &WebSocketHandle) -> Decimal {
+    match tokio::time::timeout(Duration::from_secs(5), ws.next_message()).await.unwrap() {
+        Some(WsEvent::Market(MarketEvent::Ticker(ticker))) => ticker.price,
+        other => panic!("expected a ticker, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn recorded_frames_replay_through_the_same_pipeline() {
+    let path = fixtures::temp_dir().join("frames").join("frames.ndjson");
+    let frames = vec![ticker("43000", 1), "not json".to_string(), ticker("43010.5", 2)];
+    let recorder = FrameRecorder::new(&path, 16).unwrap();
+    let client = WebSocketClient::new(server(frames.clone()).await).with_recorder(recorder.clone());
+    
+    let ws = client.connect().await.unwrap();
+    assert_eq!(next_price(&ws).await, Decimal::from(43000));
+    assert_eq!(next_price(&ws).await, "43010.5".parse().unwrap());
+    ws.disconnect().await.unwrap();
+    recorder.flush().await;
+    
+    // Every frame as received, unreadable ones too
+    let recorded: Vec<RecordedFrame> = fs::read_to_string(&path)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(recorded.iter().map(|frame| frame.text.clone()).collect::<Vec<_>>(), frames);
+    assert!(recorded.windows(2).all(|pair| pair[0].received_at <= pair[1].received_at));
+    
+    // Played back into a feed in place of the exchange, tickers reach its
+    // price cache as they did live
+    let mut config = Config::default();
+    config.data_source.replay = Some(path.display().to_string());
+    config.data_source.replay_speed = 0.0;
+    let feed = MarketFeed::new(&config);
+    let mut events = feed.events();
+    feed.connect("", ["BTC"]).await.unwrap();
+    for expected in ["43000", "43010.5"] {
+        match tokio::time::timeout(Duration::from_secs(5), events.recv()).await.unwrap().unwrap() {
+            WsEvent::Market(MarketEvent::Ticker(ticker)) => assert_eq!(ticker.price, expected.parse().unwrap()),
+            other => panic!("expected a ticker, got {:?}", other),
+        }
+    }
+    assert_eq!(feed.prices().get("BTC").unwrap().market_data.price, "43010.5".parse().unwrap());
+    assert_eq!(feed.parse_errors().get("malformed"), Some(&1));
+    
+    // It plays once: the connection ends with the recording, and there's
+    // nothing to reconnect to
+    let ended = async {
+        while feed.handle().unwrap().is_connected() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    };
+    tokio::time::timeout(Duration::from_secs(5), ended).await.unwrap();
+    assert!(feed.connect("", ["BTC"]).await.is_err());
+    assert!(!feed.post_channel().is_open());
+}
+
+#[tokio::test]
+async fn a_replay_keeps_the_recorded_pace() {
+    let path = fixtures::temp_dir().join("frames.ndjson");
+    let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    let mut file = fs::File::create(&path).unwrap();
+    // The last one a minute on, still to come when disconnecting
+    for (n, (millis, price)) in [(0, "43000"), (200, "43001"), (400, "43002"), (60_000, "43003")].into_iter().enumerate() {
+        let frame = RecordedFrame {
+            received_at: start + ChronoDuration::milliseconds(millis),
+            text: ticker(price, n as u64),
+        };
+        writeln!(file, "{}", serde_json::to_string(&frame).unwrap()).unwrap();
+    }
+    
+    // 400ms recorded, played twice as fast
+    let client = WebSocketClient::replay(ReplayWebSocketClient::new(&path).with_speed(2.0));
+    let ws = client.connect().await.unwrap();
+    assert_eq!(next_price(&ws).await, Decimal::from(43000));
+    let first = Instant::now();
+    assert_eq!(next_price(&ws).await, Decimal::from(43001));
+    assert_eq!(next_price(&ws).await, Decimal::from(43002));
+    let took = first.elapsed();
+    assert!(took >= Duration::from_millis(190) && took < Duration::from_millis(380), "took {:?}", took);
+    
+    // Disconnecting ends a replay without waiting for it
+    let disconnected = Instant::now();
+    ws.disconnect().await.unwrap();
+    assert!(disconnected.elapsed() < Duration::from_secs(1));
+}
@@ -0,0 +1,165 @@
+// The bodies the client sends, field for field, as the exchange documents
+// them. A renamed field or variant fails here rather than against the
+// exchange.
+
+use hyperliquid_trading_bot::api::types::{
+    Action, CancelRequest, CandleRequest, InfoRequest, ModifyRequest, OrderRef, OrderRequest, OrderTypeWire,
+    UsdTransfer,
+};
+use rust_decimal::Decimal;
+use serde_json::{json, Value};
+use std::str::FromStr;
+
+const USER: &str = "0x8ba1f109551bd432803012645ac136ddd64dba72";
+
+fn wire(value: &impl serde::Serialize) -> Value {
+    serde_json::to_value(value).unwrap()
+}
+
+fn limit_order(price: &str) -> OrderRequest {
+    OrderRequest {
+        a: 4,
+        b: true,
+        p: Decimal::from_str(price).unwrap(),
+        s: Decimal::from_str("0.0100").unwrap(),
+        r: false,
+        t: OrderTypeWire::Limit { tif: "Gtc".to_string() },
+        c: None,
+    }
+}
+
+#[test]
+fn info_requests_match_the_wire_format() {
+    let user = || USER.to_string();
+    let cases = [
+        (InfoRequest::Meta, json!({ "type": "meta" })),
+        (InfoRequest::MetaAndAssetCtxs, json!({ "type": "metaAndAssetCtxs" })),
+        (InfoRequest::AllMids, json!({ "type": "allMids" })),
+        (
+            InfoRequest::ClearinghouseState { user: user() },
+            json!({ "type": "clearinghouseState", "user": USER }),
+        ),
+        (InfoRequest::OpenOrders { user: user() }, json!({ "type": "openOrders", "user": USER })),
+        (
+            InfoRequest::OrderStatus { user: user(), oid: OrderRef::Oid(77738308) },
+            json!({ "type": "orderStatus", "user": USER, "oid": 77738308 }),
+        ),
+        (
+            InfoRequest::OrderStatus {
+                user: user(),
+                oid: OrderRef::Cloid("0x1234567890abcdef1234567890abcdef".to_string()),
+            },
+            json!({ "type": "orderStatus", "user": USER, "oid": "0x1234567890abcdef1234567890abcdef" }),
+        ),
+        (InfoRequest::UserFills { user: user() }, json!({ "type": "userFills", "user": USER })),
+        (
+            InfoRequest::UserFillsByTime { user: user(), start_time: 1704067200000, end_time: None },
+            json!({ "type": "userFillsByTime", "user": USER, "startTime": 1704067200000u64, "endTime": null }),
+        ),
+        (
+            InfoRequest::UserTwapSliceFills { user: user() },
+            json!({ "type": "userTwapSliceFills", "user": USER }),
+        ),
+        (
+            InfoRequest::UserFunding { user: user(), start_time: 1704067200000, end_time: Some(1704070800000) },
+            json!({ "type": "userFunding", "user": USER, "startTime": 1704067200000u64, "endTime": 1704070800000u64 }),
+        ),
+        (
+            InfoRequest::FundingHistory { coin: "BTC".to_string(), start_time: 1704067200000, end_time: None },
+            json!({ "type": "fundingHistory", "coin": "BTC", "startTime": 1704067200000u64, "endTime": null }),
+        ),
+        (InfoRequest::UserFees { user: user() }, json!({ "type": "userFees", "user": USER })),
+        (InfoRequest::Portfolio { user: user() }, json!({ "type": "portfolio", "user": USER })),
+        (InfoRequest::L2Book { coin: "ETH".to_string() }, json!({ "type": "l2Book", "coin": "ETH" })),
+        (
+            InfoRequest::CandleSnapshot {
+                req: CandleRequest {
+                    coin: "ETH".to_string(),
+                    interval: "1h".to_string(),
+                    start_time: 1704067200000,
+                    end_time: 1704153600000,
+                },
+            },
+            json!({
+                "type": "candleSnapshot",
+                "req": { "coin": "ETH", "interval": "1h", "startTime": 1704067200000u64, "endTime": 1704153600000u64 }
+            }),
+        ),
+    ];
+    
+    for (request, expected) in cases {
+        let body = wire(&request);
+        assert_eq!(body, expected, "{}", request.name());
+        assert_eq!(body["type"], request.name());
+    }
+}
+
+#[test]
+fn exchange_actions_match_the_wire_format() {
+    let order = Action::Order {
+        orders: vec![limit_order("42000.50")],
+        grouping: "na".to_string(),
+    };
+    assert_eq!(
+        wire(&order),
+        json!({
+            "type": "order",
+            "orders": [{ "a": 4, "b": true, "p": "42000.5", "s": "0.01", "r": false, "t": { "limit": { "tif": "Gtc" } } }],
+            "grouping": "na"
+        })
+    );
+    
+    let cancel = Action::Cancel {
+        cancels: vec![CancelRequest { a: 4, o: 77738308 }],
+    };
+    assert_eq!(wire(&cancel), json!({ "type": "cancel", "cancels": [{ "a": 4, "o": 77738308 }] }));
+    
+    let modify = Action::Modify(ModifyRequest {
+        oid: 77738308,
+        order: limit_order("41000"),
+    });
+    assert_eq!(
+        wire(&modify),
+        json!({
+            "type": "modify",
+            "oid": 77738308,
+            "order": { "a": 4, "b": true, "p": "41000", "s": "0.01", "r": false, "t": { "limit": { "tif": "Gtc" } } }
+        })
+    );
+    
+    let leverage = Action::UpdateLeverage {
+        asset: 4,
+        is_cross: true,
+        leverage: 5,
+    };
+    assert_eq!(
+        wire(&leverage),
+        json!({ "type": "updateLeverage", "asset": 4, "isCross": true, "leverage": 5 })
+    );
+    
+    let schedule = Action::ScheduleCancel { time: Some(1704067260000) };
+    assert_eq!(wire(&schedule), json!({ "type": "scheduleCancel", "time": 1704067260000u64 }));
+    // Clearing the schedule leaves the time out rather than sending null
+    assert_eq!(wire(&Action::ScheduleCancel { time: None }), json!({ "type": "scheduleCancel" }));
+    
+    let transfer = UsdTransfer {
+        signature_chain_id: "0x66eee".to_string(),
+        hyperliquid_chain: "Mainnet".to_string(),
+        destination: USER.to_string(),
+        amount: "250.5".to_string(),
+        time: 1704067200000,
+    };
+    let fields = json!({
+        "signatureChainId": "0x66eee",
+        "hyperliquidChain": "Mainnet",
+        "destination": USER,
+        "amount": "250.5",
+        "time": 1704067200000u64
+    });
+    for (action, name) in [(Action::UsdSend(transfer.clone()), "usdSend"), (Action::Withdraw3(transfer), "withdraw3")] {
+        let mut expected = fields.clone();
+        expected["type"] = json!(name);
+        assert_eq!(wire(&action), expected);
+        assert_eq!(action.name(), name);
+    }
+}